@@ -0,0 +1,42 @@
+//! The forward pass of a wide layer with `Softsign` and `HardSigmoid` against `Sigmoid`, and `apply_slice`
+//! against a virtual `apply` per element on 1024 values: `cargo bench --bench activations`.
+
+mod common;
+
+use nalgebra::DVector;
+use rand::{distr::Uniform, rngs::StdRng, SeedableRng};
+
+use neural::{
+    activations::{ActivationFn, HardSigmoid, Sigmoid, Softsign},
+    network::layer::Layer,
+};
+
+use common::bench;
+
+fn main() {
+    let activation_fns: [Box<dyn ActivationFn>; 3] = [Box::new(Sigmoid), Box::new(HardSigmoid), Box::new(Softsign)];
+    // Few inputs and many outputs, so the activation function is a large share of the pass.
+    let inputs = DVector::from_fn(16, |i, _| i as f32 / 8.0 - 1.0);
+
+    let values: Vec<f32> = (0..1024).map(|i| i as f32 / 256.0 - 2.0).collect();
+    for activation_fn in activation_fns.iter() {
+        let name = activation_fn.name();
+        bench(&format!("apply per element, 1024 {name}"), || {
+            let mut xs = values.clone();
+            xs.iter_mut().for_each(|x| *x = activation_fn.apply(*x));
+            xs
+        });
+        bench(&format!("apply_slice, 1024 {name}"), || {
+            let mut xs = values.clone();
+            activation_fn.apply_slice(&mut xs);
+            xs
+        });
+    }
+
+    for activation_fn in activation_fns {
+        let name = activation_fn.name();
+        let mut layer = Layer::random_with_rng(16, 8192, activation_fn, &Uniform::new(-1.0, 1.0).unwrap(), &mut StdRng::seed_from_u64(6)).unwrap();
+        bench(&format!("predict 16 -> 8192 {name}"), || layer.predict(inputs.as_view()).unwrap());
+        bench(&format!("forward 16 -> 8192 {name}"), || layer.forward(inputs.clone()).unwrap());
+    }
+}
@@ -0,0 +1,32 @@
+//! `Layer::backpropagation_step` with matrix operations against the scalar loops it replaced, on a 512×512
+//! layer: `cargo bench --bench backpropagation_step`.
+
+mod common;
+
+#[path = "../tests/common/mod.rs"]
+mod reference;
+
+use nalgebra::{DMatrix, DVector};
+use rand::{distr::Uniform, rngs::StdRng, Rng, SeedableRng};
+
+use neural::{activations::*, network::layer::Layer};
+
+use common::bench;
+
+const SIZE: usize = 512;
+
+fn main() {
+    let mut rng = StdRng::seed_from_u64(1);
+    let mut layer = Layer::random_with_rng(SIZE, SIZE, tanh!(), &Uniform::new(-0.1, 0.1).unwrap(), &mut rng).unwrap();
+    let inputs = DVector::from_fn(SIZE, |_, _| rng.random_range(-1.0..1.0));
+    let incoming = DVector::from_fn(SIZE, |_, _| rng.random_range(-1.0..1.0));
+    let outputs = layer.forward(inputs).unwrap();
+
+    bench("backpropagation_step 512x512", || layer.backpropagation_step(outputs.as_view(), incoming.as_view()));
+
+    let mut weight_gradient = DMatrix::zeros(SIZE, SIZE);
+    let mut bias_gradient = DVector::zeros(SIZE);
+    bench("scalar loops 512x512", || {
+        reference::scalar_backpropagation_step(&layer, &Tanh, outputs.as_view(), incoming.as_view(), &mut weight_gradient, &mut bias_gradient)
+    });
+}
@@ -0,0 +1,39 @@
+#![allow(dead_code)]
+
+use std::{
+    hint::black_box,
+    time::{Duration, Instant},
+};
+
+/// How long every benchmark is measured, after a warm-up of a tenth of that.
+const MEASUREMENT: Duration = Duration::from_secs(1);
+
+/// Runs `f` in batches for about a second and prints the median time per call, like criterion's estimate.
+/// Set `BENCH_FILTER` to run only the benchmarks whose name contains it.
+pub fn bench<R>(name: &str, mut f: impl FnMut() -> R) {
+    if std::env::var("BENCH_FILTER").is_ok_and(|filter| !name.contains(&filter)) {
+        return;
+    }
+
+    let warm_up = Instant::now();
+    let mut calls_per_batch = 1;
+    while warm_up.elapsed() < MEASUREMENT / 10 {
+        for _ in 0..calls_per_batch {
+            black_box(f());
+        }
+        calls_per_batch *= 2;
+    }
+
+    let mut per_call = Vec::new();
+    let measurement = Instant::now();
+    while measurement.elapsed() < MEASUREMENT {
+        let batch = Instant::now();
+        for _ in 0..calls_per_batch {
+            black_box(f());
+        }
+        per_call.push(batch.elapsed() / calls_per_batch);
+    }
+
+    per_call.sort();
+    println!("{name:<48} {:>12?} per call ({} batches of {calls_per_batch})", per_call[per_call.len() / 2], per_call.len());
+}
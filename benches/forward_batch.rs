@@ -0,0 +1,29 @@
+//! `Network::forward_batch` against a `Network::forward` per column, on the 160×120 decision boundary grid
+//! the demo draws: `cargo bench --bench forward_batch`.
+
+mod common;
+
+use nalgebra::{DMatrix, DVector};
+use rand::{distr::Uniform, rngs::StdRng, SeedableRng};
+
+use neural::{activations::*, network::Network};
+
+use common::bench;
+
+const WIDTH: usize = 160;
+const HEIGHT: usize = 120;
+
+fn main() {
+    let mut network = Network::<f32>::random_with_rng(&[2, 16, 16, 1], tanh!(), &Uniform::new(-1.0, 1.0).unwrap(), &mut StdRng::seed_from_u64(1))
+        .unwrap();
+    let grid = DMatrix::from_fn(2, WIDTH * HEIGHT, |row, column| match row {
+        0 => (column % WIDTH) as f32 / WIDTH as f32,
+        _ => (column / WIDTH) as f32 / HEIGHT as f32,
+    });
+
+    bench("forward per point, 160x120 grid", || {
+        let outputs: Result<Vec<_>, _> = grid.column_iter().map(|point| network.forward(DVector::from(point))).collect();
+        outputs.unwrap()
+    });
+    bench("forward_batch, 160x120 grid", || network.forward_batch(&grid).unwrap());
+}
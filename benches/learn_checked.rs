@@ -0,0 +1,31 @@
+//! `learn_checked` against `learn`, the cost of scanning the loss, gradients and updated parameters for
+//! non-finite values: `cargo bench --bench learn_checked`.
+
+mod common;
+
+use rand::{distr::Uniform, rngs::StdRng, Rng, SeedableRng};
+
+use neural::{activations::*, dataset::Sample, losses::MSE, network::Network};
+
+use common::bench;
+
+fn main() {
+    let mut rng = StdRng::seed_from_u64(1);
+    let dataset: Vec<_> = (0..256)
+        .map(|_| {
+            let inputs: Vec<f32> = (0..64).map(|_| rng.random_range(-1.0..1.0)).collect();
+            let outputs: Vec<f32> = (0..10).map(|_| rng.random_range(-1.0..1.0)).collect();
+            Sample::from_slices(&inputs, &outputs)
+        })
+        .collect();
+
+    for layer_sizes in [&[64, 32, 10][..], &[64, 256, 256, 10]] {
+        let network = Network::<f32>::random_with_rng(layer_sizes, tanh!(), &Uniform::new(-0.1, 0.1).unwrap(), &mut rng).unwrap();
+
+        let mut learned = network.clone();
+        bench(&format!("learn {layer_sizes:?}, 256 samples"), || learned.learn(&dataset, &MSE, 1e-4).unwrap());
+
+        let mut learned = network.clone();
+        bench(&format!("learn_checked {layer_sizes:?}, 256 samples"), || learned.learn_checked(&dataset, &MSE, 1e-4).unwrap());
+    }
+}
@@ -0,0 +1,27 @@
+//! One online step with `learn_sample` and a reused scratch against `learn` on a one-sample slice:
+//! `cargo bench --bench learn_sample`.
+
+mod common;
+
+use rand::{distr::Uniform, rngs::StdRng, SeedableRng};
+
+use neural::{activations::*, dataset::Sample, losses::MSE, network::Network};
+
+use common::bench;
+
+fn main() {
+    let sample = Sample::from_slices(&[0.5, -0.25, 1.0, 0.0], &[1.0, 0.0]);
+
+    for layer_sizes in [&[4, 8, 2][..], &[4, 64, 64, 2]] {
+        let network = Network::random_with_rng(layer_sizes, tanh!(), &Uniform::new(-0.5, 0.5).unwrap(), &mut StdRng::seed_from_u64(2)).unwrap();
+
+        let mut learned = network.clone();
+        bench(&format!("learn one sample {layer_sizes:?}"), || learned.learn(std::slice::from_ref(&sample), &MSE, 0.01).unwrap());
+
+        let mut learned = network.clone();
+        let mut scratch = learned.scratch();
+        bench(&format!("learn_sample_with_scratch {layer_sizes:?}"), || {
+            learned.learn_sample_with_scratch(sample.inputs(), sample.expected_outputs(), &MSE, 0.01, &mut scratch).unwrap()
+        });
+    }
+}
@@ -0,0 +1,29 @@
+//! `Network::learn_parallel` against `Network::learn` on a few thousand samples:
+//! `cargo bench --features rayon --bench parallel_backpropagation`.
+
+mod common;
+
+use rand::{distr::Uniform, rngs::StdRng, Rng, SeedableRng};
+
+use neural::{activations::*, dataset::Sample, losses::MSE, network::Network};
+
+use common::bench;
+
+const SAMPLES: usize = 4096;
+
+fn main() {
+    let mut rng = StdRng::seed_from_u64(1);
+    let mut network = Network::<f32>::random_with_rng(&[64, 128, 128, 10], tanh!(), &Uniform::new(-0.1, 0.1).unwrap(), &mut rng).unwrap();
+    let dataset: Vec<_> = (0..SAMPLES)
+        .map(|_| {
+            let inputs: Vec<f32> = (0..64).map(|_| rng.random_range(-1.0..1.0)).collect();
+            let outputs: Vec<f32> = (0..10).map(|_| rng.random_range(-1.0..1.0)).collect();
+            Sample::from_slices(&inputs, &outputs)
+        })
+        .collect();
+
+    bench("learn 64-128-128-10, 4096 samples", || network.learn(&dataset, &MSE, 1e-4).unwrap());
+    bench(&format!("learn_parallel, {} threads", rayon::current_num_threads()), || {
+        network.learn_parallel(&dataset, &MSE, 1e-4).unwrap()
+    });
+}
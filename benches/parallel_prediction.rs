@@ -0,0 +1,27 @@
+//! `Network::predict_all_parallel` against `Network::predict_all` for an MNIST-sized network:
+//! `cargo bench --features rayon --bench parallel_prediction`.
+
+mod common;
+
+use nalgebra::DVector;
+use rand::{distr::Uniform, rngs::StdRng, Rng, SeedableRng};
+
+use neural::{activations::*, network::Network};
+
+use common::bench;
+
+const INPUTS: usize = 2048;
+
+fn main() {
+    let mut rng = StdRng::seed_from_u64(1);
+    let network = Network::<f32>::random_with_rng(&[784, 256, 10], relu!(), &Uniform::new(-0.05, 0.05).unwrap(), &mut rng).unwrap();
+    let inputs: Vec<_> = (0..INPUTS).map(|_| DVector::from_fn(784, |_, _| rng.random_range(0.0..1.0))).collect();
+
+    bench("predict_all 784-256-10, 2048 inputs", || network.predict_all(&inputs).unwrap());
+    for threads in [1, 2, 4, rayon::current_num_threads()] {
+        let pool = rayon::ThreadPoolBuilder::new().num_threads(threads).build().unwrap();
+        bench(&format!("predict_all_parallel, {threads} threads"), || {
+            pool.install(|| network.predict_all_parallel(&inputs).unwrap())
+        });
+    }
+}
@@ -0,0 +1,109 @@
+// This is just an interactive test of the library, run it with `cargo run --example interactive --features demo`
+
+use macroquad::prelude::*;
+
+use neural::network::*;
+use neural::activations::*;
+use neural::losses;
+use neural::dataset::{self, Sample};
+use neural::training::background::BackgroundTrainer;
+use neural::visualize::{self, Gradient};
+
+fn window_conf() -> Conf {
+    Conf {
+        window_title: "Neural Network".to_string(),
+        window_width: 800,
+        window_height: 600,
+        ..Default::default()
+    }
+}
+
+fn field_texture(network: &Network) -> Texture2D {
+    let resolution = (BUFFER_COLUMNS, BUFFER_ROWS);
+    let field = visualize::grid_evaluate(network, -1.0..1.0, -1.0..1.0, resolution).unwrap();
+    let texture = Texture2D::from_rgba8(BUFFER_COLUMNS as u16, BUFFER_ROWS as u16, &visualize::to_rgba(&field, &Gradient::default()));
+    texture.set_filter(FilterMode::Nearest);
+    texture
+}
+
+fn draw_field(texture: &Texture2D) {
+    draw_texture_ex(texture, 0.0, 0.0, WHITE, DrawTextureParams {
+        dest_size: Some(vec2(screen_width(), screen_height())),
+        ..Default::default()
+    });
+}
+
+const BUFFER_ROWS: usize = 120;
+const DATASET_PATH: &str = "dataset.json";
+const BUFFER_COLUMNS: usize = 160;
+
+#[macroquad::main(window_conf)]
+#[allow(unused_variables)]
+async fn main() {
+    let network = NetworkBuilder::new().input(2).layer(50, sigmoid!()).layer(1, sigmoid!()).build().unwrap();
+    let trainer = BackgroundTrainer::spawn(network, Vec::new(), losses::MSE, 0.01, 100).unwrap();
+
+    let mut dataset = Vec::<Sample>::new();
+    let mut paused = false;
+    let mut texture = trainer.with_network(field_texture);
+    let mut progress = None;
+
+    loop {
+        let (mut mx, mut my) = mouse_position();
+        mx = mx / screen_width() * 2.0 - 1.0;
+        my = my / screen_height() * -2.0 + 1.0;
+
+        for (button, positive) in [(MouseButton::Left, true), (MouseButton::Right, false)] {
+            if is_mouse_button_pressed(button) {
+                let point = Sample::point(mx, my, positive);
+                trainer.add_sample(point.clone()).unwrap();
+                dataset.push(point);
+            }
+        }
+
+        if is_key_pressed(KeyCode::S)
+            && let Err(error) = dataset::save_json(DATASET_PATH, &dataset)
+        {
+            eprintln!("couldn't save the dataset: {error}");
+        }
+
+        if is_key_pressed(KeyCode::L) {
+            match dataset::load_json(DATASET_PATH) {
+                Ok(loaded) => match trainer.set_samples(loaded.clone()) {
+                    Ok(()) => dataset = loaded,
+                    Err(error) => eprintln!("couldn't load the dataset: {error}"),
+                },
+                Err(error) => eprintln!("couldn't load the dataset: {error}"),
+            }
+        }
+
+        if is_key_pressed(KeyCode::Space) {
+            paused = !paused;
+            if paused {
+                trainer.pause();
+            } else {
+                trainer.resume();
+            }
+        }
+
+        // The field is only evaluated again once the worker has published another round.
+        if trainer.progress() != progress {
+            progress = trainer.progress();
+            texture = trainer.with_network(field_texture);
+        }
+
+        draw_field(&texture);
+
+        for point in dataset.iter() {
+            let pos = point.inputs();
+            draw_circle(
+                (pos[0] + 1.0) / 2.0 * screen_width(),
+                (pos[1] - 1.0) / -2.0 * screen_height(),
+                5.0,
+                if point.expected_outputs()[0] == 1.0 { RED } else { BLUE },
+            );
+        }
+
+        next_frame().await;
+    }
+}
@@ -1,36 +1,265 @@
-pub trait ActivationFn: 'static + ActivationFnClone {
-    fn apply(&self, x: f32) -> f32;
-    fn derivative(&self, x: f32, activation: f32) -> f32;
+use nalgebra::{DVector, DVectorView};
+use thiserror::Error;
+
+use crate::{network::layer::LayerError, prelude::*, scalar::Scalar};
+
+pub trait ActivationFn<T: Scalar = f32>: 'static + Send + Sync + ActivationFnClone<T> {
+    fn apply(&self, x: T) -> T;
+    fn derivative(&self, x: T, activation: T) -> T;
+
+    /// Applies the function to every element in place. Layers call this once per pass instead of `apply`
+    /// per element, implementations can override it with a faster loop.
+    fn apply_slice(&self, xs: &mut [T]) {
+        for x in xs.iter_mut() {
+            *x = self.apply(*x);
+        }
+    }
+
+    /// Writes `derivative(xs[i], activations[i])` to `out[i]` for every element.
+    fn derivative_slice(&self, xs: &[T], activations: &[T], out: &mut [T]) {
+        for ((out, &x), &activation) in out.iter_mut().zip(xs.iter()).zip(activations.iter()) {
+            *out = self.derivative(x, activation);
+        }
+    }
+
+    /// Identifies the activation function in saved networks, `from_name` maps it back. It defaults to the
+    /// type name, which `from_name` doesn't know, so a network using a custom function can be saved but not
+    /// loaded again.
+    fn name(&self) -> &'static str {
+        core::any::type_name::<Self>()
+    }
+
+    /// The lowest and the highest value the function approaches, `None` for a side where it's unbounded.
+    /// Units stuck near a bound pass on little gradient, `Network::activation_stats` looks for them.
+    fn range(&self) -> (Option<T>, Option<T>) {
+        (None, None)
+    }
 }
 
-pub trait ActivationFnClone {
-    fn clone_box(&self) -> Box<dyn ActivationFn>;
+pub trait ActivationFnClone<T: Scalar = f32> {
+    fn clone_box(&self) -> Box<dyn ActivationFn<T>>;
 }
 
-impl<T> ActivationFnClone for T
+impl<T, F> ActivationFnClone<T> for F
 where
-    T: 'static + ActivationFn + Clone,
+    T: Scalar,
+    F: 'static + ActivationFn<T> + Clone,
 {
-    fn clone_box(&self) -> Box<dyn ActivationFn> {
+    fn clone_box(&self) -> Box<dyn ActivationFn<T>> {
         Box::new(self.clone())
     }
 }
 
-impl Clone for Box<dyn ActivationFn> {
+impl<T: Scalar> Clone for Box<dyn ActivationFn<T>> {
     fn clone(&self) -> Self {
         self.clone_box()
     }
 }
 
-#[derive(Clone)]
+#[derive(Clone, Copy, Default)]
 pub struct Sigmoid;
-impl ActivationFn for Sigmoid {
-    fn apply(&self, x: f32) -> f32 {
-        1.0 / (1.0 + (-x).exp())
+impl<T: Scalar> ActivationFn<T> for Sigmoid {
+    fn apply(&self, x: T) -> T {
+        T::one() / (T::one() + (-x).exp())
+    }
+
+    fn derivative(&self, x: T, activation: T) -> T {
+        activation * (T::one() - activation)
+    }
+
+    fn name(&self) -> &'static str {
+        "sigmoid"
+    }
+
+    fn range(&self) -> (Option<T>, Option<T>) {
+        (Some(T::zero()), Some(T::one()))
+    }
+}
+
+#[derive(Clone, Copy, Default)]
+pub struct Tanh;
+impl<T: Scalar> ActivationFn<T> for Tanh {
+    fn apply(&self, x: T) -> T {
+        x.tanh()
+    }
+
+    fn derivative(&self, x: T, activation: T) -> T {
+        T::one() - activation * activation
+    }
+
+    fn name(&self) -> &'static str {
+        "tanh"
+    }
+
+    fn range(&self) -> (Option<T>, Option<T>) {
+        (Some(-T::one()), Some(T::one()))
+    }
+}
+
+#[derive(Clone, Copy, Default)]
+pub struct ReLU;
+impl<T: Scalar> ActivationFn<T> for ReLU {
+    fn apply(&self, x: T) -> T {
+        x.max(T::zero())
+    }
+
+    fn derivative(&self, x: T, activation: T) -> T {
+        if x > T::zero() { T::one() } else { T::zero() }
+    }
+
+    fn name(&self) -> &'static str {
+        "relu"
+    }
+
+    fn range(&self) -> (Option<T>, Option<T>) {
+        (Some(T::zero()), None)
+    }
+}
+
+/// Passes the weighted sums through unchanged, for linear output layers, e.g. logits for `losses::BCEWithLogits`.
+#[derive(Clone, Copy, Default)]
+pub struct Identity;
+impl<T: Scalar> ActivationFn<T> for Identity {
+    fn apply(&self, x: T) -> T {
+        x
+    }
+
+    fn derivative(&self, x: T, activation: T) -> T {
+        T::one()
+    }
+
+    fn apply_slice(&self, xs: &mut [T]) {}
+
+    fn name(&self) -> &'static str {
+        "identity"
+    }
+}
+
+/// `x / (1 + |x|)`, shaped like tanh with the same range but without `exp`, so it's cheap on targets where
+/// `exp` is slow. It approaches ±1 polynomially rather than exponentially.
+#[derive(Clone, Copy, Default)]
+pub struct Softsign;
+impl<T: Scalar> ActivationFn<T> for Softsign {
+    fn apply(&self, x: T) -> T {
+        x / (T::one() + x.abs())
+    }
+
+    /// `1 / (1 + |x|)^2`, which is `(1 - |activation|)^2`.
+    fn derivative(&self, x: T, activation: T) -> T {
+        let complement = T::one() - activation.abs();
+        complement * complement
+    }
+
+    fn name(&self) -> &'static str {
+        "softsign"
+    }
+
+    fn range(&self) -> (Option<T>, Option<T>) {
+        (Some(-T::one()), Some(T::one()))
     }
+}
+
+/// `clamp(0.2 * x + 0.5, 0, 1)`, a piecewise linear stand-in for `Sigmoid` without `exp`, e.g. to swap into a
+/// sigmoid network for inference on a slow target, see `Network::replace_activation`. It's exactly 0 below
+/// -2.5 and exactly 1 above 2.5, where its derivative is 0.
+#[derive(Clone, Copy, Default)]
+pub struct HardSigmoid;
+impl<T: Scalar> ActivationFn<T> for HardSigmoid {
+    fn apply(&self, x: T) -> T {
+        (T::constant(0.2) * x + T::constant(0.5)).clamp(T::zero(), T::one())
+    }
+
+    /// 0.2 on the slope and 0 on the flat parts, including the kinks at ±2.5.
+    fn derivative(&self, x: T, activation: T) -> T {
+        if x > T::constant(-2.5) && x < T::constant(2.5) { T::constant(0.2) } else { T::zero() }
+    }
+
+    fn name(&self) -> &'static str {
+        "hard_sigmoid"
+    }
+
+    fn range(&self) -> (Option<T>, Option<T>) {
+        (Some(T::zero()), Some(T::one()))
+    }
+}
+
+/// `softmax(logits / temperature)`, e.g. for the raw outputs of a network with a linear output layer.
+/// Temperatures above 1 flatten the distribution towards uniform, temperatures below 1 sharpen it towards
+/// one-hot. Fails with `InvalidTemperature` unless `temperature` is positive and finite.
+pub fn softmax_with_temperature<T: Scalar>(logits: DVectorView<T>, temperature: T) -> Result<DVector<T>, LayerError> {
+    check_temperature(temperature)?;
+
+    // Shifting by the largest logit keeps `exp` from overflowing without changing the result.
+    let max = logits.max();
+    let exponentials = logits.map(|logit| ((logit - max) / temperature).exp());
+    let sum = exponentials.sum();
+    Ok(exponentials / sum)
+}
+
+pub(crate) fn check_temperature<T: Scalar>(temperature: T) -> Result<(), LayerError> {
+    if !(temperature > T::zero() && temperature.is_finite()) {
+        return Err(LayerError::InvalidTemperature(temperature.to_f64()));
+    }
+
+    Ok(())
+}
+
+/// The worst point of `verify_derivative`, with the error measured like in `gradcheck`.
+#[derive(Clone, Copy, Debug, Error, PartialEq)]
+#[error("the derivative at {x} is {analytic}, but the central difference gives {numeric}")]
+pub struct DerivativeMismatch {
+    pub x: f32,
+    pub analytic: f32,
+    pub numeric: f32,
+    pub relative_error: f32,
+}
+
+/// Checks an activation function's `derivative` against central differences of `apply` at every point of
+/// `xs`, e.g. in the test suite of a custom activation function. The derivative is computed the way layers
+/// do, with `derivative_slice` given the points and their activations. The relative error at a point is
+/// `|analytic - numeric| / max(|analytic| + |numeric|, 1)`, and the worst point fails the check if its error
+/// is above `tolerance` or not finite. The step is about `0.005 * max(|x|, 1)`, so points that close to
+/// a kink, like 0 for `ReLU`, fail even for a correct derivative.
+pub fn verify_derivative(f: &dyn ActivationFn, xs: &[f32], tolerance: f32) -> Result<(), DerivativeMismatch> {
+    let mut activations = xs.to_vec();
+    f.apply_slice(&mut activations);
+
+    let mut derivatives = vec![0.0; xs.len()];
+    f.derivative_slice(xs, &activations, &mut derivatives);
+
+    let worst = xs
+        .iter()
+        .zip(derivatives.iter())
+        .map(|(&x, &analytic)| {
+            let step = f32::EPSILON.cbrt() * x.abs().max(1.0);
+            let numeric = (f.apply(x + step) - f.apply(x - step)) / (2.0 * step);
+            let relative_error = (analytic - numeric).abs() / (analytic.abs() + numeric.abs()).max(1.0);
+
+            DerivativeMismatch {
+                x,
+                analytic,
+                numeric,
+                relative_error: if relative_error.is_nan() { f32::INFINITY } else { relative_error },
+            }
+        })
+        .max_by(|a, b| a.relative_error.total_cmp(&b.relative_error));
+
+    match worst {
+        Some(worst) if worst.relative_error > tolerance => Err(worst),
+        _ => Ok(()),
+    }
+}
 
-    fn derivative(&self, x: f32, activation: f32) -> f32 {
-        activation * (1.0 - activation)
+/// The built-in activation function with the given `ActivationFn::name`.
+pub fn from_name<T: Scalar>(name: &str) -> Option<Box<dyn ActivationFn<T>>> {
+    match name {
+        "sigmoid" => Some(Box::new(Sigmoid)),
+        "tanh" => Some(Box::new(Tanh)),
+        "relu" => Some(Box::new(ReLU)),
+        "identity" => Some(Box::new(Identity)),
+        "softsign" => Some(Box::new(Softsign)),
+        "hard_sigmoid" => Some(Box::new(HardSigmoid)),
+        _ => None,
     }
 }
 
@@ -41,4 +270,44 @@ macro_rules! sigmoid {
     };
 }
 
-pub use sigmoid;
\ No newline at end of file
+#[macro_export]
+macro_rules! tanh {
+    () => {
+        Box::new(Tanh)
+    };
+}
+
+#[macro_export]
+macro_rules! relu {
+    () => {
+        Box::new(ReLU)
+    };
+}
+
+#[macro_export]
+macro_rules! identity {
+    () => {
+        Box::new(Identity)
+    };
+}
+
+#[macro_export]
+macro_rules! softsign {
+    () => {
+        Box::new(Softsign)
+    };
+}
+
+#[macro_export]
+macro_rules! hard_sigmoid {
+    () => {
+        Box::new(HardSigmoid)
+    };
+}
+
+pub use hard_sigmoid;
+pub use identity;
+pub use relu;
+pub use sigmoid;
+pub use softsign;
+pub use tanh;
\ No newline at end of file
@@ -0,0 +1,244 @@
+// Command line front end for training networks on CSV files and running them
+
+use std::{
+    fs::File,
+    io::{self, BufWriter, Write},
+    ops::Range,
+    process::ExitCode,
+    str::FromStr,
+};
+
+use thiserror::Error;
+
+use neural::{
+    activations,
+    dataset::{CsvOptions, DatasetError},
+    losses,
+    network::{Network, NetworkBuilder, NetworkError, NetworkLoadError},
+    training::{callback::StdoutLogger, Trainer},
+};
+
+const USAGE: &str = "\
+usage:
+  neural train --data <csv> --inputs <columns> --outputs <columns> --layers <sizes> --out <model>
+               [--activation sigmoid|tanh|relu|identity] [--epochs 100] [--rate 0.1] [--batch-size <n>] [--header]
+  neural predict --model <model> --data <csv> --out <csv> [--inputs <columns>] [--header]
+
+Columns are zero-based, given as a list like 0,1,3 or a range like 0..4. Sizes are comma separated and
+include the input layer, e.g. 4,16,1.";
+
+#[derive(Debug, Error)]
+enum CliError {
+    #[error("{0}")]
+    Usage(String),
+
+    #[error("{path}: {source}")]
+    Dataset {
+        path: String,
+        source: DatasetError,
+    },
+
+    #[error("{path}: {source}")]
+    Model {
+        path: String,
+        source: NetworkLoadError,
+    },
+
+    #[error("{path}: {source}")]
+    Io {
+        path: String,
+        source: io::Error,
+    },
+
+    #[error("{0}")]
+    Network(#[from] NetworkError),
+}
+
+/// `--name value` pairs and `--flag`s, in any order.
+struct Args {
+    values: Vec<(String, Option<String>)>,
+}
+
+impl Args {
+    fn parse(args: impl Iterator<Item = String>) -> Result<Self, CliError> {
+        let mut values: Vec<(String, Option<String>)> = Vec::new();
+
+        for arg in args {
+            match arg.strip_prefix("--") {
+                Some(name) => values.push((name.to_string(), None)),
+                None => match values.last_mut() {
+                    Some((_, value @ None)) => *value = Some(arg),
+                    _ => return Err(CliError::Usage(format!("unexpected argument {arg:?}"))),
+                },
+            }
+        }
+
+        Ok(Self { values })
+    }
+
+    fn flag(&self, name: &str) -> bool {
+        self.values.iter().any(|(arg, _)| arg == name)
+    }
+
+    fn optional(&self, name: &str) -> Result<Option<&str>, CliError> {
+        match self.values.iter().find(|(arg, _)| arg == name) {
+            Some((_, Some(value))) => Ok(Some(value)),
+            Some((_, None)) => Err(CliError::Usage(format!("--{name} needs a value"))),
+            None => Ok(None),
+        }
+    }
+
+    fn required(&self, name: &str) -> Result<&str, CliError> {
+        self.optional(name)?.ok_or_else(|| CliError::Usage(format!("--{name} is required")))
+    }
+
+    fn parsed<T: FromStr>(&self, name: &str, default: T) -> Result<T, CliError> {
+        match self.optional(name)? {
+            Some(value) => value.parse().map_err(|_| CliError::Usage(format!("--{name} got {value:?}, which isn't valid"))),
+            None => Ok(default),
+        }
+    }
+
+    /// Rejects options the subcommand doesn't know, so typos don't silently fall back to defaults.
+    fn check_known(&self, known: &[&str]) -> Result<(), CliError> {
+        match self.values.iter().find(|(arg, _)| !known.contains(&arg.as_str())) {
+            Some((arg, _)) => Err(CliError::Usage(format!("unknown option --{arg}"))),
+            None => Ok(()),
+        }
+    }
+}
+
+/// A comma separated list of numbers, where every entry may also be a range `start..end`.
+fn parse_list(name: &str, list: &str) -> Result<Vec<usize>, CliError> {
+    let invalid = || CliError::Usage(format!("--{name} got {list:?}, expected numbers like 0,1,2 or 0..3"));
+    let mut values = Vec::new();
+
+    for entry in list.split(',').map(str::trim) {
+        match entry.split_once("..") {
+            Some((start, end)) => {
+                let range: Range<usize> = start.parse().map_err(|_| invalid())?..end.parse().map_err(|_| invalid())?;
+                if range.is_empty() {
+                    return Err(invalid());
+                }
+
+                values.extend(range);
+            }
+            None => values.push(entry.parse().map_err(|_| invalid())?),
+        }
+    }
+
+    Ok(values)
+}
+
+fn read_csv(path: &str, options: &CsvOptions, inputs: &[usize], outputs: &[usize]) -> Result<Vec<neural::dataset::Sample>, CliError> {
+    options.read(path, inputs, outputs).map_err(|source| CliError::Dataset {
+        path: path.to_string(),
+        source,
+    })
+}
+
+fn train(args: &Args) -> Result<(), CliError> {
+    args.check_known(&["data", "inputs", "outputs", "layers", "activation", "epochs", "rate", "batch-size", "out", "header"])?;
+
+    let data = args.required("data")?;
+    let inputs = parse_list("inputs", args.required("inputs")?)?;
+    let outputs = parse_list("outputs", args.required("outputs")?)?;
+    let sizes = parse_list("layers", args.required("layers")?)?;
+    let activation = args.optional("activation")?.unwrap_or("sigmoid");
+    let epochs = args.parsed("epochs", 100)?;
+    let rate = args.parsed("rate", 0.1)?;
+    let out = args.required("out")?;
+
+    if sizes.len() < 2 {
+        return Err(CliError::Usage("--layers needs at least an input and an output size".to_string()));
+    }
+
+    if sizes[0] != inputs.len() || sizes[sizes.len() - 1] != outputs.len() {
+        return Err(CliError::Usage(format!(
+            "--layers goes from {} inputs to {} outputs, but {} input and {} output columns were given",
+            sizes[0],
+            sizes[sizes.len() - 1],
+            inputs.len(),
+            outputs.len(),
+        )));
+    }
+
+    let dataset = read_csv(data, &CsvOptions::new().header(args.flag("header")), &inputs, &outputs)?;
+
+    let mut builder = NetworkBuilder::new().input(sizes[0]);
+    for &size in &sizes[1..] {
+        let activation_fn = activations::from_name(activation)
+            .ok_or_else(|| CliError::Usage(format!("--activation got {activation:?}, expected sigmoid, tanh, relu or identity")))?;
+        builder = builder.layer(size, activation_fn);
+    }
+
+    let mut network = builder.build()?;
+    let mut trainer = Trainer::new(losses::MSE, rate, epochs).callback(StdoutLogger::default());
+    if args.optional("batch-size")?.is_some() {
+        trainer = trainer.batch_size(args.parsed("batch-size", 0)?);
+    }
+
+    trainer.fit(&mut network, &dataset, &[])?;
+
+    network.save(out).map_err(|source| CliError::Io {
+        path: out.to_string(),
+        source,
+    })
+}
+
+fn predict(args: &Args) -> Result<(), CliError> {
+    args.check_known(&["model", "data", "inputs", "out", "header"])?;
+
+    let model = args.required("model")?;
+    let data = args.required("data")?;
+    let out = args.required("out")?;
+
+    let network = Network::load(model).map_err(|source| CliError::Model {
+        path: model.to_string(),
+        source,
+    })?;
+
+    let inputs = match args.optional("inputs")? {
+        Some(inputs) => parse_list("inputs", inputs)?,
+        None => (0..network.input_size()).collect(),
+    };
+
+    let dataset = read_csv(data, &CsvOptions::new().header(args.flag("header")), &inputs, &[])?;
+    let io_error = |source| CliError::Io {
+        path: out.to_string(),
+        source,
+    };
+
+    let mut writer = BufWriter::new(File::create(out).map_err(io_error)?);
+    for sample in dataset.iter() {
+        let outputs = network.predict(sample.inputs())?;
+        let row: Vec<String> = outputs.iter().map(ToString::to_string).collect();
+        writeln!(writer, "{}", row.join(",")).map_err(io_error)?;
+    }
+
+    writer.flush().map_err(io_error)
+}
+
+fn main() -> ExitCode {
+    let mut args = std::env::args().skip(1);
+    let command = args.next();
+
+    let result = Args::parse(args).and_then(|args| match command.as_deref() {
+        Some("train") => train(&args),
+        Some("predict") => predict(&args),
+        Some(command) => Err(CliError::Usage(format!("unknown command {command:?}"))),
+        None => Err(CliError::Usage("no command given".to_string())),
+    });
+
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error @ CliError::Usage(_)) => {
+            eprintln!("error: {error}\n\n{USAGE}");
+            ExitCode::from(2)
+        }
+        Err(error) => {
+            eprintln!("error: {error}");
+            ExitCode::FAILURE
+        }
+    }
+}
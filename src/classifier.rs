@@ -0,0 +1,123 @@
+//! A network or pipeline whose outputs are scores for named classes.
+
+use nalgebra::{DVector, DVectorView};
+use rand::Rng;
+
+use crate::{
+    activations::softmax_with_temperature,
+    dataset::{one_hot, Sample},
+    losses::LossFn,
+    network::{Network, NetworkError},
+    pipeline::{Pipeline, PipelineError},
+    training::{Trainer, TrainingReport},
+};
+
+/// Names the outputs of a pipeline, output `i` being the score of class `labels[i]`.
+#[derive(Debug)]
+pub struct Classifier {
+    pipeline: Pipeline,
+    labels: Vec<String>,
+    /// Whether the outputs are logits, set with `with_logits`.
+    logits: bool,
+}
+
+impl Classifier {
+    /// Fails with `ClassCountMismatch` unless there's a label for every output of the network.
+    pub fn new(network: Network, labels: impl IntoIterator<Item = impl Into<String>>) -> Result<Self, PipelineError> {
+        Self::from_pipeline(Pipeline::new(Vec::new(), network)?, labels)
+    }
+
+    pub fn from_pipeline(pipeline: Pipeline, labels: impl IntoIterator<Item = impl Into<String>>) -> Result<Self, PipelineError> {
+        let labels: Vec<String> = labels.into_iter().map(Into::into).collect();
+
+        if labels.len() != pipeline.output_size() {
+            return Err(NetworkError::ClassCountMismatch {
+                num_classes: labels.len(),
+                network_outputs: pipeline.output_size(),
+            }
+            .into());
+        }
+
+        Ok(Self { pipeline, labels, logits: false })
+    }
+
+    /// Takes the outputs as logits, so `predict_proba` applies a softmax to them, e.g. for a network with a
+    /// linear output layer. Without it the outputs are taken as they are, whatever the output layer.
+    pub fn with_logits(mut self) -> Self {
+        self.logits = true;
+        self
+    }
+
+    #[inline]
+    pub fn labels(&self) -> &[String] { &self.labels }
+
+    #[inline]
+    pub fn num_classes(&self) -> usize { self.labels.len() }
+
+    /// The index of the class with the given label.
+    pub fn class(&self, label: &str) -> Option<usize> {
+        self.labels.iter().position(|other| other == label)
+    }
+
+    #[inline]
+    pub fn pipeline(&self) -> &Pipeline { &self.pipeline }
+
+    #[inline]
+    pub fn pipeline_mut(&mut self) -> &mut Pipeline { &mut self.pipeline }
+
+    #[inline]
+    pub fn network(&self) -> &Network { self.pipeline.network() }
+
+    /// Whether the outputs are taken as logits, see `with_logits`.
+    #[inline]
+    pub fn has_logits(&self) -> bool { self.logits }
+
+    /// The score of every class: the softmax of the outputs if they're logits, the outputs as they are otherwise,
+    /// e.g. for sigmoid or `Softmax` output layers.
+    pub fn predict_proba(&self, input: DVectorView<f32>) -> Result<DVector<f32>, PipelineError> {
+        let output = self.pipeline.predict(input)?;
+
+        if self.has_logits() {
+            Ok(softmax_with_temperature(output.as_view(), 1.0).map_err(NetworkError::from)?)
+        } else {
+            Ok(output)
+        }
+    }
+
+    /// The label of the class with the highest score and the score. Ties go to the class that comes first
+    /// and NaN scores are never picked, unless they all are.
+    pub fn predict_class(&self, input: DVectorView<f32>) -> Result<(&str, f32), PipelineError> {
+        let scores = self.predict_proba(input)?;
+        let class = (1..scores.len()).fold(0, |best, i| if scores[i] > scores[best] || scores[best].is_nan() { i } else { best });
+
+        Ok((&self.labels[class], scores[class]))
+    }
+
+    /// Fits the pipeline's transforms and trains the network on the inputs with the one-hot encoded labels as
+    /// expected outputs. Fails with `LabelOutOfRange` if a label index isn't a class.
+    pub fn fit<L: LossFn>(
+        &mut self,
+        examples: &[(DVector<f32>, usize)],
+        trainer: &mut Trainer<L>,
+    ) -> Result<TrainingReport, PipelineError> {
+        self.fit_with_rng(examples, trainer, &mut rand::rng())
+    }
+
+    /// Like `fit`, but shuffles with the given RNG, so seeded runs are reproducible.
+    pub fn fit_with_rng<L: LossFn>(
+        &mut self,
+        examples: &[(DVector<f32>, usize)],
+        trainer: &mut Trainer<L>,
+        rng: &mut impl Rng,
+    ) -> Result<TrainingReport, PipelineError> {
+        let samples = examples
+            .iter()
+            .map(|(inputs, label)| Ok(Sample::new(inputs.clone(), one_hot(*label, self.num_classes())?)))
+            .collect::<Result<Vec<_>, PipelineError>>()?;
+
+        self.pipeline.fit(&samples)?;
+        let prepared = self.pipeline.prepare(&samples)?;
+
+        Ok(trainer.fit_with_rng(self.pipeline.network_mut(), &prepared, &[], rng)?)
+    }
+}
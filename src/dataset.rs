@@ -1,23 +1,624 @@
+use alloc::collections::BTreeMap;
+use core::ops::Deref;
+#[cfg(feature = "std")]
+use std::{fs, path::Path};
+
 use nalgebra::{DVector, DVectorView};
+use rand::{seq::SliceRandom, Rng};
+use thiserror::Error;
+
+use crate::{json::JsonError, prelude::*, scalar::Scalar};
+#[cfg(feature = "std")]
+use crate::json::Value;
+
+pub use augment::{Augment, FnAugment, MaskAugment, NoiseAugment};
+pub use balanced::BalancedSampler;
+pub use batch::{BatchSamples, BatchView};
+#[cfg(feature = "std")]
+pub use csv::{from_csv, CsvOptions};
+pub use duplicates::{dedup, find_conflicts, Conflict, DedupStrategy};
+pub use expansion::FeatureExpansion;
+pub use image::{from_gray_buffer, samples_from_images, to_gray_buffer};
+pub use normalizer::{Normalization, Normalizer};
+pub use replay::{ReplayBuffer, ReplayMode};
+pub use training_data::TrainingData;
+pub use window::{windowed, windowed_multivariate, WindowOptions};
 
-pub struct Sample {
-    inputs: DVector<f32>,
-    expected_outputs: DVector<f32>,
+pub mod augment;
+pub mod balanced;
+pub mod batch;
+#[cfg(feature = "std")]
+pub mod csv;
+pub mod duplicates;
+pub mod expansion;
+pub mod image;
+#[cfg(feature = "std")]
+pub mod mnist;
+pub mod normalizer;
+pub mod replay;
+pub mod training_data;
+pub mod window;
+
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(bound(serialize = "T: serde::Serialize", deserialize = "T: serde::Deserialize<'de>")))]
+pub struct Sample<T: Scalar = f32> {
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_format::vector"))]
+    inputs: DVector<T>,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_format::vector"))]
+    expected_outputs: DVector<T>,
+    weight: f32,
+    tag: Option<String>,
 }
 
-impl Sample {
-    pub fn new(inputs: DVector<f32>, expected_outputs: DVector<f32>) -> Self {
+/// A sequence of inputs with an expected output for every step, for `network::recurrent`.
+#[derive(Clone, Debug)]
+pub struct SequenceSample<T: Scalar = f32> {
+    inputs: Vec<DVector<T>>,
+    expected_outputs: Vec<DVector<T>>,
+}
+
+/// Samples that all have the same number of inputs and expected outputs. It dereferences to
+/// `[Sample]`, so it can be passed anywhere a slice of samples is taken.
+#[derive(Clone, Debug, Default)]
+pub struct Dataset {
+    samples: Vec<Sample>,
+}
+
+#[derive(Debug, Error)]
+pub enum DatasetError {
+    #[cfg(feature = "std")]
+    #[error("{0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("{0}")]
+    Json(#[from] JsonError),
+
+    #[error("the dataset is empty")]
+    EmptyDataset,
+
+    #[error("label {label} is out of range for {num_classes} classes")]
+    LabelOutOfRange {
+        label: usize,
+        num_classes: usize,
+    },
+
+    #[error("there are {inputs} inputs but {labels} labels")]
+    LabelCountMismatch {
+        inputs: usize,
+        labels: usize,
+    },
+
+    #[error("the normalizer works on vectors of size {normalizer_size}, but one of size {given_size} was given")]
+    NormalizerSizeMismatch {
+        normalizer_size: usize,
+        given_size: usize,
+    },
+
+    #[error("a replay buffer needs a capacity of at least 1")]
+    ZeroCapacity,
+
+    #[error("a feature expansion needs at least one input and a degree of at least 1, but got {input_size} inputs and degree {degree}")]
+    InvalidExpansion {
+        input_size: usize,
+        degree: usize,
+    },
+
+    #[error("the transform takes vectors of size {expected}, but one of size {given} was given")]
+    TransformSizeMismatch {
+        expected: usize,
+        given: usize,
+    },
+
+    #[error("sample {index} has {inputs} inputs and {outputs} outputs, but sample 0 has {expected_inputs} inputs and {expected_outputs} outputs")]
+    DimensionMismatch {
+        index: usize,
+        expected_inputs: usize,
+        expected_outputs: usize,
+        inputs: usize,
+        outputs: usize,
+    },
+
+    #[error("the split fraction has to be between 0 and 1, but it is {0}")]
+    InvalidFraction(f32),
+
+    #[error("a sample weight has to be finite and non-negative, but it is {0}")]
+    InvalidWeight(f32),
+
+    #[error("sample {index} has no expected outputs to take a class from")]
+    EmptyExpectedOutputs {
+        index: usize,
+    },
+
+    #[error("windows need at least one input step and one step to predict")]
+    ZeroWindow,
+
+    #[error("the series has {length} steps, but at least {required} are needed for one window")]
+    SeriesTooShort {
+        length: usize,
+        required: usize,
+    },
+
+    #[error("step {index} of the series has {found} values, but step 0 has {expected}")]
+    SeriesDimensionMismatch {
+        index: usize,
+        expected: usize,
+        found: usize,
+    },
+
+    #[error("line {line}: expected {expected} fields, but found {found}")]
+    RaggedRow {
+        line: usize,
+        expected: usize,
+        found: usize,
+    },
+
+    #[error("line {line}: column {column} doesn't exist, the row has {columns} fields")]
+    ColumnOutOfRange {
+        line: usize,
+        column: usize,
+        columns: usize,
+    },
+
+    #[error("line {line}: column {column} is empty")]
+    MissingValue {
+        line: usize,
+        column: usize,
+    },
+
+    #[error("line {line}: column {column} holds {value:?}, which isn't a number")]
+    InvalidNumber {
+        line: usize,
+        column: usize,
+        value: String,
+    },
+
+    #[error("image {index} has {pixels} pixels, but a {width}x{height} image has {}", width * height)]
+    ImageSizeMismatch {
+        index: usize,
+        width: usize,
+        height: usize,
+        pixels: usize,
+    },
+
+    #[error("the batch {start}..{end} reaches past the last of {len} samples")]
+    BatchOutOfRange {
+        start: usize,
+        end: usize,
+        len: usize,
+    },
+}
+
+impl<T: Scalar> Sample<T> {
+    pub fn new(inputs: DVector<T>, expected_outputs: DVector<T>) -> Self {
         Self {
             inputs,
             expected_outputs,
+            weight: 1.0,
+            tag: None,
+        }
+    }
+
+    /// A sample whose loss and gradient count `weight` times, see `with_weight`. Fails with `InvalidWeight`
+    /// if the weight is negative or not finite.
+    pub fn weighted(inputs: DVector<T>, expected_outputs: DVector<T>, weight: f32) -> Result<Self, DatasetError> {
+        if !(weight.is_finite() && weight >= 0.0) {
+            return Err(DatasetError::InvalidWeight(weight));
         }
+
+        Ok(Self::new(inputs, expected_outputs).with_weight(weight))
+    }
+
+    /// Attaches a label, e.g. the id of the record the sample came from. Training ignores it,
+    /// but evaluation reports like `metrics::worst_samples` carry it along.
+    pub fn with_tag(mut self, tag: impl Into<String>) -> Self {
+        self.tag = Some(tag.into());
+        self
+    }
+
+    #[inline]
+    pub fn tag(&self) -> Option<&str> {
+        self.tag.as_deref()
+    }
+
+    /// Scales this sample's contribution to the training loss and gradient, 1 by default.
+    /// Training rejects negative and non-finite weights.
+    pub fn with_weight(mut self, weight: f32) -> Self {
+        self.weight = weight;
+        self
+    }
+
+    #[inline]
+    pub fn weight(&self) -> f32 {
+        self.weight
     }
 
-    pub fn inputs(&self) -> DVectorView<f32> {
+    #[inline]
+    pub fn set_weight(&mut self, weight: f32) {
+        self.weight = weight;
+    }
+
+    /// The weight in the sample's scalar type, for scaling losses and gradients.
+    #[inline]
+    pub(crate) fn loss_weight(&self) -> T {
+        T::constant(self.weight as f64)
+    }
+
+    /// Builds a sample by copying the slices. Networks never take empty inputs or outputs, so a sample
+    /// built from an empty slice fails with a size mismatch once it's used.
+    pub fn from_slices(inputs: &[T], expected_outputs: &[T]) -> Self {
+        Self::new(DVector::from_column_slice(inputs), DVector::from_column_slice(expected_outputs))
+    }
+
+    /// A point `(x, y)` labeled with a single output, 1 for the positive class and 0 otherwise,
+    /// the kind of sample `visualize` works with.
+    pub fn point(x: T, y: T, positive: bool) -> Self {
+        let label = if positive { T::one() } else { T::zero() };
+        Self::new(DVector::from_vec(vec![x, y]), DVector::from_vec(vec![label]))
+    }
+
+    pub fn inputs(&self) -> DVectorView<'_, T> {
         self.inputs.as_view()
     }
 
-    pub fn expected_outputs(&self) -> DVectorView<f32> {
+    pub fn expected_outputs(&self) -> DVectorView<'_, T> {
         self.expected_outputs.as_view()
     }
-}
\ No newline at end of file
+
+    #[inline]
+    pub fn inputs_slice(&self) -> &[T] {
+        self.inputs.as_slice()
+    }
+
+    #[inline]
+    pub fn outputs_slice(&self) -> &[T] {
+        self.expected_outputs.as_slice()
+    }
+}
+
+impl<T: Scalar> SequenceSample<T> {
+    pub fn new(inputs: Vec<DVector<T>>, expected_outputs: Vec<DVector<T>>) -> Self {
+        Self { inputs, expected_outputs }
+    }
+
+    #[inline]
+    pub fn inputs(&self) -> &[DVector<T>] {
+        &self.inputs
+    }
+
+    #[inline]
+    pub fn expected_outputs(&self) -> &[DVector<T>] {
+        &self.expected_outputs
+    }
+
+    /// The number of steps.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.inputs.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.inputs.is_empty()
+    }
+}
+
+#[cfg(feature = "std")]
+impl Sample {
+    pub(crate) fn to_json(&self) -> Value {
+        let mut json = Value::object([
+            ("inputs", Value::numbers(self.inputs.iter().copied())),
+            ("expected_outputs", Value::numbers(self.expected_outputs.iter().copied())),
+        ]);
+
+        if self.weight != 1.0 {
+            json.insert("weight", Value::Number(self.weight as f64));
+        }
+
+        if let Some(tag) = &self.tag {
+            json.insert("tag", Value::String(tag.clone()));
+        }
+
+        json
+    }
+
+    pub(crate) fn from_json(value: &Value, path: &str) -> Result<Self, JsonError> {
+        let inputs = value.field(path, "inputs")?.as_f32_vec(&format!("{path}.inputs"))?;
+        let expected_outputs = value
+            .field(path, "expected_outputs")?
+            .as_f32_vec(&format!("{path}.expected_outputs"))?;
+
+        let weight = match value.optional_field(path, "weight")? {
+            Some(weight) => weight.as_f32(&format!("{path}.weight"))?,
+            None => 1.0,
+        };
+
+        let mut sample = Self::new(DVector::from_vec(inputs), DVector::from_vec(expected_outputs)).with_weight(weight);
+
+        if let Some(tag) = value.optional_field(path, "tag")? {
+            sample.tag = Some(tag.as_str(&format!("{path}.tag"))?.to_string());
+        }
+
+        Ok(sample)
+    }
+}
+
+impl<T: Scalar> From<(Vec<T>, Vec<T>)> for Sample<T> {
+    fn from((inputs, expected_outputs): (Vec<T>, Vec<T>)) -> Self {
+        Self::new(DVector::from_vec(inputs), DVector::from_vec(expected_outputs))
+    }
+}
+
+impl<T: Scalar> From<(&[T], &[T])> for Sample<T> {
+    fn from((inputs, expected_outputs): (&[T], &[T])) -> Self {
+        Self::from_slices(inputs, expected_outputs)
+    }
+}
+
+impl<T: Scalar, const I: usize, const O: usize> From<([T; I], [T; O])> for Sample<T> {
+    fn from((inputs, expected_outputs): ([T; I], [T; O])) -> Self {
+        Self::from_slices(&inputs, &expected_outputs)
+    }
+}
+
+/// Writes the samples as a JSON array of `{"inputs": [...], "expected_outputs": [...]}` objects.
+/// Non-finite values have no JSON representation and can't be loaded back.
+#[cfg(feature = "std")]
+pub fn save_json(path: impl AsRef<Path>, samples: &[Sample]) -> Result<(), DatasetError> {
+    let json = Value::Array(samples.iter().map(Sample::to_json).collect());
+    fs::write(path, json.to_string())?;
+    Ok(())
+}
+
+/// Reads samples written by `save_json`.
+#[cfg(feature = "std")]
+pub fn load_json(path: impl AsRef<Path>) -> Result<Vec<Sample>, DatasetError> {
+    let json = Value::parse(&fs::read_to_string(path)?)?;
+
+    json.as_array("$")?
+        .iter()
+        .enumerate()
+        .map(|(i, sample)| Sample::from_json(sample, &format!("$[{i}]")).map_err(Into::into))
+        .collect()
+}
+
+/// Inverse class frequency weights `samples / (classes * class_samples)`, so every class contributes
+/// the same total weight. The class of a sample is the argmax of its expected outputs, or the value
+/// itself for samples with a single expected output.
+pub fn balanced_weights(samples: &[Sample]) -> Vec<f32> {
+    let classes: Vec<u32> = samples.iter().map(class_key).collect();
+    let mut counts: Vec<(u32, usize)> = Vec::new();
+
+    for &class in classes.iter() {
+        match counts.iter_mut().find(|(key, _)| *key == class) {
+            Some((_, count)) => *count += 1,
+            None => counts.push((class, 1)),
+        }
+    }
+
+    classes
+        .iter()
+        .map(|class| {
+            let (_, count) = counts.iter().find(|(key, _)| key == class).unwrap();
+            samples.len() as f32 / (counts.len() * count) as f32
+        })
+        .collect()
+}
+
+/// Sets every sample's weight to its `balanced_weights` weight.
+pub fn balance_weights(samples: &mut [Sample]) {
+    let weights = balanced_weights(samples);
+
+    for (sample, weight) in samples.iter_mut().zip(weights) {
+        sample.weight = weight;
+    }
+}
+
+pub(crate) fn class_key(sample: &Sample) -> u32 {
+    match sample.expected_outputs.len() {
+        // Adding 0 turns -0 into 0, which compare equal but have different bits.
+        1 => (sample.expected_outputs[0] + 0.0).to_bits(),
+        _ => argmax(sample.expected_outputs()) as u32,
+    }
+}
+
+/// A vector of `num_classes` zeros with a one at `class`.
+pub fn one_hot(class: usize, num_classes: usize) -> Result<DVector<f32>, DatasetError> {
+    if class >= num_classes {
+        return Err(DatasetError::LabelOutOfRange {
+            label: class,
+            num_classes,
+        });
+    }
+
+    let mut vector = DVector::zeros(num_classes);
+    vector[class] = 1.0;
+    Ok(vector)
+}
+
+/// Builds classification samples whose expected outputs are the one-hot encoded labels.
+pub fn samples_from_labeled(
+    inputs: &[DVector<f32>],
+    labels: &[usize],
+    num_classes: usize,
+) -> Result<Vec<Sample>, DatasetError> {
+    if inputs.len() != labels.len() {
+        return Err(DatasetError::LabelCountMismatch {
+            inputs: inputs.len(),
+            labels: labels.len(),
+        });
+    }
+
+    inputs
+        .iter()
+        .zip(labels)
+        .map(|(inputs, &label)| Ok(Sample::new(inputs.clone(), one_hot(label, num_classes)?)))
+        .collect()
+}
+
+/// The index of the largest output, i.e. the class a classifier predicts. NaNs are ignored.
+///
+/// Panics if `output` is empty.
+pub fn argmax(output: DVectorView<f32>) -> usize {
+    assert!(!output.is_empty(), "argmax of an empty vector");
+
+    output
+        .iter()
+        .enumerate()
+        .filter(|(_, x)| !x.is_nan())
+        .max_by(|(_, a), (_, b)| a.total_cmp(b))
+        .map_or(0, |(index, _)| index)
+}
+
+/// Shuffles the samples and splits them in two, the first part holding `fraction` of them
+/// (clamped to 0..=1) and the second holding the rest.
+pub fn split<T: Scalar>(samples: &[Sample<T>], fraction: f32, rng: &mut impl Rng) -> (Vec<Sample<T>>, Vec<Sample<T>>) {
+    let mut shuffled = samples.to_vec();
+    shuffled.shuffle(rng);
+
+    let first_len = (samples.len() as f32 * fraction.clamp(0.0, 1.0)).round() as usize;
+    let second = shuffled.split_off(first_len);
+
+    (shuffled, second)
+}
+
+/// What `stratified_split` produced.
+#[derive(Clone, Debug)]
+pub struct StratifiedSplit {
+    pub train: Vec<Sample>,
+    pub test: Vec<Sample>,
+    /// Classes with a single sample, which can't be split and went into `train`, in ascending order.
+    pub singleton_classes: Vec<usize>,
+}
+
+/// Splits the samples in two like `split`, but every class on its own, so each holds `fraction` of
+/// every class's samples (rounded) in the first part, the training set. A sample's class is the argmax
+/// of its expected outputs, see `stratified_split_by` for other targets. Both parts are shuffled.
+pub fn stratified_split(samples: &[Sample], fraction: f32, rng: &mut impl Rng) -> Result<StratifiedSplit, DatasetError> {
+    if let Some(index) = samples.iter().position(|sample| sample.expected_outputs.is_empty()) {
+        return Err(DatasetError::EmptyExpectedOutputs { index });
+    }
+
+    stratified_split_by(samples, fraction, rng, |sample| argmax(sample.expected_outputs()))
+}
+
+/// `stratified_split` with the class of every sample given by `labeler`.
+pub fn stratified_split_by(
+    samples: &[Sample],
+    fraction: f32,
+    rng: &mut impl Rng,
+    labeler: impl Fn(&Sample) -> usize,
+) -> Result<StratifiedSplit, DatasetError> {
+    if !(0.0..=1.0).contains(&fraction) {
+        return Err(DatasetError::InvalidFraction(fraction));
+    }
+
+    let mut classes: BTreeMap<usize, Vec<Sample>> = BTreeMap::new();
+    for sample in samples {
+        classes.entry(labeler(sample)).or_default().push(sample.clone());
+    }
+
+    let mut split = StratifiedSplit {
+        train: Vec::with_capacity(samples.len()),
+        test: Vec::new(),
+        singleton_classes: Vec::new(),
+    };
+
+    for (class, mut class_samples) in classes {
+        if class_samples.len() == 1 {
+            split.singleton_classes.push(class);
+            split.train.append(&mut class_samples);
+            continue;
+        }
+
+        class_samples.shuffle(rng);
+        let train_len = (class_samples.len() as f32 * fraction).round() as usize;
+        split.test.extend(class_samples.drain(train_len..));
+        split.train.append(&mut class_samples);
+    }
+
+    split.train.shuffle(rng);
+    split.test.shuffle(rng);
+    Ok(split)
+}
+
+impl Dataset {
+    pub fn new(samples: Vec<Sample>) -> Result<Self, DatasetError> {
+        check_dimensions(&samples)?;
+        Ok(Self { samples })
+    }
+
+    pub fn shuffle(&mut self, rng: &mut impl Rng) {
+        self.samples.shuffle(rng);
+    }
+
+    /// Splits the dataset in order, the first part holding `fraction` of the samples (clamped to 0..=1).
+    pub fn split(mut self, fraction: f32) -> (Dataset, Dataset) {
+        let first_len = (self.samples.len() as f32 * fraction.clamp(0.0, 1.0)).round() as usize;
+        let second = self.samples.split_off(first_len);
+
+        (self, Dataset { samples: second })
+    }
+
+    /// Iterates over consecutive batches of `size` samples, the last one holding whatever is left.
+    pub fn batches(&self, size: usize) -> impl Iterator<Item = &[Sample]> {
+        self.samples.chunks(size.max(1))
+    }
+
+    /// The number of inputs and expected outputs of every sample, `None` for an empty dataset.
+    pub fn dimensions(&self) -> Option<(usize, usize)> {
+        self.samples.first().map(|sample| (sample.inputs.len(), sample.expected_outputs.len()))
+    }
+
+    pub fn into_samples(self) -> Vec<Sample> {
+        self.samples
+    }
+}
+
+impl Deref for Dataset {
+    type Target = [Sample];
+
+    fn deref(&self) -> &[Sample] {
+        &self.samples
+    }
+}
+
+impl TryFrom<Vec<Sample>> for Dataset {
+    type Error = DatasetError;
+
+    fn try_from(samples: Vec<Sample>) -> Result<Self, DatasetError> {
+        Self::new(samples)
+    }
+}
+
+impl<'a> IntoIterator for &'a Dataset {
+    type Item = &'a Sample;
+    type IntoIter = core::slice::Iter<'a, Sample>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.samples.iter()
+    }
+}
+
+/// Checks that every sample has as many inputs and expected outputs as the first one.
+pub fn check_dimensions<T: Scalar>(samples: &[Sample<T>]) -> Result<(), DatasetError> {
+    let Some(first) = samples.first() else {
+        return Ok(());
+    };
+
+    let (expected_inputs, expected_outputs) = (first.inputs.len(), first.expected_outputs.len());
+
+    match samples
+        .iter()
+        .position(|sample| sample.inputs.len() != expected_inputs || sample.expected_outputs.len() != expected_outputs)
+    {
+        Some(index) => Err(DatasetError::DimensionMismatch {
+            index,
+            expected_inputs,
+            expected_outputs,
+            inputs: samples[index].inputs.len(),
+            outputs: samples[index].expected_outputs.len(),
+        }),
+        None => Ok(()),
+    }
+}
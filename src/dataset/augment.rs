@@ -0,0 +1,96 @@
+use nalgebra::DVector;
+use rand::{Rng, RngCore};
+
+use crate::prelude::*;
+
+use super::Sample;
+
+/// Produces a perturbed copy of a training sample. Augmentations only change the inputs.
+pub trait Augment {
+    fn augment(&self, sample: &Sample, rng: &mut dyn RngCore) -> Sample;
+}
+
+impl<A: Augment + ?Sized> Augment for &A {
+    fn augment(&self, sample: &Sample, rng: &mut dyn RngCore) -> Sample {
+        (**self).augment(sample, rng)
+    }
+}
+
+impl<A: Augment + ?Sized> Augment for Box<A> {
+    fn augment(&self, sample: &Sample, rng: &mut dyn RngCore) -> Sample {
+        (**self).augment(sample, rng)
+    }
+}
+
+/// Adds independent gaussian noise with the given standard deviation to every input.
+#[derive(Clone, Copy, Debug)]
+pub struct NoiseAugment {
+    pub std_dev: f32,
+}
+
+impl NoiseAugment {
+    pub fn new(std_dev: f32) -> Self {
+        Self { std_dev }
+    }
+}
+
+impl Augment for NoiseAugment {
+    fn augment(&self, sample: &Sample, rng: &mut dyn RngCore) -> Sample {
+        let mut sample = sample.clone();
+
+        for input in sample.inputs.iter_mut() {
+            *input += standard_normal(rng) * self.std_dev;
+        }
+
+        sample
+    }
+}
+
+/// Sets every input to 0 independently with the given probability, e.g. for denoising autoencoders.
+#[derive(Clone, Copy, Debug)]
+pub struct MaskAugment {
+    pub probability: f32,
+}
+
+impl MaskAugment {
+    pub fn new(probability: f32) -> Self {
+        Self { probability }
+    }
+}
+
+impl Augment for MaskAugment {
+    fn augment(&self, sample: &Sample, rng: &mut dyn RngCore) -> Sample {
+        let mut sample = sample.clone();
+
+        for input in sample.inputs.iter_mut() {
+            if rng.random::<f32>() < self.probability {
+                *input = 0.0;
+            }
+        }
+
+        sample
+    }
+}
+
+/// Replaces the inputs with what the closure makes of them, keeping the expected outputs and the rest of the sample.
+#[derive(Clone, Copy, Debug)]
+pub struct FnAugment<F>(pub F);
+
+impl<F> Augment for FnAugment<F>
+where
+    F: Fn(&DVector<f32>, &mut dyn RngCore) -> DVector<f32>,
+{
+    fn augment(&self, sample: &Sample, rng: &mut dyn RngCore) -> Sample {
+        let mut sample = sample.clone();
+        sample.inputs = (self.0)(&sample.inputs, rng);
+        sample
+    }
+}
+
+/// Box-Muller transform of two uniform samples.
+pub(crate) fn standard_normal(rng: &mut (impl Rng + ?Sized)) -> f32 {
+    let u1 = 1.0 - rng.random::<f32>();
+    let u2 = rng.random::<f32>();
+
+    (-2.0 * u1.ln()).sqrt() * (core::f32::consts::TAU * u2).cos()
+}
@@ -0,0 +1,94 @@
+//! Mini-batches with about as many samples of every class, however imbalanced the dataset is.
+
+use alloc::collections::BTreeMap;
+
+use rand::{seq::SliceRandom, Rng};
+
+use crate::prelude::*;
+
+use super::{class_key, Sample};
+
+/// The samples of a dataset grouped by class, drawing epochs in which every class has as many samples as the
+/// largest one. Smaller classes are repeated in passes that are reshuffled every time, so within an epoch a
+/// sample appears either `k` or `k + 1` times. The epoch interleaves the classes in rounds of one sample per
+/// class in random order, so any `batch_size` consecutive samples hold every class about equally often, and
+/// exactly `batch_size / classes` times when the batch size is a multiple of the class count and the batch
+/// starts at a multiple of it, as `chunks(batch_size)` batches do.
+#[derive(Clone, Debug)]
+pub struct BalancedSampler {
+    /// The indices of every class's samples, ordered by class.
+    classes: Vec<Vec<usize>>,
+}
+
+impl BalancedSampler {
+    /// Classes are taken from the expected outputs like in `balanced_weights`: the argmax, or the value itself
+    /// for a single expected output.
+    pub fn new(samples: &[Sample]) -> Self {
+        Self::with_labels(samples, |sample| class_key(sample) as usize)
+    }
+
+    /// Classes are given by `label`, e.g. for samples whose expected outputs aren't a class encoding.
+    pub fn with_labels(samples: &[Sample], label: impl Fn(&Sample) -> usize) -> Self {
+        let mut classes: BTreeMap<usize, Vec<usize>> = BTreeMap::new();
+
+        for (index, sample) in samples.iter().enumerate() {
+            classes.entry(label(sample)).or_default().push(index);
+        }
+
+        Self { classes: classes.into_values().collect() }
+    }
+
+    /// The number of classes.
+    #[inline]
+    pub fn classes(&self) -> usize {
+        self.classes.len()
+    }
+
+    /// The indices of every class's samples, classes ordered by their label.
+    #[inline]
+    pub fn class_samples(&self) -> &[Vec<usize>] {
+        &self.classes
+    }
+
+    /// The number of samples in an epoch, the size of the largest class times the number of classes.
+    pub fn epoch_len(&self) -> usize {
+        self.classes.iter().map(Vec::len).max().unwrap_or(0) * self.classes.len()
+    }
+
+    /// The indices of an epoch's samples in training order, see the type's description.
+    pub fn epoch(&self, rng: &mut impl Rng) -> Vec<usize> {
+        let per_class = self.classes.iter().map(Vec::len).max().unwrap_or(0);
+
+        let mut streams: Vec<_> = self
+            .classes
+            .iter()
+            .map(|members| {
+                let mut stream = Vec::with_capacity(per_class);
+                let mut pass = members.clone();
+
+                while stream.len() < per_class {
+                    pass.shuffle(rng);
+                    let take = (per_class - stream.len()).min(pass.len());
+                    stream.extend_from_slice(&pass[..take]);
+                }
+
+                stream.into_iter()
+            })
+            .collect();
+
+        let mut round: Vec<usize> = (0..self.classes.len()).collect();
+        let mut order = Vec::with_capacity(self.epoch_len());
+
+        for _ in 0..per_class {
+            round.shuffle(rng);
+            order.extend(round.iter().map(|&class| streams[class].next().unwrap()));
+        }
+
+        order
+    }
+
+    /// An epoch cut into batches of `batch_size` indices, the last one possibly smaller.
+    pub fn batches(&self, batch_size: usize, rng: &mut impl Rng) -> Vec<Vec<usize>> {
+        self.epoch(rng).chunks(batch_size.max(1)).map(<[usize]>::to_vec).collect()
+    }
+}
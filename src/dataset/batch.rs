@@ -0,0 +1,163 @@
+//! Samples stored as two matrices with one column per sample, one allocation for all inputs and one for
+//! all expected outputs instead of two per `Sample`. `Network::learn_batch` trains on them directly.
+
+use core::ops::Range;
+
+use nalgebra::{DMatrix, DMatrixView, DVectorView};
+
+use crate::{prelude::*, scalar::Scalar};
+
+use super::{check_dimensions, DatasetError, Sample};
+
+/// Samples as an inputs and an expected outputs matrix, sample `i` being column `i` of both. Unlike
+/// `Sample`s they have neither weights nor tags, every sample counts once.
+#[derive(Clone, Debug, PartialEq)]
+pub struct BatchSamples<T: Scalar = f32> {
+    inputs: DMatrix<T>,
+    expected_outputs: DMatrix<T>,
+}
+
+/// Consecutive samples of a `BatchSamples`, borrowing its matrices.
+#[derive(Clone, Copy, Debug)]
+pub struct BatchView<'a, T: Scalar = f32> {
+    inputs: DMatrixView<'a, T>,
+    expected_outputs: DMatrixView<'a, T>,
+}
+
+impl<T: Scalar> BatchSamples<T> {
+    /// Fails if the matrices don't have a column for every sample each.
+    pub fn new(inputs: DMatrix<T>, expected_outputs: DMatrix<T>) -> Result<Self, DatasetError> {
+        if inputs.ncols() != expected_outputs.ncols() {
+            return Err(DatasetError::LabelCountMismatch {
+                inputs: inputs.ncols(),
+                labels: expected_outputs.ncols(),
+            });
+        }
+
+        Ok(Self { inputs, expected_outputs })
+    }
+
+    /// Copies the samples into matrices, dropping their weights and tags. Fails if the samples don't all
+    /// have the same sizes, or if there are none, since their sizes would be unknown.
+    pub fn from_samples(samples: &[Sample<T>]) -> Result<Self, DatasetError> {
+        if samples.is_empty() {
+            return Err(DatasetError::EmptyDataset);
+        }
+
+        check_dimensions(samples)?;
+
+        let inputs: Vec<_> = samples.iter().map(Sample::inputs).collect();
+        let expected_outputs: Vec<_> = samples.iter().map(Sample::expected_outputs).collect();
+
+        Ok(Self {
+            inputs: DMatrix::from_columns(&inputs),
+            expected_outputs: DMatrix::from_columns(&expected_outputs),
+        })
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.inputs.ncols()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    #[inline]
+    pub fn input_size(&self) -> usize {
+        self.inputs.nrows()
+    }
+
+    #[inline]
+    pub fn output_size(&self) -> usize {
+        self.expected_outputs.nrows()
+    }
+
+    #[inline]
+    pub fn inputs(&self) -> DMatrixView<'_, T> {
+        self.inputs.as_view()
+    }
+
+    #[inline]
+    pub fn expected_outputs(&self) -> DMatrixView<'_, T> {
+        self.expected_outputs.as_view()
+    }
+
+    /// The inputs and expected outputs of sample `index`.
+    pub fn get(&self, index: usize) -> Option<(DVectorView<'_, T>, DVectorView<'_, T>)> {
+        (index < self.len()).then(|| (self.inputs.column(index), self.expected_outputs.column(index)))
+    }
+
+    /// All samples as a view.
+    pub fn as_view(&self) -> BatchView<'_, T> {
+        self.view(0..self.len())
+    }
+
+    /// The samples in `range`, failing with `BatchOutOfRange` if it reaches past the last sample.
+    pub fn batch(&self, range: Range<usize>) -> Result<BatchView<'_, T>, DatasetError> {
+        if range.start > range.end || range.end > self.len() {
+            return Err(DatasetError::BatchOutOfRange {
+                start: range.start,
+                end: range.end,
+                len: self.len(),
+            });
+        }
+
+        Ok(self.view(range))
+    }
+
+    /// Consecutive batches of `size` samples, the last one holding whatever is left.
+    pub fn batches(&self, size: usize) -> impl Iterator<Item = BatchView<'_, T>> {
+        let size = size.max(1);
+        (0..self.len()).step_by(size).map(move |start| self.view(start..(start + size).min(self.len())))
+    }
+
+    /// The samples at `indices` in that order, copied into a new `BatchSamples`.
+    pub(crate) fn select(&self, indices: &[usize]) -> Self {
+        Self {
+            inputs: self.inputs.select_columns(indices),
+            expected_outputs: self.expected_outputs.select_columns(indices),
+        }
+    }
+
+    /// `batch` for a range known to be in bounds.
+    fn view(&self, range: Range<usize>) -> BatchView<'_, T> {
+        BatchView {
+            inputs: self.inputs.columns_range(range.clone()),
+            expected_outputs: self.expected_outputs.columns_range(range),
+        }
+    }
+
+    /// The samples as `Sample`s again, each with the default weight.
+    pub fn to_samples(&self) -> Vec<Sample<T>> {
+        self.inputs
+            .column_iter()
+            .zip(self.expected_outputs.column_iter())
+            .map(|(inputs, expected_outputs)| Sample::new(inputs.clone_owned(), expected_outputs.clone_owned()))
+            .collect()
+    }
+}
+
+impl<'a, T: Scalar> BatchView<'a, T> {
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.inputs.ncols()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    #[inline]
+    pub fn inputs(&self) -> DMatrixView<'a, T> {
+        self.inputs
+    }
+
+    #[inline]
+    pub fn expected_outputs(&self) -> DMatrixView<'a, T> {
+        self.expected_outputs
+    }
+}
@@ -0,0 +1,128 @@
+use std::{
+    fs::File,
+    io::{BufRead, BufReader},
+    path::Path,
+};
+
+use nalgebra::DVector;
+
+use super::{DatasetError, Sample};
+
+/// How `from_csv` reads a file: by default there is no header row and fields are separated by commas.
+#[derive(Clone, Debug)]
+pub struct CsvOptions {
+    header: bool,
+    delimiter: char,
+}
+
+impl Default for CsvOptions {
+    fn default() -> Self {
+        Self {
+            header: false,
+            delimiter: ',',
+        }
+    }
+}
+
+impl CsvOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Skips the first line of the file.
+    pub fn header(mut self, header: bool) -> Self {
+        self.header = header;
+        self
+    }
+
+    pub fn delimiter(mut self, delimiter: char) -> Self {
+        self.delimiter = delimiter;
+        self
+    }
+
+    pub fn read(
+        &self,
+        path: impl AsRef<Path>,
+        input_columns: &[usize],
+        output_columns: &[usize],
+    ) -> Result<Vec<Sample>, DatasetError> {
+        let file = File::open(path)?;
+        self.read_from(BufReader::new(file), input_columns, output_columns)
+    }
+
+    /// Like `read`, but parses any buffered reader. Blank lines are skipped, and every other row must
+    /// have as many fields as the first one.
+    pub fn read_from(
+        &self,
+        reader: impl BufRead,
+        input_columns: &[usize],
+        output_columns: &[usize],
+    ) -> Result<Vec<Sample>, DatasetError> {
+        let mut samples = Vec::new();
+        let mut row_len = None;
+
+        for (index, line) in reader.lines().enumerate() {
+            let line = line?;
+            let line_number = index + 1;
+
+            if (self.header && index == 0) || line.trim().is_empty() {
+                continue;
+            }
+
+            let fields: Vec<&str> = line.split(self.delimiter).map(str::trim).collect();
+
+            match row_len {
+                None => row_len = Some(fields.len()),
+                Some(expected) if expected != fields.len() => {
+                    return Err(DatasetError::RaggedRow {
+                        line: line_number,
+                        expected,
+                        found: fields.len(),
+                    });
+                }
+                _ => {}
+            }
+
+            let inputs = parse_columns(&fields, input_columns, line_number)?;
+            let expected_outputs = parse_columns(&fields, output_columns, line_number)?;
+            samples.push(Sample::new(inputs, expected_outputs));
+        }
+
+        Ok(samples)
+    }
+}
+
+/// Reads a comma separated file without a header, taking the inputs and expected outputs of every
+/// sample from the given columns, in the given order.
+pub fn from_csv(
+    path: impl AsRef<Path>,
+    input_columns: &[usize],
+    output_columns: &[usize],
+) -> Result<Vec<Sample>, DatasetError> {
+    CsvOptions::default().read(path, input_columns, output_columns)
+}
+
+fn parse_columns(fields: &[&str], columns: &[usize], line: usize) -> Result<DVector<f32>, DatasetError> {
+    let values = columns
+        .iter()
+        .map(|&column| {
+            let field = *fields.get(column).ok_or(DatasetError::ColumnOutOfRange {
+                line,
+                column,
+                columns: fields.len(),
+            })?;
+
+            if field.is_empty() {
+                return Err(DatasetError::MissingValue { line, column });
+            }
+
+            field.parse().map_err(|_| DatasetError::InvalidNumber {
+                line,
+                column,
+                value: field.to_string(),
+            })
+        })
+        .collect::<Result<Vec<f32>, _>>()?;
+
+    Ok(DVector::from_vec(values))
+}
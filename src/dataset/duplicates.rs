@@ -0,0 +1,167 @@
+//! Finding samples with (nearly) the same inputs, e.g. the same spot clicked twice in the demo, and
+//! especially those among them whose expected outputs contradict each other, which puts a floor under the
+//! loss that no network can get below.
+
+use core::cmp::Ordering;
+
+use nalgebra::DVector;
+
+use crate::prelude::*;
+
+use super::Sample;
+
+/// Samples whose inputs are all within the input tolerance of each other, directly or through other
+/// samples of the group, but whose expected outputs differ by more than the output tolerance.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Conflict {
+    /// The indices of the samples, in increasing order.
+    pub samples: Vec<usize>,
+    /// The largest difference of an expected output between two samples of the group, infinite if their
+    /// numbers of outputs differ.
+    pub output_spread: f32,
+}
+
+/// Which sample `dedup` keeps of a group of near duplicates.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DedupStrategy {
+    First,
+    Last,
+    /// A sample with the mean inputs, expected outputs and weight of the group and the first one's tag.
+    /// Groups whose expected outputs differ in size keep the first sample instead.
+    Average,
+}
+
+/// The groups of samples whose inputs are within `input_tolerance` of each other and whose expected outputs
+/// differ by more than `output_tolerance`, ordered by their first sample. Distances are the largest
+/// difference of a single value, so a tolerance of 0 finds exact duplicates, and groups are chained: two
+/// samples further apart than the tolerance share a group if a third is near both. Samples with NaN inputs
+/// or a different number of inputs are never near each other.
+///
+/// Samples are sorted along the input that varies most and only compared to those within the tolerance
+/// along it, so unless most samples crowd into a tolerance wide slab the cost is about `O(n log n)`.
+pub fn find_conflicts(samples: &[Sample], input_tolerance: f32, output_tolerance: f32) -> Vec<Conflict> {
+    near_groups(samples, input_tolerance)
+        .into_iter()
+        .filter(|group| group.len() > 1)
+        .map(|group| Conflict {
+            output_spread: output_spread(samples, &group),
+            samples: group,
+        })
+        .filter(|conflict| conflict.output_spread > output_tolerance)
+        .collect()
+}
+
+/// Replaces every group of samples whose inputs are within `input_tolerance` of each other, grouped like in
+/// `find_conflicts` but whatever their expected outputs, by a single sample chosen by `strategy`. The kept
+/// sample takes the place of the group's first one, the other samples keep their order.
+pub fn dedup(samples: &[Sample], input_tolerance: f32, strategy: DedupStrategy) -> Vec<Sample> {
+    near_groups(samples, input_tolerance)
+        .into_iter()
+        .map(|group| match strategy {
+            DedupStrategy::First => samples[group[0]].clone(),
+            DedupStrategy::Last => samples[*group.last().unwrap()].clone(),
+            DedupStrategy::Average => average(samples, &group),
+        })
+        .collect()
+}
+
+/// Every sample's group of near duplicates, singletons included, ordered by their first sample.
+fn near_groups(samples: &[Sample], tolerance: f32) -> Vec<Vec<usize>> {
+    let axis = widest_input(samples);
+    let key = |index: usize| samples[index].inputs_slice().get(axis).copied().unwrap_or(0.0);
+
+    let mut order: Vec<usize> = (0..samples.len()).collect();
+    order.sort_by(|&a, &b| {
+        samples[a].inputs().len().cmp(&samples[b].inputs().len()).then_with(|| key(a).total_cmp(&key(b)))
+    });
+
+    let mut parents: Vec<usize> = (0..samples.len()).collect();
+
+    for (position, &a) in order.iter().enumerate() {
+        for &b in &order[position + 1..] {
+            let gap = key(b) - key(a);
+            if samples[b].inputs().len() != samples[a].inputs().len() || gap > tolerance || gap.is_nan() {
+                break;
+            }
+
+            if is_near(samples[a].inputs_slice(), samples[b].inputs_slice(), tolerance) {
+                let (root_a, root_b) = (find(&mut parents, a), find(&mut parents, b));
+                parents[root_a.max(root_b)] = root_a.min(root_b);
+            }
+        }
+    }
+
+    // Every root is the smallest index of its group, so the groups come out ordered by their first sample.
+    let mut groups: Vec<Vec<usize>> = vec![Vec::new(); samples.len()];
+    for index in 0..samples.len() {
+        let root = find(&mut parents, index);
+        groups[root].push(index);
+    }
+
+    groups.retain(|group| !group.is_empty());
+    groups
+}
+
+/// The input that spans the widest range over the samples, the one sorting along separates them best.
+fn widest_input(samples: &[Sample]) -> usize {
+    let inputs = samples.first().map_or(0, |sample| sample.inputs().len());
+
+    (0..inputs)
+        .map(|input| {
+            let values = samples.iter().filter_map(|sample| sample.inputs_slice().get(input)).filter(|x| !x.is_nan());
+            let (min, max) = values.fold((f32::INFINITY, f32::NEG_INFINITY), |(min, max), &x| (min.min(x), max.max(x)));
+            (input, max - min)
+        })
+        .max_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap_or(Ordering::Equal))
+        .map_or(0, |(input, _)| input)
+}
+
+fn is_near(a: &[f32], b: &[f32], tolerance: f32) -> bool {
+    a.iter().zip(b).all(|(x, y)| (x - y).abs() <= tolerance)
+}
+
+fn find(parents: &mut [usize], mut index: usize) -> usize {
+    while parents[index] != index {
+        parents[index] = parents[parents[index]];
+        index = parents[index];
+    }
+
+    index
+}
+
+fn output_spread(samples: &[Sample], group: &[usize]) -> f32 {
+    let first = samples[group[0]].outputs_slice();
+    if group.iter().any(|&index| samples[index].outputs_slice().len() != first.len()) {
+        return f32::INFINITY;
+    }
+
+    (0..first.len())
+        .map(|output| {
+            let values = group.iter().map(|&index| samples[index].outputs_slice()[output]);
+            let (min, max) = values.fold((f32::INFINITY, f32::NEG_INFINITY), |(min, max), x| (min.min(x), max.max(x)));
+            max - min
+        })
+        .fold(0.0, f32::max)
+}
+
+fn average(samples: &[Sample], group: &[usize]) -> Sample {
+    let first = &samples[group[0]];
+    if group.iter().any(|&index| samples[index].expected_outputs().len() != first.expected_outputs().len()) {
+        return first.clone();
+    }
+
+    let count = group.len() as f32;
+    let mean = |vector: fn(&Sample) -> DVector<f32>| group.iter().map(|&index| vector(&samples[index])).sum::<DVector<f32>>() / count;
+    let weight = group.iter().map(|&index| samples[index].weight()).sum::<f32>() / count;
+
+    let sample = Sample::new(
+        mean(|sample| sample.inputs().into_owned()),
+        mean(|sample| sample.expected_outputs().into_owned()),
+    )
+    .with_weight(weight);
+
+    match first.tag() {
+        Some(tag) => sample.with_tag(tag),
+        None => sample,
+    }
+}
@@ -0,0 +1,124 @@
+use nalgebra::{DVector, DVectorView};
+
+use crate::prelude::*;
+
+use super::{DatasetError, Sample};
+
+/// Polynomial features: maps inputs to every monomial of them from degree 1 up to a maximum degree, ordered
+/// by degree and then by the features they multiply, e.g. `[a, b, a², ab, b²]` for degree 2. There's no
+/// constant term, the first layer's biases play that role. Degree 1 is the identity.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FeatureExpansion {
+    input_size: usize,
+    degree: usize,
+    interactions_only: bool,
+    /// The features every output multiplies, in ascending order.
+    terms: Vec<Vec<usize>>,
+}
+
+impl FeatureExpansion {
+    /// All monomials of `input_size` features up to `degree`.
+    pub fn new(input_size: usize, degree: usize) -> Result<Self, DatasetError> {
+        Self::with_terms(input_size, degree, false)
+    }
+
+    /// Only monomials of distinct features, `[a, b, ab]` for degree 2, leaving out powers like `a²`.
+    pub fn interactions_only(input_size: usize, degree: usize) -> Result<Self, DatasetError> {
+        Self::with_terms(input_size, degree, true)
+    }
+
+    fn with_terms(input_size: usize, degree: usize, interactions_only: bool) -> Result<Self, DatasetError> {
+        if input_size == 0 || degree == 0 {
+            return Err(DatasetError::InvalidExpansion { input_size, degree });
+        }
+
+        let mut terms: Vec<Vec<usize>> = (0..input_size).map(|feature| vec![feature]).collect();
+        let mut previous = terms.clone();
+
+        for _ in 1..degree {
+            let next: Vec<Vec<usize>> = previous
+                .iter()
+                .flat_map(|term| {
+                    let last = *term.last().unwrap();
+                    let first = if interactions_only { last + 1 } else { last };
+
+                    (first..input_size).map(move |feature| {
+                        let mut term = term.clone();
+                        term.push(feature);
+                        term
+                    })
+                })
+                .collect();
+
+            terms.extend(next.iter().cloned());
+            previous = next;
+        }
+
+        Ok(Self { input_size, degree, interactions_only, terms })
+    }
+
+    /// The number of terms `new` or `interactions_only` would build, without building them, or `None` if
+    /// there are more than `limit`, so a degree read from a file can be checked before anything is allocated.
+    #[cfg(feature = "std")]
+    pub(crate) fn term_count_up_to(input_size: usize, degree: usize, interactions_only: bool, limit: usize) -> Option<usize> {
+        if input_size == 0 {
+            return Some(0);
+        }
+
+        let (mut count, mut level) = (0usize, 1usize);
+
+        // The monomials of degree k are the multisets (or sets) of k features, counted as binomial coefficients
+        // from those of degree k - 1. Every degree adds at least one term until the sets run out.
+        for k in 1..=degree {
+            let factor = match interactions_only {
+                true if k > input_size => break,
+                true => input_size - (k - 1),
+                false => input_size.checked_add(k - 1)?,
+            };
+
+            level = level.checked_mul(factor)? / k;
+            count = count.checked_add(level).filter(|&count| count <= limit)?;
+        }
+
+        Some(count)
+    }
+
+    #[inline]
+    pub fn input_size(&self) -> usize { self.input_size }
+
+    /// The number of features an input expands to, the input size of the first layer.
+    #[inline]
+    pub fn output_size(&self) -> usize { self.terms.len() }
+
+    #[inline]
+    pub fn degree(&self) -> usize { self.degree }
+
+    #[inline]
+    pub fn is_interactions_only(&self) -> bool { self.interactions_only }
+
+    pub fn transform(&self, vector: DVectorView<f32>) -> Result<DVector<f32>, DatasetError> {
+        if vector.len() != self.input_size {
+            return Err(DatasetError::TransformSizeMismatch {
+                expected: self.input_size,
+                given: vector.len(),
+            });
+        }
+
+        Ok(DVector::from_iterator(
+            self.terms.len(),
+            self.terms.iter().map(|term| term.iter().map(|&feature| vector[feature]).product()),
+        ))
+    }
+
+    /// Returns copies of the samples with expanded inputs.
+    pub fn expand_samples(&self, samples: &[Sample]) -> Result<Vec<Sample>, DatasetError> {
+        samples
+            .iter()
+            .map(|sample| {
+                let mut sample = sample.clone();
+                sample.inputs = self.transform(sample.inputs())?;
+                Ok(sample)
+            })
+            .collect()
+    }
+}
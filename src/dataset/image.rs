@@ -0,0 +1,59 @@
+//! Conversions between 8-bit grayscale images and network inputs. Pixels are stored row-major, so the pixel
+//! in row `y` and column `x` of an image `width` pixels wide is at index `y * width + x`, the layout of the
+//! MNIST loader and of a single-channel image for `Conv2D`.
+
+use nalgebra::{DVector, DVectorView};
+
+use crate::prelude::*;
+
+use super::{one_hot, DatasetError, Sample};
+
+/// An image's pixels scaled from 0..=255 to 0..=1.
+///
+/// Panics if `pixels` doesn't hold exactly `width * height` pixels.
+pub fn from_gray_buffer(pixels: &[u8], width: usize, height: usize) -> DVector<f32> {
+    assert_eq!(pixels.len(), width * height, "a {width}x{height} image has {} pixels", width * height);
+    DVector::from_iterator(pixels.len(), pixels.iter().map(|&pixel| pixel as f32 / 255.0))
+}
+
+/// The inverse of `from_gray_buffer`, e.g. to display what an autoencoder reconstructs. Values are clamped
+/// to 0..=1 before scaling and rounded to the nearest level, NaN becomes 0.
+///
+/// Panics if `values` doesn't hold exactly `width * height` values.
+pub fn to_gray_buffer(values: DVectorView<f32>, width: usize, height: usize) -> Vec<u8> {
+    assert_eq!(values.len(), width * height, "a {width}x{height} image has {} pixels", width * height);
+    values.iter().map(|&value| (value.clamp(0.0, 1.0) * 255.0).round() as u8).collect()
+}
+
+/// Classification samples from images and their labels, with the scaled pixels as inputs and the one-hot
+/// encoded labels as expected outputs. Fails with `ImageSizeMismatch` naming the first image that doesn't
+/// hold `width * height` pixels, and like `samples_from_labeled` if the labels don't fit.
+pub fn samples_from_images(
+    images: &[&[u8]],
+    labels: &[usize],
+    num_classes: usize,
+    width: usize,
+    height: usize,
+) -> Result<Vec<Sample>, DatasetError> {
+    if images.len() != labels.len() {
+        return Err(DatasetError::LabelCountMismatch {
+            inputs: images.len(),
+            labels: labels.len(),
+        });
+    }
+
+    if let Some(index) = images.iter().position(|image| image.len() != width * height) {
+        return Err(DatasetError::ImageSizeMismatch {
+            index,
+            width,
+            height,
+            pixels: images[index].len(),
+        });
+    }
+
+    images
+        .iter()
+        .zip(labels)
+        .map(|(image, &label)| Ok(Sample::new(from_gray_buffer(image, width, height), one_hot(label, num_classes)?)))
+        .collect()
+}
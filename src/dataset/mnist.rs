@@ -0,0 +1,178 @@
+//! Loader for the IDX files the MNIST dataset is distributed in, raw or gzip compressed
+//! (the latter needs the `gzip` feature).
+
+use std::{fs, path::Path};
+
+use nalgebra::DVector;
+use thiserror::Error;
+
+use super::{from_gray_buffer, one_hot, Sample};
+
+const IMAGES_MAGIC: u32 = 0x0000_0803;
+const LABELS_MAGIC: u32 = 0x0000_0801;
+const CLASSES: usize = 10;
+
+#[derive(Debug, Error)]
+pub enum MnistError {
+    #[error("{0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("expected the magic number {expected:#010x}, but found {found:#010x}")]
+    BadMagic {
+        expected: u32,
+        found: u32,
+    },
+
+    #[error("the file is truncated, {expected} bytes were expected but only {found} are present")]
+    Truncated {
+        expected: usize,
+        found: usize,
+    },
+
+    #[error("the header gives {count} images of {rows}x{columns} pixels, more than any file holds")]
+    TooManyPixels {
+        count: usize,
+        rows: usize,
+        columns: usize,
+    },
+
+    #[error("the images have {rows}x{columns} pixels, none at all")]
+    EmptyImages {
+        rows: usize,
+        columns: usize,
+    },
+
+    #[error("there are {images} images but {labels} labels")]
+    CountMismatch {
+        images: usize,
+        labels: usize,
+    },
+
+    #[error("label {0} is not a digit")]
+    InvalidLabel(u8),
+
+    #[error("the file is gzip compressed, but the gzip feature is disabled")]
+    GzipUnsupported,
+}
+
+/// Reads an IDX image file, returning every image as a row-major vector of pixels scaled to 0..1.
+pub fn load_images(path: impl AsRef<Path>) -> Result<Vec<DVector<f32>>, MnistError> {
+    let bytes = read(path.as_ref())?;
+    let mut header = Header::new(&bytes);
+
+    header.magic(IMAGES_MAGIC)?;
+    let count = header.next()?;
+    let rows = header.next()?;
+    let columns = header.next()?;
+
+    if rows == 0 || columns == 0 {
+        return Err(MnistError::EmptyImages { rows, columns });
+    }
+
+    // The sizes come from the file, so their product is checked before it's compared to the file's length.
+    let (image_size, len) = rows
+        .checked_mul(columns)
+        .and_then(|image_size| Some((image_size, count.checked_mul(image_size)?)))
+        .ok_or(MnistError::TooManyPixels { count, rows, columns })?;
+    let pixels = header.data(len)?;
+
+    Ok((0..count)
+        .map(|i| &pixels[i * image_size..(i + 1) * image_size])
+        .map(|image| from_gray_buffer(image, columns, rows))
+        .collect())
+}
+
+/// Reads an IDX label file.
+pub fn load_labels(path: impl AsRef<Path>) -> Result<Vec<u8>, MnistError> {
+    let bytes = read(path.as_ref())?;
+    let mut header = Header::new(&bytes);
+
+    header.magic(LABELS_MAGIC)?;
+    let count = header.next()?;
+
+    Ok(header.data(count)?.to_vec())
+}
+
+/// Loads matching image and label files as samples with a one-hot expected output per digit.
+pub fn load(images_path: impl AsRef<Path>, labels_path: impl AsRef<Path>) -> Result<Vec<Sample>, MnistError> {
+    let images = load_images(images_path)?;
+    let labels = load_labels(labels_path)?;
+
+    if images.len() != labels.len() {
+        return Err(MnistError::CountMismatch {
+            images: images.len(),
+            labels: labels.len(),
+        });
+    }
+
+    images
+        .into_iter()
+        .zip(labels)
+        .map(|(image, label)| {
+            let expected_outputs = one_hot(label as usize, CLASSES).map_err(|_| MnistError::InvalidLabel(label))?;
+            Ok(Sample::new(image, expected_outputs))
+        })
+        .collect()
+}
+
+fn read(path: &Path) -> Result<Vec<u8>, MnistError> {
+    let bytes = fs::read(path)?;
+
+    if !bytes.starts_with(&[0x1f, 0x8b]) {
+        return Ok(bytes);
+    }
+
+    #[cfg(feature = "gzip")]
+    {
+        use std::io::Read;
+
+        let mut decompressed = Vec::new();
+        flate2::read::GzDecoder::new(bytes.as_slice()).read_to_end(&mut decompressed)?;
+        Ok(decompressed)
+    }
+
+    #[cfg(not(feature = "gzip"))]
+    Err(MnistError::GzipUnsupported)
+}
+
+/// Cursor over the big-endian 32 bit integers at the start of an IDX file.
+struct Header<'a> {
+    bytes: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> Header<'a> {
+    fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes, offset: 0 }
+    }
+
+    fn next(&mut self) -> Result<usize, MnistError> {
+        let end = self.offset + 4;
+        let bytes = self.bytes.get(self.offset..end).ok_or(MnistError::Truncated {
+            expected: end,
+            found: self.bytes.len(),
+        })?;
+
+        self.offset = end;
+        Ok(u32::from_be_bytes(bytes.try_into().unwrap()) as usize)
+    }
+
+    fn magic(&mut self, expected: u32) -> Result<(), MnistError> {
+        let found = self.next()? as u32;
+
+        if found != expected {
+            return Err(MnistError::BadMagic { expected, found });
+        }
+
+        Ok(())
+    }
+
+    fn data(&self, len: usize) -> Result<&'a [u8], MnistError> {
+        let end = self.offset.saturating_add(len);
+
+        self.bytes.get(self.offset..end).ok_or(MnistError::Truncated {
+            expected: end,
+            found: self.bytes.len(),
+        })
+    }
+}
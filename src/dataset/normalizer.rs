@@ -0,0 +1,159 @@
+use nalgebra::{DVector, DVectorView};
+
+use crate::prelude::*;
+
+use super::{check_dimensions, DatasetError, Sample};
+
+/// A spread below this fraction of a feature's magnitude is rounding noise, the feature counts as constant.
+const CONSTANT_SPREAD: f32 = 8.0 * f32::EPSILON;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Normalization {
+    /// Maps every feature's range in the fitted data to 0..1.
+    MinMax,
+    /// Centers every feature on its mean and divides it by its standard deviation.
+    ZScore,
+}
+
+/// Per-feature affine normalization `(x - offset) / scale`, fitted on a dataset and kept around to
+/// transform inputs at inference time or to turn normalized network outputs back into the original scale.
+/// Features that are constant in the fitted data get a scale of 1, so they're only shifted. Constant means a
+/// spread that is negligible next to the feature's magnitude, so features of any scale but constant ones are scaled.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Normalizer {
+    offset: DVector<f32>,
+    scale: DVector<f32>,
+}
+
+impl Normalizer {
+    /// Fits the normalizer to the inputs of the samples.
+    pub fn fit(samples: &[Sample], normalization: Normalization) -> Result<Self, DatasetError> {
+        check_dimensions(samples)?;
+        Self::fit_vectors(samples.iter().map(Sample::inputs), normalization)
+    }
+
+    /// Fits the normalizer to the expected outputs of the samples.
+    pub fn fit_outputs(samples: &[Sample], normalization: Normalization) -> Result<Self, DatasetError> {
+        check_dimensions(samples)?;
+        Self::fit_vectors(samples.iter().map(Sample::expected_outputs), normalization)
+    }
+
+    pub fn from_parameters(offset: DVector<f32>, scale: DVector<f32>) -> Result<Self, DatasetError> {
+        if offset.len() != scale.len() {
+            return Err(DatasetError::NormalizerSizeMismatch {
+                normalizer_size: offset.len(),
+                given_size: scale.len(),
+            });
+        }
+
+        Ok(Self { offset, scale })
+    }
+
+    fn fit_vectors<'a>(
+        vectors: impl Iterator<Item = DVectorView<'a, f32>> + Clone,
+        normalization: Normalization,
+    ) -> Result<Self, DatasetError> {
+        let count = vectors.clone().count();
+        let size = vectors.clone().next().ok_or(DatasetError::EmptyDataset)?.len();
+
+        let (offset, spread) = match normalization {
+            Normalization::MinMax => {
+                let mut min = DVector::from_element(size, f32::INFINITY);
+                let mut max = DVector::from_element(size, f32::NEG_INFINITY);
+
+                for vector in vectors {
+                    min.zip_apply(&vector, |min, x| *min = min.min(x));
+                    max.zip_apply(&vector, |max, x| *max = max.max(x));
+                }
+
+                let range = &max - &min;
+                (min, range)
+            }
+
+            Normalization::ZScore => {
+                let mut mean = DVector::zeros(size);
+                for vector in vectors.clone() {
+                    mean += vector;
+                }
+                mean /= count as f32;
+
+                let mut variance = DVector::zeros(size);
+                for vector in vectors {
+                    variance += (vector - &mean).map(|x| x * x);
+                }
+                variance /= count as f32;
+
+                (mean, variance.map(f32::sqrt))
+            }
+        };
+
+        let scale = spread.zip_map(&offset, |spread, offset| if spread > CONSTANT_SPREAD * offset.abs() { spread } else { 1.0 });
+        Ok(Self { offset, scale })
+    }
+
+    #[inline]
+    pub fn size(&self) -> usize { self.offset.len() }
+
+    #[inline]
+    pub fn offset(&self) -> DVectorView<'_, f32> { self.offset.as_view() }
+
+    #[inline]
+    pub fn scale(&self) -> DVectorView<'_, f32> { self.scale.as_view() }
+
+    pub fn transform(&self, vector: DVectorView<f32>) -> Result<DVector<f32>, DatasetError> {
+        let mut vector = vector.into_owned();
+        self.transform_mut(&mut vector)?;
+        Ok(vector)
+    }
+
+    pub fn transform_mut(&self, vector: &mut DVector<f32>) -> Result<(), DatasetError> {
+        self.check_size(vector.len())?;
+        *vector -= &self.offset;
+        vector.component_div_assign(&self.scale);
+        Ok(())
+    }
+
+    /// Maps a normalized vector back to the original scale.
+    pub fn inverse(&self, vector: DVectorView<f32>) -> Result<DVector<f32>, DatasetError> {
+        let mut vector = vector.into_owned();
+        self.inverse_mut(&mut vector)?;
+        Ok(vector)
+    }
+
+    pub fn inverse_mut(&self, vector: &mut DVector<f32>) -> Result<(), DatasetError> {
+        self.check_size(vector.len())?;
+        vector.component_mul_assign(&self.scale);
+        *vector += &self.offset;
+        Ok(())
+    }
+
+    /// Normalizes the inputs of the samples in place. Nothing is changed if any sample's inputs have the wrong size.
+    pub fn normalize_inputs(&self, samples: &mut [Sample]) -> Result<(), DatasetError> {
+        samples.iter().try_for_each(|sample| self.check_size(sample.inputs.len()))?;
+        samples.iter_mut().try_for_each(|sample| self.transform_mut(&mut sample.inputs))
+    }
+
+    /// Normalizes the expected outputs of the samples in place, like `normalize_inputs`.
+    pub fn normalize_outputs(&self, samples: &mut [Sample]) -> Result<(), DatasetError> {
+        samples.iter().try_for_each(|sample| self.check_size(sample.expected_outputs.len()))?;
+        samples.iter_mut().try_for_each(|sample| self.transform_mut(&mut sample.expected_outputs))
+    }
+
+    /// Returns copies of the samples with normalized inputs.
+    pub fn normalized_inputs(&self, samples: &[Sample]) -> Result<Vec<Sample>, DatasetError> {
+        let mut samples = samples.to_vec();
+        self.normalize_inputs(&mut samples)?;
+        Ok(samples)
+    }
+
+    fn check_size(&self, size: usize) -> Result<(), DatasetError> {
+        if size != self.size() {
+            return Err(DatasetError::NormalizerSizeMismatch {
+                normalizer_size: self.size(),
+                given_size: size,
+            });
+        }
+
+        Ok(())
+    }
+}
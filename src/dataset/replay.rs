@@ -0,0 +1,130 @@
+use rand::{seq::index, Rng};
+
+use crate::prelude::*;
+
+use super::{DatasetError, Sample};
+
+/// How a full `ReplayBuffer` makes room for a new sample.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ReplayMode {
+    /// The new sample replaces the oldest one, so the buffer holds the most recent samples.
+    #[default]
+    Ring,
+    /// Reservoir sampling: the new sample replaces a random one with probability `capacity / seen`, so the
+    /// buffer is a uniform sample of every sample pushed so far.
+    Reservoir,
+}
+
+/// A bounded dataset for training on a stream of samples, e.g. an agent's experience.
+#[derive(Clone, Debug)]
+pub struct ReplayBuffer {
+    samples: Vec<Sample>,
+    capacity: usize,
+    mode: ReplayMode,
+    /// Where the next sample goes in ring mode once the buffer is full.
+    next: usize,
+    seen: usize,
+}
+
+impl ReplayBuffer {
+    /// Fails with `ZeroCapacity` if `capacity` is 0.
+    pub fn new(capacity: usize, mode: ReplayMode) -> Result<Self, DatasetError> {
+        if capacity == 0 {
+            return Err(DatasetError::ZeroCapacity);
+        }
+
+        Ok(Self {
+            samples: Vec::with_capacity(capacity),
+            capacity,
+            mode,
+            next: 0,
+            seen: 0,
+        })
+    }
+
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    #[inline]
+    pub fn mode(&self) -> ReplayMode {
+        self.mode
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.samples.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.samples.is_empty()
+    }
+
+    /// The number of samples pushed so far, including evicted ones.
+    #[inline]
+    pub fn seen(&self) -> usize {
+        self.seen
+    }
+
+    /// The samples held, in no particular order, e.g. to train an epoch on all of them.
+    #[inline]
+    pub fn samples(&self) -> &[Sample] {
+        &self.samples
+    }
+
+    /// The samples held from the oldest to the newest. In reservoir mode that's the order they were stored in.
+    pub fn iter(&self) -> impl Iterator<Item = &Sample> {
+        self.samples[self.next..].iter().chain(self.samples[..self.next].iter())
+    }
+
+    #[cfg(feature = "std")]
+    pub fn push(&mut self, sample: Sample) {
+        self.push_with_rng(sample, &mut rand::rng());
+    }
+
+    /// Like `push`, with the given RNG deciding what reservoir sampling keeps. Ring mode draws nothing.
+    pub fn push_with_rng(&mut self, sample: Sample, rng: &mut impl Rng) {
+        self.seen += 1;
+
+        if self.samples.len() < self.capacity {
+            self.samples.push(sample);
+            return;
+        }
+
+        match self.mode {
+            ReplayMode::Ring => {
+                self.samples[self.next] = sample;
+                self.next = (self.next + 1) % self.capacity;
+            }
+
+            ReplayMode::Reservoir => {
+                let slot = rng.random_range(0..self.seen);
+                if slot < self.capacity {
+                    self.samples[slot] = sample;
+                }
+            }
+        }
+    }
+
+    /// `n` distinct samples drawn uniformly, or all of them in random order if the buffer holds fewer than `n`.
+    pub fn sample_batch(&self, n: usize, rng: &mut impl Rng) -> Vec<&Sample> {
+        index::sample(rng, self.len(), n.min(self.len()))
+            .into_iter()
+            .map(|i| &self.samples[i])
+            .collect()
+    }
+
+    /// `sample_batch` as copies, which the training APIs take directly.
+    pub fn sample_batch_cloned(&self, n: usize, rng: &mut impl Rng) -> Vec<Sample> {
+        self.sample_batch(n, rng).into_iter().cloned().collect()
+    }
+
+    /// Empties the buffer and resets its count of seen samples.
+    pub fn clear(&mut self) {
+        self.samples.clear();
+        self.next = 0;
+        self.seen = 0;
+    }
+}
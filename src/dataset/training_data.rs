@@ -0,0 +1,152 @@
+//! What `Trainer` can train on: `Sample`s in a slice, `Vec` or `Dataset`, or the matrices of a `BatchSamples`.
+
+use alloc::borrow::Cow;
+
+use rand::RngCore;
+
+use crate::{
+    losses::LossFn,
+    network::{Network, NetworkError, Optimizer},
+    prelude::*,
+    scalar::Scalar,
+};
+
+use super::{BatchSamples, Dataset, Sample};
+
+/// Samples a `Trainer` can go through by index. Steps on consecutive indices borrow the samples, steps in
+/// another order gather them, which only copies for `BatchSamples`.
+pub trait TrainingData<T: Scalar = f32> {
+    fn len(&self) -> usize;
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// All samples as `Sample`s, borrowed unless they're stored otherwise. Features that look at single
+    /// samples, like curricula, balanced batches or augmentation, train on these.
+    fn samples(&self) -> Cow<'_, [Sample<T>]>;
+
+    /// One step of `network` on the samples at `indices` like `Network::learn_with_optimizer`, returning the
+    /// mean loss and the norm of the mean gradient. Panics if an index is out of range.
+    fn learn_indices(
+        &self,
+        network: &mut Network<T>,
+        indices: &[usize],
+        loss: &impl LossFn<T>,
+        rate: T,
+        optimizer: &mut dyn Optimizer<T>,
+        gradient_noise: Option<(T, &mut dyn RngCore)>,
+    ) -> Result<(T, T), NetworkError>;
+}
+
+/// The range `indices` covers if they're consecutive and ascending.
+fn consecutive(indices: &[usize]) -> Option<core::ops::Range<usize>> {
+    let start = *indices.first()?;
+    indices
+        .iter()
+        .enumerate()
+        .all(|(offset, &index)| index == start + offset)
+        .then(|| start..start + indices.len())
+}
+
+impl<T: Scalar> TrainingData<T> for [Sample<T>] {
+    fn len(&self) -> usize {
+        <[Sample<T>]>::len(self)
+    }
+
+    fn samples(&self) -> Cow<'_, [Sample<T>]> {
+        Cow::Borrowed(self)
+    }
+
+    fn learn_indices(
+        &self,
+        network: &mut Network<T>,
+        indices: &[usize],
+        loss: &impl LossFn<T>,
+        rate: T,
+        optimizer: &mut dyn Optimizer<T>,
+        gradient_noise: Option<(T, &mut dyn RngCore)>,
+    ) -> Result<(T, T), NetworkError> {
+        match consecutive(indices) {
+            Some(range) => network.learn_with_gradient_norm(&self[range], loss, rate, optimizer, gradient_noise),
+            None => {
+                let batch: Vec<&Sample<T>> = indices.iter().map(|&index| &self[index]).collect();
+                network.learn_with_gradient_norm(&batch, loss, rate, optimizer, gradient_noise)
+            }
+        }
+    }
+}
+
+impl<T: Scalar> TrainingData<T> for Vec<Sample<T>> {
+    fn len(&self) -> usize {
+        Vec::len(self)
+    }
+
+    fn samples(&self) -> Cow<'_, [Sample<T>]> {
+        Cow::Borrowed(self)
+    }
+
+    fn learn_indices(
+        &self,
+        network: &mut Network<T>,
+        indices: &[usize],
+        loss: &impl LossFn<T>,
+        rate: T,
+        optimizer: &mut dyn Optimizer<T>,
+        gradient_noise: Option<(T, &mut dyn RngCore)>,
+    ) -> Result<(T, T), NetworkError> {
+        self.as_slice().learn_indices(network, indices, loss, rate, optimizer, gradient_noise)
+    }
+}
+
+impl TrainingData for Dataset {
+    fn len(&self) -> usize {
+        <[Sample]>::len(self)
+    }
+
+    fn samples(&self) -> Cow<'_, [Sample]> {
+        Cow::Borrowed(self)
+    }
+
+    fn learn_indices(
+        &self,
+        network: &mut Network,
+        indices: &[usize],
+        loss: &impl LossFn,
+        rate: f32,
+        optimizer: &mut dyn Optimizer,
+        gradient_noise: Option<(f32, &mut dyn RngCore)>,
+    ) -> Result<(f32, f32), NetworkError> {
+        (**self).learn_indices(network, indices, loss, rate, optimizer, gradient_noise)
+    }
+}
+
+/// Steps on consecutive indices train on a view of the matrices like `Network::learn_batch`, steps in another
+/// order on a copy of the selected columns.
+impl<T: Scalar> TrainingData<T> for BatchSamples<T> {
+    fn len(&self) -> usize {
+        BatchSamples::len(self)
+    }
+
+    fn samples(&self) -> Cow<'_, [Sample<T>]> {
+        Cow::Owned(self.to_samples())
+    }
+
+    fn learn_indices(
+        &self,
+        network: &mut Network<T>,
+        indices: &[usize],
+        loss: &impl LossFn<T>,
+        rate: T,
+        optimizer: &mut dyn Optimizer<T>,
+        gradient_noise: Option<(T, &mut dyn RngCore)>,
+    ) -> Result<(T, T), NetworkError> {
+        match consecutive(indices) {
+            Some(range) => {
+                let batch = self.batch(range).expect("indices past the samples");
+                network.learn_batch_with_gradient_norm(batch, loss, rate, optimizer, gradient_noise)
+            }
+            None => network.learn_batch_with_gradient_norm(self.select(indices).as_view(), loss, rate, optimizer, gradient_noise),
+        }
+    }
+}
@@ -0,0 +1,127 @@
+//! Supervised samples from time series: every sample's inputs are `input_window` consecutive steps and its
+//! expected outputs the `horizon` steps right after them.
+
+use nalgebra::DVector;
+
+use crate::prelude::*;
+
+use super::{DatasetError, Sample};
+
+/// How `windowed` cuts a series: by default every step starts a window and the values are used as they are.
+#[derive(Clone, Debug)]
+pub struct WindowOptions {
+    stride: usize,
+    normalize: bool,
+}
+
+impl Default for WindowOptions {
+    fn default() -> Self {
+        Self {
+            stride: 1,
+            normalize: false,
+        }
+    }
+}
+
+impl WindowOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The steps from one window's start to the next, at least 1.
+    pub fn stride(mut self, stride: usize) -> Self {
+        self.stride = stride.max(1);
+        self
+    }
+
+    /// Shifts and scales every sample by the mean and standard deviation of its input window, so the inputs
+    /// have mean 0 and standard deviation 1 and the expected outputs are on the same scale. Multivariate
+    /// series are normalized per feature. A constant window is only shifted.
+    pub fn normalize(mut self, normalize: bool) -> Self {
+        self.normalize = normalize;
+        self
+    }
+
+    pub fn windowed(&self, series: &[f32], input_window: usize, horizon: usize) -> Result<Vec<Sample>, DatasetError> {
+        let series: Vec<DVector<f32>> = series.iter().map(|&value| DVector::from_element(1, value)).collect();
+        self.windowed_multivariate(&series, input_window, horizon)
+    }
+
+    /// `windowed` for a series with several values per step. A sample's inputs are the steps of its window
+    /// one after the other, and so are its expected outputs.
+    pub fn windowed_multivariate(
+        &self,
+        series: &[DVector<f32>],
+        input_window: usize,
+        horizon: usize,
+    ) -> Result<Vec<Sample>, DatasetError> {
+        if input_window == 0 || horizon == 0 {
+            return Err(DatasetError::ZeroWindow);
+        }
+
+        let required = input_window + horizon;
+        if series.len() < required {
+            return Err(DatasetError::SeriesTooShort {
+                length: series.len(),
+                required,
+            });
+        }
+
+        let features = series[0].len();
+        if let Some(index) = series.iter().position(|step| step.len() != features) {
+            return Err(DatasetError::SeriesDimensionMismatch {
+                index,
+                expected: features,
+                found: series[index].len(),
+            });
+        }
+
+        let samples = (0..=series.len() - required)
+            .step_by(self.stride)
+            .map(|start| {
+                let concat = |steps: &[DVector<f32>]| DVector::from_iterator(
+                    steps.len() * features,
+                    steps.iter().flat_map(|step| step.iter().copied()),
+                );
+
+                let mut inputs = concat(&series[start..start + input_window]);
+                let mut expected_outputs = concat(&series[start + input_window..start + required]);
+
+                if self.normalize {
+                    normalize_window(&mut inputs, &mut expected_outputs, features);
+                }
+
+                Sample::new(inputs, expected_outputs)
+            })
+            .collect();
+
+        Ok(samples)
+    }
+}
+
+/// Normalizes both by the per feature statistics of `inputs`, whose steps are `features` values each.
+fn normalize_window(inputs: &mut DVector<f32>, expected_outputs: &mut DVector<f32>, features: usize) {
+    for feature in 0..features {
+        let values = || inputs.iter().skip(feature).step_by(features);
+        let count = values().count() as f32;
+        let mean = values().sum::<f32>() / count;
+        let std = (values().map(|x| (x - mean) * (x - mean)).sum::<f32>() / count).sqrt();
+        let scale = if std > 0.0 { 1.0 / std } else { 1.0 };
+
+        for vector in [&mut *inputs, &mut *expected_outputs] {
+            for x in vector.iter_mut().skip(feature).step_by(features) {
+                *x = (*x - mean) * scale;
+            }
+        }
+    }
+}
+
+/// `WindowOptions::windowed` with the default options.
+pub fn windowed(series: &[f32], input_window: usize, horizon: usize) -> Result<Vec<Sample>, DatasetError> {
+    WindowOptions::new().windowed(series, input_window, horizon)
+}
+
+/// `WindowOptions::windowed_multivariate` with the default options.
+pub fn windowed_multivariate(series: &[DVector<f32>], input_window: usize, horizon: usize) -> Result<Vec<Sample>, DatasetError> {
+    WindowOptions::new().windowed_multivariate(series, input_window, horizon)
+}
@@ -0,0 +1,224 @@
+//! C interface for loading networks saved with `Network::save` and running them. Every function returns a
+//! `NeuralStatus`, and on failure `neural_last_error_message` describes what went wrong. Panics are caught
+//! and reported as `NeuralStatus::Panic`, they never unwind into the caller.
+//!
+//! Build a shared library with `cargo rustc --release --features ffi --crate-type cdylib`.
+
+use std::{
+    any::Any,
+    cell::RefCell,
+    ffi::{c_char, CStr, CString},
+    panic::{self, AssertUnwindSafe},
+    ptr,
+    slice,
+};
+
+use nalgebra::DVectorView;
+use thiserror::Error;
+
+use crate::network::{Network, NetworkError, NetworkLoadError};
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NeuralStatus {
+    Ok = 0,
+    /// A handle or buffer pointer was null.
+    NullPointer = 1,
+    /// The path isn't valid UTF-8.
+    InvalidPath = 2,
+    /// The model file couldn't be read or isn't a valid saved network.
+    LoadFailed = 3,
+    /// The input buffer length isn't the network's input size.
+    InputSizeMismatch = 4,
+    /// The output buffer length isn't the network's output size.
+    OutputSizeMismatch = 5,
+    /// The network failed to run, e.g. it produced an error for the given inputs.
+    PredictionFailed = 6,
+    /// The call panicked.
+    Panic = 7,
+}
+
+#[derive(Debug, Error)]
+enum FfiError {
+    #[error("{0} is null")]
+    NullPointer(&'static str),
+
+    #[error("the path isn't valid UTF-8")]
+    InvalidPath,
+
+    #[error("couldn't load the network: {0}")]
+    LoadFailed(#[from] NetworkLoadError),
+
+    #[error("{given} inputs were given, but the network takes {expected}")]
+    InputSizeMismatch {
+        given: usize,
+        expected: usize,
+    },
+
+    #[error("the output buffer holds {given} values, but the network gives {expected}")]
+    OutputSizeMismatch {
+        given: usize,
+        expected: usize,
+    },
+
+    #[error("{0}")]
+    PredictionFailed(#[from] NetworkError),
+
+    #[error("panicked: {0}")]
+    Panic(String),
+}
+
+impl FfiError {
+    fn status(&self) -> NeuralStatus {
+        match self {
+            FfiError::NullPointer(_) => NeuralStatus::NullPointer,
+            FfiError::InvalidPath => NeuralStatus::InvalidPath,
+            FfiError::LoadFailed(_) => NeuralStatus::LoadFailed,
+            FfiError::InputSizeMismatch { .. } => NeuralStatus::InputSizeMismatch,
+            FfiError::OutputSizeMismatch { .. } => NeuralStatus::OutputSizeMismatch,
+            FfiError::PredictionFailed(_) => NeuralStatus::PredictionFailed,
+            FfiError::Panic(_) => NeuralStatus::Panic,
+        }
+    }
+}
+
+thread_local! {
+    static LAST_ERROR: RefCell<CString> = RefCell::new(CString::default());
+}
+
+/// Runs `f` without letting a panic escape, recording the error message of a failure for this thread.
+fn guard(f: impl FnOnce() -> Result<(), FfiError>) -> NeuralStatus {
+    let result = panic::catch_unwind(AssertUnwindSafe(f)).unwrap_or_else(|payload| Err(FfiError::Panic(panic_message(payload))));
+
+    let (status, message) = match result {
+        Ok(()) => (NeuralStatus::Ok, String::new()),
+        Err(error) => (error.status(), error.to_string()),
+    };
+
+    // A panic message can hold nul bytes, which C strings can't.
+    let message = CString::new(message.replace('\0', "")).unwrap_or_default();
+    LAST_ERROR.with(|last_error| *last_error.borrow_mut() = message);
+    status
+}
+
+fn panic_message(payload: Box<dyn Any + Send>) -> String {
+    payload
+        .downcast_ref::<&str>()
+        .map(|message| message.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "unknown panic".to_string())
+}
+
+/// # Safety
+///
+/// `handle` has to be null or come from `neural_network_load` without having been freed.
+unsafe fn network_ref<'a>(handle: *const Network) -> Result<&'a Network, FfiError> {
+    unsafe { handle.as_ref() }.ok_or(FfiError::NullPointer("the network handle"))
+}
+
+/// The message of this thread's last failed call, an empty string if the last call succeeded.
+/// The pointer is valid until the next call on the same thread.
+#[unsafe(no_mangle)]
+pub extern "C" fn neural_last_error_message() -> *const c_char {
+    LAST_ERROR.with(|last_error| last_error.borrow().as_ptr())
+}
+
+/// Loads a network saved with `Network::save` and stores its handle in `*network`, which has to be
+/// released with `neural_network_free`. On failure `*network` is set to null.
+///
+/// # Safety
+///
+/// `path` has to be null or a nul-terminated string, and `network` null or valid for writes.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn neural_network_load(path: *const c_char, network: *mut *mut Network) -> NeuralStatus {
+    guard(|| {
+        let network = unsafe { network.as_mut() }.ok_or(FfiError::NullPointer("the handle output"))?;
+        *network = ptr::null_mut();
+
+        if path.is_null() {
+            return Err(FfiError::NullPointer("the path"));
+        }
+
+        let path = unsafe { CStr::from_ptr(path) }.to_str().map_err(|_| FfiError::InvalidPath)?;
+        *network = Box::into_raw(Box::new(Network::load(path)?));
+        Ok(())
+    })
+}
+
+/// Releases a network handle, null is ignored. Dropping runs the `Drop` code of every layer, custom ones
+/// included, so it's guarded like every other call.
+///
+/// # Safety
+///
+/// `network` has to be null or come from `neural_network_load` without having been freed.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn neural_network_free(network: *mut Network) -> NeuralStatus {
+    guard(|| {
+        if !network.is_null() {
+            drop(unsafe { Box::from_raw(network) });
+        }
+
+        Ok(())
+    })
+}
+
+/// Stores the network's input and output sizes in `*input_size` and `*output_size`.
+///
+/// # Safety
+///
+/// `network` has to be a valid handle or null, `input_size` and `output_size` null or valid for writes.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn neural_network_sizes(network: *const Network, input_size: *mut usize, output_size: *mut usize) -> NeuralStatus {
+    guard(|| {
+        let network = unsafe { network_ref(network) }?;
+        let input_size = unsafe { input_size.as_mut() }.ok_or(FfiError::NullPointer("the input size output"))?;
+        let output_size = unsafe { output_size.as_mut() }.ok_or(FfiError::NullPointer("the output size output"))?;
+
+        *input_size = network.input_size();
+        *output_size = network.output_size();
+        Ok(())
+    })
+}
+
+/// Runs `inputs` through the network and writes its outputs to `outputs`. The lengths have to be exactly
+/// the network's input and output sizes, otherwise nothing is written.
+///
+/// # Safety
+///
+/// `network` has to be a valid handle or null, `inputs` null or valid for reading `in_len` floats and
+/// `outputs` null or valid for writing `out_len` floats.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn neural_predict(
+    network: *const Network,
+    inputs: *const f32,
+    in_len: usize,
+    outputs: *mut f32,
+    out_len: usize,
+) -> NeuralStatus {
+    guard(|| {
+        let network = unsafe { network_ref(network) }?;
+
+        if inputs.is_null() {
+            return Err(FfiError::NullPointer("the input buffer"));
+        }
+
+        if outputs.is_null() {
+            return Err(FfiError::NullPointer("the output buffer"));
+        }
+
+        if in_len != network.input_size() {
+            return Err(FfiError::InputSizeMismatch { given: in_len, expected: network.input_size() });
+        }
+
+        if out_len != network.output_size() {
+            return Err(FfiError::OutputSizeMismatch { given: out_len, expected: network.output_size() });
+        }
+
+        let inputs = unsafe { slice::from_raw_parts(inputs, in_len) };
+        let outputs = unsafe { slice::from_raw_parts_mut(outputs, out_len) };
+
+        let prediction = network.predict(DVectorView::from_slice(inputs, in_len))?;
+        outputs.copy_from_slice(prediction.as_slice());
+        Ok(())
+    })
+}
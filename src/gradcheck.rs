@@ -0,0 +1,140 @@
+use crate::{
+    dataset::Sample,
+    losses::LossFn,
+    network::{Network, NetworkError},
+    scalar::Scalar,
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Parameter {
+    Weight { input: usize, output: usize },
+    Bias { output: usize },
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct ParameterError<T: Scalar = f32> {
+    pub layer: usize,
+    pub parameter: Parameter,
+    pub analytic: T,
+    pub numeric: T,
+    pub relative_error: T,
+}
+
+#[derive(Clone, Debug)]
+pub struct GradientCheck<T: Scalar = f32> {
+    pub max_relative_error: T,
+    pub worst: Option<ParameterError<T>>,
+}
+
+impl<T: Scalar> GradientCheck<T> {
+    pub fn passed(&self, tolerance: T) -> bool {
+        self.max_relative_error <= tolerance
+    }
+}
+
+/// Compares the gradient `backpropagate` computes for one sample against central differences,
+/// perturbing every weight and bias by `epsilon`. The relative error of a parameter is
+/// `|analytic - numeric| / max(|analytic| + |numeric|, 1)`, so it turns into the absolute error for
+/// gradients too small for central differences to resolve relatively.
+///
+/// Gradients already accumulated in the network are discarded, and the parameters are left unchanged.
+/// Batch normalized layers normalize single samples differently in training and evaluation, so
+/// networks using them won't pass. Only networks of dense layers can be checked, others fail with `NotDense`.
+pub fn check_gradients<T: Scalar>(
+    network: &mut Network<T>,
+    sample: &Sample<T>,
+    loss: &impl LossFn<T>,
+    epsilon: T,
+) -> Result<GradientCheck<T>, NetworkError> {
+    let dataset = std::slice::from_ref(sample);
+
+    network.check_dense()?;
+    network.zero_gradients();
+    network.backpropagate(dataset, loss)?;
+
+    let mut analytic = Vec::new();
+    for layer in network.dense_layers() {
+        let mut gradients = Vec::with_capacity((layer.input_size() + 1) * layer.output_size());
+
+        for output in 0..layer.output_size() {
+            for input in 0..layer.input_size() {
+                gradients.push(*layer.get_weight_gradient(input, output).unwrap());
+            }
+
+            gradients.push(*layer.get_bias_gradient(output).unwrap());
+        }
+
+        analytic.push(gradients);
+    }
+
+    network.zero_gradients();
+
+    let mut check = GradientCheck {
+        max_relative_error: T::zero(),
+        worst: None,
+    };
+
+    for (layer_index, gradients) in analytic.iter().enumerate() {
+        let layer = network.dense_layer(layer_index).unwrap();
+        let (input_size, output_size) = (layer.input_size(), layer.output_size());
+        let has_bias = layer.has_bias();
+
+        for output in 0..output_size {
+            // The bias gradient of a layer without biases stays zero and isn't checked.
+            for input in 0..input_size + usize::from(has_bias) {
+                let parameter = if input < input_size {
+                    Parameter::Weight { input, output }
+                } else {
+                    Parameter::Bias { output }
+                };
+
+                let numeric = numeric_gradient(network, layer_index, parameter, dataset, loss, epsilon)?;
+                let analytic = gradients[output * (input_size + 1) + input];
+                let relative_error = (analytic - numeric).abs() / (analytic.abs() + numeric.abs()).max(T::one());
+
+                if check.worst.is_none() || relative_error > check.max_relative_error || !relative_error.is_finite() {
+                    check.max_relative_error = relative_error;
+                    check.worst = Some(ParameterError {
+                        layer: layer_index,
+                        parameter,
+                        analytic,
+                        numeric,
+                        relative_error,
+                    });
+                }
+            }
+        }
+    }
+
+    Ok(check)
+}
+
+fn numeric_gradient<T: Scalar>(
+    network: &mut Network<T>,
+    layer: usize,
+    parameter: Parameter,
+    dataset: &[Sample<T>],
+    loss: &impl LossFn<T>,
+    epsilon: T,
+) -> Result<T, NetworkError> {
+    let original = *parameter_mut(network, layer, parameter);
+
+    *parameter_mut(network, layer, parameter) = original + epsilon;
+    let plus = network.evaluate(dataset, loss);
+
+    *parameter_mut(network, layer, parameter) = original - epsilon;
+    let minus = network.evaluate(dataset, loss);
+
+    *parameter_mut(network, layer, parameter) = original;
+
+    Ok((plus? - minus?) / (T::constant(2.0) * epsilon))
+}
+
+fn parameter_mut<T: Scalar>(network: &mut Network<T>, layer: usize, parameter: Parameter) -> &mut T {
+    let layer = network.dense_layer_mut(layer).unwrap();
+
+    match parameter {
+        Parameter::Weight { input, output } => layer.get_weight_mut(input, output).unwrap(),
+        Parameter::Bias { output } => layer.get_bias_mut(output).unwrap(),
+    }
+}
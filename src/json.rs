@@ -0,0 +1,382 @@
+//! A minimal JSON reader and writer for the crate's file formats, so they don't pull in a serialization framework.
+//! The `serde` feature derives `Serialize` and `Deserialize` for samples and reports on top, but the model, dataset
+//! and pipeline files, safetensors headers and Keras imports are read and written here with or without it.
+
+use core::fmt::Write;
+
+use thiserror::Error;
+
+use crate::prelude::*;
+
+#[derive(Debug, Error)]
+pub enum JsonError {
+    #[error("invalid JSON at byte {position}: {message}")]
+    Syntax {
+        position: usize,
+        message: &'static str,
+    },
+
+    #[error("expected {expected} at {path}")]
+    UnexpectedValue {
+        path: String,
+        expected: &'static str,
+    },
+
+    #[error("missing field {field:?} at {path}")]
+    MissingField {
+        path: String,
+        field: &'static str,
+    },
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum Value {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<Value>),
+    Object(Vec<(String, Value)>),
+}
+
+impl Value {
+    pub(crate) fn object(fields: impl IntoIterator<Item = (&'static str, Value)>) -> Self {
+        Value::Object(fields.into_iter().map(|(name, value)| (name.to_string(), value)).collect())
+    }
+
+    /// Non-finite numbers have no JSON representation and are written as `null`.
+    pub(crate) fn numbers(values: impl IntoIterator<Item = f32>) -> Self {
+        Value::Array(values.into_iter().map(|x| Value::Number(x as f64)).collect())
+    }
+
+    /// Adds a field to an object, other values are left unchanged.
+    pub(crate) fn insert(&mut self, name: &str, value: Value) {
+        if let Value::Object(fields) = self {
+            fields.push((name.to_string(), value));
+        }
+    }
+
+    pub(crate) fn optional_field(&self, path: &str, field: &'static str) -> Result<Option<&Value>, JsonError> {
+        let Value::Object(fields) = self else {
+            return Err(unexpected(path, "an object"));
+        };
+
+        Ok(fields.iter().find(|(name, _)| name == field).map(|(_, value)| value))
+    }
+
+    pub(crate) fn field(&self, path: &str, field: &'static str) -> Result<&Value, JsonError> {
+        self.optional_field(path, field)?.ok_or_else(|| JsonError::MissingField {
+            path: path.to_string(),
+            field,
+        })
+    }
+
+    pub(crate) fn as_array(&self, path: &str) -> Result<&[Value], JsonError> {
+        match self {
+            Value::Array(values) => Ok(values),
+            _ => Err(unexpected(path, "an array")),
+        }
+    }
+
+    #[cfg(feature = "std")]
+    pub(crate) fn as_bool(&self, path: &str) -> Result<bool, JsonError> {
+        match self {
+            Value::Bool(value) => Ok(*value),
+            _ => Err(unexpected(path, "a boolean")),
+        }
+    }
+
+    pub(crate) fn as_f32(&self, path: &str) -> Result<f32, JsonError> {
+        match self {
+            Value::Number(x) => Ok(*x as f32),
+            _ => Err(unexpected(path, "a number")),
+        }
+    }
+
+    pub(crate) fn as_usize(&self, path: &str) -> Result<usize, JsonError> {
+        match self {
+            Value::Number(x) if *x >= 0.0 && x.fract() == 0.0 => Ok(*x as usize),
+            _ => Err(unexpected(path, "a non-negative integer")),
+        }
+    }
+
+    pub(crate) fn as_str(&self, path: &str) -> Result<&str, JsonError> {
+        match self {
+            Value::String(string) => Ok(string),
+            _ => Err(unexpected(path, "a string")),
+        }
+    }
+
+    pub(crate) fn as_f32_vec(&self, path: &str) -> Result<Vec<f32>, JsonError> {
+        self.as_array(path)?
+            .iter()
+            .enumerate()
+            .map(|(i, value)| value.as_f32(&format!("{path}[{i}]")))
+            .collect()
+    }
+
+    /// Fails on arrays and objects nested deeper than `MAX_DEPTH`, so crafted input can't overflow the stack.
+    pub(crate) fn parse(text: &str) -> Result<Self, JsonError> {
+        let mut parser = Parser { bytes: text.as_bytes(), position: 0, depth: 0 };
+        let value = parser.value()?;
+        parser.skip_whitespace();
+
+        if parser.position != parser.bytes.len() {
+            return Err(parser.error("trailing characters"));
+        }
+
+        Ok(value)
+    }
+}
+
+impl core::fmt::Display for Value {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Value::Null => f.write_str("null"),
+            Value::Bool(value) => write!(f, "{value}"),
+            Value::Number(x) if x.is_finite() && *x as f32 as f64 == *x => write!(f, "{}", *x as f32),
+            Value::Number(x) if x.is_finite() => write!(f, "{x}"),
+            Value::Number(_) => f.write_str("null"),
+            Value::String(string) => write_string(f, string),
+            Value::Array(values) => {
+                f.write_char('[')?;
+                for (i, value) in values.iter().enumerate() {
+                    if i > 0 {
+                        f.write_char(',')?;
+                    }
+                    write!(f, "{value}")?;
+                }
+                f.write_char(']')
+            }
+            Value::Object(fields) => {
+                f.write_char('{')?;
+                for (i, (name, value)) in fields.iter().enumerate() {
+                    if i > 0 {
+                        f.write_char(',')?;
+                    }
+                    write_string(f, name)?;
+                    write!(f, ":{value}")?;
+                }
+                f.write_char('}')
+            }
+        }
+    }
+}
+
+fn write_string(f: &mut impl Write, string: &str) -> core::fmt::Result {
+    f.write_char('"')?;
+
+    for c in string.chars() {
+        match c {
+            '"' => f.write_str("\\\"")?,
+            '\\' => f.write_str("\\\\")?,
+            '\n' => f.write_str("\\n")?,
+            '\r' => f.write_str("\\r")?,
+            '\t' => f.write_str("\\t")?,
+            c if (c as u32) < 0x20 => write!(f, "\\u{:04x}", c as u32)?,
+            c => f.write_char(c)?,
+        }
+    }
+
+    f.write_char('"')
+}
+
+fn unexpected(path: &str, expected: &'static str) -> JsonError {
+    JsonError::UnexpectedValue {
+        path: path.to_string(),
+        expected,
+    }
+}
+
+/// How deeply arrays and objects can nest, far more than any of the crate's formats need.
+const MAX_DEPTH: usize = 128;
+
+struct Parser<'a> {
+    bytes: &'a [u8],
+    position: usize,
+    depth: usize,
+}
+
+impl Parser<'_> {
+    fn error(&self, message: &'static str) -> JsonError {
+        JsonError::Syntax {
+            position: self.position,
+            message,
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.bytes.get(self.position).is_some_and(u8::is_ascii_whitespace) {
+            self.position += 1;
+        }
+    }
+
+    fn peek(&mut self) -> Option<u8> {
+        self.skip_whitespace();
+        self.bytes.get(self.position).copied()
+    }
+
+    fn expect(&mut self, byte: u8, message: &'static str) -> Result<(), JsonError> {
+        if self.peek() != Some(byte) {
+            return Err(self.error(message));
+        }
+
+        self.position += 1;
+        Ok(())
+    }
+
+    fn keyword(&mut self, keyword: &str, value: Value) -> Result<Value, JsonError> {
+        if !self.bytes[self.position..].starts_with(keyword.as_bytes()) {
+            return Err(self.error("unknown literal"));
+        }
+
+        self.position += keyword.len();
+        Ok(value)
+    }
+
+    fn value(&mut self) -> Result<Value, JsonError> {
+        match self.peek() {
+            Some(b'n') => self.keyword("null", Value::Null),
+            Some(b't') => self.keyword("true", Value::Bool(true)),
+            Some(b'f') => self.keyword("false", Value::Bool(false)),
+            Some(b'"') => self.string().map(Value::String),
+            Some(b'[') => self.nested(Self::array),
+            Some(b'{') => self.nested(Self::object),
+            Some(b'-' | b'0'..=b'9') => self.number(),
+            Some(_) => Err(self.error("unexpected character")),
+            None => Err(self.error("unexpected end of input")),
+        }
+    }
+
+    fn nested(&mut self, parse: fn(&mut Self) -> Result<Value, JsonError>) -> Result<Value, JsonError> {
+        if self.depth == MAX_DEPTH {
+            return Err(self.error("nested too deeply"));
+        }
+
+        self.depth += 1;
+        let value = parse(self);
+        self.depth -= 1;
+
+        value
+    }
+
+    fn number(&mut self) -> Result<Value, JsonError> {
+        let start = self.position;
+
+        while self
+            .bytes
+            .get(self.position)
+            .is_some_and(|b| b.is_ascii_digit() || matches!(b, b'-' | b'+' | b'.' | b'e' | b'E'))
+        {
+            self.position += 1;
+        }
+
+        core::str::from_utf8(&self.bytes[start..self.position])
+            .ok()
+            .and_then(|number| number.parse().ok())
+            .map(Value::Number)
+            .ok_or_else(|| self.error("invalid number"))
+    }
+
+    fn string(&mut self) -> Result<String, JsonError> {
+        self.expect(b'"', "expected a string")?;
+        let mut string = Vec::new();
+
+        loop {
+            let Some(&byte) = self.bytes.get(self.position) else {
+                return Err(self.error("unterminated string"));
+            };
+            self.position += 1;
+
+            match byte {
+                b'"' => break,
+                b'\\' => {
+                    let Some(&escape) = self.bytes.get(self.position) else {
+                        return Err(self.error("unterminated string"));
+                    };
+                    self.position += 1;
+
+                    let c = match escape {
+                        b'"' => '"',
+                        b'\\' => '\\',
+                        b'/' => '/',
+                        b'b' => '\u{8}',
+                        b'f' => '\u{c}',
+                        b'n' => '\n',
+                        b'r' => '\r',
+                        b't' => '\t',
+                        b'u' => self.unicode_escape()?,
+                        _ => return Err(self.error("invalid escape")),
+                    };
+
+                    let mut buffer = [0; 4];
+                    string.extend_from_slice(c.encode_utf8(&mut buffer).as_bytes());
+                }
+                byte => string.push(byte),
+            }
+        }
+
+        String::from_utf8(string).map_err(|_| self.error("invalid UTF-8"))
+    }
+
+    fn unicode_escape(&mut self) -> Result<char, JsonError> {
+        let digits = self
+            .bytes
+            .get(self.position..self.position + 4)
+            .and_then(|digits| core::str::from_utf8(digits).ok())
+            .and_then(|digits| u32::from_str_radix(digits, 16).ok())
+            .ok_or_else(|| self.error("invalid unicode escape"))?;
+
+        self.position += 4;
+        char::from_u32(digits).ok_or_else(|| self.error("unsupported unicode escape"))
+    }
+
+    fn array(&mut self) -> Result<Value, JsonError> {
+        self.expect(b'[', "expected an array")?;
+        let mut values = Vec::new();
+
+        if self.peek() == Some(b']') {
+            self.position += 1;
+            return Ok(Value::Array(values));
+        }
+
+        loop {
+            values.push(self.value()?);
+
+            match self.peek() {
+                Some(b',') => self.position += 1,
+                Some(b']') => {
+                    self.position += 1;
+                    return Ok(Value::Array(values));
+                }
+                _ => return Err(self.error("expected ',' or ']'")),
+            }
+        }
+    }
+
+    fn object(&mut self) -> Result<Value, JsonError> {
+        self.expect(b'{', "expected an object")?;
+        let mut fields = Vec::new();
+
+        if self.peek() == Some(b'}') {
+            self.position += 1;
+            return Ok(Value::Object(fields));
+        }
+
+        loop {
+            self.skip_whitespace();
+            let name = self.string()?;
+            self.expect(b':', "expected ':'")?;
+            fields.push((name, self.value()?));
+
+            match self.peek() {
+                Some(b',') => self.position += 1,
+                Some(b'}') => {
+                    self.position += 1;
+                    return Ok(Value::Object(fields));
+                }
+                _ => return Err(self.error("expected ',' or '}'")),
+            }
+        }
+    }
+}
@@ -1,6 +1,28 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+/// What the std prelude has beyond the core one, for the modules that also compile without std, and the float
+/// methods std has inherently, from nalgebra's libm-backed `ComplexField` without it.
+mod prelude {
+    pub use alloc::{
+        boxed::Box,
+        format,
+        string::{String, ToString},
+        vec,
+        vec::Vec,
+    };
+
+    #[cfg(not(feature = "std"))]
+    pub use nalgebra::ComplexField as _;
+}
+
 #[allow(unused_variables)]
 pub mod network;
 
+#[allow(unused_variables)]
+pub mod scalar;
+
 #[allow(unused_variables)]
 pub mod activations;
 
@@ -8,4 +30,38 @@ pub mod activations;
 pub mod losses;
 
 #[allow(unused_variables)]
-pub mod dataset;
\ No newline at end of file
+pub mod dataset;
+
+#[cfg(feature = "std")]
+#[allow(unused_variables)]
+pub mod training;
+
+#[cfg(feature = "std")]
+#[allow(unused_variables)]
+pub mod gradcheck;
+
+#[cfg(feature = "std")]
+#[allow(unused_variables)]
+pub mod metrics;
+
+#[cfg(feature = "std")]
+#[allow(unused_variables)]
+pub mod pipeline;
+
+#[cfg(feature = "std")]
+#[allow(unused_variables)]
+pub mod classifier;
+
+#[allow(unused_variables)]
+pub mod json;
+
+#[cfg(feature = "serde")]
+mod serde_format;
+
+#[cfg(feature = "std")]
+#[allow(unused_variables)]
+pub mod visualize;
+
+#[cfg(feature = "ffi")]
+#[allow(unused_variables)]
+pub mod ffi;
@@ -1,18 +1,31 @@
 use nalgebra::{DVector, DVectorView};
 use thiserror::Error;
 
-pub trait LossFn {
+use crate::{prelude::*, scalar::Scalar};
+
+pub use multi_head::MultiHeadLoss;
+
+pub mod multi_head;
+
+pub trait LossFn<T: Scalar = f32> {
     fn apply(
         &self,
-        output: DVectorView<f32>,
-        expected_output: DVectorView<f32>,
-    ) -> Result<f32, LossFnError>;
+        output: DVectorView<T>,
+        expected_output: DVectorView<T>,
+    ) -> Result<T, LossFnError>;
 
     fn partial_gradient(
         &self,
-        output: DVectorView<f32>,
-        expected_output: DVectorView<f32>,
-    ) -> Result<DVector<f32>, LossFnError>;
+        output: DVectorView<T>,
+        expected_output: DVectorView<T>,
+    ) -> Result<DVector<T>, LossFnError>;
+
+    /// The `ActivationFn::name` the output layer has to have for this loss, if it only works with one. Layers
+    /// without a single activation function are matched by their `NetworkLayer::kind`, e.g. `"softmax"`.
+    /// Training and evaluation reject networks whose output layer doesn't match.
+    fn output_activation(&self) -> Option<&'static str> {
+        None
+    }
 }
 
 #[derive(Debug, Error)]
@@ -22,13 +35,139 @@ pub enum LossFnError {
         given_output_size: usize,
         expected_output_size: usize,
     },
+
+    #[error("label smoothing has to be in [0, 1), but it's {0}")]
+    InvalidSmoothing(f32),
+
+    #[error("a quantile has to be strictly between 0 and 1, but it's {0}")]
+    InvalidQuantile(f32),
+
+    #[error("the focusing parameter has to be finite and at least 0, but it's {0}")]
+    InvalidFocusing(f32),
+
+    #[error("the weight of the positive class has to be in [0, 1], but it's {0}")]
+    InvalidAlpha(f32),
+
+    #[error("there are {quantiles} quantiles, but {outputs} outputs")]
+    QuantileCountMismatch {
+        quantiles: usize,
+        outputs: usize,
+    },
+
+    #[error("there's already a head named {0:?}")]
+    DuplicateHead(String),
+
+    #[error("head {0:?} has no outputs")]
+    EmptyHead(String),
+
+    #[error("head {head:?} covers outputs {start}..{end}, but there are only {outputs} outputs")]
+    HeadOutOfRange {
+        head: String,
+        start: usize,
+        end: usize,
+        outputs: usize,
+    },
+
+    #[error("head {head:?} overlaps head {other:?}")]
+    OverlappingHeads {
+        head: String,
+        other: String,
+    },
+
+    #[error("head {head:?} has weight {weight}, but weights have to be non-negative and finite")]
+    InvalidHeadWeight {
+        head: String,
+        weight: f32,
+    },
+
+    #[error("head {head:?} requires the output activation {required}, but another head requires {other_required}")]
+    HeadActivationConflict {
+        head: String,
+        required: &'static str,
+        other_required: &'static str,
+    },
+}
+
+/// Why `verify_gradient` failed.
+#[derive(Debug, Error)]
+pub enum GradientMismatch {
+    #[error("{0}")]
+    LossFnError(#[from] LossFnError),
+
+    #[error("the gradient has {gradient_size} components, but the output has {output_size}")]
+    SizeMismatch {
+        gradient_size: usize,
+        output_size: usize,
+    },
+
+    /// The worst component, with the error measured like in `gradcheck`.
+    #[error("the partial derivative for output {index} is {analytic}, but the central difference gives {numeric}")]
+    Component {
+        index: usize,
+        analytic: f32,
+        numeric: f32,
+        relative_error: f32,
+    },
+}
+
+/// Checks a loss function's `partial_gradient` against central differences of `apply`, perturbing every
+/// component of `output` in turn, e.g. in the test suite of a custom loss. The relative error of a component
+/// is `|analytic - numeric| / max(|analytic| + |numeric|, 1)`, and the worst one fails the check if its error
+/// is above `tolerance` or not finite. Errors of the loss itself, like mismatched sizes, are passed on.
+pub fn verify_gradient(
+    loss: &dyn LossFn,
+    output: DVectorView<f32>,
+    expected: DVectorView<f32>,
+    tolerance: f32,
+) -> Result<(), GradientMismatch> {
+    let gradient = loss.partial_gradient(output, expected)?;
+    loss.apply(output, expected)?;
+
+    if gradient.len() != output.len() {
+        return Err(GradientMismatch::SizeMismatch {
+            gradient_size: gradient.len(),
+            output_size: output.len(),
+        });
+    }
+
+    let mut perturbed = output.into_owned();
+    let mut worst: Option<(usize, f32, f32, f32)> = None;
+
+    for (index, &analytic) in gradient.iter().enumerate() {
+        let x = output[index];
+        let step = f32::EPSILON.cbrt() * x.abs().max(1.0);
+
+        perturbed[index] = x + step;
+        let above = loss.apply(perturbed.as_view(), expected)?;
+        perturbed[index] = x - step;
+        let below = loss.apply(perturbed.as_view(), expected)?;
+        perturbed[index] = x;
+
+        let numeric = (above - below) / (2.0 * step);
+        let relative_error = (analytic - numeric).abs() / (analytic.abs() + numeric.abs()).max(1.0);
+        let relative_error = if relative_error.is_nan() { f32::INFINITY } else { relative_error };
+
+        if worst.is_none_or(|(_, _, _, worst_error)| relative_error > worst_error) {
+            worst = Some((index, analytic, numeric, relative_error));
+        }
+    }
+
+    match worst {
+        Some((index, analytic, numeric, relative_error)) if relative_error > tolerance => Err(GradientMismatch::Component {
+            index,
+            analytic,
+            numeric,
+            relative_error,
+        }),
+        _ => Ok(()),
+    }
 }
 
 fn check_sizes(output_size: usize, expected_output_size: usize) -> Result<(), LossFnError> {
     if output_size != expected_output_size {
         return Err(LossFnError::OutputSizeMismatch {
             given_output_size: output_size,
-            expected_output_size: expected_output_size,
+            expected_output_size,
         });
     }
 
@@ -36,32 +175,471 @@ fn check_sizes(output_size: usize, expected_output_size: usize) -> Result<(), Lo
 }
 
 pub struct MSE;
-impl LossFn for MSE {
+impl<T: Scalar> LossFn<T> for MSE {
+    fn apply(
+        &self,
+        output: DVectorView<T>,
+        expected_output: DVectorView<T>,
+    ) -> Result<T, LossFnError> {
+        check_sizes(output.len(), expected_output.len())?;
+
+        Ok(output
+            .iter()
+            .zip(expected_output.iter())
+            .fold(T::zero(), |sum, (&x, &y)| sum + (x - y) * (x - y)) / T::from_count(output.len()))
+    }
+
+    fn partial_gradient(
+        &self,
+        output: DVectorView<T>,
+        expected_output: DVectorView<T>,
+    ) -> Result<DVector<T>, LossFnError> {
+        check_sizes(output.len(), expected_output.len())?;
+
+        Ok(DVector::from_vec(output
+            .iter()
+            .zip(expected_output.iter())
+            .map(|(&x, &y)| T::constant(2.0) * (x - y) / T::from_count(output.len()))
+            .collect()))
+    }
+}
+
+/// Binary cross-entropy of `sigmoid(z)` computed from the logits `z` directly,
+/// `max(z, 0) - z * y + ln(1 + exp(-|z|))`, which stays finite for any logit. The sigmoid is part of the
+/// loss, so the output layer has to be linear (`activations::Identity`), and its outputs are logits.
+pub struct BCEWithLogits;
+impl<T: Scalar> LossFn<T> for BCEWithLogits {
+    fn apply(
+        &self,
+        output: DVectorView<T>,
+        expected_output: DVectorView<T>,
+    ) -> Result<T, LossFnError> {
+        check_sizes(output.len(), expected_output.len())?;
+
+        Ok(output
+            .iter()
+            .zip(expected_output.iter())
+            .fold(T::zero(), |sum, (&z, &y)| sum + z.max(T::zero()) - z * y + (-z.abs()).exp().ln_1p())
+            / T::from_count(output.len()))
+    }
+
+    fn partial_gradient(
+        &self,
+        output: DVectorView<T>,
+        expected_output: DVectorView<T>,
+    ) -> Result<DVector<T>, LossFnError> {
+        check_sizes(output.len(), expected_output.len())?;
+
+        Ok(DVector::from_vec(output
+            .iter()
+            .zip(expected_output.iter())
+            .map(|(&z, &y)| (T::one() / (T::one() + (-z).exp()) - y) / T::from_count(output.len()))
+            .collect()))
+    }
+
+    fn output_activation(&self) -> Option<&'static str> {
+        Some("identity")
+    }
+}
+
+/// Mean of `ln(cosh(x - y))` over the outputs, which is close to `(x - y)^2 / 2` for small errors and to
+/// `|x - y| - ln 2` for large ones, a robust regression loss that's smooth everywhere. It's computed as
+/// `|d| + ln(1 + exp(-2|d|)) - ln 2`, which doesn't overflow for large errors like `cosh` does.
+pub struct LogCosh;
+impl<T: Scalar> LossFn<T> for LogCosh {
+    fn apply(
+        &self,
+        output: DVectorView<T>,
+        expected_output: DVectorView<T>,
+    ) -> Result<T, LossFnError> {
+        check_sizes(output.len(), expected_output.len())?;
+
+        let ln_2 = T::constant(core::f64::consts::LN_2);
+
+        Ok(output
+            .iter()
+            .zip(expected_output.iter())
+            .fold(T::zero(), |sum, (&x, &y)| {
+                let error = (x - y).abs();
+                sum + error + (T::constant(-2.0) * error).exp().ln_1p() - ln_2
+            })
+            / T::from_count(output.len()))
+    }
+
+    fn partial_gradient(
+        &self,
+        output: DVectorView<T>,
+        expected_output: DVectorView<T>,
+    ) -> Result<DVector<T>, LossFnError> {
+        check_sizes(output.len(), expected_output.len())?;
+
+        Ok(DVector::from_vec(output
+            .iter()
+            .zip(expected_output.iter())
+            .map(|(&x, &y)| (x - y).tanh() / T::from_count(output.len()))
+            .collect()))
+    }
+}
+
+/// Pinball loss for predicting quantiles instead of the mean, averaged over the outputs. With `d = y - x`
+/// an output's loss is `q * d` when it's below the target and `(q - 1) * d` when it's above, so an output
+/// trained on it converges to the `q` quantile of the target. The gradient at exactly the target is 0.
+#[derive(Clone, Debug, PartialEq)]
+pub struct QuantileLoss {
+    quantiles: Quantiles,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Quantiles {
+    Shared(f32),
+    PerOutput(Vec<f32>),
+}
+
+impl QuantileLoss {
+    /// The same quantile for every output. Fails unless it's strictly between 0 and 1.
+    pub fn new(quantile: f32) -> Result<Self, LossFnError> {
+        check_quantile(quantile)?;
+
+        Ok(Self { quantiles: Quantiles::Shared(quantile) })
+    }
+
+    /// Output `i` predicts quantile `quantiles[i]`, e.g. `[0.1, 0.5, 0.9]` for an interval around the median.
+    /// Fails unless every quantile is strictly between 0 and 1, and losses of outputs that don't number as
+    /// many as the quantiles fail with `QuantileCountMismatch`.
+    pub fn per_output(quantiles: Vec<f32>) -> Result<Self, LossFnError> {
+        quantiles.iter().try_for_each(|&quantile| check_quantile(quantile))?;
+
+        Ok(Self { quantiles: Quantiles::PerOutput(quantiles) })
+    }
+
+    /// The quantile of every output, `None` if it's shared by all of them.
+    pub fn quantiles(&self) -> Option<&[f32]> {
+        match &self.quantiles {
+            Quantiles::Shared(_) => None,
+            Quantiles::PerOutput(quantiles) => Some(quantiles),
+        }
+    }
+
+    /// The quantile of every output as the scalar type, checking there's one per output.
+    fn output_quantiles<T: Scalar>(&self, outputs: usize) -> Result<Vec<T>, LossFnError> {
+        match &self.quantiles {
+            Quantiles::Shared(quantile) => Ok(vec![T::constant(*quantile as f64); outputs]),
+            Quantiles::PerOutput(quantiles) if quantiles.len() == outputs => {
+                Ok(quantiles.iter().map(|&quantile| T::constant(quantile as f64)).collect())
+            }
+            Quantiles::PerOutput(quantiles) => Err(LossFnError::QuantileCountMismatch {
+                quantiles: quantiles.len(),
+                outputs,
+            }),
+        }
+    }
+}
+
+fn check_quantile(quantile: f32) -> Result<(), LossFnError> {
+    if !(quantile > 0.0 && quantile < 1.0) {
+        return Err(LossFnError::InvalidQuantile(quantile));
+    }
+
+    Ok(())
+}
+
+impl<T: Scalar> LossFn<T> for QuantileLoss {
+    fn apply(
+        &self,
+        output: DVectorView<T>,
+        expected_output: DVectorView<T>,
+    ) -> Result<T, LossFnError> {
+        check_sizes(output.len(), expected_output.len())?;
+        let quantiles = self.output_quantiles::<T>(output.len())?;
+
+        Ok(output
+            .iter()
+            .zip(expected_output.iter())
+            .zip(quantiles)
+            .fold(T::zero(), |sum, ((&x, &y), q)| {
+                let d = y - x;
+                sum + (q * d).max((q - T::one()) * d)
+            })
+            / T::from_count(output.len()))
+    }
+
+    fn partial_gradient(
+        &self,
+        output: DVectorView<T>,
+        expected_output: DVectorView<T>,
+    ) -> Result<DVector<T>, LossFnError> {
+        check_sizes(output.len(), expected_output.len())?;
+        let quantiles = self.output_quantiles::<T>(output.len())?;
+
+        Ok(DVector::from_vec(output
+            .iter()
+            .zip(expected_output.iter())
+            .zip(quantiles)
+            .map(|((&x, &y), q)| {
+                let gradient = if x < y {
+                    -q
+                } else if x > y {
+                    T::one() - q
+                } else {
+                    T::zero()
+                };
+
+                gradient / T::from_count(output.len())
+            })
+            .collect()))
+    }
+}
+
+/// Probabilities are clamped to at least this before taking their logarithm.
+const MIN_PROBABILITY: f64 = 1e-7;
+
+/// Cross-entropy `-sum(y * ln(p))` of a probability distribution `p` from a `Softmax` output layer.
+/// With label smoothing `epsilon` the expected outputs are mixed towards uniform first,
+/// `y' = (1 - epsilon) * y + epsilon / classes`, which keeps one-hot targets from making the network
+/// overconfident without touching the dataset.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct CategoricalCrossEntropy {
+    smoothing: f32,
+}
+
+impl CategoricalCrossEntropy {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Fails unless `epsilon` is in [0, 1).
+    pub fn with_smoothing(epsilon: f32) -> Result<Self, LossFnError> {
+        if !(0.0..1.0).contains(&epsilon) {
+            return Err(LossFnError::InvalidSmoothing(epsilon));
+        }
+
+        Ok(Self { smoothing: epsilon })
+    }
+
+    #[inline]
+    pub fn smoothing(&self) -> f32 { self.smoothing }
+
+    fn smoothed<T: Scalar>(&self, expected_output: T, classes: usize) -> T {
+        let smoothing = T::constant(self.smoothing as f64);
+        (T::one() - smoothing) * expected_output + smoothing / T::from_count(classes)
+    }
+}
+
+impl<T: Scalar> LossFn<T> for CategoricalCrossEntropy {
+    fn apply(
+        &self,
+        output: DVectorView<T>,
+        expected_output: DVectorView<T>,
+    ) -> Result<T, LossFnError> {
+        check_sizes(output.len(), expected_output.len())?;
+
+        Ok(output
+            .iter()
+            .zip(expected_output.iter())
+            .fold(T::zero(), |sum, (&p, &y)| {
+                sum - self.smoothed(y, output.len()) * p.max(T::constant(MIN_PROBABILITY)).ln()
+            }))
+    }
+
+    fn partial_gradient(
+        &self,
+        output: DVectorView<T>,
+        expected_output: DVectorView<T>,
+    ) -> Result<DVector<T>, LossFnError> {
+        check_sizes(output.len(), expected_output.len())?;
+
+        Ok(DVector::from_vec(output
+            .iter()
+            .zip(expected_output.iter())
+            .map(|(&p, &y)| -self.smoothed(y, output.len()) / p.max(T::constant(MIN_PROBABILITY)))
+            .collect()))
+    }
+
+    fn output_activation(&self) -> Option<&'static str> {
+        Some("softmax")
+    }
+}
+
+/// Focal loss for binary outputs `p` in (0, 1), e.g. from a sigmoid output layer, averaged over the outputs:
+/// `-alpha * y * (1 - p)^gamma * ln(p) - (1 - alpha) * (1 - y) * p^gamma * ln(1 - p)`.
+/// The modulating factor with `gamma` > 0 down-weights examples that are already classified well, so
+/// training concentrates on the hard ones. Without `alpha` both classes are weighted 1, and with `gamma`
+/// = 0 it's plain binary cross-entropy. `p` is clamped to 1e-7 away from 0 and 1 to keep the logarithms finite.
+#[derive(Clone, Copy, Debug)]
+pub struct FocalLoss {
+    gamma: f32,
+    alpha: Option<f32>,
+}
+
+impl FocalLoss {
+    /// Fails unless `gamma` is finite and at least 0, and `alpha`, if given, is in [0, 1].
+    pub fn new(gamma: f32, alpha: Option<f32>) -> Result<Self, LossFnError> {
+        if !(gamma.is_finite() && gamma >= 0.0) {
+            return Err(LossFnError::InvalidFocusing(gamma));
+        }
+
+        if let Some(alpha) = alpha.filter(|alpha| !(0.0..=1.0).contains(alpha)) {
+            return Err(LossFnError::InvalidAlpha(alpha));
+        }
+
+        Ok(Self { gamma, alpha })
+    }
+
+    /// The focusing parameter.
+    #[inline]
+    pub fn gamma(&self) -> f32 { self.gamma }
+
+    /// The weight of the positive class, the negative class gets `1 - alpha`.
+    #[inline]
+    pub fn alpha(&self) -> Option<f32> { self.alpha }
+
+    /// `(weight of y = 1, weight of y = 0, gamma, p clamped)`.
+    fn terms<T: Scalar>(&self, p: T) -> (T, T, T, T) {
+        let (positive, negative) = match self.alpha {
+            Some(alpha) => (T::constant(alpha as f64), T::one() - T::constant(alpha as f64)),
+            None => (T::one(), T::one()),
+        };
+
+        let min = T::constant(MIN_PROBABILITY);
+        (positive, negative, T::constant(self.gamma as f64), p.max(min).min(T::one() - min))
+    }
+}
+
+impl<T: Scalar> LossFn<T> for FocalLoss {
     fn apply(
         &self,
-        output: DVectorView<f32>,
-        expected_output: DVectorView<f32>,
-    ) -> Result<f32, LossFnError> {
+        output: DVectorView<T>,
+        expected_output: DVectorView<T>,
+    ) -> Result<T, LossFnError> {
         check_sizes(output.len(), expected_output.len())?;
 
         Ok(output
             .iter()
             .zip(expected_output.iter())
-            .map(|(&x, &y)| (x - y) * (x - y))
-            .sum::<f32>() / output.len() as f32)
+            .fold(T::zero(), |sum, (&p, &y)| {
+                let (positive, negative, gamma, p) = self.terms(p);
+                sum - positive * y * (T::one() - p).powf(gamma) * p.ln()
+                    - negative * (T::one() - y) * p.powf(gamma) * (T::one() - p).ln()
+            })
+            / T::from_count(output.len()))
     }
 
+    /// `d/dp` of both terms: `-alpha * y * (1 - p)^gamma * (1 / p - gamma * ln(p) / (1 - p))` and
+    /// `(1 - alpha) * (1 - y) * p^gamma * (1 / (1 - p) - gamma * ln(1 - p) / p)`.
     fn partial_gradient(
         &self,
-        output: DVectorView<f32>,
-        expected_output: DVectorView<f32>,
-    ) -> Result<DVector<f32>, LossFnError> {
+        output: DVectorView<T>,
+        expected_output: DVectorView<T>,
+    ) -> Result<DVector<T>, LossFnError> {
         check_sizes(output.len(), expected_output.len())?;
 
         Ok(DVector::from_vec(output
             .iter()
             .zip(expected_output.iter())
-            .map(|(x, y)| 2.0 * (x - y) / output.len() as f32)
+            .map(|(&p, &y)| {
+                let (positive, negative, gamma, p) = self.terms(p);
+                let q = T::one() - p;
+
+                let positive_gradient = -positive * y * q.powf(gamma) * (T::one() / p - gamma * p.ln() / q);
+                let negative_gradient = negative * (T::one() - y) * p.powf(gamma) * (T::one() / q - gamma * q.ln() / p);
+                (positive_gradient + negative_gradient) / T::from_count(output.len())
+            })
             .collect()))
     }
-}
\ No newline at end of file
+}
+
+/// Outputs or expected outputs with an L2 norm at or below this have no direction to compare.
+const MIN_NORM: f64 = 1e-8;
+
+/// Cosine distance `1 - cos(x, y)` between the output `x` and the expected output `y`, in [0, 2]. Only the
+/// directions matter, so scaling either vector doesn't change the loss. If either norm is at most 1e-8 the
+/// cosine is taken as 0: the loss is 1 and the gradient is zero instead of NaN.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct CosineLoss;
+
+impl CosineLoss {
+    /// `(x . y, |x|, |y|)`, or `None` if either vector is too short to have a direction.
+    fn terms<T: Scalar>(output: DVectorView<T>, expected_output: DVectorView<T>) -> Option<(T, T, T)> {
+        let (output_norm, expected_norm) = (output.norm(), expected_output.norm());
+        let min = T::constant(MIN_NORM);
+
+        (output_norm > min && expected_norm > min).then(|| (output.dot(&expected_output), output_norm, expected_norm))
+    }
+}
+
+impl<T: Scalar> LossFn<T> for CosineLoss {
+    fn apply(
+        &self,
+        output: DVectorView<T>,
+        expected_output: DVectorView<T>,
+    ) -> Result<T, LossFnError> {
+        check_sizes(output.len(), expected_output.len())?;
+
+        Ok(match Self::terms(output, expected_output) {
+            Some((dot, output_norm, expected_norm)) => T::one() - dot / (output_norm * expected_norm),
+            None => T::one(),
+        })
+    }
+
+    /// `-(y / (|x| |y|) - (x . y) x / (|x|^3 |y|))`, the quotient rule on the cosine.
+    fn partial_gradient(
+        &self,
+        output: DVectorView<T>,
+        expected_output: DVectorView<T>,
+    ) -> Result<DVector<T>, LossFnError> {
+        check_sizes(output.len(), expected_output.len())?;
+
+        Ok(match Self::terms(output, expected_output) {
+            Some((dot, output_norm, expected_norm)) => {
+                let scale = output_norm * expected_norm;
+                output * (dot / (scale * output_norm * output_norm)) - expected_output / scale
+            }
+            None => DVector::zeros(output.len()),
+        })
+    }
+}
+
+/// Predicted rates are clamped to at least this before dividing by them or taking their logarithm.
+const MIN_RATE: f64 = 1e-7;
+
+/// Poisson negative log-likelihood `mean(x - y * ln(x))` for count targets `y`, with the output `x` as the
+/// predicted rate. Dropping the `ln(y!)` term, which doesn't depend on the output, means the loss can be
+/// negative. Rates are only meaningful when positive, so the output layer should have a positive activation
+/// like softplus or exp. Outputs below 1e-7 are clamped to it in the logarithm and in the gradient, so a
+/// zero or negative output gives a large but finite loss instead of NaN or infinity.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct PoissonLoss;
+
+impl<T: Scalar> LossFn<T> for PoissonLoss {
+    fn apply(
+        &self,
+        output: DVectorView<T>,
+        expected_output: DVectorView<T>,
+    ) -> Result<T, LossFnError> {
+        check_sizes(output.len(), expected_output.len())?;
+
+        let min = T::constant(MIN_RATE);
+
+        Ok(output
+            .iter()
+            .zip(expected_output.iter())
+            .fold(T::zero(), |sum, (&x, &y)| sum + x - y * x.max(min).ln())
+            / T::from_count(output.len()))
+    }
+
+    fn partial_gradient(
+        &self,
+        output: DVectorView<T>,
+        expected_output: DVectorView<T>,
+    ) -> Result<DVector<T>, LossFnError> {
+        check_sizes(output.len(), expected_output.len())?;
+
+        let min = T::constant(MIN_RATE);
+
+        Ok(DVector::from_vec(output
+            .iter()
+            .zip(expected_output.iter())
+            .map(|(&x, &y)| (T::one() - y / x.max(min)) / T::from_count(output.len()))
+            .collect()))
+    }
+}
@@ -0,0 +1,172 @@
+//! Several losses on one output vector: every head is a contiguous slice of the output layer's outputs
+//! with its own loss function and weight, e.g. a class head trained with `BCEWithLogits` next to a
+//! regression head trained with `MSE`.
+
+use core::ops::Range;
+
+use nalgebra::{DVector, DVectorView};
+
+use crate::{prelude::*, scalar::Scalar};
+
+use super::{check_sizes, LossFn, LossFnError};
+
+struct Head<T: Scalar> {
+    name: String,
+    range: Range<usize>,
+    loss: Box<dyn LossFn<T> + Send + Sync>,
+    weight: f32,
+}
+
+/// The weighted sum of the heads' losses, each taken over its own slice of the outputs. The gradient is
+/// assembled by writing every head's weighted partial gradient into its slice, outputs outside of every head
+/// get a zero gradient. As a `LossFn` it works with `Network::learn`, `Trainer` and everything else that
+/// takes a loss, and `Network::learn_multi_head` additionally checks the heads against the network.
+///
+/// Heads are validated when they're added, so they never overlap or reach past `output_size`. The output
+/// layer only has one activation function, so heads whose losses require one have to agree on it.
+pub struct MultiHeadLoss<T: Scalar = f32> {
+    output_size: usize,
+    heads: Vec<Head<T>>,
+}
+
+impl<T: Scalar> MultiHeadLoss<T> {
+    /// No heads yet, for outputs of `output_size` values.
+    pub fn new(output_size: usize) -> Self {
+        Self { output_size, heads: Vec::new() }
+    }
+
+    /// Adds a head named `name` over the outputs in `range`, whose loss is scaled by `weight`. Fails if the
+    /// name is taken, the range is empty, reaches past the outputs or overlaps another head's, the weight
+    /// is negative or not finite, or the loss requires another output activation than an earlier head's.
+    pub fn head(
+        mut self,
+        name: impl Into<String>,
+        range: Range<usize>,
+        loss: impl LossFn<T> + Send + Sync + 'static,
+        weight: f32,
+    ) -> Result<Self, LossFnError> {
+        let name = name.into();
+
+        if self.heads.iter().any(|head| head.name == name) {
+            return Err(LossFnError::DuplicateHead(name));
+        }
+
+        if range.is_empty() {
+            return Err(LossFnError::EmptyHead(name));
+        }
+
+        if range.end > self.output_size {
+            return Err(LossFnError::HeadOutOfRange {
+                head: name,
+                start: range.start,
+                end: range.end,
+                outputs: self.output_size,
+            });
+        }
+
+        if let Some(other) = self.heads.iter().find(|head| range.start < head.range.end && head.range.start < range.end) {
+            return Err(LossFnError::OverlappingHeads {
+                head: name,
+                other: other.name.clone(),
+            });
+        }
+
+        if !(weight >= 0.0 && weight.is_finite()) {
+            return Err(LossFnError::InvalidHeadWeight { head: name, weight });
+        }
+
+        if let (Some(required), Some(other_required)) = (loss.output_activation(), self.output_activation())
+            && required != other_required
+        {
+            return Err(LossFnError::HeadActivationConflict {
+                head: name,
+                required,
+                other_required,
+            });
+        }
+
+        self.heads.push(Head {
+            name,
+            range,
+            loss: Box::new(loss),
+            weight,
+        });
+
+        Ok(self)
+    }
+
+    #[inline]
+    pub fn output_size(&self) -> usize {
+        self.output_size
+    }
+
+    /// The name, output range and weight of every head, in the order they were added.
+    pub fn heads(&self) -> impl Iterator<Item = (&str, Range<usize>, f32)> {
+        self.heads.iter().map(|head| (head.name.as_str(), head.range.clone(), head.weight))
+    }
+
+    /// Every head's own loss on its slice, unweighted, e.g. to log the heads separately.
+    pub fn head_losses(
+        &self,
+        output: DVectorView<T>,
+        expected_output: DVectorView<T>,
+    ) -> Result<Vec<(&str, T)>, LossFnError> {
+        self.check_output(output.len(), expected_output.len())?;
+
+        self.heads
+            .iter()
+            .map(|head| Ok((head.name.as_str(), head.apply(output, expected_output)?)))
+            .collect()
+    }
+
+    fn check_output(&self, output_size: usize, expected_output_size: usize) -> Result<(), LossFnError> {
+        check_sizes(output_size, expected_output_size)?;
+        check_sizes(output_size, self.output_size)
+    }
+}
+
+impl<T: Scalar> Head<T> {
+    fn apply(&self, output: DVectorView<T>, expected_output: DVectorView<T>) -> Result<T, LossFnError> {
+        let length = self.range.len();
+        self.loss.apply(output.rows(self.range.start, length), expected_output.rows(self.range.start, length))
+    }
+}
+
+impl<T: Scalar> LossFn<T> for MultiHeadLoss<T> {
+    fn apply(
+        &self,
+        output: DVectorView<T>,
+        expected_output: DVectorView<T>,
+    ) -> Result<T, LossFnError> {
+        self.check_output(output.len(), expected_output.len())?;
+
+        self.heads.iter().try_fold(T::zero(), |sum, head| {
+            Ok(sum + T::constant(head.weight as f64) * head.apply(output, expected_output)?)
+        })
+    }
+
+    fn partial_gradient(
+        &self,
+        output: DVectorView<T>,
+        expected_output: DVectorView<T>,
+    ) -> Result<DVector<T>, LossFnError> {
+        self.check_output(output.len(), expected_output.len())?;
+
+        let mut gradient = DVector::zeros(output.len());
+
+        for head in &self.heads {
+            let (start, length) = (head.range.start, head.range.len());
+            let head_gradient = head.loss.partial_gradient(output.rows(start, length), expected_output.rows(start, length))?;
+            check_sizes(head_gradient.len(), length)?;
+
+            gradient.rows_mut(start, length).copy_from(&(head_gradient * T::constant(head.weight as f64)));
+        }
+
+        Ok(gradient)
+    }
+
+    /// The activation the heads' losses require, if any of them does.
+    fn output_activation(&self) -> Option<&'static str> {
+        self.heads.iter().find_map(|head| head.loss.output_activation())
+    }
+}
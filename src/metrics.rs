@@ -0,0 +1,676 @@
+use std::fmt;
+
+use nalgebra::{DMatrix, DVector, DVectorView};
+
+use crate::{
+    activations::ActivationFn,
+    dataset::{self, Sample},
+    losses::LossFn,
+    network::{Network, NetworkError},
+};
+
+/// How outputs and expected outputs are turned into classes.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ClassificationMode {
+    /// One output, class 1 when it's at least the threshold and class 0 otherwise. Expected outputs have to be 0 or 1.
+    Binary { threshold: f32 },
+    /// The class is the index of the largest output. Expected outputs have to have a unique largest value, e.g. one-hot vectors.
+    MultiClass,
+}
+
+impl Default for ClassificationMode {
+    fn default() -> Self {
+        ClassificationMode::Binary { threshold: 0.5 }
+    }
+}
+
+impl ClassificationMode {
+    /// The class a sample is labeled with, `None` if its expected outputs aren't a valid encoding.
+    fn expected_class(&self, expected_outputs: DVectorView<f32>) -> Option<usize> {
+        match self {
+            ClassificationMode::Binary { .. } if expected_outputs.len() != 1 => None,
+            ClassificationMode::Binary { .. } if expected_outputs[0] == 0.0 => Some(0),
+            ClassificationMode::Binary { .. } if expected_outputs[0] == 1.0 => Some(1),
+            ClassificationMode::Binary { .. } => None,
+            ClassificationMode::MultiClass => {
+                let class = dataset::argmax(expected_outputs);
+                let max = expected_outputs[class];
+                let unique = expected_outputs.iter().filter(|&&x| x == max).count() == 1;
+                unique.then_some(class)
+            }
+        }
+    }
+
+    fn predicted_class(&self, outputs: DVectorView<f32>) -> usize {
+        match self {
+            ClassificationMode::Binary { threshold } => (outputs[0] >= *threshold) as usize,
+            ClassificationMode::MultiClass => dataset::argmax(outputs),
+        }
+    }
+}
+
+/// The expected class and the network's outputs for every sample.
+fn labeled_outputs(network: &mut Network, dataset: &[Sample], mode: ClassificationMode) -> Result<Vec<(usize, DVector<f32>)>, NetworkError> {
+    if dataset.is_empty() {
+        return Err(NetworkError::EmptyDataset);
+    }
+
+    network.check_dataset(dataset)?;
+
+    if matches!(mode, ClassificationMode::Binary { .. }) && network.output_size() != 1 {
+        return Err(NetworkError::NotBinary(network.output_size()));
+    }
+
+    dataset
+        .iter()
+        .enumerate()
+        .map(|(index, sample)| {
+            let expected = mode
+                .expected_class(sample.expected_outputs())
+                .ok_or(NetworkError::InvalidClassEncoding { index })?;
+
+            Ok((expected, network.forward_view(sample.inputs())?))
+        })
+        .collect()
+}
+
+/// The true and predicted class of every sample.
+fn classify(network: &mut Network, dataset: &[Sample], mode: ClassificationMode) -> Result<Vec<(usize, usize)>, NetworkError> {
+    Ok(labeled_outputs(network, dataset, mode)?
+        .into_iter()
+        .map(|(expected, outputs)| (expected, mode.predicted_class(outputs.as_view())))
+        .collect())
+}
+
+/// The fraction of samples whose predicted class is their expected class. Sample weights are ignored.
+pub fn accuracy(network: &mut Network, dataset: &[Sample], mode: ClassificationMode) -> Result<f32, NetworkError> {
+    let classes = classify(network, dataset, mode)?;
+    let correct = classes.iter().filter(|(expected, predicted)| expected == predicted).count();
+
+    Ok(correct as f32 / classes.len() as f32)
+}
+
+/// The fraction of samples whose expected class, the argmax of their expected outputs, is among the `k`
+/// largest outputs. Equal outputs rank by index, the higher one first like in `dataset::argmax`, and NaN
+/// outputs rank last, so `k = 1` matches `accuracy` in multi-class mode.
+pub fn top_k_accuracy(network: &mut Network, dataset: &[Sample], k: usize) -> Result<f32, NetworkError> {
+    if k == 0 || k > network.output_size() {
+        return Err(NetworkError::InvalidTopK {
+            k,
+            network_outputs: network.output_size(),
+        });
+    }
+
+    let samples = labeled_outputs(network, dataset, ClassificationMode::MultiClass)?;
+    let hits = samples
+        .iter()
+        .filter(|(expected, outputs)| {
+            let mut ranking: Vec<usize> = (0..outputs.len()).collect();
+            ranking.sort_by(|&a, &b| match (outputs[a].is_nan(), outputs[b].is_nan()) {
+                (true, false) => std::cmp::Ordering::Greater,
+                (false, true) => std::cmp::Ordering::Less,
+                _ => outputs[b].total_cmp(&outputs[a]).then(b.cmp(&a)),
+            });
+
+            ranking[..k].contains(expected)
+        })
+        .count();
+
+    Ok(hits as f32 / samples.len() as f32)
+}
+
+/// One operating point of a binary classifier: outputs at least `threshold` count as positive.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RocPoint {
+    pub false_positive_rate: f32,
+    pub true_positive_rate: f32,
+    pub threshold: f32,
+}
+
+/// Operating points from the highest threshold to the lowest. The first point has an infinite
+/// threshold and the rates (0, 0), the last one the lowest output as threshold and the rates (1, 1).
+#[derive(Clone, Debug, PartialEq)]
+pub struct RocCurve {
+    pub points: Vec<RocPoint>,
+}
+
+impl RocCurve {
+    /// The area under the curve by the trapezoid rule.
+    pub fn auc(&self) -> f32 {
+        self.points
+            .windows(2)
+            .map(|pair| {
+                let width = pair[1].false_positive_rate - pair[0].false_positive_rate;
+                width * (pair[0].true_positive_rate + pair[1].true_positive_rate) / 2.0
+            })
+            .sum()
+    }
+}
+
+/// The ROC curve of a network with a single output against expected outputs of 0 or 1. Samples with
+/// equal outputs share one point, so ties are counted as half right. Datasets holding only one class
+/// have no curve and give a `SingleClass` error.
+pub fn roc_curve(network: &mut Network, dataset: &[Sample]) -> Result<RocCurve, NetworkError> {
+    let sweep = threshold_counts(network, dataset)?;
+    let points = sweep.counts
+        .iter()
+        .map(|counts| RocPoint {
+            false_positive_rate: counts.false_positives as f32 / sweep.negatives as f32,
+            true_positive_rate: counts.true_positives as f32 / sweep.positives as f32,
+            threshold: counts.threshold,
+        })
+        .collect();
+
+    Ok(RocCurve { points })
+}
+
+/// The samples counted as positive at a threshold.
+struct ThresholdCounts {
+    threshold: f32,
+    true_positives: usize,
+    false_positives: usize,
+}
+
+struct ThresholdSweep {
+    counts: Vec<ThresholdCounts>,
+    positives: usize,
+    negatives: usize,
+}
+
+/// The counts at every distinct output of a network with a single output, from an infinite threshold
+/// down to the lowest output, for datasets holding both classes.
+fn threshold_counts(network: &mut Network, dataset: &[Sample]) -> Result<ThresholdSweep, NetworkError> {
+    let mut samples: Vec<(bool, f32)> = labeled_outputs(network, dataset, ClassificationMode::default())?
+        .into_iter()
+        .map(|(expected, outputs)| (expected == 1, outputs[0]))
+        .collect();
+
+    let positives = samples.iter().filter(|(positive, _)| *positive).count();
+    let negatives = samples.len() - positives;
+
+    if positives == 0 || negatives == 0 {
+        return Err(NetworkError::SingleClass);
+    }
+
+    samples.sort_by(|(_, a), (_, b)| b.total_cmp(a));
+
+    let mut counts = vec![ThresholdCounts {
+        threshold: f32::INFINITY,
+        true_positives: 0,
+        false_positives: 0,
+    }];
+    let (mut true_positives, mut false_positives) = (0, 0);
+
+    for (i, &(positive, score)) in samples.iter().enumerate() {
+        if positive {
+            true_positives += 1;
+        } else {
+            false_positives += 1;
+        }
+
+        if samples.get(i + 1).is_none_or(|&(_, next)| next != score) {
+            counts.push(ThresholdCounts {
+                threshold: score,
+                true_positives,
+                false_positives,
+            });
+        }
+    }
+
+    Ok(ThresholdSweep { counts, positives, negatives })
+}
+
+/// What `best_threshold` maximizes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ThresholdCriterion {
+    /// The harmonic mean of precision and recall.
+    F1,
+    /// Youden's J, the true positive rate minus the false positive rate.
+    YoudenJ,
+    Accuracy,
+}
+
+/// A threshold and the value of the criterion at it.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ThresholdPoint {
+    pub threshold: f32,
+    pub value: f32,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct BestThreshold {
+    pub threshold: f32,
+    pub value: f32,
+    /// Every candidate threshold from the highest to the lowest, the same ones as the points of `roc_curve`.
+    pub curve: Vec<ThresholdPoint>,
+}
+
+/// Sweeps the thresholds of `roc_curve`, every distinct output of the network and an infinite one, and
+/// returns the one where the criterion is highest, the highest of them if several are. Outputs at least
+/// the threshold count as positive, like in `ClassificationMode::Binary`. Datasets holding only one class
+/// give a `SingleClass` error.
+pub fn best_threshold(network: &mut Network, dataset: &[Sample], criterion: ThresholdCriterion) -> Result<BestThreshold, NetworkError> {
+    let sweep = threshold_counts(network, dataset)?;
+    let (positives, negatives) = (sweep.positives as f32, sweep.negatives as f32);
+
+    let curve: Vec<ThresholdPoint> = sweep.counts
+        .iter()
+        .map(|counts| {
+            let (true_positives, false_positives) = (counts.true_positives as f32, counts.false_positives as f32);
+            let value = match criterion {
+                ThresholdCriterion::F1 => 2.0 * true_positives / (true_positives + false_positives + positives),
+                ThresholdCriterion::YoudenJ => true_positives / positives - false_positives / negatives,
+                ThresholdCriterion::Accuracy => (true_positives + negatives - false_positives) / (positives + negatives),
+            };
+
+            ThresholdPoint { threshold: counts.threshold, value }
+        })
+        .collect();
+
+    let best = curve
+        .iter()
+        .fold(curve[0], |best, &point| if point.value > best.value { point } else { best });
+
+    Ok(BestThreshold {
+        threshold: best.threshold,
+        value: best.value,
+        curve,
+    })
+}
+
+/// How much accuracy a network loses when some of its activation functions are replaced, see `activation_swap`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ActivationSwap {
+    /// The number of layers whose activation function was replaced.
+    pub replaced: usize,
+    pub accuracy_before: f32,
+    pub accuracy_after: f32,
+}
+
+impl ActivationSwap {
+    /// The accuracy after the swap minus the accuracy before, negative when the swap costs accuracy.
+    #[inline]
+    pub fn delta(&self) -> f32 {
+        self.accuracy_after - self.accuracy_before
+    }
+}
+
+/// The `accuracy` of the network on `dataset` before and after `Network::replace_activation(name, replacement)`,
+/// e.g. to check whether a sigmoid network keeps its accuracy with `HardSigmoid`. The swap happens on a copy,
+/// the network itself is left as it is.
+pub fn activation_swap(
+    network: &mut Network,
+    dataset: &[Sample],
+    name: &str,
+    replacement: &dyn ActivationFn,
+    mode: ClassificationMode,
+) -> Result<ActivationSwap, NetworkError> {
+    let accuracy_before = accuracy(network, dataset, mode)?;
+
+    let mut swapped = network.clone();
+    let replaced = swapped.replace_activation(name, replacement);
+    let accuracy_after = accuracy(&mut swapped, dataset, mode)?;
+
+    Ok(ActivationSwap {
+        replaced,
+        accuracy_before,
+        accuracy_after,
+    })
+}
+
+/// Counts of samples by true class (rows) and predicted class (columns).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ConfusionMatrix {
+    counts: DMatrix<usize>,
+}
+
+impl ConfusionMatrix {
+    #[inline]
+    pub fn counts(&self) -> &DMatrix<usize> { &self.counts }
+
+    #[inline]
+    pub fn num_classes(&self) -> usize { self.counts.nrows() }
+
+    /// The fraction of samples predicted as `class` that really are, `None` if it was never predicted.
+    pub fn precision(&self, class: usize) -> Option<f32> {
+        let predicted = self.counts.column(class).sum();
+        (predicted > 0).then(|| self.counts[(class, class)] as f32 / predicted as f32)
+    }
+
+    /// The fraction of samples of `class` that were predicted as it, `None` if there are none.
+    pub fn recall(&self, class: usize) -> Option<f32> {
+        let actual = self.counts.row(class).sum();
+        (actual > 0).then(|| self.counts[(class, class)] as f32 / actual as f32)
+    }
+}
+
+impl fmt::Display for ConfusionMatrix {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let label = "true \\ predicted";
+        let width = self.counts
+            .iter()
+            .map(|count| count.to_string().len())
+            .chain(std::iter::once(self.num_classes().saturating_sub(1).to_string().len()))
+            .max()
+            .unwrap_or(1);
+
+        write!(f, "{label}")?;
+        for class in 0..self.num_classes() {
+            write!(f, " {class:>width$}")?;
+        }
+        writeln!(f)?;
+
+        for (class, row) in self.counts.row_iter().enumerate() {
+            write!(f, "{class:>label_width$}", label_width = label.len())?;
+            for count in row.iter() {
+                write!(f, " {count:>width$}")?;
+            }
+            writeln!(f)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Counts every sample by its expected class and the argmax of the network's outputs. Expected outputs
+/// without a unique largest value are reported as `InvalidClassEncoding` instead of being counted.
+pub fn confusion_matrix(network: &mut Network, dataset: &[Sample], num_classes: usize) -> Result<ConfusionMatrix, NetworkError> {
+    if network.output_size() != num_classes {
+        return Err(NetworkError::ClassCountMismatch {
+            num_classes,
+            network_outputs: network.output_size(),
+        });
+    }
+
+    confusion(network, dataset, ClassificationMode::MultiClass)
+}
+
+/// The confusion matrix for either mode, binary classification has the two classes 0 and 1.
+fn confusion(network: &mut Network, dataset: &[Sample], mode: ClassificationMode) -> Result<ConfusionMatrix, NetworkError> {
+    let num_classes = match mode {
+        ClassificationMode::Binary { .. } => 2,
+        ClassificationMode::MultiClass => network.output_size(),
+    };
+
+    let mut counts = DMatrix::zeros(num_classes, num_classes);
+
+    for (expected, predicted) in classify(network, dataset, mode)? {
+        counts[(expected, predicted)] += 1;
+    }
+
+    Ok(ConfusionMatrix { counts })
+}
+
+/// Precision, recall and F1 of one class. A class that's never predicted has a precision of 0, one
+/// without samples a recall of 0, and the F1 is 0 when both are 0; `predicted` and `support` tell these cases apart.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ClassScores {
+    pub precision: f32,
+    pub recall: f32,
+    pub f1: f32,
+    /// Samples predicted as the class.
+    pub predicted: usize,
+    /// Samples of the class.
+    pub support: usize,
+}
+
+/// Per class scores and their unweighted means over all classes.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ClassificationScores {
+    pub classes: Vec<ClassScores>,
+    pub macro_precision: f32,
+    pub macro_recall: f32,
+    pub macro_f1: f32,
+}
+
+/// Precision, recall and F1 of every class, using the same classes as `accuracy` and `confusion_matrix`.
+pub fn precision_recall_f1(network: &mut Network, dataset: &[Sample], mode: ClassificationMode) -> Result<ClassificationScores, NetworkError> {
+    let confusion = confusion(network, dataset, mode)?;
+    let counts = confusion.counts();
+
+    let classes: Vec<ClassScores> = (0..confusion.num_classes())
+        .map(|class| {
+            let precision = confusion.precision(class).unwrap_or(0.0);
+            let recall = confusion.recall(class).unwrap_or(0.0);
+            let f1 = if precision + recall > 0.0 { 2.0 * precision * recall / (precision + recall) } else { 0.0 };
+
+            ClassScores {
+                precision,
+                recall,
+                f1,
+                predicted: counts.column(class).sum(),
+                support: counts.row(class).sum(),
+            }
+        })
+        .collect();
+
+    let mean = |score: fn(&ClassScores) -> f32| classes.iter().map(score).sum::<f32>() / classes.len() as f32;
+
+    Ok(ClassificationScores {
+        macro_precision: mean(|scores| scores.precision),
+        macro_recall: mean(|scores| scores.recall),
+        macro_f1: mean(|scores| scores.f1),
+        classes,
+    })
+}
+
+/// Multi-label scores of a network whose outputs are independent binary labels.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MultilabelReport {
+    /// The scores of every output, with the conventions of `ClassScores` for labels that are never predicted
+    /// or never present.
+    pub labels: Vec<ClassScores>,
+    /// The fraction of all sample and label pairs that are predicted wrong.
+    pub hamming_loss: f32,
+    /// The fraction of samples with every label predicted right.
+    pub subset_accuracy: f32,
+    /// Scores of the counts summed over all labels, so frequent labels weigh more.
+    pub micro_precision: f32,
+    pub micro_recall: f32,
+    pub micro_f1: f32,
+    /// Unweighted means of the labels' scores, counting labels without samples or predictions as 0.
+    pub macro_precision: f32,
+    pub macro_recall: f32,
+    pub macro_f1: f32,
+}
+
+/// Scores every output as its own binary label: an output at least `threshold` predicts the label, and expected
+/// outputs have to be 0 or 1, otherwise the sample is reported as `InvalidClassEncoding`. Sample weights are ignored.
+pub fn multilabel_report(network: &mut Network, dataset: &[Sample], threshold: f32) -> Result<MultilabelReport, NetworkError> {
+    if dataset.is_empty() {
+        return Err(NetworkError::EmptyDataset);
+    }
+
+    network.check_dataset(dataset)?;
+
+    let num_labels = network.output_size();
+    let (mut true_positives, mut predicted, mut support) = (vec![0; num_labels], vec![0; num_labels], vec![0; num_labels]);
+    let (mut wrong_labels, mut exact_matches) = (0, 0);
+
+    for (index, sample) in dataset.iter().enumerate() {
+        let expected = sample.expected_outputs();
+        if expected.iter().any(|&y| y != 0.0 && y != 1.0) {
+            return Err(NetworkError::InvalidClassEncoding { index });
+        }
+
+        let outputs = network.forward_view(sample.inputs())?;
+        let mut wrong = 0;
+
+        for label in 0..num_labels {
+            let (is_predicted, is_expected) = (outputs[label] >= threshold, expected[label] == 1.0);
+
+            predicted[label] += usize::from(is_predicted);
+            support[label] += usize::from(is_expected);
+            true_positives[label] += usize::from(is_predicted && is_expected);
+            wrong += usize::from(is_predicted != is_expected);
+        }
+
+        wrong_labels += wrong;
+        exact_matches += usize::from(wrong == 0);
+    }
+
+    let labels: Vec<ClassScores> = (0..num_labels)
+        .map(|label| class_scores(true_positives[label], predicted[label], support[label]))
+        .collect();
+
+    let micro = class_scores(true_positives.iter().sum(), predicted.iter().sum(), support.iter().sum());
+    let mean = |score: fn(&ClassScores) -> f32| labels.iter().map(score).sum::<f32>() / num_labels as f32;
+
+    Ok(MultilabelReport {
+        hamming_loss: wrong_labels as f32 / (dataset.len() * num_labels) as f32,
+        subset_accuracy: exact_matches as f32 / dataset.len() as f32,
+        micro_precision: micro.precision,
+        micro_recall: micro.recall,
+        micro_f1: micro.f1,
+        macro_precision: mean(|scores| scores.precision),
+        macro_recall: mean(|scores| scores.recall),
+        macro_f1: mean(|scores| scores.f1),
+        labels,
+    })
+}
+
+/// Scores from the counts of one class, with the zero conventions of `ClassScores`.
+fn class_scores(true_positives: usize, predicted: usize, support: usize) -> ClassScores {
+    let ratio = |count: usize, total: usize| if total == 0 { 0.0 } else { count as f32 / total as f32 };
+    let (precision, recall) = (ratio(true_positives, predicted), ratio(true_positives, support));
+    let f1 = if precision + recall > 0.0 { 2.0 * precision * recall / (precision + recall) } else { 0.0 };
+
+    ClassScores {
+        precision,
+        recall,
+        f1,
+        predicted,
+        support,
+    }
+}
+
+/// Errors of one output over a dataset.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct OutputErrors {
+    pub rmse: f32,
+    pub mae: f32,
+    /// The coefficient of determination. For a constant expected output it's 1 if every prediction
+    /// is exact and 0 otherwise.
+    pub r2: f32,
+}
+
+/// Regression errors of every output, and over all of them: RMSE and MAE of all values together,
+/// R² as the mean of the outputs' R².
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RegressionReport {
+    pub outputs: Vec<OutputErrors>,
+    pub rmse: f32,
+    pub mae: f32,
+    pub r2: f32,
+}
+
+impl fmt::Display for RegressionReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{:>6} {:>12} {:>12} {:>12}", "output", "rmse", "mae", "r2")?;
+
+        for (i, errors) in self.outputs.iter().enumerate() {
+            writeln!(f, "{i:>6} {:>12.6} {:>12.6} {:>12.6}", errors.rmse, errors.mae, errors.r2)?;
+        }
+
+        writeln!(f, "{:>6} {:>12.6} {:>12.6} {:>12.6}", "all", self.rmse, self.mae, self.r2)
+    }
+}
+
+/// RMSE, MAE and R² of the network's outputs against the expected outputs. Sample weights are ignored.
+pub fn regression_report(network: &mut Network, dataset: &[Sample]) -> Result<RegressionReport, NetworkError> {
+    if dataset.is_empty() {
+        return Err(NetworkError::EmptyDataset);
+    }
+
+    network.check_dataset(dataset)?;
+
+    let num_outputs = network.output_size();
+    let mut squared_errors = vec![0.0; num_outputs];
+    let mut absolute_errors = vec![0.0; num_outputs];
+    let mut means = vec![0.0; num_outputs];
+
+    for sample in dataset.iter() {
+        let outputs = network.forward_view(sample.inputs())?;
+
+        for (i, (&output, &expected)) in outputs.iter().zip(sample.expected_outputs().iter()).enumerate() {
+            squared_errors[i] += (output - expected) * (output - expected);
+            absolute_errors[i] += (output - expected).abs();
+            means[i] += expected / dataset.len() as f32;
+        }
+    }
+
+    let mut variances = vec![0.0; num_outputs];
+    for sample in dataset.iter() {
+        for (i, &expected) in sample.expected_outputs().iter().enumerate() {
+            variances[i] += (expected - means[i]) * (expected - means[i]);
+        }
+    }
+
+    let n = dataset.len() as f32;
+    let outputs: Vec<OutputErrors> = (0..num_outputs)
+        .map(|i| OutputErrors {
+            rmse: (squared_errors[i] / n).sqrt(),
+            mae: absolute_errors[i] / n,
+            r2: match (variances[i] > 0.0, squared_errors[i] == 0.0) {
+                (true, _) => 1.0 - squared_errors[i] / variances[i],
+                (false, true) => 1.0,
+                (false, false) => 0.0,
+            },
+        })
+        .collect();
+
+    let values = n * num_outputs as f32;
+
+    Ok(RegressionReport {
+        rmse: (squared_errors.iter().sum::<f32>() / values).sqrt(),
+        mae: absolute_errors.iter().sum::<f32>() / values,
+        r2: outputs.iter().map(|errors| errors.r2).sum::<f32>() / num_outputs as f32,
+        outputs,
+    })
+}
+
+#[derive(Clone, Debug)]
+pub struct SampleLoss {
+    pub index: usize,
+    pub loss: f32,
+    /// What the network gave for the sample.
+    pub outputs: DVector<f32>,
+    pub expected_outputs: DVector<f32>,
+    pub tag: Option<String>,
+}
+
+/// The `k` samples with the highest loss, worst first, with their index in the dataset, the network's and
+/// the expected outputs and their tag, e.g. to find mislabeled samples. `k` is clamped to the dataset size,
+/// and NaN losses count as the worst. The outputs come from `predict`, so neither gradients nor the state
+/// recorded by `forward` are touched.
+pub fn worst_samples(
+    network: &mut Network,
+    dataset: &[Sample],
+    loss: &impl LossFn,
+    k: usize,
+) -> Result<Vec<SampleLoss>, NetworkError> {
+    let mut losses = Vec::with_capacity(dataset.len());
+
+    for (index, sample) in dataset.iter().enumerate() {
+        let outputs = network.predict(sample.inputs())?;
+
+        losses.push(SampleLoss {
+            index,
+            loss: loss.apply(outputs.as_view(), sample.expected_outputs())?,
+            outputs,
+            expected_outputs: sample.expected_outputs().into_owned(),
+            tag: sample.tag().map(str::to_string),
+        });
+    }
+
+    losses.sort_by(|a, b| match (a.loss.is_nan(), b.loss.is_nan()) {
+        (true, false) => std::cmp::Ordering::Less,
+        (false, true) => std::cmp::Ordering::Greater,
+        _ => b.loss.total_cmp(&a.loss),
+    });
+    losses.truncate(k);
+
+    Ok(losses)
+}
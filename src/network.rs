@@ -1,24 +1,138 @@
-use nalgebra::{DVector};
-use rand::{distr::Distribution};
+use core::{borrow::Borrow, ops::Range};
+
+use nalgebra::{DMatrix, DMatrixView, DVector, DVectorView};
+use rand::{distr::Distribution, Rng, RngCore};
 use thiserror::Error;
 
 use crate::{
     activations::ActivationFn,
-    dataset::Sample,
-    losses::{self, LossFn},
+    dataset::{BatchView, Sample},
+    losses::{self, LossFn, MultiHeadLoss},
+    prelude::*,
+    scalar::Scalar,
 };
 
-use layer::{Layer, LayerError};
+use layer::{Layer, LayerError, LayerParameters};
+use network_layer::{backpropagate_chain, forward_training_chain};
+
+pub use activation_stats::{LayerActivationStats, NeuronActivationStats, SaturationThresholds};
+pub use autoencoder::Autoencoder;
+#[cfg(feature = "std")]
+pub use binary::{LoadReport, Migration};
+pub use builder::NetworkBuilder;
+pub use compare::{LayerDiff, NetworkDiff};
+pub use constraint::WeightConstraint;
+pub use conv::{Conv2D, Convolution, ImageShape};
+pub use dot::DotOptions;
+pub use ema::EmaWeights;
+pub use embedding::Embedding;
+pub use ensemble::Ensemble;
+pub use gru::{Gate, GruLayer};
+pub use gradients::{GradientHealth, GradientThresholds, LayerGradientNorm};
+pub use initializer::{DynDistribution, Initializer, Normal};
+pub use lookahead::Lookahead;
+pub use maxout::MaxoutLayer;
+pub use network_layer::NetworkLayer;
+pub use optimizer::{Optimizer, OptimizerState, Sgd};
+pub use precision::Precision;
+pub use pruning::{LayerPruneReport, PruneReport};
+pub use quantize::{QuantizationReport, QuantizedLayer, QuantizedNetwork};
+pub use recurrent::{RecurrentLayer, RecurrentNetwork, SequenceLayer};
+pub use residual::ResidualBlock;
+pub use rprop::RProp;
+pub use scratch::NetworkScratch;
+pub use softmax::Softmax;
+pub use sparse::SparseSample;
+pub use serialization::NetworkLoadError;
+pub use static_layer::{StaticForward, StaticLayer};
 
+pub mod activation_stats;
+pub mod architecture;
+pub mod autoencoder;
+pub mod batch_norm;
+#[cfg(feature = "std")]
+pub mod binary;
+pub mod builder;
+pub mod compare;
+pub mod constraint;
+pub mod conv;
+pub mod dot;
+pub mod ema;
+pub mod embedding;
+pub mod ensemble;
+pub mod gradients;
+pub mod gru;
+pub mod import;
+pub mod initializer;
+pub mod jacobian;
+pub mod json;
 pub mod layer;
+pub mod lookahead;
+pub mod maxout;
+pub mod merge;
+pub mod network_layer;
+#[cfg(feature = "npz")]
+pub mod npz;
+#[cfg(feature = "onnx")]
+pub mod onnx;
+pub mod optimizer;
+pub mod precision;
+pub mod pruning;
+pub mod quantize;
+pub mod recurrent;
+pub mod residual;
+pub mod rprop;
+#[cfg(feature = "safetensors")]
+pub mod safetensors;
+pub mod scratch;
+pub mod serialization;
+pub mod sharing;
+pub mod softmax;
+pub mod sparse;
+pub mod static_layer;
+pub mod summary;
+
+/// The most samples `backpropagate` gathers into one matrix, so that a large dataset doesn't need a copy of
+/// its inputs and every layer's activations for all samples at once.
+const BACKPROPAGATION_CHUNK_SIZE: usize = 256;
+
+#[derive(Clone, Debug)]
+pub struct Network<T: Scalar = f32> {
+    layers: Vec<Box<dyn NetworkLayer<T>>>,
+    lr_scales: Vec<T>,
+    /// The earlier layer whose parameters every layer shares, see `share_parameters`.
+    shared: Vec<Option<usize>>,
+}
+
+#[derive(Clone, Debug)]
+pub struct ParameterSnapshot<T: Scalar = f32> {
+    layers: Vec<LayerSnapshot<T>>,
+}
 
-pub struct Network {
-    layers: Vec<Layer>,
+/// Dense layers keep their batch normalization, other layers only what `NetworkLayer::write_parameters` gives.
+#[derive(Clone, Debug)]
+enum LayerSnapshot<T: Scalar> {
+    Dense(Box<LayerParameters<T>>),
+    Other(Vec<T>),
 }
 
-pub struct NetworkCache {
-    activations: Vec<DVector<f32>>,
-    weighted_inputs: Vec<DVector<f32>>,
+/// Everything a forward pass computes that backpropagation needs. `activations[0]` is the network input
+/// and `activations[i + 1]` the output of layer `i`, whose activation function was applied to `weighted_inputs[i]`.
+#[derive(Clone, Debug)]
+pub struct NetworkCache<T: Scalar = f32> {
+    activations: Vec<DVector<T>>,
+    weighted_inputs: Vec<DVector<T>>,
+}
+
+impl<T: Scalar> NetworkCache<T> {
+    #[inline]
+    pub fn activations(&self) -> &[DVector<T>] { &self.activations }
+
+    #[inline]
+    pub fn weighted_inputs(&self) -> &[DVector<T>] { &self.weighted_inputs }
+
+    #[inline]
+    pub fn output(&self) -> DVectorView<'_, T> { self.activations.last().unwrap().as_view() }
 }
 
 #[derive(Debug, Error)]
@@ -29,13 +143,286 @@ pub enum NetworkError {
     #[error("layer {0}'s size has to be more than 0")]
     ZeroLayerSize(usize),
 
+    #[error("a network needs at least one layer")]
+    NoLayers,
+
+    #[error("layer {layer} gives {output_size} outputs, but layer {next_layer} takes {input_size} inputs")]
+    LayerChainMismatch {
+        layer: usize,
+        next_layer: usize,
+        output_size: usize,
+        input_size: usize,
+    },
+
+    #[error("layer {index} doesn't exist, the network has {layers} layers")]
+    LayerIndexOutOfRange {
+        index: usize,
+        layers: usize,
+    },
+
+    #[error("the layer range {start}..{end} ends before it starts")]
+    InvalidLayerRange {
+        start: usize,
+        end: usize,
+    },
+
+    #[error("a residual block's output size has to be its input size {input_size}, but it's {output_size}")]
+    ResidualSizeMismatch {
+        input_size: usize,
+        output_size: usize,
+    },
+
+    #[error("layer {layer} isn't a dense layer")]
+    NotDense {
+        layer: usize,
+    },
+
+    #[error("layer {layer} uses batch normalization, which only works on whole batches")]
+    BatchNormUnsupported {
+        layer: usize,
+    },
+
+    #[error("layer {layer} can't share the parameters of layer {source_layer}, their weights or biases have different shapes")]
+    SharedShapeMismatch {
+        layer: usize,
+        source_layer: usize,
+    },
+
+    #[error("layer {layer} shares its parameters with another layer, so it can't be resized")]
+    SharedLayerResize {
+        layer: usize,
+    },
+
+    #[error("the operation would change the network's input or output size")]
+    InterfaceChange,
+
+    #[error("a layer of {size} outputs with the activation function {activation:?} inserted after {inputs} units would change what the network computes")]
+    InsertionChangesFunction {
+        size: usize,
+        inputs: usize,
+        activation: &'static str,
+    },
+
+    #[error("the builder has no input size")]
+    MissingInputSize,
+
+    #[error("the dataset is empty")]
+    EmptyDataset,
+
+    #[error("the snapshot has {snapshot_layers} layers, but the network has {network_layers}")]
+    SnapshotLayerCountMismatch {
+        network_layers: usize,
+        snapshot_layers: usize,
+    },
+
+    #[error("sample {index} has {inputs} inputs and {outputs} expected outputs, but the network takes {network_inputs} inputs and gives {network_outputs} outputs")]
+    SampleSizeMismatch {
+        index: usize,
+        inputs: usize,
+        outputs: usize,
+        network_inputs: usize,
+        network_outputs: usize,
+    },
+
+    #[error("sequence {index} has {inputs} steps, but {outputs} expected outputs")]
+    SequenceLengthMismatch {
+        index: usize,
+        inputs: usize,
+        outputs: usize,
+    },
+
+    #[error("input {index} has {inputs} values, but the network takes {network_inputs} inputs")]
+    InputSizeMismatch {
+        index: usize,
+        inputs: usize,
+        network_inputs: usize,
+    },
+
+    #[error("the batch has {rows} rows, but the network takes {network_inputs} inputs, one per row")]
+    BatchInputSizeMismatch {
+        rows: usize,
+        network_inputs: usize,
+    },
+
+    #[error("the batch has {rows} rows of expected outputs, but the network gives {network_outputs} outputs")]
+    BatchOutputSizeMismatch {
+        rows: usize,
+        network_outputs: usize,
+    },
+
+    #[error("a network with 2 inputs and 1 output is needed, but this one has {inputs} inputs and {outputs} outputs")]
+    NotPlanar {
+        inputs: usize,
+        outputs: usize,
+    },
+
+    #[error("sample {index} has the weight {weight}, but weights have to be finite and non-negative")]
+    InvalidSampleWeight {
+        index: usize,
+        weight: f32,
+    },
+
+    #[error("{distributions} distributions were given for {layers} layers")]
+    DistributionCountMismatch {
+        distributions: usize,
+        layers: usize,
+    },
+
+    #[error("the network has {expected} parameters, but {given} were given")]
+    ParameterCountMismatch {
+        expected: usize,
+        given: usize,
+    },
+
+    #[error("the networks' architectures differ at layer {layer}")]
+    ArchitectureMismatch {
+        layer: usize,
+    },
+
+    #[error("the scratch buffers were made for a network with different layer sizes")]
+    ScratchMismatch,
+
+    #[error("binary classification needs a single output, but the network has {0}")]
+    NotBinary(usize),
+
+    #[error("sample {index}'s expected outputs don't encode a class")]
+    InvalidClassEncoding {
+        index: usize,
+    },
+
+    #[error("there are {num_classes} classes, but the network has {network_outputs} outputs")]
+    ClassCountMismatch {
+        num_classes: usize,
+        network_outputs: usize,
+    },
+
+    #[error("k has to be between 1 and the number of outputs ({network_outputs}), but it is {k}")]
+    InvalidTopK {
+        k: usize,
+        network_outputs: usize,
+    },
+
+    #[error("the dataset holds only one class")]
+    SingleClass,
+
+    #[error("a non-finite {kind} appeared{}", layer.map_or(String::new(), |layer| format!(" in layer {layer}")))]
+    NonFiniteValue {
+        layer: Option<usize>,
+        kind: NonFiniteKind,
+    },
+
+    #[error("the loss needs a {required:?} output layer, but the network's output layer uses {activation:?}")]
+    OutputActivationMismatch {
+        required: &'static str,
+        activation: &'static str,
+    },
+
+    #[error("no networks were given")]
+    NoNetworks,
+
+    #[error("{networks} networks were given, but {weights} weights")]
+    MergeWeightCountMismatch {
+        networks: usize,
+        weights: usize,
+    },
+
+    #[error("merge weights have to be finite, non-negative and sum to more than 0")]
+    InvalidMergeWeights,
+
+    #[error("ensemble member {member} has {inputs} inputs and {outputs} outputs, but member 0 has {expected_inputs} inputs and {expected_outputs} outputs")]
+    EnsembleSizeMismatch {
+        member: usize,
+        inputs: usize,
+        outputs: usize,
+        expected_inputs: usize,
+        expected_outputs: usize,
+    },
+
+    #[error("layer {layer}'s learning rate scale has to be finite and non-negative, but it is {scale}")]
+    InvalidLrScale {
+        layer: usize,
+        scale: f64,
+    },
+
+    #[error("the moving average's decay has to be between 0 and 1, but it is {0}")]
+    InvalidEmaDecay(f64),
+
+    #[error("lookahead needs k >= 1 and alpha in (0, 1], but got k = {k} and alpha = {alpha}")]
+    InvalidLookahead {
+        k: usize,
+        alpha: f64,
+    },
+
+    #[error("the optimizer state is for {found:?}, but the optimizer is {expected:?}")]
+    OptimizerKindMismatch {
+        expected: String,
+        found: String,
+    },
+
+    #[error("the optimizer state has no {0:?}")]
+    MissingOptimizerState(String),
+
+    #[error("the optimizer state's {buffer:?} has {found} values, but the network has {expected} parameters")]
+    OptimizerStateMismatch {
+        buffer: String,
+        expected: usize,
+        found: usize,
+    },
+
     #[error("{0}")]
     LayerError(#[from] LayerError),
 
+    #[error("RProp needs 0 < decrease < 1 < increase and 0 < min_step <= initial_step <= max_step, but got initial_step = {initial_step}, increase = {increase}, decrease = {decrease}, min_step = {min_step} and max_step = {max_step}")]
+    InvalidRProp {
+        initial_step: f64,
+        increase: f64,
+        decrease: f64,
+        min_step: f64,
+        max_step: f64,
+    },
+
     #[error("{0}")]
     LossFnError(#[from] losses::LossFnError),
 }
 
+/// Where `learn_checked` found a NaN or infinite value.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NonFiniteKind {
+    Loss,
+    WeightGradient,
+    BiasGradient,
+    Weight,
+    Bias,
+}
+
+impl core::fmt::Display for NonFiniteKind {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str(match self {
+            NonFiniteKind::Loss => "loss",
+            NonFiniteKind::WeightGradient => "weight gradient",
+            NonFiniteKind::BiasGradient => "bias gradient",
+            NonFiniteKind::Weight => "weight",
+            NonFiniteKind::Bias => "bias",
+        })
+    }
+}
+
+/// The result of `learn_robust`: the weighted mean loss of the samples the step used, and the indices of
+/// those it skipped for a non-finite loss or gradient.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RobustStep<T: Scalar = f32> {
+    pub mean_loss: T,
+    pub skipped: Vec<usize>,
+}
+
+// Networks are moved into worker threads and shared behind `Arc`s, so losing `Send` or `Sync` must not compile.
+const _: () = {
+    const fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<Network>();
+    assert_send_sync::<Layer>();
+    assert_send_sync::<Network<f64>>();
+};
+
 fn check_layer_sizes(layer_sizes: &[usize]) -> Result<(), NetworkError> {
     if layer_sizes.len() < 2 {
         return Err(NetworkError::TooFewLayers(layer_sizes.len()));
@@ -48,9 +435,10 @@ fn check_layer_sizes(layer_sizes: &[usize]) -> Result<(), NetworkError> {
     Ok(())
 }
 
-fn construct_layers<F>(layer_sizes: &[usize], constructor: F) -> Result<Vec<Layer>, LayerError> 
+fn construct_layers<T, F>(layer_sizes: &[usize], mut constructor: F) -> Result<Vec<Layer<T>>, LayerError> 
 where
-    F: Fn(usize, usize) -> Result<Layer, LayerError>
+    T: Scalar,
+    F: FnMut(usize, usize) -> Result<Layer<T>, LayerError>
 {
     layer_sizes
         .iter()
@@ -59,74 +447,1164 @@ where
         .collect()
 }
 
-impl Network {
-    pub fn zeros(layer_sizes: &[usize], activation_fn: Box<dyn ActivationFn>) -> Result<Self, NetworkError> {
+fn total_weight<T: Scalar, S: Borrow<Sample<T>>>(dataset: &[S]) -> T {
+    dataset.iter().fold(T::zero(), |total, sample| total + sample.borrow().loss_weight())
+}
+
+impl<T: Scalar> Network<T> {
+    /// Assembles a network from layers built separately, checking that every layer's output size is the next one's input size.
+    pub fn from_layers(layers: Vec<Layer<T>>) -> Result<Self, NetworkError> {
+        Self::from_network_layers(layers.into_iter().map(|layer| Box::new(layer) as Box<dyn NetworkLayer<T>>).collect())
+    }
+
+    /// `from_layers` for layers of any kind, e.g. custom ones mixed with dense layers.
+    pub fn from_network_layers(layers: Vec<Box<dyn NetworkLayer<T>>>) -> Result<Self, NetworkError> {
+        if layers.is_empty() {
+            return Err(NetworkError::NoLayers);
+        }
+
+        for (layer, pair) in layers.windows(2).enumerate() {
+            if pair[0].output_size() != pair[1].input_size() {
+                return Err(NetworkError::LayerChainMismatch {
+                    layer,
+                    next_layer: layer + 1,
+                    output_size: pair[0].output_size(),
+                    input_size: pair[1].input_size(),
+                });
+            }
+        }
+
+        Ok(Self {
+            lr_scales: vec![T::one(); layers.len()],
+            shared: vec![None; layers.len()],
+            layers,
+        })
+    }
+
+    /// Wraps dense layers that are known to chain.
+    pub(crate) fn from_dense_layers(layers: Vec<Layer<T>>) -> Self {
+        Self {
+            lr_scales: vec![T::one(); layers.len()],
+            shared: vec![None; layers.len()],
+            layers: layers.into_iter().map(|layer| Box::new(layer) as Box<dyn NetworkLayer<T>>).collect(),
+        }
+    }
+
+    pub fn zeros(layer_sizes: &[usize], activation_fn: Box<dyn ActivationFn<T>>) -> Result<Self, NetworkError> {
         check_layer_sizes(layer_sizes)?;
 
-        let layers: Vec<Layer> = construct_layers(layer_sizes, |input_size, output_size| Layer::zeros(
+        let layers: Vec<Layer<T>> = construct_layers(layer_sizes, |input_size, output_size| Layer::zeros(
             input_size,
             output_size,
             activation_fn.clone(),
         ))?;
 
-        Ok(Self { layers })
+        Ok(Self::from_dense_layers(layers))
     }
 
+    /// Samples every layer from the thread-local RNG, see `random_with_rng` for reproducible networks.
+    #[cfg(feature = "std")]
     pub fn random(
         layer_sizes: &[usize],
-        activation_fn: Box<dyn ActivationFn>,
-        distribution: &impl Distribution<f32>
+        activation_fn: Box<dyn ActivationFn<T>>,
+        distribution: &impl Distribution<T>
+    ) -> Result<Self, NetworkError> {
+        Self::random_with_rng(layer_sizes, activation_fn, distribution, &mut rand::rng())
+    }
+
+    /// Like `random`, but samples every layer from the given RNG, so a seeded RNG gives reproducible networks.
+    pub fn random_with_rng(
+        layer_sizes: &[usize],
+        activation_fn: Box<dyn ActivationFn<T>>,
+        distribution: &impl Distribution<T>,
+        rng: &mut impl Rng,
     ) -> Result<Self, NetworkError> {
         check_layer_sizes(layer_sizes)?;
 
-        let layers: Vec<Layer> = construct_layers(layer_sizes, |input_size, output_size| Layer::random(
+        let layers: Vec<Layer<T>> = construct_layers(layer_sizes, |input_size, output_size| Layer::random_with_rng(
             input_size,
             output_size,
             activation_fn.clone(),
             distribution,
+            rng,
+        ))?;
+
+        Ok(Self::from_dense_layers(layers))
+    }
+
+    /// `random` with a distribution per layer, `distributions[i]` for the weights and biases of the layer after
+    /// `layer_sizes[i]`. Fails with `DistributionCountMismatch` unless there's one distribution per layer.
+    #[cfg(feature = "std")]
+    pub fn random_per_layer(
+        layer_sizes: &[usize],
+        activation_fn: Box<dyn ActivationFn<T>>,
+        distributions: &[&dyn DynDistribution<T>],
+    ) -> Result<Self, NetworkError> {
+        Self::random_per_layer_with_rng(layer_sizes, activation_fn, distributions, &mut rand::rng())
+    }
+
+    /// Like `random_per_layer`, but samples every layer from the given RNG.
+    pub fn random_per_layer_with_rng(
+        layer_sizes: &[usize],
+        activation_fn: Box<dyn ActivationFn<T>>,
+        distributions: &[&dyn DynDistribution<T>],
+        rng: &mut impl Rng,
+    ) -> Result<Self, NetworkError> {
+        check_layer_sizes(layer_sizes)?;
+
+        if distributions.len() != layer_sizes.len() - 1 {
+            return Err(NetworkError::DistributionCountMismatch {
+                distributions: distributions.len(),
+                layers: layer_sizes.len() - 1,
+            });
+        }
+
+        let mut distributions = distributions.iter();
+        let layers: Vec<Layer<T>> = construct_layers(layer_sizes, |input_size, output_size| Layer::random_with_rng(
+            input_size,
+            output_size,
+            activation_fn.clone(),
+            distributions.next().unwrap(),
+            rng,
         ))?;
 
-        Ok(Self { layers })
+        Ok(Self::from_dense_layers(layers))
     }
 
-    pub fn forward(&mut self, input: DVector<f32>) -> Result<DVector<f32>, NetworkError> {
+    pub fn forward(&mut self, input: DVector<T>) -> Result<DVector<T>, NetworkError> {
         self.layers.iter_mut().try_fold(input, |activations, layer| {
-            layer.forward(activations).map_err(Into::into)
+            layer.forward(activations.as_view()).map_err(Into::into)
         })
     }
 
-    pub fn backpropagate(&mut self, dataset: &[Sample], loss: &impl LossFn) -> Result<(), NetworkError> {
-        for sample in dataset.iter() {
-            let outputs = self.forward(sample.inputs().into_owned())?;
-            let mut activation_partial_gradient = loss.partial_gradient(outputs.as_view(), sample.expected_outputs())?;
+    /// `forward` for a borrowed input, e.g. `sample.inputs()`, without copying it into an owned vector first.
+    pub fn forward_view(&mut self, input: DVectorView<T>) -> Result<DVector<T>, NetworkError> {
+        let (first, rest) = self.layers.split_first_mut().unwrap();
+        let outputs = first.forward(input)?;
 
-            activation_partial_gradient = self.layers.last_mut().unwrap().backpropagation_step(
-                outputs.as_view(),
-                activation_partial_gradient.as_view()
-            );
+        rest.iter_mut().try_fold(outputs, |activations, layer| {
+            layer.forward(activations.as_view()).map_err(Into::into)
+        })
+    }
+
+    /// Forward pass that doesn't touch any layer state, so it works on a shared network. The outputs are
+    /// the same as `forward`'s, but only `forward` records what `backpropagate` needs.
+    pub fn predict(&self, input: DVectorView<T>) -> Result<DVector<T>, NetworkError> {
+        let (first, rest) = self.layers.split_first().unwrap();
+        let outputs = first.predict(input)?;
 
-            for i in (0..self.layers.len() - 1).rev() {
-                let (left, right) = self.layers.split_at_mut(i + 1);
-                let previous_input = right[0].get_previous_input();
-                activation_partial_gradient = left[i]
-                    .backpropagation_step(previous_input, activation_partial_gradient.as_view());
+        rest.iter().try_fold(outputs, |activations, layer| {
+            layer.predict(activations.as_view()).map_err(Into::into)
+        })
+    }
+
+    /// `predict` on a plain slice, for callers that don't use nalgebra. The slice is viewed, not copied.
+    pub fn predict_slice(&self, input: &[T]) -> Result<Vec<T>, NetworkError> {
+        Ok(self.predict(DVectorView::from_slice(input, input.len()))?.data.into())
+    }
+
+    /// `forward_view` on a plain slice, see `predict_slice`.
+    pub fn forward_slice(&mut self, input: &[T]) -> Result<Vec<T>, NetworkError> {
+        Ok(self.forward_view(DVectorView::from_slice(input, input.len()))?.data.into())
+    }
+
+    /// Runs a batch of inputs, one sample per column, through the network using matrix-matrix products.
+    /// Unlike `forward` it doesn't touch the caches used by `backpropagate`.
+    pub fn forward_batch(&self, inputs: &DMatrix<T>) -> Result<DMatrix<T>, NetworkError> {
+        let (first, rest) = self.layers.split_first().unwrap();
+        let outputs = first.forward_batch(inputs)?;
+
+        rest.iter().try_fold(outputs, |activations, layer| {
+            layer.forward_batch(&activations).map_err(Into::into)
+        })
+    }
+
+    /// `predict` for a batch with one input per column, giving one output per column. It's `forward_batch`
+    /// with the row count checked against the network's input size first. A batch without any columns gives
+    /// outputs without any columns.
+    pub fn predict_batch(&self, inputs: &DMatrix<T>) -> Result<DMatrix<T>, NetworkError> {
+        if inputs.nrows() != self.input_size() {
+            return Err(NetworkError::BatchInputSizeMismatch {
+                rows: inputs.nrows(),
+                network_inputs: self.input_size(),
+            });
+        }
+
+        if inputs.ncols() == 0 {
+            return Ok(DMatrix::zeros(self.output_size(), 0));
+        }
+
+        self.forward_batch(inputs)
+    }
+
+    /// `predict` for every input, checking all input sizes first.
+    pub fn predict_all(&self, inputs: &[DVector<T>]) -> Result<Vec<DVector<T>>, NetworkError> {
+        self.check_inputs(inputs)?;
+        inputs.iter().map(|input| self.predict(input.as_view())).collect()
+    }
+
+    /// `predict_all` spread over the rayon thread pool, the outputs are in the order of the inputs.
+    #[cfg(feature = "rayon")]
+    pub fn predict_all_parallel(&self, inputs: &[DVector<T>]) -> Result<Vec<DVector<T>>, NetworkError> {
+        use rayon::prelude::*;
+
+        self.check_inputs(inputs)?;
+        inputs.par_iter().map(|input| self.predict(input.as_view())).collect()
+    }
+
+    fn check_inputs(&self, inputs: &[DVector<T>]) -> Result<(), NetworkError> {
+        match inputs.iter().position(|input| input.len() != self.input_size()) {
+            Some(index) => Err(NetworkError::InputSizeMismatch {
+                index,
+                inputs: inputs[index].len(),
+                network_inputs: self.input_size(),
+            }),
+            None => Ok(()),
+        }
+    }
+
+    /// Enables batch normalization on every dense layer except the output layer.
+    pub fn with_batch_norm(mut self) -> Self {
+        let hidden_layers = self.layers.len() - 1;
+
+        for layer in self.layers[..hidden_layers].iter_mut() {
+            if let Some(dense) = layer.as_dense_mut() {
+                *dense = dense.clone().with_batch_norm();
+            }
+        }
+
+        self
+    }
+
+    pub fn has_batch_norm(&self) -> bool {
+        self.dense_layers().any(|layer| layer.batch_norm().is_some())
+    }
+
+    /// Checks that every sample has as many inputs and expected outputs as the network has inputs and outputs,
+    /// and a finite, non-negative weight.
+    pub fn check_dataset(&self, dataset: &[Sample<T>]) -> Result<(), NetworkError> {
+        self.check_samples(dataset)
+    }
+
+    /// `check_dataset` for borrowed samples, e.g. a batch gathered in shuffled order.
+    fn check_samples<S: Borrow<Sample<T>>>(&self, dataset: &[S]) -> Result<(), NetworkError> {
+        let (network_inputs, network_outputs) = (self.input_size(), self.output_size());
+
+        for (index, sample) in dataset.iter().map(Borrow::borrow).enumerate() {
+            if sample.inputs().len() != network_inputs || sample.expected_outputs().len() != network_outputs {
+                return Err(NetworkError::SampleSizeMismatch {
+                    index,
+                    inputs: sample.inputs().len(),
+                    outputs: sample.expected_outputs().len(),
+                    network_inputs,
+                    network_outputs,
+                });
+            }
+
+            if !(sample.weight().is_finite() && sample.weight() >= 0.0) {
+                return Err(NetworkError::InvalidSampleWeight {
+                    index,
+                    weight: sample.weight(),
+                });
             }
         }
 
         Ok(())
     }
 
-    pub fn learn(&mut self, dataset: &[Sample], loss: &impl LossFn, rate: f32) -> Result<(), NetworkError> {
-        if dataset.is_empty() {
-            return Ok(());
+    /// Checks that the output layer has the activation function the loss requires, see `LossFn::output_activation`.
+    pub fn check_loss(&self, loss: &impl LossFn<T>) -> Result<(), NetworkError> {
+        let output_layer = self.layers.last().unwrap();
+        let activation = output_layer.activation_fn().map_or(output_layer.kind(), |activation_fn| activation_fn.name());
+
+        match loss.output_activation() {
+            Some(required) if required != activation => Err(NetworkError::OutputActivationMismatch {
+                required,
+                activation,
+            }),
+            _ => Ok(()),
         }
+    }
 
+    /// Accumulates the gradient of the summed loss over the dataset and returns that summed loss.
+    /// Every sample's loss is scaled by its weight. The samples are the columns of one matrix per layer,
+    /// so every layer's gradient is a single matrix product over the whole dataset, and with batch
+    /// normalization the dataset is the batch whose statistics normalize every layer.
+    ///
+    /// The dataset is checked with `check_dataset` before anything is accumulated, and if an error
+    /// still happens midway the gradients are left as they were before the call, so a failed call never
+    /// leaks into the next update and keeps what earlier calls accumulated.
+    pub fn backpropagate(&mut self, dataset: &[Sample<T>], loss: &impl LossFn<T>) -> Result<T, NetworkError> {
+        self.backpropagate_samples(dataset, loss)
+    }
+
+    /// `backpropagate` for borrowed samples.
+    fn backpropagate_samples<S: Borrow<Sample<T>>>(&mut self, dataset: &[S], loss: &impl LossFn<T>) -> Result<T, NetworkError> {
+        self.check_samples(dataset)?;
+        self.check_loss(loss)?;
+
+        // Every chunk takes its losses before accumulating its gradients, so only the chunks before a failing
+        // one can have added to them. A call spanning several chunks keeps the layers it started from instead.
+        let chunks = !self.has_batch_norm() && dataset.len() > BACKPROPAGATION_CHUNK_SIZE;
+        let rollback = chunks.then(|| self.layers.clone());
+        let result = self.backpropagate_batch(dataset, loss);
+
+        if let (Err(_), Some(layers)) = (&result, rollback) {
+            self.layers = layers;
+        }
+
+        result
+    }
+
+    /// `backpropagate` for gradient accumulation, returning the number of samples processed. Gradients add up
+    /// across calls until `apply_gradients` or `zero_gradients`, so several micro-batches followed by
+    /// `apply_gradients(-rate / total_weight)` step like one `learn` on all of them, with `total_weight` the
+    /// summed loss weight of every sample accumulated. A failed call leaves the gradients accumulated by
+    /// earlier calls as they were.
+    pub fn accumulate_gradients(&mut self, dataset: &[Sample<T>], loss: &impl LossFn<T>) -> Result<usize, NetworkError> {
         self.backpropagate(dataset, loss)?;
+        Ok(dataset.len())
+    }
+
+    /// Accumulates the gradient of one sample's weighted loss from a pass made with `forward_cached`,
+    /// returning that loss. Batch normalization is skipped, like in `Layer::backpropagation_step_cached`.
+    /// Only networks of dense layers record caches, for others this fails with `NotDense`.
+    pub fn backpropagate_cached(&mut self, cache: &NetworkCache<T>, sample: &Sample<T>, loss: &impl LossFn<T>) -> Result<T, NetworkError> {
+        self.check_dense()?;
+        let outputs = cache.output();
+        let sample_loss = loss.apply(outputs, sample.expected_outputs())? * sample.loss_weight();
+        let mut activation_partial_gradient = loss.partial_gradient(outputs, sample.expected_outputs())?;
+        activation_partial_gradient *= sample.loss_weight();
+
+        for (i, layer) in self.layers.iter_mut().enumerate().rev() {
+            let layer = layer.as_dense_mut().unwrap();
+            activation_partial_gradient = layer.backpropagation_step_cached(
+                cache.activations[i].as_view(),
+                cache.weighted_inputs[i].as_view(),
+                cache.activations[i + 1].as_view(),
+                activation_partial_gradient.as_view(),
+            );
+        }
+
+        Ok(sample_loss)
+    }
+
+    /// Parallel `backpropagate`: the dataset is split across the rayon thread pool, every worker
+    /// accumulates into its own gradient buffers and the sums are added to the layers at the end.
+    /// The result matches the sequential path up to floating point reassociation.
+    /// Networks with batch normalization need the whole batch at once and fall back to the sequential path,
+    /// like networks with layers other than dense ones.
+    #[cfg(feature = "rayon")]
+    pub fn backpropagate_parallel(&mut self, dataset: &[Sample<T>], loss: &(impl LossFn<T> + Sync)) -> Result<T, NetworkError> {
+        use rayon::prelude::*;
+
+        if dataset.is_empty() {
+            return Ok(T::zero());
+        }
+
+        if self.has_batch_norm() || self.check_dense().is_err() {
+            return self.backpropagate(dataset, loss);
+        }
+
+        self.check_dataset(dataset)?;
+        self.check_loss(loss)?;
+
+        let chunk_size = dataset.len().div_ceil(rayon::current_num_threads());
+        let network = &*self;
+
+        let (gradients, total_loss) = dataset
+            .par_chunks(chunk_size)
+            .map(|chunk| {
+                let mut gradients: Vec<_> = network.dense_layers().map(Layer::zero_gradient_buffers).collect();
+                let mut total_loss = T::zero();
+
+                for sample in chunk {
+                    total_loss += network.backpropagate_sample_into(sample, loss, &mut gradients)?;
+                }
+
+                Ok((gradients, total_loss))
+            })
+            .try_reduce_with(|(mut gradients, loss_a), (other, loss_b)| {
+                for ((weights, biases), (other_weights, other_biases)) in gradients.iter_mut().zip(other.iter()) {
+                    *weights += other_weights;
+                    *biases += other_biases;
+                }
+
+                Ok((gradients, loss_a + loss_b))
+            })
+            .unwrap_or(Err(NetworkError::EmptyDataset))?;
+
+        for (layer, (weight_gradient, bias_gradient)) in self.layers.iter_mut().zip(gradients.iter()) {
+            layer.as_dense_mut().unwrap().add_to_gradient(weight_gradient, bias_gradient);
+        }
+
+        Ok(total_loss)
+    }
+
+    /// `learn` on top of `backpropagate_parallel`.
+    #[cfg(feature = "rayon")]
+    pub fn learn_parallel(&mut self, dataset: &[Sample<T>], loss: &(impl LossFn<T> + Sync), rate: T) -> Result<T, NetworkError> {
+        let total_weight = total_weight(dataset);
+        if dataset.is_empty() || total_weight == T::zero() {
+            self.check_dataset(dataset)?;
+            return Ok(T::zero());
+        }
+
+        let total_loss = self.backpropagate_parallel(dataset, loss)?;
+        self.apply_gradients(-rate / total_weight);
+
+        Ok(total_loss / total_weight)
+    }
+
+    fn backpropagate_sample_into(
+        &self,
+        sample: &Sample<T>,
+        loss: &impl LossFn<T>,
+        gradients: &mut [(DMatrix<T>, DVector<T>)],
+    ) -> Result<T, NetworkError> {
+        let cache = self.forward_cache(sample.inputs())?;
+        let outputs = cache.activations.last().unwrap();
+        let sample_loss = loss.apply(outputs.as_view(), sample.expected_outputs())? * sample.loss_weight();
+        let mut activation_partial_gradient = loss.partial_gradient(outputs.as_view(), sample.expected_outputs())?;
+        activation_partial_gradient *= sample.loss_weight();
+
+        for (i, layer) in self.layers.iter().enumerate().rev() {
+            let layer = layer.as_dense().unwrap();
+            let (weight_gradient, bias_gradient) = &mut gradients[i];
+            activation_partial_gradient = layer.backpropagation_step_into(
+                cache.activations[i].as_view(),
+                cache.weighted_inputs[i].as_view(),
+                cache.activations[i + 1].as_view(),
+                activation_partial_gradient.as_view(),
+                weight_gradient,
+                bias_gradient,
+            );
+        }
+
+        Ok(sample_loss)
+    }
+
+    /// Forward pass that records every layer's input and weighted sums in the returned cache instead of the
+    /// layers, for `backpropagate_cached`. Only networks of dense layers have such a cache, for others this
+    /// fails with `NotDense`.
+    pub fn forward_cached(&self, input: DVectorView<T>) -> Result<(DVector<T>, NetworkCache<T>), NetworkError> {
+        let cache = self.forward_cache(input)?;
+        Ok((cache.output().into_owned(), cache))
+    }
+
+    fn forward_cache(&self, input: DVectorView<T>) -> Result<NetworkCache<T>, NetworkError> {
+        self.check_dense()?;
+        let mut cache = NetworkCache {
+            activations: Vec::with_capacity(self.layers.len() + 1),
+            weighted_inputs: Vec::with_capacity(self.layers.len()),
+        };
+
+        cache.activations.push(input.into_owned());
+
+        for layer in self.dense_layers() {
+            let (weighted_sums, activations) = layer.feed(cache.activations.last().unwrap().as_view())?;
+            cache.weighted_inputs.push(weighted_sums);
+            cache.activations.push(activations);
+        }
+
+        Ok(cache)
+    }
+
+    fn backpropagate_batch<S: Borrow<Sample<T>>>(&mut self, dataset: &[S], loss: &impl LossFn<T>) -> Result<T, NetworkError> {
+        if dataset.is_empty() {
+            return Ok(T::zero());
+        }
+
+        // Batch normalization takes its statistics over the whole batch, so only without it can the batch be
+        // gathered into matrices a chunk at a time.
+        let chunk_size = if self.has_batch_norm() { dataset.len() } else { BACKPROPAGATION_CHUNK_SIZE };
+        let mut total_loss = T::zero();
+
+        for chunk in dataset.chunks(chunk_size) {
+            let inputs: Vec<_> = chunk.iter().map(|sample| sample.borrow().inputs()).collect();
+            let targets = chunk.iter().map(Borrow::borrow).map(|sample| (sample.expected_outputs(), sample.loss_weight()));
+            total_loss += self.backpropagate_columns(DMatrix::from_columns(&inputs).as_view(), targets, loss)?;
+        }
+
+        Ok(total_loss)
+    }
+
+    /// The batched forward and backward pass over inputs with one sample per column, given every sample's
+    /// expected outputs and weight in column order. All losses are taken before any gradient is accumulated.
+    fn backpropagate_columns<'a>(
+        &mut self,
+        inputs: DMatrixView<T>,
+        targets: impl Iterator<Item = (DVectorView<'a, T>, T)>,
+        loss: &impl LossFn<T>,
+    ) -> Result<T, NetworkError> {
+        let activations = forward_training_chain(&mut self.layers, inputs)?;
+        let outputs = activations.last().unwrap();
+
+        let mut total_loss = T::zero();
+        let mut activation_partial_gradient = DMatrix::zeros(outputs.nrows(), outputs.ncols());
+
+        for (i, (expected_outputs, weight)) in targets.enumerate() {
+            total_loss += loss.apply(outputs.column(i), expected_outputs)? * weight;
+            let sample_gradient = loss.partial_gradient(outputs.column(i), expected_outputs)?;
+            activation_partial_gradient.set_column(i, &(sample_gradient * weight));
+        }
+
+        backpropagate_chain(&mut self.layers, inputs, &activations, activation_partial_gradient)?;
+
+        Ok(total_loss)
+    }
+
+    /// `backpropagate` for samples stored as matrices, see `BatchSamples`. The batch's inputs feed the
+    /// batched forward pass as they are, without gathering them from separate samples first.
+    pub fn backpropagate_batch_view(&mut self, batch: BatchView<T>, loss: &impl LossFn<T>) -> Result<T, NetworkError> {
+        self.check_batch(batch)?;
+        self.check_loss(loss)?;
 
+        if batch.is_empty() {
+            return Ok(T::zero());
+        }
+
+        let expected_outputs = batch.expected_outputs();
+        let targets = expected_outputs.column_iter().map(|column| (column, T::one()));
+        self.backpropagate_columns(batch.inputs(), targets, loss)
+    }
+
+    /// `learn` for samples stored as matrices, every sample weighing 1. It steps exactly like `learn` on
+    /// the same samples as `Sample`s.
+    pub fn learn_batch(&mut self, batch: BatchView<T>, loss: &impl LossFn<T>, rate: T) -> Result<T, NetworkError> {
+        self.learn_batch_with_gradient_norm(batch, loss, rate, &mut Sgd, None).map(|(mean_loss, _)| mean_loss)
+    }
+
+    /// `learn_batch` like `learn_with_gradient_norm` is `learn`.
+    pub(crate) fn learn_batch_with_gradient_norm(
+        &mut self,
+        batch: BatchView<T>,
+        loss: &impl LossFn<T>,
+        rate: T,
+        optimizer: &mut dyn Optimizer<T>,
+        gradient_noise: Option<(T, &mut dyn RngCore)>,
+    ) -> Result<(T, T), NetworkError> {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::debug_span!("learn_batch", samples = batch.len()).entered();
+
+        if batch.is_empty() {
+            self.check_batch(batch)?;
+            #[cfg(feature = "tracing")]
+            tracing::debug!("no step, the batch is empty");
+            return Ok((T::zero(), T::zero()));
+        }
+
+        let total_loss = self.backpropagate_batch_view(batch, loss)?;
+        self.step_with_gradient_norm(total_loss, T::from_count(batch.len()), rate, optimizer, gradient_noise)
+    }
+
+    fn check_batch(&self, batch: BatchView<T>) -> Result<(), NetworkError> {
+        if batch.inputs().nrows() != self.input_size() {
+            return Err(NetworkError::BatchInputSizeMismatch {
+                rows: batch.inputs().nrows(),
+                network_inputs: self.input_size(),
+            });
+        }
+
+        if batch.expected_outputs().nrows() != self.output_size() {
+            return Err(NetworkError::BatchOutputSizeMismatch {
+                rows: batch.expected_outputs().nrows(),
+                network_outputs: self.output_size(),
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Runs one gradient descent step over the dataset and returns its mean loss before the update.
+    /// Both the step and the mean are weighted by the sample weights, so with the default weight of 1
+    /// they're plain averages over the samples. A sample of the wrong size fails the step with a
+    /// `SampleSizeMismatch` naming its index before any gradient or parameter has changed. Every layer's
+    /// rate is `rate` times its `set_layer_lr_scale`.
+    ///
+    /// With the `tracing` feature every step is a `learn` span with a trace event holding its mean loss and
+    /// gradient norm. An empty dataset and a non-finite loss or gradient are debug events.
+    pub fn learn(&mut self, dataset: &[Sample<T>], loss: &impl LossFn<T>, rate: T) -> Result<T, NetworkError> {
+        self.learn_with_optimizer(dataset, loss, rate, &mut Sgd)
+    }
+
+    /// `learn` with the combined loss of several output heads. The heads have to be declared for this
+    /// network's output size, otherwise the step fails with an `OutputSizeMismatch` before anything changes.
+    pub fn learn_multi_head(&mut self, dataset: &[Sample<T>], heads: &MultiHeadLoss<T>, rate: T) -> Result<T, NetworkError> {
+        if heads.output_size() != self.output_size() {
+            return Err(losses::LossFnError::OutputSizeMismatch {
+                given_output_size: self.output_size(),
+                expected_output_size: heads.output_size(),
+            }.into());
+        }
+
+        self.learn(dataset, heads, rate)
+    }
+
+    /// `learn`, also returning the norm of the weighted mean gradient. With `gradient_noise`, noise of that
+    /// standard deviation is added to the mean gradient after its norm is taken, see `add_gradient_noise`.
+    /// The samples may be borrowed, so that a batch can be gathered in any order without copying them.
+    pub(crate) fn learn_with_gradient_norm<S: Borrow<Sample<T>>>(
+        &mut self,
+        dataset: &[S],
+        loss: &impl LossFn<T>,
+        rate: T,
+        optimizer: &mut dyn Optimizer<T>,
+        gradient_noise: Option<(T, &mut dyn RngCore)>,
+    ) -> Result<(T, T), NetworkError> {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::debug_span!("learn", samples = dataset.len()).entered();
+
+        let total_weight = total_weight(dataset);
+        if dataset.is_empty() || total_weight == T::zero() {
+            self.check_samples(dataset)?;
+            #[cfg(feature = "tracing")]
+            tracing::debug!(samples = dataset.len(), "no step, the dataset is empty or weighs nothing");
+            return Ok((T::zero(), T::zero()));
+        }
+
+        let total_loss = self.backpropagate_samples(dataset, loss)?;
+        self.step_with_gradient_norm(total_loss, total_weight, rate, optimizer, gradient_noise)
+    }
+
+    /// `learn` with the step `optimizer` takes instead of plain gradient descent, e.g. a `Lookahead`. The
+    /// gradients are zeroed if the optimizer fails.
+    pub fn learn_with_optimizer(&mut self, dataset: &[Sample<T>], loss: &impl LossFn<T>, rate: T, optimizer: &mut impl Optimizer<T>) -> Result<T, NetworkError> {
+        self.learn_with_gradient_norm(dataset, loss, rate, optimizer, None).map(|(mean_loss, _)| mean_loss)
+    }
+
+    /// The step after backpropagating a summed loss of `total_loss` over samples weighing `total_weight`,
+    /// returning the mean loss and the norm of the mean gradient.
+    fn step_with_gradient_norm(
+        &mut self,
+        total_loss: T,
+        total_weight: T,
+        rate: T,
+        optimizer: &mut dyn Optimizer<T>,
+        gradient_noise: Option<(T, &mut dyn RngCore)>,
+    ) -> Result<(T, T), NetworkError> {
+        self.merge_shared_gradients();
+        let gradient_norm = self.gradient_norm() / total_weight;
+
+        if let Some((std_dev, rng)) = gradient_noise {
+            self.add_gradient_noise(std_dev * total_weight, rng);
+        }
+
+        if let Err(error) = optimizer.step(self, rate, total_weight) {
+            self.zero_gradients();
+            return Err(error);
+        }
+
+        let mean_loss = total_loss / total_weight;
+        #[cfg(feature = "tracing")]
+        {
+            tracing::trace!(mean_loss = mean_loss.to_f64(), gradient_norm = gradient_norm.to_f64(), "step");
+            if !mean_loss.is_finite() || !gradient_norm.is_finite() {
+                tracing::debug!(mean_loss = mean_loss.to_f64(), gradient_norm = gradient_norm.to_f64(), "the step's loss or gradient isn't finite");
+            }
+        }
+
+        Ok((mean_loss, gradient_norm))
+    }
+
+    /// `learn` on a single sample given as its inputs and expected outputs, for online learning without
+    /// building a `Sample` for every step. It steps exactly like `learn(&[sample], ..)` and returns the
+    /// sample's loss before the update. Every call makes new buffers, `learn_sample_with_scratch` reuses
+    /// those of a `NetworkScratch` instead.
+    pub fn learn_sample(&mut self, inputs: DVectorView<T>, expected_outputs: DVectorView<T>, loss: &impl LossFn<T>, rate: T) -> Result<T, NetworkError> {
+        let mut scratch = self.scratch();
+        self.learn_sample_with_scratch(inputs, expected_outputs, loss, rate, &mut scratch)
+    }
+
+    fn check_sample(&self, inputs: DVectorView<T>, expected_outputs: DVectorView<T>) -> Result<(), NetworkError> {
+        if inputs.len() != self.input_size() || expected_outputs.len() != self.output_size() {
+            return Err(NetworkError::SampleSizeMismatch {
+                index: 0,
+                inputs: inputs.len(),
+                outputs: expected_outputs.len(),
+                network_inputs: self.input_size(),
+                network_outputs: self.output_size(),
+            });
+        }
+
+        Ok(())
+    }
+
+    /// `learn_sample` through the batched passes of `learn`, for the layers the scratch path doesn't cover.
+    fn learn_sample_batched(&mut self, inputs: DVectorView<T>, expected_outputs: DVectorView<T>, loss: &impl LossFn<T>, rate: T) -> Result<T, NetworkError> {
+        let inputs = DMatrix::from_columns(&[inputs]);
+        let sample_loss = match self.backpropagate_columns(inputs.as_view(), core::iter::once((expected_outputs, T::one())), loss) {
+            Ok(sample_loss) => sample_loss,
+            Err(error) => {
+                self.zero_gradients();
+                return Err(error);
+            }
+        };
+
+        self.apply_gradients(-rate);
+        Ok(sample_loss)
+    }
+
+    /// `learn` that refuses to apply a step producing NaN or infinite values. The loss, the gradients and
+    /// the updated weights and biases are checked before anything changes, so on a `NonFiniteValue` error
+    /// the parameters are still the ones from before the step and the gradients are zeroed.
+    pub fn learn_checked(&mut self, dataset: &[Sample<T>], loss: &impl LossFn<T>, rate: T) -> Result<T, NetworkError> {
+        let total_weight = total_weight(dataset);
+        if dataset.is_empty() || total_weight == T::zero() {
+            self.check_dataset(dataset)?;
+            return Ok(T::zero());
+        }
+
+        let mean_loss = self.backpropagate(dataset, loss)? / total_weight;
+        let scale = -rate / total_weight;
+        self.merge_shared_gradients();
+
+        let non_finite = if !mean_loss.is_finite() {
+            Some((None, NonFiniteKind::Loss))
+        } else {
+            self.layers
+                .iter()
+                .zip(self.lr_scales.iter())
+                .enumerate()
+                .filter(|&(_, (_, &lr_scale))| lr_scale != T::zero())
+                .find_map(|(i, (layer, &lr_scale))| layer.non_finite_update(scale * lr_scale).map(|kind| (Some(i), kind)))
+        };
+
+        if let Some((layer, kind)) = non_finite {
+            self.zero_gradients();
+            #[cfg(feature = "tracing")]
+            tracing::debug!(layer, %kind, "refused a step with a non-finite value");
+            return Err(NetworkError::NonFiniteValue { layer, kind });
+        }
+
+        self.apply_gradients(scale);
+
+        Ok(mean_loss)
+    }
+
+    /// `backpropagate` that skips samples whose loss or gradient isn't finite, e.g. because of an infinite
+    /// input, instead of letting them spread NaNs into every weight. Every sample's gradient goes into a
+    /// buffer that's only added to the layers once it's checked. Returns the summed loss of the samples
+    /// used, their summed weight and the indices of the skipped ones.
+    ///
+    /// Samples are processed one at a time, so this needs a network of dense layers and fails with
+    /// `NotDense` otherwise, and with `BatchNormUnsupported` for batch normalization, which normalizes every
+    /// sample with the statistics of the whole batch.
+    pub fn backpropagate_robust(&mut self, dataset: &[Sample<T>], loss: &impl LossFn<T>) -> Result<(T, T, Vec<usize>), NetworkError> {
+        self.check_dense()?;
+        self.check_no_batch_norm()?;
+        self.check_dataset(dataset)?;
+        self.check_loss(loss)?;
+
+        let mut gradients: Vec<_> = self.dense_layers().map(Layer::zero_gradient_buffers).collect();
+        let (mut total_loss, mut total_weight) = (T::zero(), T::zero());
+        let mut skipped = Vec::new();
+
+        for (index, sample) in dataset.iter().enumerate() {
+            for (weight_gradient, bias_gradient) in gradients.iter_mut() {
+                weight_gradient.fill(T::zero());
+                bias_gradient.fill(T::zero());
+            }
+
+            let sample_loss = match self.backpropagate_sample_into(sample, loss, &mut gradients) {
+                Ok(sample_loss) => sample_loss,
+                Err(error) => {
+                    self.zero_gradients();
+                    return Err(error);
+                }
+            };
+
+            let finite = sample_loss.is_finite() && gradients.iter().all(|(weight_gradient, bias_gradient)| {
+                weight_gradient.iter().all(|x| x.is_finite()) && bias_gradient.iter().all(|x| x.is_finite())
+            });
+
+            if !finite {
+                skipped.push(index);
+                continue;
+            }
+
+            for (layer, (weight_gradient, bias_gradient)) in self.layers.iter_mut().zip(gradients.iter()) {
+                layer.as_dense_mut().unwrap().add_to_gradient(weight_gradient, bias_gradient);
+            }
+
+            total_loss += sample_loss;
+            total_weight += sample.loss_weight();
+        }
+
+        Ok((total_loss, total_weight, skipped))
+    }
+
+    /// `learn` on top of `backpropagate_robust`: the step and the mean loss only cover the samples with a finite
+    /// loss and gradient, and the skipped ones are reported instead of failing the step. Nothing changes if
+    /// every sample is skipped.
+    pub fn learn_robust(&mut self, dataset: &[Sample<T>], loss: &impl LossFn<T>, rate: T) -> Result<RobustStep<T>, NetworkError> {
+        let (total_loss, total_weight, skipped) = self.backpropagate_robust(dataset, loss)?;
+        #[cfg(feature = "tracing")]
+        if !skipped.is_empty() {
+            tracing::debug!(skipped = skipped.len(), samples = dataset.len(), "skipped samples with a non-finite loss or gradient");
+        }
+
+        if total_weight == T::zero() {
+            self.zero_gradients();
+            return Ok(RobustStep { mean_loss: T::zero(), skipped });
+        }
+
+        self.apply_gradients(-rate / total_weight);
+
+        Ok(RobustStep {
+            mean_loss: total_loss / total_weight,
+            skipped,
+        })
+    }
+
+    /// Whether every weight and bias is finite.
+    pub fn is_finite(&self) -> bool {
+        self.layers.iter().all(|layer| layer.is_finite())
+    }
+
+    /// Mini-batch training from a stream of samples, holding at most `batch_size` of them in memory at once.
+    /// Every batch is one `learn` step, so this matches training on `dataset.chunks(batch_size)` of the same
+    /// samples. Returns the number of samples consumed.
+    pub fn learn_from_iter(
+        &mut self,
+        samples: impl IntoIterator<Item = Sample<T>>,
+        loss: &impl LossFn<T>,
+        rate: T,
+        batch_size: usize,
+    ) -> Result<usize, NetworkError> {
+        let batch_size = batch_size.max(1);
+        let mut batch = Vec::with_capacity(batch_size);
+        let mut consumed = 0;
+
+        for sample in samples {
+            batch.push(sample);
+
+            if batch.len() == batch_size {
+                self.learn(&batch, loss, rate)?;
+                consumed += batch.len();
+                batch.clear();
+            }
+        }
+
+        self.learn(&batch, loss, rate)?;
+        consumed += batch.len();
+
+        Ok(consumed)
+    }
+
+    pub fn layers(&self) -> &[Box<dyn NetworkLayer<T>>] {
+        &self.layers
+    }
+
+    #[inline]
+    pub fn layer(&self, index: usize) -> Option<&dyn NetworkLayer<T>> {
+        self.layers.get(index).map(AsRef::as_ref)
+    }
+
+    /// A layer to modify in place. Layers can't be replaced this way, since that could break the chain of sizes.
+    #[inline]
+    pub fn layer_mut(&mut self, index: usize) -> Option<&mut dyn NetworkLayer<T>> {
+        self.layers.get_mut(index).map(AsMut::as_mut)
+    }
+
+    /// Layer `index` if it's a dense layer.
+    #[inline]
+    pub fn dense_layer(&self, index: usize) -> Option<&Layer<T>> {
+        self.layers.get(index).and_then(|layer| layer.as_dense())
+    }
+
+    #[inline]
+    pub fn dense_layer_mut(&mut self, index: usize) -> Option<&mut Layer<T>> {
+        self.layers.get_mut(index).and_then(|layer| layer.as_dense_mut())
+    }
+
+    /// The dense layers in order, skipping layers of other kinds.
+    pub fn dense_layers(&self) -> impl Iterator<Item = &Layer<T>> {
+        self.layers.iter().filter_map(|layer| layer.as_dense())
+    }
+
+    /// The dense layers in order to modify in place, like `layer_mut`.
+    pub fn dense_layers_mut(&mut self) -> impl Iterator<Item = &mut Layer<T>> {
+        self.layers.iter_mut().filter_map(|layer| layer.as_dense_mut())
+    }
+
+    /// Gives every dense layer whose activation function is called `name` a copy of `replacement` instead, e.g.
+    /// `HardSigmoid` for `"sigmoid"` to run a trained network without `exp`, and returns how many layers
+    /// changed. Layers of other kinds, such as the dense layers inside a residual block, are left as they are.
+    pub fn replace_activation(&mut self, name: &str, replacement: &dyn ActivationFn<T>) -> usize {
+        let mut replaced = 0;
+
+        for layer in self.layers.iter_mut().filter_map(|layer| layer.as_dense_mut()) {
+            if layer.activation_fn().name() == name {
+                layer.set_activation_fn(replacement.clone_box());
+                replaced += 1;
+            }
+        }
+
+        replaced
+    }
+
+    /// Fails with `NotDense` naming the first layer that isn't a dense layer.
+    pub(crate) fn check_dense(&self) -> Result<(), NetworkError> {
+        match self.layers.iter().position(|layer| layer.as_dense().is_none()) {
+            Some(layer) => Err(NetworkError::NotDense { layer }),
+            None => Ok(()),
+        }
+    }
+
+    /// Fails with `BatchNormUnsupported` naming the first layer using batch normalization, for the paths
+    /// that backpropagate one sample at a time.
+    pub(crate) fn check_no_batch_norm(&self) -> Result<(), NetworkError> {
+        match self.layers.iter().position(|layer| layer.as_dense().is_some_and(|dense| dense.batch_norm().is_some())) {
+            Some(layer) => Err(NetworkError::BatchNormUnsupported { layer }),
+            None => Ok(()),
+        }
+    }
+
+    /// Discards the gradients accumulated since the last `apply_gradients`.
+    pub fn zero_gradients(&mut self) {
+        for layer in self.layers.iter_mut() {
+            layer.zero_gradient();
+        }
+    }
+
+    /// Adds the accumulated gradients times `scale` and the layer's learning rate scale to the parameters
+    /// and zeroes the gradients. `learn` is `apply_gradients(-rate / total_weight)` after one `backpropagate`.
+    /// Layers sharing parameters are updated once with the sum of their gradients, see `share_parameters`.
+    pub fn apply_gradients(&mut self, scale: T) {
+        self.merge_shared_gradients();
+
+        for (layer, &lr_scale) in self.layers.iter_mut().zip(self.lr_scales.iter()) {
+            if lr_scale == T::zero() {
+                layer.zero_gradient();
+            } else {
+                layer.apply_gradient(scale * lr_scale);
+            }
+        }
+
+        self.sync_shared_parameters();
+    }
+
+    /// Multiplies the steps `apply_gradients`, and so `learn` and its variants, take for layer `index` by
+    /// `scale`, e.g. to fine-tune early layers slower than the output layer. Every layer starts at 1. A
+    /// scale of 0 freezes the layer: gradients still flow through it to the layers in front, but its
+    /// parameters stay exactly as they are, even when its gradient isn't finite.
+    pub fn set_layer_lr_scale(&mut self, index: usize, scale: T) -> Result<(), NetworkError> {
+        if index >= self.layers.len() {
+            return Err(NetworkError::LayerIndexOutOfRange {
+                index,
+                layers: self.layers.len(),
+            });
+        }
+
+        if !scale.is_finite() || scale < T::zero() {
+            return Err(NetworkError::InvalidLrScale {
+                layer: index,
+                scale: scale.to_f64(),
+            });
+        }
+
+        self.lr_scales[index] = scale;
+        Ok(())
+    }
+
+    /// The learning rate scale of every layer, see `set_layer_lr_scale`.
+    #[inline]
+    pub fn layer_lr_scales(&self) -> &[T] { &self.lr_scales }
+
+    /// Freezes the layers in `layers` with `Layer::set_trainable(false)`, e.g. the pretrained layers of a
+    /// network being fine-tuned. They all have to be dense layers, otherwise nothing changes. A range that
+    /// ends before it starts fails with `InvalidLayerRange`.
+    pub fn freeze_layers(&mut self, layers: Range<usize>) -> Result<(), NetworkError> {
+        self.set_trainable(layers, false)
+    }
+
+    /// Undoes `freeze_layers`.
+    pub fn unfreeze_layers(&mut self, layers: Range<usize>) -> Result<(), NetworkError> {
+        self.set_trainable(layers, true)
+    }
+
+    fn set_trainable(&mut self, layers: Range<usize>, trainable: bool) -> Result<(), NetworkError> {
+        if layers.start > layers.end {
+            return Err(NetworkError::InvalidLayerRange {
+                start: layers.start,
+                end: layers.end,
+            });
+        }
+
+        if layers.end > self.layers.len() {
+            return Err(NetworkError::LayerIndexOutOfRange {
+                index: layers.end - 1,
+                layers: self.layers.len(),
+            });
+        }
+
+        if let Some(layer) = layers.clone().find(|&layer| self.layers[layer].as_dense().is_none()) {
+            return Err(NetworkError::NotDense { layer });
+        }
+
+        for layer in &mut self.layers[layers] {
+            layer.as_dense_mut().unwrap().set_trainable(trainable);
+        }
+
+        Ok(())
+    }
+
+    /// Sets the weight constraint of every dense layer, see `Layer::set_weight_constraint`.
+    pub fn set_weight_constraint(&mut self, constraint: Option<WeightConstraint>) -> Result<(), NetworkError> {
         for layer in self.layers.iter_mut() {
-            layer.apply_gradient(-rate / dataset.len() as f32);
+            if let Some(dense) = layer.as_dense_mut() {
+                dense.set_weight_constraint(constraint)?;
+            }
         }
 
         Ok(())
     }
-}
\ No newline at end of file
+
+    #[inline]
+    pub fn input_size(&self) -> usize { self.layers.first().unwrap().input_size() }
+
+    #[inline]
+    pub fn output_size(&self) -> usize { self.layers.last().unwrap().output_size() }
+
+    #[inline]
+    pub fn num_layers(&self) -> usize { self.layers.len() }
+
+    /// The sizes the network would be constructed with: the input size followed by every layer's output size.
+    pub fn layer_sizes(&self) -> Vec<usize> {
+        core::iter::once(self.input_size())
+            .chain(self.layers.iter().map(|layer| layer.output_size()))
+            .collect()
+    }
+
+    /// The number of weights and biases, same as `parameter_count`.
+    #[inline]
+    pub fn num_parameters(&self) -> usize { self.parameter_count() }
+
+    /// The number of weights and biases, `(inputs + 1) * outputs` summed over the dense layers, plus the
+    /// `NetworkLayer::parameter_count` of every other layer. Shared parameters are counted once.
+    pub fn parameter_count(&self) -> usize {
+        self.owned_layers().map(|layer| layer.parameter_count()).sum()
+    }
+
+    /// All weights and biases as one flat vector, layer by layer, each dense layer's weight matrix in column-major
+    /// order (all weights of input 0, then of input 1, ...) followed by its biases. Other layers add what
+    /// `NetworkLayer::write_parameters` gives. Batch normalization parameters aren't included, and layers that
+    /// share the parameters of an earlier layer are skipped.
+    pub fn get_params(&self) -> Vec<T> {
+        let mut params = Vec::with_capacity(self.parameter_count());
+
+        for layer in self.owned_layers() {
+            layer.write_parameters(&mut params);
+        }
+
+        params
+    }
+
+    /// Every layer except those sharing the parameters of an earlier one.
+    pub(crate) fn owned_layers(&self) -> impl Iterator<Item = &Box<dyn NetworkLayer<T>>> {
+        self.layers.iter().zip(self.shared.iter()).filter(|(_, shared)| shared.is_none()).map(|(layer, _)| layer)
+    }
+
+    /// Sets all weights and biases from a flat vector in the order of `get_params`.
+    pub fn set_params(&mut self, params: &[T]) -> Result<(), NetworkError> {
+        if params.len() != self.parameter_count() {
+            return Err(NetworkError::ParameterCountMismatch {
+                expected: self.parameter_count(),
+                given: params.len(),
+            });
+        }
+
+        let mut rest = params;
+        for (layer, shared) in self.layers.iter_mut().zip(self.shared.iter()) {
+            if shared.is_none() {
+                let (layer_params, tail) = rest.split_at(layer.parameter_count());
+                layer.read_parameters(layer_params);
+                rest = tail;
+            }
+        }
+
+        self.sync_shared_parameters();
+        Ok(())
+    }
+
+    /// Copies the weights and biases of every layer, without any gradient or cache state.
+    pub fn parameter_snapshot(&self) -> ParameterSnapshot<T> {
+        let layers = self.layers
+            .iter()
+            .map(|layer| match layer.as_dense() {
+                Some(dense) => LayerSnapshot::Dense(Box::new(dense.parameters())),
+                None => LayerSnapshot::Other(layer.flat_parameters()),
+            })
+            .collect();
+
+        ParameterSnapshot { layers }
+    }
+
+    /// Restores parameters taken by `parameter_snapshot`. Nothing is changed if any layer's shape doesn't match.
+    pub fn restore_snapshot(&mut self, snapshot: &ParameterSnapshot<T>) -> Result<(), NetworkError> {
+        if snapshot.layers.len() != self.layers.len() {
+            return Err(NetworkError::SnapshotLayerCountMismatch {
+                network_layers: self.layers.len(),
+                snapshot_layers: snapshot.layers.len(),
+            });
+        }
+
+        for (i, (layer, parameters)) in self.layers.iter().zip(snapshot.layers.iter()).enumerate() {
+            match (layer.as_dense(), parameters) {
+                (Some(dense), LayerSnapshot::Dense(parameters)) => dense.check_parameters(parameters)?,
+                (None, LayerSnapshot::Other(parameters)) if parameters.len() == layer.parameter_count() => {}
+                _ => return Err(NetworkError::ArchitectureMismatch { layer: i }),
+            }
+        }
+
+        for (layer, parameters) in self.layers.iter_mut().zip(snapshot.layers.iter()) {
+            match parameters {
+                LayerSnapshot::Dense(parameters) => layer.as_dense_mut().unwrap().set_parameters(parameters)?,
+                LayerSnapshot::Other(parameters) => layer.read_parameters(parameters),
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns the mean loss over the dataset without accumulating any gradients. Sample weights are ignored.
+    /// It runs `predict`, so neither gradients nor the state recorded by `forward` are touched.
+    pub fn evaluate(&self, dataset: &[Sample<T>], loss: &impl LossFn<T>) -> Result<T, NetworkError> {
+        if dataset.is_empty() {
+            return Err(NetworkError::EmptyDataset);
+        }
+
+        self.check_loss(loss)?;
+        let mut total_loss = T::zero();
+
+        for sample in dataset.iter() {
+            let outputs = self.predict(sample.inputs())?;
+            total_loss += loss.apply(outputs.as_view(), sample.expected_outputs())?;
+        }
+
+        Ok(total_loss / T::from_count(dataset.len()))
+    }
+}
+
+impl Network {
+    /// Builds a network whose layers are initialized by the initializer, with fan based schemes
+    /// scaled to every layer's own input and output sizes.
+    pub fn with_init(
+        layer_sizes: &[usize],
+        activation_fn: Box<dyn ActivationFn>,
+        initializer: Initializer,
+        rng: &mut impl Rng,
+    ) -> Result<Self, NetworkError> {
+        check_layer_sizes(layer_sizes)?;
+
+        let layers: Vec<Layer> = construct_layers(layer_sizes, |input_size, output_size| initializer.layer(
+            input_size,
+            output_size,
+            activation_fn.clone(),
+            rng,
+        ))?;
+
+        Ok(Self::from_dense_layers(layers))
+    }
+}
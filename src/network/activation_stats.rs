@@ -0,0 +1,173 @@
+//! Per-unit activation statistics over a dataset, to find units that stopped contributing: dead ones stuck at
+//! the lowest value of their activation function, like a ReLU unit that never fires, and saturated ones stuck
+//! at either end, like a sigmoid unit always near 0 or 1, where the derivative and so the gradient vanish.
+
+use crate::{dataset::Sample, prelude::*, scalar::Scalar};
+
+use super::{Network, NetworkError};
+
+/// When `activation_stats` counts a unit as dead or saturated.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SaturationThresholds<T: Scalar = f32> {
+    /// An activation within this distance of a bound of the activation function's `range` is at that bound.
+    pub epsilon: T,
+    /// A unit at a bound for at least this fraction of the samples is stuck there.
+    pub fraction: T,
+}
+
+impl<T: Scalar> Default for SaturationThresholds<T> {
+    fn default() -> Self {
+        Self {
+            epsilon: T::constant(1e-2),
+            fraction: T::constant(0.99),
+        }
+    }
+}
+
+/// The activations of one unit over a dataset.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct NeuronActivationStats<T: Scalar = f32> {
+    pub mean: T,
+    /// The population variance.
+    pub variance: T,
+    pub min: T,
+    pub max: T,
+    /// The fraction of samples for which the activation is at the lower bound, 0 without one.
+    pub at_lower_bound: T,
+    /// The fraction of samples for which the activation is at the upper bound, 0 without one.
+    pub at_upper_bound: T,
+    /// Stuck at the lower bound, so it passes on the same value whatever the input.
+    pub dead: bool,
+    /// Stuck at the lower or upper bound, which includes dead units.
+    pub saturated: bool,
+}
+
+/// The activation statistics of one layer's units, in the order of its outputs.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LayerActivationStats<T: Scalar = f32> {
+    /// The `ActivationFn::range` of the layer's activation function, `(None, None)` for layers without one.
+    pub range: (Option<T>, Option<T>),
+    pub neurons: Vec<NeuronActivationStats<T>>,
+}
+
+impl<T: Scalar> LayerActivationStats<T> {
+    /// The indices of the dead units.
+    pub fn dead(&self) -> Vec<usize> {
+        self.neurons.iter().enumerate().filter(|(_, neuron)| neuron.dead).map(|(i, _)| i).collect()
+    }
+
+    /// The indices of the saturated units, dead ones included.
+    pub fn saturated(&self) -> Vec<usize> {
+        self.neurons.iter().enumerate().filter(|(_, neuron)| neuron.saturated).map(|(i, _)| i).collect()
+    }
+}
+
+impl<T: Scalar> Network<T> {
+    /// `activation_stats_with` the default thresholds: a unit within 0.01 of a bound for 99% of the samples.
+    pub fn activation_stats(&self, dataset: &[Sample<T>]) -> Result<Vec<LayerActivationStats<T>>, NetworkError> {
+        self.activation_stats_with(dataset, &SaturationThresholds::default())
+    }
+
+    /// The statistics of every unit's activation over the dataset, one entry per layer. The bounds are the
+    /// `ActivationFn::range` of each layer's activation function, so units of layers without one, or of an
+    /// unbounded function like `Identity`, are never dead or saturated. It runs `predict`, so neither gradients
+    /// nor the state recorded by `forward` are touched. Fails with `EmptyDataset` for an empty dataset.
+    pub fn activation_stats_with(
+        &self,
+        dataset: &[Sample<T>],
+        thresholds: &SaturationThresholds<T>,
+    ) -> Result<Vec<LayerActivationStats<T>>, NetworkError> {
+        if dataset.is_empty() {
+            return Err(NetworkError::EmptyDataset);
+        }
+
+        self.check_dataset(dataset)?;
+
+        let ranges: Vec<(Option<T>, Option<T>)> = self.layers
+            .iter()
+            .map(|layer| layer.activation_fn().map_or((None, None), |activation_fn| activation_fn.range()))
+            .collect();
+
+        let mut accumulators: Vec<Vec<Accumulator<T>>> = self.layers
+            .iter()
+            .map(|layer| vec![Accumulator::new(); layer.output_size()])
+            .collect();
+
+        for sample in dataset {
+            let mut activations = sample.inputs().into_owned();
+
+            for ((layer, units), &(lower, upper)) in self.layers.iter().zip(accumulators.iter_mut()).zip(ranges.iter()) {
+                activations = layer.predict(activations.as_view())?;
+
+                for (unit, &activation) in units.iter_mut().zip(activations.iter()) {
+                    let near = |bound: Option<T>| bound.is_some_and(|bound| (activation - bound).abs() <= thresholds.epsilon);
+                    unit.push(activation, near(lower), near(upper));
+                }
+            }
+        }
+
+        let samples = T::from_count(dataset.len());
+
+        Ok(accumulators
+            .into_iter()
+            .zip(ranges)
+            .map(|(units, range)| LayerActivationStats {
+                range,
+                neurons: units.iter().map(|unit| unit.stats(samples, thresholds.fraction)).collect(),
+            })
+            .collect())
+    }
+}
+
+/// Welford's running mean and variance of one unit, with its extremes and how often it was at a bound.
+#[derive(Clone)]
+struct Accumulator<T: Scalar> {
+    count: T,
+    mean: T,
+    squared_deviations: T,
+    min: T,
+    max: T,
+    at_lower_bound: usize,
+    at_upper_bound: usize,
+}
+
+impl<T: Scalar> Accumulator<T> {
+    fn new() -> Self {
+        Self {
+            count: T::zero(),
+            mean: T::zero(),
+            squared_deviations: T::zero(),
+            min: T::constant(f64::INFINITY),
+            max: T::constant(f64::NEG_INFINITY),
+            at_lower_bound: 0,
+            at_upper_bound: 0,
+        }
+    }
+
+    fn push(&mut self, activation: T, at_lower_bound: bool, at_upper_bound: bool) {
+        self.count += T::one();
+        let deviation = activation - self.mean;
+        self.mean += deviation / self.count;
+        self.squared_deviations += deviation * (activation - self.mean);
+        self.min = self.min.min(activation);
+        self.max = self.max.max(activation);
+        self.at_lower_bound += usize::from(at_lower_bound);
+        self.at_upper_bound += usize::from(at_upper_bound);
+    }
+
+    fn stats(&self, samples: T, fraction: T) -> NeuronActivationStats<T> {
+        let at_lower_bound = T::from_count(self.at_lower_bound) / samples;
+        let at_upper_bound = T::from_count(self.at_upper_bound) / samples;
+
+        NeuronActivationStats {
+            mean: self.mean,
+            variance: self.squared_deviations / samples,
+            min: self.min,
+            max: self.max,
+            at_lower_bound,
+            at_upper_bound,
+            dead: at_lower_bound >= fraction,
+            saturated: at_lower_bound + at_upper_bound >= fraction,
+        }
+    }
+}
@@ -0,0 +1,197 @@
+use core::ops::Range;
+
+use rand::{distr::Distribution, Rng};
+
+use crate::{activations::ActivationFn, prelude::*};
+
+use super::{layer::Layer, Network, NetworkError, NetworkLayer};
+
+impl Network {
+    /// Inserts a layer of `size` outputs before layer `index` (after the last layer for `index == num_layers()`).
+    /// Its weights are an identity matrix, padded with zeros when it has more outputs than inputs, and its
+    /// biases zero. The layer after it takes `size` inputs from now on, its weights for new inputs are zero,
+    /// so the network computes exactly what it did before. That only holds for the identity activation
+    /// function and at least as many outputs as inputs, anything else fails with `InsertionChangesFunction`;
+    /// training can then move the new layer away from the identity. Inserting after the last layer is only
+    /// possible when `size` keeps the network's output size, and the layer after it has to be a dense layer.
+    pub fn insert_layer(&mut self, index: usize, size: usize, activation_fn: Box<dyn ActivationFn>) -> Result<(), NetworkError> {
+        if index > self.layers.len() {
+            return Err(NetworkError::LayerIndexOutOfRange {
+                index,
+                layers: self.layers.len(),
+            });
+        }
+
+        if size == 0 {
+            return Err(NetworkError::ZeroLayerSize(index + 1));
+        }
+
+        if index == self.layers.len() && size != self.output_size() {
+            return Err(NetworkError::InterfaceChange);
+        }
+
+        let input_size = self.layer_sizes()[index];
+        if activation_fn.name() != "identity" || size < input_size {
+            return Err(NetworkError::InsertionChangesFunction {
+                size,
+                inputs: input_size,
+                activation: activation_fn.name(),
+            });
+        }
+
+        self.check_dense_range(index..index + 1)?;
+        self.check_unshared(index)?;
+
+        let mut layer = Layer::zeros(input_size, size, activation_fn)?;
+        layer.weights_mut().fill_with_identity();
+
+        if let Some(next) = self.dense_layer_mut(index) {
+            next.resize(size, next.output_size(), || 0.0);
+        }
+
+        self.layers.insert(index, Box::new(layer));
+        self.lr_scales.insert(index, 1.0);
+        self.insert_shared(index);
+        Ok(())
+    }
+
+    /// Removes layer `index`. The layer after it takes the removed layer's inputs from now on: weights for
+    /// inputs it already had are kept, those for new inputs are zero. The last layer can only be removed
+    /// when that doesn't change the network's output size. The layer after it has to be a dense layer.
+    pub fn remove_layer(&mut self, index: usize) -> Result<Box<dyn NetworkLayer>, NetworkError> {
+        if index >= self.layers.len() {
+            return Err(NetworkError::LayerIndexOutOfRange {
+                index,
+                layers: self.layers.len(),
+            });
+        }
+
+        if self.layers.len() == 1 {
+            return Err(NetworkError::TooFewLayers(1));
+        }
+
+        let input_size = self.layers[index].input_size();
+        if index == self.layers.len() - 1 && input_size != self.output_size() {
+            return Err(NetworkError::InterfaceChange);
+        }
+
+        self.check_dense_range(index + 1..index + 2)?;
+        self.check_unshared(index + 1)?;
+        let removed = self.layers.remove(index);
+        self.lr_scales.remove(index);
+        self.remove_shared(index);
+
+        if let Some(next) = self.dense_layer_mut(index) {
+            next.resize(input_size, next.output_size(), || 0.0);
+        }
+
+        Ok(removed)
+    }
+
+    /// Changes the output size of hidden layer `index` to `new_size`, along with the input size of the layer after it.
+    /// Existing weights and biases are kept, new ones are drawn from the distribution. Shrinking drops the
+    /// last outputs. The output layer can't be resized, since that would change the network's output size.
+    /// Both layers have to be dense layers.
+    #[cfg(feature = "std")]
+    pub fn widen_layer(&mut self, index: usize, new_size: usize, distribution: &impl Distribution<f32>) -> Result<(), NetworkError> {
+        self.widen_layer_with_rng(index, new_size, distribution, &mut rand::rng())
+    }
+
+    /// Like `widen_layer`, but draws the new parameters from the given RNG.
+    pub fn widen_layer_with_rng(
+        &mut self,
+        index: usize,
+        new_size: usize,
+        distribution: &impl Distribution<f32>,
+        rng: &mut impl Rng,
+    ) -> Result<(), NetworkError> {
+        if index >= self.layers.len() {
+            return Err(NetworkError::LayerIndexOutOfRange {
+                index,
+                layers: self.layers.len(),
+            });
+        }
+
+        if index == self.layers.len() - 1 {
+            return Err(NetworkError::InterfaceChange);
+        }
+
+        if new_size == 0 {
+            return Err(NetworkError::ZeroLayerSize(index + 1));
+        }
+
+        self.check_dense_range(index..index + 2)?;
+        self.check_unshared(index)?;
+        self.check_unshared(index + 1)?;
+
+        let layer = self.dense_layer_mut(index).unwrap();
+        layer.resize(layer.input_size(), new_size, || distribution.sample(rng));
+
+        let next = self.dense_layer_mut(index + 1).unwrap();
+        next.resize(new_size, next.output_size(), || distribution.sample(rng));
+
+        Ok(())
+    }
+
+    /// Swaps the output layer for a new dense layer of `new_output_size` outputs reading the same inputs,
+    /// with weights and biases drawn from the distribution, e.g. to reuse the hidden layers of a trained
+    /// network for a task with other outputs. Every other layer stays as it is.
+    #[cfg(feature = "std")]
+    pub fn replace_output_layer(
+        &mut self,
+        new_output_size: usize,
+        activation_fn: Box<dyn ActivationFn>,
+        distribution: &impl Distribution<f32>,
+    ) -> Result<(), NetworkError> {
+        self.replace_output_layer_with_rng(new_output_size, activation_fn, distribution, &mut rand::rng())
+    }
+
+    /// Like `replace_output_layer`, but draws the new parameters from the given RNG.
+    pub fn replace_output_layer_with_rng(
+        &mut self,
+        new_output_size: usize,
+        activation_fn: Box<dyn ActivationFn>,
+        distribution: &impl Distribution<f32>,
+        rng: &mut impl Rng,
+    ) -> Result<(), NetworkError> {
+        if new_output_size == 0 {
+            return Err(NetworkError::ZeroLayerSize(self.layers.len()));
+        }
+
+        let input_size = self.layers.last().unwrap().input_size();
+        let layer = Layer::random_with_rng(input_size, new_output_size, activation_fn, distribution, rng)?;
+
+        *self.layers.last_mut().unwrap() = Box::new(layer);
+        *self.lr_scales.last_mut().unwrap() = 1.0;
+        self.detach_shared(self.layers.len() - 1);
+        Ok(())
+    }
+
+    /// Keeps the first `num_layers` layers and returns the others, so the network's outputs are those of
+    /// layer `num_layers - 1` from now on. `replace_output_layer` then swaps that layer for one sized for a new task.
+    pub fn truncate_to(&mut self, num_layers: usize) -> Result<Vec<Box<dyn NetworkLayer>>, NetworkError> {
+        if num_layers == 0 {
+            return Err(NetworkError::NoLayers);
+        }
+
+        if num_layers > self.layers.len() {
+            return Err(NetworkError::LayerIndexOutOfRange {
+                index: num_layers - 1,
+                layers: self.layers.len(),
+            });
+        }
+
+        self.lr_scales.truncate(num_layers);
+        // Groups start at their lowest layer, so every layer that's kept still has the layer it shares with.
+        self.shared.truncate(num_layers);
+        Ok(self.layers.split_off(num_layers))
+    }
+
+    /// Fails with `NotDense` for the first of the existing `layers` that isn't a dense layer.
+    fn check_dense_range(&self, layers: Range<usize>) -> Result<(), NetworkError> {
+        match (layers.start..layers.end.min(self.layers.len())).find(|&layer| self.layers[layer].as_dense().is_none()) {
+            Some(layer) => Err(NetworkError::NotDense { layer }),
+            None => Ok(()),
+        }
+    }
+}
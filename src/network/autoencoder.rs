@@ -0,0 +1,314 @@
+//! Autoencoders with tied weights. Stage `i` of the encoder maps `sizes[i]` values to `sizes[i + 1]` with
+//! the weight matrix `W[i]`, and the decoder runs the stages in reverse with `W[i]` transposed, each with
+//! its own biases. Every `W[i]` is stored once and used by both passes, so the gradients of the encoder
+//! and decoder stage accumulate into the same matrix, and an update moves both.
+
+use nalgebra::{DMatrix, DVector, DVectorView};
+use rand::Rng;
+
+use crate::{activations::ActivationFn, losses::LossFn, prelude::*, scalar::Scalar};
+
+use super::{check_layer_sizes, initializer::Initializer, layer::weighted_sum_gradient, NetworkError};
+
+/// A symmetric autoencoder whose decoder uses the transposed encoder weights, trained to reconstruct its inputs.
+#[derive(Clone)]
+pub struct Autoencoder<T: Scalar = f32> {
+    /// `W[i]`, with one row per output and one column per input of encoder stage `i`.
+    weights: Vec<DMatrix<T>>,
+    weight_gradients: Vec<DMatrix<T>>,
+    encoder_biases: Vec<DVector<T>>,
+    encoder_bias_gradients: Vec<DVector<T>>,
+    /// The biases of the decoder stage using `W[i]`, one per input of encoder stage `i`.
+    decoder_biases: Vec<DVector<T>>,
+    decoder_bias_gradients: Vec<DVector<T>>,
+    activation_fn: Box<dyn ActivationFn<T>>,
+}
+
+impl<T: Scalar> core::fmt::Debug for Autoencoder<T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Autoencoder")
+            .field("sizes", &self.sizes())
+            .field("activation_fn", &self.activation_fn.name())
+            .finish()
+    }
+}
+
+/// The weighted sums and activations of every stage of one pass, encoder stages first.
+struct Pass<T: Scalar> {
+    weighted_sums: Vec<DVector<T>>,
+    activations: Vec<DVector<T>>,
+}
+
+impl Autoencoder {
+    /// An autoencoder that encodes `sizes[0]` inputs down to `sizes.last()` through the given sizes and decodes
+    /// back up through them in reverse, with `activation_fn` after every stage, including the reconstruction.
+    /// The weights are drawn with the initializer recommended for the activation function and the biases
+    /// start at zero. Draws from the thread-local RNG, see `tied_with_rng` for reproducible autoencoders.
+    #[cfg(feature = "std")]
+    pub fn tied(sizes: &[usize], activation_fn: Box<dyn ActivationFn>) -> Result<Self, NetworkError> {
+        Self::tied_with_rng(sizes, activation_fn, &mut rand::rng())
+    }
+
+    /// Like `tied`, but draws every weight from the given RNG.
+    pub fn tied_with_rng(sizes: &[usize], activation_fn: Box<dyn ActivationFn>, rng: &mut impl Rng) -> Result<Self, NetworkError> {
+        check_layer_sizes(sizes)?;
+        let initializer = Initializer::recommended_for(activation_fn.as_ref());
+
+        let weights = sizes
+            .windows(2)
+            .map(|stage| {
+                let layer = initializer.layer(stage[0], stage[1], activation_fn.clone(), rng)?;
+                Ok(layer.weights().into_owned())
+            })
+            .collect::<Result<Vec<_>, NetworkError>>()?;
+
+        Ok(Self::from_weights(weights, activation_fn))
+    }
+}
+
+impl<T: Scalar> Autoencoder<T> {
+    fn from_weights(weights: Vec<DMatrix<T>>, activation_fn: Box<dyn ActivationFn<T>>) -> Self {
+        let zero_gradients = weights.iter().map(|weights| DMatrix::zeros(weights.nrows(), weights.ncols())).collect();
+        let encoder_biases: Vec<_> = weights.iter().map(|weights| DVector::zeros(weights.nrows())).collect();
+        let decoder_biases: Vec<_> = weights.iter().map(|weights| DVector::zeros(weights.ncols())).collect();
+
+        Self {
+            weight_gradients: zero_gradients,
+            encoder_bias_gradients: encoder_biases.clone(),
+            decoder_bias_gradients: decoder_biases.clone(),
+            weights,
+            encoder_biases,
+            decoder_biases,
+            activation_fn,
+        }
+    }
+
+    /// The encoder's sizes from the input to the code.
+    pub fn sizes(&self) -> Vec<usize> {
+        core::iter::once(self.input_size()).chain(self.weights.iter().map(|weights| weights.nrows())).collect()
+    }
+
+    #[inline]
+    pub fn input_size(&self) -> usize {
+        self.weights[0].ncols()
+    }
+
+    #[inline]
+    pub fn code_size(&self) -> usize {
+        self.weights.last().unwrap().nrows()
+    }
+
+    /// `W[i]` for every encoder stage, shared with the decoder.
+    #[inline]
+    pub fn weights(&self) -> &[DMatrix<T>] {
+        &self.weights
+    }
+
+    /// The weights, e.g. to perturb them for a gradient check. Changing a shape breaks the autoencoder.
+    #[inline]
+    pub fn weights_mut(&mut self) -> &mut [DMatrix<T>] {
+        &mut self.weights
+    }
+
+    /// The gradients accumulated for every `W[i]`, the sum of the encoder and the decoder stage's gradient.
+    #[inline]
+    pub fn weight_gradients(&self) -> &[DMatrix<T>] {
+        &self.weight_gradients
+    }
+
+    #[inline]
+    pub fn encoder_biases(&self) -> &[DVector<T>] {
+        &self.encoder_biases
+    }
+
+    /// The decoder biases by the encoder stage whose weights the decoder stage uses.
+    #[inline]
+    pub fn decoder_biases(&self) -> &[DVector<T>] {
+        &self.decoder_biases
+    }
+
+    /// Every weight counted once, as it's shared, plus both passes' biases.
+    pub fn parameter_count(&self) -> usize {
+        self.weights.iter().map(|weights| weights.len() + weights.nrows() + weights.ncols()).sum()
+    }
+
+    /// The code of an input.
+    pub fn encode(&self, input: DVectorView<T>) -> Result<DVector<T>, NetworkError> {
+        self.check_input(0, input.len(), self.input_size())?;
+
+        Ok(self.weights.iter().zip(&self.encoder_biases).fold(input.into_owned(), |activations, (weights, biases)| {
+            self.activate(weights * activations + biases).1
+        }))
+    }
+
+    /// The reconstruction of a code.
+    pub fn decode(&self, code: DVectorView<T>) -> Result<DVector<T>, NetworkError> {
+        self.check_input(0, code.len(), self.code_size())?;
+
+        Ok(self.weights.iter().zip(&self.decoder_biases).rev().fold(code.into_owned(), |activations, (weights, biases)| {
+            self.activate(weights.tr_mul(&activations) + biases).1
+        }))
+    }
+
+    /// `decode(encode(input))`.
+    pub fn reconstruct(&self, input: DVectorView<T>) -> Result<DVector<T>, NetworkError> {
+        self.decode(self.encode(input)?.as_view())
+    }
+
+    /// Accumulates the gradient of the reconstruction loss summed over the inputs, with every input as its own
+    /// expected output, and returns that sum. On an error the gradients accumulated so far are discarded.
+    pub fn backpropagate(&mut self, inputs: &[DVector<T>], loss: &impl LossFn<T>) -> Result<T, NetworkError> {
+        self.check_loss(loss)?;
+        for (index, input) in inputs.iter().enumerate() {
+            self.check_input(index, input.len(), self.input_size())?;
+        }
+
+        let mut total_loss = T::zero();
+
+        for input in inputs {
+            match self.backpropagate_input(input, loss) {
+                Ok(input_loss) => total_loss += input_loss,
+                Err(error) => {
+                    self.zero_gradients();
+                    return Err(error);
+                }
+            }
+        }
+
+        Ok(total_loss)
+    }
+
+    /// One gradient descent step over the inputs, returning the mean reconstruction loss before the update.
+    pub fn learn(&mut self, inputs: &[DVector<T>], loss: &impl LossFn<T>, rate: T) -> Result<T, NetworkError> {
+        let total_loss = self.backpropagate(inputs, loss)?;
+
+        if inputs.is_empty() {
+            return Ok(T::zero());
+        }
+
+        let count = T::from_count(inputs.len());
+        self.apply_gradients(-rate / count);
+
+        Ok(total_loss / count)
+    }
+
+    /// Adds the accumulated gradients times `scale` to the parameters and zeroes the gradients.
+    pub fn apply_gradients(&mut self, scale: T) {
+        for (weights, gradient) in self.weights.iter_mut().zip(&self.weight_gradients) {
+            *weights += gradient * scale;
+        }
+
+        for (biases, gradient) in self
+            .encoder_biases
+            .iter_mut()
+            .zip(&self.encoder_bias_gradients)
+            .chain(self.decoder_biases.iter_mut().zip(&self.decoder_bias_gradients))
+        {
+            *biases += gradient * scale;
+        }
+
+        self.zero_gradients();
+    }
+
+    /// Discards the gradients accumulated since the last `apply_gradients`.
+    pub fn zero_gradients(&mut self) {
+        self.weight_gradients.iter_mut().for_each(|gradient| gradient.fill(T::zero()));
+        self.encoder_bias_gradients
+            .iter_mut()
+            .chain(self.decoder_bias_gradients.iter_mut())
+            .for_each(|gradient| gradient.fill(T::zero()));
+    }
+
+    fn backpropagate_input(&mut self, input: &DVector<T>, loss: &impl LossFn<T>) -> Result<T, NetworkError> {
+        let pass = self.forward(input);
+        let stages = self.weights.len();
+        let reconstruction = pass.activations.last().unwrap().as_view();
+
+        let input_loss = loss.apply(reconstruction, input.as_view())?;
+        let mut activation_gradient = loss.partial_gradient(reconstruction, input.as_view())?;
+
+        // The decoder stage `stages + j` uses `W[stages - 1 - j]` transposed, so its weight gradient
+        // `deltas * inputs^T` is added to `W` as `inputs * deltas^T`.
+        for j in (0..stages).rev() {
+            let stage = stages - 1 - j;
+            let deltas = self.deltas(&pass, stages + j, &activation_gradient);
+
+            self.decoder_bias_gradients[stage] += &deltas;
+            self.weight_gradients[stage].ger(T::one(), &pass.activations[stages + j], &deltas, T::one());
+            activation_gradient = &self.weights[stage] * &deltas;
+        }
+
+        for stage in (0..stages).rev() {
+            let deltas = self.deltas(&pass, stage, &activation_gradient);
+
+            self.encoder_bias_gradients[stage] += &deltas;
+            self.weight_gradients[stage].ger(T::one(), &deltas, &pass.activations[stage], T::one());
+            activation_gradient = self.weights[stage].tr_mul(&deltas);
+        }
+
+        Ok(input_loss)
+    }
+
+    /// Runs an input through the encoder and the decoder, recording every stage. `activations[k]` is the
+    /// input of stage `k`, so it starts with the input itself and ends with the reconstruction.
+    fn forward(&self, input: &DVector<T>) -> Pass<T> {
+        let stages = self.weights.len();
+        let mut pass = Pass {
+            weighted_sums: Vec::with_capacity(2 * stages),
+            activations: Vec::with_capacity(2 * stages + 1),
+        };
+
+        pass.activations.push(input.clone());
+
+        for stage in 0..2 * stages {
+            let previous = pass.activations.last().unwrap();
+            let weighted_sums = if stage < stages {
+                &self.weights[stage] * previous + &self.encoder_biases[stage]
+            } else {
+                let stage = 2 * stages - 1 - stage;
+                self.weights[stage].tr_mul(previous) + &self.decoder_biases[stage]
+            };
+
+            let (weighted_sums, activations) = self.activate(weighted_sums);
+            pass.weighted_sums.push(weighted_sums);
+            pass.activations.push(activations);
+        }
+
+        pass
+    }
+
+    /// The gradient with respect to stage `k`'s weighted sums, given the gradient of its activations.
+    fn deltas(&self, pass: &Pass<T>, k: usize, activation_gradient: &DVector<T>) -> DVector<T> {
+        weighted_sum_gradient(
+            self.activation_fn.as_ref(),
+            pass.weighted_sums[k].as_view(),
+            pass.activations[k + 1].as_view(),
+            activation_gradient.as_view(),
+        )
+    }
+
+    fn activate(&self, weighted_sums: DVector<T>) -> (DVector<T>, DVector<T>) {
+        let mut activations = weighted_sums.clone();
+        self.activation_fn.apply_slice(activations.as_mut_slice());
+        (weighted_sums, activations)
+    }
+
+    fn check_input(&self, index: usize, inputs: usize, network_inputs: usize) -> Result<(), NetworkError> {
+        if inputs != network_inputs {
+            return Err(NetworkError::InputSizeMismatch { index, inputs, network_inputs });
+        }
+
+        Ok(())
+    }
+
+    /// The reconstruction comes out of `activation_fn`, so it has to be the activation the loss requires.
+    fn check_loss(&self, loss: &impl LossFn<T>) -> Result<(), NetworkError> {
+        match loss.output_activation() {
+            Some(required) if required != self.activation_fn.name() => Err(NetworkError::OutputActivationMismatch {
+                required,
+                activation: self.activation_fn.name(),
+            }),
+            _ => Ok(()),
+        }
+    }
+}
@@ -0,0 +1,263 @@
+use nalgebra::{DMatrix, DVector, DVectorView};
+
+use crate::scalar::Scalar;
+
+use super::serialization::BatchNormRecord;
+
+/// Batch normalization of a layer's weighted sums, `gamma * (z - mean) / sqrt(variance + epsilon) + beta`.
+///
+/// Training on a batch normalizes with the statistics of that batch and updates the running mean and
+/// variance, every other pass (single sample `forward`, `forward_batch`, `evaluate`) uses the running ones.
+#[derive(Clone, Debug)]
+pub struct BatchNorm<T: Scalar = f32> {
+    gamma: DVector<T>,
+    gamma_gradient: DVector<T>,
+    beta: DVector<T>,
+    beta_gradient: DVector<T>,
+
+    running_mean: DVector<T>,
+    running_variance: DVector<T>,
+    momentum: T,
+    epsilon: T,
+
+    previous_normalized: DVector<T>,
+}
+
+#[derive(Clone)]
+pub(crate) struct BatchNormCache<T: Scalar = f32> {
+    normalized: DMatrix<T>,
+    inverse_std: DVector<T>,
+}
+
+impl<T: Scalar> BatchNorm<T> {
+    pub fn new(size: usize) -> Self {
+        Self {
+            gamma: DVector::from_element(size, T::one()),
+            gamma_gradient: DVector::zeros(size),
+            beta: DVector::zeros(size),
+            beta_gradient: DVector::zeros(size),
+
+            running_mean: DVector::zeros(size),
+            running_variance: DVector::from_element(size, T::one()),
+            momentum: T::constant(0.1),
+            epsilon: T::constant(1e-5),
+
+            previous_normalized: DVector::zeros(size),
+        }
+    }
+
+    /// Changes the number of normalized values, new ones start out like in `new`.
+    pub(crate) fn resize(&mut self, size: usize) {
+        let fresh = Self::new(size);
+        let old_size = self.size().min(size);
+        let resized = |old: &DVector<T>, new: &DVector<T>| {
+            DVector::from_fn(size, |i, _| if i < old_size { old[i] } else { new[i] })
+        };
+
+        self.gamma = resized(&self.gamma, &fresh.gamma);
+        self.beta = resized(&self.beta, &fresh.beta);
+        self.running_mean = resized(&self.running_mean, &fresh.running_mean);
+        self.running_variance = resized(&self.running_variance, &fresh.running_variance);
+        self.gamma_gradient = fresh.gamma_gradient;
+        self.beta_gradient = fresh.beta_gradient;
+        self.previous_normalized = fresh.previous_normalized;
+    }
+
+    /// How much every training batch moves the running statistics, 0.1 by default.
+    pub fn with_momentum(mut self, momentum: T) -> Self {
+        self.momentum = momentum.clamp(T::zero(), T::one());
+        self
+    }
+
+    #[inline]
+    pub fn size(&self) -> usize { self.gamma.len() }
+
+    #[inline]
+    pub fn gamma(&self) -> DVectorView<'_, T> { self.gamma.as_view() }
+
+    #[inline]
+    pub fn beta(&self) -> DVectorView<'_, T> { self.beta.as_view() }
+
+    #[inline]
+    pub fn get_gamma_mut(&mut self, index: usize) -> Option<&mut T> {
+        self.gamma.get_mut(index)
+    }
+
+    #[inline]
+    pub fn get_beta_mut(&mut self, index: usize) -> Option<&mut T> {
+        self.beta.get_mut(index)
+    }
+
+    /// The gamma gradient accumulated since the last `apply_gradient`.
+    #[inline]
+    pub fn gamma_gradient(&self) -> DVectorView<'_, T> { self.gamma_gradient.as_view() }
+
+    /// The beta gradient accumulated since the last `apply_gradient`.
+    #[inline]
+    pub fn beta_gradient(&self) -> DVectorView<'_, T> { self.beta_gradient.as_view() }
+
+    #[inline]
+    pub fn running_mean(&self) -> DVectorView<'_, T> { self.running_mean.as_view() }
+
+    #[inline]
+    pub fn running_variance(&self) -> DVectorView<'_, T> { self.running_variance.as_view() }
+
+    fn inverse_running_std(&self) -> DVector<T> {
+        self.running_variance.map(|variance| T::one() / (variance + self.epsilon).sqrt())
+    }
+
+    /// The derivative of `normalize` for every value, which only scales them.
+    pub(crate) fn inference_scale(&self) -> DVector<T> {
+        self.inverse_running_std().component_mul(&self.gamma)
+    }
+
+    /// Folds the running statistics normalization into the weights and biases it follows.
+    pub(crate) fn fold(&self, weights: &mut DMatrix<T>, biases: &mut DVector<T>) {
+        let scale = self.running_variance.zip_map(&self.gamma, |variance, gamma| {
+            gamma / (variance + self.epsilon).sqrt()
+        });
+
+        for (mut row, &scale) in weights.row_iter_mut().zip(scale.iter()) {
+            row *= scale;
+        }
+
+        *biases = (&*biases - &self.running_mean).component_mul(&scale) + &self.beta;
+    }
+
+    pub(crate) fn normalize(&self, weighted_sums: &DVector<T>) -> DVector<T> {
+        let normalized = (weighted_sums - &self.running_mean).component_mul(&self.inverse_running_std());
+        normalized.component_mul(&self.gamma) + &self.beta
+    }
+
+    /// `normalize` in place.
+    pub(crate) fn normalize_mut(&self, weighted_sums: &mut DVector<T>) {
+        for (i, x) in weighted_sums.iter_mut().enumerate() {
+            let normalized = (*x - self.running_mean[i]) / (self.running_variance[i] + self.epsilon).sqrt();
+            *x = normalized * self.gamma[i] + self.beta[i];
+        }
+    }
+
+    pub(crate) fn normalize_batch(&self, weighted_sums: &mut DMatrix<T>) {
+        let inverse_std = self.inverse_running_std();
+
+        for mut column in weighted_sums.column_iter_mut() {
+            column -= &self.running_mean;
+            column.component_mul_assign(&inverse_std);
+            column.component_mul_assign(&self.gamma);
+            column += &self.beta;
+        }
+    }
+
+    pub(crate) fn forward(&mut self, weighted_sums: &DVector<T>) -> DVector<T> {
+        self.previous_normalized = (weighted_sums - &self.running_mean).component_mul(&self.inverse_running_std());
+        self.previous_normalized.component_mul(&self.gamma) + &self.beta
+    }
+
+    /// Gradient through the running statistics normalization of the last `forward`, which is a fixed affine map.
+    pub(crate) fn backward(&mut self, output_partial_gradient: &DVector<T>) -> DVector<T> {
+        self.gamma_gradient += output_partial_gradient.component_mul(&self.previous_normalized);
+        self.beta_gradient += output_partial_gradient;
+
+        output_partial_gradient
+            .component_mul(&self.gamma)
+            .component_mul(&self.inverse_running_std())
+    }
+
+    pub(crate) fn forward_training(&mut self, weighted_sums: &mut DMatrix<T>) -> BatchNormCache<T> {
+        let batch_size = T::from_count(weighted_sums.ncols());
+        let mean = weighted_sums.column_mean();
+        let mut variance = DVector::zeros(self.size());
+
+        for column in weighted_sums.column_iter() {
+            variance += (column - &mean).map(|x| x * x);
+        }
+        variance /= batch_size;
+
+        let inverse_std = variance.map(|variance| T::one() / (variance + self.epsilon).sqrt());
+
+        for mut column in weighted_sums.column_iter_mut() {
+            column -= &mean;
+            column.component_mul_assign(&inverse_std);
+        }
+
+        let normalized = weighted_sums.clone();
+
+        for mut column in weighted_sums.column_iter_mut() {
+            column.component_mul_assign(&self.gamma);
+            column += &self.beta;
+        }
+
+        self.running_mean = &self.running_mean * (T::one() - self.momentum) + mean * self.momentum;
+        self.running_variance = &self.running_variance * (T::one() - self.momentum) + variance * self.momentum;
+
+        BatchNormCache { normalized, inverse_std }
+    }
+
+    /// Gradient through the batch statistics, including their dependence on every sample of the batch.
+    pub(crate) fn backward_training(&mut self, cache: &BatchNormCache<T>, output_partial_gradient: &DMatrix<T>) -> DMatrix<T> {
+        let batch_size = T::from_count(output_partial_gradient.ncols());
+
+        for (gradient, normalized) in output_partial_gradient.column_iter().zip(cache.normalized.column_iter()) {
+            self.gamma_gradient += gradient.component_mul(&normalized);
+            self.beta_gradient += gradient;
+        }
+
+        let mut normalized_gradient = output_partial_gradient.clone();
+        for mut column in normalized_gradient.column_iter_mut() {
+            column.component_mul_assign(&self.gamma);
+        }
+
+        let gradient_sum: DVector<T> = normalized_gradient.column_sum();
+        let weighted_sum: DVector<T> = normalized_gradient.component_mul(&cache.normalized).column_sum();
+
+        let mut weighted_sums_gradient = normalized_gradient;
+        for (mut column, normalized) in weighted_sums_gradient.column_iter_mut().zip(cache.normalized.column_iter()) {
+            let corrected = &column * batch_size - &gradient_sum - normalized.component_mul(&weighted_sum);
+            column.copy_from(&(corrected.component_mul(&cache.inverse_std) / batch_size));
+        }
+
+        weighted_sums_gradient
+    }
+
+    pub(crate) fn apply_gradient(&mut self, scale: T) {
+        self.gamma += &self.gamma_gradient * scale;
+        self.beta += &self.beta_gradient * scale;
+        self.zero_gradient();
+    }
+
+    pub(crate) fn zero_gradient(&mut self) {
+        self.gamma_gradient.fill(T::zero());
+        self.beta_gradient.fill(T::zero());
+    }
+}
+
+impl BatchNorm {
+    pub(crate) fn from_record(record: BatchNormRecord) -> Self {
+        let size = record.gamma.len();
+
+        Self {
+            gamma: record.gamma,
+            gamma_gradient: DVector::zeros(size),
+            beta: record.beta,
+            beta_gradient: DVector::zeros(size),
+
+            running_mean: record.running_mean,
+            running_variance: record.running_variance,
+            momentum: record.momentum,
+            epsilon: record.epsilon,
+
+            previous_normalized: DVector::zeros(size),
+        }
+    }
+
+    pub(crate) fn to_record(&self) -> BatchNormRecord {
+        BatchNormRecord {
+            gamma: self.gamma.clone(),
+            beta: self.beta.clone(),
+            running_mean: self.running_mean.clone(),
+            running_variance: self.running_variance.clone(),
+            momentum: self.momentum,
+            epsilon: self.epsilon,
+        }
+    }
+}
@@ -0,0 +1,386 @@
+//! The binary model format written by `Network::save`, all numbers little-endian:
+//!
+//! - the magic bytes `NRLN` and the format version as a `u32`
+//! - the precision of the weights and biases as a `u8`, 0 for `f32` and 1 for `f16`, and the layer count as a `u32`
+//! - per layer: the input and output sizes as `u32`s, the activation name as a `u32` byte length
+//!   followed by UTF-8, a `u8` batch normalization flag, a `u8` flag that's 1 if the layer has biases,
+//!   a `u8` flag that's 1 if the layer shares the parameters of an earlier layer followed by that layer's
+//!   index as a `u32`, the piece count as a `u32`, more than 1 only for a maxout layer, whose activation
+//!   name is `maxout`, then per piece the weights row by row and the biases, if any, unless the layer
+//!   shares them
+//! - per batch normalized layer, after its biases: gamma, beta, the running mean and variance,
+//!   then momentum and epsilon, all `f32`s
+//!
+//! Version 1 files have no precision byte, their weights and biases are `f32`s, files before version 3
+//! have no bias flag, every layer has biases, files before version 4 have no sharing flag, no layer
+//! shares parameters, and files before version 5 have no piece count, every layer has one piece. They
+//! still load: every version has a `Layout` saying which fields its files have, and what an older file
+//! leaves out is filled in and listed in the `LoadReport` of `Network::load_with_report`. Files from a
+//! newer version fail with `UnsupportedVersion`.
+
+use std::{
+    fs::File,
+    io::{self, BufReader, BufWriter, Read, Write},
+    path::Path,
+};
+
+use nalgebra::{DMatrix, DVector};
+
+use super::{
+    precision::Precision,
+    serialization::{BatchNormRecord, LayerRecord},
+    Network,
+    NetworkError,
+    NetworkLoadError,
+};
+
+const MAGIC: &[u8; 4] = b"NRLN";
+pub const FORMAT_VERSION: u32 = 5;
+
+/// What `Network::load_with_report` read.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LoadReport {
+    /// The format version of the file.
+    pub version: u32,
+    /// The fields the file's version doesn't have and that were filled in, empty for a current file.
+    pub migrations: Vec<Migration>,
+}
+
+impl LoadReport {
+    /// Whether the file is from an older version, so saving the network again would upgrade it.
+    pub fn is_outdated(&self) -> bool {
+        self.version < FORMAT_VERSION
+    }
+}
+
+/// A field that files of an older version don't have, and the value loading assumed for it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Migration {
+    /// Version 1 has no precision byte, the weights and biases were read as `f32`s.
+    AssumedF32Precision,
+    /// Versions before 3 have no bias flags, every layer was given biases.
+    AssumedBiases,
+    /// Versions before 4 have no sharing flags, every layer was given its own parameters.
+    AssumedUnshared,
+    /// Versions before 5 have no piece counts, every layer was read as a single piece.
+    AssumedSinglePiece,
+}
+
+/// The fields files of a format version have beyond those every version has.
+struct Layout {
+    precision_byte: bool,
+    bias_flags: bool,
+    sharing_flags: bool,
+    piece_counts: bool,
+}
+
+impl Layout {
+    fn of_version(version: u32) -> Result<Self, NetworkLoadError> {
+        match version {
+            1 => Ok(Self { precision_byte: false, bias_flags: false, sharing_flags: false, piece_counts: false }),
+            2 => Ok(Self { precision_byte: true, bias_flags: false, sharing_flags: false, piece_counts: false }),
+            3 => Ok(Self { precision_byte: true, bias_flags: true, sharing_flags: false, piece_counts: false }),
+            4 => Ok(Self { precision_byte: true, bias_flags: true, sharing_flags: true, piece_counts: false }),
+            5 => Ok(Self { precision_byte: true, bias_flags: true, sharing_flags: true, piece_counts: true }),
+            _ => Err(NetworkLoadError::UnsupportedVersion {
+                found: version,
+                supported: FORMAT_VERSION,
+            }),
+        }
+    }
+
+    fn migrations(&self) -> Vec<Migration> {
+        let mut migrations = Vec::new();
+
+        if !self.precision_byte {
+            migrations.push(Migration::AssumedF32Precision);
+        }
+
+        if !self.bias_flags {
+            migrations.push(Migration::AssumedBiases);
+        }
+
+        if !self.sharing_flags {
+            migrations.push(Migration::AssumedUnshared);
+        }
+
+        if !self.piece_counts {
+            migrations.push(Migration::AssumedSinglePiece);
+        }
+
+        migrations
+    }
+}
+
+impl Network {
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        self.save_with_precision(path, Precision::F32)
+    }
+
+    /// `save` storing the weights and biases in the given precision.
+    pub fn save_with_precision(&self, path: impl AsRef<Path>, precision: Precision) -> io::Result<()> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        self.write_to_with_precision(&mut writer, precision)?;
+        writer.flush()
+    }
+
+    /// Loads a network saved by any version of `save`, see the module documentation.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, NetworkLoadError> {
+        Self::read_from(BufReader::new(File::open(path)?))
+    }
+
+    /// `load` that also tells which version the file has and what was filled in for an older one.
+    pub fn load_with_report(path: impl AsRef<Path>) -> Result<(Self, LoadReport), NetworkLoadError> {
+        Self::read_from_with_report(BufReader::new(File::open(path)?))
+    }
+
+    /// Fails with `io::ErrorKind::InvalidInput` if the network has layers other than dense and maxout ones,
+    /// or a size that doesn't fit the format's `u32`s.
+    pub fn write_to(&self, writer: impl Write) -> io::Result<()> {
+        self.write_to_with_precision(writer, Precision::F32)
+    }
+
+    /// `write_to` storing the weights and biases in the given precision.
+    pub fn write_to_with_precision(&self, mut writer: impl Write, precision: Precision) -> io::Result<()> {
+        let records = self.to_records().map_err(|error| io::Error::new(io::ErrorKind::InvalidInput, error))?;
+
+        writer.write_all(MAGIC)?;
+        write_u32(&mut writer, FORMAT_VERSION)?;
+        writer.write_all(&[precision_flag(precision)])?;
+        write_size(&mut writer, records.len())?;
+
+        for record in records.iter() {
+            write_size(&mut writer, record.weights.ncols())?;
+            write_size(&mut writer, record.weights.nrows())?;
+            write_size(&mut writer, record.activation.len())?;
+            writer.write_all(record.activation.as_bytes())?;
+            writer.write_all(&[record.batch_norm.is_some() as u8])?;
+            writer.write_all(&[record.biases.is_some() as u8])?;
+            writer.write_all(&[record.shared_with.is_some() as u8])?;
+            if let Some(source) = record.shared_with {
+                write_size(&mut writer, source)?;
+            }
+
+            write_size(&mut writer, 1 + record.maxout_pieces.len())?;
+            if record.shared_with.is_none() {
+                write_values(&mut writer, precision, record.weights.transpose().iter())?;
+                if let Some(biases) = &record.biases {
+                    write_values(&mut writer, precision, biases.iter())?;
+                }
+
+                for (weights, biases) in &record.maxout_pieces {
+                    write_values(&mut writer, precision, weights.transpose().iter())?;
+                    write_values(&mut writer, precision, biases.iter())?;
+                }
+            }
+
+            if let Some(batch_norm) = &record.batch_norm {
+                write_values(&mut writer, Precision::F32, batch_norm.gamma.iter())?;
+                write_values(&mut writer, Precision::F32, batch_norm.beta.iter())?;
+                write_values(&mut writer, Precision::F32, batch_norm.running_mean.iter())?;
+                write_values(&mut writer, Precision::F32, batch_norm.running_variance.iter())?;
+                write_values(&mut writer, Precision::F32, [batch_norm.momentum, batch_norm.epsilon].iter())?;
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn read_from(reader: impl Read) -> Result<Self, NetworkLoadError> {
+        Self::read_from_with_report(reader).map(|(network, _)| network)
+    }
+
+    pub fn read_from_with_report(mut reader: impl Read) -> Result<(Self, LoadReport), NetworkLoadError> {
+        let mut magic = [0; 4];
+        read_exact(&mut reader, &mut magic)?;
+
+        if &magic != MAGIC {
+            return Err(NetworkLoadError::BadMagic);
+        }
+
+        let version = read_u32(&mut reader)?;
+        let layout = Layout::of_version(version)?;
+
+        let precision = if !layout.precision_byte {
+            Precision::F32
+        } else {
+            let mut flag = [0];
+            read_exact(&mut reader, &mut flag)?;
+            match flag[0] {
+                0 => Precision::F32,
+                1 => Precision::F16,
+                flag => return Err(NetworkLoadError::InvalidPrecisionFlag(flag)),
+            }
+        };
+
+        let layer_count = read_u32(&mut reader)? as usize;
+        let mut records = Vec::new();
+
+        for layer in 0..layer_count {
+            let input_size = read_u32(&mut reader)? as usize;
+            let output_size = read_u32(&mut reader)? as usize;
+            // More weights than the address space can't be in the file, whatever the rest of it says.
+            let weight_count = input_size.checked_mul(output_size).ok_or(NetworkLoadError::Truncated)?;
+
+            let name_len = read_u32(&mut reader)? as usize;
+            let mut name = Vec::new();
+            read_to(&mut reader, name_len, &mut name)?;
+            let activation = String::from_utf8(name).map_err(|_| NetworkLoadError::InvalidActivationName { layer })?;
+
+            let mut flag = [0];
+            read_exact(&mut reader, &mut flag)?;
+            let has_batch_norm = match flag[0] {
+                0 => false,
+                1 => true,
+                _ => return Err(NetworkLoadError::InvalidBatchNormFlag { layer }),
+            };
+
+            let has_biases = if !layout.bias_flags {
+                true
+            } else {
+                read_exact(&mut reader, &mut flag)?;
+                match flag[0] {
+                    0 => false,
+                    1 => true,
+                    _ => return Err(NetworkLoadError::InvalidBiasFlag { layer }),
+                }
+            };
+
+            let shared_with = if !layout.sharing_flags {
+                None
+            } else {
+                read_exact(&mut reader, &mut flag)?;
+                match flag[0] {
+                    0 => None,
+                    1 => Some(read_u32(&mut reader)? as usize),
+                    _ => return Err(NetworkLoadError::InvalidSharingFlag { layer }),
+                }
+            };
+
+            let pieces = if layout.piece_counts { read_u32(&mut reader)? as usize } else { 1 };
+            if pieces == 0 || (pieces > 1 && (shared_with.is_some() || !has_biases)) {
+                return Err(NetworkLoadError::InvalidMaxoutLayer { layer });
+            }
+
+            let (weights, biases) = match shared_with {
+                // The shared parameters are stored once, with the earlier layer.
+                Some(source) => {
+                    let source_record: &LayerRecord = records
+                        .get(source)
+                        .ok_or(NetworkLoadError::InvalidSharedLayer { layer, source_layer: source })?;
+
+                    if source_record.weights.shape() != (output_size, input_size) || source_record.biases.is_some() != has_biases {
+                        return Err(NetworkError::SharedShapeMismatch { layer, source_layer: source }.into());
+                    }
+
+                    (source_record.weights.clone(), source_record.biases.clone())
+                }
+                None => {
+                    let weights = read_values(&mut reader, precision, weight_count)?;
+                    let biases = if has_biases {
+                        Some(DVector::from_vec(read_values(&mut reader, precision, output_size)?))
+                    } else {
+                        None
+                    };
+
+                    (DMatrix::from_row_slice(output_size, input_size, &weights), biases)
+                }
+            };
+
+            let mut maxout_pieces = Vec::new();
+            for _ in 1..pieces {
+                let weights = read_values(&mut reader, precision, weight_count)?;
+                let biases = read_values(&mut reader, precision, output_size)?;
+                maxout_pieces.push((DMatrix::from_row_slice(output_size, input_size, &weights), DVector::from_vec(biases)));
+            }
+
+            let batch_norm = if has_batch_norm {
+                let gamma = read_values(&mut reader, Precision::F32, output_size)?;
+                let beta = read_values(&mut reader, Precision::F32, output_size)?;
+                let running_mean = read_values(&mut reader, Precision::F32, output_size)?;
+                let running_variance = read_values(&mut reader, Precision::F32, output_size)?;
+                let settings = read_values(&mut reader, Precision::F32, 2)?;
+
+                Some(BatchNormRecord {
+                    gamma: DVector::from_vec(gamma),
+                    beta: DVector::from_vec(beta),
+                    running_mean: DVector::from_vec(running_mean),
+                    running_variance: DVector::from_vec(running_variance),
+                    momentum: settings[0],
+                    epsilon: settings[1],
+                })
+            } else {
+                None
+            };
+
+            records.push(LayerRecord {
+                weights,
+                biases,
+                activation,
+                batch_norm,
+                shared_with,
+                maxout_pieces,
+            });
+        }
+
+        let report = LoadReport {
+            version,
+            migrations: layout.migrations(),
+        };
+
+        Ok((Self::from_records(records)?, report))
+    }
+}
+
+fn write_u32(writer: &mut impl Write, value: u32) -> io::Result<()> {
+    writer.write_all(&value.to_le_bytes())
+}
+
+/// Writes a size or count as a `u32`, failing with `InvalidInput` instead of truncating one that doesn't fit.
+fn write_size(writer: &mut impl Write, size: usize) -> io::Result<()> {
+    let size = u32::try_from(size)
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, format!("the size {size} doesn't fit in a u32")))?;
+    write_u32(writer, size)
+}
+
+fn precision_flag(precision: Precision) -> u8 {
+    match precision {
+        Precision::F32 => 0,
+        Precision::F16 => 1,
+    }
+}
+
+fn write_values<'a>(writer: &mut impl Write, precision: Precision, values: impl Iterator<Item = &'a f32>) -> io::Result<()> {
+    let mut bytes = Vec::new();
+    precision.write(values.copied(), &mut bytes);
+    writer.write_all(&bytes)
+}
+
+fn read_exact(reader: &mut impl Read, buffer: &mut [u8]) -> Result<(), NetworkLoadError> {
+    reader.read_exact(buffer).map_err(|error| match error.kind() {
+        io::ErrorKind::UnexpectedEof => NetworkLoadError::Truncated,
+        _ => error.into(),
+    })
+}
+
+/// Reads exactly `len` bytes without trusting `len` enough to allocate it upfront,
+/// so a corrupted length fails as a truncated file instead of exhausting memory.
+fn read_to(reader: &mut impl Read, len: usize, buffer: &mut Vec<u8>) -> Result<(), NetworkLoadError> {
+    if reader.take(len as u64).read_to_end(buffer)? != len {
+        return Err(NetworkLoadError::Truncated);
+    }
+
+    Ok(())
+}
+
+fn read_u32(reader: &mut impl Read) -> Result<u32, NetworkLoadError> {
+    let mut bytes = [0; 4];
+    read_exact(reader, &mut bytes)?;
+    Ok(u32::from_le_bytes(bytes))
+}
+
+fn read_values(reader: &mut impl Read, precision: Precision, count: usize) -> Result<Vec<f32>, NetworkLoadError> {
+    let mut bytes = Vec::new();
+    read_to(reader, count.checked_mul(precision.size()).ok_or(NetworkLoadError::Truncated)?, &mut bytes)?;
+    Ok(precision.read(&bytes))
+}
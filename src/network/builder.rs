@@ -0,0 +1,117 @@
+use rand::Rng;
+#[cfg(feature = "std")]
+use rand::{rngs::StdRng, SeedableRng};
+
+use crate::{activations::ActivationFn, prelude::*};
+
+use super::{
+    check_layer_sizes,
+    initializer::Initializer,
+    Network,
+    NetworkError,
+};
+
+/// Builds a network layer by layer, e.g. `NetworkBuilder::new().input(2).layer(50, sigmoid!()).layer(1, sigmoid!()).build()`.
+/// Layers without their own initializer use the one set with `init`, or without one the initializer
+/// `Initializer::recommended_for` their activation function. With `init(Initializer::Uniform(-0.5, 0.5))`,
+/// it builds the same layers as `Network::random_with_rng` with that distribution.
+#[derive(Default)]
+pub struct NetworkBuilder {
+    input_size: Option<usize>,
+    layers: Vec<LayerSpec>,
+    init: Option<Initializer>,
+    #[cfg(feature = "std")]
+    seed: Option<u64>,
+}
+
+struct LayerSpec {
+    size: usize,
+    activation_fn: Box<dyn ActivationFn>,
+    init: Option<Initializer>,
+    use_bias: bool,
+}
+
+impl NetworkBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn input(mut self, size: usize) -> Self {
+        self.input_size = Some(size);
+        self
+    }
+
+    pub fn layer(mut self, size: usize, activation_fn: Box<dyn ActivationFn>) -> Self {
+        self.layers.push(LayerSpec {
+            size,
+            activation_fn,
+            init: None,
+            use_bias: true,
+        });
+        self
+    }
+
+    /// Adds a layer with its own initializer.
+    pub fn layer_with_init(mut self, size: usize, activation_fn: Box<dyn ActivationFn>, init: Initializer) -> Self {
+        self.layers.push(LayerSpec {
+            size,
+            activation_fn,
+            init: Some(init),
+            use_bias: true,
+        });
+        self
+    }
+
+    /// Drops the biases of the last layer added, see `Layer::without_bias`. Does nothing before the first layer.
+    pub fn without_bias(mut self) -> Self {
+        if let Some(layer) = self.layers.last_mut() {
+            layer.use_bias = false;
+        }
+        self
+    }
+
+    /// The initializer of every layer added without one, instead of the recommended one.
+    pub fn init(mut self, init: Initializer) -> Self {
+        self.init = Some(init);
+        self
+    }
+
+    /// Seeds a `StdRng` for the initialization, so the same seed gives the same network.
+    #[cfg(feature = "std")]
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    #[cfg(feature = "std")]
+    pub fn build(self) -> Result<Network, NetworkError> {
+        match self.seed {
+            Some(seed) => self.build_with_rng(&mut StdRng::seed_from_u64(seed)),
+            None => self.build_with_rng(&mut rand::rng()),
+        }
+    }
+
+    /// Builds the network drawing from the given RNG, ignoring any seed.
+    pub fn build_with_rng(self, rng: &mut impl Rng) -> Result<Network, NetworkError> {
+        let input_size = self.input_size.ok_or(NetworkError::MissingInputSize)?;
+
+        let layer_sizes: Vec<usize> = core::iter::once(input_size)
+            .chain(self.layers.iter().map(|layer| layer.size))
+            .collect();
+        check_layer_sizes(&layer_sizes)?;
+
+        let layers = self.layers
+            .into_iter()
+            .zip(layer_sizes.iter())
+            .map(|(layer, &input_size)| {
+                let init = layer.init
+                    .or(self.init)
+                    .unwrap_or_else(|| Initializer::recommended_for(layer.activation_fn.as_ref()));
+                let dense = init.layer(input_size, layer.size, layer.activation_fn, rng);
+                dense.map(|dense| if layer.use_bias { dense } else { dense.without_bias() })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Network::from_dense_layers(layers))
+    }
+}
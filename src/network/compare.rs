@@ -0,0 +1,112 @@
+use crate::{prelude::*, scalar::Scalar};
+
+use super::{Network, NetworkError, NetworkLayer};
+
+/// How far the parameters of two layers of the same shape are apart.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LayerDiff<T: Scalar = f32> {
+    pub max_weight_diff: T,
+    pub mean_weight_diff: T,
+    pub max_bias_diff: T,
+    pub mean_bias_diff: T,
+}
+
+/// The per layer parameter differences of two networks with the same architecture.
+#[derive(Clone, Debug, PartialEq)]
+pub struct NetworkDiff<T: Scalar = f32> {
+    pub layers: Vec<LayerDiff<T>>,
+}
+
+impl<T: Scalar> NetworkDiff<T> {
+    /// The largest absolute difference of any weight or bias.
+    pub fn max_abs_diff(&self) -> T {
+        self.layers
+            .iter()
+            .map(|layer| layer.max_weight_diff.max(layer.max_bias_diff))
+            .fold(T::zero(), T::max)
+    }
+}
+
+impl<T: Scalar> Network<T> {
+    /// Whether both networks have the same architecture and all their weights and biases are within `tolerance`.
+    pub fn approx_eq(&self, other: &Network<T>, tolerance: T) -> bool {
+        self.check_architecture(other).is_ok()
+            && self.layers.iter().zip(other.layers.iter()).all(|(a, b)| {
+                let within = |(&x, &y): (&T, &T)| (x - y).abs() <= tolerance;
+
+                a.flat_parameters().iter().zip(b.flat_parameters().iter()).all(within)
+            })
+    }
+
+    /// The per layer differences between the parameters of both networks, an error if their architectures differ.
+    /// Layers other than dense ones have no biases, all their parameters count as weights.
+    pub fn diff(&self, other: &Network<T>) -> Result<NetworkDiff<T>, NetworkError> {
+        self.check_architecture(other)?;
+
+        let layers = self.layers
+            .iter()
+            .zip(other.layers.iter())
+            .map(|(a, b)| {
+                let (weights, biases) = match (a.as_dense(), b.as_dense()) {
+                    (Some(a), Some(b)) => (
+                        abs_diff(a.weights().iter(), b.weights().iter()),
+                        abs_diff(a.biases().iter(), b.biases().iter()),
+                    ),
+                    _ => (abs_diff(a.flat_parameters().iter(), b.flat_parameters().iter()), Vec::new()),
+                };
+
+                LayerDiff {
+                    max_weight_diff: weights.iter().copied().fold(T::zero(), T::max),
+                    mean_weight_diff: mean(&weights),
+                    max_bias_diff: biases.iter().copied().fold(T::zero(), T::max),
+                    mean_bias_diff: mean(&biases),
+                }
+            })
+            .collect();
+
+        Ok(NetworkDiff { layers })
+    }
+
+    /// Layer counts, kinds, sizes and parameter counts have to match, for dense layers also the activation
+    /// functions and the use of batch norm.
+    pub(crate) fn check_architecture(&self, other: &Network<T>) -> Result<(), NetworkError> {
+        let same = |a: &dyn NetworkLayer<T>, b: &dyn NetworkLayer<T>| {
+            let same_dense = match (a.as_dense(), b.as_dense()) {
+                (Some(a), Some(b)) => {
+                    a.activation_fn().name() == b.activation_fn().name() && a.batch_norm().is_some() == b.batch_norm().is_some()
+                }
+                (None, None) => true,
+                _ => false,
+            };
+
+            same_dense
+                && a.kind() == b.kind()
+                && a.input_size() == b.input_size()
+                && a.output_size() == b.output_size()
+                && a.parameter_count() == b.parameter_count()
+        };
+
+        let layer = self.layers
+            .iter()
+            .zip(other.layers.iter())
+            .position(|(a, b)| !same(a.as_ref(), b.as_ref()))
+            .or((self.layers.len() != other.layers.len()).then(|| self.layers.len().min(other.layers.len())));
+
+        match layer {
+            Some(layer) => Err(NetworkError::ArchitectureMismatch { layer }),
+            None => Ok(()),
+        }
+    }
+}
+
+fn abs_diff<'a, T: Scalar>(a: impl Iterator<Item = &'a T>, b: impl Iterator<Item = &'a T>) -> Vec<T> {
+    a.zip(b).map(|(&x, &y)| (x - y).abs()).collect()
+}
+
+fn mean<T: Scalar>(values: &[T]) -> T {
+    if values.is_empty() {
+        return T::zero();
+    }
+
+    values.iter().copied().fold(T::zero(), |sum, x| sum + x) / T::from_count(values.len())
+}
@@ -0,0 +1,43 @@
+use nalgebra::DMatrix;
+
+use crate::scalar::Scalar;
+
+/// A hard limit on a dense layer's weights, enforced after every update. Biases aren't constrained.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum WeightConstraint {
+    /// Clamps every weight to `min..=max`.
+    ClipRange(f32, f32),
+    /// Rescales the incoming weights of every output whose L2 norm is above the limit down to it.
+    MaxNorm(f32),
+}
+
+impl WeightConstraint {
+    /// A range needs `min <= max` and a norm has to be positive, either may be infinite.
+    pub fn is_valid(&self) -> bool {
+        match *self {
+            WeightConstraint::ClipRange(min, max) => min <= max,
+            WeightConstraint::MaxNorm(max_norm) => max_norm > 0.0,
+        }
+    }
+
+    /// Applies the constraint to a weight matrix with one row per output.
+    pub(crate) fn apply<T: Scalar>(&self, weights: &mut DMatrix<T>) {
+        match *self {
+            WeightConstraint::ClipRange(min, max) => {
+                let (min, max) = (T::constant(min as f64), T::constant(max as f64));
+                weights.apply(|weight| *weight = weight.clamp(min, max));
+            }
+
+            WeightConstraint::MaxNorm(max_norm) => {
+                let max_norm = T::constant(max_norm as f64);
+
+                for mut row in weights.row_iter_mut() {
+                    let norm = row.norm();
+                    if norm > max_norm {
+                        row *= max_norm / norm;
+                    }
+                }
+            }
+        }
+    }
+}
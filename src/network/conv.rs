@@ -0,0 +1,483 @@
+//! 2D convolution over images stored in plain vectors. An image of `ImageShape { height, width, channels }`
+//! is flattened channel by channel and every channel row by row, so the value at `(channel, row, column)`
+//! is at `ImageShape::index(channel, row, column)`. A `Conv2D` produces its output in the same layout.
+
+use nalgebra::{DMatrix, DMatrixView, DVector, DVectorView};
+use rand::{distr::Distribution, Rng};
+
+use crate::{activations::ActivationFn, prelude::*, scalar::Scalar};
+
+use super::{gradients::LayerGradientNorm, layer::LayerError, NetworkLayer, NonFiniteKind};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ImageShape {
+    pub height: usize,
+    pub width: usize,
+    pub channels: usize,
+}
+
+impl ImageShape {
+    pub fn new(height: usize, width: usize, channels: usize) -> Self {
+        Self { height, width, channels }
+    }
+
+    /// The length of the flattened image.
+    #[inline]
+    pub fn size(&self) -> usize {
+        self.height * self.width * self.channels
+    }
+
+    /// Where the value at `(channel, row, column)` is in the flattened image.
+    #[inline]
+    pub fn index(&self, channel: usize, row: usize, column: usize) -> usize {
+        (channel * self.height + row) * self.width + column
+    }
+}
+
+/// The kernel size, output channels, stride and padding of a `Conv2D`. The stride is 1 and there is no
+/// padding by default. Padding adds that many rows and columns of zeros on every side of the input.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Convolution {
+    pub output_channels: usize,
+    pub kernel_height: usize,
+    pub kernel_width: usize,
+    pub stride: usize,
+    pub padding: usize,
+}
+
+impl Convolution {
+    pub fn new(output_channels: usize, (kernel_height, kernel_width): (usize, usize)) -> Self {
+        Self {
+            output_channels,
+            kernel_height,
+            kernel_width,
+            stride: 1,
+            padding: 0,
+        }
+    }
+
+    pub fn stride(mut self, stride: usize) -> Self {
+        self.stride = stride;
+        self
+    }
+
+    pub fn padding(mut self, padding: usize) -> Self {
+        self.padding = padding;
+        self
+    }
+
+    /// The shape of the output for an input of the given shape.
+    pub fn output_shape(&self, input_shape: ImageShape) -> Result<ImageShape, LayerError> {
+        if input_shape.size() == 0 {
+            return Err(LayerError::ZeroInputSize);
+        }
+
+        if self.output_channels == 0 {
+            return Err(LayerError::ZeroOutputSize);
+        }
+
+        let (padded_height, padded_width) = (input_shape.height + 2 * self.padding, input_shape.width + 2 * self.padding);
+        if self.stride == 0 || self.kernel_height == 0 || self.kernel_width == 0
+            || self.kernel_height > padded_height || self.kernel_width > padded_width
+        {
+            return Err(LayerError::InvalidConvolution {
+                height: input_shape.height,
+                width: input_shape.width,
+                convolution: *self,
+            });
+        }
+
+        Ok(ImageShape {
+            height: (padded_height - self.kernel_height) / self.stride + 1,
+            width: (padded_width - self.kernel_width) / self.stride + 1,
+            channels: self.output_channels,
+        })
+    }
+}
+
+/// A convolutional layer with one bias per output channel, followed by an activation function. It has the
+/// same training lifecycle as `Layer`: `forward` records what `backpropagation_step` needs, which
+/// accumulates gradients until `apply_gradient` applies and resets them. As a `NetworkLayer` it trains
+/// in a `Network` like any other layer, sample by sample within every batch.
+#[derive(Clone)]
+pub struct Conv2D<T: Scalar = f32> {
+    input_shape: ImageShape,
+    output_shape: ImageShape,
+    convolution: Convolution,
+    /// One row per output channel, one column per kernel position, ordered like the input layout.
+    kernels: DMatrix<T>,
+    kernel_gradient: DMatrix<T>,
+    biases: DVector<T>,
+    bias_gradient: DVector<T>,
+    activation_fn: Box<dyn ActivationFn<T>>,
+
+    /// The input patches of the last `forward`, one column per output position.
+    previous_patches: DMatrix<T>,
+    previous_weighted_sums: DVector<T>,
+    /// The last training batch run through `NetworkLayer::forward_training_batch`.
+    previous_batch: Option<ConvBatchCache<T>>,
+}
+
+/// The patches of every sample of a training batch, with the weighted sums and outputs one sample per column.
+#[derive(Clone)]
+struct ConvBatchCache<T: Scalar> {
+    patches: Vec<DMatrix<T>>,
+    weighted_sums: DMatrix<T>,
+    outputs: DMatrix<T>,
+}
+
+/// Adds the gradient of one sample's `deltas`, one row per output position and one column per output
+/// channel, to the kernel and bias gradients.
+fn accumulate_gradient<T: Scalar>(kernel_gradient: &mut DMatrix<T>, bias_gradient: &mut DVector<T>, patches: &DMatrix<T>, deltas: &DMatrix<T>) {
+    *bias_gradient += deltas.row_sum_tr();
+    kernel_gradient.gemm(T::one(), &deltas.transpose(), &patches.transpose(), T::one());
+}
+
+impl<T: Scalar> core::fmt::Debug for Conv2D<T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Conv2D")
+            .field("input_shape", &self.input_shape)
+            .field("output_shape", &self.output_shape)
+            .field("convolution", &self.convolution)
+            .field("activation_fn", &self.activation_fn.name())
+            .finish()
+    }
+}
+
+impl<T: Scalar> Conv2D<T> {
+    pub fn zeros(input_shape: ImageShape, convolution: Convolution, activation_fn: Box<dyn ActivationFn<T>>) -> Result<Self, LayerError> {
+        let output_shape = convolution.output_shape(input_shape)?;
+        let kernel_size = input_shape.channels * convolution.kernel_height * convolution.kernel_width;
+        let positions = output_shape.height * output_shape.width;
+
+        Ok(Self {
+            input_shape,
+            output_shape,
+            convolution,
+            kernels: DMatrix::zeros(convolution.output_channels, kernel_size),
+            kernel_gradient: DMatrix::zeros(convolution.output_channels, kernel_size),
+            biases: DVector::zeros(convolution.output_channels),
+            bias_gradient: DVector::zeros(convolution.output_channels),
+            activation_fn,
+
+            previous_patches: DMatrix::zeros(kernel_size, positions),
+            previous_weighted_sums: DVector::zeros(output_shape.size()),
+            previous_batch: None,
+        })
+    }
+
+    /// Draws from the thread-local RNG, see `random_with_rng` for reproducible layers.
+    #[cfg(feature = "std")]
+    pub fn random(
+        input_shape: ImageShape,
+        convolution: Convolution,
+        activation_fn: Box<dyn ActivationFn<T>>,
+        distribution: &impl Distribution<T>,
+    ) -> Result<Self, LayerError> {
+        Self::random_with_rng(input_shape, convolution, activation_fn, distribution, &mut rand::rng())
+    }
+
+    /// Like `random`, but draws every kernel weight and bias from the given RNG.
+    pub fn random_with_rng(
+        input_shape: ImageShape,
+        convolution: Convolution,
+        activation_fn: Box<dyn ActivationFn<T>>,
+        distribution: &impl Distribution<T>,
+        rng: &mut impl Rng,
+    ) -> Result<Self, LayerError> {
+        let mut layer = Self::zeros(input_shape, convolution, activation_fn)?;
+        layer.kernels.iter_mut().chain(layer.biases.iter_mut()).for_each(|x| *x = distribution.sample(rng));
+        Ok(layer)
+    }
+
+    pub fn forward(&mut self, inputs: DVector<T>) -> Result<DVector<T>, LayerError> {
+        self.forward_view(inputs.as_view())
+    }
+
+    /// `forward` for borrowed inputs.
+    pub fn forward_view(&mut self, inputs: DVectorView<T>) -> Result<DVector<T>, LayerError> {
+        self.check_input_size(inputs.len())?;
+        self.previous_patches = self.patches(inputs);
+        self.previous_weighted_sums = self.weighted_sums(&self.previous_patches);
+
+        let mut activations = self.previous_weighted_sums.clone();
+        self.activation_fn.apply_slice(activations.as_mut_slice());
+        Ok(activations)
+    }
+
+    /// Forward pass that leaves the layer untouched.
+    pub fn predict(&self, inputs: DVectorView<T>) -> Result<DVector<T>, LayerError> {
+        self.check_input_size(inputs.len())?;
+        let mut activations = self.weighted_sums(&self.patches(inputs));
+        self.activation_fn.apply_slice(activations.as_mut_slice());
+        Ok(activations)
+    }
+
+    /// Accumulates the gradient for the last `forward`, whose outputs are `previous_outputs`, and returns
+    /// the gradient with respect to its inputs.
+    pub fn backpropagation_step(&mut self, previous_outputs: DVectorView<T>, output_partial_gradient: DVectorView<T>) -> DVector<T> {
+        let deltas = self.deltas(self.previous_weighted_sums.as_view(), previous_outputs, output_partial_gradient);
+        accumulate_gradient(&mut self.kernel_gradient, &mut self.bias_gradient, &self.previous_patches, &deltas);
+        self.unpatch(&self.kernels.tr_mul(&deltas.transpose()))
+    }
+
+    /// The gradient with respect to the weighted sums of one sample, one row per output position and one
+    /// column per output channel, which is the output layout.
+    fn deltas(&self, weighted_sums: DVectorView<T>, outputs: DVectorView<T>, output_partial_gradient: DVectorView<T>) -> DMatrix<T> {
+        let mut deltas = DVector::zeros(self.output_size());
+        self.activation_fn.derivative_slice(weighted_sums.as_slice(), outputs.as_slice(), deltas.as_mut_slice());
+        deltas.component_mul_assign(&output_partial_gradient);
+
+        let positions = self.output_shape.height * self.output_shape.width;
+        DMatrix::from_column_slice(positions, self.convolution.output_channels, deltas.as_slice())
+    }
+
+    pub fn apply_gradient(&mut self, scale: T) {
+        self.kernels += &self.kernel_gradient * scale;
+        self.biases += &self.bias_gradient * scale;
+        self.zero_gradient();
+    }
+
+    pub(crate) fn zero_gradient(&mut self) {
+        self.kernel_gradient.fill(T::zero());
+        self.bias_gradient.fill(T::zero());
+    }
+
+    #[inline]
+    pub fn input_size(&self) -> usize { self.input_shape.size() }
+
+    #[inline]
+    pub fn output_size(&self) -> usize { self.output_shape.size() }
+
+    #[inline]
+    pub fn input_shape(&self) -> ImageShape { self.input_shape }
+
+    #[inline]
+    pub fn output_shape(&self) -> ImageShape { self.output_shape }
+
+    #[inline]
+    pub fn convolution(&self) -> Convolution { self.convolution }
+
+    /// One row per output channel. Column `(channel * kernel_height + row) * kernel_width + column` weights
+    /// the input at that offset in that channel.
+    #[inline]
+    pub fn kernels(&self) -> DMatrixView<'_, T> { self.kernels.as_view() }
+
+    #[inline]
+    pub fn biases(&self) -> DVectorView<'_, T> { self.biases.as_view() }
+
+    #[inline]
+    pub fn kernel_gradient(&self) -> DMatrixView<'_, T> { self.kernel_gradient.as_view() }
+
+    #[inline]
+    pub fn bias_gradient(&self) -> DVectorView<'_, T> { self.bias_gradient.as_view() }
+
+    pub fn set_kernels(&mut self, kernels: DMatrix<T>) -> Result<(), LayerError> {
+        if kernels.shape() != self.kernels.shape() {
+            return Err(LayerError::ParameterShapeMismatch {
+                layer_input_size: self.kernels.ncols(),
+                layer_output_size: self.kernels.nrows(),
+                given_input_size: kernels.ncols(),
+                given_output_size: kernels.nrows(),
+            });
+        }
+
+        self.kernels = kernels;
+        Ok(())
+    }
+
+    pub fn set_biases(&mut self, biases: DVector<T>) -> Result<(), LayerError> {
+        if biases.len() != self.biases.len() {
+            return Err(LayerError::BiasSizeMismatch {
+                layer_output_size: self.biases.len(),
+                given_size: biases.len(),
+            });
+        }
+
+        self.biases = biases;
+        Ok(())
+    }
+
+    /// The flattened weighted sums for the given patches, in the output layout.
+    fn weighted_sums(&self, patches: &DMatrix<T>) -> DVector<T> {
+        let mut sums = patches.tr_mul(&self.kernels.transpose());
+        for (mut column, &bias) in sums.column_iter_mut().zip(self.biases.iter()) {
+            column.add_scalar_mut(bias);
+        }
+
+        DVector::from_vec(sums.as_slice().to_vec())
+    }
+
+    /// The input position a kernel offset reads at an output position, `None` inside the padding.
+    fn input_position(&self, output: usize, offset: usize, size: usize) -> Option<usize> {
+        (output * self.convolution.stride + offset).checked_sub(self.convolution.padding).filter(|&position| position < size)
+    }
+
+    /// Calls `f(patch row, output position, input index)` for every input value a kernel position reads.
+    fn for_each_tap(&self, mut f: impl FnMut(usize, usize, usize)) {
+        let Convolution { kernel_height, kernel_width, .. } = self.convolution;
+
+        for output_row in 0..self.output_shape.height {
+            for output_column in 0..self.output_shape.width {
+                let position = output_row * self.output_shape.width + output_column;
+
+                for channel in 0..self.input_shape.channels {
+                    for kernel_row in 0..kernel_height {
+                        let Some(row) = self.input_position(output_row, kernel_row, self.input_shape.height) else {
+                            continue;
+                        };
+
+                        for kernel_column in 0..kernel_width {
+                            let Some(column) = self.input_position(output_column, kernel_column, self.input_shape.width) else {
+                                continue;
+                            };
+
+                            let patch_row = (channel * kernel_height + kernel_row) * kernel_width + kernel_column;
+                            f(patch_row, position, self.input_shape.index(channel, row, column));
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// The input values under the kernel at every output position, one column per position.
+    fn patches(&self, inputs: DVectorView<T>) -> DMatrix<T> {
+        let mut patches = DMatrix::zeros(self.kernels.ncols(), self.output_shape.height * self.output_shape.width);
+        self.for_each_tap(|patch_row, position, index| patches[(patch_row, position)] = inputs[index]);
+        patches
+    }
+
+    /// Sums patch gradients back into a gradient over the input, the reverse of `patches`.
+    fn unpatch(&self, patch_gradients: &DMatrix<T>) -> DVector<T> {
+        let mut gradient = DVector::zeros(self.input_size());
+        self.for_each_tap(|patch_row, position, index| gradient[index] += patch_gradients[(patch_row, position)]);
+        gradient
+    }
+
+    fn check_input_size(&self, input_size: usize) -> Result<(), LayerError> {
+        if self.input_size() != input_size {
+            return Err(LayerError::InputSizeMismatch {
+                layer_input_size: self.input_size(),
+                given_input_size: input_size,
+            });
+        }
+
+        Ok(())
+    }
+}
+
+impl<T: Scalar> NetworkLayer<T> for Conv2D<T> {
+    #[inline]
+    fn input_size(&self) -> usize { Conv2D::input_size(self) }
+
+    #[inline]
+    fn output_size(&self) -> usize { Conv2D::output_size(self) }
+
+    fn forward(&mut self, inputs: DVectorView<T>) -> Result<DVector<T>, LayerError> {
+        self.forward_view(inputs)
+    }
+
+    fn predict(&self, inputs: DVectorView<T>) -> Result<DVector<T>, LayerError> {
+        Conv2D::predict(self, inputs)
+    }
+
+    fn forward_training_batch(&mut self, inputs: DMatrixView<T>) -> Result<DMatrix<T>, LayerError> {
+        self.check_input_size(inputs.nrows())?;
+        let patches: Vec<DMatrix<T>> = inputs.column_iter().map(|input| self.patches(input.as_view())).collect();
+        let mut weighted_sums = DMatrix::zeros(self.output_size(), inputs.ncols());
+
+        for (mut column, patches) in weighted_sums.column_iter_mut().zip(&patches) {
+            column.copy_from(&self.weighted_sums(patches));
+        }
+
+        let mut outputs = weighted_sums.clone();
+        self.activation_fn.apply_slice(outputs.as_mut_slice());
+
+        self.previous_batch = Some(ConvBatchCache {
+            patches,
+            weighted_sums,
+            outputs: outputs.clone(),
+        });
+
+        Ok(outputs)
+    }
+
+    fn backpropagation_step_batch(&mut self, _inputs: DMatrixView<T>, output_partial_gradient: &DMatrix<T>) -> Result<DMatrix<T>, LayerError> {
+        let cache = match self.previous_batch.take() {
+            Some(cache) if cache.outputs.shape() == output_partial_gradient.shape() => cache,
+            _ => return Err(LayerError::NoTrainingBatch(output_partial_gradient.ncols())),
+        };
+
+        let mut input_gradient = DMatrix::zeros(self.input_size(), output_partial_gradient.ncols());
+
+        for (i, patches) in cache.patches.iter().enumerate() {
+            let deltas = self.deltas(cache.weighted_sums.column(i), cache.outputs.column(i), output_partial_gradient.column(i));
+            accumulate_gradient(&mut self.kernel_gradient, &mut self.bias_gradient, patches, &deltas);
+            input_gradient.set_column(i, &self.unpatch(&self.kernels.tr_mul(&deltas.transpose())));
+        }
+
+        Ok(input_gradient)
+    }
+
+    fn apply_gradient(&mut self, scale: T) {
+        Conv2D::apply_gradient(self, scale);
+    }
+
+    fn zero_gradient(&mut self) {
+        Conv2D::zero_gradient(self);
+    }
+
+    fn boxed_clone(&self) -> Box<dyn NetworkLayer<T>> {
+        Box::new(self.clone())
+    }
+
+    fn kind(&self) -> &'static str { "conv2d" }
+
+    fn activation_fn(&self) -> Option<&dyn ActivationFn<T>> { Some(self.activation_fn.as_ref()) }
+
+    fn parameter_count(&self) -> usize { self.kernels.len() + self.biases.len() }
+
+    /// The kernels in column-major order, followed by the biases.
+    fn write_parameters(&self, parameters: &mut Vec<T>) {
+        parameters.extend(self.kernels.iter());
+        parameters.extend(self.biases.iter());
+    }
+
+    fn read_parameters(&mut self, parameters: &[T]) {
+        let (kernels, biases) = parameters.split_at(self.kernels.len());
+        self.kernels.copy_from_slice(kernels);
+        self.biases.copy_from_slice(biases);
+    }
+
+    fn gradient_norm(&self) -> LayerGradientNorm<T> {
+        LayerGradientNorm {
+            weights: self.kernel_gradient.norm(),
+            biases: self.bias_gradient.norm(),
+        }
+    }
+
+    fn non_finite_update(&self, scale: T) -> Option<NonFiniteKind> {
+        if !self.kernel_gradient.iter().all(|x| x.is_finite()) {
+            return Some(NonFiniteKind::WeightGradient);
+        }
+
+        if !self.bias_gradient.iter().all(|x| x.is_finite()) {
+            return Some(NonFiniteKind::BiasGradient);
+        }
+
+        if !self.kernels.iter().zip(self.kernel_gradient.iter()).all(|(&w, &g)| (w + g * scale).is_finite()) {
+            return Some(NonFiniteKind::Weight);
+        }
+
+        if !self.biases.iter().zip(self.bias_gradient.iter()).all(|(&b, &g)| (b + g * scale).is_finite()) {
+            return Some(NonFiniteKind::Bias);
+        }
+
+        None
+    }
+
+    fn is_finite(&self) -> bool {
+        self.kernels.iter().chain(self.biases.iter()).all(|x| x.is_finite())
+    }
+}
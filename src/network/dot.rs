@@ -0,0 +1,117 @@
+//! Graphviz DOT export of a network's structure, rendered with e.g. `dot -Tsvg network.dot -o network.svg`.
+
+use core::fmt::Write;
+
+use crate::prelude::*;
+
+use super::Network;
+
+/// How `Network::to_dot` draws a network: by default layers of up to 16 units get a node per unit, and
+/// edges between dense layers show their weights.
+#[derive(Clone, Debug)]
+pub struct DotOptions {
+    max_nodes: usize,
+    weights: bool,
+}
+
+impl Default for DotOptions {
+    fn default() -> Self {
+        Self {
+            max_nodes: 16,
+            weights: true,
+        }
+    }
+}
+
+impl DotOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Layers with more units are drawn as a single node, at least 1.
+    pub fn max_nodes(mut self, max_nodes: usize) -> Self {
+        self.max_nodes = max_nodes.max(1);
+        self
+    }
+
+    /// Draws the edge of every weight with a width growing with its magnitude, relative to the largest in its
+    /// layer, blue for positive weights and red for negative ones. Edges to or from summarized layers are
+    /// plain either way.
+    pub fn weights(mut self, weights: bool) -> Self {
+        self.weights = weights;
+        self
+    }
+}
+
+impl Network {
+    /// The network as a Graphviz digraph laid out from left to right, a cluster for the inputs and for every
+    /// layer labeled with its size and activation function, or its `NetworkLayer::kind` for layers other than
+    /// dense ones. Nodes are named `n{column}_{unit}`, column 0 being the inputs and column `i + 1` the outputs
+    /// of layer `i`, and a summarized column is the single node `n{column}`.
+    pub fn to_dot(&self, options: DotOptions) -> String {
+        let sizes = self.layer_sizes();
+        let nodes = |column: usize| -> Vec<String> {
+            if sizes[column] > options.max_nodes {
+                vec![format!("n{column}")]
+            } else {
+                (0..sizes[column]).map(|unit| format!("n{column}_{unit}")).collect()
+            }
+        };
+
+        let mut dot = String::new();
+        writeln!(dot, "digraph network {{").unwrap();
+        writeln!(dot, "    rankdir=LR;").unwrap();
+        writeln!(dot, "    splines=line;").unwrap();
+        writeln!(dot, "    node [shape=circle, label=\"\", width=0.3];").unwrap();
+
+        for (column, &size) in sizes.iter().enumerate() {
+            let label = match column {
+                0 => format!("inputs\\n{size}"),
+                _ => {
+                    let layer = &self.layers[column - 1];
+                    let kind = layer.activation_fn().map_or(layer.kind(), |activation_fn| activation_fn.name());
+                    format!("layer {}\\n{size} {kind}", column - 1)
+                }
+            };
+
+            writeln!(dot, "    subgraph cluster_{column} {{").unwrap();
+            writeln!(dot, "        label=\"{label}\";").unwrap();
+
+            if size > options.max_nodes {
+                writeln!(dot, "        n{column} [shape=box, label=\"{size} units\"];").unwrap();
+            } else {
+                for node in nodes(column) {
+                    writeln!(dot, "        {node};").unwrap();
+                }
+            }
+
+            writeln!(dot, "    }}").unwrap();
+        }
+
+        for (i, layer) in self.layers.iter().enumerate() {
+            let (inputs, outputs) = (nodes(i), nodes(i + 1));
+            let summarized = sizes[i] > options.max_nodes || sizes[i + 1] > options.max_nodes;
+
+            let dense = layer.as_dense().filter(|_| options.weights && !summarized);
+            let max_weight = dense.map_or(0.0, |dense| dense.weights().amax());
+
+            for (input, input_node) in inputs.iter().enumerate() {
+                for (output, output_node) in outputs.iter().enumerate() {
+                    let attributes = match dense.and_then(|dense| dense.get_weight(input, output)) {
+                        Some(&weight) if max_weight > 0.0 => {
+                            let color = if weight < 0.0 { "red" } else { "blue" };
+                            format!(" [penwidth={:.2}, color={color}]", 0.25 + 2.75 * weight.abs() / max_weight)
+                        }
+                        _ if summarized => " [style=bold]".to_string(),
+                        _ => String::new(),
+                    };
+
+                    writeln!(dot, "    {input_node} -> {output_node}{attributes};").unwrap();
+                }
+            }
+        }
+
+        writeln!(dot, "}}").unwrap();
+        dot
+    }
+}
@@ -0,0 +1,106 @@
+use crate::{prelude::*, scalar::Scalar};
+
+use super::{Network, NetworkError};
+
+/// An exponential moving average of a network's parameters, `average = decay * average + (1 - decay) * params`
+/// after every update, in the order of `Network::get_params`. Averaged (Polyak) weights are often better on
+/// unseen data than the last step's.
+#[derive(Clone, Debug)]
+pub struct EmaWeights<T: Scalar = f32> {
+    decay: T,
+    average: Vec<T>,
+    /// The network's own parameters while `apply_to` has swapped the average in.
+    saved: Option<Vec<T>>,
+}
+
+impl<T: Scalar> EmaWeights<T> {
+    /// Starts the average at the network's current parameters. Fails with `InvalidEmaDecay` unless `decay`
+    /// is strictly between 0 and 1.
+    pub fn new(network: &Network<T>, decay: T) -> Result<Self, NetworkError> {
+        if !(decay > T::zero() && decay < T::one()) {
+            return Err(NetworkError::InvalidEmaDecay(decay.to_f64()));
+        }
+
+        Ok(Self {
+            decay,
+            average: network.get_params(),
+            saved: None,
+        })
+    }
+
+    /// Continues an average, e.g. one from a checkpoint. Fails like `new`, and with `ParameterCountMismatch`
+    /// unless the average has a value for every parameter of the network.
+    pub fn with_average(network: &Network<T>, decay: T, average: Vec<T>) -> Result<Self, NetworkError> {
+        let mut ema = Self::new(network, decay)?;
+        if average.len() != ema.average.len() {
+            return Err(NetworkError::ParameterCountMismatch {
+                expected: ema.average.len(),
+                given: average.len(),
+            });
+        }
+
+        ema.average = average;
+        Ok(ema)
+    }
+
+    #[inline]
+    pub fn decay(&self) -> T {
+        self.decay
+    }
+
+    /// The averaged parameters, in the order of `Network::get_params`.
+    #[inline]
+    pub fn average(&self) -> &[T] {
+        &self.average
+    }
+
+    /// Whether `apply_to` has swapped the average into a network that wasn't restored yet.
+    #[inline]
+    pub fn is_applied(&self) -> bool {
+        self.saved.is_some()
+    }
+
+    /// Moves the average towards the network's parameters, to be called after every update.
+    pub fn update(&mut self, network: &Network<T>) -> Result<(), NetworkError> {
+        let params = network.get_params();
+        if params.len() != self.average.len() {
+            return Err(NetworkError::ParameterCountMismatch {
+                expected: self.average.len(),
+                given: params.len(),
+            });
+        }
+
+        let step = T::one() - self.decay;
+        for (average, param) in self.average.iter_mut().zip(params) {
+            *average = self.decay * *average + step * param;
+        }
+
+        Ok(())
+    }
+
+    /// A copy of the network with the averaged parameters.
+    pub fn to_network(&self, network: &Network<T>) -> Result<Network<T>, NetworkError> {
+        let mut averaged = network.clone();
+        averaged.set_params(&self.average)?;
+        Ok(averaged)
+    }
+
+    /// Swaps the averaged parameters into the network, e.g. to evaluate it, keeping its own for `restore`.
+    /// Applying again before restoring keeps the parameters saved first.
+    pub fn apply_to(&mut self, network: &mut Network<T>) -> Result<(), NetworkError> {
+        let params = network.get_params();
+        network.set_params(&self.average)?;
+        self.saved.get_or_insert(params);
+        Ok(())
+    }
+
+    /// Puts back the parameters `apply_to` replaced, exactly. Does nothing if nothing is applied.
+    pub fn restore(&mut self, network: &mut Network<T>) -> Result<(), NetworkError> {
+        if let Some(saved) = &self.saved {
+            network.set_params(saved)?;
+            self.saved = None;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,282 @@
+//! Learned dense vectors for categorical features. The inputs of an `Embedding` are indices stored as
+//! scalars, one per feature, and its output is the concatenation of their embeddings, feature by feature.
+
+use nalgebra::{DMatrix, DMatrixView, DVector, DVectorView};
+use rand::{distr::Distribution, Rng};
+
+use crate::{prelude::*, scalar::Scalar};
+
+use super::{gradients::LayerGradientNorm, layer::LayerError, NetworkLayer, NonFiniteKind};
+
+/// Cloning copies everything, including accumulated gradients and the indices of the last `forward`. As a
+/// `NetworkLayer` it's the first layer of a `Network` whose inputs are indices.
+#[derive(Clone, Debug)]
+pub struct Embedding<T: Scalar = f32> {
+    /// One row per index.
+    embeddings: DMatrix<T>,
+    gradient: DMatrix<T>,
+    features: usize,
+
+    previous_indices: Vec<usize>,
+    /// The indices of every sample of the last training batch run through `NetworkLayer::forward_training_batch`.
+    previous_batch: Option<Vec<Vec<usize>>>,
+    /// Rows with a non-zero gradient since the last `apply_gradient`, possibly repeated.
+    touched: Vec<usize>,
+}
+
+impl<T: Scalar> Embedding<T> {
+    /// A table of `num_embeddings` vectors of `dimension` values, looked up for `features` indices per input.
+    pub fn zeros(num_embeddings: usize, dimension: usize, features: usize) -> Result<Self, LayerError> {
+        if features == 0 || num_embeddings == 0 {
+            return Err(LayerError::ZeroInputSize);
+        }
+
+        if dimension == 0 {
+            return Err(LayerError::ZeroOutputSize);
+        }
+
+        Ok(Self {
+            embeddings: DMatrix::zeros(num_embeddings, dimension),
+            gradient: DMatrix::zeros(num_embeddings, dimension),
+            features,
+
+            previous_indices: Vec::new(),
+            previous_batch: None,
+            touched: Vec::new(),
+        })
+    }
+
+    /// Draws from the thread-local RNG, see `random_with_rng` for reproducible layers.
+    #[cfg(feature = "std")]
+    pub fn random(num_embeddings: usize, dimension: usize, features: usize, distribution: &impl Distribution<T>) -> Result<Self, LayerError> {
+        Self::random_with_rng(num_embeddings, dimension, features, distribution, &mut rand::rng())
+    }
+
+    /// Like `random`, but draws every embedding from the given RNG.
+    pub fn random_with_rng(
+        num_embeddings: usize,
+        dimension: usize,
+        features: usize,
+        distribution: &impl Distribution<T>,
+        rng: &mut impl Rng,
+    ) -> Result<Self, LayerError> {
+        let mut layer = Self::zeros(num_embeddings, dimension, features)?;
+        layer.embeddings.iter_mut().for_each(|x| *x = distribution.sample(rng));
+        Ok(layer)
+    }
+
+    pub fn forward(&mut self, inputs: DVector<T>) -> Result<DVector<T>, LayerError> {
+        self.forward_view(inputs.as_view())
+    }
+
+    /// `forward` for borrowed inputs.
+    pub fn forward_view(&mut self, inputs: DVectorView<T>) -> Result<DVector<T>, LayerError> {
+        self.previous_indices = self.indices(inputs)?;
+        Ok(self.lookup(&self.previous_indices))
+    }
+
+    /// Forward pass that leaves the layer untouched.
+    pub fn predict(&self, inputs: DVectorView<T>) -> Result<DVector<T>, LayerError> {
+        Ok(self.lookup(&self.indices(inputs)?))
+    }
+
+    /// Accumulates the gradient for the last `forward` into the rows it looked up. Indices have no
+    /// gradient, so the returned gradient with respect to the inputs is zero.
+    pub fn backpropagation_step(&mut self, previous_outputs: DVectorView<T>, output_partial_gradient: DVectorView<T>) -> DVector<T> {
+        let indices = core::mem::take(&mut self.previous_indices);
+        self.accumulate_gradient(&indices, output_partial_gradient);
+        self.previous_indices = indices;
+
+        DVector::zeros(self.features)
+    }
+
+    fn accumulate_gradient(&mut self, indices: &[usize], output_partial_gradient: DVectorView<T>) {
+        let dimension = self.dimension();
+
+        for (feature, &index) in indices.iter().enumerate() {
+            let gradient = output_partial_gradient.rows(feature * dimension, dimension).transpose();
+            let mut row = self.gradient.row_mut(index);
+            row += gradient;
+            self.touched.push(index);
+        }
+    }
+
+    /// Applies the accumulated gradient to the rows that were looked up, the others stay untouched.
+    pub fn apply_gradient(&mut self, scale: T) {
+        self.touched.sort_unstable();
+        self.touched.dedup();
+
+        for &index in &self.touched {
+            let gradient = self.gradient.row(index) * scale;
+            let mut row = self.embeddings.row_mut(index);
+            row += gradient;
+        }
+
+        self.zero_gradient();
+    }
+
+    pub(crate) fn zero_gradient(&mut self) {
+        for &index in &self.touched {
+            self.gradient.row_mut(index).fill(T::zero());
+        }
+
+        self.touched.clear();
+    }
+
+    /// The number of indices every input holds.
+    #[inline]
+    pub fn input_size(&self) -> usize { self.features }
+
+    #[inline]
+    pub fn output_size(&self) -> usize { self.features * self.dimension() }
+
+    #[inline]
+    pub fn num_embeddings(&self) -> usize { self.embeddings.nrows() }
+
+    #[inline]
+    pub fn dimension(&self) -> usize { self.embeddings.ncols() }
+
+    /// One row per index.
+    #[inline]
+    pub fn embeddings(&self) -> DMatrixView<'_, T> { self.embeddings.as_view() }
+
+    #[inline]
+    pub fn gradient(&self) -> DMatrixView<'_, T> { self.gradient.as_view() }
+
+    pub fn set_embeddings(&mut self, embeddings: DMatrix<T>) -> Result<(), LayerError> {
+        if embeddings.shape() != self.embeddings.shape() {
+            return Err(LayerError::ParameterShapeMismatch {
+                layer_input_size: self.num_embeddings(),
+                layer_output_size: self.dimension(),
+                given_input_size: embeddings.nrows(),
+                given_output_size: embeddings.ncols(),
+            });
+        }
+
+        self.embeddings = embeddings;
+        Ok(())
+    }
+
+    fn lookup(&self, indices: &[usize]) -> DVector<T> {
+        DVector::from_iterator(
+            self.output_size(),
+            indices.iter().flat_map(|&index| self.embeddings.row(index).iter().copied().collect::<Vec<_>>()),
+        )
+    }
+
+    fn indices(&self, inputs: DVectorView<T>) -> Result<Vec<usize>, LayerError> {
+        if inputs.len() != self.features {
+            return Err(LayerError::InputSizeMismatch {
+                layer_input_size: self.features,
+                given_input_size: inputs.len(),
+            });
+        }
+
+        inputs
+            .iter()
+            .enumerate()
+            .map(|(position, &value)| {
+                let value = value.to_f64();
+                if value >= 0.0 && value.fract() == 0.0 && value < self.num_embeddings() as f64 {
+                    Ok(value as usize)
+                } else {
+                    Err(LayerError::InvalidIndex {
+                        position,
+                        value,
+                        num_embeddings: self.num_embeddings(),
+                    })
+                }
+            })
+            .collect()
+    }
+}
+
+impl<T: Scalar> NetworkLayer<T> for Embedding<T> {
+    #[inline]
+    fn input_size(&self) -> usize { Embedding::input_size(self) }
+
+    #[inline]
+    fn output_size(&self) -> usize { Embedding::output_size(self) }
+
+    fn forward(&mut self, inputs: DVectorView<T>) -> Result<DVector<T>, LayerError> {
+        self.forward_view(inputs)
+    }
+
+    fn predict(&self, inputs: DVectorView<T>) -> Result<DVector<T>, LayerError> {
+        Embedding::predict(self, inputs)
+    }
+
+    fn forward_training_batch(&mut self, inputs: DMatrixView<T>) -> Result<DMatrix<T>, LayerError> {
+        let indices: Vec<Vec<usize>> = inputs.column_iter().map(|input| self.indices(input.as_view())).collect::<Result<_, _>>()?;
+        let mut outputs = DMatrix::zeros(self.output_size(), inputs.ncols());
+
+        for (mut column, indices) in outputs.column_iter_mut().zip(&indices) {
+            column.copy_from(&self.lookup(indices));
+        }
+
+        self.previous_batch = Some(indices);
+        Ok(outputs)
+    }
+
+    /// The gradient with respect to the inputs is zero, like in `backpropagation_step`.
+    fn backpropagation_step_batch(&mut self, _inputs: DMatrixView<T>, output_partial_gradient: &DMatrix<T>) -> Result<DMatrix<T>, LayerError> {
+        let batch = match self.previous_batch.take() {
+            Some(batch) if batch.len() == output_partial_gradient.ncols() && output_partial_gradient.nrows() == self.output_size() => batch,
+            _ => return Err(LayerError::NoTrainingBatch(output_partial_gradient.ncols())),
+        };
+
+        for (indices, gradient) in batch.iter().zip(output_partial_gradient.column_iter()) {
+            self.accumulate_gradient(indices, gradient);
+        }
+
+        Ok(DMatrix::zeros(self.features, output_partial_gradient.ncols()))
+    }
+
+    fn apply_gradient(&mut self, scale: T) {
+        Embedding::apply_gradient(self, scale);
+    }
+
+    fn zero_gradient(&mut self) {
+        Embedding::zero_gradient(self);
+    }
+
+    fn boxed_clone(&self) -> Box<dyn NetworkLayer<T>> {
+        Box::new(self.clone())
+    }
+
+    fn kind(&self) -> &'static str { "embedding" }
+
+    fn parameter_count(&self) -> usize { self.embeddings.len() }
+
+    /// The embedding table in column-major order.
+    fn write_parameters(&self, parameters: &mut Vec<T>) {
+        parameters.extend(self.embeddings.iter());
+    }
+
+    fn read_parameters(&mut self, parameters: &[T]) {
+        self.embeddings.copy_from_slice(parameters);
+    }
+
+    fn gradient_norm(&self) -> LayerGradientNorm<T> {
+        LayerGradientNorm {
+            weights: self.gradient.norm(),
+            biases: T::zero(),
+        }
+    }
+
+    fn non_finite_update(&self, scale: T) -> Option<NonFiniteKind> {
+        if !self.gradient.iter().all(|x| x.is_finite()) {
+            return Some(NonFiniteKind::WeightGradient);
+        }
+
+        if !self.embeddings.iter().zip(self.gradient.iter()).all(|(&w, &g)| (w + g * scale).is_finite()) {
+            return Some(NonFiniteKind::Weight);
+        }
+
+        None
+    }
+
+    fn is_finite(&self) -> bool {
+        self.embeddings.iter().all(|x| x.is_finite())
+    }
+}
@@ -0,0 +1,120 @@
+use nalgebra::{DVector, DVectorView};
+
+use crate::{dataset::Sample, losses::LossFn, prelude::*, scalar::Scalar};
+
+use super::{Network, NetworkError};
+
+/// Networks with the same input and output sizes whose predictions are combined, e.g. copies of an
+/// architecture trained from different seeds. Unlike `Network::average` the members' architectures may differ.
+#[derive(Clone, Debug)]
+pub struct Ensemble<T: Scalar = f32> {
+    networks: Vec<Network<T>>,
+    weights: Vec<T>,
+}
+
+impl<T: Scalar> Ensemble<T> {
+    /// An ensemble whose members all count the same.
+    pub fn new(networks: Vec<Network<T>>) -> Result<Self, NetworkError> {
+        let weights = vec![T::one(); networks.len()];
+        Self::weighted(networks, weights)
+    }
+
+    /// An ensemble whose members count by the matching entry of `weights`, e.g. their validation accuracy.
+    /// The weights are normalized by their sum.
+    pub fn weighted(networks: Vec<Network<T>>, weights: Vec<T>) -> Result<Self, NetworkError> {
+        let (first, rest) = networks.split_first().ok_or(NetworkError::NoNetworks)?;
+
+        if weights.len() != networks.len() {
+            return Err(NetworkError::MergeWeightCountMismatch {
+                networks: networks.len(),
+                weights: weights.len(),
+            });
+        }
+
+        let total_weight = weights.iter().fold(T::zero(), |total, &weight| total + weight);
+        if weights.iter().any(|weight| !weight.is_finite() || *weight < T::zero()) || total_weight <= T::zero() {
+            return Err(NetworkError::InvalidMergeWeights);
+        }
+
+        for (i, network) in rest.iter().enumerate() {
+            if network.input_size() != first.input_size() || network.output_size() != first.output_size() {
+                return Err(NetworkError::EnsembleSizeMismatch {
+                    member: i + 1,
+                    inputs: network.input_size(),
+                    outputs: network.output_size(),
+                    expected_inputs: first.input_size(),
+                    expected_outputs: first.output_size(),
+                });
+            }
+        }
+
+        let weights = weights.into_iter().map(|weight| weight / total_weight).collect();
+        Ok(Self { networks, weights })
+    }
+
+    #[inline]
+    pub fn networks(&self) -> &[Network<T>] { &self.networks }
+
+    /// The members' weights, summing to 1.
+    #[inline]
+    pub fn weights(&self) -> &[T] { &self.weights }
+
+    #[inline]
+    pub fn input_size(&self) -> usize { self.networks[0].input_size() }
+
+    #[inline]
+    pub fn output_size(&self) -> usize { self.networks[0].output_size() }
+
+    /// Every member's prediction, in order.
+    pub fn member_predictions(&self, input: DVectorView<T>) -> Result<Vec<DVector<T>>, NetworkError> {
+        self.networks.iter().map(|network| network.predict(input)).collect()
+    }
+
+    /// The weighted mean of the members' predictions.
+    pub fn predict(&self, input: DVectorView<T>) -> Result<DVector<T>, NetworkError> {
+        let mut mean = DVector::zeros(self.output_size());
+
+        for (prediction, &weight) in self.member_predictions(input)?.iter().zip(self.weights.iter()) {
+            mean += prediction * weight;
+        }
+
+        Ok(mean)
+    }
+
+    /// The class most members predict, every member voting for the argmax of its output with its weight.
+    /// Ties go to the lowest class.
+    pub fn vote(&self, input: DVectorView<T>) -> Result<usize, NetworkError> {
+        let mut votes = DVector::<T>::zeros(self.output_size());
+
+        for (prediction, &weight) in self.member_predictions(input)?.iter().zip(self.weights.iter()) {
+            votes[argmax(prediction.as_view())] += weight;
+        }
+
+        Ok(argmax(votes.as_view()))
+    }
+
+    /// The mean loss of `predict` over the dataset, like `Network::evaluate`.
+    pub fn evaluate(&self, dataset: &[Sample<T>], loss: &impl LossFn<T>) -> Result<T, NetworkError> {
+        if dataset.is_empty() {
+            return Err(NetworkError::EmptyDataset);
+        }
+
+        let mut total_loss = T::zero();
+
+        for sample in dataset.iter() {
+            let outputs = self.predict(sample.inputs())?;
+            total_loss += loss.apply(outputs.as_view(), sample.expected_outputs())?;
+        }
+
+        Ok(total_loss / T::from_count(dataset.len()))
+    }
+}
+
+/// The index of the first largest value.
+fn argmax<T: Scalar>(values: DVectorView<T>) -> usize {
+    values
+        .iter()
+        .enumerate()
+        .fold((0, values[0]), |(best, max), (i, &x)| if x > max { (i, x) } else { (best, max) })
+        .0
+}
@@ -0,0 +1,109 @@
+use nalgebra::{DMatrixView, DVectorView};
+use rand::Rng;
+
+use crate::{dataset::augment::standard_normal, prelude::*, scalar::Scalar};
+
+use super::Network;
+
+/// L2 norms of a layer's accumulated gradients.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct LayerGradientNorm<T: Scalar = f32> {
+    pub weights: T,
+    pub biases: T,
+}
+
+impl<T: Scalar> LayerGradientNorm<T> {
+    /// The norm of the weight and bias gradients taken together.
+    pub fn total(&self) -> T {
+        (self.weights * self.weights + self.biases * self.biases).sqrt()
+    }
+}
+
+/// Gradient norms outside of which `gradient_health` reports a problem.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GradientThresholds<T: Scalar = f32> {
+    /// Any layer whose gradient norm is below this is vanishing.
+    pub vanishing: T,
+    /// A global gradient norm above this is exploding.
+    pub exploding: T,
+}
+
+impl<T: Scalar> Default for GradientThresholds<T> {
+    fn default() -> Self {
+        Self {
+            vanishing: T::constant(1e-7),
+            exploding: T::constant(1e3),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum GradientHealth<T: Scalar = f32> {
+    Healthy,
+    /// The global gradient norm is above `GradientThresholds::exploding`, or not finite.
+    Exploding { norm: T },
+    /// The gradient of `layer`, the first one from the input side, is below `GradientThresholds::vanishing`.
+    Vanishing { layer: usize, norm: T },
+}
+
+impl<T: Scalar> Network<T> {
+    /// The norms of every layer's gradients accumulated since the last `apply_gradient`, so they're
+    /// readable between `backpropagate` and the update. Batch normalization parameters aren't included.
+    pub fn gradient_norms(&self) -> Vec<LayerGradientNorm<T>> {
+        self.layers.iter().map(|layer| layer.gradient_norm()).collect()
+    }
+
+    /// The weight and bias gradients of every dense layer with the layer's index, e.g. to find a layer that
+    /// doesn't learn. They're what `backpropagate` accumulated, so they're only meaningful between it and
+    /// `apply_gradients`, which zeroes them. Layers of other kinds are skipped.
+    pub fn gradients(&self) -> impl Iterator<Item = (usize, DMatrixView<'_, T>, DVectorView<'_, T>)> {
+        self.layers
+            .iter()
+            .enumerate()
+            .filter_map(|(index, layer)| layer.as_dense().map(|layer| (index, layer.weight_gradient(), layer.bias_gradient())))
+    }
+
+    /// The L2 norm of all accumulated gradients as one vector.
+    pub fn gradient_norm(&self) -> T {
+        self.gradient_norms()
+            .iter()
+            .fold(T::zero(), |sum, norm| sum + norm.weights * norm.weights + norm.biases * norm.biases)
+            .sqrt()
+    }
+
+    /// Classifies the accumulated gradients, exploding takes precedence over vanishing.
+    pub fn gradient_health(&self, thresholds: &GradientThresholds<T>) -> GradientHealth<T> {
+        let norms = self.gradient_norms();
+        let global = norms.iter().fold(T::zero(), |sum, norm| sum + norm.total() * norm.total()).sqrt();
+
+        if !global.is_finite() || global > thresholds.exploding {
+            return GradientHealth::Exploding { norm: global };
+        }
+
+        norms
+            .iter()
+            .enumerate()
+            .find(|(_, norm)| norm.total() < thresholds.vanishing)
+            .map_or(GradientHealth::Healthy, |(layer, norm)| GradientHealth::Vanishing {
+                layer,
+                norm: norm.total(),
+            })
+    }
+
+    /// Adds independent zero-mean gaussian noise with the given standard deviation to every accumulated weight
+    /// and bias gradient of the dense layers, e.g. to help training escape poor regions. Nothing is drawn and
+    /// nothing changes for a standard deviation of 0.
+    pub fn add_gradient_noise(&mut self, std_dev: T, rng: &mut (impl Rng + ?Sized)) {
+        if std_dev == T::zero() {
+            return;
+        }
+
+        for layer in self.layers.iter_mut().filter_map(|layer| layer.as_dense_mut()) {
+            let (weight_gradient, bias_gradient) = layer.gradients_mut();
+
+            for gradient in weight_gradient.iter_mut().chain(bias_gradient.into_iter().flat_map(|biases| biases.iter_mut())) {
+                *gradient += T::constant(standard_normal(rng) as f64) * std_dev;
+            }
+        }
+    }
+}
@@ -0,0 +1,307 @@
+//! Gated recurrent units, with `z` the update gate, `r` the reset gate and `n` the candidate state:
+//!
+//! - `z[t] = sigmoid(W_z * x[t] + U_z * h[t - 1] + b_z)`
+//! - `r[t] = sigmoid(W_r * x[t] + U_r * h[t - 1] + b_r)`
+//! - `n[t] = tanh(W_n * x[t] + U_n * (r[t] ⊙ h[t - 1]) + b_n)`
+//! - `h[t] = (1 - z[t]) ⊙ n[t] + z[t] ⊙ h[t - 1]`
+//!
+//! with `h[-1] = 0`, so every sequence starts from a zero state.
+
+use nalgebra::{DMatrix, DMatrixView, DVector, DVectorView};
+use rand::{distr::Distribution, Rng};
+
+use crate::{prelude::*, scalar::Scalar};
+
+use super::{
+    layer::LayerError,
+    recurrent::{check_shape, check_state, SequenceLayer},
+};
+
+/// The input weights `W`, recurrent weights `U` and biases `b` of one GRU gate, with their gradients.
+#[derive(Clone, Debug)]
+pub struct Gate<T: Scalar = f32> {
+    input_weights: DMatrix<T>,
+    input_weight_gradient: DMatrix<T>,
+    recurrent_weights: DMatrix<T>,
+    recurrent_weight_gradient: DMatrix<T>,
+    biases: DVector<T>,
+    bias_gradient: DVector<T>,
+}
+
+impl<T: Scalar> Gate<T> {
+    fn zeros(input_size: usize, hidden_size: usize) -> Self {
+        Self {
+            input_weights: DMatrix::zeros(hidden_size, input_size),
+            input_weight_gradient: DMatrix::zeros(hidden_size, input_size),
+            recurrent_weights: DMatrix::zeros(hidden_size, hidden_size),
+            recurrent_weight_gradient: DMatrix::zeros(hidden_size, hidden_size),
+            biases: DVector::zeros(hidden_size),
+            bias_gradient: DVector::zeros(hidden_size),
+        }
+    }
+
+    /// `W * input + U * state + b`.
+    fn weighted_sums(&self, input: DVectorView<T>, state: &DVector<T>) -> DVector<T> {
+        let mut weighted_sums = &self.biases + &self.input_weights * input;
+        weighted_sums.gemv(T::one(), &self.recurrent_weights, state, T::one());
+        weighted_sums
+    }
+
+    /// Accumulates the gradient for the gate's weighted sums `deltas`, adds the input gradient to
+    /// `input_gradient` and returns the gradient with respect to `state`.
+    fn accumulate(&mut self, deltas: &DVector<T>, input: &DVector<T>, state: &DVector<T>, input_gradient: &mut DVector<T>) -> DVector<T> {
+        self.bias_gradient += deltas;
+        self.input_weight_gradient.ger(T::one(), deltas, input, T::one());
+        self.recurrent_weight_gradient.ger(T::one(), deltas, state, T::one());
+
+        *input_gradient += self.input_weights.tr_mul(deltas);
+        self.recurrent_weights.tr_mul(deltas)
+    }
+
+    fn apply_gradient(&mut self, scale: T) {
+        self.input_weights += &self.input_weight_gradient * scale;
+        self.recurrent_weights += &self.recurrent_weight_gradient * scale;
+        self.biases += &self.bias_gradient * scale;
+        self.zero_gradient();
+    }
+
+    fn zero_gradient(&mut self) {
+        self.input_weight_gradient.fill(T::zero());
+        self.recurrent_weight_gradient.fill(T::zero());
+        self.bias_gradient.fill(T::zero());
+    }
+
+    /// One row per hidden unit and one column per input.
+    #[inline]
+    pub fn input_weights(&self) -> DMatrixView<'_, T> { self.input_weights.as_view() }
+
+    /// One row per hidden unit and one column per unit of the state it reads.
+    #[inline]
+    pub fn recurrent_weights(&self) -> DMatrixView<'_, T> { self.recurrent_weights.as_view() }
+
+    #[inline]
+    pub fn biases(&self) -> DVectorView<'_, T> { self.biases.as_view() }
+
+    #[inline]
+    pub fn input_weight_gradient(&self) -> DMatrixView<'_, T> { self.input_weight_gradient.as_view() }
+
+    #[inline]
+    pub fn recurrent_weight_gradient(&self) -> DMatrixView<'_, T> { self.recurrent_weight_gradient.as_view() }
+
+    #[inline]
+    pub fn bias_gradient(&self) -> DVectorView<'_, T> { self.bias_gradient.as_view() }
+
+    pub fn set_input_weights(&mut self, weights: DMatrix<T>) -> Result<(), LayerError> {
+        check_shape(&self.input_weights, &weights)?;
+        self.input_weights = weights;
+        Ok(())
+    }
+
+    pub fn set_recurrent_weights(&mut self, weights: DMatrix<T>) -> Result<(), LayerError> {
+        check_shape(&self.recurrent_weights, &weights)?;
+        self.recurrent_weights = weights;
+        Ok(())
+    }
+
+    pub fn set_biases(&mut self, biases: DVector<T>) -> Result<(), LayerError> {
+        if biases.len() != self.biases.len() {
+            return Err(LayerError::BiasSizeMismatch {
+                layer_output_size: self.biases.len(),
+                given_size: biases.len(),
+            });
+        }
+
+        self.biases = biases;
+        Ok(())
+    }
+}
+
+/// What a step of the last `forward` needs for backpropagation.
+#[derive(Clone, Debug)]
+struct GruStep<T: Scalar> {
+    input: DVector<T>,
+    previous_state: DVector<T>,
+    update: DVector<T>,
+    reset: DVector<T>,
+    candidate: DVector<T>,
+}
+
+/// A gated recurrent unit layer. Cloning copies everything, including accumulated gradients and the
+/// sequence recorded by the last `forward`, which every `forward` replaces, so nothing of one sequence
+/// leaks into the next.
+#[derive(Clone, Debug)]
+pub struct GruLayer<T: Scalar = f32> {
+    update: Gate<T>,
+    reset: Gate<T>,
+    candidate: Gate<T>,
+
+    previous_steps: Vec<GruStep<T>>,
+}
+
+fn sigmoid<T: Scalar>(x: T) -> T {
+    T::one() / (T::one() + (-x).exp())
+}
+
+impl<T: Scalar> GruLayer<T> {
+    pub fn zeros(input_size: usize, hidden_size: usize) -> Result<Self, LayerError> {
+        if input_size == 0 {
+            return Err(LayerError::ZeroInputSize);
+        }
+
+        if hidden_size == 0 {
+            return Err(LayerError::ZeroOutputSize);
+        }
+
+        Ok(Self {
+            update: Gate::zeros(input_size, hidden_size),
+            reset: Gate::zeros(input_size, hidden_size),
+            candidate: Gate::zeros(input_size, hidden_size),
+
+            previous_steps: Vec::new(),
+        })
+    }
+
+    /// Draws from the thread-local RNG, see `random_with_rng` for reproducible layers.
+    #[cfg(feature = "std")]
+    pub fn random(input_size: usize, hidden_size: usize, distribution: &impl Distribution<T>) -> Result<Self, LayerError> {
+        Self::random_with_rng(input_size, hidden_size, distribution, &mut rand::rng())
+    }
+
+    /// Like `random`, but draws every weight and bias from the given RNG.
+    pub fn random_with_rng(
+        input_size: usize,
+        hidden_size: usize,
+        distribution: &impl Distribution<T>,
+        rng: &mut impl Rng,
+    ) -> Result<Self, LayerError> {
+        let mut layer = Self::zeros(input_size, hidden_size)?;
+
+        for gate in [&mut layer.update, &mut layer.reset, &mut layer.candidate] {
+            gate.input_weights
+                .iter_mut()
+                .chain(gate.recurrent_weights.iter_mut())
+                .chain(gate.biases.iter_mut())
+                .for_each(|x| *x = distribution.sample(rng));
+        }
+
+        Ok(layer)
+    }
+
+    #[inline]
+    pub fn update_gate(&self) -> &Gate<T> { &self.update }
+
+    #[inline]
+    pub fn update_gate_mut(&mut self) -> &mut Gate<T> { &mut self.update }
+
+    #[inline]
+    pub fn reset_gate(&self) -> &Gate<T> { &self.reset }
+
+    #[inline]
+    pub fn reset_gate_mut(&mut self) -> &mut Gate<T> { &mut self.reset }
+
+    /// The gate computing the candidate state, whose recurrent weights read the reset state `r ⊙ h`.
+    #[inline]
+    pub fn candidate_gate(&self) -> &Gate<T> { &self.candidate }
+
+    #[inline]
+    pub fn candidate_gate_mut(&mut self) -> &mut Gate<T> { &mut self.candidate }
+
+    fn step(&self, input: DVectorView<T>, state: &DVector<T>) -> GruStep<T> {
+        let update = self.update.weighted_sums(input, state).map(sigmoid);
+        let reset = self.reset.weighted_sums(input, state).map(sigmoid);
+        let candidate = self.candidate.weighted_sums(input, &reset.component_mul(state)).map(|x| x.tanh());
+
+        GruStep {
+            input: input.clone_owned(),
+            previous_state: state.clone(),
+            update,
+            reset,
+            candidate,
+        }
+    }
+
+    fn check_inputs(&self, inputs: &[DVector<T>]) -> Result<(), LayerError> {
+        match inputs.iter().find(|input| input.len() != self.input_size()) {
+            Some(input) => Err(LayerError::InputSizeMismatch {
+                layer_input_size: self.input_size(),
+                given_input_size: input.len(),
+            }),
+            None => Ok(()),
+        }
+    }
+}
+
+impl<T: Scalar> GruStep<T> {
+    fn state(&self) -> DVector<T> {
+        self.update.zip_zip_map(&self.candidate, &self.previous_state, |z, n, h| (T::one() - z) * n + z * h)
+    }
+}
+
+impl<T: Scalar> SequenceLayer<T> for GruLayer<T> {
+    fn forward_from(&mut self, inputs: &[DVector<T>], state: &DVector<T>) -> Result<Vec<DVector<T>>, LayerError> {
+        self.check_inputs(inputs)?;
+        check_state(state, self.hidden_size())?;
+        self.previous_steps.clear();
+
+        let mut state = state.clone();
+        let mut states = Vec::with_capacity(inputs.len());
+
+        for input in inputs {
+            let step = self.step(input.as_view(), &state);
+            state = step.state();
+            states.push(state.clone());
+            self.previous_steps.push(step);
+        }
+
+        Ok(states)
+    }
+
+    fn predict(&self, inputs: &[DVector<T>]) -> Result<Vec<DVector<T>>, LayerError> {
+        self.check_inputs(inputs)?;
+        let mut state = DVector::zeros(self.hidden_size());
+
+        Ok(inputs
+            .iter()
+            .map(|input| {
+                state = self.step(input.as_view(), &state).state();
+                state.clone()
+            })
+            .collect())
+    }
+
+    fn step_back(&mut self, step: usize, state_gradient: &DVector<T>, input_gradient: &mut DVector<T>) -> DVector<T> {
+        let GruStep { input, previous_state, update, reset, candidate } = &self.previous_steps[step];
+
+        let candidate_deltas = state_gradient.zip_zip_map(update, candidate, |g, z, n| g * (T::one() - z) * (T::one() - n * n));
+        let update_deltas = state_gradient.zip_zip_map(update, &(previous_state - candidate), |g, z, d| g * d * z * (T::one() - z));
+        let mut previous_state_gradient = state_gradient.component_mul(update);
+
+        let reset_state = reset.component_mul(previous_state);
+        let reset_state_gradient = self.candidate.accumulate(&candidate_deltas, input, &reset_state, input_gradient);
+        previous_state_gradient += reset_state_gradient.component_mul(reset);
+
+        let reset_deltas = reset_state_gradient.zip_zip_map(previous_state, reset, |g, h, r| g * h * r * (T::one() - r));
+        previous_state_gradient += self.reset.accumulate(&reset_deltas, input, previous_state, input_gradient);
+        previous_state_gradient += self.update.accumulate(&update_deltas, input, previous_state, input_gradient);
+
+        previous_state_gradient
+    }
+
+    fn apply_gradient(&mut self, scale: T) {
+        for gate in [&mut self.update, &mut self.reset, &mut self.candidate] {
+            gate.apply_gradient(scale);
+        }
+    }
+
+    fn zero_gradient(&mut self) {
+        for gate in [&mut self.update, &mut self.reset, &mut self.candidate] {
+            gate.zero_gradient();
+        }
+    }
+
+    #[inline]
+    fn input_size(&self) -> usize { self.update.input_weights.ncols() }
+
+    #[inline]
+    fn hidden_size(&self) -> usize { self.update.input_weights.nrows() }
+}
@@ -0,0 +1,108 @@
+//! Import of dense networks trained elsewhere, from a JSON interchange format:
+//!
+//! ```json
+//! {
+//!   "layer_sizes": [2, 3, 1],
+//!   "activations": ["sigmoid", "sigmoid"],
+//!   "layers": [
+//!     { "kernel": [[0.1, 0.2, 0.3], [0.4, 0.5, 0.6]], "bias": [0.0, 0.1, 0.2] },
+//!     { "kernel": [[0.7], [0.8], [0.9]], "bias": [0.3] }
+//!   ]
+//! }
+//! ```
+//!
+//! Kernels use the `[input][output]` layout of Keras' `Dense` layers and NumPy's `x @ kernel`, which is
+//! the transpose of the crate's `[output][input]` weight matrices, so they're transposed on import. A layer
+//! without `bias`, like a Keras `Dense(use_bias=False)`, is imported as a layer without biases.
+
+use nalgebra::{DMatrix, DVector};
+
+use crate::{activations, json::Value, prelude::*};
+
+use super::{
+    layer::Layer,
+    Network,
+    NetworkError,
+    NetworkLoadError,
+};
+
+impl Network {
+    pub fn import_dense_json(json: &str) -> Result<Self, NetworkLoadError> {
+        let json = Value::parse(json)?;
+
+        let layer_sizes = json
+            .field("$", "layer_sizes")?
+            .as_array("$.layer_sizes")?
+            .iter()
+            .enumerate()
+            .map(|(i, size)| size.as_usize(&format!("$.layer_sizes[{i}]")))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        super::check_layer_sizes(&layer_sizes)?;
+
+        let activation_names = json.field("$", "activations")?.as_array("$.activations")?;
+        let layer_parameters = json.field("$", "layers")?.as_array("$.layers")?;
+        let layer_count = layer_sizes.len() - 1;
+
+        for (given_count, name) in [(activation_names.len(), "activations"), (layer_parameters.len(), "layers")] {
+            if given_count != layer_count {
+                return Err(NetworkLoadError::LayerCountMismatch {
+                    expected: layer_count,
+                    found: given_count,
+                    field: name,
+                });
+            }
+        }
+
+        let mut layers = Vec::with_capacity(layer_count);
+
+        for (layer, sizes) in layer_sizes.windows(2).enumerate() {
+            let (input_size, output_size) = (sizes[0], sizes[1]);
+            let path = format!("$.layers[{layer}]");
+
+            let name = activation_names[layer].as_str(&format!("$.activations[{layer}]"))?;
+            let activation_fn = activations::from_name(name).ok_or_else(|| NetworkLoadError::UnknownActivation {
+                layer,
+                name: name.to_string(),
+            })?;
+
+            let kernel = layer_parameters[layer].field(&path, "kernel")?.as_array(&format!("{path}.kernel"))?;
+            if kernel.len() != input_size {
+                return Err(NetworkLoadError::WeightShapeMismatch { layer, input_size, output_size });
+            }
+
+            // The sizes come from the document, so the values grow with the rows that check out instead of
+            // being allocated upfront.
+            let mut kernel_values = Vec::new();
+            for (input, row) in kernel.iter().enumerate() {
+                let row = row.as_f32_vec(&format!("{path}.kernel[{input}]"))?;
+
+                if row.len() != output_size {
+                    return Err(NetworkLoadError::WeightShapeMismatch { layer, input_size, output_size });
+                }
+
+                kernel_values.extend(row);
+            }
+
+            let biases = layer_parameters[layer]
+                .optional_field(&path, "bias")?
+                .map(|biases| biases.as_f32_vec(&format!("{path}.bias")))
+                .transpose()?;
+
+            let mut dense = Layer::zeros(input_size, output_size, activation_fn).map_err(NetworkError::from)?;
+            // The kernel rows are inputs, so reading it row by row into a column major
+            // `output_size x input_size` matrix transposes it.
+            dense.set_weights(DMatrix::from_column_slice(output_size, input_size, &kernel_values))
+                .map_err(NetworkError::from)?;
+
+            match biases {
+                Some(biases) => dense.set_biases(DVector::from_vec(biases)).map_err(NetworkError::from)?,
+                None => dense = dense.without_bias(),
+            }
+
+            layers.push(dense);
+        }
+
+        Ok(Self::from_dense_layers(layers))
+    }
+}
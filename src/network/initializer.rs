@@ -0,0 +1,135 @@
+use rand::{
+    distr::{Distribution, Uniform},
+    Rng, RngCore,
+};
+
+use crate::{activations::ActivationFn, dataset::augment::standard_normal, prelude::*, scalar::Scalar};
+
+use super::layer::{Layer, LayerError};
+
+/// How the weights and biases of a new layer are drawn.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Initializer {
+    Zeros,
+    /// Uniform in `low..high`.
+    Uniform(f32, f32),
+    /// Gaussian with the given mean and standard deviation.
+    Normal(f32, f32),
+    /// Glorot uniform weights in `±sqrt(6 / (fan_in + fan_out))` and zero biases, suited to sigmoid and tanh.
+    XavierUniform,
+    /// Glorot normal weights with variance `2 / (fan_in + fan_out)` and zero biases.
+    XavierNormal,
+    /// He uniform weights in `±sqrt(6 / fan_in)` and zero biases, suited to ReLU.
+    HeUniform,
+    /// He normal weights with variance `2 / fan_in` and zero biases.
+    HeNormal,
+    /// LeCun normal weights with variance `1 / fan_in` and zero biases.
+    LecunNormal,
+}
+
+/// Gaussian distribution over `f32`, sampled with the Box-Muller transform.
+#[derive(Clone, Copy, Debug)]
+pub struct Normal {
+    mean: f32,
+    std_dev: f32,
+}
+
+impl Normal {
+    pub fn new(mean: f32, std_dev: f32) -> Self {
+        Self { mean, std_dev }
+    }
+}
+
+impl Distribution<f32> for Normal {
+    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> f32 {
+        self.mean + standard_normal(rng) * self.std_dev
+    }
+}
+
+/// A `Distribution` that can be used as a trait object, which `Distribution` itself can't because its
+/// `sample` is generic over the RNG. Every distribution implements it, and `&dyn DynDistribution<T>` is a
+/// `Distribution<T>` again, e.g. for a list of different distributions like in `Network::random_per_layer`.
+pub trait DynDistribution<T> {
+    fn sample_dyn(&self, rng: &mut dyn RngCore) -> T;
+}
+
+impl<T, D: Distribution<T>> DynDistribution<T> for D {
+    fn sample_dyn(&self, rng: &mut dyn RngCore) -> T {
+        self.sample(rng)
+    }
+}
+
+impl<T> Distribution<T> for dyn DynDistribution<T> + '_ {
+    fn sample<R: Rng + ?Sized>(&self, mut rng: &mut R) -> T {
+        self.sample_dyn(&mut rng)
+    }
+}
+
+impl Initializer {
+    /// The scheme suited to the activation function, by its `ActivationFn::name`: He for ReLU, and Xavier for
+    /// anything else, including sigmoid, tanh and linear layers.
+    pub fn recommended_for<T: Scalar>(activation_fn: &dyn ActivationFn<T>) -> Self {
+        match activation_fn.name() {
+            "relu" => Initializer::HeNormal,
+            _ => Initializer::XavierUniform,
+        }
+    }
+
+    pub(crate) fn layer(
+        &self,
+        input_size: usize,
+        output_size: usize,
+        activation_fn: Box<dyn ActivationFn>,
+        rng: &mut impl Rng,
+    ) -> Result<Layer, LayerError> {
+        match *self {
+            Initializer::Zeros => Layer::zeros(input_size, output_size, activation_fn),
+            Initializer::Uniform(low, high) => {
+                let distribution = Uniform::new(low, high).map_err(|_| LayerError::InvalidInitializer(*self))?;
+                Layer::random_with_rng(input_size, output_size, activation_fn, &distribution, rng)
+            }
+            Initializer::Normal(mean, std_dev) => {
+                if !(std_dev >= 0.0 && std_dev.is_finite() && mean.is_finite()) {
+                    return Err(LayerError::InvalidInitializer(*self));
+                }
+
+                Layer::random_with_rng(input_size, output_size, activation_fn, &Normal::new(mean, std_dev), rng)
+            }
+            Initializer::XavierUniform | Initializer::HeUniform => {
+                let limit = (3.0 * self.fan_variance(input_size, output_size)).sqrt();
+                let distribution = Uniform::new_inclusive(-limit, limit).unwrap();
+                fan_layer(input_size, output_size, activation_fn, &distribution, rng)
+            }
+            Initializer::XavierNormal | Initializer::HeNormal | Initializer::LecunNormal => {
+                let std_dev = self.fan_variance(input_size, output_size).sqrt();
+                fan_layer(input_size, output_size, activation_fn, &Normal::new(0.0, std_dev), rng)
+            }
+        }
+    }
+
+    /// The weight variance of the fan based schemes.
+    fn fan_variance(&self, fan_in: usize, fan_out: usize) -> f32 {
+        match self {
+            Initializer::XavierUniform | Initializer::XavierNormal => 2.0 / (fan_in + fan_out) as f32,
+            Initializer::LecunNormal => 1.0 / fan_in as f32,
+            _ => 2.0 / fan_in as f32,
+        }
+    }
+}
+
+/// A layer with weights drawn from the distribution and zero biases.
+fn fan_layer(
+    input_size: usize,
+    output_size: usize,
+    activation_fn: Box<dyn ActivationFn>,
+    distribution: &impl Distribution<f32>,
+    rng: &mut impl Rng,
+) -> Result<Layer, LayerError> {
+    let mut layer = Layer::zeros(input_size, output_size, activation_fn)?;
+
+    for weight in layer.weights_mut().iter_mut() {
+        *weight = distribution.sample(rng);
+    }
+
+    Ok(layer)
+}
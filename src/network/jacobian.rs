@@ -0,0 +1,90 @@
+//! Derivatives of a network's outputs with respect to its inputs, e.g. to see which inputs a prediction
+//! depends on most.
+
+use nalgebra::{DMatrix, DVector, DVectorView};
+
+use crate::{losses::LossFn, prelude::*, scalar::Scalar};
+
+use super::{Network, NetworkError};
+
+/// What the backward pass of a layer needs from the forward pass.
+enum LayerPass<T: Scalar> {
+    /// A dense layer's weighted sums and outputs.
+    Dense(DVector<T>, DVector<T>),
+    /// The inputs of a layer of another kind.
+    Other(DVector<T>),
+}
+
+impl<T: Scalar> Network<T> {
+    /// The Jacobian of the outputs with respect to the inputs at `input`, with one row per output and one
+    /// column per input, so entry `(i, j)` is how much output `i` changes with input `j`. It's the derivative
+    /// of `predict`, so batch normalization uses its running statistics. The accumulated gradients and
+    /// everything else about the network are left untouched.
+    pub fn jacobian(&self, input: DVectorView<T>) -> Result<DMatrix<T>, NetworkError> {
+        self.backpropagate_rows(input, |outputs| Ok(DMatrix::identity(outputs.len(), outputs.len())))
+    }
+
+    /// The gradient of the loss with respect to the inputs at `input`, a saliency map for a single number.
+    /// It's `jacobian(input)` transposed times the loss's gradient, but costs a single backward pass.
+    pub fn input_gradient(
+        &self,
+        input: DVectorView<T>,
+        loss: &impl LossFn<T>,
+        expected_outputs: DVectorView<T>,
+    ) -> Result<DVector<T>, NetworkError> {
+        self.check_loss(loss)?;
+
+        let gradient = self.backpropagate_rows(input, |outputs| {
+            let gradient = loss.partial_gradient(outputs.as_view(), expected_outputs)?;
+            Ok(DMatrix::from_row_slice(1, gradient.len(), gradient.as_slice()))
+        })?;
+
+        Ok(gradient.row(0).transpose())
+    }
+
+    /// Runs `predict` on `input`, seeds the backward pass with the rows `seed` gives for the outputs, the
+    /// derivatives of something with respect to them, and returns those rows with respect to the inputs.
+    /// Dense layers backpropagate the rows directly. Other layers backpropagate them as a batch of copies of
+    /// their input through a clone of themselves, so their own gradients don't change either.
+    fn backpropagate_rows(
+        &self,
+        input: DVectorView<T>,
+        seed: impl FnOnce(&DVector<T>) -> Result<DMatrix<T>, NetworkError>,
+    ) -> Result<DMatrix<T>, NetworkError> {
+        let mut passes = Vec::with_capacity(self.layers.len());
+        let mut outputs = input.into_owned();
+
+        for layer in &self.layers {
+            match layer.as_dense() {
+                Some(dense) => {
+                    let (weighted_sums, activations) = dense.feed(outputs.as_view())?;
+                    passes.push(LayerPass::Dense(weighted_sums, activations.clone()));
+                    outputs = activations;
+                }
+                None => {
+                    let activations = layer.predict(outputs.as_view())?;
+                    passes.push(LayerPass::Other(outputs));
+                    outputs = activations;
+                }
+            }
+        }
+
+        let mut rows = seed(&outputs)?;
+
+        for (layer, pass) in self.layers.iter().zip(passes).rev() {
+            rows = match pass {
+                LayerPass::Dense(weighted_sums, activations) => {
+                    layer.as_dense().unwrap().input_jacobian(weighted_sums.as_view(), activations.as_view(), &rows)
+                }
+                LayerPass::Other(inputs) => {
+                    let mut layer = layer.boxed_clone();
+                    let batch = DMatrix::from_fn(inputs.len(), rows.nrows(), |i, _| inputs[i]);
+                    layer.forward_training_batch(batch.as_view())?;
+                    layer.backpropagation_step_batch(batch.as_view(), &rows.transpose())?.transpose()
+                }
+            };
+        }
+
+        Ok(rows)
+    }
+}
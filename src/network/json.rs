@@ -0,0 +1,211 @@
+//! The JSON model format of `Network::to_json`:
+//!
+//! ```json
+//! {
+//!   "format": "neural",
+//!   "version": 1,
+//!   "layers": [
+//!     {
+//!       "input_size": 2,
+//!       "output_size": 1,
+//!       "activation": "sigmoid",
+//!       "weights": [[0.5, -0.25]],
+//!       "biases": [0.1]
+//!     }
+//!   ]
+//! }
+//! ```
+//!
+//! `weights` holds one row per output, each with one weight per input. A batch normalized layer also has
+//! a `batch_norm` object with `gamma`, `beta`, `running_mean` and `running_variance` arrays and the
+//! `momentum` and `epsilon` numbers. A layer sharing the parameters of an earlier one, see
+//! `Network::share_parameters`, has a `shared_with` index and a copy of that layer's weights and biases.
+//! A maxout layer has the activation `maxout`, its first piece as `weights` and `biases` and the others
+//! in a `pieces` array of objects with `weights` and `biases` of their own.
+//! Unknown keys are ignored when loading.
+
+use nalgebra::{DMatrix, DVector};
+
+use crate::{
+    json::{JsonError, Value},
+    prelude::*,
+};
+
+use super::{
+    serialization::{BatchNormRecord, LayerRecord},
+    Network,
+    NetworkError,
+    NetworkLoadError,
+};
+
+const FORMAT_NAME: &str = "neural";
+pub const JSON_FORMAT_VERSION: u32 = 1;
+
+impl Network {
+    /// Fails with `NotDense` if the network has layers other than dense and maxout ones.
+    pub fn to_json(&self) -> Result<String, NetworkError> {
+        Ok(self.to_json_value()?.to_string())
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, NetworkLoadError> {
+        Self::from_json_value(&Value::parse(json)?)
+    }
+
+    pub(crate) fn to_json_value(&self) -> Result<Value, NetworkError> {
+        let layers = self.to_records()?.iter().map(layer_to_json).collect();
+
+        Ok(Value::object([
+            ("format", Value::String(FORMAT_NAME.to_string())),
+            ("version", Value::Number(JSON_FORMAT_VERSION as f64)),
+            ("layers", Value::Array(layers)),
+        ]))
+    }
+
+    /// Paths in errors start at `$` even if the network is nested in another document.
+    pub(crate) fn from_json_value(json: &Value) -> Result<Self, NetworkLoadError> {
+        if json.field("$", "format")?.as_str("$.format")? != FORMAT_NAME {
+            return Err(NetworkLoadError::BadMagic);
+        }
+
+        let version = json.field("$", "version")?.as_usize("$.version")?;
+        if version != JSON_FORMAT_VERSION as usize {
+            return Err(NetworkLoadError::UnsupportedVersion {
+                found: version as u32,
+                supported: JSON_FORMAT_VERSION,
+            });
+        }
+
+        let records = json
+            .field("$", "layers")?
+            .as_array("$.layers")?
+            .iter()
+            .enumerate()
+            .map(|(layer, json)| layer_from_json(layer, json))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Self::from_records(records)
+    }
+}
+
+fn weights_to_json(weights: &DMatrix<f32>) -> Value {
+    Value::Array(weights.row_iter().map(|row| Value::numbers(row.iter().copied())).collect())
+}
+
+fn layer_to_json(record: &LayerRecord) -> Value {
+    let mut json = Value::object([
+        ("input_size", Value::Number(record.weights.ncols() as f64)),
+        ("output_size", Value::Number(record.weights.nrows() as f64)),
+        ("activation", Value::String(record.activation.clone())),
+        ("weights", weights_to_json(&record.weights)),
+    ]);
+
+    if let Some(biases) = &record.biases {
+        json.insert("biases", Value::numbers(biases.iter().copied()));
+    }
+
+    if !record.maxout_pieces.is_empty() {
+        let pieces = record.maxout_pieces
+            .iter()
+            .map(|(weights, biases)| Value::object([
+                ("weights", weights_to_json(weights)),
+                ("biases", Value::numbers(biases.iter().copied())),
+            ]))
+            .collect();
+
+        json.insert("pieces", Value::Array(pieces));
+    }
+
+    if let Some(source) = record.shared_with {
+        json.insert("shared_with", Value::Number(source as f64));
+    }
+
+    if let Some(batch_norm) = &record.batch_norm {
+        json.insert("batch_norm", Value::object([
+            ("gamma", Value::numbers(batch_norm.gamma.iter().copied())),
+            ("beta", Value::numbers(batch_norm.beta.iter().copied())),
+            ("running_mean", Value::numbers(batch_norm.running_mean.iter().copied())),
+            ("running_variance", Value::numbers(batch_norm.running_variance.iter().copied())),
+            ("momentum", Value::Number(batch_norm.momentum as f64)),
+            ("epsilon", Value::Number(batch_norm.epsilon as f64)),
+        ]));
+    }
+
+    json
+}
+
+fn layer_from_json(layer: usize, json: &Value) -> Result<LayerRecord, NetworkLoadError> {
+    let path = format!("$.layers[{layer}]");
+    let input_size = json.field(&path, "input_size")?.as_usize(&format!("{path}.input_size"))?;
+    let output_size = json.field(&path, "output_size")?.as_usize(&format!("{path}.output_size"))?;
+    let activation = json.field(&path, "activation")?.as_str(&format!("{path}.activation"))?.to_string();
+
+    let weights = weights_from_json(layer, json, &path, input_size, output_size)?;
+
+    let mut maxout_pieces = Vec::new();
+    if let Some(pieces) = json.optional_field(&path, "pieces")? {
+        for (piece, value) in pieces.as_array(&format!("{path}.pieces"))?.iter().enumerate() {
+            let path = format!("{path}.pieces[{piece}]");
+            let weights = weights_from_json(layer, value, &path, input_size, output_size)?;
+            let biases = value.field(&path, "biases")?.as_f32_vec(&format!("{path}.biases"))?;
+            maxout_pieces.push((weights, DVector::from_vec(biases)));
+        }
+    }
+
+    let batch_norm = match json.optional_field(&path, "batch_norm")? {
+        Some(batch_norm) => {
+            let path = format!("{path}.batch_norm");
+            let vector = |field: &'static str| -> Result<DVector<f32>, JsonError> {
+                Ok(DVector::from_vec(batch_norm.field(&path, field)?.as_f32_vec(&format!("{path}.{field}"))?))
+            };
+
+            Some(BatchNormRecord {
+                gamma: vector("gamma")?,
+                beta: vector("beta")?,
+                running_mean: vector("running_mean")?,
+                running_variance: vector("running_variance")?,
+                momentum: batch_norm.field(&path, "momentum")?.as_f32(&format!("{path}.momentum"))?,
+                epsilon: batch_norm.field(&path, "epsilon")?.as_f32(&format!("{path}.epsilon"))?,
+            })
+        }
+        None => None,
+    };
+
+    Ok(LayerRecord {
+        weights,
+        // Layers without biases leave the field out.
+        biases: match json.optional_field(&path, "biases")? {
+            Some(biases) => Some(DVector::from_vec(biases.as_f32_vec(&format!("{path}.biases"))?)),
+            None => None,
+        },
+        activation,
+        batch_norm,
+        shared_with: match json.optional_field(&path, "shared_with")? {
+            Some(source) => Some(source.as_usize(&format!("{path}.shared_with"))?),
+            None => None,
+        },
+        maxout_pieces,
+    })
+}
+
+/// The `weights` rows of a layer or a maxout piece at `path`.
+fn weights_from_json(layer: usize, json: &Value, path: &str, input_size: usize, output_size: usize) -> Result<DMatrix<f32>, NetworkLoadError> {
+    let rows = json.field(path, "weights")?.as_array(&format!("{path}.weights"))?;
+    if rows.len() != output_size {
+        return Err(NetworkLoadError::WeightShapeMismatch { layer, input_size, output_size });
+    }
+
+    // The sizes come from the document, so the weights grow with the rows that check out instead of being
+    // allocated upfront.
+    let mut weights = Vec::new();
+    for (row_index, row) in rows.iter().enumerate() {
+        let row = row.as_f32_vec(&format!("{path}.weights[{row_index}]"))?;
+
+        if row.len() != input_size {
+            return Err(NetworkLoadError::WeightShapeMismatch { layer, input_size, output_size });
+        }
+
+        weights.extend(row);
+    }
+
+    Ok(DMatrix::from_row_slice(output_size, input_size, &weights))
+}
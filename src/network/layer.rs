@@ -1,5 +1,6 @@
 use nalgebra::{
     DMatrix,
+    DMatrixView,
     DVector,
     DVectorView,
 };
@@ -11,17 +12,62 @@ use rand::{
 
 use thiserror::Error;
 
-use crate::activations::ActivationFn;
+use crate::{activations::ActivationFn, prelude::*, scalar::Scalar};
 
-pub struct Layer {
-    weights: DMatrix<f32>,
-    weight_gradient: DMatrix<f32>,
-    biases: DVector<f32>,
-    bias_gradient: DVector<f32>,
-    activation_fn: Box<dyn ActivationFn>,
+use super::{constraint::WeightConstraint, conv::Convolution, initializer::Initializer};
 
-    previous_inputs: DVector<f32>,
-    previous_weighted_sums: DVector<f32>,
+use super::{
+    batch_norm::{BatchNorm, BatchNormCache},
+    NonFiniteKind,
+    serialization::LayerRecord,
+};
+
+/// Cloning copies everything, including accumulated gradients and the state recorded by the last `forward`.
+#[derive(Clone)]
+pub struct Layer<T: Scalar = f32> {
+    weights: DMatrix<T>,
+    weight_gradient: DMatrix<T>,
+    biases: DVector<T>,
+    bias_gradient: DVector<T>,
+    activation_fn: Box<dyn ActivationFn<T>>,
+    batch_norm: Option<BatchNorm<T>>,
+    trainable: bool,
+    /// Without biases, `biases` stays all zeros and is neither trained nor saved.
+    use_bias: bool,
+    weight_constraint: Option<WeightConstraint>,
+
+    previous_inputs: DVector<T>,
+    previous_weighted_sums: DVector<T>,
+    /// The cache and outputs of the last training batch run through `NetworkLayer::forward_training_batch`.
+    previous_batch: Option<(LayerBatchCache<T>, DMatrix<T>)>,
+}
+
+impl<T: Scalar> core::fmt::Debug for Layer<T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Layer")
+            .field("input_size", &self.input_size())
+            .field("output_size", &self.output_size())
+            .field("activation_fn", &self.activation_fn.name())
+            .field("batch_norm", &self.batch_norm.is_some())
+            .field("trainable", &self.trainable)
+            .field("use_bias", &self.use_bias)
+            .field("weight_constraint", &self.weight_constraint)
+            .finish()
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct LayerParameters<T: Scalar = f32> {
+    weights: DMatrix<T>,
+    biases: DVector<T>,
+    batch_norm: Option<BatchNorm<T>>,
+}
+
+/// What a training batch pass through a layer needs to remember for the backward pass, besides its inputs.
+#[derive(Clone)]
+pub(crate) struct LayerBatchCache<T: Scalar = f32> {
+    activation_inputs: DMatrix<T>,
+    batch_norm: Option<BatchNormCache<T>>,
 }
 
 #[derive(Debug, Error)]
@@ -37,6 +83,73 @@ pub enum LayerError {
 
     #[error("output size has to be more than 0")]
     ZeroOutputSize,
+
+    #[error("{0:?} isn't a valid initializer")]
+    InvalidInitializer(Initializer),
+
+    #[error("{0:?} isn't a valid weight constraint")]
+    InvalidWeightConstraint(WeightConstraint),
+
+    #[error("the given parameters don't match this layer's batch normalization setting")]
+    BatchNormMismatch,
+
+    #[error("this layer has no biases")]
+    NoBiases,
+
+    #[error("this layer takes {layer_input_size} inputs, but a sparse input sets input {index}")]
+    SparseIndexOutOfRange {
+        index: usize,
+        layer_input_size: usize,
+    },
+
+    #[error("this layer has {layer_output_size} outputs, but {given_size} biases were given")]
+    BiasSizeMismatch {
+        layer_output_size: usize,
+        given_size: usize,
+    },
+
+    #[error("the temperature has to be positive, but it's {0}")]
+    InvalidTemperature(f64),
+
+    #[error("input {position} is {value}, which isn't an index below {num_embeddings}")]
+    InvalidIndex {
+        position: usize,
+        value: f64,
+        num_embeddings: usize,
+    },
+
+    #[error("the convolution {convolution:?} doesn't fit a {height}x{width} input, it needs a non-zero stride and a kernel no larger than the padded input")]
+    InvalidConvolution {
+        height: usize,
+        width: usize,
+        convolution: Convolution,
+    },
+
+    #[error("this layer has {layer_input_size} inputs and {layer_output_size} outputs, but the given parameters are for {given_input_size} inputs and {given_output_size} outputs")]
+    ParameterShapeMismatch {
+        layer_input_size: usize,
+        layer_output_size: usize,
+        given_input_size: usize,
+        given_output_size: usize,
+    },
+
+    #[error("a maxout layer needs at least one piece")]
+    ZeroPieces,
+
+    #[error("the layer uses the activation function {found:?}, but {expected:?} was expected")]
+    ActivationMismatch {
+        expected: &'static str,
+        found: &'static str,
+    },
+
+    #[error("this layer has a hidden state of size {hidden_size}, but a state of size {given_size} was given")]
+    StateSizeMismatch {
+        hidden_size: usize,
+        given_size: usize,
+    },
+
+    #[error("a gradient for {0} samples was given, but the layer has no training forward pass of that many to backpropagate")]
+    NoTrainingBatch(usize),
 }
 
 fn check_sizes(input_size: usize, output_size: usize) -> Result<(), LayerError> {
@@ -51,16 +164,58 @@ fn check_sizes(input_size: usize, output_size: usize) -> Result<(), LayerError>
     Ok(())
 }
 
-fn random_vec<T>(size: usize, distribution: &impl Distribution<T>) -> Vec<T> {
-    let rng = rand::rng();
+fn random_vec<U>(size: usize, distribution: &impl Distribution<U>, rng: &mut impl Rng) -> Vec<U> {
     rng.sample_iter(distribution).take(size).collect()
 }
 
-impl Layer {
+/// Adds to the gradients unless they're `None`, as for a frozen layer, whose input gradient is still needed.
+/// A layer without biases passes no bias gradient.
+#[allow(clippy::too_many_arguments)]
+fn accumulate_gradient<T: Scalar>(
+    weights: &DMatrix<T>,
+    activation_fn: &dyn ActivationFn<T>,
+    batch_norm: Option<&mut BatchNorm<T>>,
+    inputs: DVectorView<T>,
+    weighted_sums: DVectorView<T>,
+    outputs: DVectorView<T>,
+    output_partial_gradient: DVectorView<T>,
+    gradients: Option<(&mut DMatrix<T>, Option<&mut DVector<T>>)>,
+) -> DVector<T> {
+    let mut bias_partial_derivatives = weighted_sum_gradient(activation_fn, weighted_sums, outputs, output_partial_gradient);
+
+    if let Some(batch_norm) = batch_norm {
+        bias_partial_derivatives = batch_norm.backward(&bias_partial_derivatives);
+    }
+
+    if let Some((weight_gradient, bias_gradient)) = gradients {
+        if let Some(bias_gradient) = bias_gradient {
+            *bias_gradient += &bias_partial_derivatives;
+        }
+
+        weight_gradient.ger(T::one(), &bias_partial_derivatives, &inputs, T::one());
+    }
+
+    weights.tr_mul(&bias_partial_derivatives)
+}
+
+/// The gradient with respect to the weighted sums the activation function was applied to.
+pub(crate) fn weighted_sum_gradient<T: Scalar>(
+    activation_fn: &dyn ActivationFn<T>,
+    weighted_sums: DVectorView<T>,
+    outputs: DVectorView<T>,
+    output_partial_gradient: DVectorView<T>,
+) -> DVector<T> {
+    let mut gradient = DVector::zeros(weighted_sums.len());
+    activation_fn.derivative_slice(weighted_sums.as_slice(), outputs.as_slice(), gradient.as_mut_slice());
+    gradient.component_mul_assign(&output_partial_gradient);
+    gradient
+}
+
+impl<T: Scalar> Layer<T> {
     pub fn zeros(
         input_size: usize,
         output_size: usize,
-        activation_fn: Box<dyn ActivationFn>,
+        activation_fn: Box<dyn ActivationFn<T>>,
     ) -> Result<Self, LayerError> {
         check_sizes(input_size, output_size)?;
 
@@ -70,17 +225,35 @@ impl Layer {
             biases: DVector::zeros(output_size),
             bias_gradient: DVector::zeros(output_size),
             activation_fn,
+            batch_norm: None,
+            trainable: true,
+            use_bias: true,
+            weight_constraint: None,
 
             previous_inputs: DVector::zeros(input_size),
             previous_weighted_sums: DVector::zeros(output_size),
+            previous_batch: None,
         })
     }
 
+    /// Draws from the thread-local RNG, see `random_with_rng` for reproducible layers.
+    #[cfg(feature = "std")]
     pub fn random(
         input_size: usize,
         output_size: usize,
-        activation_fn: Box<dyn ActivationFn>,
-        distribution: &impl Distribution<f32>,
+        activation_fn: Box<dyn ActivationFn<T>>,
+        distribution: &impl Distribution<T>,
+    ) -> Result<Self, LayerError> {
+        Self::random_with_rng(input_size, output_size, activation_fn, distribution, &mut rand::rng())
+    }
+
+    /// Like `random`, but draws every weight and bias from the given RNG, so a seeded RNG gives reproducible layers.
+    pub fn random_with_rng(
+        input_size: usize,
+        output_size: usize,
+        activation_fn: Box<dyn ActivationFn<T>>,
+        distribution: &impl Distribution<T>,
+        rng: &mut impl Rng,
     ) -> Result<Self, LayerError> {
         check_sizes(input_size, output_size)?;
 
@@ -88,57 +261,493 @@ impl Layer {
             weights: DMatrix::from_vec(
                 output_size,
                 input_size,
-                random_vec(output_size * input_size, distribution),
+                random_vec(output_size * input_size, distribution, rng),
             ),
 
             weight_gradient: DMatrix::zeros(output_size, input_size),
 
             biases: DVector::from_vec(
-                random_vec(output_size, distribution)
+                random_vec(output_size, distribution, rng)
             ),
 
             bias_gradient: DVector::zeros(output_size),
 
             activation_fn,
+            batch_norm: None,
+            trainable: true,
+            use_bias: true,
+            weight_constraint: None,
 
             previous_inputs: DVector::zeros(input_size),
             previous_weighted_sums: DVector::zeros(output_size),
+            previous_batch: None,
         })
     }
 
-    pub fn forward(&mut self, inputs: DVector<f32>) -> Result<DVector<f32>, LayerError> {
+    /// Builds a layer from parameters whose shapes were already checked, without biases if they're `None`.
+    pub(crate) fn from_parts(
+        weights: DMatrix<T>,
+        biases: Option<DVector<T>>,
+        activation_fn: Box<dyn ActivationFn<T>>,
+        batch_norm: Option<BatchNorm<T>>,
+    ) -> Self {
+        let (output_size, input_size) = weights.shape();
+        let use_bias = biases.is_some();
+
+        Self {
+            weights,
+            weight_gradient: DMatrix::zeros(output_size, input_size),
+            biases: biases.unwrap_or_else(|| DVector::zeros(output_size)),
+            bias_gradient: DVector::zeros(output_size),
+            activation_fn,
+            batch_norm,
+            trainable: true,
+            use_bias,
+            weight_constraint: None,
+
+            previous_inputs: DVector::zeros(input_size),
+            previous_weighted_sums: DVector::zeros(output_size),
+            previous_batch: None,
+        }
+    }
+
+    /// Normalizes the weighted sums with batch normalization before the activation function.
+    pub fn with_batch_norm(mut self) -> Self {
+        self.batch_norm = Some(BatchNorm::new(self.output_size()));
+        self
+    }
+
+    #[inline]
+    pub fn batch_norm(&self) -> Option<&BatchNorm<T>> {
+        self.batch_norm.as_ref()
+    }
+
+    #[inline]
+    pub fn batch_norm_mut(&mut self) -> Option<&mut BatchNorm<T>> {
+        self.batch_norm.as_mut()
+    }
+
+    /// Drops the biases, so the weighted sums are the weights times the inputs alone, e.g. in front of
+    /// batch normalization, whose shift makes biases redundant.
+    pub fn without_bias(mut self) -> Self {
+        self.use_bias = false;
+        self.biases.fill(T::zero());
+        self.bias_gradient.fill(T::zero());
+        self
+    }
+
+    #[inline]
+    pub fn has_bias(&self) -> bool { self.use_bias }
+
+    /// Freezes the layer with `false`: backpropagation still passes the gradient on to the layers in
+    /// front, but skips accumulating this layer's own, and `apply_gradient` leaves its weights, biases and
+    /// batch normalization parameters as they are. Batch normalization's running statistics still update
+    /// in training passes.
+    pub fn set_trainable(&mut self, trainable: bool) {
+        self.trainable = trainable;
+    }
+
+    #[inline]
+    pub fn is_trainable(&self) -> bool { self.trainable }
+
+    /// Replaces the activation function and keeps the weights, e.g. to swap an approximation in for inference.
+    /// The last training batch is kept too, so backpropagate it before swapping.
+    pub fn set_activation_fn(&mut self, activation_fn: Box<dyn ActivationFn<T>>) {
+        self.activation_fn = activation_fn;
+    }
+
+    /// Enforces the constraint on the weights at the end of every `apply_gradient` from now on, `None` removes it.
+    /// Fails with `InvalidWeightConstraint` if it isn't valid.
+    pub fn set_weight_constraint(&mut self, constraint: Option<WeightConstraint>) -> Result<(), LayerError> {
+        if let Some(constraint) = constraint && !constraint.is_valid() {
+            return Err(LayerError::InvalidWeightConstraint(constraint));
+        }
+
+        self.weight_constraint = constraint;
+        Ok(())
+    }
+
+    #[inline]
+    pub fn weight_constraint(&self) -> Option<WeightConstraint> { self.weight_constraint }
+
+    pub fn forward(&mut self, inputs: DVector<T>) -> Result<DVector<T>, LayerError> {
         self.check_input_size(inputs.len())?;
-        self.previous_weighted_sums = &self.weights * &inputs + &self.biases;
         self.previous_inputs = inputs;
-        Ok(self.previous_weighted_sums.map(|x| self.activation_fn.apply(x)))
+        Ok(self.forward_previous_inputs())
+    }
+
+    /// `forward` for borrowed inputs, which are copied into the buffer the layer keeps for backpropagation.
+    pub fn forward_view(&mut self, inputs: DVectorView<T>) -> Result<DVector<T>, LayerError> {
+        self.check_input_size(inputs.len())?;
+        self.previous_inputs.copy_from(&inputs);
+        Ok(self.forward_previous_inputs())
+    }
+
+    fn forward_previous_inputs(&mut self) -> DVector<T> {
+        let weighted_sums = &self.weights * &self.previous_inputs + &self.biases;
+        self.previous_weighted_sums = match &mut self.batch_norm {
+            Some(batch_norm) => batch_norm.forward(&weighted_sums),
+            None => weighted_sums,
+        };
+        let mut activations = self.previous_weighted_sums.clone();
+        self.activation_fn.apply_slice(activations.as_mut_slice());
+        activations
+    }
+
+    /// Forward pass that leaves the layer untouched.
+    pub fn predict(&self, inputs: DVectorView<T>) -> Result<DVector<T>, LayerError> {
+        self.check_input_size(inputs.len())?;
+        let mut weighted_sums = &self.weights * inputs + &self.biases;
+
+        if let Some(batch_norm) = &self.batch_norm {
+            weighted_sums = batch_norm.normalize(&weighted_sums);
+        }
+
+        self.activation_fn.apply_slice(weighted_sums.as_mut_slice());
+        Ok(weighted_sums)
+    }
+
+    /// `predict` into preallocated buffers of this layer's output size, without allocating.
+    pub(crate) fn predict_into(&self, inputs: DVectorView<T>, weighted_sums: &mut DVector<T>, activations: &mut DVector<T>) {
+        // The biases are added after the product, in the order of `predict` and the training passes.
+        weighted_sums.gemv(T::one(), &self.weights, &inputs, T::zero());
+        *weighted_sums += &self.biases;
+
+        if let Some(batch_norm) = &self.batch_norm {
+            batch_norm.normalize_mut(weighted_sums);
+        }
+
+        activations.copy_from(weighted_sums);
+        self.activation_fn.apply_slice(activations.as_mut_slice());
+    }
+
+    /// Forward pass over a batch with one sample per column. The training caches are left untouched.
+    pub fn forward_batch(&self, inputs: &DMatrix<T>) -> Result<DMatrix<T>, LayerError> {
+        self.check_input_size(inputs.nrows())?;
+        let mut weighted_sums = &self.weights * inputs;
+
+        for mut column in weighted_sums.column_iter_mut() {
+            column += &self.biases;
+        }
+
+        if let Some(batch_norm) = &self.batch_norm {
+            batch_norm.normalize_batch(&mut weighted_sums);
+        }
+
+        self.activation_fn.apply_slice(weighted_sums.as_mut_slice());
+        Ok(weighted_sums)
+    }
+
+    /// Training forward pass over a batch with one sample per column, using batch statistics for batch normalization.
+    pub(crate) fn forward_training_batch(&mut self, inputs: DMatrixView<T>) -> Result<(DMatrix<T>, LayerBatchCache<T>), LayerError> {
+        self.check_input_size(inputs.nrows())?;
+        let mut activation_inputs = &self.weights * inputs;
+
+        for mut column in activation_inputs.column_iter_mut() {
+            column += &self.biases;
+        }
+
+        let batch_norm = self.batch_norm
+            .as_mut()
+            .map(|batch_norm| batch_norm.forward_training(&mut activation_inputs));
+
+        let mut outputs = activation_inputs.clone();
+        self.activation_fn.apply_slice(outputs.as_mut_slice());
+
+        Ok((outputs, LayerBatchCache {
+            activation_inputs,
+            batch_norm,
+        }))
+    }
+
+    /// Backward pass for `forward_training_batch` over the same inputs, accumulating the gradient of the whole batch.
+    pub(crate) fn backpropagation_step_batch(
+        &mut self,
+        cache: &LayerBatchCache<T>,
+        inputs: DMatrixView<T>,
+        outputs: &DMatrix<T>,
+        output_partial_gradient: &DMatrix<T>,
+    ) -> DMatrix<T> {
+        let mut bias_partial_derivatives = DMatrix::zeros(outputs.nrows(), outputs.ncols());
+        self.activation_fn.derivative_slice(
+            cache.activation_inputs.as_slice(),
+            outputs.as_slice(),
+            bias_partial_derivatives.as_mut_slice(),
+        );
+        bias_partial_derivatives.component_mul_assign(output_partial_gradient);
+
+        if let (Some(batch_norm), Some(batch_norm_cache)) = (&mut self.batch_norm, &cache.batch_norm) {
+            bias_partial_derivatives = batch_norm.backward_training(batch_norm_cache, &bias_partial_derivatives);
+        }
+
+        if self.trainable {
+            if self.use_bias {
+                self.bias_gradient += bias_partial_derivatives.column_sum();
+            }
+
+            // One rank-one update per sample instead of a product with the transposed inputs, which would
+            // allocate that transpose on every step.
+            for (deltas, inputs) in bias_partial_derivatives.column_iter().zip(inputs.column_iter()) {
+                self.weight_gradient.ger(T::one(), &deltas, &inputs, T::one());
+            }
+        }
+
+        self.weights.tr_mul(&bias_partial_derivatives)
+    }
+
+    pub(crate) fn set_previous_batch(&mut self, cache: LayerBatchCache<T>, outputs: DMatrix<T>) {
+        self.previous_batch = Some((cache, outputs));
+    }
+
+    pub(crate) fn take_previous_batch(&mut self) -> Option<(LayerBatchCache<T>, DMatrix<T>)> {
+        self.previous_batch.take()
+    }
+
+    /// Accumulates the gradient for the last `forward` of this layer, whose outputs are `previous_outputs`,
+    /// and returns the gradient with respect to its inputs.
+    pub fn backpropagation_step(&mut self, previous_outputs: DVectorView<T>, output_partial_gradient: DVectorView<T>) -> DVector<T> {
+        accumulate_gradient(
+            &self.weights,
+            self.activation_fn.as_ref(),
+            self.batch_norm.as_mut(),
+            self.previous_inputs.as_view(),
+            self.previous_weighted_sums.as_view(),
+            previous_outputs,
+            output_partial_gradient,
+            self.trainable.then_some((&mut self.weight_gradient, self.use_bias.then_some(&mut self.bias_gradient))),
+        )
+    }
+
+    /// `backpropagation_step` for a pass recorded in a `NetworkCache` rather than in the layer, given this
+    /// layer's inputs, the weighted sums its activation function was applied to, and its outputs.
+    /// Batch normalization is skipped, networks using it backpropagate whole batches instead.
+    pub fn backpropagation_step_cached(
+        &mut self,
+        inputs: DVectorView<T>,
+        weighted_sums: DVectorView<T>,
+        outputs: DVectorView<T>,
+        output_partial_gradient: DVectorView<T>,
+    ) -> DVector<T> {
+        accumulate_gradient(
+            &self.weights,
+            self.activation_fn.as_ref(),
+            None,
+            inputs,
+            weighted_sums,
+            outputs,
+            output_partial_gradient,
+            self.trainable.then_some((&mut self.weight_gradient, self.use_bias.then_some(&mut self.bias_gradient))),
+        )
+    }
+
+    /// Forward pass that leaves the layer untouched, returning the weighted sums and the activations.
+    pub(crate) fn feed(&self, inputs: DVectorView<T>) -> Result<(DVector<T>, DVector<T>), LayerError> {
+        self.check_input_size(inputs.len())?;
+        Ok(self.activate(&self.weights * inputs + &self.biases))
     }
 
-    pub fn backpropagation_step(&mut self, previous_outputs: DVectorView<f32>, output_partial_gradient: DVectorView<f32>) -> DVector<f32> {
-        let mut input_partial_gradient = DVector::zeros(self.input_size());
+    /// Turns the rows of `output_jacobian`, derivatives of something with respect to this layer's outputs, into
+    /// derivatives with respect to its inputs, for a `feed` that gave `weighted_sums` and `outputs`. Batch
+    /// normalization uses its running statistics like in `predict`, and the gradients are left untouched.
+    pub(crate) fn input_jacobian(&self, weighted_sums: DVectorView<T>, outputs: DVectorView<T>, output_jacobian: &DMatrix<T>) -> DMatrix<T> {
+        let mut scale = DVector::zeros(weighted_sums.len());
+        self.activation_fn.derivative_slice(weighted_sums.as_slice(), outputs.as_slice(), scale.as_mut_slice());
 
-        for output_index in 0..self.output_size() {
-            let activation_function_derivative = self.activation_fn.derivative(
-                self.previous_weighted_sums[output_index],
-                previous_outputs[output_index],
-            );
+        if let Some(batch_norm) = &self.batch_norm {
+            scale.component_mul_assign(&batch_norm.inference_scale());
+        }
 
-            let bias_partial_derivative = activation_function_derivative * output_partial_gradient[output_index];
-            self.bias_gradient[output_index] += bias_partial_derivative;
+        let mut weighted_sum_jacobian = output_jacobian.clone();
+        for (mut column, &scale) in weighted_sum_jacobian.column_iter_mut().zip(scale.iter()) {
+            column *= scale;
+        }
 
-            for input_index in 0..self.input_size() {
-                self.weight_gradient[(output_index, input_index)] += self.previous_inputs[input_index] * bias_partial_derivative;
-                input_partial_gradient[input_index] += self.weights[(output_index, input_index)] * bias_partial_derivative;
+        weighted_sum_jacobian * &self.weights
+    }
+
+    /// `feed` for an input given by its non-zero entries as `(index, value)` pairs, adding up only the weight
+    /// columns they touch. Repeated indices add up.
+    pub(crate) fn feed_sparse(&self, inputs: &[(usize, T)]) -> Result<(DVector<T>, DVector<T>), LayerError> {
+        let mut weighted_sums = self.biases.clone();
+
+        for &(index, value) in inputs {
+            if index >= self.input_size() {
+                return Err(LayerError::SparseIndexOutOfRange {
+                    index,
+                    layer_input_size: self.input_size(),
+                });
             }
+
+            weighted_sums.axpy(value, &self.weights.column(index), T::one());
         }
 
-        input_partial_gradient
+        Ok(self.activate(weighted_sums))
     }
 
-    pub fn apply_gradient(&mut self, scale: f32) {
-        self.weights += &self.weight_gradient * scale;
-        self.biases += &self.bias_gradient * scale;
-        self.weight_gradient.fill(0.0);
-        self.bias_gradient.fill(0.0);
+    /// Batch normalization in evaluation mode and the activation function, returning both the weighted
+    /// sums it was applied to and the activations.
+    fn activate(&self, mut weighted_sums: DVector<T>) -> (DVector<T>, DVector<T>) {
+        if let Some(batch_norm) = &self.batch_norm {
+            weighted_sums = batch_norm.normalize(&weighted_sums);
+        }
+
+        let mut activations = weighted_sums.clone();
+        self.activation_fn.apply_slice(activations.as_mut_slice());
+        (weighted_sums, activations)
+    }
+
+    /// `backpropagation_step_cached` for a pass done with `feed_sparse`, only accumulating the weight gradient
+    /// columns of the inputs that were set. The input gradient isn't computed, the layer has to come first, and
+    /// batch normalization isn't accounted for, `backpropagate_sparse` rejects it.
+    pub(crate) fn backpropagation_step_sparse(
+        &mut self,
+        inputs: &[(usize, T)],
+        weighted_sums: DVectorView<T>,
+        outputs: DVectorView<T>,
+        output_partial_gradient: DVectorView<T>,
+    ) {
+        if !self.trainable {
+            return;
+        }
+
+        let bias_partial_derivatives = weighted_sum_gradient(self.activation_fn.as_ref(), weighted_sums, outputs, output_partial_gradient);
+
+        if self.use_bias {
+            self.bias_gradient += &bias_partial_derivatives;
+        }
+
+        for &(index, value) in inputs {
+            self.weight_gradient.column_mut(index).axpy(value, &bias_partial_derivatives, T::one());
+        }
+    }
+
+    /// `backpropagation_step_cached` into preallocated buffers of this layer's output size, without allocating.
+    /// `deltas` gets the gradient with respect to the weighted sums and `input_gradient`, if given, the one
+    /// with respect to the inputs. Batch normalization is skipped like there.
+    pub(crate) fn backpropagation_step_scratch(
+        &mut self,
+        inputs: DVectorView<T>,
+        weighted_sums: DVectorView<T>,
+        outputs: DVectorView<T>,
+        output_partial_gradient: &DVector<T>,
+        deltas: &mut DVector<T>,
+        input_gradient: Option<&mut DVector<T>>,
+    ) {
+        self.activation_fn.derivative_slice(weighted_sums.as_slice(), outputs.as_slice(), deltas.as_mut_slice());
+        deltas.component_mul_assign(output_partial_gradient);
+
+        if self.trainable {
+            if self.use_bias {
+                self.bias_gradient += &*deltas;
+            }
+
+            self.weight_gradient.ger(T::one(), deltas, &inputs, T::one());
+        }
+
+        if let Some(input_gradient) = input_gradient {
+            input_gradient.gemv_tr(T::one(), &self.weights, deltas, T::zero());
+        }
+    }
+
+    /// `backpropagation_step` for a pass done with `feed`, accumulating into the given buffers instead of the layer's own.
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn backpropagation_step_into(
+        &self,
+        inputs: DVectorView<T>,
+        weighted_sums: DVectorView<T>,
+        outputs: DVectorView<T>,
+        output_partial_gradient: DVectorView<T>,
+        weight_gradient: &mut DMatrix<T>,
+        bias_gradient: &mut DVector<T>,
+    ) -> DVector<T> {
+        accumulate_gradient(
+            &self.weights,
+            self.activation_fn.as_ref(),
+            None,
+            inputs,
+            weighted_sums,
+            outputs,
+            output_partial_gradient,
+            self.trainable.then_some((weight_gradient, self.use_bias.then_some(bias_gradient))),
+        )
+    }
+
+    pub(crate) fn zero_gradient_buffers(&self) -> (DMatrix<T>, DVector<T>) {
+        (
+            DMatrix::zeros(self.output_size(), self.input_size()),
+            DVector::zeros(self.output_size()),
+        )
+    }
+
+    pub(crate) fn add_to_gradient(&mut self, weight_gradient: &DMatrix<T>, bias_gradient: &DVector<T>) {
+        self.weight_gradient += weight_gradient;
+
+        if self.use_bias {
+            self.bias_gradient += bias_gradient;
+        }
+    }
+
+    /// Adds the accumulated gradient times `scale` to the parameters, enforces the weight constraint and zeroes
+    /// the gradient. A frozen layer only zeroes it.
+    pub fn apply_gradient(&mut self, scale: T) {
+        if !self.trainable {
+            self.zero_gradient();
+            return;
+        }
+
+        // In place, so a step doesn't allocate.
+        self.weights.zip_apply(&self.weight_gradient, |weight, gradient| *weight += gradient * scale);
+        self.biases.zip_apply(&self.bias_gradient, |bias, gradient| *bias += gradient * scale);
+        self.weight_gradient.fill(T::zero());
+        self.bias_gradient.fill(T::zero());
+
+        if let Some(batch_norm) = &mut self.batch_norm {
+            batch_norm.apply_gradient(scale);
+        }
+
+        if let Some(constraint) = &self.weight_constraint {
+            constraint.apply(&mut self.weights);
+        }
+    }
+
+    /// What `apply_gradient(scale)` would first turn non-finite: a gradient, or a weight or bias after the update.
+    pub(crate) fn non_finite_update(&self, scale: T) -> Option<NonFiniteKind> {
+        if !self.trainable {
+            return None;
+        }
+
+        if !self.weight_gradient.iter().all(|x| x.is_finite()) {
+            return Some(NonFiniteKind::WeightGradient);
+        }
+
+        if !self.bias_gradient.iter().all(|x| x.is_finite()) {
+            return Some(NonFiniteKind::BiasGradient);
+        }
+
+        if !self.weights.iter().zip(self.weight_gradient.iter()).all(|(&w, &g)| (w + g * scale).is_finite()) {
+            return Some(NonFiniteKind::Weight);
+        }
+
+        if !self.biases.iter().zip(self.bias_gradient.iter()).all(|(&b, &g)| (b + g * scale).is_finite()) {
+            return Some(NonFiniteKind::Bias);
+        }
+
+        None
+    }
+
+    pub fn is_finite(&self) -> bool {
+        self.weights.iter().chain(self.biases.iter()).all(|x| x.is_finite())
+    }
+
+    /// Discards the gradient accumulated since the last `apply_gradient`, for training loops that manage it
+    /// themselves.
+    pub fn zero_gradient(&mut self) {
+        self.weight_gradient.fill(T::zero());
+        self.bias_gradient.fill(T::zero());
+
+        if let Some(batch_norm) = &mut self.batch_norm {
+            batch_norm.zero_gradient();
+        }
     }
 
     #[inline]
@@ -148,30 +757,187 @@ impl Layer {
     pub fn output_size(&self) -> usize { self.weights.nrows() }
 
     #[inline]
-    pub fn get_weight(&self, input: usize, output: usize) -> Option<&f32> {
+    pub(crate) fn activation_fn(&self) -> &dyn ActivationFn<T> { self.activation_fn.as_ref() }
+
+    /// The weight matrix, one row per output and one column per input.
+    #[inline]
+    pub fn weights(&self) -> DMatrixView<'_, T> { self.weights.as_view() }
+
+    #[inline]
+    pub(crate) fn weights_mut(&mut self) -> &mut DMatrix<T> { &mut self.weights }
+
+    /// All zeros for a layer without biases.
+    #[inline]
+    pub fn biases(&self) -> DVectorView<'_, T> { self.biases.as_view() }
+
+    #[inline]
+    pub(crate) fn biases_mut(&mut self) -> &mut DVector<T> { &mut self.biases }
+
+    /// The weight gradient accumulated since the last `apply_gradient`, shaped like `weights`.
+    #[inline]
+    pub fn weight_gradient(&self) -> DMatrixView<'_, T> { self.weight_gradient.as_view() }
+
+    /// The bias gradient accumulated since the last `apply_gradient`, all zeros for a layer without biases.
+    #[inline]
+    pub fn bias_gradient(&self) -> DVectorView<'_, T> { self.bias_gradient.as_view() }
+
+    /// The weight gradient and, unless the layer has no biases, the bias gradient, for changing them in place.
+    pub(crate) fn gradients_mut(&mut self) -> (&mut DMatrix<T>, Option<&mut DVector<T>>) {
+        (&mut self.weight_gradient, self.use_bias.then_some(&mut self.bias_gradient))
+    }
+
+    /// Every weight with its gradient, then every bias with its gradient unless the layer has no biases, for
+    /// optimizers that update the parameters one by one.
+    pub(crate) fn parameters_with_gradients_mut(&mut self) -> impl Iterator<Item = (&mut T, T)> {
+        let biases = if self.use_bias { self.biases.len() } else { 0 };
+
+        self.weights
+            .iter_mut()
+            .zip(self.weight_gradient.iter().copied())
+            .chain(self.biases.iter_mut().zip(self.bias_gradient.iter().copied()).take(biases))
+    }
+
+    #[inline]
+    pub fn get_weight(&self, input: usize, output: usize) -> Option<&T> {
         self.weights.get((output, input))
     }
 
     #[inline]
-    pub fn get_weight_mut(&mut self, input: usize, output: usize) -> Option<&mut f32> {
+    pub fn get_weight_mut(&mut self, input: usize, output: usize) -> Option<&mut T> {
         self.weights.get_mut((output, input))
     }
 
+    /// `None` for a layer without biases.
+    #[inline]
+    pub fn get_bias(&self, output: usize) -> Option<&T> {
+        if self.use_bias { self.biases.get(output) } else { None }
+    }
+
+    /// `None` for a layer without biases, whose biases have to stay zero.
     #[inline]
-    pub fn get_bias(&self, output: usize) -> Option<&f32> {
-        self.biases.get(output)
+    pub fn get_bias_mut(&mut self, output: usize) -> Option<&mut T> {
+        if self.use_bias { self.biases.get_mut(output) } else { None }
     }
 
     #[inline]
-    pub fn get_bias_mut(&mut self, output: usize) -> Option<&mut f32> {
-        self.biases.get_mut(output)
+    pub fn get_weight_gradient(&self, input: usize, output: usize) -> Option<&T> {
+        self.weight_gradient.get((output, input))
     }
 
-    pub fn get_previous_input(&self) -> DVectorView<f32> {
+    #[inline]
+    pub fn get_bias_gradient(&self, output: usize) -> Option<&T> {
+        self.bias_gradient.get(output)
+    }
+
+    pub fn parameters(&self) -> LayerParameters<T> {
+        LayerParameters {
+            weights: self.weights.clone(),
+            biases: self.biases.clone(),
+            batch_norm: self.batch_norm.clone(),
+        }
+    }
+
+    /// A layer without biases ignores the given ones.
+    pub fn set_parameters(&mut self, parameters: &LayerParameters<T>) -> Result<(), LayerError> {
+        self.check_parameters(parameters)?;
+        self.weights.copy_from(&parameters.weights);
+
+        if self.use_bias {
+            self.biases.copy_from(&parameters.biases);
+        }
+
+        self.batch_norm.clone_from(&parameters.batch_norm);
+        Ok(())
+    }
+
+    /// Changes the layer's shape, keeping the weights and biases that still fit and filling new ones with `fill`.
+    /// Gradients and the state of the last `forward` are reset, and batch normalization restarts for new outputs.
+    pub(crate) fn resize(&mut self, input_size: usize, output_size: usize, mut fill: impl FnMut() -> T) {
+        let (old_output_size, old_input_size) = self.weights.shape();
+
+        self.weights = DMatrix::from_fn(output_size, input_size, |output, input| {
+            if output < old_output_size && input < old_input_size {
+                self.weights[(output, input)]
+            } else {
+                fill()
+            }
+        });
+
+        self.biases = DVector::from_fn(output_size, |output, _| {
+            if output < old_output_size {
+                self.biases[output]
+            } else if self.use_bias {
+                fill()
+            } else {
+                T::zero()
+            }
+        });
+
+        if let Some(batch_norm) = &mut self.batch_norm {
+            batch_norm.resize(output_size);
+        }
+
+        self.weight_gradient = DMatrix::zeros(output_size, input_size);
+        self.bias_gradient = DVector::zeros(output_size);
+        self.previous_inputs = DVector::zeros(input_size);
+        self.previous_weighted_sums = DVector::zeros(output_size);
+        self.previous_batch = None;
+    }
+
+    /// Replaces the weight matrix, which has one row per output and one column per input.
+    pub fn set_weights(&mut self, weights: DMatrix<T>) -> Result<(), LayerError> {
+        if weights.shape() != self.weights.shape() {
+            return Err(LayerError::ParameterShapeMismatch {
+                layer_input_size: self.input_size(),
+                layer_output_size: self.output_size(),
+                given_input_size: weights.ncols(),
+                given_output_size: weights.nrows(),
+            });
+        }
+
+        self.weights = weights;
+        Ok(())
+    }
+
+    /// Fails with `NoBiases` for a layer without biases.
+    pub fn set_biases(&mut self, biases: DVector<T>) -> Result<(), LayerError> {
+        if !self.use_bias {
+            return Err(LayerError::NoBiases);
+        }
+
+        if biases.len() != self.output_size() {
+            return Err(LayerError::BiasSizeMismatch {
+                layer_output_size: self.output_size(),
+                given_size: biases.len(),
+            });
+        }
+
+        self.biases = biases;
+        Ok(())
+    }
+
+    pub fn get_previous_input(&self) -> DVectorView<'_, T> {
         self.previous_inputs.as_view()
     }
 
-    fn check_input_size(&self, input_size: usize) -> Result<(), LayerError> {
+    pub(crate) fn check_parameters(&self, parameters: &LayerParameters<T>) -> Result<(), LayerError> {
+        if parameters.weights.shape() != self.weights.shape() {
+            return Err(LayerError::ParameterShapeMismatch {
+                layer_input_size: self.input_size(),
+                layer_output_size: self.output_size(),
+                given_input_size: parameters.weights.ncols(),
+                given_output_size: parameters.weights.nrows(),
+            });
+        }
+
+        if parameters.batch_norm.is_some() != self.batch_norm.is_some() {
+            return Err(LayerError::BatchNormMismatch);
+        }
+
+        Ok(())
+    }
+
+    pub(crate) fn check_input_size(&self, input_size: usize) -> Result<(), LayerError> {
         if self.input_size() != input_size {
             return Err(LayerError::InputSizeMismatch {
                 layer_input_size: self.input_size(),
@@ -181,4 +947,17 @@ impl Layer {
 
         Ok(())
     }
-}
\ No newline at end of file
+}
+
+impl Layer {
+    pub(crate) fn to_record(&self) -> LayerRecord {
+        LayerRecord {
+            weights: self.weights.clone(),
+            biases: self.use_bias.then(|| self.biases.clone()),
+            activation: self.activation_fn.name().to_string(),
+            batch_norm: self.batch_norm.as_ref().map(BatchNorm::to_record),
+            shared_with: None,
+            maxout_pieces: Vec::new(),
+        }
+    }
+}
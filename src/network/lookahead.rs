@@ -0,0 +1,126 @@
+use crate::{prelude::*, scalar::Scalar};
+
+use super::{optimizer::{Optimizer, OptimizerState, Sgd}, Network, NetworkError};
+
+/// Lookahead around another optimizer: it keeps a copy of "slow" parameters, lets the inner optimizer take
+/// `k` steps on the network's "fast" parameters, then moves the slow ones `alpha` of the way towards them,
+/// `slow += alpha * (fast - slow)`, and resets the network to the result. The slow parameters are taken
+/// from the network at the first step. With `k = 1` and `alpha = 1` the network is left exactly as the inner
+/// optimizer leaves it.
+#[derive(Clone, Debug)]
+pub struct Lookahead<O = Sgd, T: Scalar = f32> {
+    inner: O,
+    k: usize,
+    alpha: T,
+    /// Empty before the first step.
+    slow: Vec<T>,
+    steps: usize,
+}
+
+impl<O: Optimizer<T>, T: Scalar> Lookahead<O, T> {
+    /// Fails with `InvalidLookahead` unless `k` is at least 1 and `alpha` is in (0, 1].
+    pub fn new(inner: O, k: usize, alpha: T) -> Result<Self, NetworkError> {
+        if k == 0 || !(alpha > T::zero() && alpha <= T::one()) {
+            return Err(NetworkError::InvalidLookahead {
+                k,
+                alpha: alpha.to_f64(),
+            });
+        }
+
+        Ok(Self {
+            inner,
+            k,
+            alpha,
+            slow: Vec::new(),
+            steps: 0,
+        })
+    }
+
+    #[inline]
+    pub fn k(&self) -> usize {
+        self.k
+    }
+
+    #[inline]
+    pub fn alpha(&self) -> T {
+        self.alpha
+    }
+
+    #[inline]
+    pub fn inner(&self) -> &O {
+        &self.inner
+    }
+
+    /// The slow parameters in the order of `Network::get_params`, empty before the first step.
+    #[inline]
+    pub fn slow_params(&self) -> &[T] {
+        &self.slow
+    }
+
+    /// The inner optimizer's steps since the last synchronization.
+    #[inline]
+    pub fn steps_since_sync(&self) -> usize {
+        self.steps
+    }
+}
+
+impl<O: Optimizer<T>, T: Scalar> Optimizer<T> for Lookahead<O, T> {
+    /// The inner optimizer's step, synchronizing after every `k`th. Fails with `ParameterCountMismatch` if the
+    /// network isn't the one of the earlier steps, before anything changes.
+    fn step(&mut self, network: &mut Network<T>, rate: T, total_weight: T) -> Result<(), NetworkError> {
+        if self.slow.is_empty() {
+            self.slow = network.get_params();
+        } else if self.slow.len() != network.parameter_count() {
+            return Err(NetworkError::ParameterCountMismatch {
+                expected: self.slow.len(),
+                given: network.parameter_count(),
+            });
+        }
+
+        self.inner.step(network, rate, total_weight)?;
+
+        self.steps += 1;
+        if self.steps < self.k {
+            return Ok(());
+        }
+
+        for (slow, fast) in self.slow.iter_mut().zip(network.get_params()) {
+            *slow += self.alpha * (fast - *slow);
+        }
+
+        network.set_params(&self.slow)?;
+        self.steps = 0;
+        Ok(())
+    }
+
+    fn reset(&mut self) {
+        self.inner.reset();
+        self.slow.clear();
+        self.steps = 0;
+    }
+
+    /// The slow parameters and the steps since the last synchronization, with the inner optimizer's state
+    /// nested under `inner`. The kind is `lookahead(<inner kind>)`.
+    fn save_state(&self) -> OptimizerState<T> {
+        let inner = self.inner.save_state();
+        let mut state = OptimizerState::new(format!("lookahead({})", inner.kind));
+        state.buffers.push(("slow".to_string(), self.slow.clone()));
+        state.counters.push(("steps".to_string(), self.steps as u64));
+        state.nest("inner", inner);
+        state
+    }
+
+    fn load_state(&mut self, state: &OptimizerState<T>, network: &Network<T>) -> Result<(), NetworkError> {
+        let inner_kind = self.inner.save_state().kind;
+        state.check_kind(&format!("lookahead({inner_kind})"))?;
+
+        let slow = state.buffer("slow", network)?;
+        let steps = state.counter("steps")?;
+        self.inner.load_state(&state.nested("inner", &inner_kind), network)?;
+
+        // A count of `k` or more from a lookahead with a larger `k` synchronizes on the next step.
+        self.slow = slow.to_vec();
+        self.steps = usize::try_from(steps).unwrap_or(usize::MAX);
+        Ok(())
+    }
+}
@@ -0,0 +1,317 @@
+//! Maxout units: every output is the largest of `k` linear pieces of the input,
+//! `y[j] = max_p (W_p[j] · x + b_p[j])`, a learned convex piecewise linear activation that a scalar
+//! `ActivationFn` can't express. Only the winning piece of an output gets its gradient.
+
+use nalgebra::{DMatrix, DMatrixView, DVector, DVectorView};
+use rand::{distr::Distribution, Rng};
+
+use crate::{prelude::*, scalar::Scalar};
+
+use super::{gradients::LayerGradientNorm, layer::LayerError, serialization::LayerRecord, NetworkLayer, NonFiniteKind};
+
+/// The `kind` of maxout layers and the activation name of their `LayerRecord`s.
+pub const MAXOUT: &str = "maxout";
+
+/// A layer of maxout units for use in a `Network`, with one weight matrix and bias vector per piece.
+/// Cloning copies everything, including accumulated gradients and the last training batch.
+#[derive(Clone, Debug)]
+pub struct MaxoutLayer<T: Scalar = f32> {
+    weights: Vec<DMatrix<T>>,
+    weight_gradients: Vec<DMatrix<T>>,
+    biases: Vec<DVector<T>>,
+    bias_gradients: Vec<DVector<T>>,
+
+    /// The winning piece of every output of the last training batch, in the layout of the outputs.
+    previous_winners: DMatrix<usize>,
+}
+
+impl<T: Scalar> MaxoutLayer<T> {
+    /// `output_size` units of `pieces` pieces each, all of them zero.
+    pub fn zeros(input_size: usize, output_size: usize, pieces: usize) -> Result<Self, LayerError> {
+        if input_size == 0 {
+            return Err(LayerError::ZeroInputSize);
+        }
+
+        if output_size == 0 {
+            return Err(LayerError::ZeroOutputSize);
+        }
+
+        if pieces == 0 {
+            return Err(LayerError::ZeroPieces);
+        }
+
+        Ok(Self {
+            weights: vec![DMatrix::zeros(output_size, input_size); pieces],
+            weight_gradients: vec![DMatrix::zeros(output_size, input_size); pieces],
+            biases: vec![DVector::zeros(output_size); pieces],
+            bias_gradients: vec![DVector::zeros(output_size); pieces],
+
+            previous_winners: DMatrix::zeros(output_size, 0),
+        })
+    }
+
+    /// Draws from the thread-local RNG, see `random_with_rng` for reproducible layers.
+    #[cfg(feature = "std")]
+    pub fn random(
+        input_size: usize,
+        output_size: usize,
+        pieces: usize,
+        distribution: &impl Distribution<T>,
+    ) -> Result<Self, LayerError> {
+        Self::random_with_rng(input_size, output_size, pieces, distribution, &mut rand::rng())
+    }
+
+    /// Like `random`, but draws every weight from the given RNG. The biases start at zero, the pieces
+    /// differ by their weights.
+    pub fn random_with_rng(
+        input_size: usize,
+        output_size: usize,
+        pieces: usize,
+        distribution: &impl Distribution<T>,
+        rng: &mut impl Rng,
+    ) -> Result<Self, LayerError> {
+        let mut layer = Self::zeros(input_size, output_size, pieces)?;
+
+        for weights in layer.weights.iter_mut() {
+            weights.iter_mut().for_each(|weight| *weight = distribution.sample(rng));
+        }
+
+        Ok(layer)
+    }
+
+    #[inline]
+    pub fn pieces(&self) -> usize { self.weights.len() }
+
+    /// The weights of every piece, each with one row per output.
+    #[inline]
+    pub fn weights(&self) -> &[DMatrix<T>] { &self.weights }
+
+    #[inline]
+    pub fn biases(&self) -> &[DVector<T>] { &self.biases }
+
+    #[inline]
+    pub fn weight_gradients(&self) -> &[DMatrix<T>] { &self.weight_gradients }
+
+    #[inline]
+    pub fn bias_gradients(&self) -> &[DVector<T>] { &self.bias_gradients }
+
+    /// Replaces the weights and biases of one piece. Panics if `piece` is out of range.
+    pub fn set_piece(&mut self, piece: usize, weights: DMatrix<T>, biases: DVector<T>) -> Result<(), LayerError> {
+        super::recurrent::check_shape(&self.weights[piece], &weights)?;
+
+        if biases.len() != self.output_size() {
+            return Err(LayerError::BiasSizeMismatch {
+                layer_output_size: self.output_size(),
+                given_size: biases.len(),
+            });
+        }
+
+        self.weights[piece] = weights;
+        self.biases[piece] = biases;
+        Ok(())
+    }
+
+    /// The outputs of a batch with one sample per column and the piece every output came from. Ties go
+    /// to the first piece, and a NaN piece only wins if every piece is NaN.
+    fn maxout(&self, inputs: DMatrixView<T>) -> Result<(DMatrix<T>, DMatrix<usize>), LayerError> {
+        self.check_input_size(inputs.nrows())?;
+
+        let mut outputs = DMatrix::zeros(self.output_size(), inputs.ncols());
+        let mut winners = DMatrix::zeros(self.output_size(), inputs.ncols());
+
+        for (piece, (weights, biases)) in self.weights.iter().zip(&self.biases).enumerate() {
+            let mut weighted_sums = weights * inputs;
+            for mut column in weighted_sums.column_iter_mut() {
+                column += biases;
+            }
+
+            for ((output, winner), &weighted_sum) in outputs.iter_mut().zip(winners.iter_mut()).zip(weighted_sums.iter()) {
+                if piece == 0 || weighted_sum > *output || output.to_f64().is_nan() {
+                    *output = weighted_sum;
+                    *winner = piece;
+                }
+            }
+        }
+
+        Ok((outputs, winners))
+    }
+
+    fn check_input_size(&self, input_size: usize) -> Result<(), LayerError> {
+        if input_size != self.input_size() {
+            return Err(LayerError::InputSizeMismatch {
+                layer_input_size: self.input_size(),
+                given_input_size: input_size,
+            });
+        }
+
+        Ok(())
+    }
+}
+
+impl MaxoutLayer {
+    /// A layer from the row-major weights and the biases of its pieces, for the loaders of the exported
+    /// formats, which check the sizes.
+    #[cfg(any(feature = "npz", feature = "safetensors"))]
+    pub(crate) fn from_row_major_pieces(
+        input_size: usize,
+        output_size: usize,
+        pieces: Vec<(Vec<f32>, Vec<f32>)>,
+    ) -> Result<Self, LayerError> {
+        let mut maxout = Self::zeros(input_size, output_size, pieces.len())?;
+        for (piece, (weights, biases)) in pieces.into_iter().enumerate() {
+            maxout.set_piece(piece, DMatrix::from_row_slice(output_size, input_size, &weights), DVector::from_vec(biases))?;
+        }
+
+        Ok(maxout)
+    }
+
+    pub(crate) fn to_record(&self) -> LayerRecord {
+        LayerRecord {
+            weights: self.weights[0].clone(),
+            biases: Some(self.biases[0].clone()),
+            activation: MAXOUT.to_string(),
+            batch_norm: None,
+            shared_with: None,
+            maxout_pieces: self.weights.iter().cloned().zip(self.biases.iter().cloned()).skip(1).collect(),
+        }
+    }
+}
+
+impl<T: Scalar> NetworkLayer<T> for MaxoutLayer<T> {
+    #[inline]
+    fn input_size(&self) -> usize { self.weights[0].ncols() }
+
+    #[inline]
+    fn output_size(&self) -> usize { self.weights[0].nrows() }
+
+    fn forward(&mut self, inputs: DVectorView<T>) -> Result<DVector<T>, LayerError> {
+        self.predict(inputs)
+    }
+
+    fn predict(&self, inputs: DVectorView<T>) -> Result<DVector<T>, LayerError> {
+        let (outputs, _) = self.maxout(DMatrix::from_iterator(inputs.len(), 1, inputs.iter().copied()).as_view())?;
+        Ok(outputs.column(0).into_owned())
+    }
+
+    fn forward_batch(&self, inputs: &DMatrix<T>) -> Result<DMatrix<T>, LayerError> {
+        Ok(self.maxout(inputs.as_view())?.0)
+    }
+
+    fn forward_training_batch(&mut self, inputs: DMatrixView<T>) -> Result<DMatrix<T>, LayerError> {
+        let (outputs, winners) = self.maxout(inputs)?;
+        self.previous_winners = winners;
+        Ok(outputs)
+    }
+
+    /// Every output's gradient goes to the piece that won it, each piece backpropagates like a linear layer.
+    fn backpropagation_step_batch(&mut self, inputs: DMatrixView<T>, output_partial_gradient: &DMatrix<T>) -> Result<DMatrix<T>, LayerError> {
+        self.check_input_size(inputs.nrows())?;
+
+        if self.previous_winners.shape() != output_partial_gradient.shape() || inputs.ncols() != output_partial_gradient.ncols() {
+            return Err(LayerError::NoTrainingBatch(output_partial_gradient.ncols()));
+        }
+
+        let mut input_gradient = DMatrix::zeros(self.input_size(), output_partial_gradient.ncols());
+
+        for piece in 0..self.pieces() {
+            let mut piece_gradient = output_partial_gradient.clone();
+            piece_gradient
+                .iter_mut()
+                .zip(self.previous_winners.iter())
+                .filter(|(_, winner)| **winner != piece)
+                .for_each(|(gradient, _)| *gradient = T::zero());
+
+            for (deltas, inputs) in piece_gradient.column_iter().zip(inputs.column_iter()) {
+                self.weight_gradients[piece].ger(T::one(), &deltas, &inputs, T::one());
+            }
+            self.bias_gradients[piece] += piece_gradient.column_sum();
+            input_gradient.gemm_tr(T::one(), &self.weights[piece], &piece_gradient, T::one());
+        }
+
+        Ok(input_gradient)
+    }
+
+    fn apply_gradient(&mut self, scale: T) {
+        for (weights, gradient) in self.weights.iter_mut().zip(&self.weight_gradients) {
+            *weights += gradient * scale;
+        }
+
+        for (biases, gradient) in self.biases.iter_mut().zip(&self.bias_gradients) {
+            *biases += gradient * scale;
+        }
+
+        self.zero_gradient();
+    }
+
+    fn zero_gradient(&mut self) {
+        self.weight_gradients.iter_mut().for_each(|gradient| gradient.fill(T::zero()));
+        self.bias_gradients.iter_mut().for_each(|gradient| gradient.fill(T::zero()));
+    }
+
+    fn boxed_clone(&self) -> Box<dyn NetworkLayer<T>> {
+        Box::new(self.clone())
+    }
+
+    fn kind(&self) -> &'static str { MAXOUT }
+
+    fn parameter_count(&self) -> usize { (self.input_size() + 1) * self.output_size() * self.pieces() }
+
+    /// Piece by piece, the weight matrix in column-major order followed by the biases.
+    fn write_parameters(&self, parameters: &mut Vec<T>) {
+        for (weights, biases) in self.weights.iter().zip(&self.biases) {
+            parameters.extend(weights.iter());
+            parameters.extend(biases.iter());
+        }
+    }
+
+    fn read_parameters(&mut self, parameters: &[T]) {
+        let mut rest = parameters;
+
+        for (weights, biases) in self.weights.iter_mut().zip(self.biases.iter_mut()) {
+            let (piece_weights, tail) = rest.split_at(weights.len());
+            let (piece_biases, tail) = tail.split_at(biases.len());
+            weights.copy_from_slice(piece_weights);
+            biases.copy_from_slice(piece_biases);
+            rest = tail;
+        }
+    }
+
+    fn gradient_norm(&self) -> LayerGradientNorm<T> {
+        let squared = |sum: T, norm: T| sum + norm * norm;
+
+        LayerGradientNorm {
+            weights: self.weight_gradients.iter().map(|gradient| gradient.norm()).fold(T::zero(), squared).sqrt(),
+            biases: self.bias_gradients.iter().map(|gradient| gradient.norm()).fold(T::zero(), squared).sqrt(),
+        }
+    }
+
+    fn non_finite_update(&self, scale: T) -> Option<NonFiniteKind> {
+        let finite = |values: &[T]| values.iter().all(|x| x.is_finite());
+        let finite_after = |parameters: &[T], gradient: &[T]| {
+            parameters.iter().zip(gradient).all(|(&x, &g)| (x + g * scale).is_finite())
+        };
+
+        if !self.weight_gradients.iter().all(|gradient| finite(gradient.as_slice())) {
+            return Some(NonFiniteKind::WeightGradient);
+        }
+
+        if !self.bias_gradients.iter().all(|gradient| finite(gradient.as_slice())) {
+            return Some(NonFiniteKind::BiasGradient);
+        }
+
+        if !self.weights.iter().zip(&self.weight_gradients).all(|(weights, gradient)| finite_after(weights.as_slice(), gradient.as_slice())) {
+            return Some(NonFiniteKind::Weight);
+        }
+
+        if !self.biases.iter().zip(&self.bias_gradients).all(|(biases, gradient)| finite_after(biases.as_slice(), gradient.as_slice())) {
+            return Some(NonFiniteKind::Bias);
+        }
+
+        None
+    }
+
+    fn is_finite(&self) -> bool {
+        self.weights.iter().all(|weights| weights.iter().all(|x| x.is_finite()))
+            && self.biases.iter().all(|biases| biases.iter().all(|x| x.is_finite()))
+    }
+}
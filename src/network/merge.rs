@@ -0,0 +1,94 @@
+use rand::Rng;
+
+use crate::{prelude::*, scalar::Scalar};
+
+use super::{Network, NetworkError};
+
+impl<T: Scalar> Network<T> {
+    /// The elementwise mean of the weights and biases of networks with the same architecture, e.g. copies
+    /// trained on different shards of a dataset. Batch normalization parameters are taken from the first network.
+    pub fn average(networks: &[&Network<T>]) -> Result<Network<T>, NetworkError> {
+        Self::weighted_average(networks, &vec![T::one(); networks.len()])
+    }
+
+    /// Like `average`, with every network's parameters weighted by the matching entry of `weights`,
+    /// e.g. the number of samples it was trained on. The weights are normalized by their sum.
+    pub fn weighted_average(networks: &[&Network<T>], weights: &[T]) -> Result<Network<T>, NetworkError> {
+        let (first, rest) = networks.split_first().ok_or(NetworkError::NoNetworks)?;
+
+        if weights.len() != networks.len() {
+            return Err(NetworkError::MergeWeightCountMismatch {
+                networks: networks.len(),
+                weights: weights.len(),
+            });
+        }
+
+        let total_weight = weights.iter().fold(T::zero(), |total, &weight| total + weight);
+        if weights.iter().any(|weight| !weight.is_finite() || *weight < T::zero()) || total_weight <= T::zero() {
+            return Err(NetworkError::InvalidMergeWeights);
+        }
+
+        for network in rest {
+            first.check_architecture(network)?;
+        }
+
+        let mut average = (*first).clone();
+        average.zero_gradients();
+
+        for (i, layer) in average.layers.iter_mut().enumerate() {
+            let mut parameters = vec![T::zero(); layer.parameter_count()];
+
+            for (network, &weight) in networks.iter().zip(weights.iter()) {
+                let scale = weight / total_weight;
+
+                for (x, y) in parameters.iter_mut().zip(network.layers[i].flat_parameters()) {
+                    *x += y * scale;
+                }
+            }
+
+            layer.read_parameters(&parameters);
+        }
+
+        Ok(average)
+    }
+
+    /// Uniform crossover: every weight and bias, or parameter of a layer other than a dense one, of the child is taken from one of the two parents with
+    /// equal probability. Batch normalization parameters are taken from `parent_a`.
+    pub fn crossover(parent_a: &Network<T>, parent_b: &Network<T>, rng: &mut impl Rng) -> Result<Network<T>, NetworkError> {
+        parent_a.check_architecture(parent_b)?;
+
+        let mut child = parent_a.clone();
+        child.zero_gradients();
+
+        for (layer, other) in child.layers.iter_mut().zip(parent_b.layers.iter()) {
+            let mut parameters = layer.flat_parameters();
+
+            for (x, y) in parameters.iter_mut().zip(other.flat_parameters()) {
+                if rng.random() {
+                    *x = y;
+                }
+            }
+
+            layer.read_parameters(&parameters);
+        }
+
+        Ok(child)
+    }
+
+    /// Layer crossover: every layer of the child, with all its parameters, is taken from one of the two
+    /// parents with equal probability.
+    pub fn crossover_layers(parent_a: &Network<T>, parent_b: &Network<T>, rng: &mut impl Rng) -> Result<Network<T>, NetworkError> {
+        parent_a.check_architecture(parent_b)?;
+
+        let mut child = parent_a.clone();
+
+        for (layer, other) in child.layers.iter_mut().zip(parent_b.layers.iter()) {
+            if rng.random() {
+                layer.clone_from(other);
+            }
+        }
+
+        child.zero_gradients();
+        Ok(child)
+    }
+}
@@ -0,0 +1,234 @@
+//! The interface every layer of a `Network` implements. Dense `Layer`s are the usual kind, but anything
+//! with a differentiable forward pass over batches can be mixed in, e.g. a fixed scaling layer.
+
+use core::any::Any;
+
+use nalgebra::{DMatrix, DMatrixView, DVector, DVectorView};
+
+use crate::{activations::ActivationFn, prelude::*, scalar::Scalar};
+
+use super::{gradients::LayerGradientNorm, layer::{Layer, LayerError}, NonFiniteKind};
+
+/// A layer `Network` can train. The training passes work on batches with one sample per column:
+/// `forward_training_batch` records whatever `backpropagation_step_batch` needs besides the inputs, which it's
+/// given again, and that accumulates the gradient until `apply_gradient` or `zero_gradient`. The inputs are
+/// views, so the first layer trains on a `BatchSamples` batch without a copy of it.
+///
+/// Everything about parameters is optional, a layer without any keeps the provided methods. Features that
+/// need the structure of a dense layer, like serialization, merging or batch normalization, find dense
+/// layers by downcasting, see `dyn NetworkLayer::downcast_ref`.
+pub trait NetworkLayer<T: Scalar = f32>: Any + core::fmt::Debug + Send + Sync {
+    fn input_size(&self) -> usize;
+
+    fn output_size(&self) -> usize;
+
+    /// Forward pass for one sample, which may record state like `Layer::forward` does.
+    fn forward(&mut self, inputs: DVectorView<T>) -> Result<DVector<T>, LayerError>;
+
+    /// Forward pass that leaves the layer untouched.
+    fn predict(&self, inputs: DVectorView<T>) -> Result<DVector<T>, LayerError>;
+
+    /// `predict` for a batch with one sample per column.
+    fn forward_batch(&self, inputs: &DMatrix<T>) -> Result<DMatrix<T>, LayerError> {
+        let outputs: Vec<DVector<T>> = inputs
+            .column_iter()
+            .map(|input| self.predict(input))
+            .collect::<Result<_, _>>()?;
+
+        if outputs.is_empty() {
+            return Ok(DMatrix::zeros(self.output_size(), 0));
+        }
+
+        Ok(DMatrix::from_columns(&outputs))
+    }
+
+    /// Training forward pass over a batch, recording what `backpropagation_step_batch` needs besides the inputs.
+    fn forward_training_batch(&mut self, inputs: DMatrixView<T>) -> Result<DMatrix<T>, LayerError>;
+
+    /// Accumulates the gradient for the last `forward_training_batch`, given its inputs again and the gradient
+    /// with respect to its outputs, and returns the gradient with respect to its inputs. Fails with
+    /// `NoTrainingBatch` if there was no training forward pass over a batch of the gradient's size.
+    fn backpropagation_step_batch(&mut self, inputs: DMatrixView<T>, output_partial_gradient: &DMatrix<T>) -> Result<DMatrix<T>, LayerError>;
+
+    /// Adds the accumulated gradient times `scale` to the parameters and zeroes the gradient.
+    fn apply_gradient(&mut self, scale: T);
+
+    fn zero_gradient(&mut self);
+
+    fn boxed_clone(&self) -> Box<dyn NetworkLayer<T>>;
+
+    /// What kind of layer this is, for summaries and error messages.
+    fn kind(&self) -> &'static str { "custom" }
+
+    /// The activation function the outputs went through last, if there's a single one.
+    fn activation_fn(&self) -> Option<&dyn ActivationFn<T>> { None }
+
+    /// The number of trainable parameters, which `write_parameters` and `read_parameters` go through.
+    fn parameter_count(&self) -> usize { 0 }
+
+    /// Appends all trainable parameters to `parameters` in a fixed order.
+    fn write_parameters(&self, parameters: &mut Vec<T>) {}
+
+    /// Sets all trainable parameters from `parameter_count` values in the order of `write_parameters`.
+    fn read_parameters(&mut self, parameters: &[T]) {}
+
+    /// The norms of the gradients accumulated since the last `apply_gradient`.
+    fn gradient_norm(&self) -> LayerGradientNorm<T> {
+        LayerGradientNorm {
+            weights: T::zero(),
+            biases: T::zero(),
+        }
+    }
+
+    /// What `apply_gradient(scale)` would first turn non-finite, if anything.
+    fn non_finite_update(&self, scale: T) -> Option<NonFiniteKind> { None }
+
+    /// Whether every parameter is finite.
+    fn is_finite(&self) -> bool { true }
+}
+
+impl<T: Scalar> dyn NetworkLayer<T> {
+    /// The layer as its concrete type, `None` if it's of another type.
+    pub fn downcast_ref<L: NetworkLayer<T>>(&self) -> Option<&L> {
+        (self as &dyn Any).downcast_ref()
+    }
+
+    pub fn downcast_mut<L: NetworkLayer<T>>(&mut self) -> Option<&mut L> {
+        (self as &mut dyn Any).downcast_mut()
+    }
+
+    /// All trainable parameters in the order of `write_parameters`.
+    pub fn flat_parameters(&self) -> Vec<T> {
+        let mut parameters = Vec::with_capacity(self.parameter_count());
+        self.write_parameters(&mut parameters);
+        parameters
+    }
+
+    /// The layer as a dense layer, `None` for any other kind.
+    #[inline]
+    pub fn as_dense(&self) -> Option<&Layer<T>> { self.downcast_ref() }
+
+    #[inline]
+    pub fn as_dense_mut(&mut self) -> Option<&mut Layer<T>> { self.downcast_mut() }
+}
+
+/// The training forward pass through layers that chain, returning every layer's outputs in order.
+pub(crate) fn forward_training_chain<T: Scalar>(layers: &mut [Box<dyn NetworkLayer<T>>], inputs: DMatrixView<T>) -> Result<Vec<DMatrix<T>>, LayerError> {
+    let mut activations: Vec<DMatrix<T>> = Vec::with_capacity(layers.len());
+
+    for layer in layers.iter_mut() {
+        let outputs = layer.forward_training_batch(activations.last().map_or(inputs, DMatrix::as_view))?;
+        activations.push(outputs);
+    }
+
+    Ok(activations)
+}
+
+/// The backward pass for `forward_training_chain` over the same inputs, given the activations it returned
+/// and the gradient with respect to the last layer's outputs. Returns the gradient with respect to the inputs.
+pub(crate) fn backpropagate_chain<T: Scalar>(
+    layers: &mut [Box<dyn NetworkLayer<T>>],
+    inputs: DMatrixView<T>,
+    activations: &[DMatrix<T>],
+    output_partial_gradient: DMatrix<T>,
+) -> Result<DMatrix<T>, LayerError> {
+    layers.iter_mut().enumerate().rev().try_fold(output_partial_gradient, |gradient, (i, layer)| {
+        let layer_inputs = if i == 0 { inputs } else { activations[i - 1].as_view() };
+        layer.backpropagation_step_batch(layer_inputs, &gradient)
+    })
+}
+
+impl<T: Scalar> Clone for Box<dyn NetworkLayer<T>> {
+    fn clone(&self) -> Self {
+        self.boxed_clone()
+    }
+}
+
+impl<T: Scalar> NetworkLayer<T> for Layer<T> {
+    #[inline]
+    fn input_size(&self) -> usize { Layer::input_size(self) }
+
+    #[inline]
+    fn output_size(&self) -> usize { Layer::output_size(self) }
+
+    fn forward(&mut self, inputs: DVectorView<T>) -> Result<DVector<T>, LayerError> {
+        self.forward_view(inputs)
+    }
+
+    fn predict(&self, inputs: DVectorView<T>) -> Result<DVector<T>, LayerError> {
+        Layer::predict(self, inputs)
+    }
+
+    fn forward_batch(&self, inputs: &DMatrix<T>) -> Result<DMatrix<T>, LayerError> {
+        Layer::forward_batch(self, inputs)
+    }
+
+    fn forward_training_batch(&mut self, inputs: DMatrixView<T>) -> Result<DMatrix<T>, LayerError> {
+        let (outputs, cache) = Layer::forward_training_batch(self, inputs)?;
+        self.set_previous_batch(cache, outputs.clone());
+        Ok(outputs)
+    }
+
+    fn backpropagation_step_batch(&mut self, inputs: DMatrixView<T>, output_partial_gradient: &DMatrix<T>) -> Result<DMatrix<T>, LayerError> {
+        self.check_input_size(inputs.nrows())?;
+
+        match self.take_previous_batch() {
+            Some((cache, outputs)) if outputs.shape() == output_partial_gradient.shape() && inputs.ncols() == outputs.ncols() => {
+                Ok(Layer::backpropagation_step_batch(self, &cache, inputs, &outputs, output_partial_gradient))
+            }
+            _ => Err(LayerError::NoTrainingBatch(output_partial_gradient.ncols())),
+        }
+    }
+
+    fn apply_gradient(&mut self, scale: T) {
+        Layer::apply_gradient(self, scale);
+    }
+
+    fn zero_gradient(&mut self) {
+        Layer::zero_gradient(self);
+    }
+
+    fn boxed_clone(&self) -> Box<dyn NetworkLayer<T>> {
+        Box::new(self.clone())
+    }
+
+    fn kind(&self) -> &'static str { "dense" }
+
+    fn activation_fn(&self) -> Option<&dyn ActivationFn<T>> { Some(Layer::activation_fn(self)) }
+
+    fn parameter_count(&self) -> usize { (self.input_size() + usize::from(self.has_bias())) * self.output_size() }
+
+    /// The weight matrix in column-major order, followed by the biases if the layer has them. Batch normalization
+    /// parameters aren't included.
+    fn write_parameters(&self, parameters: &mut Vec<T>) {
+        parameters.extend(self.weights().iter());
+
+        if self.has_bias() {
+            parameters.extend(self.biases().iter());
+        }
+    }
+
+    fn read_parameters(&mut self, parameters: &[T]) {
+        let (weights, biases) = parameters.split_at(self.input_size() * self.output_size());
+        self.weights_mut().copy_from_slice(weights);
+
+        if self.has_bias() {
+            self.biases_mut().copy_from_slice(biases);
+        }
+    }
+
+    fn gradient_norm(&self) -> LayerGradientNorm<T> {
+        LayerGradientNorm {
+            weights: self.weight_gradient().norm(),
+            biases: self.bias_gradient().norm(),
+        }
+    }
+
+    fn non_finite_update(&self, scale: T) -> Option<NonFiniteKind> {
+        Layer::non_finite_update(self, scale)
+    }
+
+    fn is_finite(&self) -> bool {
+        Layer::is_finite(self)
+    }
+}
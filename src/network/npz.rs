@@ -0,0 +1,491 @@
+//! NumPy `.npz` export and import of a network's parameters, readable with `np.load`. Every layer is stored as
+//! the arrays `layer{i}_weights` shaped `(outputs, inputs)` and `layer{i}_biases` shaped `(outputs,)`, a layer
+//! without biases has no bias array. Batch normalized layers add `layer{i}_batch_norm_gamma`, `_beta`,
+//! `_running_mean` and `_running_variance`, all shaped `(outputs,)`. A maxout layer stores its first piece as
+//! the layer's weights and biases and every further piece `p` as `layer{i}_piece{p}_weights` and
+//! `layer{i}_piece{p}_biases`. Arrays are little-endian f32.
+//!
+//! nalgebra stores matrices column-major, while NumPy defaults to row-major (C order). Weights are written in
+//! C order, so `weights[j, i]` in NumPy is the weight from input `i` to output `j`, as in `Layer::weights`,
+//! and `weights @ x + biases` is a layer's weighted sum. Loading takes either order and f4 or f8 arrays.
+//!
+//! The archive is an uncompressed zip, like `np.savez` writes. Archives from `np.savez_compressed` aren't supported.
+
+use std::{fs, io, path::Path};
+
+use nalgebra::{DMatrix, DVector};
+use thiserror::Error;
+
+use crate::activations::ActivationFn;
+
+use super::{batch_norm::BatchNorm, layer::Layer, maxout::MaxoutLayer, Network, NetworkError, NetworkLayer};
+
+const BATCH_NORM_ARRAYS: [&str; 4] = ["gamma", "beta", "running_mean", "running_variance"];
+
+const NPY_MAGIC: &[u8] = b"\x93NUMPY";
+const LOCAL_HEADER_SIGNATURE: u32 = 0x0403_4b50;
+const CENTRAL_HEADER_SIGNATURE: u32 = 0x0201_4b50;
+const END_OF_CENTRAL_DIRECTORY_SIGNATURE: u32 = 0x0605_4b50;
+/// Marks a size or offset that's stored in the zip64 extra field instead.
+const ZIP64_MARKER: u32 = u32::MAX;
+
+#[derive(Debug, Error)]
+pub enum NpzError {
+    #[error("{0}")]
+    Io(#[from] io::Error),
+
+    #[error("{0}")]
+    NetworkError(#[from] NetworkError),
+
+    #[error("the file isn't a zip archive or it's truncated")]
+    InvalidArchive,
+
+    #[error("the array {0:?} is compressed, only archives written by np.savez are supported")]
+    Compressed(String),
+
+    #[error("the data of array {0:?} doesn't match its checksum")]
+    ChecksumMismatch(String),
+
+    #[error("the array {0:?} isn't a valid .npy array")]
+    InvalidArray(String),
+
+    #[error("the array {name:?} has the dtype {dtype}, but only <f4 and <f8 are supported")]
+    UnsupportedDtype {
+        name: String,
+        dtype: String,
+    },
+
+    #[error("the array {0:?} is missing")]
+    MissingArray(String),
+
+    #[error("the array {0:?} isn't part of the network")]
+    UnexpectedArray(String),
+
+    #[error("the array {name:?} has the shape {found:?}, but {expected:?} was expected")]
+    ShapeMismatch {
+        name: String,
+        expected: Vec<usize>,
+        found: Vec<usize>,
+    },
+
+    #[error("the network has {layers} layers, but {activations} activation functions were given")]
+    ActivationCountMismatch {
+        layers: usize,
+        activations: usize,
+    },
+}
+
+/// An array's shape and its values in C order.
+struct Array {
+    shape: Vec<usize>,
+    data: Vec<f32>,
+}
+
+impl Network {
+    pub fn export_npz(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let bytes = self.to_npz().map_err(|error| io::Error::new(io::ErrorKind::InvalidInput, error))?;
+        fs::write(path, bytes)
+    }
+
+    /// Fails with `NotDense` if the network has layers other than dense and maxout ones.
+    pub fn to_npz(&self) -> Result<Vec<u8>, NetworkError> {
+        let mut files = Vec::new();
+
+        for (i, record) in self.to_records()?.into_iter().enumerate() {
+            let output_size = record.weights.nrows();
+
+            files.push((
+                format!("layer{i}_weights.npy"),
+                npy(&[output_size, record.weights.ncols()], record.weights.transpose().as_slice()),
+            ));
+            if let Some(biases) = record.biases {
+                files.push((format!("layer{i}_biases.npy"), npy(&[output_size], biases.as_slice())));
+            }
+
+            for (p, (weights, biases)) in record.maxout_pieces.iter().enumerate() {
+                let p = p + 1;
+                files.push((format!("layer{i}_piece{p}_weights.npy"), npy(&[output_size, weights.ncols()], weights.transpose().as_slice())));
+                files.push((format!("layer{i}_piece{p}_biases.npy"), npy(&[output_size], biases.as_slice())));
+            }
+
+            if let Some(batch_norm) = record.batch_norm {
+                let vectors = [batch_norm.gamma, batch_norm.beta, batch_norm.running_mean, batch_norm.running_variance];
+
+                for (name, vector) in BATCH_NORM_ARRAYS.iter().zip(vectors) {
+                    files.push((format!("layer{i}_batch_norm_{name}.npy"), npy(&[output_size], vector.as_slice())));
+                }
+            }
+        }
+
+        Ok(zip(&files))
+    }
+
+    /// Loads parameters saved with `export_npz` or written by NumPy in the same layout into a network with the
+    /// given layer sizes and one activation function per layer. Batch normalization is enabled for the layers
+    /// that have its arrays. The layers with piece arrays are maxout layers, their activation function is
+    /// ignored.
+    pub fn import_npz(
+        path: impl AsRef<Path>,
+        layer_sizes: &[usize],
+        activation_fns: Vec<Box<dyn ActivationFn>>,
+    ) -> Result<Self, NpzError> {
+        Self::from_npz(&fs::read(path)?, layer_sizes, activation_fns)
+    }
+
+    pub fn from_npz(bytes: &[u8], layer_sizes: &[usize], activation_fns: Vec<Box<dyn ActivationFn>>) -> Result<Self, NpzError> {
+        super::check_layer_sizes(layer_sizes)?;
+
+        if activation_fns.len() != layer_sizes.len() - 1 {
+            return Err(NpzError::ActivationCountMismatch {
+                layers: layer_sizes.len() - 1,
+                activations: activation_fns.len(),
+            });
+        }
+
+        let mut arrays = read_arrays(bytes)?;
+        let mut take = |name: String, shape: &[usize]| -> Result<Option<Vec<f32>>, NpzError> {
+            let Some(index) = arrays.iter().position(|(array_name, _)| *array_name == name) else {
+                return Ok(None);
+            };
+
+            let (_, array) = arrays.swap_remove(index);
+            if array.shape != shape {
+                return Err(NpzError::ShapeMismatch {
+                    name,
+                    expected: shape.to_vec(),
+                    found: array.shape,
+                });
+            }
+
+            Ok(Some(array.data))
+        };
+
+        let mut layers = Vec::with_capacity(layer_sizes.len() - 1);
+
+        for ((i, sizes), activation_fn) in layer_sizes.windows(2).enumerate().zip(activation_fns) {
+            let (input_size, output_size) = (sizes[0], sizes[1]);
+            let required = |name: String, data: Option<Vec<f32>>| data.ok_or(NpzError::MissingArray(name));
+
+            let name = format!("layer{i}_weights");
+            let weights = required(name.clone(), take(name, &[output_size, input_size])?)?;
+            // A layer without biases has no bias array.
+            let biases = take(format!("layer{i}_biases"), &[output_size])?;
+
+            let mut pieces = Vec::new();
+            while let Some(weights) = take(format!("layer{i}_piece{}_weights", pieces.len() + 1), &[output_size, input_size])? {
+                let name = format!("layer{i}_piece{}_biases", pieces.len() + 1);
+                pieces.push((weights, required(name.clone(), take(name, &[output_size])?)?));
+            }
+
+            if !pieces.is_empty() {
+                // Batch normalization arrays of a maxout layer are left over and rejected below.
+                pieces.insert(0, (weights, required(format!("layer{i}_biases"), biases)?));
+                layers.push(Box::new(MaxoutLayer::from_row_major_pieces(input_size, output_size, pieces).map_err(NetworkError::from)?) as Box<dyn NetworkLayer>);
+                continue;
+            }
+
+            let mut batch_norm_vectors = Vec::new();
+            for array in BATCH_NORM_ARRAYS {
+                let name = format!("layer{i}_batch_norm_{array}");
+                batch_norm_vectors.push((name.clone(), take(name, &[output_size])?));
+            }
+
+            let batch_norm = if batch_norm_vectors.iter().all(|(_, data)| data.is_none()) {
+                None
+            } else {
+                let mut vectors = Vec::with_capacity(4);
+                for (name, data) in batch_norm_vectors {
+                    vectors.push(DVector::from_vec(required(name, data)?));
+                }
+
+                let [gamma, beta, running_mean, running_variance] = vectors.try_into().unwrap();
+                let mut record = BatchNorm::new(output_size).to_record();
+                record.gamma = gamma;
+                record.beta = beta;
+                record.running_mean = running_mean;
+                record.running_variance = running_variance;
+                Some(BatchNorm::from_record(record))
+            };
+
+            layers.push(Box::new(Layer::from_parts(
+                DMatrix::from_row_slice(output_size, input_size, &weights),
+                biases.map(DVector::from_vec),
+                activation_fn,
+                batch_norm,
+            )));
+        }
+
+        if let Some((name, _)) = arrays.into_iter().next() {
+            return Err(NpzError::UnexpectedArray(name));
+        }
+
+        Ok(Self::from_network_layers(layers)?)
+    }
+}
+
+/// A version 1.0 `.npy` file of a little-endian f32 array in C order.
+fn npy(shape: &[usize], data: &[f32]) -> Vec<u8> {
+    let shape = match shape {
+        [size] => format!("({size},)"),
+        _ => format!("({})", shape.iter().map(usize::to_string).collect::<Vec<_>>().join(", ")),
+    };
+
+    // The header ends in a newline and is padded so that the data starts at a multiple of 64 bytes.
+    let mut header = format!("{{'descr': '<f4', 'fortran_order': False, 'shape': {shape}, }}").into_bytes();
+    let unpadded = NPY_MAGIC.len() + 4 + header.len() + 1;
+    header.resize(header.len() + unpadded.next_multiple_of(64) - unpadded, b' ');
+    header.push(b'\n');
+
+    let mut bytes = Vec::with_capacity(NPY_MAGIC.len() + 4 + header.len() + data.len() * 4);
+    bytes.extend_from_slice(NPY_MAGIC);
+    bytes.extend_from_slice(&[1, 0]);
+    bytes.extend_from_slice(&(header.len() as u16).to_le_bytes());
+    bytes.extend_from_slice(&header);
+
+    for value in data {
+        bytes.extend_from_slice(&value.to_le_bytes());
+    }
+
+    bytes
+}
+
+/// An uncompressed zip archive of the given files.
+fn zip(files: &[(String, Vec<u8>)]) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    let mut central_directory = Vec::new();
+
+    for (name, data) in files {
+        let offset = bytes.len() as u32;
+        let checksum = crc32(data);
+
+        // The version needed, flags, method, modification time and date (1980-01-01), checksum and sizes are
+        // shared by the local and the central header.
+        let mut common = Vec::with_capacity(26);
+        common.extend_from_slice(&20u16.to_le_bytes());
+        common.extend_from_slice(&0u16.to_le_bytes());
+        common.extend_from_slice(&0u16.to_le_bytes());
+        common.extend_from_slice(&0u16.to_le_bytes());
+        common.extend_from_slice(&0x21u16.to_le_bytes());
+        common.extend_from_slice(&checksum.to_le_bytes());
+        common.extend_from_slice(&(data.len() as u32).to_le_bytes());
+        common.extend_from_slice(&(data.len() as u32).to_le_bytes());
+        common.extend_from_slice(&(name.len() as u16).to_le_bytes());
+        common.extend_from_slice(&0u16.to_le_bytes());
+
+        bytes.extend_from_slice(&LOCAL_HEADER_SIGNATURE.to_le_bytes());
+        bytes.extend_from_slice(&common);
+        bytes.extend_from_slice(name.as_bytes());
+        bytes.extend_from_slice(data);
+
+        central_directory.extend_from_slice(&CENTRAL_HEADER_SIGNATURE.to_le_bytes());
+        central_directory.extend_from_slice(&20u16.to_le_bytes());
+        central_directory.extend_from_slice(&common);
+        // The comment length, disk number, internal and external attributes.
+        central_directory.extend_from_slice(&[0; 10]);
+        central_directory.extend_from_slice(&offset.to_le_bytes());
+        central_directory.extend_from_slice(name.as_bytes());
+    }
+
+    let central_directory_offset = bytes.len() as u32;
+    bytes.extend_from_slice(&central_directory);
+
+    bytes.extend_from_slice(&END_OF_CENTRAL_DIRECTORY_SIGNATURE.to_le_bytes());
+    bytes.extend_from_slice(&[0; 4]);
+    bytes.extend_from_slice(&(files.len() as u16).to_le_bytes());
+    bytes.extend_from_slice(&(files.len() as u16).to_le_bytes());
+    bytes.extend_from_slice(&(central_directory.len() as u32).to_le_bytes());
+    bytes.extend_from_slice(&central_directory_offset.to_le_bytes());
+    bytes.extend_from_slice(&0u16.to_le_bytes());
+
+    bytes
+}
+
+/// The arrays of an `.npz` archive by name, without the `.npy` extension.
+fn read_arrays(bytes: &[u8]) -> Result<Vec<(String, Array)>, NpzError> {
+    let mut arrays = Vec::new();
+
+    for (name, data) in read_zip(bytes)? {
+        let name = name.strip_suffix(".npy").map(str::to_string).unwrap_or(name);
+        let array = read_npy(&name, data)?;
+        arrays.push((name, array));
+    }
+
+    Ok(arrays)
+}
+
+/// The files of an uncompressed zip archive. The sizes and offsets are taken from the central directory,
+/// or from its zip64 extra fields, which `np.savez` writes.
+fn read_zip(bytes: &[u8]) -> Result<Vec<(String, &[u8])>, NpzError> {
+    // The end of central directory record is 22 bytes, followed by a comment of up to 65535 bytes.
+    let end = (0..=bytes.len().saturating_sub(22))
+        .rev()
+        .take(22 + u16::MAX as usize)
+        .find(|&position| u32_at(bytes, position) == Some(END_OF_CENTRAL_DIRECTORY_SIGNATURE))
+        .ok_or(NpzError::InvalidArchive)?;
+
+    let count = u16_at(bytes, end + 10).ok_or(NpzError::InvalidArchive)? as usize;
+    let mut position = u32_at(bytes, end + 16).ok_or(NpzError::InvalidArchive)? as usize;
+    let mut files = Vec::with_capacity(count);
+
+    for _ in 0..count {
+        if u32_at(bytes, position) != Some(CENTRAL_HEADER_SIGNATURE) {
+            return Err(NpzError::InvalidArchive);
+        }
+
+        let field = |offset: usize| u16_at(bytes, position + offset).map(usize::from).ok_or(NpzError::InvalidArchive);
+        let (method, name_len, extra_len, comment_len) = (field(10)?, field(28)?, field(30)?, field(32)?);
+        let word = |offset: usize| u32_at(bytes, position + offset).ok_or(NpzError::InvalidArchive);
+        let (checksum, mut compressed_size, mut size, mut offset) = (word(16)?, word(20)? as u64, word(24)? as u64, word(42)? as u64);
+
+        let name = bytes
+            .get(position + 46..position + 46 + name_len)
+            .ok_or(NpzError::InvalidArchive)?;
+        let name = String::from_utf8_lossy(name).into_owned();
+        let extra = bytes
+            .get(position + 46 + name_len..position + 46 + name_len + extra_len)
+            .ok_or(NpzError::InvalidArchive)?;
+
+        // The zip64 extra field holds the values marked in the header, in this order.
+        if let Some(mut zip64) = zip64_field(extra) {
+            for value in [&mut size, &mut compressed_size, &mut offset] {
+                if *value == ZIP64_MARKER as u64 {
+                    *value = u64_at(zip64, 0).ok_or(NpzError::InvalidArchive)?;
+                    zip64 = &zip64[8..];
+                }
+            }
+        }
+
+        if method != 0 {
+            return Err(NpzError::Compressed(name));
+        }
+
+        if compressed_size != size {
+            return Err(NpzError::InvalidArchive);
+        }
+
+        let local = offset as usize;
+        if u32_at(bytes, local) != Some(LOCAL_HEADER_SIGNATURE) {
+            return Err(NpzError::InvalidArchive);
+        }
+
+        let local_name_len = u16_at(bytes, local + 26).ok_or(NpzError::InvalidArchive)? as usize;
+        let local_extra_len = u16_at(bytes, local + 28).ok_or(NpzError::InvalidArchive)? as usize;
+        let start = local + 30 + local_name_len + local_extra_len;
+        let data = start
+            .checked_add(size as usize)
+            .and_then(|end| bytes.get(start..end))
+            .ok_or(NpzError::InvalidArchive)?;
+
+        if crc32(data) != checksum {
+            return Err(NpzError::ChecksumMismatch(name));
+        }
+
+        files.push((name, data));
+        position += 46 + name_len + extra_len + comment_len;
+    }
+
+    Ok(files)
+}
+
+fn zip64_field(mut extra: &[u8]) -> Option<&[u8]> {
+    while let (Some(id), Some(len)) = (u16_at(extra, 0), u16_at(extra, 2)) {
+        let data = extra.get(4..4 + len as usize)?;
+        if id == 1 {
+            return Some(data);
+        }
+
+        extra = &extra[4 + len as usize..];
+    }
+
+    None
+}
+
+fn read_npy(name: &str, bytes: &[u8]) -> Result<Array, NpzError> {
+    let invalid = || NpzError::InvalidArray(name.to_string());
+
+    if !bytes.starts_with(NPY_MAGIC) {
+        return Err(invalid());
+    }
+
+    // Version 1 has a 2 byte header length, versions 2 and 3 a 4 byte one.
+    let (header_start, header_len) = match bytes.get(NPY_MAGIC.len()) {
+        Some(1) => (10, u16_at(bytes, 8).ok_or_else(invalid)? as usize),
+        Some(2 | 3) => (12, u32_at(bytes, 8).ok_or_else(invalid)? as usize),
+        _ => return Err(invalid()),
+    };
+
+    let header = bytes
+        .get(header_start..header_start + header_len)
+        .and_then(|header| std::str::from_utf8(header).ok())
+        .ok_or_else(invalid)?;
+    let data = &bytes[header_start + header_len..];
+
+    let dtype = header_value(header, "descr")
+        .and_then(|value| value.strip_prefix('\''))
+        .and_then(|value| value.split('\'').next())
+        .ok_or_else(invalid)?;
+    let fortran_order = header_value(header, "fortran_order").ok_or_else(invalid)?.starts_with("True");
+    let shape = header_value(header, "shape")
+        .and_then(|value| value.strip_prefix('('))
+        .and_then(|value| value.split(')').next())
+        .ok_or_else(invalid)?
+        .split(',')
+        .map(str::trim)
+        .filter(|dim| !dim.is_empty())
+        .map(|dim| dim.parse::<usize>().map_err(|_| invalid()))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    // The shape comes from the file, so its size is checked before it's trusted for slicing.
+    let len = shape.iter().try_fold(1usize, |len, &dim| len.checked_mul(dim)).ok_or_else(invalid)?;
+    let values: Vec<f32> = match dtype {
+        "<f4" => data
+            .get(..len.checked_mul(4).ok_or_else(invalid)?)
+            .ok_or_else(invalid)?
+            .chunks_exact(4)
+            .map(|value| f32::from_le_bytes(value.try_into().unwrap()))
+            .collect(),
+        "<f8" => data
+            .get(..len.checked_mul(8).ok_or_else(invalid)?)
+            .ok_or_else(invalid)?
+            .chunks_exact(8)
+            .map(|value| f64::from_le_bytes(value.try_into().unwrap()) as f32)
+            .collect(),
+        _ => return Err(NpzError::UnsupportedDtype {
+            name: name.to_string(),
+            dtype: dtype.to_string(),
+        }),
+    };
+
+    // A matrix in Fortran order is column-major, any other array reads the same in either order.
+    let data = match shape[..] {
+        [rows, columns] if fortran_order => DMatrix::from_column_slice(rows, columns, &values).transpose().as_slice().to_vec(),
+        _ => values,
+    };
+
+    Ok(Array { shape, data })
+}
+
+/// The text after `'key':` in an `.npy` header, which is a Python dict literal.
+fn header_value<'a>(header: &'a str, key: &str) -> Option<&'a str> {
+    let start = header.find(&format!("'{key}'"))? + key.len() + 2;
+    Some(header[start..].trim_start().strip_prefix(':')?.trim_start())
+}
+
+fn u16_at(bytes: &[u8], position: usize) -> Option<u16> {
+    Some(u16::from_le_bytes(bytes.get(position..position + 2)?.try_into().unwrap()))
+}
+
+fn u32_at(bytes: &[u8], position: usize) -> Option<u32> {
+    Some(u32::from_le_bytes(bytes.get(position..position + 4)?.try_into().unwrap()))
+}
+
+fn u64_at(bytes: &[u8], position: usize) -> Option<u64> {
+    Some(u64::from_le_bytes(bytes.get(position..position + 8)?.try_into().unwrap()))
+}
+
+/// The CRC-32 zip archives use to check their files, bit by bit with the reversed polynomial.
+fn crc32(bytes: &[u8]) -> u32 {
+    !bytes.iter().fold(u32::MAX, |crc, &byte| {
+        (0..8).fold(crc ^ byte as u32, |crc, _| if crc & 1 == 1 { (crc >> 1) ^ 0xedb8_8320 } else { crc >> 1 })
+    })
+}
@@ -0,0 +1,214 @@
+//! ONNX export. Every layer becomes a `MatMul` with its transposed weights, an `Add` of its biases and
+//! the activation's operator, on a `[batch, inputs]` float input named `input`. Batch normalization is
+//! folded into the weights and biases using the running statistics. The protobuf is written by hand,
+//! so the feature needs no extra dependencies.
+
+use std::{fs, io, path::Path};
+
+use nalgebra::{DMatrix, DVector};
+use thiserror::Error;
+
+use super::{serialization::LayerRecord, Network, NetworkError};
+
+const IR_VERSION: u64 = 8;
+const OPSET_VERSION: u64 = 13;
+const FLOAT: u64 = 1;
+
+#[derive(Debug, Error)]
+pub enum OnnxExportError {
+    #[error("{0}")]
+    Io(#[from] io::Error),
+
+    #[error("{0}")]
+    Network(#[from] NetworkError),
+
+    #[error("layer {layer} uses the activation function {name:?}, which has no ONNX operator")]
+    UnsupportedActivation {
+        layer: usize,
+        name: String,
+    },
+}
+
+impl Network {
+    pub fn export_onnx(&self, path: impl AsRef<Path>) -> Result<(), OnnxExportError> {
+        fs::write(path, self.to_onnx()?)?;
+        Ok(())
+    }
+
+    /// The serialized ONNX `ModelProto` that `export_onnx` writes. Fails with `NotDense` if the network has
+    /// layers other than dense ones.
+    pub fn to_onnx(&self) -> Result<Vec<u8>, OnnxExportError> {
+        self.check_dense()?;
+        let records = self.to_records()?;
+        let mut graph = Message::new();
+        let mut current = "input".to_string();
+
+        for (layer, record) in records.iter().enumerate() {
+            let (weights, biases) = folded_parameters(record);
+            let operator = activation_operator(&record.activation).ok_or_else(|| OnnxExportError::UnsupportedActivation {
+                layer,
+                name: record.activation.clone(),
+            })?;
+
+            let weights_name = format!("layer{layer}.weight");
+            let biases_name = format!("layer{layer}.bias");
+            let product = format!("layer{layer}.matmul");
+            let sum = format!("layer{layer}.add");
+
+            // `weights` is column major, so its data is the row major layout of its transpose.
+            graph.message(5, &tensor(&weights_name, &[weights.ncols(), weights.nrows()], weights.as_slice()));
+            graph.message(5, &tensor(&biases_name, &[biases.len()], biases.as_slice()));
+
+            graph.message(1, &node("MatMul", &[&current, &weights_name], &product));
+            graph.message(1, &node("Add", &[&product, &biases_name], &sum));
+            current = sum;
+
+            if let Some(operator) = operator {
+                let activation = format!("layer{layer}.activation");
+                graph.message(1, &node(operator, &[&current], &activation));
+                current = activation;
+            }
+        }
+
+        graph.message(1, &node("Identity", &[&current], "output"));
+        graph.string(2, "neural");
+        graph.message(11, &value_info("input", records[0].weights.ncols()));
+        graph.message(12, &value_info("output", records.last().unwrap().weights.nrows()));
+
+        let mut opset = Message::new();
+        opset.string(1, "");
+        opset.varint(2, OPSET_VERSION);
+
+        let mut model = Message::new();
+        model.varint(1, IR_VERSION);
+        model.string(2, "neural");
+        model.message(7, &graph);
+        model.message(8, &opset);
+
+        Ok(model.bytes)
+    }
+}
+
+/// The ONNX operator of an activation function, `Some(None)` for the identity.
+fn activation_operator(name: &str) -> Option<Option<&'static str>> {
+    match name {
+        "sigmoid" => Some(Some("Sigmoid")),
+        "tanh" => Some(Some("Tanh")),
+        "relu" => Some(Some("Relu")),
+        "softsign" => Some(Some("Softsign")),
+        // ONNX's default alpha and beta are the 0.2 and 0.5 of `HardSigmoid`.
+        "hard_sigmoid" => Some(Some("HardSigmoid")),
+        "identity" | "linear" => Some(None),
+        _ => None,
+    }
+}
+
+/// The layer's weights and biases with its batch normalization folded in. A layer without biases gets zeros.
+fn folded_parameters(record: &LayerRecord) -> (DMatrix<f32>, DVector<f32>) {
+    let biases = record.biases.clone().unwrap_or_else(|| DVector::zeros(record.weights.nrows()));
+    let Some(batch_norm) = &record.batch_norm else {
+        return (record.weights.clone(), biases);
+    };
+
+    let scale = batch_norm.running_variance.zip_map(&batch_norm.gamma, |variance, gamma| {
+        gamma / (variance + batch_norm.epsilon).sqrt()
+    });
+
+    let mut weights = record.weights.clone();
+    for (mut row, &scale) in weights.row_iter_mut().zip(scale.iter()) {
+        row *= scale;
+    }
+
+    let biases = (biases - &batch_norm.running_mean).component_mul(&scale) + &batch_norm.beta;
+    (weights, biases)
+}
+
+fn tensor(name: &str, dims: &[usize], values: &[f32]) -> Message {
+    let mut tensor = Message::new();
+
+    for &dim in dims {
+        tensor.varint(1, dim as u64);
+    }
+
+    tensor.varint(2, FLOAT);
+    tensor.string(8, name);
+    tensor.bytes(9, &values.iter().flat_map(|value| value.to_le_bytes()).collect::<Vec<_>>());
+    tensor
+}
+
+fn node(operator: &str, inputs: &[&str], output: &str) -> Message {
+    let mut node = Message::new();
+
+    for input in inputs {
+        node.string(1, input);
+    }
+
+    node.string(2, output);
+    node.string(3, output);
+    node.string(4, operator);
+    node
+}
+
+/// A `[batch, size]` float tensor with a symbolic batch dimension.
+fn value_info(name: &str, size: usize) -> Message {
+    let mut batch = Message::new();
+    batch.string(2, "batch");
+
+    let mut features = Message::new();
+    features.varint(1, size as u64);
+
+    let mut shape = Message::new();
+    shape.message(1, &batch);
+    shape.message(1, &features);
+
+    let mut tensor_type = Message::new();
+    tensor_type.varint(1, FLOAT);
+    tensor_type.message(2, &shape);
+
+    let mut type_proto = Message::new();
+    type_proto.message(1, &tensor_type);
+
+    let mut value_info = Message::new();
+    value_info.string(1, name);
+    value_info.message(2, &type_proto);
+    value_info
+}
+
+/// Protobuf wire format encoder for the few field types ONNX needs.
+struct Message {
+    bytes: Vec<u8>,
+}
+
+impl Message {
+    fn new() -> Self {
+        Self { bytes: Vec::new() }
+    }
+
+    fn raw_varint(&mut self, mut value: u64) {
+        while value >= 0x80 {
+            self.bytes.push(value as u8 | 0x80);
+            value >>= 7;
+        }
+
+        self.bytes.push(value as u8);
+    }
+
+    fn varint(&mut self, field: u32, value: u64) {
+        self.raw_varint((field as u64) << 3);
+        self.raw_varint(value);
+    }
+
+    fn bytes(&mut self, field: u32, bytes: &[u8]) {
+        self.raw_varint(((field as u64) << 3) | 2);
+        self.raw_varint(bytes.len() as u64);
+        self.bytes.extend_from_slice(bytes);
+    }
+
+    fn string(&mut self, field: u32, string: &str) {
+        self.bytes(field, string.as_bytes());
+    }
+
+    fn message(&mut self, field: u32, message: &Message) {
+        self.bytes(field, &message.bytes);
+    }
+}
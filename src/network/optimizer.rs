@@ -0,0 +1,140 @@
+//! How a step turns the accumulated gradient into new parameters. `Network::learn` and `Trainer` take plain
+//! gradient descent steps with `Sgd` unless given another `Optimizer`, like `RProp` or a `Lookahead` around
+//! either.
+
+use crate::{prelude::*, scalar::Scalar};
+
+use super::{Network, NetworkError};
+
+/// Updates a network's parameters from the gradient it accumulated in `backpropagate`.
+pub trait Optimizer<T: Scalar = f32> {
+    /// Updates the parameters with the gradient summed over samples weighing `total_weight` in total, at
+    /// learning rate `rate`, and zeroes the gradient. Optimizers with state tie it to the network of their
+    /// first step.
+    fn step(&mut self, network: &mut Network<T>, rate: T, total_weight: T) -> Result<(), NetworkError>;
+
+    /// Forgets any state, e.g. to use the optimizer on another network.
+    fn reset(&mut self) {}
+
+    /// The state a checkpoint needs to resume stepping where the optimizer left off. Optimizers without
+    /// state keep the provided methods.
+    fn save_state(&self) -> OptimizerState<T> {
+        OptimizerState::new(STATELESS)
+    }
+
+    /// Replaces the state with one `save_state` returned, for stepping `network`. Fails with
+    /// `OptimizerKindMismatch` if it's another optimizer's state and with `MissingOptimizerState` or
+    /// `OptimizerStateMismatch` if it lacks a value or doesn't fit the network, before anything changes.
+    fn load_state(&mut self, state: &OptimizerState<T>, network: &Network<T>) -> Result<(), NetworkError> {
+        state.check_kind(STATELESS)
+    }
+}
+
+impl<T: Scalar, O: Optimizer<T> + ?Sized> Optimizer<T> for Box<O> {
+    fn step(&mut self, network: &mut Network<T>, rate: T, total_weight: T) -> Result<(), NetworkError> {
+        (**self).step(network, rate, total_weight)
+    }
+
+    fn reset(&mut self) {
+        (**self).reset();
+    }
+
+    fn save_state(&self) -> OptimizerState<T> {
+        (**self).save_state()
+    }
+
+    fn load_state(&mut self, state: &OptimizerState<T>, network: &Network<T>) -> Result<(), NetworkError> {
+        (**self).load_state(state, network)
+    }
+}
+
+/// The kind of the state of optimizers without one.
+const STATELESS: &str = "stateless";
+
+/// An optimizer's state: buffers with one value per parameter, in the order of `Network::get_params`, or
+/// empty before the first step, and counters. The kind names the optimizer, and wrapping optimizers
+/// like `Lookahead` nest the kind and prefix the names of the state they wrap.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct OptimizerState<T: Scalar = f32> {
+    pub kind: String,
+    pub buffers: Vec<(String, Vec<T>)>,
+    pub counters: Vec<(String, u64)>,
+}
+
+impl<T: Scalar> OptimizerState<T> {
+    pub fn new(kind: impl Into<String>) -> Self {
+        Self {
+            kind: kind.into(),
+            buffers: Vec::new(),
+            counters: Vec::new(),
+        }
+    }
+
+    /// Fails with `OptimizerKindMismatch` unless the state is of the given kind.
+    pub fn check_kind(&self, kind: &str) -> Result<(), NetworkError> {
+        if self.kind != kind {
+            return Err(NetworkError::OptimizerKindMismatch {
+                expected: kind.to_string(),
+                found: self.kind.clone(),
+            });
+        }
+
+        Ok(())
+    }
+
+    /// The buffer named `name`, failing with `OptimizerStateMismatch` unless it's empty or has a value for
+    /// every parameter of `network`.
+    pub fn buffer(&self, name: &str, network: &Network<T>) -> Result<&[T], NetworkError> {
+        let (_, values) = self
+            .buffers
+            .iter()
+            .find(|(buffer, _)| buffer == name)
+            .ok_or_else(|| NetworkError::MissingOptimizerState(name.to_string()))?;
+
+        if !values.is_empty() && values.len() != network.parameter_count() {
+            return Err(NetworkError::OptimizerStateMismatch {
+                buffer: name.to_string(),
+                expected: network.parameter_count(),
+                found: values.len(),
+            });
+        }
+
+        Ok(values)
+    }
+
+    pub fn counter(&self, name: &str) -> Result<u64, NetworkError> {
+        self.counters
+            .iter()
+            .find(|(counter, _)| counter == name)
+            .map(|&(_, value)| value)
+            .ok_or_else(|| NetworkError::MissingOptimizerState(name.to_string()))
+    }
+
+    /// Adds the buffers and counters of `inner` with their names prefixed by `prefix`.
+    pub fn nest(&mut self, prefix: &str, inner: OptimizerState<T>) {
+        self.buffers.extend(inner.buffers.into_iter().map(|(name, values)| (format!("{prefix}.{name}"), values)));
+        self.counters.extend(inner.counters.into_iter().map(|(name, value)| (format!("{prefix}.{name}"), value)));
+    }
+
+    /// The state `nest` added under `prefix`, of the given kind.
+    pub fn nested(&self, prefix: &str, kind: &str) -> Self {
+        let unprefixed = |name: &String| name.strip_prefix(prefix).and_then(|name| name.strip_prefix('.')).map(str::to_string);
+
+        Self {
+            kind: kind.to_string(),
+            buffers: self.buffers.iter().filter_map(|(name, values)| Some((unprefixed(name)?, values.clone()))).collect(),
+            counters: self.counters.iter().filter_map(|(name, value)| Some((unprefixed(name)?, *value))).collect(),
+        }
+    }
+}
+
+/// Plain gradient descent, a step of `rate` against the weighted mean gradient, which is what `learn` does.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Sgd;
+
+impl<T: Scalar> Optimizer<T> for Sgd {
+    fn step(&mut self, network: &mut Network<T>, rate: T, total_weight: T) -> Result<(), NetworkError> {
+        network.apply_gradients(-rate / total_weight);
+        Ok(())
+    }
+}
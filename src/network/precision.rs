@@ -0,0 +1,101 @@
+//! The precision saved weights and biases are stored in, with the conversion between `f32` and IEEE 754
+//! half precision. Loading always gives `f32`s again.
+
+/// How `Network::save_with_precision` and `Network::to_safetensors_with_precision` store weights and biases.
+/// Half precision halves their size, a weight rounds to the nearest `f16`, so its relative error is at
+/// most 2^-11, or 2^-25 absolute for magnitudes below 2^-14 where `f16` has fewer bits.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Precision {
+    #[default]
+    F32,
+    F16,
+}
+
+impl Precision {
+    /// The bytes one value takes.
+    #[inline]
+    pub fn size(self) -> usize {
+        match self {
+            Precision::F32 => 4,
+            Precision::F16 => 2,
+        }
+    }
+
+    /// Appends `values` in little-endian.
+    #[cfg(feature = "std")]
+    pub(crate) fn write(self, values: impl Iterator<Item = f32>, bytes: &mut Vec<u8>) {
+        match self {
+            Precision::F32 => bytes.extend(values.flat_map(f32::to_le_bytes)),
+            Precision::F16 => bytes.extend(values.flat_map(|value| f32_to_f16(value).to_le_bytes())),
+        }
+    }
+
+    /// Reads little-endian values, `bytes.len()` has to be a multiple of `size`.
+    #[cfg(feature = "std")]
+    pub(crate) fn read(self, bytes: &[u8]) -> Vec<f32> {
+        match self {
+            Precision::F32 => bytes.chunks_exact(4).map(|bytes| f32::from_le_bytes(bytes.try_into().unwrap())).collect(),
+            Precision::F16 => bytes.chunks_exact(2).map(|bytes| f16_to_f32(u16::from_le_bytes(bytes.try_into().unwrap()))).collect(),
+        }
+    }
+}
+
+/// Shifts `value` right, rounding to nearest with ties to even.
+#[cfg(feature = "std")]
+fn round_shift(value: u32, shift: u32) -> u32 {
+    let result = value >> shift;
+    let remainder = value & ((1 << shift) - 1);
+    let halfway = 1 << (shift - 1);
+
+    if remainder > halfway || (remainder == halfway && result & 1 == 1) {
+        result + 1
+    } else {
+        result
+    }
+}
+
+/// The bits of the `f16` nearest to `value`. Values too large become infinite and NaNs stay NaN.
+#[cfg(feature = "std")]
+pub(crate) fn f32_to_f16(value: f32) -> u16 {
+    let bits = value.to_bits();
+    let sign = ((bits >> 16) & 0x8000) as u16;
+    let exponent = ((bits >> 23) & 0xff) as i32;
+    let mantissa = bits & 0x7f_ffff;
+
+    if exponent == 0xff {
+        return sign | 0x7c00 | if mantissa != 0 { 0x200 } else { 0 };
+    }
+
+    let exponent = exponent - 127 + 15;
+    if exponent >= 0x1f {
+        return sign | 0x7c00;
+    }
+
+    if exponent <= 0 {
+        if exponent < -10 {
+            return sign;
+        }
+
+        // Subnormal, a carry out of the mantissa correctly gives the smallest normal number.
+        return sign | round_shift(mantissa | 0x80_0000, (14 - exponent) as u32) as u16;
+    }
+
+    // A carry out of the mantissa increments the exponent, up to infinity.
+    sign | round_shift(((exponent as u32) << 23) | mantissa, 13) as u16
+}
+
+/// The value of the `f16` with the given bits, which `f32` represents exactly.
+#[cfg(feature = "std")]
+pub(crate) fn f16_to_f32(half: u16) -> f32 {
+    let sign = ((half & 0x8000) as u32) << 16;
+    let exponent = ((half >> 10) & 0x1f) as u32;
+    let mantissa = (half & 0x3ff) as u32;
+
+    let bits = match exponent {
+        0 => (mantissa as f32 * 2f32.powi(-24)).to_bits(),
+        0x1f => 0x7f80_0000 | (mantissa << 13),
+        _ => ((exponent + 112) << 23) | (mantissa << 13),
+    };
+
+    f32::from_bits(sign | bits)
+}
@@ -0,0 +1,87 @@
+use crate::{prelude::*, scalar::Scalar};
+
+use super::Network;
+
+/// What one `prune` call did to a layer.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LayerPruneReport {
+    /// Parameters that were set to zero by this call.
+    pub pruned: usize,
+    /// Parameters considered for pruning that are still non-zero.
+    pub remaining: usize,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PruneReport {
+    pub layers: Vec<LayerPruneReport>,
+    pub pruned: usize,
+    pub remaining: usize,
+}
+
+impl<T: Scalar> Network<T> {
+    /// Sets every weight of the dense layers with `|w| < threshold` to exactly zero, biases are left alone.
+    /// Other layers aren't pruned and report nothing pruned or remaining.
+    pub fn prune(&mut self, threshold: T) -> PruneReport {
+        self.prune_parameters(threshold, false)
+    }
+
+    /// Like `prune`, but biases below the threshold are zeroed too.
+    pub fn prune_with_biases(&mut self, threshold: T) -> PruneReport {
+        self.prune_parameters(threshold, true)
+    }
+
+    /// The fraction of weights and biases, or parameters of layers other than dense ones, that are exactly zero.
+    pub fn sparsity(&self) -> f32 {
+        let zeros: usize = self
+            .owned_layers()
+            .map(|layer| layer.flat_parameters().iter().filter(|x| x.is_zero()).count())
+            .sum();
+
+        zeros as f32 / self.parameter_count() as f32
+    }
+
+    fn prune_parameters(&mut self, threshold: T, biases: bool) -> PruneReport {
+        let prune = |parameters: &mut [T], report: &mut LayerPruneReport| {
+            for x in parameters.iter_mut() {
+                if x.is_zero() {
+                    continue;
+                }
+
+                if x.abs() < threshold {
+                    *x = T::zero();
+                    report.pruned += 1;
+                } else {
+                    report.remaining += 1;
+                }
+            }
+        };
+
+        let layers: Vec<LayerPruneReport> = self.layers
+            .iter_mut()
+            .zip(self.shared.iter())
+            .map(|(layer, shared)| {
+                let mut report = LayerPruneReport { pruned: 0, remaining: 0 };
+                // Layers sharing parameters get those pruned in the layer they share them with.
+                let (Some(layer), None) = (layer.as_dense_mut(), shared) else {
+                    return report;
+                };
+
+                prune(layer.weights_mut().as_mut_slice(), &mut report);
+
+                if biases && layer.has_bias() {
+                    prune(layer.biases_mut().as_mut_slice(), &mut report);
+                }
+
+                report
+            })
+            .collect();
+
+        self.sync_shared_parameters();
+
+        PruneReport {
+            pruned: layers.iter().map(|layer| layer.pruned).sum(),
+            remaining: layers.iter().map(|layer| layer.remaining).sum(),
+            layers,
+        }
+    }
+}
@@ -0,0 +1,195 @@
+//! Post-training int8 quantization for inference. Every row of a layer's weights, the weights of one output,
+//! is quantized symmetrically with its own scale, `w ≈ scale * q` with `q` in `-127..=127`, which keeps
+//! rows of small weights precise next to rows of large ones. Biases stay `f32`, they're a small part of the
+//! parameters.
+
+use nalgebra::{DMatrix, DVector, DVectorView};
+
+use crate::{activations::ActivationFn, dataset::{argmax, Sample}, prelude::*};
+
+use super::{layer::{Layer, LayerError}, Network, NetworkError};
+
+/// A dense layer with int8 weights, see `Network::quantize`. Batch normalization of the layer it was
+/// quantized from is folded into the weights and biases before quantizing.
+#[derive(Clone)]
+pub struct QuantizedLayer {
+    weights: DMatrix<i8>,
+    scales: DVector<f32>,
+    biases: DVector<f32>,
+    activation_fn: Box<dyn ActivationFn>,
+}
+
+impl core::fmt::Debug for QuantizedLayer {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("QuantizedLayer")
+            .field("input_size", &self.input_size())
+            .field("output_size", &self.output_size())
+            .field("activation_fn", &self.activation_fn.name())
+            .finish()
+    }
+}
+
+/// A network of `QuantizedLayer`s for inference only.
+#[derive(Clone, Debug)]
+pub struct QuantizedNetwork {
+    layers: Vec<QuantizedLayer>,
+}
+
+/// How far a quantized network's outputs are from those of the network it was quantized from.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct QuantizationReport {
+    pub max_abs_error: f32,
+    /// The mean over every output of every sample.
+    pub mean_abs_error: f32,
+    /// The fraction of samples whose largest output is the same in both, i.e. whose predicted class agrees.
+    pub argmax_agreement: f32,
+}
+
+impl QuantizedLayer {
+    fn quantize(layer: &Layer) -> Self {
+        let mut weights = layer.weights().into_owned();
+        let mut biases = layer.biases().into_owned();
+
+        if let Some(batch_norm) = layer.batch_norm() {
+            batch_norm.fold(&mut weights, &mut biases);
+        }
+
+        let scales = DVector::from_iterator(
+            weights.nrows(),
+            weights.row_iter().map(|row| row.amax() / i8::MAX as f32),
+        );
+
+        let quantized = DMatrix::from_fn(weights.nrows(), weights.ncols(), |row, col| {
+            match scales[row] {
+                0.0 => 0,
+                scale => (weights[(row, col)] / scale).round().clamp(-127.0, 127.0) as i8,
+            }
+        });
+
+        Self {
+            weights: quantized,
+            scales,
+            biases,
+            activation_fn: layer.activation_fn().clone_box(),
+        }
+    }
+
+    /// Accumulates the int8 weights times the inputs per output and rescales once at the end.
+    pub fn predict(&self, inputs: DVectorView<f32>) -> Result<DVector<f32>, LayerError> {
+        if inputs.len() != self.input_size() {
+            return Err(LayerError::InputSizeMismatch {
+                layer_input_size: self.input_size(),
+                given_input_size: inputs.len(),
+            });
+        }
+
+        let mut sums = DVector::zeros(self.output_size());
+        for (column, &input) in self.weights.column_iter().zip(inputs.iter()) {
+            for (sum, &weight) in sums.iter_mut().zip(column.iter()) {
+                *sum += weight as f32 * input;
+            }
+        }
+
+        let mut outputs = sums.component_mul(&self.scales) + &self.biases;
+        self.activation_fn.apply_slice(outputs.as_mut_slice());
+        Ok(outputs)
+    }
+
+    /// The weight `w[output, input]` the quantized one stands for.
+    pub fn dequantized_weight(&self, input: usize, output: usize) -> Option<f32> {
+        self.weights.get((output, input)).map(|&q| q as f32 * self.scales[output])
+    }
+
+    #[inline]
+    pub fn input_size(&self) -> usize { self.weights.ncols() }
+
+    #[inline]
+    pub fn output_size(&self) -> usize { self.weights.nrows() }
+
+    /// One row per output and one column per input.
+    #[inline]
+    pub fn weights(&self) -> &DMatrix<i8> { &self.weights }
+
+    /// The scale of every row of the weights.
+    #[inline]
+    pub fn scales(&self) -> DVectorView<'_, f32> { self.scales.as_view() }
+
+    #[inline]
+    pub fn biases(&self) -> DVectorView<'_, f32> { self.biases.as_view() }
+
+    /// The bytes the weights and their scales take, against 4 bytes per weight for `f32` weights.
+    pub fn weight_bytes(&self) -> usize {
+        self.weights.len() * size_of::<i8>() + self.scales.len() * size_of::<f32>()
+    }
+}
+
+impl QuantizedNetwork {
+    pub fn predict(&self, input: DVectorView<f32>) -> Result<DVector<f32>, NetworkError> {
+        let (first, rest) = self.layers.split_first().unwrap();
+        let outputs = first.predict(input)?;
+
+        rest.iter().try_fold(outputs, |activations, layer| {
+            layer.predict(activations.as_view()).map_err(Into::into)
+        })
+    }
+
+    /// Compares the predictions for the dataset's inputs with those of `network`, usually the network this
+    /// one was quantized from, to judge what quantizing cost.
+    pub fn compare(&self, network: &Network, dataset: &[Sample]) -> Result<QuantizationReport, NetworkError> {
+        if dataset.is_empty() {
+            return Err(NetworkError::EmptyDataset);
+        }
+
+        network.check_dataset(dataset)?;
+
+        let mut max_abs_error = 0.0f32;
+        let mut total_abs_error = 0.0;
+        let mut agreements = 0;
+
+        for sample in dataset {
+            let expected = network.predict(sample.inputs())?;
+            let outputs = self.predict(sample.inputs())?;
+
+            for (&x, &y) in outputs.iter().zip(expected.iter()) {
+                max_abs_error = max_abs_error.max((x - y).abs());
+                total_abs_error += (x - y).abs();
+            }
+
+            if argmax(outputs.as_view()) == argmax(expected.as_view()) {
+                agreements += 1;
+            }
+        }
+
+        Ok(QuantizationReport {
+            max_abs_error,
+            mean_abs_error: total_abs_error / (dataset.len() * network.output_size()) as f32,
+            argmax_agreement: agreements as f32 / dataset.len() as f32,
+        })
+    }
+
+    #[inline]
+    pub fn layers(&self) -> &[QuantizedLayer] { &self.layers }
+
+    #[inline]
+    pub fn input_size(&self) -> usize { self.layers.first().unwrap().input_size() }
+
+    #[inline]
+    pub fn output_size(&self) -> usize { self.layers.last().unwrap().output_size() }
+
+    /// The bytes the weights of every layer take, see `QuantizedLayer::weight_bytes`.
+    pub fn weight_bytes(&self) -> usize {
+        self.layers.iter().map(QuantizedLayer::weight_bytes).sum()
+    }
+}
+
+impl Network {
+    /// Quantizes every layer's weights to int8 for inference, see `QuantizedNetwork::compare` for what
+    /// that costs in accuracy. Every layer has to be a dense layer.
+    pub fn quantize(&self) -> Result<QuantizedNetwork, NetworkError> {
+        self.check_dense()?;
+
+        Ok(QuantizedNetwork {
+            layers: self.dense_layers().map(QuantizedLayer::quantize).collect(),
+        })
+    }
+}
@@ -0,0 +1,537 @@
+//! Elman recurrence: `h[t] = f(input_weights * x[t] + recurrent_weights * h[t - 1] + biases)` with
+//! `h[-1] = 0`, so every sequence starts from a zero state. `RecurrentNetwork` reads an output from every
+//! hidden state of a `SequenceLayer` with a dense layer and trains on `SequenceSample`s with
+//! backpropagation through time, or on long sequences chunk by chunk with `learn_chunked`.
+
+use core::ops::Range;
+
+use nalgebra::{DMatrix, DMatrixView, DVector, DVectorView};
+use rand::{distr::Distribution, Rng};
+
+use crate::{activations::ActivationFn, dataset::SequenceSample, losses::LossFn, prelude::*, scalar::Scalar};
+
+use super::{
+    layer::{Layer, LayerError},
+    NetworkError,
+};
+
+/// A layer that carries a hidden state through a sequence, like `RecurrentLayer` and `GruLayer`.
+/// It has the training lifecycle of `Layer`, with whole sequences in place of single inputs.
+pub trait SequenceLayer<T: Scalar = f32> {
+    /// Runs a sequence through the layer from a zero state, returning the hidden state after every step.
+    /// The whole sequence is recorded for `backpropagate_through_time`, replacing the previous one.
+    fn forward(&mut self, inputs: &[DVector<T>]) -> Result<Vec<DVector<T>>, LayerError> {
+        self.forward_from(inputs, &DVector::zeros(self.hidden_size()))
+    }
+
+    /// `forward` starting from the given hidden state instead of a zero one, e.g. the last state of the previous
+    /// chunk of a longer sequence. The state is treated as a constant, so no gradient flows back into it.
+    fn forward_from(&mut self, inputs: &[DVector<T>], state: &DVector<T>) -> Result<Vec<DVector<T>>, LayerError>;
+
+    /// `forward` that leaves the layer untouched.
+    fn predict(&self, inputs: &[DVector<T>]) -> Result<Vec<DVector<T>>, LayerError>;
+
+    /// Accumulates the gradient of `step` of the last `forward` given the gradient of the state it
+    /// returned, adds the step's input gradient to `input_gradient` and returns the gradient of the state before it.
+    fn step_back(&mut self, step: usize, state_gradient: &DVector<T>, input_gradient: &mut DVector<T>) -> DVector<T>;
+
+    fn apply_gradient(&mut self, scale: T);
+
+    fn zero_gradient(&mut self);
+
+    fn input_size(&self) -> usize;
+
+    fn hidden_size(&self) -> usize;
+
+    /// Accumulates the gradient for the sequence of the last `forward`, given the gradient of the loss with
+    /// respect to every hidden state it returned, and returns the gradient with respect to every input.
+    /// With a `truncation` of `k`, the gradient of each step's state flows back through at most `k` steps.
+    fn backpropagate_through_time(&mut self, state_gradients: &[DVector<T>], truncation: Option<usize>) -> Vec<DVector<T>> {
+        let steps = state_gradients.len();
+        let mut input_gradients = vec![DVector::zeros(self.input_size()); steps];
+
+        match truncation {
+            Some(truncation) if truncation < steps => {
+                for (t, state_gradient) in state_gradients.iter().enumerate() {
+                    let mut gradient = state_gradient.clone();
+                    for step in (t.saturating_sub(truncation - 1)..=t).rev() {
+                        gradient = self.step_back(step, &gradient, &mut input_gradients[step]);
+                    }
+                }
+            }
+            _ => {
+                let mut carried = DVector::zeros(self.hidden_size());
+                for step in (0..steps).rev() {
+                    carried = self.step_back(step, &(&state_gradients[step] + &carried), &mut input_gradients[step]);
+                }
+            }
+        }
+
+        input_gradients
+    }
+}
+
+/// Cloning copies everything, including accumulated gradients and the sequence recorded by the last `forward`.
+#[derive(Clone)]
+pub struct RecurrentLayer<T: Scalar = f32> {
+    input_weights: DMatrix<T>,
+    input_weight_gradient: DMatrix<T>,
+    recurrent_weights: DMatrix<T>,
+    recurrent_weight_gradient: DMatrix<T>,
+    biases: DVector<T>,
+    bias_gradient: DVector<T>,
+    activation_fn: Box<dyn ActivationFn<T>>,
+
+    previous_inputs: Vec<DVector<T>>,
+    previous_weighted_sums: Vec<DVector<T>>,
+    /// The hidden state before every step of the last `forward`, then the final one.
+    previous_states: Vec<DVector<T>>,
+}
+
+impl<T: Scalar> core::fmt::Debug for RecurrentLayer<T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("RecurrentLayer")
+            .field("input_size", &self.input_size())
+            .field("hidden_size", &self.hidden_size())
+            .field("activation_fn", &self.activation_fn.name())
+            .finish()
+    }
+}
+
+impl<T: Scalar> RecurrentLayer<T> {
+    pub fn zeros(input_size: usize, hidden_size: usize, activation_fn: Box<dyn ActivationFn<T>>) -> Result<Self, LayerError> {
+        if input_size == 0 {
+            return Err(LayerError::ZeroInputSize);
+        }
+
+        if hidden_size == 0 {
+            return Err(LayerError::ZeroOutputSize);
+        }
+
+        Ok(Self {
+            input_weights: DMatrix::zeros(hidden_size, input_size),
+            input_weight_gradient: DMatrix::zeros(hidden_size, input_size),
+            recurrent_weights: DMatrix::zeros(hidden_size, hidden_size),
+            recurrent_weight_gradient: DMatrix::zeros(hidden_size, hidden_size),
+            biases: DVector::zeros(hidden_size),
+            bias_gradient: DVector::zeros(hidden_size),
+            activation_fn,
+
+            previous_inputs: Vec::new(),
+            previous_weighted_sums: Vec::new(),
+            previous_states: Vec::new(),
+        })
+    }
+
+    /// Draws from the thread-local RNG, see `random_with_rng` for reproducible layers.
+    #[cfg(feature = "std")]
+    pub fn random(
+        input_size: usize,
+        hidden_size: usize,
+        activation_fn: Box<dyn ActivationFn<T>>,
+        distribution: &impl Distribution<T>,
+    ) -> Result<Self, LayerError> {
+        Self::random_with_rng(input_size, hidden_size, activation_fn, distribution, &mut rand::rng())
+    }
+
+    /// Like `random`, but draws every weight and bias from the given RNG.
+    pub fn random_with_rng(
+        input_size: usize,
+        hidden_size: usize,
+        activation_fn: Box<dyn ActivationFn<T>>,
+        distribution: &impl Distribution<T>,
+        rng: &mut impl Rng,
+    ) -> Result<Self, LayerError> {
+        let mut layer = Self::zeros(input_size, hidden_size, activation_fn)?;
+
+        layer.input_weights
+            .iter_mut()
+            .chain(layer.recurrent_weights.iter_mut())
+            .chain(layer.biases.iter_mut())
+            .for_each(|x| *x = distribution.sample(rng));
+
+        Ok(layer)
+    }
+
+    /// One row per hidden unit and one column per input.
+    #[inline]
+    pub fn input_weights(&self) -> DMatrixView<'_, T> { self.input_weights.as_view() }
+
+    /// One row per hidden unit and one column per unit of the previous state.
+    #[inline]
+    pub fn recurrent_weights(&self) -> DMatrixView<'_, T> { self.recurrent_weights.as_view() }
+
+    #[inline]
+    pub fn biases(&self) -> DVectorView<'_, T> { self.biases.as_view() }
+
+    #[inline]
+    pub fn input_weight_gradient(&self) -> DMatrixView<'_, T> { self.input_weight_gradient.as_view() }
+
+    #[inline]
+    pub fn recurrent_weight_gradient(&self) -> DMatrixView<'_, T> { self.recurrent_weight_gradient.as_view() }
+
+    #[inline]
+    pub fn bias_gradient(&self) -> DVectorView<'_, T> { self.bias_gradient.as_view() }
+
+    pub fn set_input_weights(&mut self, weights: DMatrix<T>) -> Result<(), LayerError> {
+        check_shape(&self.input_weights, &weights)?;
+        self.input_weights = weights;
+        Ok(())
+    }
+
+    pub fn set_recurrent_weights(&mut self, weights: DMatrix<T>) -> Result<(), LayerError> {
+        check_shape(&self.recurrent_weights, &weights)?;
+        self.recurrent_weights = weights;
+        Ok(())
+    }
+
+    pub fn set_biases(&mut self, biases: DVector<T>) -> Result<(), LayerError> {
+        if biases.len() != self.hidden_size() {
+            return Err(LayerError::BiasSizeMismatch {
+                layer_output_size: self.hidden_size(),
+                given_size: biases.len(),
+            });
+        }
+
+        self.biases = biases;
+        Ok(())
+    }
+
+    fn step(&self, input: DVectorView<T>, state: &DVector<T>) -> (DVector<T>, DVector<T>) {
+        let mut weighted_sums = &self.biases + &self.input_weights * input;
+        weighted_sums.gemv(T::one(), &self.recurrent_weights, state, T::one());
+
+        let mut activations = weighted_sums.clone();
+        self.activation_fn.apply_slice(activations.as_mut_slice());
+        (weighted_sums, activations)
+    }
+
+    fn check_inputs(&self, inputs: &[DVector<T>]) -> Result<(), LayerError> {
+        match inputs.iter().find(|input| input.len() != self.input_size()) {
+            Some(input) => Err(LayerError::InputSizeMismatch {
+                layer_input_size: self.input_size(),
+                given_input_size: input.len(),
+            }),
+            None => Ok(()),
+        }
+    }
+}
+
+impl<T: Scalar> SequenceLayer<T> for RecurrentLayer<T> {
+    fn forward_from(&mut self, inputs: &[DVector<T>], state: &DVector<T>) -> Result<Vec<DVector<T>>, LayerError> {
+        self.check_inputs(inputs)?;
+        check_state(state, self.hidden_size())?;
+        self.previous_inputs = inputs.to_vec();
+        self.previous_weighted_sums.clear();
+        self.previous_states = vec![state.clone()];
+
+        for input in inputs {
+            let (weighted_sums, state) = self.step(input.as_view(), self.previous_states.last().unwrap());
+            self.previous_weighted_sums.push(weighted_sums);
+            self.previous_states.push(state);
+        }
+
+        Ok(self.previous_states[1..].to_vec())
+    }
+
+    fn predict(&self, inputs: &[DVector<T>]) -> Result<Vec<DVector<T>>, LayerError> {
+        self.check_inputs(inputs)?;
+        let mut state = DVector::zeros(self.hidden_size());
+
+        Ok(inputs
+            .iter()
+            .map(|input| {
+                state = self.step(input.as_view(), &state).1;
+                state.clone()
+            })
+            .collect())
+    }
+
+    fn step_back(&mut self, step: usize, state_gradient: &DVector<T>, input_gradient: &mut DVector<T>) -> DVector<T> {
+        let mut deltas = DVector::zeros(self.hidden_size());
+        self.activation_fn.derivative_slice(
+            self.previous_weighted_sums[step].as_slice(),
+            self.previous_states[step + 1].as_slice(),
+            deltas.as_mut_slice(),
+        );
+        deltas.component_mul_assign(state_gradient);
+
+        self.bias_gradient += &deltas;
+        self.input_weight_gradient.ger(T::one(), &deltas, &self.previous_inputs[step], T::one());
+        self.recurrent_weight_gradient.ger(T::one(), &deltas, &self.previous_states[step], T::one());
+
+        *input_gradient += self.input_weights.tr_mul(&deltas);
+        self.recurrent_weights.tr_mul(&deltas)
+    }
+
+    fn apply_gradient(&mut self, scale: T) {
+        self.input_weights += &self.input_weight_gradient * scale;
+        self.recurrent_weights += &self.recurrent_weight_gradient * scale;
+        self.biases += &self.bias_gradient * scale;
+        self.zero_gradient();
+    }
+
+    fn zero_gradient(&mut self) {
+        self.input_weight_gradient.fill(T::zero());
+        self.recurrent_weight_gradient.fill(T::zero());
+        self.bias_gradient.fill(T::zero());
+    }
+
+    #[inline]
+    fn input_size(&self) -> usize { self.input_weights.ncols() }
+
+    #[inline]
+    fn hidden_size(&self) -> usize { self.input_weights.nrows() }
+}
+
+pub(crate) fn check_state<T: Scalar>(state: &DVector<T>, hidden_size: usize) -> Result<(), LayerError> {
+    if state.len() != hidden_size {
+        return Err(LayerError::StateSizeMismatch {
+            hidden_size,
+            given_size: state.len(),
+        });
+    }
+
+    Ok(())
+}
+
+pub(crate) fn check_shape<T: Scalar>(current: &DMatrix<T>, given: &DMatrix<T>) -> Result<(), LayerError> {
+    if current.shape() != given.shape() {
+        return Err(LayerError::ParameterShapeMismatch {
+            layer_input_size: current.ncols(),
+            layer_output_size: current.nrows(),
+            given_input_size: given.ncols(),
+            given_output_size: given.nrows(),
+        });
+    }
+
+    Ok(())
+}
+
+/// A `SequenceLayer` followed by a dense output layer that is applied to the hidden state after every step.
+#[derive(Clone, Debug)]
+pub struct RecurrentNetwork<T: Scalar = f32, R: SequenceLayer<T> = RecurrentLayer<T>> {
+    recurrent: R,
+    output: Layer<T>,
+    truncation: Option<usize>,
+}
+
+impl<T: Scalar, R: SequenceLayer<T>> RecurrentNetwork<T, R> {
+    /// The output layer has to take the recurrent layer's hidden state as its input. Its batch
+    /// normalization, if any, is skipped, since sequences are trained one step at a time.
+    pub fn new(recurrent: R, output: Layer<T>) -> Result<Self, NetworkError> {
+        if output.input_size() != recurrent.hidden_size() {
+            return Err(NetworkError::LayerChainMismatch {
+                layer: 0,
+                next_layer: 1,
+                output_size: recurrent.hidden_size(),
+                input_size: output.input_size(),
+            });
+        }
+
+        Ok(Self {
+            recurrent,
+            output,
+            truncation: None,
+        })
+    }
+
+    /// Limits backpropagation through time to `steps` steps back from every output, by default
+    /// gradients flow back to the start of the sequence.
+    pub fn truncation(mut self, steps: usize) -> Self {
+        self.truncation = Some(steps.max(1));
+        self
+    }
+
+    #[inline]
+    pub fn recurrent(&self) -> &R { &self.recurrent }
+
+    #[inline]
+    pub fn output(&self) -> &Layer<T> { &self.output }
+
+    #[inline]
+    pub fn input_size(&self) -> usize { self.recurrent.input_size() }
+
+    #[inline]
+    pub fn output_size(&self) -> usize { self.output.output_size() }
+
+    /// The output after every step of the sequence.
+    pub fn predict(&self, inputs: &[DVector<T>]) -> Result<Vec<DVector<T>>, NetworkError> {
+        self.recurrent
+            .predict(inputs)?
+            .iter()
+            .map(|state| self.output.predict(state.as_view()).map_err(Into::into))
+            .collect()
+    }
+
+    /// Checks that every sequence has one expected output per step and that all of them fit the network.
+    pub fn check_dataset(&self, dataset: &[SequenceSample<T>]) -> Result<(), NetworkError> {
+        let (network_inputs, network_outputs) = (self.input_size(), self.output_size());
+
+        for (index, sample) in dataset.iter().enumerate() {
+            if sample.inputs().len() != sample.expected_outputs().len() {
+                return Err(NetworkError::SequenceLengthMismatch {
+                    index,
+                    inputs: sample.inputs().len(),
+                    outputs: sample.expected_outputs().len(),
+                });
+            }
+
+            let mismatch = sample
+                .inputs()
+                .iter()
+                .zip(sample.expected_outputs())
+                .find(|(input, output)| input.len() != network_inputs || output.len() != network_outputs);
+
+            if let Some((input, output)) = mismatch {
+                return Err(NetworkError::SampleSizeMismatch {
+                    index,
+                    inputs: input.len(),
+                    outputs: output.len(),
+                    network_inputs,
+                    network_outputs,
+                });
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Accumulates the gradient of the loss summed over every step of every sequence and returns that sum.
+    pub fn backpropagate(&mut self, dataset: &[SequenceSample<T>], loss: &impl LossFn<T>) -> Result<T, NetworkError> {
+        self.check_dataset(dataset)?;
+        let result = self.backpropagate_checked(dataset, loss);
+
+        if result.is_err() {
+            self.recurrent.zero_gradient();
+            self.output.zero_gradient();
+        }
+
+        result
+    }
+
+    fn backpropagate_checked(&mut self, dataset: &[SequenceSample<T>], loss: &impl LossFn<T>) -> Result<T, NetworkError> {
+        let mut total_loss = T::zero();
+
+        for sample in dataset {
+            let mut state = DVector::zeros(self.recurrent.hidden_size());
+            total_loss += self.backpropagate_chunk(sample, 0..sample.len(), &mut state, loss)?;
+        }
+
+        Ok(total_loss)
+    }
+
+    /// One gradient descent step over the dataset, returning the mean loss per step before the update.
+    pub fn learn(&mut self, dataset: &[SequenceSample<T>], loss: &impl LossFn<T>, rate: T) -> Result<T, NetworkError> {
+        let steps: usize = dataset.iter().map(SequenceSample::len).sum();
+        let total_loss = self.backpropagate(dataset, loss)?;
+
+        if steps == 0 {
+            return Ok(T::zero());
+        }
+
+        let scale = -rate / T::from_count(steps);
+        self.recurrent.apply_gradient(scale);
+        self.output.apply_gradient(scale);
+
+        Ok(total_loss / T::from_count(steps))
+    }
+
+    /// Truncated backpropagation through time over chunks of `chunk_length` steps (at least 1), returning the
+    /// mean loss per step. Every sequence is split into chunks, the last one possibly shorter, and the `i`th
+    /// chunks of all sequences make up the `i`th gradient descent step. Each chunk starts from the state its
+    /// sequence ended the previous chunk in, but gradients stop at chunk boundaries, and `truncation` still
+    /// applies within a chunk. Sequences run out of chunks at different times, a sequence shorter than
+    /// `chunk_length` is one chunk. With a `chunk_length` of at least the longest sequence this is `learn`.
+    ///
+    /// On an error the gradients of the current chunk are discarded, but the steps before it have been taken.
+    pub fn learn_chunked(
+        &mut self,
+        dataset: &[SequenceSample<T>],
+        loss: &impl LossFn<T>,
+        rate: T,
+        chunk_length: usize,
+    ) -> Result<T, NetworkError> {
+        self.check_dataset(dataset)?;
+
+        let chunk_length = chunk_length.max(1);
+        let mut states = vec![DVector::zeros(self.recurrent.hidden_size()); dataset.len()];
+        let (mut total_loss, mut total_steps) = (T::zero(), 0);
+        let mut start = 0;
+
+        loop {
+            let mut steps = 0;
+            let mut chunk_loss = T::zero();
+
+            for (sample, state) in dataset.iter().zip(&mut states) {
+                let end = sample.len().min(start + chunk_length);
+                if start >= end {
+                    continue;
+                }
+
+                match self.backpropagate_chunk(sample, start..end, state, loss) {
+                    Ok(sample_loss) => chunk_loss += sample_loss,
+                    Err(error) => {
+                        self.recurrent.zero_gradient();
+                        self.output.zero_gradient();
+                        return Err(error);
+                    }
+                }
+
+                steps += end - start;
+            }
+
+            if steps == 0 {
+                break;
+            }
+
+            let scale = -rate / T::from_count(steps);
+            self.recurrent.apply_gradient(scale);
+            self.output.apply_gradient(scale);
+
+            total_loss += chunk_loss;
+            total_steps += steps;
+            start += chunk_length;
+        }
+
+        if total_steps == 0 {
+            return Ok(T::zero());
+        }
+
+        Ok(total_loss / T::from_count(total_steps))
+    }
+
+    /// Accumulates the gradient of the steps `range` of a sequence started from `state`, which is replaced
+    /// by the state after the chunk, and returns their summed loss.
+    fn backpropagate_chunk(
+        &mut self,
+        sample: &SequenceSample<T>,
+        range: Range<usize>,
+        state: &mut DVector<T>,
+        loss: &impl LossFn<T>,
+    ) -> Result<T, NetworkError> {
+        let states = self.recurrent.forward_from(&sample.inputs()[range.clone()], state)?;
+        let mut state_gradients = Vec::with_capacity(states.len());
+        let mut total_loss = T::zero();
+
+        for (state, expected) in states.iter().zip(&sample.expected_outputs()[range]) {
+            let (weighted_sums, outputs) = self.output.feed(state.as_view())?;
+            total_loss += loss.apply(outputs.as_view(), expected.as_view())?;
+
+            let output_gradient = loss.partial_gradient(outputs.as_view(), expected.as_view())?;
+            state_gradients.push(self.output.backpropagation_step_cached(
+                state.as_view(),
+                weighted_sums.as_view(),
+                outputs.as_view(),
+                output_gradient.as_view(),
+            ));
+        }
+
+        self.recurrent.backpropagate_through_time(&state_gradients, self.truncation);
+        if let Some(last) = states.last() {
+            *state = last.clone();
+        }
+
+        Ok(total_loss)
+    }
+}
@@ -0,0 +1,187 @@
+//! Residual blocks, which add their input to the output of a stack of layers, `y = x + f(x)`. The gradient
+//! with respect to `x` is the output gradient plus the one backpropagated through `f`, so it reaches the
+//! layers in front of the block undiminished however deep the stack is.
+
+use nalgebra::{DMatrix, DMatrixView, DVector, DVectorView};
+
+use crate::{prelude::*, scalar::Scalar};
+
+use super::{
+    gradients::LayerGradientNorm,
+    layer::{Layer, LayerError},
+    network_layer::{backpropagate_chain, forward_training_chain},
+    NetworkError,
+    NetworkLayer,
+    NonFiniteKind,
+};
+
+/// A stack of layers whose input is added to its output, for use as one layer of a `Network`.
+#[derive(Clone, Debug)]
+pub struct ResidualBlock<T: Scalar = f32> {
+    layers: Vec<Box<dyn NetworkLayer<T>>>,
+    /// The outputs of every layer of the stack for the last training batch.
+    previous_activations: Vec<DMatrix<T>>,
+}
+
+impl<T: Scalar> ResidualBlock<T> {
+    /// Fails if the layers don't chain or the stack's output size isn't its input size.
+    pub fn new(layers: Vec<Box<dyn NetworkLayer<T>>>) -> Result<Self, NetworkError> {
+        let (first, last) = match (layers.first(), layers.last()) {
+            (Some(first), Some(last)) => (first, last),
+            _ => return Err(NetworkError::NoLayers),
+        };
+
+        for (layer, pair) in layers.windows(2).enumerate() {
+            if pair[0].output_size() != pair[1].input_size() {
+                return Err(NetworkError::LayerChainMismatch {
+                    layer,
+                    next_layer: layer + 1,
+                    output_size: pair[0].output_size(),
+                    input_size: pair[1].input_size(),
+                });
+            }
+        }
+
+        if first.input_size() != last.output_size() {
+            return Err(NetworkError::ResidualSizeMismatch {
+                input_size: first.input_size(),
+                output_size: last.output_size(),
+            });
+        }
+
+        Ok(Self { layers, previous_activations: Vec::new() })
+    }
+
+    /// `new` for a stack of dense layers.
+    pub fn from_layers(layers: Vec<Layer<T>>) -> Result<Self, NetworkError> {
+        Self::new(layers.into_iter().map(|layer| Box::new(layer) as Box<dyn NetworkLayer<T>>).collect())
+    }
+
+    #[inline]
+    pub fn layers(&self) -> &[Box<dyn NetworkLayer<T>>] { &self.layers }
+
+    #[inline]
+    pub fn layer(&self, index: usize) -> Option<&dyn NetworkLayer<T>> { self.layers.get(index).map(AsRef::as_ref) }
+
+    #[inline]
+    pub fn layer_mut(&mut self, index: usize) -> Option<&mut dyn NetworkLayer<T>> { self.layers.get_mut(index).map(AsMut::as_mut) }
+
+    fn check_input_size(&self, input_size: usize) -> Result<(), LayerError> {
+        if input_size != self.input_size() {
+            return Err(LayerError::InputSizeMismatch {
+                layer_input_size: self.input_size(),
+                given_input_size: input_size,
+            });
+        }
+
+        Ok(())
+    }
+}
+
+impl<T: Scalar> NetworkLayer<T> for ResidualBlock<T> {
+    #[inline]
+    fn input_size(&self) -> usize { self.layers[0].input_size() }
+
+    #[inline]
+    fn output_size(&self) -> usize { self.input_size() }
+
+    fn forward(&mut self, inputs: DVectorView<T>) -> Result<DVector<T>, LayerError> {
+        self.check_input_size(inputs.len())?;
+        let outputs = self.layers
+            .iter_mut()
+            .try_fold(inputs.clone_owned(), |activations, layer| layer.forward(activations.as_view()))?;
+
+        Ok(outputs + inputs)
+    }
+
+    fn predict(&self, inputs: DVectorView<T>) -> Result<DVector<T>, LayerError> {
+        self.check_input_size(inputs.len())?;
+        let outputs = self.layers
+            .iter()
+            .try_fold(inputs.clone_owned(), |activations, layer| layer.predict(activations.as_view()))?;
+
+        Ok(outputs + inputs)
+    }
+
+    fn forward_batch(&self, inputs: &DMatrix<T>) -> Result<DMatrix<T>, LayerError> {
+        self.check_input_size(inputs.nrows())?;
+        let outputs = self.layers
+            .iter()
+            .try_fold(inputs.clone(), |activations, layer| layer.forward_batch(&activations))?;
+
+        Ok(outputs + inputs)
+    }
+
+    fn forward_training_batch(&mut self, inputs: DMatrixView<T>) -> Result<DMatrix<T>, LayerError> {
+        self.check_input_size(inputs.nrows())?;
+        self.previous_activations = forward_training_chain(&mut self.layers, inputs)?;
+
+        Ok(self.previous_activations.last().unwrap() + inputs)
+    }
+
+    fn backpropagation_step_batch(&mut self, inputs: DMatrixView<T>, output_partial_gradient: &DMatrix<T>) -> Result<DMatrix<T>, LayerError> {
+        let activations = core::mem::take(&mut self.previous_activations);
+        let stack_gradient = backpropagate_chain(&mut self.layers, inputs, &activations, output_partial_gradient.clone())?;
+
+        Ok(stack_gradient + output_partial_gradient)
+    }
+
+    fn apply_gradient(&mut self, scale: T) {
+        for layer in self.layers.iter_mut() {
+            layer.apply_gradient(scale);
+        }
+    }
+
+    fn zero_gradient(&mut self) {
+        for layer in self.layers.iter_mut() {
+            layer.zero_gradient();
+        }
+    }
+
+    fn boxed_clone(&self) -> Box<dyn NetworkLayer<T>> {
+        Box::new(self.clone())
+    }
+
+    fn kind(&self) -> &'static str { "residual" }
+
+    fn parameter_count(&self) -> usize {
+        self.layers.iter().map(|layer| layer.parameter_count()).sum()
+    }
+
+    /// The parameters of every layer of the stack in order.
+    fn write_parameters(&self, parameters: &mut Vec<T>) {
+        for layer in self.layers.iter() {
+            layer.write_parameters(parameters);
+        }
+    }
+
+    fn read_parameters(&mut self, parameters: &[T]) {
+        let mut rest = parameters;
+        for layer in self.layers.iter_mut() {
+            let (layer_parameters, tail) = rest.split_at(layer.parameter_count());
+            layer.read_parameters(layer_parameters);
+            rest = tail;
+        }
+    }
+
+    /// The weight and bias gradient norms of the whole stack.
+    fn gradient_norm(&self) -> LayerGradientNorm<T> {
+        let (weights, biases) = self.layers.iter().fold((T::zero(), T::zero()), |(weights, biases), layer| {
+            let norm = layer.gradient_norm();
+            (weights + norm.weights * norm.weights, biases + norm.biases * norm.biases)
+        });
+
+        LayerGradientNorm {
+            weights: weights.sqrt(),
+            biases: biases.sqrt(),
+        }
+    }
+
+    fn non_finite_update(&self, scale: T) -> Option<NonFiniteKind> {
+        self.layers.iter().find_map(|layer| layer.non_finite_update(scale))
+    }
+
+    fn is_finite(&self) -> bool {
+        self.layers.iter().all(|layer| layer.is_finite())
+    }
+}
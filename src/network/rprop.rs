@@ -0,0 +1,228 @@
+//! Resilient backpropagation (iRprop-): every parameter moves by its own step size in the direction
+//! opposite to the sign of its full-batch gradient, ignoring the gradient's magnitude. A step grows while
+//! the sign stays the same and shrinks when it flips, so no learning rate has to be tuned.
+
+use crate::{dataset::Sample, losses::LossFn, prelude::*, scalar::Scalar};
+
+use super::{optimizer::{Optimizer, OptimizerState}, total_weight, Network, NetworkError};
+
+/// The RProp state of a network: a step size and the previous gradient of every parameter, in the order of
+/// the dense layers' weights (column-major) and biases. It's sized on the first step and tied to that network.
+///
+/// RProp needs the gradient of the whole dataset, so it goes with the full-batch `backpropagate`, not
+/// with mini-batches. Only dense layers are supported. The scale and shift of batch normalization aren't
+/// updated, layers frozen with `set_trainable(false)` or a learning rate scale of 0 are skipped, and other
+/// learning rate scales multiply the steps.
+#[derive(Clone, Debug)]
+pub struct RProp<T: Scalar = f32> {
+    initial_step: T,
+    increase: T,
+    decrease: T,
+    min_step: T,
+    max_step: T,
+    steps: Vec<T>,
+    previous_gradients: Vec<T>,
+}
+
+impl<T: Scalar> Default for RProp<T> {
+    /// The usual settings: steps start at 0.1, grow by 1.2, shrink by 0.5 and stay within 1e-6..=50.
+    fn default() -> Self {
+        Self::new(T::constant(0.1), T::constant(1.2), T::constant(0.5), T::constant(1e-6), T::constant(50.0)).unwrap()
+    }
+}
+
+impl<T: Scalar> RProp<T> {
+    /// Fails with `InvalidRProp` unless `0 < decrease < 1 < increase` and `0 < min_step <= initial_step <= max_step`,
+    /// all of them finite.
+    pub fn new(initial_step: T, increase: T, decrease: T, min_step: T, max_step: T) -> Result<Self, NetworkError> {
+        let finite = [initial_step, increase, decrease, min_step, max_step].iter().all(|x| x.is_finite());
+
+        if !(finite
+            && decrease > T::zero()
+            && decrease < T::one()
+            && increase > T::one()
+            && min_step > T::zero()
+            && min_step <= initial_step
+            && initial_step <= max_step)
+        {
+            return Err(NetworkError::InvalidRProp {
+                initial_step: initial_step.to_f64(),
+                increase: increase.to_f64(),
+                decrease: decrease.to_f64(),
+                min_step: min_step.to_f64(),
+                max_step: max_step.to_f64(),
+            });
+        }
+
+        Ok(Self {
+            initial_step,
+            increase,
+            decrease,
+            min_step,
+            max_step,
+            steps: Vec::new(),
+            previous_gradients: Vec::new(),
+        })
+    }
+
+    /// The current step size of every parameter, empty before the first step.
+    #[inline]
+    pub fn step_sizes(&self) -> &[T] {
+        &self.steps
+    }
+
+    /// Forgets the step sizes and previous gradients, e.g. to use the optimizer on another network.
+    pub fn reset(&mut self) {
+        self.steps.clear();
+        self.previous_gradients.clear();
+    }
+
+    /// One full-batch step: backpropagates the whole dataset like `learn` and updates the parameters with
+    /// `step`. Returns the weighted mean loss before the update.
+    pub fn learn(&mut self, network: &mut Network<T>, dataset: &[Sample<T>], loss: &impl LossFn<T>) -> Result<T, NetworkError> {
+        network.check_rprop_layers()?;
+
+        let total_weight = total_weight(dataset);
+        if dataset.is_empty() || total_weight == T::zero() {
+            network.check_dataset(dataset)?;
+            return Ok(T::zero());
+        }
+
+        let total_loss = network.backpropagate(dataset, loss)?;
+        self.step(network)?;
+
+        Ok(total_loss / total_weight)
+    }
+
+    /// Updates the parameters with the gradient the network has accumulated, which only matters by its signs,
+    /// and zeroes it. A parameter whose gradient changed its sign since the last step shrinks its step and
+    /// waits for the next one, otherwise its step grows if the sign stayed the same and the parameter moves.
+    /// Fails with `NotDense` if the network has another kind of layer with parameters, and with
+    /// `ParameterCountMismatch` if it isn't the network of the earlier steps, both before anything changes.
+    pub fn step(&mut self, network: &mut Network<T>) -> Result<(), NetworkError> {
+        network.check_rprop_layers()?;
+
+        let count = network.parameter_count();
+        if self.steps.is_empty() {
+            self.steps = vec![self.initial_step; count];
+            self.previous_gradients = vec![T::zero(); count];
+        } else if self.steps.len() != count {
+            return Err(NetworkError::ParameterCountMismatch {
+                expected: self.steps.len(),
+                given: count,
+            });
+        }
+
+        network.merge_shared_gradients();
+
+        let mut index = 0;
+        for ((layer, &lr_scale), shared) in network.layers.iter_mut().zip(network.lr_scales.iter()).zip(network.shared.iter()) {
+            // Layers without parameters, e.g. softmax, have nothing to update, and layers sharing parameters
+            // are updated through the layer they share them with.
+            let Some(layer) = layer.as_dense_mut() else {
+                continue;
+            };
+
+            if shared.is_some() {
+                layer.zero_gradient();
+                continue;
+            }
+
+            let size = (layer.input_size() + usize::from(layer.has_bias())) * layer.output_size();
+            let (steps, previous_gradients) = (&mut self.steps[index..index + size], &mut self.previous_gradients[index..index + size]);
+            index += size;
+
+            if lr_scale == T::zero() || !layer.is_trainable() {
+                layer.zero_gradient();
+                continue;
+            }
+
+            for ((parameter, gradient), (step, previous_gradient)) in layer
+                .parameters_with_gradients_mut()
+                .zip(steps.iter_mut().zip(previous_gradients.iter_mut()))
+            {
+                let agreement = gradient * *previous_gradient;
+
+                if agreement < T::zero() {
+                    *step = (*step * self.decrease).max(self.min_step);
+                    *previous_gradient = T::zero();
+                    continue;
+                }
+
+                if agreement > T::zero() {
+                    *step = (*step * self.increase).min(self.max_step);
+                }
+
+                *parameter -= sign(gradient) * *step * lr_scale;
+                *previous_gradient = gradient;
+            }
+
+            if let Some(constraint) = layer.weight_constraint() {
+                constraint.apply(layer.weights_mut());
+            }
+
+            layer.zero_gradient();
+        }
+
+        network.sync_shared_parameters();
+        Ok(())
+    }
+}
+
+fn sign<T: Scalar>(x: T) -> T {
+    if x > T::zero() {
+        T::one()
+    } else if x < T::zero() {
+        -T::one()
+    } else {
+        T::zero()
+    }
+}
+
+impl<T: Scalar> Network<T> {
+    /// Whether every layer with parameters is a dense layer, as `RProp` needs.
+    fn check_rprop_layers(&self) -> Result<(), NetworkError> {
+        match self.layers.iter().position(|layer| layer.as_dense().is_none() && layer.parameter_count() > 0) {
+            Some(layer) => Err(NetworkError::NotDense { layer }),
+            None => Ok(()),
+        }
+    }
+}
+
+/// RProp as the optimizer of `learn_with_optimizer` or a `Trainer`, ignoring the rate and the samples' weight,
+/// since only the gradient's signs matter. Best used with full batches.
+impl<T: Scalar> Optimizer<T> for RProp<T> {
+    fn step(&mut self, network: &mut Network<T>, _rate: T, _total_weight: T) -> Result<(), NetworkError> {
+        RProp::step(self, network)
+    }
+
+    fn reset(&mut self) {
+        RProp::reset(self);
+    }
+
+    /// The step sizes and previous gradients, both empty before the first step.
+    fn save_state(&self) -> OptimizerState<T> {
+        let mut state = OptimizerState::new("rprop");
+        state.buffers.push(("steps".to_string(), self.steps.clone()));
+        state.buffers.push(("previous_gradients".to_string(), self.previous_gradients.clone()));
+        state
+    }
+
+    fn load_state(&mut self, state: &OptimizerState<T>, network: &Network<T>) -> Result<(), NetworkError> {
+        state.check_kind("rprop")?;
+        let steps = state.buffer("steps", network)?;
+        let previous_gradients = state.buffer("previous_gradients", network)?;
+
+        if steps.len() != previous_gradients.len() {
+            return Err(NetworkError::OptimizerStateMismatch {
+                buffer: "previous_gradients".to_string(),
+                expected: steps.len(),
+                found: previous_gradients.len(),
+            });
+        }
+
+        self.steps = steps.to_vec();
+        self.previous_gradients = previous_gradients.to_vec();
+        Ok(())
+    }
+}
@@ -0,0 +1,360 @@
+//! safetensors export and import of a network's parameters. Every layer is stored as the tensors
+//! `layer{i}.weight` shaped `[outputs, inputs]` and `layer{i}.bias` shaped `[outputs]`, the layout of
+//! PyTorch's `Linear`. A layer without biases has no bias tensor and is marked bias-free with a
+//! `"layer{i}.bias": "none"` entry in the header's `__metadata__`, a missing bias tensor without that mark
+//! fails to load with `MissingTensor`. Batch normalized layers add `layer{i}.batch_norm.gamma`, `.beta`,
+//! `.running_mean` and `.running_variance`, all shaped `[outputs]`. A maxout layer stores its first piece as
+//! the layer's weight and bias and every further piece `p` as `layer{i}.piece{p}.weight` and
+//! `layer{i}.piece{p}.bias`. Weights and biases can be stored as F16 tensors instead, batch normalization
+//! tensors are always F32. Loading takes either dtype for any tensor.
+
+use std::{fs, io, path::Path};
+
+use nalgebra::{DMatrix, DVector};
+use thiserror::Error;
+
+use crate::{
+    activations::ActivationFn,
+    json::{JsonError, Value},
+};
+
+use super::{
+    batch_norm::BatchNorm,
+    layer::Layer,
+    maxout::MaxoutLayer,
+    precision::Precision,
+    Network,
+    NetworkError,
+    NetworkLayer,
+};
+
+const BATCH_NORM_TENSORS: [&str; 4] = ["gamma", "beta", "running_mean", "running_variance"];
+
+/// The `__metadata__` value marking a layer's bias tensor as left out on purpose.
+const NO_BIAS: &str = "none";
+
+#[derive(Debug, Error)]
+pub enum SafetensorsError {
+    #[error("{0}")]
+    Io(#[from] io::Error),
+
+    #[error("{0}")]
+    Json(#[from] JsonError),
+
+    #[error("{0}")]
+    NetworkError(#[from] NetworkError),
+
+    #[error("the file is truncated or its header is malformed")]
+    InvalidHeader,
+
+    #[error("the tensor {0:?} is missing")]
+    MissingTensor(String),
+
+    #[error("the tensor {0:?} isn't part of the network")]
+    UnexpectedTensor(String),
+
+    #[error("the tensor {name:?} has the dtype {dtype}, but only F32 and F16 are supported")]
+    UnsupportedDtype {
+        name: String,
+        dtype: String,
+    },
+
+    #[error("the tensor {name:?} has the shape {found:?}, but {expected:?} was expected")]
+    ShapeMismatch {
+        name: String,
+        expected: Vec<usize>,
+        found: Vec<usize>,
+    },
+
+    #[error("the data of tensor {0:?} lies outside the file")]
+    InvalidDataOffsets(String),
+}
+
+struct Tensor {
+    shape: Vec<usize>,
+    data: Vec<f32>,
+}
+
+impl Network {
+    pub fn save_safetensors(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        self.save_safetensors_with_precision(path, Precision::F32)
+    }
+
+    /// `save_safetensors` storing the weights and biases in the given precision.
+    pub fn save_safetensors_with_precision(&self, path: impl AsRef<Path>, precision: Precision) -> io::Result<()> {
+        let bytes = self
+            .to_safetensors_with_precision(precision)
+            .map_err(|error| io::Error::new(io::ErrorKind::InvalidInput, error))?;
+        fs::write(path, bytes)
+    }
+
+    /// Fails with `NotDense` if the network has layers other than dense and maxout ones.
+    pub fn to_safetensors(&self) -> Result<Vec<u8>, NetworkError> {
+        self.to_safetensors_with_precision(Precision::F32)
+    }
+
+    /// `to_safetensors` storing the weights and biases in the given precision.
+    pub fn to_safetensors_with_precision(&self, precision: Precision) -> Result<Vec<u8>, NetworkError> {
+        let mut tensors: Vec<(String, Vec<usize>, Precision, Vec<f32>)> = Vec::new();
+        let mut metadata = Vec::new();
+
+        for (i, record) in self.to_records()?.into_iter().enumerate() {
+            let output_size = record.weights.nrows();
+
+            tensors.push((
+                format!("layer{i}.weight"),
+                vec![output_size, record.weights.ncols()],
+                precision,
+                record.weights.transpose().as_slice().to_vec(),
+            ));
+            match record.biases {
+                Some(biases) => tensors.push((format!("layer{i}.bias"), vec![output_size], precision, biases.as_slice().to_vec())),
+                None => metadata.push((format!("layer{i}.bias"), Value::String(NO_BIAS.to_string()))),
+            }
+
+            for (p, (weights, biases)) in record.maxout_pieces.iter().enumerate() {
+                let p = p + 1;
+                tensors.push((
+                    format!("layer{i}.piece{p}.weight"),
+                    vec![output_size, weights.ncols()],
+                    precision,
+                    weights.transpose().as_slice().to_vec(),
+                ));
+                tensors.push((format!("layer{i}.piece{p}.bias"), vec![output_size], precision, biases.as_slice().to_vec()));
+            }
+
+            if let Some(batch_norm) = record.batch_norm {
+                let vectors = [batch_norm.gamma, batch_norm.beta, batch_norm.running_mean, batch_norm.running_variance];
+
+                for (name, vector) in BATCH_NORM_TENSORS.iter().zip(vectors) {
+                    tensors.push((
+                        format!("layer{i}.batch_norm.{name}"),
+                        vec![output_size],
+                        Precision::F32,
+                        vector.as_slice().to_vec(),
+                    ));
+                }
+            }
+        }
+
+        let mut header = Vec::new();
+        if !metadata.is_empty() {
+            header.push(("__metadata__".to_string(), Value::Object(metadata)));
+        }
+
+        let mut offset = 0;
+
+        for (name, shape, precision, data) in tensors.iter() {
+            let end = offset + data.len() * precision.size();
+
+            header.push((name.clone(), Value::object([
+                ("dtype", Value::String(dtype_name(*precision).to_string())),
+                ("shape", Value::Array(shape.iter().map(|&dim| Value::Number(dim as f64)).collect())),
+                ("data_offsets", Value::Array(vec![Value::Number(offset as f64), Value::Number(end as f64)])),
+            ])));
+
+            offset = end;
+        }
+
+        let mut header = Value::Object(header).to_string().into_bytes();
+        header.resize(header.len().next_multiple_of(8), b' ');
+
+        let mut bytes = Vec::with_capacity(8 + header.len() + offset);
+        bytes.extend_from_slice(&(header.len() as u64).to_le_bytes());
+        bytes.extend_from_slice(&header);
+
+        for (_, _, precision, data) in tensors.into_iter() {
+            precision.write(data.into_iter(), &mut bytes);
+        }
+
+        Ok(bytes)
+    }
+
+    /// Loads parameters saved with `save_safetensors` into a network with the given layer sizes, every
+    /// layer using `activation_fn`. Batch normalization is enabled for the layers that have its tensors, the
+    /// layers marked bias-free have no biases, and the layers with piece tensors are maxout layers.
+    pub fn load_safetensors(
+        path: impl AsRef<Path>,
+        layer_sizes: &[usize],
+        activation_fn: Box<dyn ActivationFn>,
+    ) -> Result<Self, SafetensorsError> {
+        Self::from_safetensors(&fs::read(path)?, layer_sizes, activation_fn)
+    }
+
+    pub fn from_safetensors(
+        bytes: &[u8],
+        layer_sizes: &[usize],
+        activation_fn: Box<dyn ActivationFn>,
+    ) -> Result<Self, SafetensorsError> {
+        super::check_layer_sizes(layer_sizes)?;
+
+        let (mut tensors, metadata) = read_tensors(bytes)?;
+        let mut take = |name: String, shape: &[usize]| -> Result<Option<Vec<f32>>, SafetensorsError> {
+            let Some(index) = tensors.iter().position(|(tensor_name, _)| *tensor_name == name) else {
+                return Ok(None);
+            };
+
+            let (_, tensor) = tensors.swap_remove(index);
+            if tensor.shape != shape {
+                return Err(SafetensorsError::ShapeMismatch {
+                    name,
+                    expected: shape.to_vec(),
+                    found: tensor.shape,
+                });
+            }
+
+            Ok(Some(tensor.data))
+        };
+
+        let mut layers = Vec::with_capacity(layer_sizes.len() - 1);
+
+        for (i, sizes) in layer_sizes.windows(2).enumerate() {
+            let (input_size, output_size) = (sizes[0], sizes[1]);
+            let required = |name: String, data: Option<Vec<f32>>| data.ok_or(SafetensorsError::MissingTensor(name));
+
+            let name = format!("layer{i}.weight");
+            let weights = required(name.clone(), take(name, &[output_size, input_size])?)?;
+            let name = format!("layer{i}.bias");
+            let bias_free = metadata.iter().any(|(key, value)| *key == name && value == NO_BIAS);
+            let biases = match take(name.clone(), &[output_size])? {
+                Some(_) if bias_free => return Err(SafetensorsError::UnexpectedTensor(name)),
+                None if !bias_free => return Err(SafetensorsError::MissingTensor(name)),
+                biases => biases,
+            };
+
+            let mut pieces = Vec::new();
+            while let Some(weights) = take(format!("layer{i}.piece{}.weight", pieces.len() + 1), &[output_size, input_size])? {
+                let name = format!("layer{i}.piece{}.bias", pieces.len() + 1);
+                pieces.push((weights, required(name.clone(), take(name, &[output_size])?)?));
+            }
+
+            if !pieces.is_empty() {
+                // Batch normalization tensors of a maxout layer are left over and rejected below.
+                pieces.insert(0, (weights, required(format!("layer{i}.bias"), biases)?));
+                let maxout = MaxoutLayer::from_row_major_pieces(input_size, output_size, pieces).map_err(NetworkError::from)?;
+                layers.push(Box::new(maxout) as Box<dyn NetworkLayer>);
+                continue;
+            }
+
+            let mut batch_norm_vectors = Vec::new();
+            for tensor in BATCH_NORM_TENSORS {
+                let name = format!("layer{i}.batch_norm.{tensor}");
+                batch_norm_vectors.push((name.clone(), take(name, &[output_size])?));
+            }
+
+            let batch_norm = if batch_norm_vectors.iter().all(|(_, data)| data.is_none()) {
+                None
+            } else {
+                let mut vectors = Vec::with_capacity(4);
+                for (name, data) in batch_norm_vectors {
+                    vectors.push(DVector::from_vec(required(name, data)?));
+                }
+
+                let [gamma, beta, running_mean, running_variance] = vectors.try_into().unwrap();
+                let mut record = BatchNorm::new(output_size).to_record();
+                record.gamma = gamma;
+                record.beta = beta;
+                record.running_mean = running_mean;
+                record.running_variance = running_variance;
+                Some(BatchNorm::from_record(record))
+            };
+
+            layers.push(Box::new(Layer::from_parts(
+                DMatrix::from_row_slice(output_size, input_size, &weights),
+                biases.map(DVector::from_vec),
+                activation_fn.clone(),
+                batch_norm,
+            )));
+        }
+
+        if let Some((name, _)) = tensors.into_iter().next() {
+            return Err(SafetensorsError::UnexpectedTensor(name));
+        }
+
+        Ok(Self::from_network_layers(layers)?)
+    }
+}
+
+/// The string entries of a file's `__metadata__`.
+type Metadata = Vec<(String, String)>;
+
+/// The tensors of a file and its metadata.
+fn read_tensors(bytes: &[u8]) -> Result<(Vec<(String, Tensor)>, Metadata), SafetensorsError> {
+    let header_len = bytes
+        .get(..8)
+        .map(|len| u64::from_le_bytes(len.try_into().unwrap()) as usize)
+        .ok_or(SafetensorsError::InvalidHeader)?;
+
+    let header = bytes
+        .get(8..8usize.saturating_add(header_len))
+        .and_then(|header| std::str::from_utf8(header).ok())
+        .ok_or(SafetensorsError::InvalidHeader)?;
+
+    let data = &bytes[8 + header_len..];
+    let Value::Object(entries) = Value::parse(header)? else {
+        return Err(SafetensorsError::InvalidHeader);
+    };
+
+    let mut tensors = Vec::with_capacity(entries.len());
+    let mut metadata = Vec::new();
+
+    for (name, entry) in entries.iter() {
+        if name == "__metadata__" {
+            let Value::Object(fields) = entry else {
+                return Err(SafetensorsError::InvalidHeader);
+            };
+
+            for (key, value) in fields {
+                metadata.push((key.clone(), value.as_str(&format!("$.__metadata__.{key}"))?.to_string()));
+            }
+
+            continue;
+        }
+
+        let path = format!("$.{name}");
+
+        let dtype = entry.field(&path, "dtype")?.as_str(&format!("{path}.dtype"))?;
+        let precision = match dtype {
+            "F32" => Precision::F32,
+            "F16" => Precision::F16,
+            _ => return Err(SafetensorsError::UnsupportedDtype {
+                name: name.clone(),
+                dtype: dtype.to_string(),
+            }),
+        };
+
+        let shape = entry
+            .field(&path, "shape")?
+            .as_array(&format!("{path}.shape"))?
+            .iter()
+            .map(|dim| dim.as_usize(&format!("{path}.shape")))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let offsets = entry.field(&path, "data_offsets")?.as_array(&format!("{path}.data_offsets"))?;
+        let [start, end] = offsets else {
+            return Err(SafetensorsError::InvalidDataOffsets(name.clone()));
+        };
+
+        let (start, end) = (start.as_usize(&path)?, end.as_usize(&path)?);
+        // The shape comes from the file, so its size is checked for overflow.
+        let size = shape.iter().try_fold(precision.size(), |size, &dim| size.checked_mul(dim));
+        let tensor_bytes = data
+            .get(start..end)
+            .filter(|tensor_bytes| Some(tensor_bytes.len()) == size)
+            .ok_or_else(|| SafetensorsError::InvalidDataOffsets(name.clone()))?;
+
+        tensors.push((name.clone(), Tensor {
+            shape,
+            data: precision.read(tensor_bytes),
+        }));
+    }
+
+    Ok((tensors, metadata))
+}
+
+fn dtype_name(precision: Precision) -> &'static str {
+    match precision {
+        Precision::F32 => "F32",
+        Precision::F16 => "F16",
+    }
+}
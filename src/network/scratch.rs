@@ -0,0 +1,126 @@
+use nalgebra::{DVector, DVectorView};
+
+use crate::{losses::LossFn, prelude::*, scalar::Scalar};
+
+use super::{layer::LayerError, Network, NetworkError};
+
+/// Buffers for the weighted sums, activations and gradients of every layer, so `forward_into` and
+/// `learn_sample_with_scratch` don't allocate. Made by `Network::scratch` for one network and only usable
+/// with networks of the same layer sizes.
+#[derive(Clone, Debug)]
+pub struct NetworkScratch<T: Scalar = f32> {
+    weighted_sums: Vec<DVector<T>>,
+    activations: Vec<DVector<T>>,
+    /// The loss gradient with respect to every layer's activations.
+    gradients: Vec<DVector<T>>,
+    /// The loss gradient with respect to every layer's weighted sums.
+    deltas: Vec<DVector<T>>,
+}
+
+impl<T: Scalar> Network<T> {
+    pub fn scratch(&self) -> NetworkScratch<T> {
+        let buffers: Vec<DVector<T>> = self.layers.iter().map(|layer| DVector::zeros(layer.output_size())).collect();
+
+        NetworkScratch {
+            weighted_sums: buffers.clone(),
+            activations: buffers.clone(),
+            gradients: buffers.clone(),
+            deltas: buffers,
+        }
+    }
+
+    /// `predict` into the given buffers. Once `output` has the network's output size, which it's resized
+    /// to otherwise, no call allocates, unless the network has layers other than dense ones.
+    pub fn forward_into(
+        &self,
+        input: DVectorView<T>,
+        scratch: &mut NetworkScratch<T>,
+        output: &mut DVector<T>,
+    ) -> Result<(), NetworkError> {
+        self.check_scratch(scratch)?;
+
+        if input.len() != self.input_size() {
+            return Err(LayerError::InputSizeMismatch {
+                layer_input_size: self.input_size(),
+                given_input_size: input.len(),
+            }.into());
+        }
+
+        for (i, layer) in self.layers.iter().enumerate() {
+            let (previous, rest) = scratch.activations.split_at_mut(i);
+            let inputs = previous.last().map_or(input, |activations| activations.as_view());
+
+            match layer.as_dense() {
+                Some(dense) => dense.predict_into(inputs, &mut scratch.weighted_sums[i], &mut rest[0]),
+                None => rest[0].copy_from(&layer.predict(inputs)?),
+            }
+        }
+
+        if output.len() != self.output_size() {
+            *output = DVector::zeros(self.output_size());
+        }
+
+        output.copy_from(scratch.activations.last().unwrap());
+        Ok(())
+    }
+
+    /// `learn_sample` with the given buffers. Past the loss gradient, which `LossFn::partial_gradient`
+    /// returns as a new vector, no call allocates, unless the network has layers other than dense ones or
+    /// batch normalization, which take the batched path of `learn`.
+    pub fn learn_sample_with_scratch(
+        &mut self,
+        inputs: DVectorView<T>,
+        expected_outputs: DVectorView<T>,
+        loss: &impl LossFn<T>,
+        rate: T,
+        scratch: &mut NetworkScratch<T>,
+    ) -> Result<T, NetworkError> {
+        self.check_scratch(scratch)?;
+        self.check_sample(inputs, expected_outputs)?;
+        self.check_loss(loss)?;
+
+        let dense = self.layers.iter().all(|layer| layer.as_dense().is_some_and(|dense| dense.batch_norm().is_none()));
+        if !dense {
+            return self.learn_sample_batched(inputs, expected_outputs, loss, rate);
+        }
+
+        for (i, layer) in self.layers.iter().enumerate() {
+            let (previous, rest) = scratch.activations.split_at_mut(i);
+            let layer_inputs = previous.last().map_or(inputs, |activations| activations.as_view());
+            layer.as_dense().unwrap().predict_into(layer_inputs, &mut scratch.weighted_sums[i], &mut rest[0]);
+        }
+
+        // Nothing is accumulated before the loss, so a failing loss leaves the gradients as they were.
+        let outputs = scratch.activations.last().unwrap().as_view();
+        let sample_loss = loss.apply(outputs, expected_outputs)?;
+        scratch.gradients.last_mut().unwrap().copy_from(&loss.partial_gradient(outputs, expected_outputs)?);
+
+        for (i, layer) in self.layers.iter_mut().enumerate().rev() {
+            let layer_inputs = if i == 0 { inputs } else { scratch.activations[i - 1].as_view() };
+            let (previous_gradients, gradients) = scratch.gradients.split_at_mut(i);
+
+            layer.as_dense_mut().unwrap().backpropagation_step_scratch(
+                layer_inputs,
+                scratch.weighted_sums[i].as_view(),
+                scratch.activations[i].as_view(),
+                &gradients[0],
+                &mut scratch.deltas[i],
+                previous_gradients.last_mut(),
+            );
+        }
+
+        self.apply_gradients(-rate);
+        Ok(sample_loss)
+    }
+
+    fn check_scratch(&self, scratch: &NetworkScratch<T>) -> Result<(), NetworkError> {
+        let fits = scratch.activations.len() == self.layers.len()
+            && self.layers.iter().zip(scratch.activations.iter()).all(|(layer, buffer)| layer.output_size() == buffer.len());
+
+        if !fits {
+            return Err(NetworkError::ScratchMismatch);
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,308 @@
+use nalgebra::{DMatrix, DVector};
+use thiserror::Error;
+
+use crate::{activations, json::JsonError, prelude::*};
+
+use super::{
+    batch_norm::BatchNorm,
+    layer::Layer,
+    maxout::{MaxoutLayer, MAXOUT},
+    Network,
+    NetworkError,
+    NetworkLayer,
+};
+
+/// A layer's saved state as plain data, independent of any file format, so it can be written with
+/// whatever serialization the application uses. Gradients and caches aren't part of it.
+/// The activation function is stored by its `ActivationFn::name`.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LayerRecord {
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_format::matrix"))]
+    pub weights: DMatrix<f32>,
+    /// `None` for a layer without biases.
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_format::option_vector"))]
+    pub biases: Option<DVector<f32>>,
+    pub activation: String,
+    pub batch_norm: Option<BatchNormRecord>,
+    /// The earlier layer whose weights and biases this one shares, see `Network::share_parameters`. The
+    /// record's own weights and biases are a copy of that layer's.
+    pub shared_with: Option<usize>,
+    /// The weights and biases of a `MaxoutLayer`'s pieces after the first, whose are `weights` and `biases`.
+    /// Empty for a dense layer. A maxout layer's activation is `"maxout"`, and it has biases but neither
+    /// batch normalization nor shared parameters.
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_format::pieces"))]
+    pub maxout_pieces: Vec<(DMatrix<f32>, DVector<f32>)>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BatchNormRecord {
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_format::vector"))]
+    pub gamma: DVector<f32>,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_format::vector"))]
+    pub beta: DVector<f32>,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_format::vector"))]
+    pub running_mean: DVector<f32>,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_format::vector"))]
+    pub running_variance: DVector<f32>,
+    pub momentum: f32,
+    pub epsilon: f32,
+}
+
+#[derive(Debug, Error)]
+pub enum NetworkLoadError {
+    #[cfg(feature = "std")]
+    #[error("{0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("{0}")]
+    Json(#[from] JsonError),
+
+    #[error("{0}")]
+    NetworkError(#[from] NetworkError),
+
+    #[error("the file isn't a saved network")]
+    BadMagic,
+
+    #[error("the file has format version {found}, but only versions up to {supported} are supported")]
+    UnsupportedVersion {
+        found: u32,
+        supported: u32,
+    },
+
+    #[error("the file is truncated")]
+    Truncated,
+
+    #[error("the activation function name of layer {layer} isn't valid UTF-8")]
+    InvalidActivationName {
+        layer: usize,
+    },
+
+    #[error("the batch normalization flag of layer {layer} is neither 0 nor 1")]
+    InvalidBatchNormFlag {
+        layer: usize,
+    },
+
+    #[error("the bias flag of layer {layer} is neither 0 nor 1")]
+    InvalidBiasFlag {
+        layer: usize,
+    },
+
+    #[error("the sharing flag of layer {layer} is neither 0 nor 1")]
+    InvalidSharingFlag {
+        layer: usize,
+    },
+
+    #[error("the precision flag {0} is neither 0 nor 1")]
+    InvalidPrecisionFlag(u8),
+
+    #[error("layer {layer} uses the activation function {name:?}, which isn't known")]
+    UnknownActivation {
+        layer: usize,
+        name: String,
+    },
+
+    #[error("layer {layer} takes {input_size} inputs, but the previous layer gives {previous_output_size} outputs")]
+    LayerSizeMismatch {
+        layer: usize,
+        input_size: usize,
+        previous_output_size: usize,
+    },
+
+    #[error("{found} {field} were given for {expected} layers")]
+    LayerCountMismatch {
+        expected: usize,
+        found: usize,
+        field: &'static str,
+    },
+
+    #[error("the weights of layer {layer} aren't {output_size} rows of {input_size} values")]
+    WeightShapeMismatch {
+        layer: usize,
+        input_size: usize,
+        output_size: usize,
+    },
+
+    #[error("layer {layer} shares the parameters of layer {source_layer}, which doesn't come before it")]
+    InvalidSharedLayer {
+        layer: usize,
+        source_layer: usize,
+    },
+
+    #[error("layer {layer} has maxout pieces, but its activation isn't \"maxout\"")]
+    UnexpectedPieces {
+        layer: usize,
+    },
+
+    #[error("layer {layer} is a maxout layer, which has biases but neither batch normalization nor shared parameters")]
+    InvalidMaxoutLayer {
+        layer: usize,
+    },
+
+    #[error("layer {layer} has {output_size} outputs, but {given_size} {parameter} values")]
+    ParameterSizeMismatch {
+        layer: usize,
+        output_size: usize,
+        given_size: usize,
+        parameter: &'static str,
+    },
+}
+
+impl Network {
+    /// Only dense and maxout layers have records, for any other layer this fails with `NotDense`.
+    pub fn to_records(&self) -> Result<Vec<LayerRecord>, NetworkError> {
+        self.layers
+            .iter()
+            .zip(self.shared.iter())
+            .enumerate()
+            .map(|(index, (layer, &shared_with))| {
+                if let Some(dense) = layer.as_dense() {
+                    Ok(LayerRecord { shared_with, ..dense.to_record() })
+                } else if let Some(maxout) = layer.downcast_ref::<MaxoutLayer>() {
+                    Ok(maxout.to_record())
+                } else {
+                    Err(NetworkError::NotDense { layer: index })
+                }
+            })
+            .collect()
+    }
+
+    /// Rebuilds a network from saved layers, checking that every layer's parameters have consistent
+    /// shapes, that consecutive layers chain, that every activation function is known and that shared
+    /// parameters come from an earlier layer of the same shape. Records with the activation `"maxout"`
+    /// become `MaxoutLayer`s.
+    pub fn from_records(records: Vec<LayerRecord>) -> Result<Self, NetworkLoadError> {
+        let mut layer_sizes: Vec<usize> = records.first().map(|record| record.weights.ncols()).into_iter().collect();
+        layer_sizes.extend(records.iter().map(|record| record.weights.nrows()));
+        super::check_layer_sizes(&layer_sizes)?;
+
+        for (layer, pair) in records.windows(2).enumerate() {
+            if pair[1].weights.ncols() != pair[0].weights.nrows() {
+                return Err(NetworkLoadError::LayerSizeMismatch {
+                    layer: layer + 1,
+                    input_size: pair[1].weights.ncols(),
+                    previous_output_size: pair[0].weights.nrows(),
+                });
+            }
+        }
+
+        for (layer, record) in records.iter().enumerate() {
+            if let Some(source) = record.shared_with && source >= layer {
+                return Err(NetworkLoadError::InvalidSharedLayer { layer, source_layer: source });
+            }
+        }
+
+        let sharing: Vec<Option<usize>> = records.iter().map(|record| record.shared_with).collect();
+
+        let layers = records
+            .into_iter()
+            .enumerate()
+            .map(|(layer, record)| {
+                let output_size = record.weights.nrows();
+                let size_mismatch = |given_size, parameter| NetworkLoadError::ParameterSizeMismatch {
+                    layer,
+                    output_size,
+                    given_size,
+                    parameter,
+                };
+
+                if let Some(biases) = &record.biases && biases.len() != output_size {
+                    return Err(size_mismatch(biases.len(), "bias"));
+                }
+
+                if record.activation == MAXOUT {
+                    return maxout_from_record(layer, record);
+                }
+
+                if !record.maxout_pieces.is_empty() {
+                    return Err(NetworkLoadError::UnexpectedPieces { layer });
+                }
+
+                let activation_fn = activations::from_name(&record.activation).ok_or_else(|| {
+                    NetworkLoadError::UnknownActivation {
+                        layer,
+                        name: record.activation.clone(),
+                    }
+                })?;
+
+                let batch_norm = match record.batch_norm {
+                    Some(batch_norm) => {
+                        let sizes = [
+                            batch_norm.gamma.len(),
+                            batch_norm.beta.len(),
+                            batch_norm.running_mean.len(),
+                            batch_norm.running_variance.len(),
+                        ];
+
+                        if let Some(&given_size) = sizes.iter().find(|&&size| size != output_size) {
+                            return Err(size_mismatch(given_size, "batch normalization"));
+                        }
+
+                        Some(BatchNorm::from_record(batch_norm))
+                    }
+                    None => None,
+                };
+
+                Ok(Box::new(Layer::from_parts(record.weights, record.biases, activation_fn, batch_norm)) as Box<dyn NetworkLayer>)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut network = Self::from_network_layers(layers)?;
+        for (layer, source) in sharing.into_iter().enumerate() {
+            if let Some(source) = source {
+                network.share_parameters(layer, source)?;
+            }
+        }
+
+        Ok(network)
+    }
+}
+
+/// A maxout layer from a record whose pieces were checked to chain with the other layers' sizes.
+fn maxout_from_record(layer: usize, record: LayerRecord) -> Result<Box<dyn NetworkLayer>, NetworkLoadError> {
+    let (Some(biases), None, None) = (record.biases, &record.batch_norm, record.shared_with) else {
+        return Err(NetworkLoadError::InvalidMaxoutLayer { layer });
+    };
+
+    let (output_size, input_size) = record.weights.shape();
+    let pieces = 1 + record.maxout_pieces.len();
+    let mut maxout = MaxoutLayer::zeros(input_size, output_size, pieces).map_err(NetworkError::from)?;
+
+    let all_pieces = core::iter::once((record.weights, biases)).chain(record.maxout_pieces);
+    for (piece, (weights, biases)) in all_pieces.enumerate() {
+        if weights.shape() != (output_size, input_size) {
+            return Err(NetworkLoadError::WeightShapeMismatch { layer, input_size, output_size });
+        }
+
+        if biases.len() != output_size {
+            return Err(NetworkLoadError::ParameterSizeMismatch {
+                layer,
+                output_size,
+                given_size: biases.len(),
+                parameter: "bias",
+            });
+        }
+
+        maxout.set_piece(piece, weights, biases).map_err(NetworkError::from)?;
+    }
+
+    Ok(Box::new(maxout))
+}
+
+/// A network serializes as its `to_records`, so only networks of dense and maxout layers can be serialized.
+#[cfg(feature = "serde")]
+impl serde::Serialize for Network {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.to_records().map_err(serde::ser::Error::custom)?.serialize(serializer)
+    }
+}
+
+/// Deserializing goes through `from_records`, so it fails with its errors for layers that don't chain or
+/// unknown activation functions.
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Network {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Network::from_records(Vec::deserialize(deserializer)?).map_err(serde::de::Error::custom)
+    }
+}
@@ -0,0 +1,169 @@
+//! Dense layers sharing one set of weights and biases, e.g. the same transformation applied at two places of
+//! a siamese network. Every layer keeps a copy of the shared parameters for its forward and backward passes,
+//! and the network keeps the copies equal: `apply_gradients` adds the gradients of all layers of a group into
+//! the group's first layer, updates that one and copies its parameters to the others. So every usage
+//! contributes to one update, and the group counts once in `parameter_count`, `get_params` and saved files.
+
+use crate::scalar::Scalar;
+
+use super::{Network, NetworkError};
+
+impl<T: Scalar> Network<T> {
+    /// Makes layer `layer` share the weights and biases of layer `source` from now on, the parameters `layer`
+    /// had are replaced. If either one already shares its parameters, their groups are merged with the
+    /// parameters of `source`'s group. Both have to be dense layers with the same input and output sizes and
+    /// either both or neither with biases. Activation functions and batch normalization stay per layer, while
+    /// the learning rate scale, trainability and weight constraint of the group's first layer apply to the
+    /// shared parameters.
+    pub fn share_parameters(&mut self, layer: usize, source: usize) -> Result<(), NetworkError> {
+        for index in [layer, source] {
+            if index >= self.layers.len() {
+                return Err(NetworkError::LayerIndexOutOfRange {
+                    index,
+                    layers: self.layers.len(),
+                });
+            }
+        }
+
+        let (Some(dense), Some(source_dense)) = (self.layers[layer].as_dense(), self.layers[source].as_dense()) else {
+            let layer = if self.layers[source].as_dense().is_none() { source } else { layer };
+            return Err(NetworkError::NotDense { layer });
+        };
+
+        if dense.weights().shape() != source_dense.weights().shape() || dense.has_bias() != source_dense.has_bias() {
+            return Err(NetworkError::SharedShapeMismatch { layer, source_layer: source });
+        }
+
+        let (owner, source_owner) = (self.parameter_owner(layer), self.parameter_owner(source));
+        if owner == source_owner {
+            return Ok(());
+        }
+
+        let source_dense = self.layers[source_owner].as_dense().unwrap();
+        let (weights, biases) = (source_dense.weights().into_owned(), source_dense.biases().into_owned());
+        let dense = self.layers[owner].as_dense_mut().unwrap();
+        dense.weights_mut().copy_from(&weights);
+        dense.biases_mut().copy_from(&biases);
+
+        let first = owner.min(source_owner);
+        for index in 0..self.layers.len() {
+            if [owner, source_owner].contains(&self.parameter_owner(index)) {
+                self.shared[index] = (index != first).then_some(first);
+            }
+        }
+
+        self.sync_shared_parameters();
+        Ok(())
+    }
+
+    /// Gives layer `layer` its own copy of the parameters it shares, if any, so it's trained on its own from
+    /// now on. The rest of its group keeps sharing.
+    pub fn unshare_parameters(&mut self, layer: usize) -> Result<(), NetworkError> {
+        if layer >= self.layers.len() {
+            return Err(NetworkError::LayerIndexOutOfRange {
+                index: layer,
+                layers: self.layers.len(),
+            });
+        }
+
+        self.detach_shared(layer);
+        Ok(())
+    }
+
+    /// The layer whose parameters layer `layer` uses, the first layer of its group, or `None` if it has its own
+    /// or is itself the first layer of a group.
+    #[inline]
+    pub fn shared_parameters(&self, layer: usize) -> Option<usize> {
+        self.shared.get(layer).copied().flatten()
+    }
+
+    /// The layer holding the parameters layer `index` uses, `index` itself unless it shares them.
+    #[inline]
+    pub(crate) fn parameter_owner(&self, index: usize) -> usize {
+        self.shared[index].unwrap_or(index)
+    }
+
+    /// Adds the accumulated weight and bias gradients of every layer that shares its parameters to the group's
+    /// first layer and zeroes its own, so the group's gradient is in one place before an update.
+    pub(crate) fn merge_shared_gradients(&mut self) {
+        for layer in 0..self.layers.len() {
+            let Some(owner) = self.shared[layer] else {
+                continue;
+            };
+
+            let (head, tail) = self.layers.split_at_mut(layer);
+            let (owner, member) = (head[owner].as_dense_mut().unwrap(), tail[0].as_dense_mut().unwrap());
+            let ((owner_weights, owner_biases), (member_weights, member_biases)) = (owner.gradients_mut(), member.gradients_mut());
+
+            *owner_weights += &*member_weights;
+            member_weights.fill(T::zero());
+
+            if let (Some(owner_biases), Some(member_biases)) = (owner_biases, member_biases) {
+                *owner_biases += &*member_biases;
+                member_biases.fill(T::zero());
+            }
+        }
+    }
+
+    /// Copies the weights and biases of every group's first layer to the rest of the group.
+    pub(crate) fn sync_shared_parameters(&mut self) {
+        for layer in 0..self.layers.len() {
+            let Some(owner) = self.shared[layer] else {
+                continue;
+            };
+
+            let (head, tail) = self.layers.split_at_mut(layer);
+            let (owner, member) = (head[owner].as_dense().unwrap(), tail[0].as_dense_mut().unwrap());
+            member.weights_mut().copy_from(&owner.weights());
+            member.biases_mut().copy_from(&owner.biases());
+        }
+    }
+
+    /// Takes layer `layer` out of its group. If it's the group's first layer, the next one takes its place.
+    pub(crate) fn detach_shared(&mut self, layer: usize) {
+        if self.shared[layer].take().is_some() {
+            return;
+        }
+
+        let mut successor = None;
+        for index in layer + 1..self.shared.len() {
+            if self.shared[index] == Some(layer) {
+                let first = *successor.get_or_insert(index);
+                self.shared[index] = (index != first).then_some(first);
+            }
+        }
+    }
+
+    /// Fails with `SharedLayerResize` if layer `layer` exists and shares its parameters with another layer.
+    pub(crate) fn check_unshared(&self, layer: usize) -> Result<(), NetworkError> {
+        if self.shared_parameters(layer).is_some() || self.shared.contains(&Some(layer)) {
+            return Err(NetworkError::SharedLayerResize { layer });
+        }
+
+        Ok(())
+    }
+
+    /// Keeps the groups pointing at the right layers after a layer was inserted before `index`.
+    pub(crate) fn insert_shared(&mut self, index: usize) {
+        for owner in self.shared.iter_mut().flatten() {
+            if *owner >= index {
+                *owner += 1;
+            }
+        }
+
+        self.shared.insert(index, None);
+    }
+
+    /// Takes layer `index` out of its group and keeps the other groups pointing at the right layers, before
+    /// the layer is removed.
+    pub(crate) fn remove_shared(&mut self, index: usize) {
+        self.detach_shared(index);
+        self.shared.remove(index);
+
+        for owner in self.shared.iter_mut().flatten() {
+            if *owner > index {
+                *owner -= 1;
+            }
+        }
+    }
+}
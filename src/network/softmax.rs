@@ -0,0 +1,117 @@
+//! A softmax output layer with a temperature, `softmax(z / T)`, e.g. for knowledge distillation or for
+//! sharpening and flattening the outputs at inference.
+
+use nalgebra::{DMatrix, DMatrixView, DVector, DVectorView};
+
+use crate::{
+    activations::{check_temperature, softmax_with_temperature},
+    prelude::*,
+    scalar::Scalar,
+};
+
+use super::{layer::LayerError, NetworkLayer};
+
+/// Normalizes its inputs into a probability distribution. It has no parameters, so it's used as the
+/// last layer of a `Network` after a linear layer whose outputs are the logits.
+#[derive(Clone, Debug)]
+pub struct Softmax<T: Scalar = f32> {
+    size: usize,
+    temperature: T,
+
+    /// The outputs of the last training batch, which are all the backward pass needs.
+    previous_outputs: DMatrix<T>,
+}
+
+impl<T: Scalar> Softmax<T> {
+    /// A softmax over `size` inputs, fails unless `temperature` is positive.
+    pub fn new(size: usize, temperature: T) -> Result<Self, LayerError> {
+        if size == 0 {
+            return Err(LayerError::ZeroInputSize);
+        }
+
+        check_temperature(temperature)?;
+
+        Ok(Self {
+            size,
+            temperature,
+            previous_outputs: DMatrix::zeros(size, 0),
+        })
+    }
+
+    #[inline]
+    pub fn temperature(&self) -> T { self.temperature }
+
+    /// Changes the temperature, e.g. to train at a high one and predict at 1.
+    pub fn set_temperature(&mut self, temperature: T) -> Result<(), LayerError> {
+        check_temperature(temperature)?;
+        self.temperature = temperature;
+        Ok(())
+    }
+
+    fn check_input_size(&self, input_size: usize) -> Result<(), LayerError> {
+        if input_size != self.size {
+            return Err(LayerError::InputSizeMismatch {
+                layer_input_size: self.size,
+                given_input_size: input_size,
+            });
+        }
+
+        Ok(())
+    }
+}
+
+impl<T: Scalar> NetworkLayer<T> for Softmax<T> {
+    #[inline]
+    fn input_size(&self) -> usize { self.size }
+
+    #[inline]
+    fn output_size(&self) -> usize { self.size }
+
+    fn forward(&mut self, inputs: DVectorView<T>) -> Result<DVector<T>, LayerError> {
+        self.predict(inputs)
+    }
+
+    fn predict(&self, inputs: DVectorView<T>) -> Result<DVector<T>, LayerError> {
+        self.check_input_size(inputs.len())?;
+        softmax_with_temperature(inputs, self.temperature)
+    }
+
+    fn forward_training_batch(&mut self, inputs: DMatrixView<T>) -> Result<DMatrix<T>, LayerError> {
+        self.check_input_size(inputs.nrows())?;
+        self.previous_outputs = DMatrix::zeros(self.size, inputs.ncols());
+
+        for (mut outputs, inputs) in self.previous_outputs.column_iter_mut().zip(inputs.column_iter()) {
+            outputs.copy_from(&softmax_with_temperature(inputs, self.temperature)?);
+        }
+
+        Ok(self.previous_outputs.clone())
+    }
+
+    /// With `s` the outputs and `g` their gradient, the gradient of the logits is `s ⊙ (g - g·s) / T`.
+    fn backpropagation_step_batch(&mut self, _inputs: DMatrixView<T>, output_partial_gradient: &DMatrix<T>) -> Result<DMatrix<T>, LayerError> {
+        if self.previous_outputs.shape() != output_partial_gradient.shape() {
+            return Err(LayerError::NoTrainingBatch(output_partial_gradient.ncols()));
+        }
+
+        let mut input_gradient = output_partial_gradient.clone();
+
+        for (mut gradient, outputs) in input_gradient.column_iter_mut().zip(self.previous_outputs.column_iter()) {
+            let weighted_mean = gradient.dot(&outputs);
+            gradient.add_scalar_mut(-weighted_mean);
+            gradient.component_mul_assign(&outputs);
+            gradient /= self.temperature;
+        }
+
+        Ok(input_gradient)
+    }
+
+    fn apply_gradient(&mut self, scale: T) {}
+
+    fn zero_gradient(&mut self) {}
+
+    fn boxed_clone(&self) -> Box<dyn NetworkLayer<T>> {
+        Box::new(self.clone())
+    }
+
+    fn kind(&self) -> &'static str { "softmax" }
+}
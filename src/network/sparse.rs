@@ -0,0 +1,116 @@
+//! Inputs given by their non-zero entries as `(index, value)` pairs, e.g. bag-of-words features. The
+//! first layer adds up the weight columns of the entries that are set instead of multiplying the whole
+//! weight matrix, and its weight gradient only accumulates in those columns. Later layers run densely.
+
+use nalgebra::{DVector, DVectorView};
+
+use crate::{losses::LossFn, prelude::*, scalar::Scalar};
+
+use super::{Network, NetworkError};
+
+/// A sample with a sparse input: the input's non-zero entries as `(index, value)` pairs and the expected outputs.
+pub type SparseSample<T = f32> = (Vec<(usize, T)>, DVector<T>);
+
+impl<T: Scalar> Network<T> {
+    /// `predict` for a sparse input, the same as `predict` on the input with every other entry zero.
+    /// The first layer has to be a dense layer, otherwise this fails with `NotDense`, and an index that
+    /// isn't an input fails with a `SparseIndexOutOfRange` layer error.
+    pub fn predict_sparse(&self, input: &[(usize, T)]) -> Result<DVector<T>, NetworkError> {
+        let (first, rest) = self.layers.split_first().unwrap();
+        let first = first.as_dense().ok_or(NetworkError::NotDense { layer: 0 })?;
+        let (_, outputs) = first.feed_sparse(input)?;
+
+        rest.iter().try_fold(outputs, |activations, layer| {
+            layer.predict(activations.as_view()).map_err(Into::into)
+        })
+    }
+
+    /// `backpropagate` for samples with sparse inputs, returning the summed loss. Samples are processed one at
+    /// a time, so this needs a network of dense layers and fails with `NotDense` otherwise, and with
+    /// `BatchNormUnsupported` for batch normalization. On an error the gradients accumulated so far are discarded.
+    pub fn backpropagate_sparse(&mut self, dataset: &[SparseSample<T>], loss: &impl LossFn<T>) -> Result<T, NetworkError> {
+        self.check_dense()?;
+        self.check_no_batch_norm()?;
+        self.check_loss(loss)?;
+
+        if let Some(index) = dataset.iter().position(|(_, expected_outputs)| expected_outputs.len() != self.output_size()) {
+            return Err(NetworkError::SampleSizeMismatch {
+                index,
+                inputs: self.input_size(),
+                outputs: dataset[index].1.len(),
+                network_inputs: self.input_size(),
+                network_outputs: self.output_size(),
+            });
+        }
+
+        let mut total_loss = T::zero();
+
+        for (inputs, expected_outputs) in dataset.iter() {
+            match self.backpropagate_sparse_sample(inputs, expected_outputs.as_view(), loss) {
+                Ok(sample_loss) => total_loss += sample_loss,
+                Err(error) => {
+                    self.zero_gradients();
+                    return Err(error);
+                }
+            }
+        }
+
+        Ok(total_loss)
+    }
+
+    /// `learn` on top of `backpropagate_sparse`, returning the mean loss before the step.
+    pub fn learn_sparse(&mut self, dataset: &[SparseSample<T>], loss: &impl LossFn<T>, rate: T) -> Result<T, NetworkError> {
+        if dataset.is_empty() {
+            return Ok(T::zero());
+        }
+
+        let total_loss = self.backpropagate_sparse(dataset, loss)?;
+        let count = T::from_count(dataset.len());
+        self.apply_gradients(-rate / count);
+
+        Ok(total_loss / count)
+    }
+
+    fn backpropagate_sparse_sample(
+        &mut self,
+        inputs: &[(usize, T)],
+        expected_outputs: DVectorView<T>,
+        loss: &impl LossFn<T>,
+    ) -> Result<T, NetworkError> {
+        let mut weighted_inputs = Vec::with_capacity(self.layers.len());
+        let mut activations = Vec::with_capacity(self.layers.len());
+
+        let (first, rest) = self.layers.split_first().unwrap();
+        let (weighted_sums, outputs) = first.as_dense().unwrap().feed_sparse(inputs)?;
+        weighted_inputs.push(weighted_sums);
+        activations.push(outputs);
+
+        for layer in rest {
+            let (weighted_sums, outputs) = layer.as_dense().unwrap().feed(activations.last().unwrap().as_view())?;
+            weighted_inputs.push(weighted_sums);
+            activations.push(outputs);
+        }
+
+        let outputs = activations.last().unwrap().as_view();
+        let sample_loss = loss.apply(outputs, expected_outputs)?;
+        let mut activation_partial_gradient = loss.partial_gradient(outputs, expected_outputs)?;
+
+        for i in (1..self.layers.len()).rev() {
+            activation_partial_gradient = self.layers[i].as_dense_mut().unwrap().backpropagation_step_cached(
+                activations[i - 1].as_view(),
+                weighted_inputs[i].as_view(),
+                activations[i].as_view(),
+                activation_partial_gradient.as_view(),
+            );
+        }
+
+        self.layers[0].as_dense_mut().unwrap().backpropagation_step_sparse(
+            inputs,
+            weighted_inputs[0].as_view(),
+            activations[0].as_view(),
+            activation_partial_gradient.as_view(),
+        );
+
+        Ok(sample_loss)
+    }
+}
@@ -0,0 +1,105 @@
+//! Layers with their sizes in the type, for deploying trained networks where inference mustn't allocate.
+//! Training stays on `Layer`, a trained layer is converted with `StaticLayer::try_from`.
+
+use nalgebra::{SMatrix, SVector};
+
+use crate::{activations::ActivationFn, scalar::Scalar};
+
+use super::layer::{Layer, LayerError};
+
+/// A layer of `IN` inputs and `OUT` outputs on stack allocated matrices, with its activation function as
+/// the type `A`, e.g. `StaticLayer<4, 8, ReLU>`. Batch normalization of the layer it was converted from is
+/// folded into the weights and biases.
+#[derive(Clone)]
+pub struct StaticLayer<const IN: usize, const OUT: usize, A, T: Scalar = f32> {
+    weights: SMatrix<T, OUT, IN>,
+    biases: SVector<T, OUT>,
+    activation_fn: A,
+}
+
+/// Inference through static layers. Pairs of them are stacked as tuples, `(first, second)`, which nest
+/// for deeper networks: `(first, (second, third))`.
+pub trait StaticForward {
+    type Input;
+    type Output;
+
+    fn forward(&self, input: &Self::Input) -> Self::Output;
+}
+
+impl<const IN: usize, const OUT: usize, A: ActivationFn<T>, T: Scalar> StaticLayer<IN, OUT, A, T> {
+    pub fn new(weights: SMatrix<T, OUT, IN>, biases: SVector<T, OUT>, activation_fn: A) -> Self {
+        Self { weights, biases, activation_fn }
+    }
+
+    /// Converts a trained layer, failing with `ParameterShapeMismatch` if its sizes aren't `IN` and `OUT`
+    /// and with `ActivationMismatch` if its activation function isn't `activation_fn`, by name. `try_from`
+    /// does the same for activation functions without settings.
+    pub fn from_layer(layer: &Layer<T>, activation_fn: A) -> Result<Self, LayerError> {
+        if layer.input_size() != IN || layer.output_size() != OUT {
+            return Err(LayerError::ParameterShapeMismatch {
+                layer_input_size: layer.input_size(),
+                layer_output_size: layer.output_size(),
+                given_input_size: IN,
+                given_output_size: OUT,
+            });
+        }
+
+        if layer.activation_fn().name() != activation_fn.name() {
+            return Err(LayerError::ActivationMismatch {
+                expected: activation_fn.name(),
+                found: layer.activation_fn().name(),
+            });
+        }
+
+        let mut weights = layer.weights().into_owned();
+        let mut biases = layer.biases().into_owned();
+
+        if let Some(batch_norm) = layer.batch_norm() {
+            batch_norm.fold(&mut weights, &mut biases);
+        }
+
+        Ok(Self {
+            weights: SMatrix::from_column_slice(weights.as_slice()),
+            biases: SVector::from_column_slice(biases.as_slice()),
+            activation_fn,
+        })
+    }
+
+    #[inline]
+    pub fn weights(&self) -> &SMatrix<T, OUT, IN> { &self.weights }
+
+    #[inline]
+    pub fn biases(&self) -> &SVector<T, OUT> { &self.biases }
+}
+
+impl<const IN: usize, const OUT: usize, A: ActivationFn<T>, T: Scalar> StaticForward for StaticLayer<IN, OUT, A, T> {
+    type Input = SVector<T, IN>;
+    type Output = SVector<T, OUT>;
+
+    fn forward(&self, input: &SVector<T, IN>) -> SVector<T, OUT> {
+        let mut outputs = self.weights * input + self.biases;
+        self.activation_fn.apply_slice(outputs.as_mut_slice());
+        outputs
+    }
+}
+
+impl<A, B> StaticForward for (A, B)
+where
+    A: StaticForward,
+    B: StaticForward<Input = A::Output>,
+{
+    type Input = A::Input;
+    type Output = B::Output;
+
+    fn forward(&self, input: &A::Input) -> B::Output {
+        self.1.forward(&self.0.forward(input))
+    }
+}
+
+impl<const IN: usize, const OUT: usize, A: ActivationFn<T> + Default, T: Scalar> TryFrom<&Layer<T>> for StaticLayer<IN, OUT, A, T> {
+    type Error = LayerError;
+
+    fn try_from(layer: &Layer<T>) -> Result<Self, LayerError> {
+        Self::from_layer(layer, A::default())
+    }
+}
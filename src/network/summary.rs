@@ -0,0 +1,134 @@
+use core::fmt::{self, Write};
+
+use crate::{prelude::*, scalar::Scalar};
+
+use super::{layer::Layer, Network, NetworkLayer};
+
+/// Rows and columns of a matrix shown by `dump_weights` before it's cut off.
+const MAX_SHOWN: usize = 8;
+
+impl Network {
+    /// A table of the layers with their sizes, activation functions and parameter counts. Layers other than
+    /// dense ones show their `NetworkLayer::kind` instead of an activation function, and layers sharing the
+    /// parameters of an earlier layer show that layer instead of a count.
+    pub fn summary(&self) -> String {
+        let rows: Vec<[String; 5]> = self.layers
+            .iter()
+            .enumerate()
+            .map(|(i, layer)| {
+                [
+                    i.to_string(),
+                    layer.input_size().to_string(),
+                    layer.output_size().to_string(),
+                    activation(layer.as_ref()),
+                    match self.shared_parameters(i) {
+                        Some(owner) => format!("shared with {owner}"),
+                        None => layer.parameter_count().to_string(),
+                    },
+                ]
+            })
+            .collect();
+
+        let header = ["layer", "inputs", "outputs", "activation", "parameters"].map(str::to_string);
+        let mut widths = header.clone().map(|column| column.len());
+        for row in rows.iter() {
+            for (width, cell) in widths.iter_mut().zip(row.iter()) {
+                *width = (*width).max(cell.len());
+            }
+        }
+
+        let mut summary = String::new();
+        for row in core::iter::once(&header).chain(rows.iter()) {
+            let line: Vec<String> = row
+                .iter()
+                .zip(widths.iter())
+                .map(|(cell, &width)| format!("{cell:<width$}"))
+                .collect();
+
+            writeln!(summary, "{}", line.join("  ").trim_end()).unwrap();
+        }
+
+        writeln!(summary, "total parameters: {}", self.parameter_count()).unwrap();
+        summary
+    }
+
+    /// Writes every layer's weight matrix (one row per output) and biases with `precision` decimals.
+    /// Matrices larger than 8 rows or columns are cut off, with the full size stated in the heading.
+    /// Layers other than dense ones only get a line with their kind and parameter count.
+    pub fn dump_weights(&self, writer: &mut impl Write, precision: usize) -> fmt::Result {
+        for (i, layer) in self.layers.iter().enumerate() {
+            let Some(layer) = layer.as_dense() else {
+                writeln!(writer, "layer {i}: {} layer with {} parameters", layer.kind(), layer.parameter_count())?;
+                continue;
+            };
+
+            let weights = layer.weights();
+            let (rows, columns) = weights.shape();
+            let shown_rows = rows.min(MAX_SHOWN);
+            let shown_columns = columns.min(MAX_SHOWN);
+
+            let cells: Vec<Vec<String>> = (0..shown_rows)
+                .map(|row| (0..shown_columns).map(|column| format!("{:.precision$}", weights[(row, column)])).collect())
+                .collect();
+            let width = cells.iter().flatten().map(String::len).max().unwrap_or(0);
+
+            writeln!(writer, "layer {i} weights ({rows} x {columns}):")?;
+            for row in cells.iter() {
+                let line: Vec<String> = row.iter().map(|cell| format!("{cell:>width$}")).collect();
+                let ellipsis = if columns > shown_columns { "  …" } else { "" };
+                writeln!(writer, "  {}{ellipsis}", line.join(" "))?;
+            }
+
+            if rows > shown_rows {
+                writeln!(writer, "  … {} more rows", rows - shown_rows)?;
+            }
+
+            let biases: Vec<String> = layer.biases()
+                .iter()
+                .take(MAX_SHOWN)
+                .map(|bias| format!("{bias:.precision$}"))
+                .collect();
+            let ellipsis = if rows > MAX_SHOWN { " …" } else { "" };
+            writeln!(writer, "layer {i} biases ({rows}): {}{ellipsis}", biases.join(" "))?;
+        }
+
+        Ok(())
+    }
+}
+
+/// A dense layer's activation function, with batch normalization if it has it, or another layer's kind.
+fn activation<T: Scalar>(layer: &dyn NetworkLayer<T>) -> String {
+    match layer.as_dense() {
+        Some(dense) if dense.batch_norm().is_some() => format!("{} + batch norm", dense.activation_fn().name()),
+        Some(dense) => dense.activation_fn().name().to_string(),
+        None => layer.kind().to_string(),
+    }
+}
+
+/// The architecture on one line, e.g. `Network: 2 -> 50 (sigmoid) -> 1 (sigmoid), 201 parameters`, every
+/// layer given by its output size and activation function as in `summary`. No parameters are shown.
+impl<T: Scalar> fmt::Display for Network<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Network: {}", self.input_size())?;
+
+        for layer in self.layers.iter() {
+            write!(f, " -> {} ({})", layer.output_size(), activation(layer.as_ref()))?;
+        }
+
+        write!(f, ", {} parameters", self.parameter_count())
+    }
+}
+
+/// Like `Network`'s, e.g. `Layer: 2 -> 50 (sigmoid), 150 parameters`.
+impl<T: Scalar> fmt::Display for Layer<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Layer: {} -> {} ({}), {} parameters",
+            self.input_size(),
+            self.output_size(),
+            activation(self as &dyn NetworkLayer<T>),
+            self.parameter_count(),
+        )
+    }
+}
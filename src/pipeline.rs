@@ -0,0 +1,451 @@
+//! A network bundled with the preprocessing its inputs go through, so training and inference can't apply
+//! different transforms. Pipelines are saved as a single JSON document:
+//!
+//! ```json
+//! {
+//!   "format": "neural-pipeline",
+//!   "version": 1,
+//!   "transforms": [
+//!     { "type": "normalize", "normalization": "z_score", "offset": [0.5, 2], "scale": [1, 0.25] },
+//!     { "type": "expand", "input_size": 2, "degree": 2, "interactions_only": false }
+//!   ],
+//!   "network": { "format": "neural", "version": 1, "layers": [] },
+//!   "output_normalization": { "normalization": "min_max", "offset": [0], "scale": [10] }
+//! }
+//! ```
+//!
+//! `network` is a network in the format of `Network::to_json` and `output_normalization` is left out if the
+//! pipeline has none.
+
+use std::{fs, path::Path};
+
+use nalgebra::{DVector, DVectorView};
+use thiserror::Error;
+
+use crate::{
+    dataset::{DatasetError, FeatureExpansion, Normalization, Normalizer, Sample},
+    json::{JsonError, Value},
+    losses::LossFn,
+    network::{Network, NetworkError, NetworkLoadError},
+};
+
+const FORMAT_NAME: &str = "neural-pipeline";
+pub const PIPELINE_FORMAT_VERSION: u32 = 1;
+
+#[derive(Debug, Error)]
+pub enum PipelineError {
+    #[error("{0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("{0}")]
+    Json(#[from] JsonError),
+
+    #[error("{0}")]
+    DatasetError(#[from] DatasetError),
+
+    #[error("{0}")]
+    NetworkError(#[from] NetworkError),
+
+    #[error("{0}")]
+    NetworkLoadError(#[from] NetworkLoadError),
+
+    #[error("transform {transform} gives {output_size} values, but the {next} takes {input_size}")]
+    TransformSizeMismatch {
+        transform: usize,
+        output_size: usize,
+        next: &'static str,
+        input_size: usize,
+    },
+
+    #[error("the file isn't a saved pipeline")]
+    BadMagic,
+
+    #[error("the file has format version {found}, but only version {supported} is supported")]
+    UnsupportedVersion {
+        found: u32,
+        supported: u32,
+    },
+
+    #[error("transform {transform} has the type {name:?}, which isn't known")]
+    UnknownTransform {
+        transform: usize,
+        name: String,
+    },
+
+    #[error("{0:?} isn't a known normalization")]
+    UnknownNormalization(String),
+
+    #[error("transform {transform} expands to more than the {input_size} values the {next} takes")]
+    ExpansionTooLarge {
+        transform: usize,
+        next: &'static str,
+        input_size: usize,
+    },
+}
+
+/// A step of a pipeline's preprocessing.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Transform {
+    /// A normalizer that `Pipeline::fit` refits with its normalization.
+    Normalize {
+        normalization: Normalization,
+        normalizer: Normalizer,
+    },
+    Expand(FeatureExpansion),
+}
+
+impl Transform {
+    /// A normalizer of `size` features that leaves them unchanged until the pipeline is fitted.
+    pub fn normalize(normalization: Normalization, size: usize) -> Self {
+        Transform::Normalize {
+            normalization,
+            normalizer: identity_normalizer(size),
+        }
+    }
+
+    pub fn input_size(&self) -> usize {
+        match self {
+            Transform::Normalize { normalizer, .. } => normalizer.size(),
+            Transform::Expand(expansion) => expansion.input_size(),
+        }
+    }
+
+    pub fn output_size(&self) -> usize {
+        match self {
+            Transform::Normalize { normalizer, .. } => normalizer.size(),
+            Transform::Expand(expansion) => expansion.output_size(),
+        }
+    }
+
+    pub fn transform(&self, vector: DVectorView<f32>) -> Result<DVector<f32>, DatasetError> {
+        match self {
+            Transform::Normalize { normalizer, .. } => normalizer.transform(vector),
+            Transform::Expand(expansion) => expansion.transform(vector),
+        }
+    }
+
+    /// Returns copies of the samples with transformed inputs.
+    pub fn transform_samples(&self, samples: &[Sample]) -> Result<Vec<Sample>, DatasetError> {
+        match self {
+            Transform::Normalize { normalizer, .. } => normalizer.normalized_inputs(samples),
+            Transform::Expand(expansion) => expansion.expand_samples(samples),
+        }
+    }
+}
+
+impl From<FeatureExpansion> for Transform {
+    fn from(expansion: FeatureExpansion) -> Self {
+        Transform::Expand(expansion)
+    }
+}
+
+/// Input transforms applied in order before a network, and optionally a normalization of the network's
+/// outputs that `predict` undoes, for regression targets on a scale the output layer can't reach.
+#[derive(Debug)]
+pub struct Pipeline {
+    transforms: Vec<Transform>,
+    network: Network,
+    output_normalization: Option<(Normalization, Normalizer)>,
+}
+
+impl Pipeline {
+    /// Fails if a transform's output size isn't the next transform's input size, or the last one's isn't
+    /// the network's.
+    pub fn new(transforms: Vec<Transform>, network: Network) -> Result<Self, PipelineError> {
+        for (transform, pair) in transforms.windows(2).enumerate() {
+            if pair[0].output_size() != pair[1].input_size() {
+                return Err(PipelineError::TransformSizeMismatch {
+                    transform,
+                    output_size: pair[0].output_size(),
+                    next: "next transform",
+                    input_size: pair[1].input_size(),
+                });
+            }
+        }
+
+        if let Some(last) = transforms.last()
+            && last.output_size() != network.input_size()
+        {
+            return Err(PipelineError::TransformSizeMismatch {
+                transform: transforms.len() - 1,
+                output_size: last.output_size(),
+                next: "network",
+                input_size: network.input_size(),
+            });
+        }
+
+        Ok(Self {
+            transforms,
+            network,
+            output_normalization: None,
+        })
+    }
+
+    /// Trains the network on normalized expected outputs and maps its predictions back. The normalizer
+    /// leaves the outputs unchanged until the pipeline is fitted.
+    pub fn with_output_normalization(mut self, normalization: Normalization) -> Self {
+        let normalizer = identity_normalizer(self.network.output_size());
+        self.output_normalization = Some((normalization, normalizer));
+        self
+    }
+
+    #[inline]
+    pub fn transforms(&self) -> &[Transform] { &self.transforms }
+
+    #[inline]
+    pub fn network(&self) -> &Network { &self.network }
+
+    #[inline]
+    pub fn network_mut(&mut self) -> &mut Network { &mut self.network }
+
+    #[inline]
+    pub fn output_normalizer(&self) -> Option<&Normalizer> {
+        self.output_normalization.as_ref().map(|(_, normalizer)| normalizer)
+    }
+
+    #[inline]
+    pub fn input_size(&self) -> usize {
+        self.transforms.first().map_or(self.network.input_size(), Transform::input_size)
+    }
+
+    #[inline]
+    pub fn output_size(&self) -> usize { self.network.output_size() }
+
+    #[inline]
+    pub fn into_network(self) -> Network { self.network }
+
+    /// Refits every normalizer to the samples as they reach it, after the transforms before it, and the
+    /// output normalizer to the expected outputs. The network is left unchanged.
+    pub fn fit(&mut self, samples: &[Sample]) -> Result<(), PipelineError> {
+        if let Some(sample) = samples.iter().find(|sample| sample.inputs().len() != self.input_size()) {
+            return Err(DatasetError::TransformSizeMismatch {
+                expected: self.input_size(),
+                given: sample.inputs().len(),
+            }
+            .into());
+        }
+
+        let mut transformed = samples.to_vec();
+
+        for transform in self.transforms.iter_mut() {
+            if let Transform::Normalize { normalization, normalizer } = transform {
+                *normalizer = Normalizer::fit(&transformed, *normalization)?;
+            }
+
+            transformed = transform.transform_samples(&transformed)?;
+        }
+
+        if let Some((normalization, normalizer)) = &mut self.output_normalization {
+            *normalizer = Normalizer::fit_outputs(samples, *normalization)?;
+        }
+
+        Ok(())
+    }
+
+    /// The samples as the network sees them, with transformed inputs and normalized expected outputs. To
+    /// train for many epochs, e.g. with a `Trainer`, prepare the samples once and train `network_mut` on them.
+    pub fn prepare(&self, samples: &[Sample]) -> Result<Vec<Sample>, PipelineError> {
+        let mut prepared = samples.to_vec();
+
+        for transform in self.transforms.iter() {
+            prepared = transform.transform_samples(&prepared)?;
+        }
+
+        if let Some((_, normalizer)) = &self.output_normalization {
+            normalizer.normalize_outputs(&mut prepared)?;
+        }
+
+        Ok(prepared)
+    }
+
+    /// `Network::learn` on the prepared samples, returning the loss on the normalized outputs.
+    pub fn learn(&mut self, samples: &[Sample], loss: &impl LossFn, rate: f32) -> Result<f32, PipelineError> {
+        let prepared = self.prepare(samples)?;
+        Ok(self.network.learn(&prepared, loss, rate)?)
+    }
+
+    /// Transforms the input, runs the network and maps its output back to the original scale.
+    pub fn predict(&self, input: DVectorView<f32>) -> Result<DVector<f32>, PipelineError> {
+        let mut input = input.into_owned();
+        for transform in self.transforms.iter() {
+            input = transform.transform(input.as_view())?;
+        }
+
+        let mut output = self.network.predict(input.as_view())?;
+        if let Some((_, normalizer)) = &self.output_normalization {
+            normalizer.inverse_mut(&mut output)?;
+        }
+
+        Ok(output)
+    }
+
+    /// Fails with `NotDense` if the network has layers other than dense ones.
+    pub fn to_json(&self) -> Result<String, NetworkError> {
+        let transforms = self.transforms.iter().map(transform_to_json).collect();
+
+        let mut json = Value::object([
+            ("format", Value::String(FORMAT_NAME.to_string())),
+            ("version", Value::Number(PIPELINE_FORMAT_VERSION as f64)),
+            ("transforms", Value::Array(transforms)),
+            ("network", self.network.to_json_value()?),
+        ]);
+
+        if let Some((normalization, normalizer)) = &self.output_normalization {
+            json.insert("output_normalization", Value::object(normalizer_fields(*normalization, normalizer)));
+        }
+
+        Ok(json.to_string())
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, PipelineError> {
+        let json = Value::parse(json)?;
+
+        if json.field("$", "format")?.as_str("$.format")? != FORMAT_NAME {
+            return Err(PipelineError::BadMagic);
+        }
+
+        let version = json.field("$", "version")?.as_usize("$.version")?;
+        if version != PIPELINE_FORMAT_VERSION as usize {
+            return Err(PipelineError::UnsupportedVersion {
+                found: version as u32,
+                supported: PIPELINE_FORMAT_VERSION,
+            });
+        }
+
+        let network = Network::from_json_value(json.field("$", "network")?)?;
+        let transforms_json = json.field("$", "transforms")?.as_array("$.transforms")?;
+
+        // Read from the last transform on, so that an expansion's size is checked against what it feeds
+        // before its terms are built.
+        let mut transforms = Vec::with_capacity(transforms_json.len());
+        let mut next = ("network", network.input_size());
+        for (transform, json) in transforms_json.iter().enumerate().rev() {
+            let parsed = transform_from_json(transform, json, next)?;
+            next = ("next transform", parsed.input_size());
+            transforms.push(parsed);
+        }
+
+        transforms.reverse();
+        let mut pipeline = Self::new(transforms, network)?;
+
+        if let Some(json) = json.optional_field("$", "output_normalization")? {
+            let (normalization, normalizer) = normalizer_from_json("$.output_normalization", json)?;
+
+            if normalizer.size() != pipeline.network.output_size() {
+                return Err(DatasetError::NormalizerSizeMismatch {
+                    normalizer_size: normalizer.size(),
+                    given_size: pipeline.network.output_size(),
+                }
+                .into());
+            }
+
+            pipeline.output_normalization = Some((normalization, normalizer));
+        }
+
+        Ok(pipeline)
+    }
+
+    /// Saves the pipeline as JSON, see `to_json`.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), PipelineError> {
+        fs::write(path, self.to_json()?)?;
+        Ok(())
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, PipelineError> {
+        Self::from_json(&fs::read_to_string(path)?)
+    }
+}
+
+fn identity_normalizer(size: usize) -> Normalizer {
+    Normalizer::from_parameters(DVector::zeros(size), DVector::from_element(size, 1.0)).unwrap()
+}
+
+fn normalization_name(normalization: Normalization) -> &'static str {
+    match normalization {
+        Normalization::MinMax => "min_max",
+        Normalization::ZScore => "z_score",
+    }
+}
+
+fn transform_to_json(transform: &Transform) -> Value {
+    match transform {
+        Transform::Normalize { normalization, normalizer } => Value::object(
+            [("type", Value::String("normalize".to_string()))]
+                .into_iter()
+                .chain(normalizer_fields(*normalization, normalizer)),
+        ),
+
+        Transform::Expand(expansion) => Value::object([
+            ("type", Value::String("expand".to_string())),
+            ("input_size", Value::Number(expansion.input_size() as f64)),
+            ("degree", Value::Number(expansion.degree() as f64)),
+            ("interactions_only", Value::Bool(expansion.is_interactions_only())),
+        ]),
+    }
+}
+
+fn normalizer_fields(normalization: Normalization, normalizer: &Normalizer) -> [(&'static str, Value); 3] {
+    [
+        ("normalization", Value::String(normalization_name(normalization).to_string())),
+        ("offset", Value::numbers(normalizer.offset().iter().copied())),
+        ("scale", Value::numbers(normalizer.scale().iter().copied())),
+    ]
+}
+
+/// `next` is what the transform's outputs go to and its input size.
+fn transform_from_json(transform: usize, json: &Value, next: (&'static str, usize)) -> Result<Transform, PipelineError> {
+    let path = format!("$.transforms[{transform}]");
+
+    match json.field(&path, "type")?.as_str(&format!("{path}.type"))? {
+        "normalize" => {
+            let (normalization, normalizer) = normalizer_from_json(&path, json)?;
+            Ok(Transform::Normalize { normalization, normalizer })
+        }
+
+        "expand" => {
+            let input_size = json.field(&path, "input_size")?.as_usize(&format!("{path}.input_size"))?;
+            let degree = json.field(&path, "degree")?.as_usize(&format!("{path}.degree"))?;
+            let interactions_only = json
+                .field(&path, "interactions_only")?
+                .as_bool(&format!("{path}.interactions_only"))?;
+
+            let (next, next_input_size) = next;
+            let output_size = FeatureExpansion::term_count_up_to(input_size, degree, interactions_only, next_input_size);
+            match output_size {
+                None => return Err(PipelineError::ExpansionTooLarge { transform, next, input_size: next_input_size }),
+                // An empty expansion is rejected with `InvalidExpansion` below.
+                Some(output_size) if output_size != 0 && output_size != next_input_size => {
+                    return Err(PipelineError::TransformSizeMismatch { transform, output_size, next, input_size: next_input_size });
+                }
+                Some(_) => {}
+            }
+
+            let expansion = if interactions_only {
+                FeatureExpansion::interactions_only(input_size, degree)?
+            } else {
+                FeatureExpansion::new(input_size, degree)?
+            };
+
+            Ok(Transform::Expand(expansion))
+        }
+
+        name => Err(PipelineError::UnknownTransform {
+            transform,
+            name: name.to_string(),
+        }),
+    }
+}
+
+fn normalizer_from_json(path: &str, json: &Value) -> Result<(Normalization, Normalizer), PipelineError> {
+    let normalization = match json.field(path, "normalization")?.as_str(&format!("{path}.normalization"))? {
+        "min_max" => Normalization::MinMax,
+        "z_score" => Normalization::ZScore,
+        name => return Err(PipelineError::UnknownNormalization(name.to_string())),
+    };
+
+    let offset = json.field(path, "offset")?.as_f32_vec(&format!("{path}.offset"))?;
+    let scale = json.field(path, "scale")?.as_f32_vec(&format!("{path}.scale"))?;
+    let normalizer = Normalizer::from_parameters(DVector::from_vec(offset), DVector::from_vec(scale))?;
+
+    Ok((normalization, normalizer))
+}
@@ -0,0 +1,24 @@
+use nalgebra::RealField;
+
+/// The floating point types networks compute in, `f32` (the default everywhere) and `f64`.
+pub trait Scalar: RealField + Copy + Send + Sync + 'static {
+    /// Converts a constant written as `f64` into the scalar type.
+    #[inline]
+    fn constant(x: f64) -> Self {
+        nalgebra::convert(x)
+    }
+
+    /// Converts a count, e.g. a batch size to divide by.
+    #[inline]
+    fn from_count(n: usize) -> Self {
+        nalgebra::convert(n as f64)
+    }
+
+    /// The value as `f64`, e.g. for error messages and index conversions.
+    #[inline]
+    fn to_f64(self) -> f64 {
+        nalgebra::try_convert(self).unwrap_or(f64::NAN)
+    }
+}
+
+impl<T: RealField + Copy + Send + Sync + 'static> Scalar for T {}
@@ -0,0 +1,87 @@
+//! How the `serde` feature writes nalgebra types: vectors as sequences of numbers and matrices as sequences
+//! of rows, like the crate's own JSON formats, for `#[serde(with = "...")]` on the fields holding them.
+
+pub(crate) mod vector {
+    use nalgebra::DVector;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    use crate::{prelude::*, scalar::Scalar};
+
+    pub fn serialize<T: Scalar + Serialize, S: Serializer>(vector: &DVector<T>, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(vector.iter())
+    }
+
+    pub fn deserialize<'de, T: Scalar + Deserialize<'de>, D: Deserializer<'de>>(deserializer: D) -> Result<DVector<T>, D::Error> {
+        Vec::deserialize(deserializer).map(DVector::from_vec)
+    }
+}
+
+pub(crate) mod option_vector {
+    use nalgebra::DVector;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    use crate::{prelude::*, scalar::Scalar};
+
+    pub fn serialize<T: Scalar + Serialize, S: Serializer>(vector: &Option<DVector<T>>, serializer: S) -> Result<S::Ok, S::Error> {
+        vector.as_ref().map(|vector| vector.as_slice()).serialize(serializer)
+    }
+
+    pub fn deserialize<'de, T: Scalar + Deserialize<'de>, D: Deserializer<'de>>(deserializer: D) -> Result<Option<DVector<T>>, D::Error> {
+        Ok(Option::<Vec<T>>::deserialize(deserializer)?.map(DVector::from_vec))
+    }
+}
+
+pub(crate) mod matrix {
+    use nalgebra::DMatrix;
+    use serde::{de::Error, Deserialize, Deserializer, Serialize, Serializer};
+
+    use crate::{prelude::*, scalar::Scalar};
+
+    pub fn serialize<T: Scalar + Serialize, S: Serializer>(matrix: &DMatrix<T>, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(matrix.row_iter().map(|row| row.iter().cloned().collect::<Vec<T>>()))
+    }
+
+    /// Fails for rows of different lengths.
+    pub fn deserialize<'de, T: Scalar + Deserialize<'de>, D: Deserializer<'de>>(deserializer: D) -> Result<DMatrix<T>, D::Error> {
+        let rows = Vec::<Vec<T>>::deserialize(deserializer)?;
+        let columns = rows.first().map_or(0, Vec::len);
+
+        if rows.iter().any(|row| row.len() != columns) {
+            return Err(D::Error::custom("the rows of a matrix have different lengths"));
+        }
+
+        Ok(DMatrix::from_row_iterator(rows.len(), columns, rows.into_iter().flatten()))
+    }
+}
+
+pub(crate) mod pieces {
+    use nalgebra::{DMatrix, DVector};
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    use crate::prelude::*;
+
+    /// The weights and biases of every piece.
+    type Pieces = Vec<(DMatrix<f32>, DVector<f32>)>;
+
+    #[derive(Serialize, Deserialize)]
+    struct Piece {
+        #[serde(with = "super::matrix")]
+        weights: DMatrix<f32>,
+        #[serde(with = "super::vector")]
+        biases: DVector<f32>,
+    }
+
+    pub fn serialize<S: Serializer>(pieces: &[(DMatrix<f32>, DVector<f32>)], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(pieces.iter().map(|(weights, biases)| Piece {
+            weights: weights.clone(),
+            biases: biases.clone(),
+        }))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Pieces, D::Error> {
+        Ok(Vec::<Piece>::deserialize(deserializer)?
+            .into_iter()
+            .map(|piece| (piece.weights, piece.biases))
+            .collect())
+    }
+}
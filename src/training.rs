@@ -0,0 +1,579 @@
+use std::{ops::ControlFlow, time::Instant};
+
+use rand::{seq::SliceRandom, Rng, RngCore};
+use thiserror::Error;
+
+use crate::{
+    dataset::{class_key, Augment, BalancedSampler, Sample, TrainingData},
+    losses::LossFn,
+    network::{EmaWeights, Network, NetworkError, Optimizer, ParameterSnapshot, Sgd},
+};
+
+use callback::{BatchContext, EpochContext, TrainingCallback};
+use curriculum::Curriculum;
+use gradient_noise::GradientNoise;
+use metric::{Metric, MetricSet};
+use schedule::{OneCycle, ReduceLrOnPlateau};
+use stop::StopToken;
+use history::TrainingHistory;
+
+pub mod background;
+pub mod bagging;
+pub mod callback;
+pub mod checkpoint;
+pub mod cross_validation;
+pub mod curriculum;
+pub mod gradient_noise;
+pub mod history;
+pub mod lr_finder;
+pub mod metric;
+pub mod multi_start;
+pub mod progress;
+pub mod schedule;
+pub mod snapshot_ensemble;
+pub mod stop;
+pub mod tuning;
+
+pub struct Trainer<'a, L: LossFn> {
+    loss: L,
+    rate: f32,
+    epochs: usize,
+    batch_size: Option<usize>,
+    optimizer: Box<dyn Optimizer + 'a>,
+    shuffle: bool,
+    restore_best: bool,
+    augment: Option<Box<dyn Augment + 'a>>,
+    ema_decay: Option<f32>,
+    gradient_noise: Option<GradientNoise>,
+    schedule: Option<OneCycle>,
+    plateau: Option<ReduceLrOnPlateau>,
+    stop_token: Option<StopToken>,
+    curriculum: Option<Curriculum<'a>>,
+    balanced: Option<LabelFn<'a>>,
+    metrics: Vec<(Box<dyn Metric + 'a>, MetricSet)>,
+    callbacks: Vec<Box<dyn TrainingCallback + 'a>>,
+    /// Added to the epochs the callbacks see, for runs made of several `fit`s like a snapshot ensemble's cycles.
+    epoch_offset: usize,
+    /// The epochs of the whole run the callbacks are told about, `epochs` unless several `fit`s make up the run.
+    run_epochs: Option<usize>,
+    /// The moving average of a checkpoint, continued by the next `fit`, see `resume_from`.
+    resumed_ema: Option<EmaWeights>,
+}
+
+/// Gives a sample's class for balanced batches.
+type LabelFn<'a> = Box<dyn Fn(&Sample) -> usize + 'a>;
+
+#[derive(Debug, Error)]
+pub enum TrainingError {
+    #[error("{0}")]
+    NetworkError(#[from] NetworkError),
+
+    #[error("the learning rate range {min_rate}..{max_rate} has to be positive and increasing")]
+    InvalidRateRange {
+        min_rate: f32,
+        max_rate: f32,
+    },
+
+    #[error("at least two steps are needed, but {0} were given")]
+    TooFewSteps(usize),
+
+    #[error("{folds} folds can't be made from {samples} samples, at least two folds and one sample per fold are needed")]
+    InvalidFoldCount {
+        folds: usize,
+        samples: usize,
+    },
+
+    #[error("the size range {min}..={max} of hidden layer {layer} is empty or includes 0")]
+    InvalidHiddenSizeRange {
+        layer: usize,
+        min: usize,
+        max: usize,
+    },
+
+    #[error("the search space has no {0} to choose from")]
+    EmptySearchSpace(&'static str),
+
+    #[error("the schedule's {0} is out of range")]
+    InvalidSchedule(&'static str),
+
+    #[error("step {step} is past the schedule's budget of {total_steps} steps")]
+    ScheduleExhausted {
+        step: usize,
+        total_steps: usize,
+    },
+
+    #[error("gradient noise needs a finite, non-negative eta and gamma, but got eta = {eta} and gamma = {gamma}")]
+    InvalidGradientNoise {
+        eta: f32,
+        gamma: f32,
+    },
+
+    #[error("the temperature has to be positive and finite, but it's {0}")]
+    InvalidTemperature(f32),
+
+    #[error("at least one restart is needed")]
+    NoRestarts,
+}
+
+pub struct TrainingReport {
+    pub history: TrainingHistory,
+    pub stopped_early: bool,
+    /// Whether the stop token ended training. The history only has the epochs that were completed.
+    pub cancelled: bool,
+    /// The number of batches trained, including those of an epoch cut short by the stop token.
+    pub batches: usize,
+    pub best_epoch: Option<usize>,
+    pub best_weights: Option<ParameterSnapshot>,
+    /// The moving average of the parameters when training kept one, see `Trainer::ema`.
+    pub ema_weights: Option<EmaWeights>,
+}
+
+impl<'a, L: LossFn> Trainer<'a, L> {
+    pub fn new(loss: L, rate: f32, epochs: usize) -> Self {
+        Self {
+            loss,
+            rate,
+            epochs,
+            batch_size: None,
+            optimizer: Box::new(Sgd),
+            shuffle: false,
+            restore_best: false,
+            augment: None,
+            ema_decay: None,
+            gradient_noise: None,
+            schedule: None,
+            plateau: None,
+            stop_token: None,
+            curriculum: None,
+            balanced: None,
+            metrics: Vec::new(),
+            callbacks: Vec::new(),
+            epoch_offset: 0,
+            run_epochs: None,
+            resumed_ema: None,
+        }
+    }
+
+    /// Splits every epoch into mini-batches of the given size, by default the whole dataset is one batch.
+    pub fn batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = Some(batch_size.max(1));
+        self
+    }
+
+    /// Steps with the optimizer instead of plain gradient descent, e.g. a `Lookahead`. It's given the rate of
+    /// every batch, and its state carries over from one `fit` to the next.
+    pub fn optimizer(mut self, optimizer: impl Optimizer + 'a) -> Self {
+        self.optimizer = Box::new(optimizer);
+        self
+    }
+
+    /// Shuffles the training set at the start of every epoch.
+    pub fn shuffle(mut self, shuffle: bool) -> Self {
+        self.shuffle = shuffle;
+        self
+    }
+
+    /// Restores the parameters of the epoch with the lowest validation loss (or training loss without
+    /// a validation set) once training finishes. The snapshot is also kept in `TrainingReport::best_weights`.
+    /// Epochs whose loss isn't finite are skipped, so a run that diverges from the start restores nothing.
+    pub fn restore_best(mut self, restore_best: bool) -> Self {
+        self.restore_best = restore_best;
+        self
+    }
+
+    /// Trains on freshly augmented copies of every batch, the dataset itself and the validation set are left as is.
+    /// Corrupting the inputs with e.g. `NoiseAugment` or `MaskAugment` trains the network to denoise them.
+    pub fn augment(mut self, augment: impl Augment + 'a) -> Self {
+        self.augment = Some(Box::new(augment));
+        self
+    }
+
+    /// Keeps an `EmaWeights` average with the given decay, updated after every batch and returned in
+    /// `TrainingReport::ema_weights`. The network itself and its validation losses keep the trained parameters.
+    /// Every `fit` starts a new average, unless `resume_from` loaded one.
+    pub fn ema(mut self, decay: f32) -> Self {
+        self.ema_decay = Some(decay);
+        self
+    }
+
+    /// Adds annealed gaussian noise to the gradient of every batch, drawn from the RNG `fit_with_rng` is given.
+    /// The steps are counted across epochs from the start of every `fit`.
+    pub fn gradient_noise(mut self, noise: GradientNoise) -> Self {
+        self.gradient_noise = Some(noise);
+        self
+    }
+
+    /// Takes the rate of every batch from the one-cycle schedule instead of the fixed rate, counting steps from
+    /// the start of every `fit`. Training stops early, with `TrainingReport::stopped_early` set, before an epoch
+    /// that wouldn't fit in what's left of the schedule's budget.
+    pub fn one_cycle(mut self, schedule: OneCycle) -> Self {
+        self.schedule = Some(schedule);
+        self
+    }
+
+    /// Lowers the rate after every epoch as the plateau scheduler decides, watching the validation loss, or the
+    /// training loss without a validation set. It starts over in every `fit`. The rate of every epoch is in the
+    /// history, see `TrainingHistory::rate_reductions`. A one-cycle schedule takes precedence.
+    pub fn reduce_lr_on_plateau(mut self, plateau: ReduceLrOnPlateau) -> Self {
+        self.plateau = Some(plateau);
+        self
+    }
+
+    /// Checks the token before every batch and returns once it's stopped, with `TrainingReport::cancelled` set.
+    /// Every batch trained was applied completely, and the best weights are still restored if asked for.
+    pub fn stop_token(mut self, stop_token: StopToken) -> Self {
+        self.stop_token = Some(stop_token);
+        self
+    }
+
+    /// Orders every epoch by the curriculum's scores instead of the dataset's order. Shuffling only breaks
+    /// ties of an ordered curriculum, and batches are cut from the ordered epoch.
+    pub fn curriculum(mut self, curriculum: Curriculum<'a>) -> Self {
+        self.curriculum = Some(curriculum);
+        self
+    }
+
+    /// Trains every epoch on a `BalancedSampler` epoch of the dataset, in which every class has as many samples
+    /// as the largest one and batches hold every class about equally often. Classes are taken from the expected
+    /// outputs like in `balanced_weights`. Epochs are longer than the dataset when it's imbalanced, and the
+    /// sampler's order replaces shuffling and a curriculum.
+    pub fn balanced_batches(self) -> Self {
+        self.balanced_batches_by(|sample| class_key(sample) as usize)
+    }
+
+    /// `balanced_batches` with the classes given by `label`.
+    pub fn balanced_batches_by(mut self, label: impl Fn(&Sample) -> usize + 'a) -> Self {
+        self.balanced = Some(Box::new(label));
+        self
+    }
+
+    /// Evaluates the metric on the training set, the validation set or both after every epoch, in registration
+    /// order and before the callbacks, which see the values in `EpochContext::metrics`. The values are recorded
+    /// in the history, see `MetricSet` for their names. The training set value is taken after the epoch's last
+    /// step, so unlike the training loss it isn't an average over the epoch.
+    pub fn metric(mut self, metric: impl Metric + 'a, set: MetricSet) -> Self {
+        self.metrics.push((Box::new(metric), set));
+        self
+    }
+
+    /// Registers a callback that runs after every epoch, in registration order.
+    pub fn callback(mut self, callback: impl TrainingCallback + 'a) -> Self {
+        self.callbacks.push(Box::new(callback));
+        self
+    }
+
+    /// Trains the network for the configured number of epochs. The validation set is evaluated
+    /// after every epoch, unless it is empty, in which case the history has no validation losses.
+    ///
+    /// With the `tracing` feature the run is a `fit` span with an `epoch` span per epoch, and every epoch
+    /// ends with an info event holding the epoch, its losses, gradient norm, rate and `elapsed_ms`. Why a
+    /// run stopped, an empty epoch and a non-finite loss are debug events.
+    pub fn fit(
+        &mut self,
+        network: &mut Network,
+        dataset: &(impl TrainingData + ?Sized),
+        validation: &[Sample],
+    ) -> Result<TrainingReport, NetworkError> {
+        self.fit_with_rng(network, dataset, validation, &mut rand::rng())
+    }
+
+    /// Like `fit`, but shuffles with the given RNG, so seeded runs are reproducible.
+    ///
+    /// A dataset that doesn't store `Sample`s, like `BatchSamples`, is copied into them once if a curriculum,
+    /// balanced batches, augmentation or a metric on the training set needs single samples.
+    pub fn fit_with_rng(
+        &mut self,
+        network: &mut Network,
+        dataset: &(impl TrainingData + ?Sized),
+        validation: &[Sample],
+        rng: &mut impl Rng,
+    ) -> Result<TrainingReport, NetworkError> {
+        let needs_samples = self.curriculum.is_some()
+            || self.balanced.is_some()
+            || self.augment.is_some()
+            || self.metrics.iter().any(|(_, set)| set.includes_train());
+
+        if needs_samples {
+            self.fit_data(network, &*dataset.samples(), validation, rng)
+        } else {
+            self.fit_data(network, dataset, validation, rng)
+        }
+    }
+
+    /// `fit_with_rng` once the dataset has `Sample`s whenever a feature needs them, so `samples` only
+    /// borrows.
+    fn fit_data(
+        &mut self,
+        network: &mut Network,
+        dataset: &(impl TrainingData + ?Sized),
+        validation: &[Sample],
+        rng: &mut impl Rng,
+    ) -> Result<TrainingReport, NetworkError> {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::info_span!("fit", epochs = self.epochs, samples = dataset.len(), batch_size = self.batch_size).entered();
+
+        // The epoch's order of sample indices. Shuffling reshuffles the previous epoch's order.
+        let mut order: Vec<usize> = (0..dataset.len()).collect();
+        let mut scores = Vec::new();
+        let mut report = TrainingReport {
+            history: TrainingHistory::new(),
+            stopped_early: false,
+            cancelled: false,
+            batches: 0,
+            best_epoch: None,
+            best_weights: None,
+            ema_weights: self.ema_weights(network)?,
+        };
+
+        let sampler = self.balanced.as_ref().map(|label| BalancedSampler::with_labels(&dataset.samples(), label));
+        let epoch_len = sampler.as_ref().map_or(dataset.len(), BalancedSampler::epoch_len);
+
+        let mut best_loss = f32::INFINITY;
+        let mut rate = self.rate;
+        let mut plateau = self.plateau;
+        if let Some(plateau) = &mut plateau {
+            plateau.reset();
+        }
+
+        for epoch in 0..self.epochs {
+            let start = Instant::now();
+            #[cfg(feature = "tracing")]
+            let _span = tracing::debug_span!("epoch", epoch = self.epoch_offset + epoch).entered();
+
+            if let Some(schedule) = &self.schedule
+                && report.batches + self.batch_count(epoch_len) > schedule.total_steps()
+            {
+                #[cfg(feature = "tracing")]
+                tracing::debug!(batches = report.batches, "stopped, the schedule has no steps left for another epoch");
+                report.stopped_early = true;
+                break;
+            }
+
+            if let Some(sampler) = &sampler {
+                order = sampler.epoch(rng);
+            } else if let Some(curriculum) = &self.curriculum {
+                if curriculum.rescores_at(epoch) {
+                    scores = curriculum.scores(network, &dataset.samples());
+                }
+
+                order = curriculum.order(&scores, self.shuffle, rng);
+            } else if self.shuffle {
+                order.shuffle(rng);
+            }
+
+            let epoch_result = self.train_epoch(network, dataset, &order, epoch, rate, &mut report, rng);
+
+            let Some((train_loss, gradient_norm, epoch_rate)) = epoch_result? else {
+                #[cfg(feature = "tracing")]
+                tracing::debug!(batches = report.batches, "cancelled by the stop token");
+                report.cancelled = true;
+                break;
+            };
+
+            let validation_loss = if validation.is_empty() {
+                None
+            } else {
+                Some(network.evaluate(validation, &self.loss)?)
+            };
+
+            let metrics = self.evaluate_metrics(network, dataset, validation)?;
+            #[cfg(feature = "tracing")]
+            {
+                tracing::info!(
+                    epoch = self.epoch_offset + epoch,
+                    train_loss,
+                    validation_loss,
+                    gradient_norm,
+                    rate = epoch_rate,
+                    elapsed_ms = start.elapsed().as_secs_f64() * 1000.0,
+                    "epoch",
+                );
+                if !train_loss.is_finite() {
+                    tracing::debug!(epoch = self.epoch_offset + epoch, train_loss, "the training loss isn't finite");
+                }
+            }
+            report.history.push(train_loss, validation_loss, epoch_rate, gradient_norm, start.elapsed());
+            for (name, value) in &metrics {
+                report.history.push_metric(name, *value);
+            }
+
+            let monitored_loss = validation_loss.unwrap_or(train_loss);
+            if let Some(plateau) = &mut plateau {
+                rate = plateau.step(monitored_loss, rate);
+            }
+
+            // Starting from infinity, an epoch whose loss is NaN or infinite is never the best.
+            if self.restore_best && monitored_loss < best_loss {
+                best_loss = monitored_loss;
+                report.best_epoch = Some(epoch);
+                report.best_weights = Some(network.parameter_snapshot());
+            }
+
+            let ctx = EpochContext {
+                epoch: self.epoch_offset + epoch,
+                train_loss,
+                validation_loss,
+                gradient_norm,
+                metrics: &metrics,
+                network,
+            };
+
+            let mut flow = ControlFlow::Continue(());
+            for callback in self.callbacks.iter_mut() {
+                if callback.on_epoch_end(&ctx).is_break() {
+                    flow = ControlFlow::Break(());
+                }
+            }
+
+            if flow.is_break() {
+                #[cfg(feature = "tracing")]
+                tracing::debug!(epoch = self.epoch_offset + epoch, "stopped early by a callback");
+                report.stopped_early = true;
+                break;
+            }
+        }
+
+        if let Some(best_weights) = &report.best_weights {
+            network.restore_snapshot(best_weights)?;
+        }
+
+        Ok(report)
+    }
+
+    /// The moving average a `fit` keeps: a resumed one with the configured decay, or a fresh one.
+    fn ema_weights(&mut self, network: &Network) -> Result<Option<EmaWeights>, NetworkError> {
+        let resumed = self.resumed_ema.take();
+        let Some(decay) = self.ema_decay else {
+            return Ok(None);
+        };
+
+        match resumed {
+            Some(ema_weights) if ema_weights.decay() == decay => Ok(Some(ema_weights)),
+            _ => EmaWeights::new(network, decay).map(Some),
+        }
+    }
+
+    /// The name and value of every metric on the sets it's registered for.
+    fn evaluate_metrics(
+        &self,
+        network: &mut Network,
+        dataset: &(impl TrainingData + ?Sized),
+        validation: &[Sample],
+    ) -> Result<Vec<(String, f32)>, NetworkError> {
+        let mut values = Vec::new();
+
+        for (metric, set) in &self.metrics {
+            if set.includes_train() {
+                values.push((metric.name().to_string(), metric.compute(network, &dataset.samples())?));
+            }
+
+            if set.includes_validation() && !validation.is_empty() {
+                values.push((metric::validation_name(metric.name()), metric.compute(network, validation)?));
+            }
+        }
+
+        Ok(values)
+    }
+
+    /// The number of samples in an epoch of `dataset`, more than it holds with balanced batches.
+    fn epoch_len(&self, dataset: &(impl TrainingData + ?Sized)) -> usize {
+        match &self.balanced {
+            Some(label) => BalancedSampler::with_labels(&dataset.samples(), label).epoch_len(),
+            None => dataset.len(),
+        }
+    }
+
+    fn batch_count(&self, samples: usize) -> usize {
+        samples.div_ceil(self.batch_size.unwrap_or(samples).max(1))
+    }
+
+    /// The epoch's mean loss, the mean over its batches of the applied gradient's norm and the rate of its last
+    /// batch, or `None` if the stop token ended it. The epoch goes through the samples at the indices of `order`.
+    /// Every batch counts towards `report.batches`.
+    #[allow(clippy::too_many_arguments)]
+    fn train_epoch(
+        &mut self,
+        network: &mut Network,
+        dataset: &(impl TrainingData + ?Sized),
+        order: &[usize],
+        epoch: usize,
+        mut rate: f32,
+        report: &mut TrainingReport,
+        rng: &mut impl Rng,
+    ) -> Result<Option<(f32, f32, f32)>, NetworkError> {
+        let epoch_len = order.len();
+        if epoch_len == 0 {
+            #[cfg(feature = "tracing")]
+            tracing::debug!("no steps, the epoch is empty");
+            return Ok(Some((0.0, 0.0, rate)));
+        }
+
+        let batch_size = self.batch_size.unwrap_or(epoch_len);
+        let batches = epoch_len.div_ceil(batch_size);
+        let mut total_loss = 0.0;
+        let mut total_gradient_norm = 0.0;
+
+        for index in 0..batches {
+            if self.stop_token.as_ref().is_some_and(StopToken::is_stopped) {
+                return Ok(None);
+            }
+
+            if let Some(schedule) = &self.schedule {
+                rate = schedule.rate_at(report.batches).expect("epochs past the budget aren't started");
+            }
+
+            let range = index * batch_size..((index + 1) * batch_size).min(epoch_len);
+            let (batch_loss, gradient_norm) = self.train_batch(network, dataset, &order[range.clone()], rate, report, rng)?;
+
+            let ctx = BatchContext {
+                epoch: self.epoch_offset + epoch,
+                batch: index,
+                batches: Some(batches),
+                epochs: Some(self.run_epochs.unwrap_or(self.epochs)),
+                loss: batch_loss,
+            };
+
+            for callback in self.callbacks.iter_mut() {
+                callback.on_batch_end(&ctx);
+            }
+
+            total_loss += batch_loss * range.len() as f32;
+            total_gradient_norm += gradient_norm;
+        }
+
+        Ok(Some((total_loss / epoch_len as f32, total_gradient_norm / batches as f32, rate)))
+    }
+
+    /// One step on the samples at `indices`, augmented if asked for, returning their mean loss and gradient norm.
+    fn train_batch(
+        &mut self,
+        network: &mut Network,
+        dataset: &(impl TrainingData + ?Sized),
+        indices: &[usize],
+        rate: f32,
+        report: &mut TrainingReport,
+        rng: &mut impl Rng,
+    ) -> Result<(f32, f32), NetworkError> {
+        let gradient_noise = self.gradient_noise.map(|noise| noise.std_dev(report.batches));
+
+        let step = match &self.augment {
+            Some(augment) => {
+                let samples = dataset.samples();
+                let augmented: Vec<Sample> = indices.iter().map(|&index| augment.augment(&samples[index], rng)).collect();
+                let gradient_noise = gradient_noise.map(|std_dev| (std_dev, &mut *rng as &mut dyn RngCore));
+                network.learn_with_gradient_norm(&augmented, &self.loss, rate, &mut *self.optimizer, gradient_noise)?
+            }
+            None => {
+                let gradient_noise = gradient_noise.map(|std_dev| (std_dev, &mut *rng as &mut dyn RngCore));
+                dataset.learn_indices(network, indices, &self.loss, rate, &mut *self.optimizer, gradient_noise)?
+            }
+        };
+        report.batches += 1;
+
+        if let Some(ema_weights) = &mut report.ema_weights {
+            ema_weights.update(network)?;
+        }
+
+        Ok(step)
+    }
+}
@@ -0,0 +1,252 @@
+//! Training on a worker thread, for interactive programs whose render loop can't wait for `learn`.
+
+use std::{
+    sync::{
+        mpsc::{self, Receiver, RecvError, Sender, TryRecvError},
+        Arc, Mutex, MutexGuard, PoisonError,
+    },
+    thread::{self, JoinHandle},
+};
+
+use crate::{
+    dataset::Sample,
+    losses::LossFn,
+    network::{Network, NetworkError},
+};
+
+use super::stop::StopToken;
+
+/// Published by the worker after every round of epochs.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Progress {
+    /// The number of epochs trained so far.
+    pub epoch: usize,
+    /// The mean loss of the round's last epoch.
+    pub loss: f32,
+}
+
+enum Command {
+    AddSamples(Vec<Sample>),
+    SetSamples(Vec<Sample>),
+    Pause,
+    Resume,
+    Stop,
+}
+
+/// What the worker published last. Every round replaces it, so a caller that doesn't look for a while
+/// misses rounds instead of queuing them up.
+struct Published {
+    network: Network,
+    progress: Option<Progress>,
+}
+
+/// Owns a network that a worker thread trains with full-batch `learn` steps, one per epoch, on a dataset
+/// that can grow while it runs. After every `epochs_per_update` epochs the worker publishes a copy of the
+/// network and its `Progress`, replacing the last ones. The worker idles while paused or while the dataset
+/// is empty, and it stops when the trainer is stopped or dropped, or when its `stop_token` is stopped. The
+/// token is checked between epochs, so stopping doesn't wait for the rest of the round.
+pub struct BackgroundTrainer {
+    commands: Sender<Command>,
+    latest: Arc<Mutex<Published>>,
+    input_size: usize,
+    output_size: usize,
+    stop_token: StopToken,
+    worker: Option<JoinHandle<Result<Network, NetworkError>>>,
+}
+
+impl BackgroundTrainer {
+    pub fn spawn<L>(network: Network, dataset: Vec<Sample>, loss: L, rate: f32, epochs_per_update: usize) -> Result<Self, NetworkError>
+    where
+        L: LossFn + Send + 'static,
+    {
+        network.check_dataset(&dataset)?;
+        network.check_loss(&loss)?;
+
+        let (commands, command_receiver) = mpsc::channel();
+        let latest = Arc::new(Mutex::new(Published {
+            network: network.clone(),
+            progress: None,
+        }));
+        let stop_token = StopToken::new();
+
+        let worker = Worker {
+            network,
+            dataset,
+            loss,
+            rate,
+            epochs_per_update: epochs_per_update.max(1),
+            commands: command_receiver,
+            latest: Arc::clone(&latest),
+            stop_token: stop_token.clone(),
+        };
+
+        Ok(Self {
+            commands,
+            input_size: worker.network.input_size(),
+            output_size: worker.network.output_size(),
+            latest,
+            stop_token,
+            worker: Some(thread::spawn(move || worker.run())),
+        })
+    }
+
+    /// Adds samples to the dataset the worker trains on, from its next round on. Fails with a
+    /// `SampleSizeMismatch` naming the index in `samples` if one doesn't fit the network.
+    pub fn add_samples(&self, samples: Vec<Sample>) -> Result<(), NetworkError> {
+        self.check_samples(&samples)?;
+        self.send(Command::AddSamples(samples));
+        Ok(())
+    }
+
+    pub fn add_sample(&self, sample: Sample) -> Result<(), NetworkError> {
+        self.add_samples(vec![sample])
+    }
+
+    /// Replaces the dataset the worker trains on.
+    pub fn set_samples(&self, samples: Vec<Sample>) -> Result<(), NetworkError> {
+        self.check_samples(&samples)?;
+        self.send(Command::SetSamples(samples));
+        Ok(())
+    }
+
+    pub fn pause(&self) {
+        self.send(Command::Pause);
+    }
+
+    pub fn resume(&self) {
+        self.send(Command::Resume);
+    }
+
+    /// The progress of the worker's last round, `None` before the first. Its epoch tells whether the
+    /// network changed since an earlier call.
+    pub fn progress(&self) -> Option<Progress> {
+        self.latest().progress
+    }
+
+    /// Calls `f` with the network as of the worker's last round, without copying it. The worker waits to
+    /// publish its next round until `f` returns.
+    pub fn with_network<R>(&self, f: impl FnOnce(&Network) -> R) -> R {
+        f(&self.latest().network)
+    }
+
+    /// A copy of the network as of the worker's last round.
+    pub fn network(&self) -> Network {
+        self.with_network(Network::clone)
+    }
+
+    /// A token that stops the worker after its current epoch, e.g. to hand to code that can't own the trainer.
+    /// The trained network is still returned by `stop`.
+    pub fn stop_token(&self) -> StopToken {
+        self.stop_token.clone()
+    }
+
+    /// Whether the worker still runs. It only stops on its own if a training step fails, `stop` returns the error.
+    pub fn is_running(&self) -> bool {
+        self.worker.as_ref().is_some_and(|worker| !worker.is_finished())
+    }
+
+    /// Stops the worker after its current round and returns the trained network, or the error that stopped it.
+    /// A panic of the worker is resumed here.
+    pub fn stop(mut self) -> Result<Network, NetworkError> {
+        match self.join() {
+            Ok(result) => result,
+            Err(panic) => std::panic::resume_unwind(panic),
+        }
+    }
+
+    /// Stops and joins the worker, returning what it returned or its panic.
+    fn join(&mut self) -> thread::Result<Result<Network, NetworkError>> {
+        self.stop_token.stop();
+        self.send(Command::Stop);
+        self.worker.take().expect("the worker is only joined once").join()
+    }
+
+    /// A panic while publishing happens before the network is replaced, so a poisoned lock still holds a
+    /// whole network.
+    fn latest(&self) -> MutexGuard<'_, Published> {
+        self.latest.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Commands to a worker that has already stopped are dropped.
+    fn send(&self, command: Command) {
+        let _ = self.commands.send(command);
+    }
+
+    fn check_samples(&self, samples: &[Sample]) -> Result<(), NetworkError> {
+        let mismatch = samples
+            .iter()
+            .position(|sample| sample.inputs().len() != self.input_size || sample.expected_outputs().len() != self.output_size);
+
+        match mismatch {
+            Some(index) => Err(NetworkError::SampleSizeMismatch {
+                index,
+                inputs: samples[index].inputs().len(),
+                outputs: samples[index].expected_outputs().len(),
+                network_inputs: self.input_size,
+                network_outputs: self.output_size,
+            }),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Stops the worker and waits for it. Its result is dropped, and so is its panic, as dropping may already
+/// happen while unwinding.
+impl Drop for BackgroundTrainer {
+    fn drop(&mut self) {
+        if self.worker.is_some() {
+            let _ = self.join();
+        }
+    }
+}
+
+struct Worker<L> {
+    network: Network,
+    dataset: Vec<Sample>,
+    loss: L,
+    rate: f32,
+    epochs_per_update: usize,
+    commands: Receiver<Command>,
+    latest: Arc<Mutex<Published>>,
+    stop_token: StopToken,
+}
+
+impl<L: LossFn> Worker<L> {
+    fn run(mut self) -> Result<Network, NetworkError> {
+        let mut paused = false;
+        let mut epoch = 0;
+
+        loop {
+            let command = if paused || self.dataset.is_empty() {
+                self.commands.recv().map_err(|RecvError| TryRecvError::Disconnected)
+            } else {
+                self.commands.try_recv()
+            };
+
+            match command {
+                Ok(Command::AddSamples(samples)) => self.dataset.extend(samples),
+                Ok(Command::SetSamples(samples)) => self.dataset = samples,
+                Ok(Command::Pause) => paused = true,
+                Ok(Command::Resume) => paused = false,
+                Ok(Command::Stop) | Err(TryRecvError::Disconnected) => return Ok(self.network),
+                Err(TryRecvError::Empty) => {
+                    let mut loss = 0.0;
+                    let mut epochs = 0;
+                    while epochs < self.epochs_per_update && !self.stop_token.is_stopped() {
+                        loss = self.network.learn(&self.dataset, &self.loss, self.rate)?;
+                        epochs += 1;
+                    }
+
+                    if epochs == 0 {
+                        return Ok(self.network);
+                    }
+
+                    epoch += epochs;
+                    let mut latest = self.latest.lock().unwrap_or_else(PoisonError::into_inner);
+                    latest.network.clone_from(&self.network);
+                    latest.progress = Some(Progress { epoch, loss });
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,120 @@
+use nalgebra::DVector;
+use rand::Rng;
+
+use crate::{
+    dataset::Sample,
+    losses::LossFn,
+    network::{Ensemble, Network, NetworkError},
+};
+
+use super::{Trainer, TrainingError};
+
+pub struct Bagging {
+    pub ensemble: Ensemble,
+    /// The indices of the samples every member was trained on, drawn with replacement.
+    pub bags: Vec<Vec<usize>>,
+    /// Every member's mean loss on the samples missing from its bag, `None` if its bag holds all of them.
+    pub member_oob_losses: Vec<Option<f32>>,
+    /// The mean loss over the samples missing from at least one bag, each predicted by the mean output of
+    /// the members that didn't see it. `None` if every sample was in every bag.
+    pub oob_loss: Option<f32>,
+    /// The number of samples `oob_loss` was computed on.
+    pub oob_samples: usize,
+}
+
+/// Bootstrap aggregating: every one of `members` fresh networks from `factory` is trained on a bootstrap
+/// resample of the dataset, as many samples drawn with replacement, so each sees about 63% of the distinct
+/// samples. Every member trains with a fresh trainer from `trainer`, so no optimizer or callback state carries
+/// over from one member to the next. The members are combined into an equally weighted ensemble.
+pub fn bag<'t, L, F, T>(
+    dataset: &[Sample],
+    members: usize,
+    mut factory: F,
+    trainer: T,
+    rng: &mut impl Rng,
+) -> Result<Bagging, TrainingError>
+where
+    L: LossFn + 't,
+    F: FnMut() -> Result<Network, NetworkError>,
+    T: Fn() -> Trainer<'t, L>,
+{
+    if dataset.is_empty() {
+        return Err(NetworkError::EmptyDataset.into());
+    }
+
+    let mut networks = Vec::with_capacity(members);
+    let mut bags = Vec::with_capacity(members);
+
+    for _ in 0..members {
+        let bag: Vec<usize> = (0..dataset.len()).map(|_| rng.random_range(0..dataset.len())).collect();
+        let resample: Vec<Sample> = bag.iter().map(|&i| dataset[i].clone()).collect();
+
+        let mut trainer = trainer();
+        let mut network = factory()?;
+        trainer.fit_with_rng(&mut network, &resample, &[], rng)?;
+
+        networks.push(network);
+        bags.push(bag);
+    }
+
+    let ensemble = Ensemble::new(networks)?;
+    let loss = trainer().loss;
+
+    let in_bag: Vec<Vec<bool>> = bags
+        .iter()
+        .map(|bag| {
+            let mut in_bag = vec![false; dataset.len()];
+            bag.iter().for_each(|&i| in_bag[i] = true);
+            in_bag
+        })
+        .collect();
+
+    let member_oob_losses = ensemble
+        .networks()
+        .iter()
+        .zip(in_bag.iter())
+        .map(|(network, in_bag)| {
+            let held_out: Vec<Sample> = dataset
+                .iter()
+                .zip(in_bag.iter())
+                .filter(|&(_, &in_bag)| !in_bag)
+                .map(|(sample, _)| sample.clone())
+                .collect();
+
+            if held_out.is_empty() {
+                Ok(None)
+            } else {
+                network.evaluate(&held_out, &loss).map(Some)
+            }
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut total_loss = 0.0;
+    let mut oob_samples = 0;
+
+    for (i, sample) in dataset.iter().enumerate() {
+        let mut mean = DVector::zeros(ensemble.output_size());
+        let mut voters = 0;
+
+        for (network, in_bag) in ensemble.networks().iter().zip(in_bag.iter()) {
+            if !in_bag[i] {
+                mean += network.predict(sample.inputs())?;
+                voters += 1;
+            }
+        }
+
+        if voters > 0 {
+            mean /= voters as f32;
+            total_loss += loss.apply(mean.as_view(), sample.expected_outputs()).map_err(NetworkError::from)?;
+            oob_samples += 1;
+        }
+    }
+
+    Ok(Bagging {
+        ensemble,
+        bags,
+        member_oob_losses,
+        oob_loss: (oob_samples > 0).then(|| total_loss / oob_samples as f32),
+        oob_samples,
+    })
+}
@@ -0,0 +1,214 @@
+use std::ops::ControlFlow;
+
+use crate::network::Network;
+
+pub struct EpochContext<'a> {
+    pub epoch: usize,
+    pub train_loss: f32,
+    pub validation_loss: Option<f32>,
+    /// The mean over the epoch's batches of the norm of the gradient each step applied.
+    pub gradient_norm: f32,
+    /// The name and value of every metric the trainer evaluated, see `Trainer::metric`.
+    pub metrics: &'a [(String, f32)],
+    pub network: &'a Network,
+}
+
+impl EpochContext<'_> {
+    /// The value of the metric recorded under `name`, e.g. `validation_accuracy`.
+    pub fn metric(&self, name: &str) -> Option<f32> {
+        self.metrics.iter().find(|(metric, _)| metric == name).map(|&(_, value)| value)
+    }
+}
+
+/// What a callback learns after every batch.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BatchContext {
+    pub epoch: usize,
+    /// The batch's index within the epoch.
+    pub batch: usize,
+    /// The number of batches per epoch, if it's known up front.
+    pub batches: Option<usize>,
+    /// The number of epochs training runs for at most, if it's known up front.
+    pub epochs: Option<usize>,
+    /// The batch's mean loss before its step.
+    pub loss: f32,
+}
+
+pub trait TrainingCallback {
+    /// Called after every epoch, returning `ControlFlow::Break` stops the training.
+    fn on_epoch_end(&mut self, ctx: &EpochContext) -> ControlFlow<()>;
+
+    /// Called after every batch's step, e.g. for progress displays. Does nothing by default.
+    fn on_batch_end(&mut self, ctx: &BatchContext) {}
+}
+
+impl<C: TrainingCallback + ?Sized> TrainingCallback for &mut C {
+    fn on_epoch_end(&mut self, ctx: &EpochContext) -> ControlFlow<()> {
+        (**self).on_epoch_end(ctx)
+    }
+
+    fn on_batch_end(&mut self, ctx: &BatchContext) {
+        (**self).on_batch_end(ctx)
+    }
+}
+
+impl<C: TrainingCallback + ?Sized> TrainingCallback for Box<C> {
+    fn on_epoch_end(&mut self, ctx: &EpochContext) -> ControlFlow<()> {
+        (**self).on_epoch_end(ctx)
+    }
+
+    fn on_batch_end(&mut self, ctx: &BatchContext) {
+        (**self).on_batch_end(ctx)
+    }
+}
+
+/// Prints the losses of every `every`-th epoch to stdout.
+pub struct StdoutLogger {
+    every: usize,
+}
+
+impl StdoutLogger {
+    pub fn new(every: usize) -> Self {
+        Self { every: every.max(1) }
+    }
+}
+
+impl Default for StdoutLogger {
+    fn default() -> Self {
+        Self::new(1)
+    }
+}
+
+impl TrainingCallback for StdoutLogger {
+    fn on_epoch_end(&mut self, ctx: &EpochContext) -> ControlFlow<()> {
+        if ctx.epoch.is_multiple_of(self.every) {
+            match ctx.validation_loss {
+                Some(validation_loss) => println!(
+                    "epoch {}: train loss {}, validation loss {}",
+                    ctx.epoch, ctx.train_loss, validation_loss
+                ),
+                None => println!("epoch {}: train loss {}", ctx.epoch, ctx.train_loss),
+            }
+        }
+
+        ControlFlow::Continue(())
+    }
+}
+
+/// Remembers the epoch with the lowest loss, preferring the validation loss when there is one.
+/// With a patience set, it stops the training once the loss hasn't improved for that many epochs. The state
+/// is kept from one `fit` to the next, where the epochs count from 0 again.
+#[derive(Default)]
+pub struct BestLossTracker {
+    best: Option<(usize, f32)>,
+    patience: Option<usize>,
+}
+
+impl BestLossTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_patience(patience: usize) -> Self {
+        Self {
+            best: None,
+            patience: Some(patience),
+        }
+    }
+
+    pub fn best_epoch(&self) -> Option<usize> {
+        self.best.map(|(epoch, _)| epoch)
+    }
+
+    pub fn best_loss(&self) -> Option<f32> {
+        self.best.map(|(_, loss)| loss)
+    }
+}
+
+impl TrainingCallback for BestLossTracker {
+    fn on_epoch_end(&mut self, ctx: &EpochContext) -> ControlFlow<()> {
+        let loss = ctx.validation_loss.unwrap_or(ctx.train_loss);
+
+        match self.best {
+            Some((_, best_loss)) if loss >= best_loss || loss.is_nan() => {}
+            _ => self.best = Some((ctx.epoch, loss)),
+        }
+
+        if let (Some(patience), Some((best_epoch, _))) = (self.patience, self.best)
+            && ctx.epoch.saturating_sub(best_epoch) >= patience
+        {
+            return ControlFlow::Break(());
+        }
+
+        ControlFlow::Continue(())
+    }
+}
+
+/// Remembers the epoch with the best value of a named metric, like `BestLossTracker` does for the loss, e.g.
+/// to stop once `validation_accuracy` stops rising. Epochs without the metric are ignored, but still count
+/// towards the patience. Like `BestLossTracker`, the state is kept from one `fit` to the next.
+pub struct BestMetricTracker {
+    metric: String,
+    higher_is_better: bool,
+    best: Option<(usize, f32)>,
+    patience: Option<usize>,
+}
+
+impl BestMetricTracker {
+    /// Tracks the lowest value of the metric, like for losses.
+    pub fn minimizing(metric: impl Into<String>) -> Self {
+        Self::new(metric.into(), false)
+    }
+
+    /// Tracks the highest value of the metric, like for accuracy.
+    pub fn maximizing(metric: impl Into<String>) -> Self {
+        Self::new(metric.into(), true)
+    }
+
+    fn new(metric: String, higher_is_better: bool) -> Self {
+        Self {
+            metric,
+            higher_is_better,
+            best: None,
+            patience: None,
+        }
+    }
+
+    /// Stops the training once the metric hasn't improved for `patience` epochs.
+    pub fn with_patience(mut self, patience: usize) -> Self {
+        self.patience = Some(patience);
+        self
+    }
+
+    pub fn best_epoch(&self) -> Option<usize> {
+        self.best.map(|(epoch, _)| epoch)
+    }
+
+    pub fn best_value(&self) -> Option<f32> {
+        self.best.map(|(_, value)| value)
+    }
+}
+
+impl TrainingCallback for BestMetricTracker {
+    fn on_epoch_end(&mut self, ctx: &EpochContext) -> ControlFlow<()> {
+        if let Some(value) = ctx.metric(&self.metric) {
+            let improved = match self.best {
+                _ if value.is_nan() => false,
+                Some((_, best)) if self.higher_is_better => value > best,
+                Some((_, best)) => value < best,
+                None => true,
+            };
+
+            if improved {
+                self.best = Some((ctx.epoch, value));
+            }
+        }
+
+        let since = self.best.map_or(ctx.epoch + 1, |(best_epoch, _)| ctx.epoch.saturating_sub(best_epoch));
+        if self.patience.is_some_and(|patience| since >= patience) {
+            return ControlFlow::Break(());
+        }
+
+        ControlFlow::Continue(())
+    }
+}
@@ -0,0 +1,206 @@
+//! Checkpoints for resuming training, written by `Trainer::save_checkpoint`, all numbers little-endian:
+//!
+//! - the magic bytes `NRCK` and the format version as a `u32`
+//! - the network in the binary model format of `Network::save`
+//! - the optimizer state: its kind, the buffer count as a `u32` and per buffer its name, the value count as a
+//!   `u32` and the values as `f32`s, then the counter count as a `u32` and per counter its name and a `u64`
+//! - a `u8` flag that's 1 if there's a moving average of the parameters, followed by its decay as an `f32`,
+//!   the value count as a `u32` and the values as `f32`s
+//!
+//! Names and kinds are a `u32` byte length followed by UTF-8. The optimizer state and the average are
+//! checked against the network when the checkpoint is resumed.
+
+use std::{
+    fs::File,
+    io::{self, BufReader, BufWriter, Read, Write},
+    path::Path,
+};
+
+use thiserror::Error;
+
+use crate::{
+    losses::LossFn,
+    network::{EmaWeights, Network, NetworkError, NetworkLoadError, OptimizerState},
+};
+
+use super::Trainer;
+
+const MAGIC: &[u8; 4] = b"NRCK";
+pub const CHECKPOINT_VERSION: u32 = 1;
+
+#[derive(Debug, Error)]
+pub enum CheckpointError {
+    #[error("{0}")]
+    Io(#[from] io::Error),
+
+    #[error("{0}")]
+    NetworkLoadError(#[from] NetworkLoadError),
+
+    #[error("{0}")]
+    NetworkError(#[from] NetworkError),
+
+    #[error("the file isn't a checkpoint")]
+    BadMagic,
+
+    #[error("the checkpoint has format version {found}, but only versions up to {supported} are supported")]
+    UnsupportedVersion {
+        found: u32,
+        supported: u32,
+    },
+
+    #[error("the checkpoint is truncated")]
+    Truncated,
+
+    #[error("a name in the optimizer state isn't valid UTF-8")]
+    InvalidName,
+
+    #[error("the moving average flag {0} is neither 0 nor 1")]
+    InvalidEmaFlag(u8),
+}
+
+impl<'a, L: LossFn> Trainer<'a, L> {
+    /// Saves the network with the optimizer's state and, if given, the moving average of the parameters a `fit`
+    /// returned in `TrainingReport::ema_weights`, see the module documentation. Resuming the checkpoint with
+    /// `resume_from` continues training as if it hadn't stopped. Fails with `io::ErrorKind::InvalidInput` if
+    /// the network has layers other than dense and maxout ones, like `Network::save`.
+    pub fn save_checkpoint(&self, path: impl AsRef<Path>, network: &Network, ema_weights: Option<&EmaWeights>) -> Result<(), CheckpointError> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        writer.write_all(MAGIC)?;
+        write_u32(&mut writer, CHECKPOINT_VERSION)?;
+        network.write_to(&mut writer)?;
+
+        let state = self.optimizer.save_state();
+        write_name(&mut writer, &state.kind)?;
+        write_len(&mut writer, state.buffers.len())?;
+        for (name, values) in &state.buffers {
+            write_name(&mut writer, name)?;
+            write_values(&mut writer, values)?;
+        }
+
+        write_len(&mut writer, state.counters.len())?;
+        for (name, value) in &state.counters {
+            write_name(&mut writer, name)?;
+            writer.write_all(&value.to_le_bytes())?;
+        }
+
+        match ema_weights {
+            Some(ema_weights) => {
+                writer.write_all(&[1])?;
+                writer.write_all(&ema_weights.decay().to_le_bytes())?;
+                write_values(&mut writer, ema_weights.average())?;
+            }
+            None => writer.write_all(&[0])?,
+        }
+
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// Loads a checkpoint of `save_checkpoint`, returning its network and giving its state to the optimizer.
+    /// The next `fit` continues the checkpoint's moving average if the trainer keeps one with the same decay.
+    /// Fails with `OptimizerKindMismatch` if the checkpoint is of another kind of optimizer and with
+    /// `OptimizerStateMismatch` or `ParameterCountMismatch` if its state doesn't fit its network, leaving the
+    /// trainer as it was.
+    pub fn resume_from(&mut self, path: impl AsRef<Path>) -> Result<Network, CheckpointError> {
+        let mut reader = BufReader::new(File::open(path)?);
+
+        let mut magic = [0; 4];
+        read_exact(&mut reader, &mut magic)?;
+        if &magic != MAGIC {
+            return Err(CheckpointError::BadMagic);
+        }
+
+        let version = read_u32(&mut reader)?;
+        if version == 0 || version > CHECKPOINT_VERSION {
+            return Err(CheckpointError::UnsupportedVersion {
+                found: version,
+                supported: CHECKPOINT_VERSION,
+            });
+        }
+
+        let network = Network::read_from(&mut reader)?;
+
+        let mut state = OptimizerState::new(read_name(&mut reader)?);
+        for _ in 0..read_u32(&mut reader)? {
+            let name = read_name(&mut reader)?;
+            state.buffers.push((name, read_values(&mut reader)?));
+        }
+
+        for _ in 0..read_u32(&mut reader)? {
+            let name = read_name(&mut reader)?;
+            let mut value = [0; 8];
+            read_exact(&mut reader, &mut value)?;
+            state.counters.push((name, u64::from_le_bytes(value)));
+        }
+
+        let mut flag = [0];
+        read_exact(&mut reader, &mut flag)?;
+        let ema_weights = match flag[0] {
+            0 => None,
+            1 => {
+                let mut decay = [0; 4];
+                read_exact(&mut reader, &mut decay)?;
+                Some(EmaWeights::with_average(&network, f32::from_le_bytes(decay), read_values(&mut reader)?)?)
+            }
+            flag => return Err(CheckpointError::InvalidEmaFlag(flag)),
+        };
+
+        self.optimizer.load_state(&state, &network)?;
+        self.resumed_ema = ema_weights;
+        Ok(network)
+    }
+}
+
+fn write_u32(writer: &mut impl Write, value: u32) -> io::Result<()> {
+    writer.write_all(&value.to_le_bytes())
+}
+
+fn write_len(writer: &mut impl Write, len: usize) -> io::Result<()> {
+    let len = u32::try_from(len).map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "too many values for a checkpoint"))?;
+    write_u32(writer, len)
+}
+
+fn write_name(writer: &mut impl Write, name: &str) -> io::Result<()> {
+    write_len(writer, name.len())?;
+    writer.write_all(name.as_bytes())
+}
+
+fn write_values(writer: &mut impl Write, values: &[f32]) -> io::Result<()> {
+    write_len(writer, values.len())?;
+    values.iter().try_for_each(|value| writer.write_all(&value.to_le_bytes()))
+}
+
+fn read_exact(reader: &mut impl Read, buffer: &mut [u8]) -> Result<(), CheckpointError> {
+    reader.read_exact(buffer).map_err(|error| match error.kind() {
+        io::ErrorKind::UnexpectedEof => CheckpointError::Truncated,
+        _ => error.into(),
+    })
+}
+
+/// Reads exactly `len` bytes without allocating them upfront, so a corrupted length fails as a truncated
+/// file instead of exhausting memory.
+fn read_to(reader: &mut impl Read, len: usize) -> Result<Vec<u8>, CheckpointError> {
+    let mut bytes = Vec::new();
+    if reader.take(len as u64).read_to_end(&mut bytes)? != len {
+        return Err(CheckpointError::Truncated);
+    }
+
+    Ok(bytes)
+}
+
+fn read_u32(reader: &mut impl Read) -> Result<u32, CheckpointError> {
+    let mut bytes = [0; 4];
+    read_exact(reader, &mut bytes)?;
+    Ok(u32::from_le_bytes(bytes))
+}
+
+fn read_name(reader: &mut impl Read) -> Result<String, CheckpointError> {
+    let len = read_u32(reader)? as usize;
+    String::from_utf8(read_to(reader, len)?).map_err(|_| CheckpointError::InvalidName)
+}
+
+fn read_values(reader: &mut impl Read) -> Result<Vec<f32>, CheckpointError> {
+    let len = read_u32(reader)? as usize;
+    let bytes = read_to(reader, len.checked_mul(4).ok_or(CheckpointError::Truncated)?)?;
+    Ok(bytes.chunks_exact(4).map(|value| f32::from_le_bytes(value.try_into().unwrap())).collect())
+}
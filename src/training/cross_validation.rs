@@ -0,0 +1,81 @@
+use rand::{seq::SliceRandom, Rng};
+
+use crate::{
+    dataset::Sample,
+    losses::LossFn,
+    network::{Network, NetworkError},
+};
+
+use super::{Trainer, TrainingError};
+
+#[derive(Clone, Debug)]
+pub struct CrossValidation {
+    pub fold_losses: Vec<f32>,
+    pub mean_loss: f32,
+    pub std_dev: f32,
+    /// The indices of the samples every fold was validated on, so every sample is in exactly one of them.
+    pub folds: Vec<Vec<usize>>,
+}
+
+/// k-fold cross-validation: the shuffled dataset is split into `folds` parts whose sizes differ by at most
+/// one, and for every part a fresh network from `factory` is trained on the rest and evaluated on it. Every
+/// fold trains with a fresh trainer from `trainer`, so no optimizer or callback state carries over from one
+/// fold to the next.
+pub fn cross_validate<'t, L, F, T>(
+    dataset: &[Sample],
+    folds: usize,
+    mut factory: F,
+    trainer: T,
+    rng: &mut impl Rng,
+) -> Result<CrossValidation, TrainingError>
+where
+    L: LossFn + 't,
+    F: FnMut() -> Result<Network, NetworkError>,
+    T: Fn() -> Trainer<'t, L>,
+{
+    if folds < 2 || folds > dataset.len() {
+        return Err(TrainingError::InvalidFoldCount {
+            folds,
+            samples: dataset.len(),
+        });
+    }
+
+    let mut order: Vec<usize> = (0..dataset.len()).collect();
+    order.shuffle(rng);
+
+    let mut fold_losses = Vec::with_capacity(folds);
+    let mut fold_indices = Vec::with_capacity(folds);
+
+    for (start, end) in fold_bounds(order.len(), folds) {
+        let validation: Vec<Sample> = order[start..end].iter().map(|&i| dataset[i].clone()).collect();
+        let training: Vec<Sample> = order[..start]
+            .iter()
+            .chain(order[end..].iter())
+            .map(|&i| dataset[i].clone())
+            .collect();
+
+        let mut trainer = trainer();
+        let mut network = factory()?;
+        trainer.fit_with_rng(&mut network, &training, &[], rng)?;
+        fold_losses.push(network.evaluate(&validation, &trainer.loss)?);
+        fold_indices.push(order[start..end].to_vec());
+    }
+
+    let mean_loss = fold_losses.iter().sum::<f32>() / folds as f32;
+    let variance = fold_losses
+        .iter()
+        .map(|loss| (loss - mean_loss) * (loss - mean_loss))
+        .sum::<f32>() / folds as f32;
+
+    Ok(CrossValidation {
+        fold_losses,
+        mean_loss,
+        std_dev: variance.sqrt(),
+        folds: fold_indices,
+    })
+}
+
+/// The `[start, end)` range of every fold, covering `0..len` without overlap.
+fn fold_bounds(len: usize, folds: usize) -> impl Iterator<Item = (usize, usize)> {
+    (0..folds).map(move |fold| (fold * len / folds, (fold + 1) * len / folds))
+}
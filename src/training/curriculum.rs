@@ -0,0 +1,113 @@
+//! Curriculum learning: every epoch presents the samples in an order given by a difficulty score, easy ones
+//! (low scores) first, either strictly sorted or sampled with a preference for easy samples. Every sample is
+//! still trained on exactly once per epoch, and batches are cut from the ordered epoch as usual.
+
+use rand::{seq::SliceRandom, Rng};
+
+use crate::{dataset::Sample, losses::LossFn, network::Network};
+
+use super::TrainingError;
+
+/// How a `Curriculum` turns scores into an epoch's order.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CurriculumStrategy {
+    /// Sorted by increasing score. With shuffling on, samples with equal scores are shuffled among themselves.
+    Ordered,
+    /// Drawn one after the other without replacement, each remaining sample with a probability proportional to
+    /// `exp(-score / temperature)`. A low temperature approaches `Ordered`, a high one a plain shuffle.
+    Sampled { temperature: f32 },
+}
+
+/// Scores a sample's difficulty, lower is easier.
+type ScoreFn<'a> = Box<dyn Fn(&Sample, &mut Network) -> f32 + 'a>;
+
+/// The difficulty score of every sample and the strategy that orders epochs by it, see `Trainer::curriculum`.
+/// Scores are taken before the first epoch and then every `rescore_every` epochs, a NaN score counts as the
+/// hardest. Scoring runs between epochs, when no gradient is accumulated, and any gradient a scorer
+/// accumulates is discarded, so it can't leak into training.
+pub struct Curriculum<'a> {
+    score: ScoreFn<'a>,
+    strategy: CurriculumStrategy,
+    rescore_every: usize,
+}
+
+impl<'a> Curriculum<'a> {
+    pub fn ordered(score: impl Fn(&Sample, &mut Network) -> f32 + 'a) -> Self {
+        Self {
+            score: Box::new(score),
+            strategy: CurriculumStrategy::Ordered,
+            rescore_every: 1,
+        }
+    }
+
+    /// Fails with `InvalidTemperature` unless `temperature` is positive and finite.
+    pub fn sampled(score: impl Fn(&Sample, &mut Network) -> f32 + 'a, temperature: f32) -> Result<Self, TrainingError> {
+        if !(temperature > 0.0 && temperature.is_finite()) {
+            return Err(TrainingError::InvalidTemperature(temperature));
+        }
+
+        Ok(Self {
+            score: Box::new(score),
+            strategy: CurriculumStrategy::Sampled { temperature },
+            rescore_every: 1,
+        })
+    }
+
+    /// Scores the samples again every `epochs` epochs (at least 1) instead of before every epoch, since a pass
+    /// over the whole dataset can cost as much as training on it.
+    pub fn rescore_every(mut self, epochs: usize) -> Self {
+        self.rescore_every = epochs.max(1);
+        self
+    }
+
+    #[inline]
+    pub fn strategy(&self) -> CurriculumStrategy {
+        self.strategy
+    }
+
+    /// Whether the scores are taken before `epoch`.
+    pub(crate) fn rescores_at(&self, epoch: usize) -> bool {
+        epoch.is_multiple_of(self.rescore_every)
+    }
+
+    /// The score of every sample, with the network's gradients zeroed afterwards.
+    pub fn scores(&self, network: &mut Network, dataset: &[Sample]) -> Vec<f32> {
+        let scores = dataset.iter().map(|sample| (self.score)(sample, network)).collect();
+        network.zero_gradients();
+        scores
+    }
+
+    /// A permutation of the indices of `scores`, the order of an epoch. `shuffle` only matters to `Ordered`.
+    pub fn order(&self, scores: &[f32], shuffle: bool, rng: &mut impl Rng) -> Vec<usize> {
+        let scores: Vec<f32> = scores.iter().map(|&score| if score.is_nan() { f32::INFINITY } else { score }).collect();
+        let mut order: Vec<usize> = (0..scores.len()).collect();
+
+        match self.strategy {
+            CurriculumStrategy::Ordered => {
+                if shuffle {
+                    order.shuffle(rng);
+                }
+
+                order.sort_by(|&a, &b| scores[a].total_cmp(&scores[b]));
+            }
+
+            // Sorting by the log weight plus gumbel noise is the same as drawing without replacement.
+            CurriculumStrategy::Sampled { temperature } => {
+                let keys: Vec<f64> = scores
+                    .iter()
+                    .map(|&score| -(score as f64) / temperature as f64 - (-rng.random::<f64>().ln()).ln())
+                    .collect();
+
+                order.sort_by(|&a, &b| keys[b].total_cmp(&keys[a]));
+            }
+        }
+
+        order
+    }
+}
+
+/// A scorer for `Curriculum` that takes a sample's loss under the current network, so the samples the network
+/// already fits come first. Samples the network can't evaluate get a NaN score.
+pub fn loss_score<'a>(loss: impl LossFn + 'a) -> impl Fn(&Sample, &mut Network) -> f32 + 'a {
+    move |sample, network| network.evaluate(std::slice::from_ref(sample), &loss).unwrap_or(f32::NAN)
+}
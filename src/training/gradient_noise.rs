@@ -0,0 +1,40 @@
+use super::TrainingError;
+
+/// Annealed gaussian gradient noise: step `t` of training, counted from 0, adds zero-mean noise with variance
+/// `eta / (1 + t)^gamma` to the mean gradient before the update, see `Trainer::gradient_noise`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GradientNoise {
+    eta: f32,
+    gamma: f32,
+}
+
+impl GradientNoise {
+    /// Fails with `InvalidGradientNoise` unless both are finite and non-negative.
+    pub fn new(eta: f32, gamma: f32) -> Result<Self, TrainingError> {
+        if !(eta.is_finite() && eta >= 0.0 && gamma.is_finite() && gamma >= 0.0) {
+            return Err(TrainingError::InvalidGradientNoise { eta, gamma });
+        }
+
+        Ok(Self { eta, gamma })
+    }
+
+    #[inline]
+    pub fn eta(&self) -> f32 {
+        self.eta
+    }
+
+    #[inline]
+    pub fn gamma(&self) -> f32 {
+        self.gamma
+    }
+
+    /// The noise's variance at step `t`.
+    pub fn variance(&self, t: usize) -> f32 {
+        self.eta / (1.0 + t as f32).powf(self.gamma)
+    }
+
+    /// The noise's standard deviation at step `t`.
+    pub fn std_dev(&self, t: usize) -> f32 {
+        self.variance(t).sqrt()
+    }
+}
@@ -0,0 +1,199 @@
+use std::{io::{self, Write}, time::Duration};
+
+#[cfg(feature = "serde")]
+use crate::json::Value;
+
+/// The columns of `TrainingHistory::to_csv` and the fields of `TrainingHistory::to_json`, in order, before
+/// those of the metrics.
+pub const HISTORY_COLUMNS: [&str; 6] = ["epoch", "train_loss", "validation_loss", "learning_rate", "duration_ms", "gradient_norm"];
+
+#[derive(Clone, Debug, Default)]
+pub struct TrainingHistory {
+    train_losses: Vec<f32>,
+    validation_losses: Vec<Option<f32>>,
+    learning_rates: Vec<f32>,
+    gradient_norms: Vec<f32>,
+    durations: Vec<Duration>,
+    /// Every metric's name and values by epoch, in the order they were first recorded.
+    metrics: Vec<(String, Vec<Option<f32>>)>,
+}
+
+impl TrainingHistory {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push(
+        &mut self,
+        train_loss: f32,
+        validation_loss: Option<f32>,
+        learning_rate: f32,
+        gradient_norm: f32,
+        duration: Duration,
+    ) {
+        self.train_losses.push(train_loss);
+        self.validation_losses.push(validation_loss);
+        self.learning_rates.push(learning_rate);
+        self.gradient_norms.push(gradient_norm);
+        self.durations.push(duration);
+    }
+
+    /// Adds the epochs of `other` after these, with their metrics, e.g. to join the histories of several `fit`s.
+    pub fn append(&mut self, other: &TrainingHistory) {
+        for epoch in 0..other.len() {
+            self.push(
+                other.train_losses[epoch],
+                other.validation_losses[epoch],
+                other.learning_rates[epoch],
+                other.gradient_norms[epoch],
+                other.durations[epoch],
+            );
+
+            for (index, (name, _)) in other.metrics.iter().enumerate() {
+                if let Some(value) = other.metric_at(index, epoch) {
+                    self.push_metric(name, value);
+                }
+            }
+        }
+    }
+
+    /// Records the value of the metric `name` for the last epoch pushed. Epochs without a value are `None`.
+    pub fn push_metric(&mut self, name: &str, value: f32) {
+        let Some(epoch) = self.len().checked_sub(1) else {
+            return;
+        };
+
+        let index = match self.metrics.iter().position(|(metric, _)| metric == name) {
+            Some(index) => index,
+            None => {
+                self.metrics.push((name.to_string(), Vec::new()));
+                self.metrics.len() - 1
+            }
+        };
+
+        let values = &mut self.metrics[index].1;
+        values.resize(epoch + 1, None);
+        values[epoch] = Some(value);
+    }
+
+    /// The metric's value for every epoch, `None` if it wasn't recorded at all. Epochs after the last one it
+    /// was recorded for aren't in the slice.
+    pub fn metric(&self, name: &str) -> Option<&[Option<f32>]> {
+        self.metrics.iter().find(|(metric, _)| metric == name).map(|(_, values)| values.as_slice())
+    }
+
+    /// The names of the recorded metrics, in the order they were first recorded.
+    pub fn metric_names(&self) -> impl Iterator<Item = &str> {
+        self.metrics.iter().map(|(name, _)| name.as_str())
+    }
+
+    /// The metric's value in `epoch`, if it was recorded.
+    fn metric_at(&self, index: usize, epoch: usize) -> Option<f32> {
+        self.metrics[index].1.get(epoch).copied().flatten()
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize { self.train_losses.len() }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool { self.train_losses.is_empty() }
+
+    #[inline]
+    pub fn train_losses(&self) -> &[f32] { &self.train_losses }
+
+    #[inline]
+    pub fn validation_losses(&self) -> &[Option<f32>] { &self.validation_losses }
+
+    #[inline]
+    pub fn learning_rates(&self) -> &[f32] { &self.learning_rates }
+
+    /// The epochs trained with a lower rate than the epoch before, e.g. after `ReduceLrOnPlateau` lowered it.
+    pub fn rate_reductions(&self) -> Vec<usize> {
+        (1..self.len()).filter(|&epoch| self.learning_rates[epoch] < self.learning_rates[epoch - 1]).collect()
+    }
+
+    /// Every epoch's mean gradient norm, see `EpochContext::gradient_norm`.
+    #[inline]
+    pub fn gradient_norms(&self) -> &[f32] { &self.gradient_norms }
+
+    #[inline]
+    pub fn durations(&self) -> &[Duration] { &self.durations }
+
+    /// Writes a header row with `HISTORY_COLUMNS` and the metric names and one row per epoch, counted from 0.
+    /// Epochs without a validation loss or a metric's value leave that field empty. Durations are fractional
+    /// milliseconds and numbers are written with as many digits as it takes to read them back exactly.
+    pub fn to_csv(&self, mut writer: impl Write) -> io::Result<()> {
+        let header: Vec<&str> = HISTORY_COLUMNS.iter().copied().chain(self.metric_names()).collect();
+        writeln!(writer, "{}", header.join(","))?;
+
+        for epoch in 0..self.len() {
+            let validation_loss = self.validation_losses[epoch].map_or(String::new(), |loss| loss.to_string());
+
+            write!(
+                writer,
+                "{},{},{},{},{},{}",
+                epoch,
+                self.train_losses[epoch],
+                validation_loss,
+                self.learning_rates[epoch],
+                self.durations[epoch].as_secs_f64() * 1000.0,
+                self.gradient_norms[epoch],
+            )?;
+
+            for index in 0..self.metrics.len() {
+                write!(writer, ",{}", self.metric_at(index, epoch).map_or(String::new(), |value| value.to_string()))?;
+            }
+
+            writeln!(writer)?;
+        }
+
+        Ok(())
+    }
+
+    /// An array with an object per epoch holding the fields of `HISTORY_COLUMNS` and one per metric. A missing
+    /// validation loss or metric value is `null`, and so are non-finite numbers.
+    #[cfg(feature = "serde")]
+    pub fn to_json(&self) -> String {
+        let epochs = (0..self.len())
+            .map(|epoch| {
+                let fields = [
+                    ("epoch", Value::Number(epoch as f64)),
+                    ("train_loss", Value::Number(self.train_losses[epoch] as f64)),
+                    ("validation_loss", self.validation_losses[epoch].map_or(Value::Null, |loss| Value::Number(loss as f64))),
+                    ("learning_rate", Value::Number(self.learning_rates[epoch] as f64)),
+                    ("duration_ms", Value::Number(self.durations[epoch].as_secs_f64() * 1000.0)),
+                    ("gradient_norm", Value::Number(self.gradient_norms[epoch] as f64)),
+                ];
+
+                let metrics = self.metrics.iter().enumerate().map(|(index, (name, _))| {
+                    (name.clone(), self.metric_at(index, epoch).map_or(Value::Null, |value| Value::Number(value as f64)))
+                });
+
+                Value::Object(fields.into_iter().map(|(name, value)| (name.to_string(), value)).chain(metrics).collect())
+            })
+            .collect();
+
+        Value::Array(epochs).to_string()
+    }
+
+    /// The epoch with the lowest validation loss, or the lowest training loss if no epoch was validated.
+    pub fn best_epoch(&self) -> Option<usize> {
+        let validated = self.validation_losses
+            .iter()
+            .enumerate()
+            .filter_map(|(epoch, loss)| loss.map(|loss| (epoch, loss)));
+
+        if self.validation_losses.iter().any(Option::is_some) {
+            lowest(validated)
+        } else {
+            lowest(self.train_losses.iter().copied().enumerate())
+        }
+    }
+}
+
+fn lowest(losses: impl Iterator<Item = (usize, f32)>) -> Option<usize> {
+    losses
+        .filter(|(_, loss)| !loss.is_nan())
+        .min_by(|(_, a), (_, b)| a.total_cmp(b))
+        .map(|(epoch, _)| epoch)
+}
@@ -0,0 +1,130 @@
+use crate::{
+    dataset::Sample,
+    losses::LossFn,
+    network::{Network, NetworkError},
+};
+
+use super::TrainingError;
+
+/// Learning rate range test: trains one mini-batch per step while the rate grows exponentially
+/// from `min_rate` to `max_rate`, recording the loss at every rate.
+pub struct LearningRateFinder {
+    min_rate: f32,
+    max_rate: f32,
+    steps: usize,
+    batch_size: Option<usize>,
+    divergence_factor: f32,
+}
+
+pub struct LearningRateCurve {
+    pub rates: Vec<f32>,
+    pub losses: Vec<f32>,
+    pub suggested_rate: Option<f32>,
+}
+
+impl LearningRateFinder {
+    pub fn new(min_rate: f32, max_rate: f32, steps: usize) -> Self {
+        Self {
+            min_rate,
+            max_rate,
+            steps,
+            batch_size: None,
+            divergence_factor: 4.0,
+        }
+    }
+
+    /// Size of the mini-batch trained at every step, by default the whole dataset.
+    pub fn batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = Some(batch_size.max(1));
+        self
+    }
+
+    /// The sweep stops once the loss exceeds its minimum so far by this factor, 4 by default.
+    pub fn divergence_factor(mut self, divergence_factor: f32) -> Self {
+        self.divergence_factor = divergence_factor;
+        self
+    }
+
+    /// Runs the sweep and restores the network's parameters afterwards. The suggested rate is the one
+    /// where the loss fell the fastest before reaching its minimum.
+    pub fn run(
+        &self,
+        network: &mut Network,
+        dataset: &[Sample],
+        loss: &impl LossFn,
+    ) -> Result<LearningRateCurve, TrainingError> {
+        if !(self.min_rate > 0.0 && self.max_rate > self.min_rate) {
+            return Err(TrainingError::InvalidRateRange {
+                min_rate: self.min_rate,
+                max_rate: self.max_rate,
+            });
+        }
+
+        if self.steps < 2 {
+            return Err(TrainingError::TooFewSteps(self.steps));
+        }
+
+        if dataset.is_empty() {
+            return Err(NetworkError::EmptyDataset.into());
+        }
+
+        let snapshot = network.parameter_snapshot();
+        let result = self.sweep(network, dataset, loss);
+        network.restore_snapshot(&snapshot)?;
+
+        let (rates, losses) = result?;
+        let suggested_rate = suggest(&rates, &losses);
+
+        Ok(LearningRateCurve {
+            rates,
+            losses,
+            suggested_rate,
+        })
+    }
+
+    fn sweep(
+        &self,
+        network: &mut Network,
+        dataset: &[Sample],
+        loss: &impl LossFn,
+    ) -> Result<(Vec<f32>, Vec<f32>), NetworkError> {
+        let batch_size = self.batch_size.unwrap_or(dataset.len());
+        let mut batches = dataset.chunks(batch_size).cycle();
+        let growth = (self.max_rate / self.min_rate).powf(1.0 / (self.steps - 1) as f32);
+
+        let mut rates = Vec::with_capacity(self.steps);
+        let mut losses = Vec::with_capacity(self.steps);
+        let mut min_loss = f32::INFINITY;
+
+        for step in 0..self.steps {
+            let rate = self.min_rate * growth.powi(step as i32);
+            let batch_loss = network.learn(batches.next().unwrap(), loss, rate)?;
+
+            rates.push(rate);
+            losses.push(batch_loss);
+            min_loss = min_loss.min(batch_loss);
+
+            // A zero minimum can't be exceeded by a factor, any loss above it would count as diverging.
+            if !batch_loss.is_finite() || (min_loss > 0.0 && batch_loss > min_loss * self.divergence_factor) {
+                break;
+            }
+        }
+
+        Ok((rates, losses))
+    }
+}
+
+fn suggest(rates: &[f32], losses: &[f32]) -> Option<f32> {
+    let min_index = losses
+        .iter()
+        .enumerate()
+        .filter(|(_, loss)| loss.is_finite())
+        .min_by(|(_, a), (_, b)| a.total_cmp(b))?
+        .0;
+
+    (0..min_index)
+        .map(|i| (i, losses[i + 1] - losses[i]))
+        .filter(|&(_, change)| change < 0.0)
+        .min_by(|(_, a), (_, b)| a.total_cmp(b))
+        .map(|(i, _)| rates[i])
+}
@@ -0,0 +1,114 @@
+//! Metrics that `Trainer` evaluates after every epoch and records in the history next to the losses.
+
+use crate::{
+    dataset::Sample,
+    losses::LossFn,
+    metrics::{self, ClassificationMode},
+    network::{Network, NetworkError},
+};
+
+/// A number that measures how well a network does on a dataset, e.g. its accuracy.
+pub trait Metric {
+    /// The name the metric is recorded under, which has to be unique among a trainer's metrics.
+    fn name(&self) -> &str;
+
+    fn compute(&self, network: &mut Network, dataset: &[Sample]) -> Result<f32, NetworkError>;
+
+    /// Whether higher values are better, like for accuracy. Losses and other errors are lower-is-better,
+    /// which is the default.
+    fn higher_is_better(&self) -> bool {
+        false
+    }
+}
+
+impl<M: Metric + ?Sized> Metric for Box<M> {
+    fn name(&self) -> &str {
+        (**self).name()
+    }
+
+    fn compute(&self, network: &mut Network, dataset: &[Sample]) -> Result<f32, NetworkError> {
+        (**self).compute(network, dataset)
+    }
+
+    fn higher_is_better(&self) -> bool {
+        (**self).higher_is_better()
+    }
+}
+
+/// Which dataset a metric is evaluated on. Training set values are recorded under the metric's name and
+/// validation set values under its name prefixed with `validation_`, e.g. `validation_accuracy`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MetricSet {
+    Train,
+    /// Skipped when `fit` has no validation set.
+    Validation,
+    Both,
+}
+
+impl MetricSet {
+    pub(crate) fn includes_train(self) -> bool {
+        matches!(self, MetricSet::Train | MetricSet::Both)
+    }
+
+    pub(crate) fn includes_validation(self) -> bool {
+        matches!(self, MetricSet::Validation | MetricSet::Both)
+    }
+}
+
+/// The name validation values of the metric `name` are recorded under.
+pub fn validation_name(name: &str) -> String {
+    format!("validation_{name}")
+}
+
+/// The share of correctly classified samples, see `metrics::accuracy`, named `accuracy`.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Accuracy {
+    mode: ClassificationMode,
+}
+
+impl Accuracy {
+    pub fn new(mode: ClassificationMode) -> Self {
+        Self { mode }
+    }
+}
+
+impl Metric for Accuracy {
+    fn name(&self) -> &str {
+        "accuracy"
+    }
+
+    fn compute(&self, network: &mut Network, dataset: &[Sample]) -> Result<f32, NetworkError> {
+        metrics::accuracy(network, dataset, self.mode)
+    }
+
+    fn higher_is_better(&self) -> bool {
+        true
+    }
+}
+
+/// The weighted mean loss under any loss function, see `Network::evaluate`, e.g. to watch a loss other than
+/// the one trained on. Named `mean_loss` unless given another name.
+pub struct MeanLoss<L: LossFn> {
+    loss: L,
+    name: String,
+}
+
+impl<L: LossFn> MeanLoss<L> {
+    pub fn new(loss: L) -> Self {
+        Self::named(loss, "mean_loss")
+    }
+
+    pub fn named(loss: L, name: impl Into<String>) -> Self {
+        Self { loss, name: name.into() }
+    }
+}
+
+impl<L: LossFn> Metric for MeanLoss<L> {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn compute(&self, network: &mut Network, dataset: &[Sample]) -> Result<f32, NetworkError> {
+        network.evaluate(dataset, &self.loss)
+    }
+}
@@ -0,0 +1,148 @@
+//! Multi-start training: small networks often end up in a poor local minimum depending on their initial
+//! weights, so several independently initialized networks are trained and the one with the lowest validation
+//! loss is kept.
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use crate::{
+    dataset::Sample,
+    losses::LossFn,
+    network::{Network, NetworkError},
+};
+
+use super::{Trainer, TrainingError};
+
+/// How one restart of `multi_start` ended.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Restart {
+    /// The seed of the RNG the restart's network was initialized and trained with.
+    pub seed: u64,
+    /// The training loss of the last epoch, NaN if no epoch was trained.
+    pub train_loss: f32,
+    pub validation_loss: f32,
+}
+
+pub struct MultiStart {
+    /// The trained network with the lowest validation loss.
+    pub network: Network,
+    /// The index of `network`'s restart in `restarts`.
+    pub best: usize,
+    /// Every restart in order, including the best one.
+    pub restarts: Vec<Restart>,
+}
+
+impl MultiStart {
+    pub fn validation_losses(&self) -> Vec<f32> {
+        self.restarts.iter().map(|restart| restart.validation_loss).collect()
+    }
+}
+
+/// Trains `restarts` networks and keeps the one with the lowest loss on `validation`, the first of them on a
+/// tie, and a NaN loss counts as the highest. Every restart gets its own seed drawn from `rng` up front, and
+/// `factory` initializes its network and the trainer shuffles with an RNG seeded with it, so the restarts
+/// start from different weights and a seeded `rng` gives the same result every time. Every restart trains with
+/// a fresh trainer from `trainer`, so no callback state or schedule carries over from one restart to the next.
+/// Fails with `NoRestarts` for 0 restarts and with `EmptyDataset` for an empty validation set, before anything
+/// is trained.
+pub fn multi_start<'t, L, F, T>(
+    restarts: usize,
+    factory: F,
+    dataset: &[Sample],
+    validation: &[Sample],
+    trainer: T,
+    rng: &mut impl Rng,
+) -> Result<MultiStart, TrainingError>
+where
+    L: LossFn + 't,
+    F: Fn(&mut StdRng) -> Result<Network, NetworkError>,
+    T: Fn() -> Trainer<'t, L>,
+{
+    let seeds = seeds(restarts, validation, rng)?;
+    let runs = seeds
+        .into_iter()
+        .map(|seed| restart(seed, &factory, dataset, validation, &mut trainer()))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(best_of(runs))
+}
+
+/// `multi_start` with the restarts spread over the rayon thread pool. The result is the same as `multi_start`'s
+/// with the same `rng`.
+#[cfg(feature = "rayon")]
+pub fn multi_start_parallel<'t, L, F, T>(
+    restarts: usize,
+    factory: F,
+    dataset: &[Sample],
+    validation: &[Sample],
+    trainer: T,
+    rng: &mut impl Rng,
+) -> Result<MultiStart, TrainingError>
+where
+    L: LossFn + 't,
+    F: Fn(&mut StdRng) -> Result<Network, NetworkError> + Sync,
+    T: Fn() -> Trainer<'t, L> + Sync,
+{
+    use rayon::prelude::*;
+
+    let seeds = seeds(restarts, validation, rng)?;
+    let runs = seeds
+        .into_par_iter()
+        .map(|seed| restart(seed, &factory, dataset, validation, &mut trainer()))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(best_of(runs))
+}
+
+fn seeds(restarts: usize, validation: &[Sample], rng: &mut impl Rng) -> Result<Vec<u64>, TrainingError> {
+    if restarts == 0 {
+        return Err(TrainingError::NoRestarts);
+    }
+
+    if validation.is_empty() {
+        return Err(NetworkError::EmptyDataset.into());
+    }
+
+    Ok((0..restarts).map(|_| rng.random()).collect())
+}
+
+fn restart<L, F>(
+    seed: u64,
+    factory: &F,
+    dataset: &[Sample],
+    validation: &[Sample],
+    trainer: &mut Trainer<L>,
+) -> Result<(Network, Restart), TrainingError>
+where
+    L: LossFn,
+    F: Fn(&mut StdRng) -> Result<Network, NetworkError>,
+{
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut network = factory(&mut rng)?;
+    let report = trainer.fit_with_rng(&mut network, dataset, validation, &mut rng)?;
+    let validation_loss = network.evaluate(validation, &trainer.loss)?;
+
+    Ok((
+        network,
+        Restart {
+            seed,
+            train_loss: report.history.train_losses().last().copied().unwrap_or(f32::NAN),
+            validation_loss,
+        },
+    ))
+}
+
+/// `runs` has at least one run.
+fn best_of(runs: Vec<(Network, Restart)>) -> MultiStart {
+    let best = (1..runs.len()).fold(0, |best, i| {
+        let (loss, best_loss) = (runs[i].1.validation_loss, runs[best].1.validation_loss);
+        if loss < best_loss || (best_loss.is_nan() && !loss.is_nan()) { i } else { best }
+    });
+
+    let (networks, restarts): (Vec<Network>, Vec<Restart>) = runs.into_iter().unzip();
+
+    MultiStart {
+        network: networks.into_iter().nth(best).unwrap(),
+        best,
+        restarts,
+    }
+}
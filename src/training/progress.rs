@@ -0,0 +1,144 @@
+//! A progress display for training runs, driven by the callback events.
+
+use std::{
+    io::{self, Stdout, Write},
+    ops::ControlFlow,
+    time::{Duration, Instant},
+};
+
+use super::callback::{BatchContext, EpochContext, TrainingCallback};
+
+const BAR_WIDTH: usize = 20;
+const SPINNER: [char; 4] = ['|', '/', '-', '\\'];
+
+/// Draws a status line after batches and a summary line after every epoch. The status line shows the epoch and
+/// batch out of their totals, a bar, the batch loss and the estimated time left. If a total isn't known, e.g. for
+/// a stream of batches, it shows a spinner and the counts alone. Status lines are redrawn in place at most once
+/// per interval, 100 ms by default, so a fast training loop isn't slowed down by the terminal.
+pub struct ProgressReporter<W: Write = Stdout> {
+    writer: W,
+    interval: Duration,
+    start: Option<Instant>,
+    last_draw: Option<Instant>,
+    epochs: Option<usize>,
+    frame: usize,
+}
+
+impl ProgressReporter {
+    pub fn stdout() -> Self {
+        Self::new(io::stdout())
+    }
+}
+
+impl Default for ProgressReporter {
+    fn default() -> Self {
+        Self::stdout()
+    }
+}
+
+impl<W: Write> ProgressReporter<W> {
+    pub fn new(writer: W) -> Self {
+        Self {
+            writer,
+            interval: Duration::from_millis(100),
+            start: None,
+            last_draw: None,
+            epochs: None,
+            frame: 0,
+        }
+    }
+
+    /// The shortest time between two status lines, 0 draws one after every batch.
+    pub fn interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+
+    /// The status line for a batch, without the carriage return that redraws it.
+    pub fn batch_line(&mut self, ctx: &BatchContext) -> String {
+        let elapsed = self.start.get_or_insert_with(Instant::now).elapsed();
+        self.epochs = ctx.epochs;
+
+        match (ctx.epochs, ctx.batches) {
+            (Some(epochs), Some(batches)) => {
+                let done = ctx.epoch * batches + ctx.batch + 1;
+                let total = (epochs * batches).max(done);
+                let filled = BAR_WIDTH * done / total;
+                let eta = elapsed.mul_f64((total - done) as f64 / done as f64);
+
+                format!(
+                    "epoch {}/{} [{}{}] batch {}/{} loss {:.6} eta {}",
+                    ctx.epoch + 1,
+                    epochs,
+                    "=".repeat(filled),
+                    " ".repeat(BAR_WIDTH - filled),
+                    ctx.batch + 1,
+                    batches,
+                    ctx.loss,
+                    format_duration(eta),
+                )
+            }
+
+            _ => {
+                self.frame = (self.frame + 1) % SPINNER.len();
+                let epoch = ctx.epochs.map_or((ctx.epoch + 1).to_string(), |epochs| format!("{}/{}", ctx.epoch + 1, epochs));
+                let batch = ctx.batches.map_or((ctx.batch + 1).to_string(), |batches| format!("{}/{}", ctx.batch + 1, batches));
+
+                format!(
+                    "{} epoch {} batch {} loss {:.6} elapsed {}",
+                    SPINNER[self.frame],
+                    epoch,
+                    batch,
+                    ctx.loss,
+                    format_duration(elapsed),
+                )
+            }
+        }
+    }
+
+    /// The summary line for an epoch.
+    pub fn epoch_line(&self, ctx: &EpochContext) -> String {
+        let epoch = self.epochs.map_or((ctx.epoch + 1).to_string(), |epochs| format!("{}/{}", ctx.epoch + 1, epochs));
+
+        match ctx.validation_loss {
+            Some(validation_loss) => format!("epoch {epoch}: train loss {:.6}, validation loss {validation_loss:.6}", ctx.train_loss),
+            None => format!("epoch {epoch}: train loss {:.6}", ctx.train_loss),
+        }
+    }
+}
+
+impl<W: Write> TrainingCallback for ProgressReporter<W> {
+    fn on_batch_end(&mut self, ctx: &BatchContext) {
+        let now = Instant::now();
+        if self.last_draw.is_some_and(|last_draw| now - last_draw < self.interval) {
+            return;
+        }
+
+        self.last_draw = Some(now);
+        let line = self.batch_line(ctx);
+        let _ = write!(self.writer, "\r\x1b[2K{line}").and_then(|()| self.writer.flush());
+    }
+
+    fn on_epoch_end(&mut self, ctx: &EpochContext) -> ControlFlow<()> {
+        let line = self.epoch_line(ctx);
+        let _ = writeln!(self.writer, "\r\x1b[2K{line}");
+        self.last_draw = None;
+
+        ControlFlow::Continue(())
+    }
+}
+
+/// `1h02m03s`, `2m03s` or `3.4s`.
+fn format_duration(duration: Duration) -> String {
+    let seconds = duration.as_secs();
+
+    match seconds {
+        3600.. => format!("{}h{:02}m{:02}s", seconds / 3600, seconds / 60 % 60, seconds % 60),
+        60.. => format!("{}m{:02}s", seconds / 60, seconds % 60),
+        _ => format!("{:.1}s", duration.as_secs_f64()),
+    }
+}
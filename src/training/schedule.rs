@@ -0,0 +1,244 @@
+use std::f32::consts::PI;
+
+use super::TrainingError;
+
+/// How `OneCycle` moves between two rates.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Annealing {
+    /// Half a cosine wave, flat at both ends.
+    #[default]
+    Cosine,
+    Linear,
+}
+
+impl Annealing {
+    /// The rate `progress` of the way from `start` to `end`.
+    fn interpolate(self, start: f32, end: f32, progress: f32) -> f32 {
+        let progress = match self {
+            Annealing::Cosine => (1.0 - (PI * progress).cos()) / 2.0,
+            Annealing::Linear => progress,
+        };
+
+        start + (end - start) * progress
+    }
+}
+
+/// The one-cycle policy over a fixed budget of steps: the rate rises from `max_rate / initial_div` to `max_rate` over the
+/// warmup fraction of the steps, then falls to `max_rate / (initial_div * final_div)` at the last step. With `n` steps,
+/// step `t` sits at `t / (n - 1)` of the cycle.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct OneCycle {
+    max_rate: f32,
+    total_steps: usize,
+    warmup: f32,
+    initial_div: f32,
+    final_div: f32,
+    annealing: Annealing,
+    step: usize,
+}
+
+impl OneCycle {
+    /// A cycle with 30% warmup, `initial_div` 25, `final_div` 1e4 and cosine annealing. Fails with `InvalidSchedule`
+    /// unless the rate is positive and finite and there's at least one step.
+    pub fn new(max_rate: f32, total_steps: usize) -> Result<Self, TrainingError> {
+        if !(max_rate > 0.0 && max_rate.is_finite()) {
+            return Err(TrainingError::InvalidSchedule("maximum rate"));
+        }
+
+        if total_steps == 0 {
+            return Err(TrainingError::InvalidSchedule("step budget"));
+        }
+
+        Ok(Self {
+            max_rate,
+            total_steps,
+            warmup: 0.3,
+            initial_div: 25.0,
+            final_div: 1e4,
+            annealing: Annealing::Cosine,
+            step: 0,
+        })
+    }
+
+    /// The fraction of the cycle spent rising, in [0, 1).
+    pub fn warmup(mut self, fraction: f32) -> Result<Self, TrainingError> {
+        if !(0.0..1.0).contains(&fraction) {
+            return Err(TrainingError::InvalidSchedule("warmup fraction"));
+        }
+
+        self.warmup = fraction;
+        Ok(self)
+    }
+
+    /// The initial rate is `max_rate / initial_div`, which has to be at least 1.
+    pub fn initial_div(mut self, initial_div: f32) -> Result<Self, TrainingError> {
+        if !(initial_div >= 1.0 && initial_div.is_finite()) {
+            return Err(TrainingError::InvalidSchedule("initial divisor"));
+        }
+
+        self.initial_div = initial_div;
+        Ok(self)
+    }
+
+    /// The final rate is the initial rate divided by `final_div`, which has to be at least 1.
+    pub fn final_div(mut self, final_div: f32) -> Result<Self, TrainingError> {
+        if !(final_div >= 1.0 && final_div.is_finite()) {
+            return Err(TrainingError::InvalidSchedule("final divisor"));
+        }
+
+        self.final_div = final_div;
+        Ok(self)
+    }
+
+    pub fn annealing(mut self, annealing: Annealing) -> Self {
+        self.annealing = annealing;
+        self
+    }
+
+    #[inline]
+    pub fn total_steps(&self) -> usize {
+        self.total_steps
+    }
+
+    #[inline]
+    pub fn max_rate(&self) -> f32 {
+        self.max_rate
+    }
+
+    #[inline]
+    pub fn initial_rate(&self) -> f32 {
+        self.max_rate / self.initial_div
+    }
+
+    #[inline]
+    pub fn final_rate(&self) -> f32 {
+        self.initial_rate() / self.final_div
+    }
+
+    /// The rate of step `step`, failing with `ScheduleExhausted` past the budget.
+    pub fn rate_at(&self, step: usize) -> Result<f32, TrainingError> {
+        if step >= self.total_steps {
+            return Err(TrainingError::ScheduleExhausted {
+                step,
+                total_steps: self.total_steps,
+            });
+        }
+
+        let position = if self.total_steps == 1 { 0.0 } else { step as f32 / (self.total_steps - 1) as f32 };
+
+        Ok(if position < self.warmup {
+            self.annealing.interpolate(self.initial_rate(), self.max_rate, position / self.warmup)
+        } else {
+            self.annealing.interpolate(self.max_rate, self.final_rate(), (position - self.warmup) / (1.0 - self.warmup))
+        })
+    }
+
+    /// The rate of the next step, for driving a training loop by hand.
+    pub fn next_rate(&mut self) -> Result<f32, TrainingError> {
+        let rate = self.rate_at(self.step)?;
+        self.step += 1;
+        Ok(rate)
+    }
+
+    /// The number of steps `next_rate` has handed out.
+    #[inline]
+    pub fn steps_taken(&self) -> usize {
+        self.step
+    }
+}
+
+/// Lowers the rate when the monitored loss stops improving: once it hasn't dropped more than `min_delta` below
+/// the best loss so far for `patience` epochs in a row, the rate is multiplied by `factor`, but not below the
+/// floor. A cooldown skips that many epochs after a reduction before counting again. NaN losses never improve.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ReduceLrOnPlateau {
+    factor: f32,
+    patience: usize,
+    min_delta: f32,
+    min_rate: f32,
+    cooldown: usize,
+
+    best: Option<f32>,
+    bad_epochs: usize,
+    cooldown_left: usize,
+}
+
+impl ReduceLrOnPlateau {
+    /// No minimum improvement, floor or cooldown. Fails with `InvalidSchedule` unless `factor` is in (0, 1).
+    pub fn new(factor: f32, patience: usize) -> Result<Self, TrainingError> {
+        if !(factor > 0.0 && factor < 1.0) {
+            return Err(TrainingError::InvalidSchedule("reduction factor"));
+        }
+
+        Ok(Self {
+            factor,
+            patience,
+            min_delta: 0.0,
+            min_rate: 0.0,
+            cooldown: 0,
+
+            best: None,
+            bad_epochs: 0,
+            cooldown_left: 0,
+        })
+    }
+
+    /// How much lower than the best loss a loss has to be to count as an improvement.
+    pub fn min_delta(mut self, min_delta: f32) -> Self {
+        self.min_delta = min_delta;
+        self
+    }
+
+    /// The rate is never reduced below this.
+    pub fn min_rate(mut self, min_rate: f32) -> Self {
+        self.min_rate = min_rate;
+        self
+    }
+
+    /// Epochs after a reduction during which epochs without improvement aren't counted.
+    pub fn cooldown(mut self, cooldown: usize) -> Self {
+        self.cooldown = cooldown;
+        self
+    }
+
+    /// The lowest loss seen so far.
+    #[inline]
+    pub fn best(&self) -> Option<f32> {
+        self.best
+    }
+
+    /// Takes an epoch's loss and the rate it was trained with, and returns the rate for the next epoch.
+    pub fn step(&mut self, loss: f32, rate: f32) -> f32 {
+        let in_cooldown = self.cooldown_left > 0;
+        if in_cooldown {
+            self.cooldown_left -= 1;
+            self.bad_epochs = 0;
+        }
+
+        if !loss.is_nan() && self.best.is_none_or(|best| loss < best - self.min_delta) {
+            self.best = Some(loss);
+            self.bad_epochs = 0;
+            return rate;
+        }
+
+        if in_cooldown {
+            return rate;
+        }
+
+        self.bad_epochs += 1;
+        if self.bad_epochs < self.patience || rate <= self.min_rate {
+            return rate;
+        }
+
+        self.bad_epochs = 0;
+        self.cooldown_left = self.cooldown;
+        (rate * self.factor).max(self.min_rate)
+    }
+
+    /// Forgets the losses seen, e.g. to train again from scratch.
+    pub fn reset(&mut self) {
+        self.best = None;
+        self.bad_epochs = 0;
+        self.cooldown_left = 0;
+    }
+}
@@ -0,0 +1,133 @@
+//! Snapshot ensembles: one network trained with a learning rate that restarts in cycles, whose parameters at
+//! the end of every cycle, when the rate is lowest, are kept as the members of an ensemble.
+
+use rand::Rng;
+
+use crate::{
+    dataset::Sample,
+    losses::LossFn,
+    network::{Ensemble, Network, NetworkError},
+};
+
+use super::{
+    history::TrainingHistory,
+    schedule::{Annealing, OneCycle},
+    Trainer, TrainingError,
+};
+
+/// Trains with a trainer for `cycles` cycles of `cycle_epochs` epochs each. Every cycle's rate starts at
+/// `max_rate` and falls along half a cosine wave to the minimum rate at its last batch, the schedule of a
+/// `OneCycle` without warmup. The trainer's own epochs, schedule and `restore_best` are overridden, everything
+/// else about it, like the batch size and the callbacks, applies to every cycle. The callbacks see one run:
+/// epochs are counted on across cycles and their state carries over, so e.g. a `BestLossTracker` with a
+/// patience can stop the ensemble in a later cycle.
+pub struct SnapshotEnsemble<'a, L: LossFn> {
+    trainer: Trainer<'a, L>,
+    max_rate: f32,
+    min_rate: f32,
+    cycle_epochs: usize,
+    cycles: usize,
+}
+
+/// What `SnapshotEnsemble::fit` produced besides the trained network.
+pub struct SnapshotEnsembleReport {
+    /// The epochs of all cycles, counted from 0.
+    pub history: TrainingHistory,
+    /// The last epoch of every completed cycle, when its snapshot was taken.
+    pub snapshot_epochs: Vec<usize>,
+    /// A copy of the network at the end of every completed cycle, in order.
+    pub snapshots: Vec<Network>,
+    /// Whether a callback or the stop token ended training before the last cycle. The cycle that was cut
+    /// short has no snapshot.
+    pub stopped_early: bool,
+}
+
+impl SnapshotEnsembleReport {
+    /// An ensemble of the snapshots with equal weights. Fails with `NoNetworks` if no cycle was completed.
+    pub fn ensemble(&self) -> Result<Ensemble, NetworkError> {
+        Ensemble::new(self.snapshots.clone())
+    }
+}
+
+impl<'a, L: LossFn> SnapshotEnsemble<'a, L> {
+    /// The minimum rate is `max_rate / 1000` unless set with `min_rate`. Fails with `InvalidSchedule` unless
+    /// the rate is positive and finite and there's at least one cycle of at least one epoch.
+    pub fn new(trainer: Trainer<'a, L>, max_rate: f32, cycle_epochs: usize, cycles: usize) -> Result<Self, TrainingError> {
+        if !(max_rate > 0.0 && max_rate.is_finite()) {
+            return Err(TrainingError::InvalidSchedule("maximum rate"));
+        }
+
+        if cycle_epochs == 0 {
+            return Err(TrainingError::InvalidSchedule("cycle length"));
+        }
+
+        if cycles == 0 {
+            return Err(TrainingError::InvalidSchedule("cycle count"));
+        }
+
+        Ok(Self {
+            trainer,
+            max_rate,
+            min_rate: max_rate / 1000.0,
+            cycle_epochs,
+            cycles,
+        })
+    }
+
+    /// The rate every cycle ends at, positive and at most the maximum rate.
+    pub fn min_rate(mut self, min_rate: f32) -> Result<Self, TrainingError> {
+        if !(min_rate > 0.0 && min_rate <= self.max_rate) {
+            return Err(TrainingError::InvalidSchedule("minimum rate"));
+        }
+
+        self.min_rate = min_rate;
+        Ok(self)
+    }
+
+    pub fn fit(&mut self, network: &mut Network, dataset: &[Sample], validation: &[Sample]) -> Result<SnapshotEnsembleReport, TrainingError> {
+        self.fit_with_rng(network, dataset, validation, &mut rand::rng())
+    }
+
+    /// Like `fit`, but shuffles with the given RNG, so seeded runs are reproducible.
+    pub fn fit_with_rng(
+        &mut self,
+        network: &mut Network,
+        dataset: &[Sample],
+        validation: &[Sample],
+        rng: &mut impl Rng,
+    ) -> Result<SnapshotEnsembleReport, TrainingError> {
+        let cycle = OneCycle::new(self.max_rate, self.cycle_epochs * self.trainer.batch_count(self.trainer.epoch_len(dataset)).max(1))?
+            .warmup(0.0)?
+            .initial_div(1.0)?
+            .final_div(self.max_rate / self.min_rate)?
+            .annealing(Annealing::Cosine);
+
+        self.trainer.epochs = self.cycle_epochs;
+        self.trainer.schedule = Some(cycle);
+        self.trainer.restore_best = false;
+        self.trainer.run_epochs = Some(self.cycles * self.cycle_epochs);
+
+        let mut report = SnapshotEnsembleReport {
+            history: TrainingHistory::new(),
+            snapshot_epochs: Vec::new(),
+            snapshots: Vec::new(),
+            stopped_early: false,
+        };
+
+        for _ in 0..self.cycles {
+            self.trainer.epoch_offset = report.history.len();
+            let cycle_report = self.trainer.fit_with_rng(network, dataset, validation, rng)?;
+            report.history.append(&cycle_report.history);
+
+            if cycle_report.stopped_early || cycle_report.cancelled {
+                report.stopped_early = true;
+                break;
+            }
+
+            report.snapshot_epochs.push(report.history.len() - 1);
+            report.snapshots.push(network.clone());
+        }
+
+        Ok(report)
+    }
+}
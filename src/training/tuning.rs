@@ -0,0 +1,430 @@
+use std::{
+    cell::Cell,
+    ops::{ControlFlow, RangeInclusive},
+    rc::Rc,
+    time::{Duration, Instant},
+};
+
+use rand::{rngs::StdRng, seq::IndexedRandom, Rng, SeedableRng};
+
+use crate::{
+    dataset::{self, Sample},
+    losses::LossFn,
+    network::{Network, NetworkError},
+};
+
+use super::{
+    callback::{EpochContext, TrainingCallback},
+    cross_validation::cross_validate,
+    Trainer,
+    TrainingError,
+};
+
+/// One combination of hyperparameters to try.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Candidate {
+    pub rate: f32,
+    /// `None` trains on the whole dataset as one batch.
+    pub batch_size: Option<usize>,
+    /// The hidden layer sizes, which the network factory builds the network from.
+    pub hidden_layers: Vec<usize>,
+    /// The name of the hidden layers' activation function, for `activations::from_name` in the factory.
+    pub activation: String,
+    pub epochs: usize,
+}
+
+/// How a candidate's validation loss is measured.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Validation {
+    /// Trains on the given fraction of the shuffled dataset and evaluates on the rest.
+    Split(f32),
+    /// k-fold cross-validation with the given number of folds, the loss being the mean over the folds.
+    KFold(usize),
+}
+
+/// The values to search and how to search them. Every dimension starts with a single value: a rate of 0.1,
+/// full batches, no hidden layers, sigmoid and 100 epochs, validated on a 20% split without a budget.
+#[derive(Clone, Debug)]
+pub struct Grid {
+    rates: Vec<f32>,
+    batch_sizes: Vec<Option<usize>>,
+    hidden_layers: Vec<Vec<usize>>,
+    activations: Vec<String>,
+    epochs: Vec<usize>,
+    validation: Validation,
+    max_candidates: Option<usize>,
+    time_limit: Option<Duration>,
+}
+
+/// The distributions `random_search` draws candidates from: a log-uniform learning rate, every hidden layer's
+/// size uniform in its range, and the batch size, activation function and epochs picked uniformly from their
+/// lists. It starts like `Grid`, with rates in `0.001..=1`.
+#[derive(Clone, Debug)]
+pub struct SearchSpace {
+    rates: (f32, f32),
+    batch_sizes: Vec<Option<usize>>,
+    hidden_layers: Vec<RangeInclusive<usize>>,
+    activations: Vec<String>,
+    epochs: Vec<usize>,
+    validation: Validation,
+    time_limit: Option<Duration>,
+}
+
+/// A candidate's validation loss.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Trial {
+    pub candidate: Candidate,
+    pub validation_loss: f32,
+    /// Whether training was stopped early because the training loss stopped being finite.
+    pub diverged: bool,
+}
+
+#[derive(Clone, Debug)]
+pub struct SearchReport {
+    /// The candidates evaluated, from the lowest validation loss to the highest, NaN losses last.
+    pub trials: Vec<Trial>,
+    /// The candidates the budget left out.
+    pub skipped: usize,
+}
+
+impl Default for Grid {
+    fn default() -> Self {
+        Self {
+            rates: vec![0.1],
+            batch_sizes: vec![None],
+            hidden_layers: vec![Vec::new()],
+            activations: vec!["sigmoid".to_string()],
+            epochs: vec![100],
+            validation: Validation::Split(0.8),
+            max_candidates: None,
+            time_limit: None,
+        }
+    }
+}
+
+impl Grid {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn rates(mut self, rates: impl IntoIterator<Item = f32>) -> Self {
+        self.rates = rates.into_iter().collect();
+        self
+    }
+
+    pub fn batch_sizes(mut self, batch_sizes: impl IntoIterator<Item = Option<usize>>) -> Self {
+        self.batch_sizes = batch_sizes.into_iter().collect();
+        self
+    }
+
+    pub fn hidden_layers(mut self, hidden_layers: impl IntoIterator<Item = Vec<usize>>) -> Self {
+        self.hidden_layers = hidden_layers.into_iter().collect();
+        self
+    }
+
+    pub fn activations(mut self, activations: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.activations = activations.into_iter().map(Into::into).collect();
+        self
+    }
+
+    pub fn epochs(mut self, epochs: impl IntoIterator<Item = usize>) -> Self {
+        self.epochs = epochs.into_iter().collect();
+        self
+    }
+
+    pub fn validation(mut self, validation: Validation) -> Self {
+        self.validation = validation;
+        self
+    }
+
+    /// Evaluates at most this many candidates, in the order of `candidates`.
+    pub fn max_candidates(mut self, max_candidates: usize) -> Self {
+        self.max_candidates = Some(max_candidates);
+        self
+    }
+
+    /// Starts no new candidate once the search has run for this long.
+    pub fn time_limit(mut self, time_limit: Duration) -> Self {
+        self.time_limit = Some(time_limit);
+        self
+    }
+
+    /// Every combination of the values, the rates varying slowest and the epochs fastest.
+    pub fn candidates(&self) -> Vec<Candidate> {
+        let mut candidates = Vec::new();
+
+        for &rate in self.rates.iter() {
+            for &batch_size in self.batch_sizes.iter() {
+                for hidden_layers in self.hidden_layers.iter() {
+                    for activation in self.activations.iter() {
+                        for &epochs in self.epochs.iter() {
+                            candidates.push(Candidate {
+                                rate,
+                                batch_size,
+                                hidden_layers: hidden_layers.clone(),
+                                activation: activation.clone(),
+                                epochs,
+                            });
+                        }
+                    }
+                }
+            }
+        }
+
+        candidates
+    }
+}
+
+impl Default for SearchSpace {
+    fn default() -> Self {
+        Self {
+            rates: (0.001, 1.0),
+            batch_sizes: vec![None],
+            hidden_layers: Vec::new(),
+            activations: vec!["sigmoid".to_string()],
+            epochs: vec![100],
+            validation: Validation::Split(0.8),
+            time_limit: None,
+        }
+    }
+}
+
+impl SearchSpace {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Learning rates are drawn log-uniformly from `min_rate..=max_rate`.
+    pub fn rates(mut self, min_rate: f32, max_rate: f32) -> Self {
+        self.rates = (min_rate, max_rate);
+        self
+    }
+
+    pub fn batch_sizes(mut self, batch_sizes: impl IntoIterator<Item = Option<usize>>) -> Self {
+        self.batch_sizes = batch_sizes.into_iter().collect();
+        self
+    }
+
+    /// One range per hidden layer, the layer's size being drawn uniformly from it.
+    pub fn hidden_layers(mut self, hidden_layers: impl IntoIterator<Item = RangeInclusive<usize>>) -> Self {
+        self.hidden_layers = hidden_layers.into_iter().collect();
+        self
+    }
+
+    pub fn activations(mut self, activations: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.activations = activations.into_iter().map(Into::into).collect();
+        self
+    }
+
+    pub fn epochs(mut self, epochs: impl IntoIterator<Item = usize>) -> Self {
+        self.epochs = epochs.into_iter().collect();
+        self
+    }
+
+    pub fn validation(mut self, validation: Validation) -> Self {
+        self.validation = validation;
+        self
+    }
+
+    /// Starts no new trial once the search has run for this long.
+    pub fn time_limit(mut self, time_limit: Duration) -> Self {
+        self.time_limit = Some(time_limit);
+        self
+    }
+
+    /// Draws a candidate. Fails with `InvalidRateRange`, `InvalidHiddenSizeRange` or `EmptySearchSpace` if
+    /// a distribution has nothing to draw from.
+    pub fn sample(&self, rng: &mut impl Rng) -> Result<Candidate, TrainingError> {
+        let (min_rate, max_rate) = self.rates;
+        if !(min_rate > 0.0 && max_rate > min_rate) {
+            return Err(TrainingError::InvalidRateRange { min_rate, max_rate });
+        }
+
+        let hidden_layers = self.hidden_layers
+            .iter()
+            .enumerate()
+            .map(|(layer, sizes)| {
+                if sizes.is_empty() || *sizes.start() == 0 {
+                    return Err(TrainingError::InvalidHiddenSizeRange {
+                        layer,
+                        min: *sizes.start(),
+                        max: *sizes.end(),
+                    });
+                }
+
+                Ok(rng.random_range(sizes.clone()))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Candidate {
+            rate: rng.random_range(min_rate.ln()..=max_rate.ln()).exp(),
+            batch_size: *self.batch_sizes.choose(rng).ok_or(TrainingError::EmptySearchSpace("batch sizes"))?,
+            hidden_layers,
+            activation: self.activations.choose(rng).ok_or(TrainingError::EmptySearchSpace("activations"))?.clone(),
+            epochs: *self.epochs.choose(rng).ok_or(TrainingError::EmptySearchSpace("epoch counts"))?,
+        })
+    }
+}
+
+impl SearchReport {
+    /// The candidate with the lowest validation loss.
+    pub fn best(&self) -> Option<&Trial> {
+        self.trials.first()
+    }
+}
+
+/// Trains a fresh network from `factory` for every candidate of the grid and ranks the candidates by their
+/// validation loss. Every candidate trains with a fresh trainer from `trainer`, which supplies the loss and
+/// everything else that isn't searched, with the candidate's rate, epochs and batch size set on it, so no
+/// optimizer or callback state carries over from one candidate to the next. Every candidate sees the same
+/// split or folds and the same shuffles, drawn from a seed taken from `rng`, so seeded searches are
+/// reproducible. A candidate whose training loss stops being finite is stopped right away and ranked by the
+/// loss it ended with.
+pub fn grid_search<'t, L, F, T>(
+    grid: &Grid,
+    dataset: &[Sample],
+    factory: F,
+    trainer: T,
+    rng: &mut impl Rng,
+) -> Result<SearchReport, TrainingError>
+where
+    L: LossFn + 't,
+    F: FnMut(&Candidate) -> Result<Network, NetworkError>,
+    T: Fn() -> Trainer<'t, L>,
+{
+    let budget = Budget {
+        validation: grid.validation,
+        max_candidates: grid.max_candidates,
+        time_limit: grid.time_limit,
+    };
+
+    run_trials(grid.candidates(), &budget, dataset, factory, trainer, rng.random())
+}
+
+/// `grid_search` over `trials` candidates drawn from the search space. The candidates are drawn from `rng`
+/// before any training starts.
+pub fn random_search<'t, L, F, T>(
+    space: &SearchSpace,
+    trials: usize,
+    dataset: &[Sample],
+    factory: F,
+    trainer: T,
+    rng: &mut impl Rng,
+) -> Result<SearchReport, TrainingError>
+where
+    L: LossFn + 't,
+    F: FnMut(&Candidate) -> Result<Network, NetworkError>,
+    T: Fn() -> Trainer<'t, L>,
+{
+    let candidates = (0..trials).map(|_| space.sample(rng)).collect::<Result<Vec<_>, _>>()?;
+    let budget = Budget {
+        validation: space.validation,
+        max_candidates: None,
+        time_limit: space.time_limit,
+    };
+
+    run_trials(candidates, &budget, dataset, factory, trainer, rng.random())
+}
+
+struct Budget {
+    validation: Validation,
+    max_candidates: Option<usize>,
+    time_limit: Option<Duration>,
+}
+
+/// Stops training once the training loss isn't finite, recording that it did in the shared flag.
+struct DivergenceStop(Rc<Cell<bool>>);
+
+impl TrainingCallback for DivergenceStop {
+    fn on_epoch_end(&mut self, ctx: &EpochContext) -> ControlFlow<()> {
+        if ctx.train_loss.is_finite() {
+            ControlFlow::Continue(())
+        } else {
+            self.0.set(true);
+            ControlFlow::Break(())
+        }
+    }
+}
+
+fn run_trials<'t, L, F, T>(
+    candidates: Vec<Candidate>,
+    budget: &Budget,
+    dataset: &[Sample],
+    mut factory: F,
+    trainer: T,
+    seed: u64,
+) -> Result<SearchReport, TrainingError>
+where
+    L: LossFn + 't,
+    F: FnMut(&Candidate) -> Result<Network, NetworkError>,
+    T: Fn() -> Trainer<'t, L>,
+{
+    let start = Instant::now();
+    let mut trials = Vec::new();
+
+    for candidate in candidates.iter() {
+        let over_budget = budget.max_candidates.is_some_and(|max| trials.len() >= max)
+            || budget.time_limit.is_some_and(|limit| start.elapsed() >= limit);
+
+        if over_budget {
+            break;
+        }
+
+        let diverged = Rc::new(Cell::new(false));
+        let configured = || {
+            let mut trainer = trainer();
+            trainer.rate = candidate.rate;
+            trainer.epochs = candidate.epochs;
+            trainer.batch_size = candidate.batch_size.map(|batch_size| batch_size.max(1));
+            trainer.callback(DivergenceStop(Rc::clone(&diverged)))
+        };
+
+        let mut rng = StdRng::seed_from_u64(seed);
+        let validation_loss = evaluate(budget.validation, dataset, || factory(candidate), configured, &mut rng)?;
+
+        trials.push(Trial {
+            candidate: candidate.clone(),
+            validation_loss,
+            diverged: diverged.get(),
+        });
+    }
+
+    trials.sort_by(|a, b| match (a.validation_loss.is_nan(), b.validation_loss.is_nan()) {
+        (false, false) => a.validation_loss.total_cmp(&b.validation_loss),
+        (nan_a, nan_b) => nan_a.cmp(&nan_b),
+    });
+
+    Ok(SearchReport {
+        skipped: candidates.len() - trials.len(),
+        trials,
+    })
+}
+
+fn evaluate<'t, L, F, T>(
+    validation: Validation,
+    dataset: &[Sample],
+    mut factory: F,
+    trainer: T,
+    rng: &mut impl Rng,
+) -> Result<f32, TrainingError>
+where
+    L: LossFn + 't,
+    F: FnMut() -> Result<Network, NetworkError>,
+    T: Fn() -> Trainer<'t, L>,
+{
+    match validation {
+        Validation::Split(fraction) => {
+            let (training, validation) = dataset::split(dataset, fraction, rng);
+            if validation.is_empty() {
+                return Err(NetworkError::EmptyDataset.into());
+            }
+
+            let mut trainer = trainer();
+            let mut network = factory()?;
+            trainer.fit_with_rng(&mut network, &training, &[], rng)?;
+            Ok(network.evaluate(&validation, &trainer.loss)?)
+        }
+
+        Validation::KFold(folds) => Ok(cross_validate(dataset, folds, factory, trainer, rng)?.mean_loss),
+    }
+}
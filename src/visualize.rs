@@ -0,0 +1,89 @@
+//! Scalar fields of two-input, one-output networks over a rectangle of the plane, e.g. for drawing the
+//! decision boundary of a classifier trained on `Sample::point`s. Fields are laid out like an image:
+//! row 0 is the top, `y_range.end`, and column 0 the left edge, `x_range.start`.
+
+use std::ops::Range;
+
+use nalgebra::DMatrix;
+
+use crate::network::{Network, NetworkError};
+
+/// Maps a field value to an RGBA color.
+pub trait Colormap {
+    fn color(&self, value: f32) -> [u8; 4];
+}
+
+impl<F: Fn(f32) -> [u8; 4]> Colormap for F {
+    fn color(&self, value: f32) -> [u8; 4] {
+        self(value)
+    }
+}
+
+/// Linear interpolation from `low` at 0 to `high` at 1, values outside are clamped.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Gradient {
+    pub low: [u8; 4],
+    pub high: [u8; 4],
+}
+
+impl Default for Gradient {
+    /// Blue at 0 to red at 1.
+    fn default() -> Self {
+        Self {
+            low: [0, 77, 255, 255],
+            high: [255, 77, 0, 255],
+        }
+    }
+}
+
+impl Colormap for Gradient {
+    fn color(&self, value: f32) -> [u8; 4] {
+        let t = if value.is_nan() { 0.0 } else { value.clamp(0.0, 1.0) };
+        std::array::from_fn(|i| (self.low[i] as f32 + (self.high[i] as f32 - self.low[i] as f32) * t).round() as u8)
+    }
+}
+
+/// The point of cell `(row, column)` in a grid of `resolution = (columns, rows)` cells spanning the ranges,
+/// corners included, so the top left cell is at `(x_range.start, y_range.end)`.
+pub fn grid_point(x_range: &Range<f32>, y_range: &Range<f32>, resolution: (usize, usize), row: usize, column: usize) -> (f32, f32) {
+    let fraction = |index: usize, count: usize| if count > 1 { index as f32 / (count - 1) as f32 } else { 0.0 };
+
+    (
+        x_range.start + (x_range.end - x_range.start) * fraction(column, resolution.0),
+        y_range.end - (y_range.end - y_range.start) * fraction(row, resolution.1),
+    )
+}
+
+/// The network's output at every `grid_point` of a `resolution = (columns, rows)` grid, as a rows by
+/// columns matrix. All points go through `forward_batch` at once. The network has to take 2 inputs and
+/// give 1 output.
+pub fn grid_evaluate(
+    network: &Network,
+    x_range: Range<f32>,
+    y_range: Range<f32>,
+    resolution: (usize, usize),
+) -> Result<DMatrix<f32>, NetworkError> {
+    if network.input_size() != 2 || network.output_size() != 1 {
+        return Err(NetworkError::NotPlanar {
+            inputs: network.input_size(),
+            outputs: network.output_size(),
+        });
+    }
+
+    let (columns, rows) = resolution;
+    let points = DMatrix::from_fn(2, rows * columns, |coordinate, index| {
+        let (x, y) = grid_point(&x_range, &y_range, resolution, index / columns, index % columns);
+        if coordinate == 0 { x } else { y }
+    });
+
+    let outputs = network.forward_batch(&points)?;
+    Ok(DMatrix::from_fn(rows, columns, |row, column| outputs[(0, row * columns + column)]))
+}
+
+/// The field as RGBA bytes, row by row from the top, ready for an image or texture.
+pub fn to_rgba(field: &DMatrix<f32>, colormap: &impl Colormap) -> Vec<u8> {
+    (0..field.nrows())
+        .flat_map(|row| (0..field.ncols()).map(move |column| field[(row, column)]))
+        .flat_map(|value| colormap.color(value))
+        .collect()
+}
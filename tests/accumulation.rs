@@ -0,0 +1,63 @@
+mod common;
+
+use rand::{distr::Uniform, rngs::StdRng, SeedableRng};
+
+use neural::{activations::*, dataset::Sample, losses::MSE, network::Network};
+
+use common::{assert_close, sample};
+
+fn network() -> Network<f64> {
+    Network::random_with_rng(&[2, 4, 1], sigmoid!(), &Uniform::new(-1.0, 1.0).unwrap(), &mut StdRng::seed_from_u64(7)).unwrap()
+}
+
+fn dataset() -> Vec<Sample<f64>> {
+    (0..20).map(|i| sample(&[(i as f64 * 0.7).sin(), (i as f64 * 1.3).cos()], &[(i % 3) as f64 / 2.0])).collect()
+}
+
+#[test]
+fn micro_batches_step_like_one_full_batch() {
+    let dataset = dataset();
+    let (mut accumulated, mut full) = (network(), network());
+
+    for _ in 0..3 {
+        let processed: usize = dataset.chunks(6).map(|chunk| accumulated.accumulate_gradients(chunk, &MSE).unwrap()).sum();
+        assert_eq!(processed, 20);
+        accumulated.apply_gradients(-0.5 / 20.0);
+
+        full.learn(&dataset, &MSE, 0.5).unwrap();
+        assert_close(&accumulated.get_params(), &full.get_params(), 1e-12);
+    }
+}
+
+#[test]
+fn weighted_micro_batches_scale_by_the_total_weight() {
+    let dataset: Vec<_> = dataset().into_iter().enumerate().map(|(i, sample)| sample.with_weight((i % 4) as f32 * 0.5)).collect();
+    let total_weight: f64 = dataset.iter().map(|sample| sample.weight() as f64).sum();
+    let (mut accumulated, mut full) = (network(), network());
+
+    for chunk in dataset.chunks(7) {
+        accumulated.accumulate_gradients(chunk, &MSE).unwrap();
+    }
+    accumulated.apply_gradients(-0.5 / total_weight);
+    full.learn(&dataset, &MSE, 0.5).unwrap();
+
+    assert_close(&accumulated.get_params(), &full.get_params(), 1e-12);
+}
+
+#[test]
+fn zeroing_discards_what_was_accumulated() {
+    let dataset = dataset();
+    let (mut accumulated, mut full) = (network(), network());
+
+    accumulated.accumulate_gradients(&dataset[..10], &MSE).unwrap();
+    accumulated.zero_gradients();
+    accumulated.accumulate_gradients(&dataset, &MSE).unwrap();
+    accumulated.apply_gradients(-0.5 / 20.0);
+    full.learn(&dataset, &MSE, 0.5).unwrap();
+    assert_close(&accumulated.get_params(), &full.get_params(), 1e-12);
+
+    // Applying zeroes the gradients too, so the next step starts from scratch.
+    let before = accumulated.get_params();
+    accumulated.apply_gradients(-0.5);
+    assert_eq!(accumulated.get_params(), before);
+}
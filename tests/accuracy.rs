@@ -0,0 +1,79 @@
+use nalgebra::DMatrix;
+
+use neural::{
+    activations::*,
+    dataset::Sample,
+    metrics::{accuracy, ClassificationMode},
+    network::{layer::Layer, Network, NetworkError},
+};
+
+/// A network whose outputs are its inputs, so the samples spell out what it predicts.
+fn identity(size: usize) -> Network {
+    let mut layer = Layer::zeros(size, size, identity!()).unwrap();
+    layer.set_weights(DMatrix::identity(size, size)).unwrap();
+    Network::from_layers(vec![layer]).unwrap()
+}
+
+fn binary() -> Vec<Sample> {
+    [(0.2, 0.0), (0.7, 1.0), (0.5, 0.0), (0.9, 1.0)]
+        .map(|(output, expected)| Sample::from_slices(&[output], &[expected]))
+        .to_vec()
+}
+
+#[test]
+fn binary_mode_compares_the_output_with_the_threshold() {
+    let mut network = identity(1);
+
+    // 0.5 is on the threshold and counts as class 1, which is wrong for the third sample.
+    assert_eq!(accuracy(&mut network, &binary(), ClassificationMode::default()).unwrap(), 0.75);
+    assert_eq!(accuracy(&mut network, &binary(), ClassificationMode::Binary { threshold: 0.6 }).unwrap(), 1.0);
+    assert_eq!(accuracy(&mut network, &binary(), ClassificationMode::Binary { threshold: 0.95 }).unwrap(), 0.5);
+}
+
+#[test]
+fn multi_class_mode_compares_argmaxes() {
+    let dataset = [
+        Sample::from_slices(&[0.1, 0.8, 0.1], &[0.0, 1.0, 0.0]),
+        Sample::from_slices(&[0.5, 0.2, 0.3], &[0.0, 0.0, 1.0]),
+        Sample::from_slices(&[0.3, 0.3, 0.9], &[0.0, 0.0, 1.0]),
+        Sample::from_slices(&[0.6, 0.1, 0.2], &[1.0, 0.0, 0.0]),
+        Sample::from_slices(&[-1.0, -3.0, -2.0], &[0.0, 0.2, 0.1]),
+    ];
+
+    assert_eq!(accuracy(&mut identity(3), &dataset, ClassificationMode::MultiClass).unwrap(), 0.6);
+    assert_eq!(accuracy(&mut identity(3), &dataset[..4], ClassificationMode::MultiClass).unwrap(), 0.75);
+}
+
+#[test]
+fn an_empty_dataset_is_an_error() {
+    for mode in [ClassificationMode::default(), ClassificationMode::MultiClass] {
+        assert!(matches!(accuracy(&mut identity(1), &[], mode), Err(NetworkError::EmptyDataset)));
+    }
+}
+
+#[test]
+fn invalid_class_encodings_are_reported_with_their_index() {
+    let mut dataset = binary();
+    dataset.insert(2, Sample::from_slices(&[0.4], &[0.5]));
+    assert!(matches!(
+        accuracy(&mut identity(1), &dataset, ClassificationMode::default()),
+        Err(NetworkError::InvalidClassEncoding { index: 2 })
+    ));
+
+    // Two largest expected outputs don't name a class.
+    let dataset = [
+        Sample::from_slices(&[0.1, 0.8, 0.1], &[0.0, 1.0, 0.0]),
+        Sample::from_slices(&[0.1, 0.8, 0.1], &[0.5, 0.5, 0.0]),
+    ];
+    assert!(matches!(
+        accuracy(&mut identity(3), &dataset, ClassificationMode::MultiClass),
+        Err(NetworkError::InvalidClassEncoding { index: 1 })
+    ));
+}
+
+#[test]
+fn binary_mode_needs_a_single_output() {
+    let dataset = [Sample::from_slices(&[0.1, 0.8, 0.1], &[0.0, 1.0, 0.0])];
+
+    assert!(matches!(accuracy(&mut identity(3), &dataset, ClassificationMode::default()), Err(NetworkError::NotBinary(3))));
+}
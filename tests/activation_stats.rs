@@ -0,0 +1,93 @@
+mod common;
+
+use nalgebra::{DMatrix, DVector};
+
+use neural::{
+    activations::*,
+    dataset::Sample,
+    losses::MSE,
+    network::{layer::Layer, Network, NetworkError, SaturationThresholds},
+};
+
+/// Over the XOR inputs, the ReLU units are dead, alive, constant above 0 and off for three inputs out of
+/// four, the sigmoid units are stuck within 0.01 of 1 and of 0, and the identity output is constant without bounds.
+fn network() -> Network {
+    let mut relu = Layer::zeros(2, 4, relu!()).unwrap();
+    relu.set_weights(DMatrix::from_row_slice(4, 2, &[1.0, 1.0, 1.0, 1.0, 0.0, 0.0, 1.0, -1.0])).unwrap();
+    relu.set_biases(DVector::from_vec(vec![-10.0, 0.0, 1.0, 0.0])).unwrap();
+
+    let mut sigmoid = Layer::zeros(4, 2, sigmoid!()).unwrap();
+    sigmoid.set_biases(DVector::from_vec(vec![5.0, -5.0])).unwrap();
+
+    let identity = Layer::zeros(2, 1, identity!()).unwrap();
+
+    Network::from_layers(vec![relu, sigmoid, identity]).unwrap()
+}
+
+#[test]
+fn dead_and_saturated_units_are_flagged() {
+    let stats = network().activation_stats(&common::xor()).unwrap();
+    assert_eq!(stats.len(), 3);
+
+    let relu = &stats[0];
+    assert_eq!(relu.range, (Some(0.0), None));
+    assert_eq!(relu.dead(), [0]);
+    assert_eq!(relu.saturated(), [0]);
+    assert_eq!((relu.neurons[0].min, relu.neurons[0].max, relu.neurons[0].at_lower_bound), (0.0, 0.0, 1.0));
+    // 0, 1, 1 and 2.
+    let alive = relu.neurons[1];
+    assert_eq!((alive.mean, alive.min, alive.max), (1.0, 0.0, 2.0));
+    assert!((alive.variance - 0.5).abs() < 1e-6);
+    assert_eq!((alive.at_lower_bound, alive.at_upper_bound), (0.25, 0.0));
+    // Constant, but away from the only bound.
+    assert_eq!((relu.neurons[2].mean, relu.neurons[2].variance), (1.0, 0.0));
+    assert!(!relu.neurons[2].saturated);
+    assert_eq!(relu.neurons[3].at_lower_bound, 0.75);
+
+    let sigmoid = &stats[1];
+    assert_eq!(sigmoid.range, (Some(0.0), Some(1.0)));
+    assert_eq!((sigmoid.neurons[0].at_upper_bound, sigmoid.neurons[1].at_lower_bound), (1.0, 1.0));
+    assert_eq!(sigmoid.dead(), [1]);
+    assert_eq!(sigmoid.saturated(), [0, 1]);
+
+    let identity = &stats[2];
+    assert_eq!(identity.range, (None, None));
+    assert!(identity.saturated().is_empty());
+}
+
+#[test]
+fn the_thresholds_decide_what_counts_as_stuck() {
+    let thresholds = SaturationThresholds { epsilon: 1e-2, fraction: 0.75 };
+    let stats = network().activation_stats_with(&common::xor(), &thresholds).unwrap();
+    assert_eq!(stats[0].dead(), [0, 3]);
+
+    // Only activations exactly at a bound count, which the sigmoid units never reach.
+    let thresholds = SaturationThresholds { epsilon: 0.0, fraction: 0.99 };
+    let stats = network().activation_stats_with(&common::xor(), &thresholds).unwrap();
+    assert_eq!(stats[0].dead(), [0]);
+    assert!(stats[1].saturated().is_empty());
+}
+
+#[test]
+fn rejects_empty_and_mismatched_datasets() {
+    assert!(matches!(network().activation_stats(&[]), Err(NetworkError::EmptyDataset)));
+    assert!(network().activation_stats(&[Sample::from_slices(&[1.0, 2.0, 3.0], &[0.0])]).is_err());
+}
+
+#[test]
+fn training_afterwards_is_unaffected() {
+    let dataset = common::xor();
+    let mut network = network();
+    network.backpropagate(&dataset[..2], &MSE).unwrap();
+    let mut reference = network.clone();
+
+    network.activation_stats(&dataset).unwrap();
+
+    for _ in 0..3 {
+        network.learn(&dataset, &MSE, 0.1).unwrap();
+        reference.learn(&dataset, &MSE, 0.1).unwrap();
+    }
+    assert_eq!(network.get_params(), reference.get_params());
+    // The dead unit gets no gradient, so its weights stay where they were.
+    assert_eq!(network.dense_layer(0).unwrap().weights().row(0), DMatrix::from_row_slice(1, 2, &[1.0, 1.0]));
+}
@@ -0,0 +1,169 @@
+mod common;
+
+use nalgebra::{DMatrix, DVector};
+
+use neural::{
+    activations::{self, softmax_with_temperature, verify_derivative, ActivationFn, HardSigmoid, Sigmoid, Softsign},
+    dataset::Sample,
+    losses::MSE,
+    metrics::{activation_swap, ActivationSwap, ClassificationMode},
+    network::{
+        layer::{Layer, LayerError},
+        Network,
+    },
+};
+
+/// Points on both sides of 0, away from the kinks of ReLU at 0 and HardSigmoid at ±2.5.
+const POINTS: [f32; 9] = [-6.0, -3.0, -1.5, -0.75, -0.1, 0.3, 1.2, 2.0, 4.0];
+
+#[test]
+fn built_in_derivatives_match_central_differences() {
+    for name in ["sigmoid", "tanh", "relu", "identity", "softsign", "hard_sigmoid"] {
+        let f = activations::from_name::<f32>(name).unwrap();
+
+        if let Err(mismatch) = verify_derivative(f.as_ref(), &POINTS, 1e-3) {
+            panic!("{name}: {mismatch:?}");
+        }
+    }
+}
+
+/// Softsign with the derivative of tanh.
+#[derive(Clone)]
+struct WrongSoftsign;
+impl ActivationFn for WrongSoftsign {
+    fn apply(&self, x: f32) -> f32 {
+        x / (1.0 + x.abs())
+    }
+
+    fn derivative(&self, _x: f32, activation: f32) -> f32 {
+        1.0 - activation * activation
+    }
+
+    fn name(&self) -> &'static str {
+        "wrong_softsign"
+    }
+}
+
+#[test]
+fn slice_methods_match_the_scalar_ones() {
+    let names = ["sigmoid", "tanh", "relu", "identity", "softsign", "hard_sigmoid"];
+    let activation_fns = names.map(|name| activations::from_name::<f32>(name).unwrap());
+    // `WrongSoftsign` doesn't override the slice methods, so it checks the provided fallbacks.
+    for f in activation_fns.iter().map(AsRef::as_ref).chain([&WrongSoftsign as &dyn ActivationFn]) {
+        let mut outputs = POINTS;
+        f.apply_slice(&mut outputs);
+        assert_eq!(outputs, POINTS.map(|x| f.apply(x)), "{}", f.name());
+
+        let mut derivatives = [0.0; POINTS.len()];
+        f.derivative_slice(&POINTS, &outputs, &mut derivatives);
+        let expected: Vec<f32> = POINTS.iter().zip(&outputs).map(|(&x, &activation)| f.derivative(x, activation)).collect();
+        assert_eq!(derivatives.as_slice(), expected, "{}", f.name());
+    }
+}
+
+/// The identity, except that the slice methods negate and zero, to tell which path a layer takes.
+#[derive(Clone)]
+struct SliceOnly;
+impl ActivationFn for SliceOnly {
+    fn apply(&self, x: f32) -> f32 {
+        x
+    }
+
+    fn derivative(&self, _x: f32, _activation: f32) -> f32 {
+        1.0
+    }
+
+    fn apply_slice(&self, xs: &mut [f32]) {
+        xs.iter_mut().for_each(|x| *x = -*x);
+    }
+
+    fn derivative_slice(&self, _xs: &[f32], _activations: &[f32], out: &mut [f32]) {
+        out.fill(0.0);
+    }
+
+    fn name(&self) -> &'static str {
+        "slice_only"
+    }
+}
+
+#[test]
+fn layers_go_through_the_slice_methods() {
+    let mut layer = Layer::zeros(2, 2, Box::new(SliceOnly)).unwrap();
+    layer.set_weights(DMatrix::identity(2, 2)).unwrap();
+    let inputs = DVector::from_vec(vec![1.0, -2.0]);
+
+    assert_eq!(layer.predict(inputs.as_view()).unwrap(), -&inputs);
+
+    let mut network = Network::from_layers(vec![layer]).unwrap();
+    assert_eq!(network.forward(inputs.clone()).unwrap(), -&inputs);
+
+    network.backpropagate(&[Sample::new(inputs, DVector::from_vec(vec![5.0, 5.0]))], &MSE).unwrap();
+    assert!(network.dense_layer(0).unwrap().weight_gradient().iter().all(|&gradient| gradient == 0.0));
+}
+
+#[test]
+fn a_wrong_derivative_is_caught() {
+    let mismatch = verify_derivative(&WrongSoftsign, &POINTS, 1e-3).unwrap_err();
+
+    assert!(POINTS.contains(&mismatch.x) && mismatch.relative_error > 1e-3);
+}
+
+#[test]
+fn softmax_with_temperature_rejects_temperatures_that_arent_positive() {
+    let logits = DVector::from_vec(vec![1.0f32, 2.0, 3.0]);
+
+    for temperature in [0.0, -1.0, f32::INFINITY, f32::NAN] {
+        assert!(matches!(softmax_with_temperature(logits.as_view(), temperature), Err(LayerError::InvalidTemperature(_))));
+    }
+
+    // A high temperature flattens the distribution, a low one sharpens it.
+    let flat = softmax_with_temperature(logits.as_view(), 100.0).unwrap();
+    let sharp = softmax_with_temperature(logits.as_view(), 0.01).unwrap();
+    assert!((flat.sum() - 1.0).abs() < 1e-6 && (flat[2] - flat[0]).abs() < 0.01);
+    assert!(sharp[2] > 0.999);
+}
+
+#[test]
+fn hard_sigmoid_is_flat_outside_its_slope() {
+    for (x, value, derivative) in [(-10.0, 0.0, 0.0), (-2.5, 0.0, 0.0), (0.0, 0.5, 0.2), (1.0, 0.7, 0.2), (2.5, 1.0, 0.0), (7.0, 1.0, 0.0)] {
+        let activation: f32 = HardSigmoid.apply(x);
+        assert!((activation - value).abs() < 1e-6, "{x}: {activation}");
+        assert_eq!(HardSigmoid.derivative(x, activation), derivative, "{x}");
+    }
+}
+
+#[test]
+fn softsign_approaches_its_bounds_polynomially() {
+    for (x, value) in [(0.0, 0.0), (1.0, 0.5), (-3.0, -0.75), (99.0, 0.99)] {
+        let activation: f32 = Softsign.apply(x);
+        assert!((activation - value).abs() < 1e-6, "{x}: {activation}");
+        assert!((Softsign.derivative(x, activation) - 1.0 / (1.0 + x.abs()).powi(2)).abs() < 1e-6, "{x}");
+    }
+}
+
+/// XOR from saturated sigmoid units, which keep their outputs on the same side of 0.5 under `HardSigmoid`.
+fn xor() -> Network {
+    let mut hidden = Layer::zeros(2, 2, Box::new(Sigmoid)).unwrap();
+    hidden.set_weights(DMatrix::from_row_slice(2, 2, &[20.0, 20.0, -20.0, -20.0])).unwrap();
+    hidden.set_biases(DVector::from_vec(vec![-10.0, 30.0])).unwrap();
+
+    let mut output = Layer::zeros(2, 1, Box::new(Sigmoid)).unwrap();
+    output.set_weights(DMatrix::from_row_slice(1, 2, &[20.0, 20.0])).unwrap();
+    output.set_biases(DVector::from_vec(vec![-30.0])).unwrap();
+
+    Network::from_layers(vec![hidden, output]).unwrap()
+}
+
+#[test]
+fn a_sigmoid_network_keeps_its_accuracy_with_hard_sigmoid() {
+    let mut network = xor();
+    let swap = activation_swap(&mut network, &common::xor(), "sigmoid", &HardSigmoid, ClassificationMode::default()).unwrap();
+
+    assert_eq!(swap, ActivationSwap { replaced: 2, accuracy_before: 1.0, accuracy_after: 1.0 });
+    assert_eq!(swap.delta(), 0.0);
+
+    // The swap happened on a copy, so the network still has both sigmoid layers to replace.
+    assert_eq!(network.replace_activation("sigmoid", &HardSigmoid), 2);
+    assert_eq!(network.replace_activation("sigmoid", &HardSigmoid), 0);
+    assert_eq!(network.replace_activation("hard_sigmoid", &Sigmoid), 2);
+}
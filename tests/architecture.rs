@@ -0,0 +1,119 @@
+mod common;
+
+use nalgebra::DVector;
+use rand::{distr::Uniform, rngs::StdRng, SeedableRng};
+
+use neural::{
+    activations::*,
+    dataset::Sample,
+    losses::MSE,
+    network::{Network, NetworkError},
+    training::Trainer,
+};
+
+use common::xor;
+
+fn network() -> Network {
+    Network::random_with_rng(&[3, 4, 2], sigmoid!(), &Uniform::new(-1.0, 1.0).unwrap(), &mut StdRng::seed_from_u64(6)).unwrap()
+}
+
+fn outputs(network: &Network) -> Vec<DVector<f32>> {
+    [[0.5, -1.0, 2.0], [0.0, 0.0, 0.0], [-3.0, 1.5, 0.25]]
+        .iter()
+        .map(|inputs| network.predict(DVector::from_row_slice(inputs).as_view()).unwrap())
+        .collect()
+}
+
+#[test]
+fn inserting_an_identity_layer_keeps_the_outputs() {
+    let original = network();
+    let before = outputs(&original);
+
+    // In front of the first layer, between the layers as wide as its inputs and wider, and after the last.
+    for (index, size) in [(0, 3), (1, 4), (1, 7), (2, 2)] {
+        let mut grown = original.clone();
+        grown.insert_layer(index, size, identity!()).unwrap();
+
+        assert_eq!(grown.layer_sizes().len(), 4);
+        assert_eq!(outputs(&grown), before, "index {index}, size {size}");
+    }
+}
+
+#[test]
+fn rejects_insertions_that_change_the_outputs() {
+    let mut network = network();
+
+    assert!(matches!(
+        network.insert_layer(1, 4, sigmoid!()),
+        Err(NetworkError::InsertionChangesFunction { size: 4, inputs: 4, activation: "sigmoid" })
+    ));
+    assert!(matches!(
+        network.insert_layer(1, 3, identity!()),
+        Err(NetworkError::InsertionChangesFunction { size: 3, inputs: 4, activation: "identity" })
+    ));
+    assert_eq!(network.layer_sizes(), [3, 4, 2]);
+}
+
+#[test]
+fn replacing_the_output_layer_keeps_the_hidden_layers() {
+    let mut rng = StdRng::seed_from_u64(1);
+    let mut network = Network::random_with_rng(&[3, 4, 5, 2], sigmoid!(), &Uniform::new(-1.0, 1.0).unwrap(), &mut rng).unwrap();
+    let original = network.clone();
+
+    network.replace_output_layer_with_rng(6, identity!(), &Uniform::new(-0.1, 0.1).unwrap(), &mut rng).unwrap();
+
+    assert_eq!(network.layer_sizes(), [3, 4, 5, 6]);
+    for layer in 0..2 {
+        let (kept, before) = (network.dense_layer(layer).unwrap(), original.dense_layer(layer).unwrap());
+        assert_eq!(kept.weights(), before.weights());
+        assert_eq!(kept.biases(), before.biases());
+    }
+
+    let output = network.dense_layer(2).unwrap();
+    assert_eq!(network.layer(2).unwrap().activation_fn().unwrap().name(), "identity");
+    assert!(output.weights().iter().chain(output.biases().iter()).all(|x| x.abs() <= 0.1));
+
+    assert!(matches!(network.replace_output_layer(0, sigmoid!(), &Uniform::new(-0.1, 0.1).unwrap()), Err(NetworkError::ZeroLayerSize(3))));
+    assert_eq!(network.layer_sizes(), [3, 4, 5, 6]);
+}
+
+#[test]
+fn truncating_returns_the_layers_cut_off() {
+    let mut network = Network::random_with_rng(&[3, 4, 5, 2], sigmoid!(), &Uniform::new(-1.0, 1.0).unwrap(), &mut StdRng::seed_from_u64(2)).unwrap();
+    let original = network.clone();
+
+    assert!(matches!(network.truncate_to(0), Err(NetworkError::NoLayers)));
+    assert!(matches!(network.truncate_to(4), Err(NetworkError::LayerIndexOutOfRange { index: 3, layers: 3 })));
+
+    let removed = network.truncate_to(1).unwrap();
+    assert_eq!(network.layer_sizes(), [3, 4]);
+    assert_eq!(removed.iter().map(|layer| (layer.input_size(), layer.output_size())).collect::<Vec<_>>(), [(4, 5), (5, 2)]);
+
+    // The network now outputs what the first layer did.
+    let inputs = DVector::from_row_slice(&[0.5, -1.0, 2.0]);
+    assert_eq!(network.predict(inputs.as_view()).unwrap(), original.dense_layer(0).unwrap().predict(inputs.as_view()).unwrap());
+}
+
+#[test]
+fn a_pretrained_network_fine_tunes_for_a_new_task() {
+    let mut rng = StdRng::seed_from_u64(3);
+    let mut network = Network::random_with_rng(&[2, 6, 1], sigmoid!(), &Uniform::new(-1.0, 1.0).unwrap(), &mut rng).unwrap();
+    Trainer::new(MSE, 2.0, 4000).fit_with_rng(&mut network, &xor(), &[], &mut rng).unwrap();
+
+    // The new task has two outputs, XNOR and XOR, which the features learned for XOR already separate.
+    network.replace_output_layer_with_rng(2, sigmoid!(), &Uniform::new(-1.0, 1.0).unwrap(), &mut rng).unwrap();
+    network.freeze_layers(0..1).unwrap();
+    let hidden = network.dense_layer(0).unwrap().clone();
+
+    let dataset: Vec<Sample> = xor()
+        .iter()
+        .map(|sample| {
+            let target = sample.expected_outputs()[0];
+            Sample::from_slices(sample.inputs().as_slice(), &[1.0 - target, target])
+        })
+        .collect();
+    Trainer::new(MSE, 2.0, 2000).fit_with_rng(&mut network, &dataset, &[], &mut rng).unwrap();
+
+    assert_eq!(network.dense_layer(0).unwrap().weights(), hidden.weights());
+    assert!(network.evaluate(&dataset, &MSE).unwrap() < 0.01);
+}
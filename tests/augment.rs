@@ -0,0 +1,160 @@
+mod common;
+
+use std::cell::RefCell;
+
+use nalgebra::DVector;
+use rand::{distr::Uniform, rngs::StdRng, Rng, RngCore, SeedableRng};
+
+use neural::{
+    activations::*,
+    dataset::{Augment, FnAugment, MaskAugment, NoiseAugment, Sample},
+    losses::MSE,
+    network::Network,
+    training::Trainer,
+};
+
+use common::xor;
+
+fn sample() -> Sample {
+    Sample::from_slices(&[1.0, -2.0], &[0.5]).with_weight(2.0).with_tag("original")
+}
+
+#[test]
+fn noise_has_the_requested_mean_and_standard_deviation() {
+    let mut rng = StdRng::seed_from_u64(22);
+    let original = sample();
+    let draws = 20_000;
+
+    let mut sums = [0.0f64; 2];
+    let mut squares = [0.0f64; 2];
+    let mut products = 0.0f64;
+    for _ in 0..draws {
+        let augmented = NoiseAugment::new(0.5).augment(&original, &mut rng);
+        let noise: Vec<f64> = augmented.inputs().iter().zip(original.inputs().iter()).map(|(a, b)| f64::from(a - b)).collect();
+
+        for i in 0..2 {
+            sums[i] += noise[i];
+            squares[i] += noise[i] * noise[i];
+        }
+        products += noise[0] * noise[1];
+        assert_eq!(augmented.expected_outputs(), original.expected_outputs());
+        assert_eq!((augmented.weight(), augmented.tag()), (2.0, Some("original")));
+    }
+
+    let draws = f64::from(draws);
+    for i in 0..2 {
+        let mean = sums[i] / draws;
+        let std_dev = (squares[i] / draws - mean * mean).sqrt();
+        assert!(mean.abs() < 0.02, "input {i}: mean {mean}");
+        assert!((std_dev - 0.5).abs() < 0.015, "input {i}: standard deviation {std_dev}");
+    }
+    // The inputs get independent noise, so it's uncorrelated.
+    assert!((products / draws / 0.25).abs() < 0.03);
+}
+
+#[test]
+fn masking_zeroes_inputs_with_the_given_probability() {
+    let mut rng = StdRng::seed_from_u64(23);
+    let original = Sample::from_slices(&[1.0; 10], &[1.0]);
+
+    let masked: usize = (0..2000)
+        .map(|_| MaskAugment::new(0.3).augment(&original, &mut rng).inputs().iter().filter(|&&input| input == 0.0).count())
+        .sum();
+
+    assert!((masked as f32 / 20_000.0 - 0.3).abs() < 0.015, "{masked}");
+}
+
+#[test]
+fn xor_still_trains_on_noisy_inputs() {
+    let dataset = xor();
+    let mut network = Network::random_with_rng(&[2, 4, 1], sigmoid!(), &Uniform::new(-1.0, 1.0).unwrap(), &mut StdRng::seed_from_u64(24)).unwrap();
+
+    Trainer::new(MSE, 2.0, 3000)
+        .augment(NoiseAugment::new(0.05))
+        .fit_with_rng(&mut network, &dataset, &[], &mut StdRng::seed_from_u64(25))
+        .unwrap();
+
+    // Evaluated on the clean inputs.
+    assert!(network.evaluate(&dataset, &MSE).unwrap() < 0.01);
+}
+
+/// Every combination of 4 bits with each bit given twice, and the input as its own expected output.
+fn redundant_patterns() -> Vec<Sample> {
+    (0..16)
+        .map(|bits: u32| {
+            let pattern: Vec<f32> = (0..8).map(|i| ((bits >> (i / 2)) & 1) as f32).collect();
+            Sample::from_slices(&pattern, &pattern)
+        })
+        .collect()
+}
+
+#[test]
+fn corruption_leaves_the_dataset_and_validation_clean() {
+    let dataset = redundant_patterns();
+    let validation = redundant_patterns();
+    let mut network = Network::random_with_rng(&[8, 3, 8], sigmoid!(), &Uniform::new(-1.0, 1.0).unwrap(), &mut StdRng::seed_from_u64(30)).unwrap();
+
+    let report = Trainer::new(MSE, 1.0, 5)
+        .augment(NoiseAugment::new(10.0))
+        .fit_with_rng(&mut network, &dataset, &validation, &mut StdRng::seed_from_u64(31))
+        .unwrap();
+
+    for (sample, original) in dataset.iter().chain(validation.iter()).zip(redundant_patterns().iter().cycle()) {
+        assert_eq!(sample.inputs(), original.inputs());
+    }
+
+    // The last validation loss is that of the clean inputs, the training losses those of the very noisy ones.
+    let validation_loss = report.history.validation_losses().last().unwrap().unwrap();
+    assert!((validation_loss - network.evaluate(&validation, &MSE).unwrap()).abs() < 1e-6);
+    assert!(report.history.train_losses().last().unwrap() > &validation_loss);
+}
+
+#[test]
+fn every_epoch_draws_fresh_corruption_reproducibly() {
+    let corrupted_inputs = |seed: u64| {
+        let seen = RefCell::new(Vec::new());
+        let record = FnAugment(|inputs: &DVector<f32>, rng: &mut dyn RngCore| {
+            let corrupted = inputs.map(|input| if rng.random::<f32>() < 0.5 { 0.0 } else { input });
+            seen.borrow_mut().push(corrupted.clone());
+            corrupted
+        });
+
+        let mut network = Network::random_with_rng(&[8, 3, 8], sigmoid!(), &Uniform::new(-1.0, 1.0).unwrap(), &mut StdRng::seed_from_u64(32)).unwrap();
+        Trainer::new(MSE, 1.0, 3).augment(record).fit_with_rng(&mut network, &redundant_patterns(), &[], &mut StdRng::seed_from_u64(seed)).unwrap();
+        seen.into_inner()
+    };
+
+    let inputs = corrupted_inputs(33);
+    // 16 samples in each of 3 epochs, every one corrupted anew.
+    assert_eq!(inputs.len(), 48);
+    assert_ne!(inputs[..16], inputs[16..32]);
+    assert_ne!(inputs[16..32], inputs[32..]);
+
+    assert_eq!(corrupted_inputs(33), inputs);
+    assert_ne!(corrupted_inputs(34), inputs);
+}
+
+#[test]
+fn denoising_reconstructs_masked_inputs_better() {
+    let dataset = redundant_patterns();
+    let train = |augment: Option<MaskAugment>| {
+        let mut network = Network::random_with_rng(&[8, 16, 8], sigmoid!(), &Uniform::new(-1.0, 1.0).unwrap(), &mut StdRng::seed_from_u64(35)).unwrap();
+        let trainer = Trainer::new(MSE, 2.0, 2000);
+        let mut trainer = match augment {
+            Some(augment) => trainer.augment(augment),
+            None => trainer,
+        };
+
+        trainer.fit_with_rng(&mut network, &dataset, &[], &mut StdRng::seed_from_u64(36)).unwrap();
+        network
+    };
+
+    let (plain, denoising) = (train(None), train(Some(MaskAugment::new(0.25))));
+
+    // Masked inputs with the clean patterns as expected outputs.
+    let mut rng = StdRng::seed_from_u64(37);
+    let masked: Vec<Sample> = (0..20).flat_map(|_| dataset.iter().map(|sample| MaskAugment::new(0.25).augment(sample, &mut rng)).collect::<Vec<_>>()).collect();
+
+    let (plain_loss, denoising_loss) = (plain.evaluate(&masked, &MSE).unwrap(), denoising.evaluate(&masked, &MSE).unwrap());
+    assert!(denoising_loss < 0.6 * plain_loss, "denoising {denoising_loss} vs plain {plain_loss}");
+}
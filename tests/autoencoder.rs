@@ -0,0 +1,157 @@
+use nalgebra::{DMatrix, DVector};
+use rand::{distr::Uniform, rngs::StdRng, Rng, SeedableRng};
+
+use neural::{
+    activations::*,
+    losses::{BCEWithLogits, LossFn, MSE},
+    network::{Autoencoder, NetworkError},
+};
+
+fn autoencoder(sizes: &[usize], activation_fn: Box<dyn ActivationFn>, seed: u64) -> Autoencoder {
+    Autoencoder::tied_with_rng(sizes, activation_fn, &mut StdRng::seed_from_u64(seed)).unwrap()
+}
+
+/// Points near a line in 4 dimensions, so 2 code values are enough to reconstruct them.
+fn toy_inputs() -> Vec<DVector<f32>> {
+    let mut rng = StdRng::seed_from_u64(9);
+    let noise = Uniform::new(-0.05, 0.05).unwrap();
+
+    (0..32)
+        .map(|_| {
+            let t: f32 = rng.random_range(0.0..1.0);
+            DVector::from_fn(4, |i, _| 0.2 + 0.6 * if i % 2 == 0 { t } else { 1.0 - t } + rng.sample(noise))
+        })
+        .collect()
+}
+
+/// The reconstruction loss of a one-stage identity autoencoder whose encoder and decoder weights are
+/// given separately, in f64 so that central differences are exact up to rounding.
+fn untied_loss(encoder: &DMatrix<f64>, decoder: &DMatrix<f64>, autoencoder: &Autoencoder, input: &DVector<f64>) -> f64 {
+    let code = encoder * input + autoencoder.encoder_biases()[0].map(f64::from);
+    let reconstruction = decoder.tr_mul(&code) + autoencoder.decoder_biases()[0].map(f64::from);
+    (reconstruction - input).norm_squared() / input.len() as f64
+}
+
+#[test]
+fn the_decoder_uses_the_transposed_encoder_weights() {
+    let autoencoder = autoencoder(&[4, 2], identity!(), 1);
+    let weights = &autoencoder.weights()[0];
+    let input = DVector::from_vec(vec![0.5, -1.0, 2.0, 0.25]);
+
+    let code = autoencoder.encode(input.as_view()).unwrap();
+    assert!((&code - weights * &input).amax() < 1e-6);
+    assert!((autoencoder.decode(code.as_view()).unwrap() - weights.transpose() * &code).amax() < 1e-6);
+    assert!((autoencoder.reconstruct(input.as_view()).unwrap() - weights.transpose() * weights * &input).amax() < 1e-5);
+
+    assert_eq!(autoencoder.sizes(), [4, 2]);
+    assert_eq!((autoencoder.input_size(), autoencoder.code_size()), (4, 2));
+    // 8 shared weights, 2 encoder and 4 decoder biases.
+    assert_eq!(autoencoder.parameter_count(), 14);
+}
+
+#[test]
+fn the_tied_gradient_is_the_sum_of_the_encoder_and_decoder_gradients() {
+    let mut autoencoder = autoencoder(&[3, 2], identity!(), 2);
+    let input = DVector::from_vec(vec![0.8, -0.3, 0.5]);
+    autoencoder.backpropagate(std::slice::from_ref(&input), &MSE).unwrap();
+
+    let weights = autoencoder.weights()[0].map(f64::from);
+    let input = input.map(f64::from);
+    let step = 1e-4;
+
+    for (row, column) in (0..2).flat_map(|row| (0..3).map(move |column| (row, column))) {
+        let partial = |encoder_step: f64, decoder_step: f64| {
+            let (mut encoder, mut decoder) = (weights.clone(), weights.clone());
+            encoder[(row, column)] += encoder_step;
+            decoder[(row, column)] += decoder_step;
+            untied_loss(&encoder, &decoder, &autoencoder, &input)
+        };
+
+        let encoder_gradient = (partial(step, 0.0) - partial(-step, 0.0)) / (2.0 * step);
+        let decoder_gradient = (partial(0.0, step) - partial(0.0, -step)) / (2.0 * step);
+        let tied_gradient = autoencoder.weight_gradients()[0][(row, column)] as f64;
+
+        // Both passes contribute, so neither alone is the tied gradient.
+        assert!(encoder_gradient.abs() > 1e-3 && decoder_gradient.abs() > 1e-3);
+        assert!((tied_gradient - (encoder_gradient + decoder_gradient)).abs() < 1e-5, "({row}, {column}): {tied_gradient} vs {encoder_gradient} + {decoder_gradient}");
+    }
+}
+
+#[test]
+fn deep_tied_gradients_match_central_differences() {
+    let mut autoencoder = autoencoder(&[4, 3, 2], sigmoid!(), 3);
+    let inputs = toy_inputs()[..4].to_vec();
+    autoencoder.backpropagate(&inputs, &MSE).unwrap();
+    let analytic: Vec<f32> = autoencoder.weight_gradients().iter().flat_map(|gradient| gradient.iter().copied()).collect();
+
+    let total_loss = |autoencoder: &Autoencoder| -> f64 {
+        inputs.iter().map(|input| MSE.apply(autoencoder.reconstruct(input.as_view()).unwrap().as_view(), input.as_view()).unwrap() as f64).sum()
+    };
+
+    let step = 1e-2;
+    let mut numeric = Vec::new();
+    for stage in 0..2 {
+        for i in 0..autoencoder.weights()[stage].len() {
+            let mut perturbed = autoencoder.clone();
+            perturbed.weights_mut()[stage].as_mut_slice()[i] += step;
+            let plus = total_loss(&perturbed);
+            perturbed.weights_mut()[stage].as_mut_slice()[i] -= 2.0 * step;
+            let minus = total_loss(&perturbed);
+            numeric.push((plus - minus) / (2.0 * step as f64));
+        }
+    }
+
+    let scale = numeric.iter().fold(0.0f64, |max, x| max.max(x.abs()));
+    for (i, (&analytic, numeric)) in analytic.iter().zip(numeric).enumerate() {
+        assert!((analytic as f64 - numeric).abs() < 1e-2 * scale, "weight {i}: {analytic} vs {numeric}");
+    }
+}
+
+#[test]
+fn the_weights_stay_tied_while_learning() {
+    let mut autoencoder = autoencoder(&[4, 3, 2], sigmoid!(), 4);
+    let before = autoencoder.weights().to_vec();
+
+    for _ in 0..10 {
+        autoencoder.learn(&toy_inputs(), &MSE, 1.0).unwrap();
+    }
+
+    // Still one matrix per stage, moved by the updates, that the decoder reads transposed.
+    assert_eq!(autoencoder.weights().len(), 2);
+    assert!((&autoencoder.weights()[0] - &before[0]).amax() > 1e-4);
+    assert!(autoencoder.weight_gradients().iter().all(|gradient| gradient.amax() == 0.0));
+
+    let code = DVector::from_vec(vec![0.3, 0.7]);
+    let sigmoid = |x: f32| 1.0 / (1.0 + (-x).exp());
+    let hidden = (autoencoder.weights()[1].tr_mul(&code) + &autoencoder.decoder_biases()[1]).map(sigmoid);
+    let expected = (autoencoder.weights()[0].tr_mul(&hidden) + &autoencoder.decoder_biases()[0]).map(sigmoid);
+    assert!((autoencoder.decode(code.as_view()).unwrap() - expected).amax() < 1e-6);
+}
+
+#[test]
+fn reconstruction_error_decreases_on_toy_data() {
+    let mut autoencoder = autoencoder(&[4, 2], sigmoid!(), 5);
+    let inputs = toy_inputs();
+
+    let first = autoencoder.learn(&inputs, &MSE, 2.0).unwrap();
+    let mut last = first;
+    for _ in 0..2000 {
+        last = autoencoder.learn(&inputs, &MSE, 2.0).unwrap();
+    }
+
+    assert!(last < first / 5.0, "{first} -> {last}");
+}
+
+#[test]
+fn invalid_inputs_and_losses_are_rejected() {
+    let mut autoencoder = autoencoder(&[4, 2], sigmoid!(), 6);
+    let inputs = vec![DVector::from_element(4, 0.5), DVector::from_element(3, 0.5)];
+
+    assert!(matches!(autoencoder.learn(&inputs, &MSE, 1.0), Err(NetworkError::InputSizeMismatch { index: 1, inputs: 3, network_inputs: 4 })));
+    assert!(matches!(autoencoder.encode(DVector::zeros(5).as_view()), Err(NetworkError::InputSizeMismatch { .. })));
+    assert!(matches!(autoencoder.decode(DVector::zeros(4).as_view()), Err(NetworkError::InputSizeMismatch { .. })));
+    assert!(matches!(autoencoder.learn(&inputs[..1], &BCEWithLogits, 1.0), Err(NetworkError::OutputActivationMismatch { .. })));
+    assert!(autoencoder.weight_gradients().iter().all(|gradient| gradient.amax() == 0.0));
+
+    assert!(Autoencoder::tied_with_rng(&[4], sigmoid!(), &mut StdRng::seed_from_u64(0)).is_err());
+}
@@ -0,0 +1,94 @@
+mod common;
+
+use std::{thread, time::Duration};
+
+use nalgebra::{DVector, DVectorView};
+use rand::{distr::Uniform, rngs::StdRng, SeedableRng};
+
+use neural::{
+    activations::*,
+    losses::{LossFn, LossFnError, MSE},
+    network::Network,
+    training::background::BackgroundTrainer,
+};
+
+fn network() -> Network {
+    Network::random_with_rng(&[2, 4, 1], sigmoid!(), &Uniform::new(-1.0, 1.0).unwrap(), &mut StdRng::seed_from_u64(3)).unwrap()
+}
+
+/// Panics on the first training step.
+struct Panicking;
+
+impl LossFn for Panicking {
+    fn apply(&self, _output: DVectorView<f32>, _expected_output: DVectorView<f32>) -> Result<f32, LossFnError> {
+        panic!("the loss failed");
+    }
+
+    fn partial_gradient(&self, _output: DVectorView<f32>, _expected_output: DVectorView<f32>) -> Result<DVector<f32>, LossFnError> {
+        panic!("the loss failed");
+    }
+}
+
+fn wait_for(mut condition: impl FnMut() -> bool) {
+    for _ in 0..1000 {
+        if condition() {
+            return;
+        }
+
+        thread::sleep(Duration::from_millis(5));
+    }
+
+    panic!("timed out");
+}
+
+#[test]
+fn publishes_only_the_latest_round() {
+    let trainer = BackgroundTrainer::spawn(network(), Vec::new(), MSE, 0.5, 10).unwrap();
+    assert_eq!(trainer.progress(), None);
+
+    trainer.add_samples(common::xor()).unwrap();
+    wait_for(|| trainer.progress().is_some_and(|progress| progress.epoch >= 50));
+
+    // Progress isn't queued, every call sees the last round.
+    let progress = trainer.progress().unwrap();
+    assert_eq!(progress.epoch % 10, 0);
+    assert!(progress.loss.is_finite());
+
+    let outputs = trainer.with_network(|network| network.predict(common::xor()[0].inputs()).unwrap());
+    assert_eq!(outputs.len(), 1);
+
+    let trained = trainer.stop().unwrap();
+    assert_eq!(trained.input_size(), 2);
+}
+
+#[test]
+fn dropping_ignores_a_panicked_worker() {
+    let trainer = BackgroundTrainer::spawn(network(), common::xor(), Panicking, 0.5, 1).unwrap();
+    wait_for(|| !trainer.is_running());
+
+    // The network from before the panic is still there.
+    assert_eq!(trainer.network().get_params(), network().get_params());
+    drop(trainer);
+}
+
+#[test]
+#[should_panic(expected = "the loss failed")]
+fn stopping_resumes_a_worker_panic() {
+    let trainer = BackgroundTrainer::spawn(network(), common::xor(), Panicking, 0.5, 1).unwrap();
+    wait_for(|| !trainer.is_running());
+    let _ = trainer.stop();
+}
+
+#[test]
+fn the_stop_token_stops_the_worker_from_another_thread() {
+    let trainer = BackgroundTrainer::spawn(network(), common::xor(), MSE, 0.5, 100).unwrap();
+    wait_for(|| trainer.progress().is_some());
+
+    let token = trainer.stop_token();
+    thread::spawn(move || token.stop()).join().unwrap();
+    wait_for(|| !trainer.is_running());
+
+    // The worker publishes the round it cut short, so it returns the network last published.
+    let published = trainer.network();
+    assert_eq!(trainer.stop().unwrap().get_params(), published.get_params());
+}
@@ -0,0 +1,53 @@
+mod common;
+
+use nalgebra::{DMatrix, DVector};
+use rand::{distr::Uniform, rngs::StdRng, Rng, SeedableRng};
+
+use neural::{
+    activations::*,
+    losses::MSE,
+    network::{layer::Layer, Network},
+};
+
+use common::{analytic_gradient, assert_close, numeric_gradient, sample, scalar_backpropagation_step};
+
+#[test]
+fn a_dataset_larger_than_a_chunk_has_the_full_gradient() {
+    let mut rng = StdRng::seed_from_u64(1);
+    let network = Network::<f64>::random_with_rng(&[3, 4, 2], tanh!(), &Uniform::new(-0.5, 0.5).unwrap(), &mut rng).unwrap();
+    let dataset: Vec<_> = (0..600)
+        .map(|_| {
+            let inputs: Vec<f64> = (0..3).map(|_| rng.random_range(-1.0..1.0)).collect();
+            sample(&inputs, &[inputs[0] * inputs[1], inputs[2].sin()])
+        })
+        .collect();
+
+    assert_close(&analytic_gradient(&network, &dataset, &MSE), &numeric_gradient(&network, &dataset, &MSE), 1e-8);
+}
+
+#[test]
+fn a_layer_step_matches_the_scalar_loops() {
+    let mut rng = StdRng::seed_from_u64(8);
+    let uniform = Uniform::new(-1.0, 1.0).unwrap();
+
+    for (input_size, output_size) in [(1, 1), (5, 7), (16, 3)] {
+        let mut layer = Layer::<f64>::random_with_rng(input_size, output_size, tanh!(), &uniform, &mut rng).unwrap();
+        let mut weight_gradient = DMatrix::zeros(output_size, input_size);
+        let mut bias_gradient = DVector::zeros(output_size);
+
+        // Two steps, so the gradients are accumulated rather than overwritten.
+        for _ in 0..2 {
+            let inputs = DVector::from_fn(input_size, |_, _| rng.random_range(-1.0..1.0));
+            let incoming = DVector::from_fn(output_size, |_, _| rng.random_range(-1.0..1.0));
+            let outputs = layer.forward(inputs).unwrap();
+
+            let expected = scalar_backpropagation_step(&layer, &Tanh, outputs.as_view(), incoming.as_view(), &mut weight_gradient, &mut bias_gradient);
+            let input_gradient = layer.backpropagation_step(outputs.as_view(), incoming.as_view());
+
+            assert_close(input_gradient.as_slice(), expected.as_slice(), 1e-12);
+        }
+
+        assert_close(layer.weight_gradient().clone_owned().as_slice(), weight_gradient.as_slice(), 1e-12);
+        assert_close(layer.bias_gradient().clone_owned().as_slice(), bias_gradient.as_slice(), 1e-12);
+    }
+}
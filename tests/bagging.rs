@@ -0,0 +1,139 @@
+use std::{cell::Cell, ops::ControlFlow};
+
+use nalgebra::DVector;
+use rand::{distr::Uniform, rngs::StdRng, SeedableRng};
+
+use neural::{
+    activations::*,
+    dataset::Sample,
+    losses::{LossFn, MSE},
+    network::{Network, NetworkError},
+    training::{
+        bagging::bag,
+        callback::{EpochContext, TrainingCallback},
+        Trainer,
+    },
+};
+
+/// `y = x²` on 200 points.
+fn dataset() -> Vec<Sample> {
+    (0..200).map(|i| i as f32 / 100.0 - 1.0).map(|x| Sample::from_slices(&[x], &[x * x])).collect()
+}
+
+fn factory() -> Result<Network, NetworkError> {
+    Network::random_with_rng(&[1, 3, 1], tanh!(), &Uniform::new(-1.0, 1.0).unwrap(), &mut StdRng::seed_from_u64(2))
+}
+
+fn trainer() -> Trainer<'static, MSE> {
+    Trainer::new(MSE, 0.1, 2).batch_size(20)
+}
+
+#[test]
+fn bags_hold_about_63_percent_of_the_distinct_samples() {
+    let dataset = dataset();
+    let bagging = bag(&dataset, 20, factory, trainer, &mut StdRng::seed_from_u64(1)).unwrap();
+
+    assert_eq!(bagging.bags.len(), 20);
+    assert_eq!(bagging.ensemble.networks().len(), 20);
+
+    let mut unique = 0.0;
+    for bag in &bagging.bags {
+        assert_eq!(bag.len(), dataset.len());
+        assert!(bag.iter().all(|&i| i < dataset.len()));
+
+        let mut distinct = bag.clone();
+        distinct.sort();
+        distinct.dedup();
+        unique += distinct.len() as f32 / dataset.len() as f32 / 20.0;
+    }
+    // 1 - 1/e, give or take the spread of 20 bags of 200.
+    assert!((unique - 0.632).abs() < 0.02, "{unique}");
+}
+
+#[test]
+fn a_seed_gives_the_same_bags_and_losses() {
+    let run = |seed| bag(&dataset(), 4, factory, trainer, &mut StdRng::seed_from_u64(seed)).unwrap();
+    let (first, second) = (run(3), run(3));
+
+    assert_eq!(first.bags, second.bags);
+    assert_eq!(first.member_oob_losses, second.member_oob_losses);
+    assert_eq!(first.oob_loss, second.oob_loss);
+    assert_ne!(first.bags, run(4).bags);
+}
+
+#[test]
+fn out_of_bag_losses_only_use_held_out_samples() {
+    let dataset = dataset();
+    let bagging = bag(&dataset, 5, factory, trainer, &mut StdRng::seed_from_u64(6)).unwrap();
+    let held_out = |bag: &[usize]| -> Vec<usize> { (0..dataset.len()).filter(|i| !bag.contains(i)).collect() };
+
+    for ((network, bag), oob_loss) in bagging.ensemble.networks().iter().zip(&bagging.bags).zip(&bagging.member_oob_losses) {
+        let samples: Vec<Sample> = held_out(bag).into_iter().map(|i| dataset[i].clone()).collect();
+        assert_eq!(*oob_loss, Some(network.evaluate(&samples, &MSE).unwrap()));
+    }
+
+    let mut total = 0.0;
+    let mut samples = 0;
+    for (i, sample) in dataset.iter().enumerate() {
+        let voters: Vec<&Network> = bagging.ensemble.networks()
+            .iter()
+            .zip(&bagging.bags)
+            .filter(|(_, bag)| !bag.contains(&i))
+            .map(|(network, _)| network)
+            .collect();
+        if voters.is_empty() {
+            continue;
+        }
+
+        let mean = voters.iter().map(|network| network.predict(sample.inputs()).unwrap()).sum::<DVector<f32>>() / voters.len() as f32;
+        total += MSE.apply(mean.as_view(), sample.expected_outputs()).unwrap();
+        samples += 1;
+    }
+
+    assert_eq!(bagging.oob_samples, samples);
+    assert!((bagging.oob_loss.unwrap() - total / samples as f32).abs() < 1e-6);
+}
+
+#[test]
+fn a_bag_of_everything_has_no_out_of_bag_loss() {
+    let dataset = vec![Sample::from_slices(&[0.5], &[0.25])];
+    let bagging = bag(&dataset, 3, factory, trainer, &mut StdRng::seed_from_u64(1)).unwrap();
+
+    assert_eq!(bagging.member_oob_losses, [None, None, None]);
+    assert_eq!((bagging.oob_loss, bagging.oob_samples), (None, 0));
+}
+
+/// Stops the training after the given number of epochs.
+struct StopAfter(usize);
+
+impl TrainingCallback for StopAfter {
+    fn on_epoch_end(&mut self, _: &EpochContext) -> ControlFlow<()> {
+        self.0 = self.0.saturating_sub(1);
+        if self.0 == 0 { ControlFlow::Break(()) } else { ControlFlow::Continue(()) }
+    }
+}
+
+/// Counts the epochs of every trainer it's given to.
+struct CountEpochs<'a>(&'a Cell<usize>);
+
+impl TrainingCallback for CountEpochs<'_> {
+    fn on_epoch_end(&mut self, _: &EpochContext) -> ControlFlow<()> {
+        self.0.set(self.0.get() + 1);
+        ControlFlow::Continue(())
+    }
+}
+
+#[test]
+fn every_member_trains_with_a_fresh_trainer() {
+    let epochs = Cell::new(0);
+    let counted = || Trainer::new(MSE, 0.1, 20).callback(StopAfter(3)).callback(CountEpochs(&epochs));
+
+    bag(&dataset(), 4, factory, counted, &mut StdRng::seed_from_u64(1)).unwrap();
+    // A stop callback shared between the members would end every member after the first one right away.
+    assert_eq!(epochs.get(), 12);
+}
+
+#[test]
+fn rejects_an_empty_dataset() {
+    assert!(bag(&[], 3, factory, trainer, &mut StdRng::seed_from_u64(1)).is_err());
+}
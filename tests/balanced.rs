@@ -0,0 +1,84 @@
+use rand::{distr::Uniform, rngs::StdRng, SeedableRng};
+
+use neural::{
+    activations::*,
+    dataset::{balanced::BalancedSampler, Sample},
+    losses::MSE,
+    network::Network,
+    training::Trainer,
+};
+
+/// 12 samples of class 0, 5 of class 1 and 1 of class 2, one-hot encoded.
+fn dataset() -> Vec<Sample> {
+    [(0, 12), (1, 5), (2, 1)]
+        .into_iter()
+        .flat_map(|(class, count)| {
+            (0..count).map(move |i| {
+                let mut expected = [0.0; 3];
+                expected[class] = 1.0;
+                Sample::from_slices(&[class as f32, i as f32], &expected)
+            })
+        })
+        .collect()
+}
+
+fn class(index: usize) -> usize {
+    match index {
+        0..12 => 0,
+        12..17 => 1,
+        _ => 2,
+    }
+}
+
+#[test]
+fn every_batch_holds_every_class_equally_often() {
+    let sampler = BalancedSampler::new(&dataset());
+    assert_eq!((sampler.classes(), sampler.epoch_len()), (3, 36));
+
+    let batches = sampler.batches(6, &mut StdRng::seed_from_u64(1));
+    assert_eq!(batches.len(), 6);
+
+    for batch in &batches {
+        let mut counts = [0; 3];
+        batch.iter().for_each(|&index| counts[class(index)] += 1);
+        assert_eq!(counts, [2, 2, 2], "{batch:?}");
+    }
+}
+
+#[test]
+fn an_epoch_covers_the_majority_once_and_repeats_the_minorities() {
+    let epoch = BalancedSampler::new(&dataset()).epoch(&mut StdRng::seed_from_u64(2));
+
+    let mut counts = [0; 18];
+    epoch.iter().for_each(|&index| counts[index] += 1);
+
+    assert!(counts[..12].iter().all(|&count| count == 1));
+    // 12 draws from 5 samples, in reshuffled passes.
+    assert!(counts[12..17].iter().all(|&count| count == 2 || count == 3), "{counts:?}");
+    assert_eq!(counts[12..17].iter().sum::<usize>(), 12);
+    assert_eq!(counts[17], 12);
+}
+
+#[test]
+fn epochs_are_reproducible_under_a_seed() {
+    let sampler = BalancedSampler::with_labels(&dataset(), |sample| sample.inputs()[0] as usize);
+    let epoch = |seed| sampler.epoch(&mut StdRng::seed_from_u64(seed));
+
+    assert_eq!(epoch(3), epoch(3));
+    assert_ne!(epoch(3), epoch(4));
+}
+
+#[test]
+fn the_trainer_steps_through_balanced_epochs() {
+    let dataset = dataset();
+    let mut network = Network::random_with_rng(&[2, 3], sigmoid!(), &Uniform::new(-0.5, 0.5).unwrap(), &mut StdRng::seed_from_u64(5)).unwrap();
+
+    let report = Trainer::new(MSE, 0.1, 2)
+        .batch_size(6)
+        .balanced_batches()
+        .fit_with_rng(&mut network, &dataset, &[], &mut StdRng::seed_from_u64(6))
+        .unwrap();
+
+    // Epochs of 36 samples in batches of 6.
+    assert_eq!(report.batches, 12);
+}
@@ -0,0 +1,107 @@
+mod common;
+
+use rand::{distr::Uniform, rngs::StdRng, Rng, SeedableRng};
+
+use neural::{
+    activations::*,
+    dataset::Sample,
+    losses::MSE,
+    network::Network,
+    training::Trainer,
+};
+
+use common::{assert_close, sample, xor};
+
+fn dataset() -> Vec<Sample<f64>> {
+    let mut rng = StdRng::seed_from_u64(13);
+    (0..8)
+        .map(|_| {
+            let inputs: Vec<f64> = (0..3).map(|_| rng.random_range(-1.0..1.0)).collect();
+            sample(&inputs, &[inputs[0] * inputs[1], inputs[2].cos()])
+        })
+        .collect()
+}
+
+/// The summed training loss, with every layer normalized by the statistics of the whole dataset.
+fn training_loss(network: &Network<f64>, dataset: &[Sample<f64>]) -> f64 {
+    network.clone().backpropagate(dataset, &MSE).unwrap()
+}
+
+/// Central differences of `training_loss` with respect to the parameter `parameter` picks out.
+fn numeric_gradient(network: &Network<f64>, dataset: &[Sample<f64>], parameter: impl Fn(&mut Network<f64>) -> &mut f64) -> f64 {
+    let epsilon = 1e-6;
+    let mut shifted = network.clone();
+
+    *parameter(&mut shifted) += epsilon;
+    let plus = training_loss(&shifted, dataset);
+    *parameter(&mut shifted) -= 2.0 * epsilon;
+    let minus = training_loss(&shifted, dataset);
+
+    (plus - minus) / (2.0 * epsilon)
+}
+
+#[test]
+fn gamma_and_beta_gradients_match_central_differences() {
+    let dataset = dataset();
+    let network = Network::<f64>::random_with_rng(&[3, 4, 4, 2], tanh!(), &Uniform::new(-1.0, 1.0).unwrap(), &mut StdRng::seed_from_u64(14))
+        .unwrap()
+        .with_batch_norm();
+    let mut analytic = network.clone();
+    analytic.backpropagate(&dataset, &MSE).unwrap();
+
+    for layer in 0..2 {
+        let batch_norm = analytic.dense_layer(layer).unwrap().batch_norm().unwrap();
+        let numeric = |gamma: bool| -> Vec<f64> {
+            (0..4)
+                .map(|i| {
+                    numeric_gradient(&network, &dataset, |network| {
+                        let batch_norm = network.dense_layer_mut(layer).unwrap().batch_norm_mut().unwrap();
+                        if gamma { batch_norm.get_gamma_mut(i) } else { batch_norm.get_beta_mut(i) }.unwrap()
+                    })
+                })
+                .collect()
+        };
+
+        assert_close(batch_norm.gamma_gradient().as_slice(), &numeric(true), 1e-7);
+        assert_close(batch_norm.beta_gradient().as_slice(), &numeric(false), 1e-7);
+    }
+}
+
+#[test]
+fn weight_gradients_go_through_the_batch_statistics() {
+    let dataset = dataset();
+    let network = Network::<f64>::random_with_rng(&[3, 4, 2], sigmoid!(), &Uniform::new(-1.0, 1.0).unwrap(), &mut StdRng::seed_from_u64(15))
+        .unwrap()
+        .with_batch_norm();
+    let mut analytic = network.clone();
+    analytic.backpropagate(&dataset, &MSE).unwrap();
+    let layer = analytic.dense_layer(0).unwrap();
+
+    let numeric: Vec<f64> = (0..4)
+        .flat_map(|output| (0..3).map(move |input| (input, output)))
+        .map(|(input, output)| {
+            numeric_gradient(&network, &dataset, |network| network.dense_layer_mut(0).unwrap().get_weight_mut(input, output).unwrap())
+        })
+        .collect();
+    let weight_gradient: Vec<f64> = layer.weight_gradient().transpose().iter().copied().collect();
+
+    assert_close(&weight_gradient, &numeric, 1e-7);
+}
+
+#[test]
+fn a_deep_sigmoid_network_trains_with_batch_norm_but_stalls_without() {
+    let train = |batch_norm: bool| {
+        let mut network = Network::random_with_rng(&[2, 8, 8, 8, 8, 1], sigmoid!(), &Uniform::new(-1.0, 1.0).unwrap(), &mut StdRng::seed_from_u64(16))
+            .unwrap();
+        if batch_norm {
+            network = network.with_batch_norm();
+        }
+
+        let report = Trainer::new(MSE, 1.0, 300).fit(&mut network, &xor(), &[]).unwrap();
+        *report.history.train_losses().last().unwrap()
+    };
+
+    let (with, without) = (train(true), train(false));
+    assert!(with < 0.02, "with batch norm: {with}");
+    assert!(without > 0.2, "without batch norm: {without}");
+}
@@ -0,0 +1,89 @@
+mod common;
+
+use nalgebra::{DMatrix, DVector};
+use rand::{distr::Uniform, rngs::StdRng, SeedableRng};
+
+use neural::{
+    activations::*,
+    losses::{BCEWithLogits, LossFn},
+    network::{layer::Layer, Network, NetworkError},
+};
+
+use common::{analytic_gradient, assert_close, numeric_gradient, sample};
+
+/// `-y ln(sigmoid(z)) - (1 - y) ln(1 - sigmoid(z))` the straightforward way.
+fn naive(z: f32, y: f32) -> f32 {
+    let p = 1.0 / (1.0 + (-z).exp());
+    -y * p.ln() - (1.0 - y) * (1.0 - p).ln()
+}
+
+#[test]
+fn extreme_logits_give_finite_losses_where_the_sigmoid_saturates() {
+    for (z, y, expected) in [(100.0, 1.0, 0.0), (100.0, 0.0, 100.0), (-100.0, 1.0, 100.0), (-100.0, 0.0, 0.0)] {
+        let (output, target) = (DVector::from_vec(vec![z]), DVector::from_vec(vec![y]));
+        let loss: f32 = BCEWithLogits.apply(output.as_view(), target.as_view()).unwrap();
+        let gradient = BCEWithLogits.partial_gradient(output.as_view(), target.as_view()).unwrap();
+
+        assert!((loss - expected).abs() < 1e-6, "z = {z}, y = {y}: {loss}");
+        assert!(gradient[0].is_finite() && gradient[0].abs() <= 1.0);
+        // In f32 the sigmoid of ±100 is exactly 1 or 0, so the separate log overflows for the wrong class.
+        if expected > 0.0 {
+            assert_eq!(naive(z, y), f32::INFINITY);
+        }
+    }
+}
+
+#[test]
+fn matches_the_naive_loss_for_moderate_logits() {
+    let (z, y) = ([-3.0, -0.5, 0.0, 0.7, 4.0], [0.0, 1.0, 1.0, 0.0, 1.0]);
+    let loss: f32 = BCEWithLogits.apply(DVector::from_vec(z.to_vec()).as_view(), DVector::from_vec(y.to_vec()).as_view()).unwrap();
+    let expected = z.iter().zip(&y).map(|(&z, &y)| naive(z, y)).sum::<f32>() / 5.0;
+
+    assert!((loss - expected).abs() < 1e-6, "{loss} vs {expected}");
+}
+
+#[test]
+fn gradients_match_finite_differences_at_extreme_logits() {
+    let expected = DVector::from_vec(vec![1.0, 0.0, 1.0, 0.0, 1.0]);
+    let logits = DVector::from_vec(vec![100.0, 100.0, -100.0, -100.0, 0.3]);
+    let gradient = BCEWithLogits.partial_gradient(logits.as_view(), expected.as_view()).unwrap();
+
+    let epsilon = 1e-4;
+    let numeric: Vec<f64> = (0..logits.len())
+        .map(|i| {
+            let (mut plus, mut minus) = (logits.clone(), logits.clone());
+            plus[i] += epsilon;
+            minus[i] -= epsilon;
+            let apply = |z: &DVector<f64>| BCEWithLogits.apply(z.as_view(), expected.as_view()).unwrap();
+            (apply(&plus) - apply(&minus)) / (2.0 * epsilon)
+        })
+        .collect();
+
+    assert_close(gradient.as_slice(), &numeric, 1e-8);
+}
+
+#[test]
+fn network_gradients_match_finite_differences_at_extreme_logits() {
+    // A linear network whose logits are ±100 for the two inputs, each seen with both labels.
+    let mut layer = Layer::<f64>::zeros(2, 1, identity!()).unwrap();
+    layer.set_weights(DMatrix::from_row_slice(1, 2, &[100.0, -100.0])).unwrap();
+    let network = Network::from_layers(vec![layer]).unwrap();
+    let dataset = [([1.0, 0.0], 1.0), ([1.0, 0.0], 0.0), ([0.0, 1.0], 1.0), ([0.0, 1.0], 0.0)].map(|(inputs, label)| sample(&inputs, &[label]));
+
+    let gradient = analytic_gradient(&network, &dataset, &BCEWithLogits);
+    assert!(gradient.iter().all(|x| x.is_finite()));
+    assert_close(&gradient, &numeric_gradient(&network, &dataset, &BCEWithLogits), 1e-5);
+    assert!((network.evaluate(&dataset, &BCEWithLogits).unwrap() - 50.0).abs() < 1e-9);
+}
+
+#[test]
+fn a_non_linear_output_layer_is_rejected() {
+    let mut network =
+        Network::random_with_rng(&[2, 3, 1], sigmoid!(), &Uniform::new(-1.0, 1.0).unwrap(), &mut StdRng::seed_from_u64(1)).unwrap();
+    let before = network.get_params();
+
+    let mismatch = |result| matches!(result, Err(NetworkError::OutputActivationMismatch { required: "identity", activation: "sigmoid" }));
+    assert!(mismatch(network.learn(&common::xor(), &BCEWithLogits, 0.1)));
+    assert!(mismatch(network.evaluate(&common::xor(), &BCEWithLogits)));
+    assert_eq!(network.get_params(), before);
+}
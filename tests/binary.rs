@@ -0,0 +1,190 @@
+use nalgebra::DVector;
+use rand::{distr::Uniform, rngs::StdRng, SeedableRng};
+
+use neural::{
+    activations::*,
+    network::{
+        binary::{LoadReport, Migration, FORMAT_VERSION},
+        layer::Layer,
+        MaxoutLayer,
+        Network,
+        NetworkLoadError,
+    },
+};
+
+const FIXTURES: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/binary");
+
+fn load(version: u32) -> (Network, LoadReport) {
+    Network::load_with_report(format!("{FIXTURES}/v{version}.nrln")).unwrap()
+}
+
+fn predict(network: &Network, inputs: &[f32]) -> f32 {
+    network.predict(DVector::from_row_slice(inputs).as_view()).unwrap()[0]
+}
+
+fn assert_xor(network: &Network, sign: f32) {
+    for (inputs, expected) in [([0.0, 0.0], 0.0), ([0.0, 1.0], 1.0), ([1.0, 0.0], 1.0), ([1.0, 1.0], 0.0)] {
+        let predicted = predict(network, &inputs);
+        assert!((predicted - sign * expected).abs() < 1e-3, "{inputs:?}: {predicted}");
+    }
+}
+
+#[test]
+fn version_1_files_have_f32_parameters_and_biases_everywhere() {
+    let (network, report) = load(1);
+
+    assert_eq!(report.version, 1);
+    assert_eq!(
+        report.migrations,
+        [Migration::AssumedF32Precision, Migration::AssumedBiases, Migration::AssumedUnshared, Migration::AssumedSinglePiece]
+    );
+    assert_xor(&network, 1.0);
+}
+
+#[test]
+fn version_2_files_can_have_f16_parameters() {
+    let (network, report) = load(2);
+
+    assert_eq!(report.version, 2);
+    assert_eq!(report.migrations, [Migration::AssumedBiases, Migration::AssumedUnshared, Migration::AssumedSinglePiece]);
+    assert_eq!(network.layer(0).unwrap().as_dense().unwrap().biases(), DVector::from_vec(vec![-10.0, 30.0]));
+    assert_xor(&network, 1.0);
+}
+
+#[test]
+fn version_3_files_can_have_bias_free_layers() {
+    let (network, report) = load(3);
+
+    assert_eq!(report.version, 3);
+    assert_eq!(report.migrations, [Migration::AssumedUnshared, Migration::AssumedSinglePiece]);
+    assert!(!network.layer(2).unwrap().as_dense().unwrap().has_bias());
+    // The last layer negates the XOR output.
+    assert_xor(&network, -1.0);
+}
+
+#[test]
+fn version_4_files_can_share_parameters() {
+    let (network, report) = load(4);
+
+    assert_eq!(report.version, 4);
+    assert_eq!(report.migrations, [Migration::AssumedSinglePiece]);
+    assert_eq!(network.shared_parameters(1), Some(0));
+    // Both layers compute 2x + 0.5.
+    assert_eq!(predict(&network, &[1.0]), 5.5);
+}
+
+#[test]
+fn version_5_files_can_have_maxout_layers() {
+    let (network, report) = load(5);
+
+    assert_eq!(report.version, FORMAT_VERSION);
+    assert!(report.migrations.is_empty() && !report.is_outdated());
+    assert_eq!(network.layer(0).unwrap().downcast_ref::<MaxoutLayer>().unwrap().pieces(), 2);
+    // The larger of the first input and the second plus 0.25.
+    assert_eq!(predict(&network, &[1.0, 0.5]), 1.0);
+    assert_eq!(predict(&network, &[-1.0, 0.5]), 0.75);
+}
+
+#[test]
+fn saving_an_old_file_again_upgrades_it() {
+    let (network, _) = load(1);
+    let mut bytes = Vec::new();
+    network.write_to(&mut bytes).unwrap();
+
+    let (upgraded, report) = Network::read_from_with_report(bytes.as_slice()).unwrap();
+    assert_eq!(report.version, FORMAT_VERSION);
+    assert_eq!(upgraded.get_params(), network.get_params());
+}
+
+/// A relu layer and a sigmoid layer with batch normalization, and its current file.
+fn saved() -> (Network, Vec<u8>) {
+    let mut rng = StdRng::seed_from_u64(3);
+    let uniform = Uniform::new(-1.0, 1.0).unwrap();
+    let network = Network::from_layers(vec![
+        Layer::random_with_rng(2, 3, relu!(), &uniform, &mut rng).unwrap(),
+        Layer::random_with_rng(3, 1, sigmoid!(), &uniform, &mut rng).unwrap().with_batch_norm(),
+    ])
+    .unwrap();
+
+    let mut bytes = Vec::new();
+    network.write_to(&mut bytes).unwrap();
+    (network, bytes)
+}
+
+/// Where the fields of the first layer start: after the magic, the version, the precision and the layer count.
+const FIRST_LAYER: usize = 4 + 4 + 1 + 4;
+/// The input and output sizes and the length of `"relu"` come before the flags.
+const FIRST_FLAGS: usize = FIRST_LAYER + 4 + 4 + 4 + 4;
+
+fn read(bytes: &[u8]) -> Result<Network, NetworkLoadError> {
+    Network::read_from(bytes)
+}
+
+#[test]
+fn a_saved_network_runs_forward_like_the_original() {
+    let (mut network, bytes) = saved();
+    let mut loaded = read(&bytes).unwrap();
+
+    assert_eq!(loaded.get_params(), network.get_params());
+    for inputs in [[0.0, 0.0], [0.5, -1.5], [3.0, 2.0]] {
+        let inputs = DVector::from_row_slice(&inputs);
+        assert_eq!(loaded.forward(inputs.clone()).unwrap(), network.forward(inputs).unwrap());
+    }
+}
+
+#[test]
+fn a_file_cut_short_anywhere_is_truncated() {
+    let (_, bytes) = saved();
+
+    for len in 4..bytes.len() {
+        assert!(matches!(read(&bytes[..len]), Err(NetworkLoadError::Truncated)), "{len} of {} bytes", bytes.len());
+    }
+    // Too short even for the magic bytes.
+    assert!(matches!(read(&bytes[..2]), Err(NetworkLoadError::Truncated)));
+}
+
+#[test]
+fn rejects_other_magic_bytes_and_newer_versions() {
+    let (_, mut bytes) = saved();
+    bytes[0] = b'X';
+    assert!(matches!(read(&bytes), Err(NetworkLoadError::BadMagic)));
+
+    let (_, mut bytes) = saved();
+    bytes[4..8].copy_from_slice(&(FORMAT_VERSION + 1).to_le_bytes());
+    assert!(matches!(read(&bytes), Err(NetworkLoadError::UnsupportedVersion { found, supported: FORMAT_VERSION }) if found == FORMAT_VERSION + 1));
+}
+
+#[test]
+fn rejects_flags_that_are_neither_0_nor_1() {
+    let corrupt = |offset: usize| {
+        let (_, mut bytes) = saved();
+        bytes[offset] = 7;
+        read(&bytes)
+    };
+
+    assert!(matches!(corrupt(8), Err(NetworkLoadError::InvalidPrecisionFlag(7))));
+    assert!(matches!(corrupt(FIRST_FLAGS), Err(NetworkLoadError::InvalidBatchNormFlag { layer: 0 })));
+    assert!(matches!(corrupt(FIRST_FLAGS + 1), Err(NetworkLoadError::InvalidBiasFlag { layer: 0 })));
+    assert!(matches!(corrupt(FIRST_FLAGS + 2), Err(NetworkLoadError::InvalidSharingFlag { layer: 0 })));
+}
+
+#[test]
+fn rejects_corrupted_names_and_sizes() {
+    let (_, mut bytes) = saved();
+    bytes[FIRST_LAYER + 12] = 0xff;
+    assert!(matches!(read(&bytes), Err(NetworkLoadError::InvalidActivationName { layer: 0 })));
+
+    let (_, mut bytes) = saved();
+    bytes[FIRST_LAYER + 12..FIRST_LAYER + 16].copy_from_slice(b"gelu");
+    assert!(matches!(read(&bytes), Err(NetworkLoadError::UnknownActivation { layer: 0, .. })));
+
+    // Sizes claiming about 2^64 weights fail as a file that can't hold them, without allocating them first.
+    let (_, mut bytes) = saved();
+    bytes[FIRST_LAYER..FIRST_LAYER + 8].copy_from_slice(&[0xff; 8]);
+    assert!(matches!(read(&bytes), Err(NetworkLoadError::Truncated)));
+
+    // The first layer now has 4 outputs, which the 3 inputs of the next one don't take.
+    let (_, mut bytes) = saved();
+    bytes[FIRST_LAYER + 4..FIRST_LAYER + 8].copy_from_slice(&4u32.to_le_bytes());
+    assert!(read(&bytes).is_err());
+}
@@ -0,0 +1,88 @@
+use rand::{distr::Uniform, rngs::StdRng, SeedableRng};
+
+use neural::{
+    activations::*,
+    network::{Initializer, Network, NetworkBuilder, NetworkError},
+};
+
+#[test]
+fn a_uniform_initializer_builds_what_random_builds() {
+    let built = NetworkBuilder::new()
+        .input(2)
+        .layer(50, sigmoid!())
+        .layer(1, sigmoid!())
+        .init(Initializer::Uniform(-0.5, 0.5))
+        .build_with_rng(&mut StdRng::seed_from_u64(42))
+        .unwrap();
+    let random = Network::random_with_rng(&[2, 50, 1], sigmoid!(), &Uniform::new(-0.5, 0.5).unwrap(), &mut StdRng::seed_from_u64(42)).unwrap();
+
+    assert_eq!(built.layer_sizes(), random.layer_sizes());
+    assert_eq!(built.get_params(), random.get_params());
+    assert_eq!(built.to_string(), random.to_string());
+}
+
+#[test]
+fn a_seed_builds_the_same_network_every_time() {
+    let builder = || NetworkBuilder::new().input(3).layer(4, relu!()).layer(2, sigmoid!()).seed(42);
+
+    assert_eq!(builder().build().unwrap().get_params(), builder().build().unwrap().get_params());
+    assert_eq!(builder().build().unwrap().get_params(), builder().build_with_rng(&mut StdRng::seed_from_u64(42)).unwrap().get_params());
+    assert_ne!(builder().build().unwrap().get_params(), builder().seed(43).build().unwrap().get_params());
+}
+
+#[test]
+fn every_layer_has_its_own_activation_and_initializer() {
+    let network = NetworkBuilder::new()
+        .input(3)
+        .layer(4, relu!())
+        .layer_with_init(4, tanh!(), Initializer::Uniform(1.0, 2.0))
+        .layer(2, sigmoid!())
+        .init(Initializer::Zeros)
+        .build_with_rng(&mut StdRng::seed_from_u64(1))
+        .unwrap();
+
+    assert_eq!(network.to_string(), "Network: 3 -> 4 (relu) -> 4 (tanh) -> 2 (sigmoid), 46 parameters");
+
+    // The builder's initializer applies to the layers without their own, whenever it's set.
+    let parameters = |layer| {
+        let layer = network.dense_layer(layer).unwrap();
+        layer.weights().iter().chain(layer.biases().iter()).copied().collect::<Vec<_>>()
+    };
+    assert!(parameters(0).iter().chain(&parameters(2)).all(|&x| x == 0.0));
+    assert!(parameters(1).iter().all(|&x| (1.0..2.0).contains(&x)));
+}
+
+#[test]
+fn without_an_initializer_layers_get_the_one_recommended_for_their_activation() {
+    let network = NetworkBuilder::new().input(20).layer(30, relu!()).layer(10, tanh!()).seed(3).build().unwrap();
+
+    // He and Xavier initializations both leave the biases at zero, and bound the uniform weights by their fan.
+    for layer in network.dense_layers() {
+        assert!(layer.biases().iter().all(|&bias| bias == 0.0));
+        assert!(layer.weights().iter().any(|&weight| weight != 0.0));
+    }
+    let xavier_bound = (6.0f32 / (30.0 + 10.0)).sqrt();
+    assert!(network.dense_layer(1).unwrap().weights().iter().all(|weight| weight.abs() <= xavier_bound));
+    assert_eq!(Initializer::recommended_for::<f32>(relu!().as_ref()), Initializer::HeNormal);
+    assert_eq!(Initializer::recommended_for::<f32>(tanh!().as_ref()), Initializer::XavierUniform);
+}
+
+#[test]
+fn without_bias_drops_the_biases_of_the_last_layer_added() {
+    let network = NetworkBuilder::new().without_bias().input(2).layer(3, sigmoid!()).without_bias().layer(1, sigmoid!()).seed(0).build().unwrap();
+
+    assert!(!network.dense_layer(0).unwrap().has_bias());
+    assert!(network.dense_layer(1).unwrap().has_bias());
+    assert_eq!(network.parameter_count(), 2 * 3 + 4);
+}
+
+#[test]
+fn misuse_is_rejected() {
+    assert!(matches!(NetworkBuilder::new().layer(1, sigmoid!()).seed(0).build(), Err(NetworkError::MissingInputSize)));
+    assert!(matches!(NetworkBuilder::new().input(2).seed(0).build(), Err(NetworkError::TooFewLayers(1))));
+    assert!(matches!(NetworkBuilder::new().input(0).layer(1, sigmoid!()).seed(0).build(), Err(NetworkError::ZeroLayerSize(0))));
+    assert!(matches!(
+        NetworkBuilder::new().input(2).layer(3, sigmoid!()).layer(0, sigmoid!()).seed(0).build(),
+        Err(NetworkError::ZeroLayerSize(2))
+    ));
+}
@@ -0,0 +1,103 @@
+mod common;
+
+use rand::{distr::Uniform, rngs::StdRng, SeedableRng};
+
+use neural::{
+    activations::*,
+    dataset::Sample,
+    losses::{LogCosh, LossFn, MSE},
+    network::{layer::Layer, MaxoutLayer, Network, NetworkError},
+};
+
+use common::{assert_close, sample};
+
+fn network(seed: u64) -> Network<f64> {
+    Network::random_with_rng(&[3, 5, 4, 2], sigmoid!(), &Uniform::new(-1.0, 1.0).unwrap(), &mut StdRng::seed_from_u64(seed)).unwrap()
+}
+
+fn dataset() -> Vec<Sample<f64>> {
+    (0..12)
+        .map(|i| {
+            let x = i as f64;
+            let sample = sample(&[(x * 0.3).sin(), (x * 0.7).cos(), x / 12.0], &[(i % 2) as f64, (i % 3 == 0) as u8 as f64]);
+            sample.with_weight(1.0 + (i % 4) as f32 * 0.5)
+        })
+        .collect()
+}
+
+/// Every layer's weight and bias gradients, flattened.
+fn gradients(network: &Network<f64>) -> Vec<f64> {
+    network.dense_layers().flat_map(|layer| layer.weight_gradient().iter().chain(layer.bias_gradient().iter()).copied().collect::<Vec<_>>()).collect()
+}
+
+#[test]
+fn the_cache_holds_every_layer_input_and_weighted_sum() {
+    let mut network = network(1);
+    let input = dataset()[3].inputs().clone_owned();
+    let (outputs, cache) = network.forward_cached(input.as_view()).unwrap();
+
+    assert_eq!(cache.activations().len(), 4);
+    assert_eq!(cache.weighted_inputs().len(), 3);
+    assert_eq!(cache.activations()[0], input);
+    for (activations, weighted_inputs) in cache.activations()[1..].iter().zip(cache.weighted_inputs()) {
+        assert_eq!(*activations, weighted_inputs.map(|x| 1.0 / (1.0 + (-x).exp())));
+    }
+
+    assert_eq!(cache.output().clone_owned(), outputs);
+    assert_eq!(outputs, network.predict(input.as_view()).unwrap());
+    assert_eq!(outputs, network.forward(input).unwrap());
+}
+
+#[test]
+fn cached_gradients_equal_backpropagate() {
+    fn check(loss: &impl LossFn<f64>) {
+        let dataset = dataset();
+        let (mut batched, mut cached) = (network(2), network(2));
+
+        let batched_loss = batched.backpropagate(&dataset, loss).unwrap();
+
+        // Every cache is taken from the unchanged network first, so they can come from anywhere, in any order.
+        let caches: Vec<_> = dataset.iter().map(|sample| cached.forward_cached(sample.inputs()).unwrap().1).collect();
+        let mut cached_loss = 0.0;
+        for (cache, sample) in caches.iter().zip(&dataset).rev() {
+            cached_loss += cached.backpropagate_cached(cache, sample, loss).unwrap();
+        }
+
+        assert!((cached_loss - batched_loss).abs() < 1e-12, "{cached_loss} vs {batched_loss}");
+        assert_close(&gradients(&cached), &gradients(&batched), 1e-12);
+        assert!(gradients(&cached).iter().any(|&gradient| gradient.abs() > 1e-3));
+    }
+
+    check(&MSE);
+    check(&LogCosh);
+}
+
+#[test]
+fn cached_steps_train_like_learn() {
+    let dataset = dataset();
+    let (mut learned, mut cached) = (network(3), network(3));
+    let total_weight: f64 = dataset.iter().map(|sample| f64::from(sample.weight())).sum();
+
+    for _ in 0..10 {
+        learned.learn(&dataset, &MSE, 0.5).unwrap();
+
+        for sample in &dataset {
+            let (_, cache) = cached.forward_cached(sample.inputs()).unwrap();
+            cached.backpropagate_cached(&cache, sample, &MSE).unwrap();
+        }
+        cached.apply_gradients(-0.5 / total_weight);
+    }
+
+    assert_close(&cached.get_params(), &learned.get_params(), 1e-12);
+}
+
+#[test]
+fn only_dense_networks_have_caches() {
+    let mut rng = StdRng::seed_from_u64(4);
+    let uniform = Uniform::new(-1.0, 1.0).unwrap();
+    let maxout = MaxoutLayer::random_with_rng(2, 3, 2, &uniform, &mut rng).unwrap();
+    let output = Layer::random_with_rng(3, 1, sigmoid!(), &uniform, &mut rng).unwrap();
+    let network = Network::from_network_layers(vec![Box::new(maxout), Box::new(output)]).unwrap();
+
+    assert!(matches!(network.forward_cached(nalgebra::DVector::zeros(2).as_view()), Err(NetworkError::NotDense { layer: 0 })));
+}
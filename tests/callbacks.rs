@@ -0,0 +1,96 @@
+mod common;
+
+use std::ops::ControlFlow;
+
+use rand::{distr::Uniform, rngs::StdRng, SeedableRng};
+
+use neural::{
+    activations::*,
+    dataset::Sample,
+    losses::MSE,
+    network::Network,
+    training::{
+        callback::{BestLossTracker, EpochContext, TrainingCallback},
+        Trainer,
+    },
+};
+
+use common::xor;
+
+fn network() -> Network {
+    Network::random_with_rng(&[2, 3, 1], sigmoid!(), &Uniform::new(-1.0, 1.0).unwrap(), &mut StdRng::seed_from_u64(11)).unwrap()
+}
+
+/// Records what it's shown and stops the training at `stop_at`, if set.
+#[derive(Default)]
+struct Recorder {
+    epochs: Vec<usize>,
+    train_losses: Vec<f32>,
+    validation_losses: Vec<Option<f32>>,
+    stop_at: Option<usize>,
+}
+
+impl TrainingCallback for Recorder {
+    fn on_epoch_end(&mut self, ctx: &EpochContext) -> ControlFlow<()> {
+        self.epochs.push(ctx.epoch);
+        self.train_losses.push(ctx.train_loss);
+        self.validation_losses.push(ctx.validation_loss);
+
+        if self.stop_at == Some(ctx.epoch) {
+            ControlFlow::Break(())
+        } else {
+            ControlFlow::Continue(())
+        }
+    }
+}
+
+#[test]
+fn sees_every_epoch_in_order() {
+    let mut recorder = Recorder::default();
+    let mut network = network();
+
+    let report = Trainer::new(MSE, 0.5, 12).callback(&mut recorder).fit(&mut network, &xor(), &xor()).unwrap();
+
+    assert_eq!(recorder.epochs, (0..12).collect::<Vec<_>>());
+    assert_eq!(recorder.train_losses, report.history.train_losses());
+    assert_eq!(recorder.validation_losses, report.history.validation_losses());
+    assert!(!report.stopped_early);
+}
+
+#[test]
+fn breaking_stops_the_training_after_that_epoch() {
+    let mut stopping = Recorder { stop_at: Some(3), ..Recorder::default() };
+    let mut later = Recorder::default();
+    let mut network = network();
+
+    let report = Trainer::new(MSE, 0.5, 12)
+        .callback(&mut stopping)
+        .callback(&mut later)
+        .fit(&mut network, &xor(), &[])
+        .unwrap();
+
+    assert!(report.stopped_early);
+    assert_eq!(report.history.train_losses().len(), 4);
+    assert_eq!(stopping.epochs, [0, 1, 2, 3]);
+    // Every callback still sees the epoch that stopped the training.
+    assert_eq!(later.epochs, [0, 1, 2, 3]);
+    assert_eq!(later.validation_losses, [None; 4]);
+}
+
+#[test]
+fn the_best_loss_tracker_stops_once_the_validation_loss_stops_improving() {
+    // Training pulls the output past the validation target, after which the validation loss only grows.
+    let train = vec![Sample::from_slices(&[1.0, 1.0], &[1.0])];
+    let validation = vec![Sample::from_slices(&[1.0, 1.0], &[0.75])];
+    let mut tracker = BestLossTracker::with_patience(3);
+    let mut network = network();
+
+    let report = Trainer::new(MSE, 0.5, 200).callback(&mut tracker).fit(&mut network, &train, &validation).unwrap();
+
+    let losses: Vec<f32> = report.history.validation_losses().iter().map(|loss| loss.unwrap()).collect();
+    let best_epoch = tracker.best_epoch().unwrap();
+    assert!(report.stopped_early);
+    assert_eq!(losses.len(), best_epoch + 4);
+    assert_eq!(tracker.best_loss(), Some(losses[best_epoch]));
+    assert!(losses.iter().all(|&loss| loss >= losses[best_epoch]));
+}
@@ -0,0 +1,123 @@
+use nalgebra::{DVector, DVectorView};
+use rand::{distr::Uniform, rngs::StdRng, Rng, SeedableRng};
+
+use neural::{
+    activations::*,
+    dataset::Sample,
+    losses::{LossFn, LossFnError, MSE},
+    network::{Network, NetworkError},
+};
+
+/// MSE that fails on any sample whose first expected output is NaN, after the size checks have passed.
+struct FailsOnNan;
+impl LossFn for FailsOnNan {
+    fn apply(&self, output: DVectorView<f32>, expected_output: DVectorView<f32>) -> Result<f32, LossFnError> {
+        if expected_output[0].is_nan() {
+            return Err(LossFnError::InvalidSmoothing(f32::NAN));
+        }
+
+        MSE.apply(output, expected_output)
+    }
+
+    fn partial_gradient(&self, output: DVectorView<f32>, expected_output: DVectorView<f32>) -> Result<DVector<f32>, LossFnError> {
+        MSE.partial_gradient(output, expected_output)
+    }
+}
+
+fn network() -> Network {
+    Network::random_with_rng(&[2, 5, 1], tanh!(), &Uniform::new(-1.0, 1.0).unwrap(), &mut StdRng::seed_from_u64(9)).unwrap()
+}
+
+/// More samples than `backpropagate` gathers into one matrix at a time.
+fn dataset(len: usize) -> Vec<Sample> {
+    let mut rng = StdRng::seed_from_u64(10);
+    (0..len)
+        .map(|_| {
+            let inputs = [rng.random_range(-1.0..1.0), rng.random_range(-1.0..1.0)];
+            Sample::from_slices(&inputs, &[inputs[0] * inputs[1]])
+        })
+        .collect()
+}
+
+fn gradients(network: &Network) -> Vec<f32> {
+    network
+        .dense_layers()
+        .flat_map(|layer| layer.weight_gradient().iter().chain(layer.bias_gradient().iter()).copied().collect::<Vec<_>>())
+        .collect()
+}
+
+#[test]
+fn names_the_first_sample_of_the_wrong_size() {
+    let mut dataset = dataset(10);
+    dataset[6] = Sample::from_slices(&[1.0, 2.0, 3.0], &[0.0]);
+    dataset[8] = Sample::from_slices(&[1.0, 2.0], &[0.0, 1.0]);
+    let mut network = network();
+    let before = network.get_params();
+
+    assert!(matches!(
+        network.check_dataset(&dataset),
+        Err(NetworkError::SampleSizeMismatch { index: 6, inputs: 3, outputs: 1, network_inputs: 2, network_outputs: 1 })
+    ));
+    assert!(matches!(network.learn(&dataset, &MSE, 0.1), Err(NetworkError::SampleSizeMismatch { index: 6, .. })));
+    assert!(matches!(network.check_dataset(&dataset[7..]), Err(NetworkError::SampleSizeMismatch { index: 1, outputs: 2, .. })));
+
+    assert_eq!(network.get_params(), before);
+    assert!(gradients(&network).iter().all(|&gradient| gradient == 0.0));
+}
+
+#[test]
+fn a_failed_call_keeps_the_gradients_accumulated_before_it() {
+    let mut dataset = dataset(700);
+    let mut network = network();
+    network.accumulate_gradients(&dataset[..20], &MSE).unwrap();
+    let accumulated = gradients(&network);
+    assert!(accumulated.iter().any(|&gradient| gradient != 0.0));
+
+    // The failing sample is in the third chunk, after two chunks have been backpropagated.
+    dataset[600] = Sample::from_slices(&[0.5, 0.5], &[f32::NAN]);
+    assert!(network.accumulate_gradients(&dataset, &FailsOnNan).is_err());
+
+    assert_eq!(gradients(&network), accumulated);
+}
+
+#[test]
+fn a_failed_call_doesnt_leak_into_the_next_step() {
+    let mut broken = dataset(700);
+    broken[600] = Sample::from_slices(&[0.5, 0.5], &[f32::NAN]);
+    let dataset = dataset(40);
+    let mut network = network();
+    let mut untouched = self::network();
+
+    assert!(network.learn(&broken, &FailsOnNan, 0.1).is_err());
+    assert_eq!(network.get_params(), untouched.get_params());
+
+    let loss = network.learn(&dataset, &MSE, 0.1).unwrap();
+    assert_eq!(loss, untouched.learn(&dataset, &MSE, 0.1).unwrap());
+    assert_eq!(network.get_params(), untouched.get_params());
+}
+
+#[test]
+fn learn_and_backpropagate_name_a_malformed_sample_before_any_work() {
+    let mut network = network();
+    network.backpropagate(&dataset(20), &MSE).unwrap();
+    let (params, accumulated) = (network.get_params(), gradients(&network));
+
+    for malformed in [Sample::from_slices(&[1.0, 2.0, 3.0], &[0.0]), Sample::from_slices(&[1.0, 2.0], &[])] {
+        let (inputs, outputs) = (malformed.inputs().len(), malformed.expected_outputs().len());
+        let mut dataset = dataset(1000);
+        dataset[327] = malformed;
+
+        let error = network.learn(&dataset, &MSE, 0.1).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            format!("sample 327 has {inputs} inputs and {outputs} expected outputs, but the network takes 2 inputs and gives 1 outputs")
+        );
+        assert!(matches!(
+            network.backpropagate(&dataset, &MSE),
+            Err(NetworkError::SampleSizeMismatch { index: 327, network_inputs: 2, network_outputs: 1, .. })
+        ));
+
+        assert_eq!(network.get_params(), params);
+        assert_eq!(gradients(&network), accumulated);
+    }
+}
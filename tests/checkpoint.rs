@@ -0,0 +1,111 @@
+mod common;
+
+use std::path::PathBuf;
+
+use rand::{distr::Uniform, rngs::StdRng, SeedableRng};
+
+use neural::{
+    activations::*,
+    losses::MSE,
+    network::{Lookahead, Network, NetworkError, OptimizerState, Optimizer, RProp, Sgd},
+    training::{checkpoint::CheckpointError, Trainer},
+};
+
+fn network() -> Network {
+    Network::random_with_rng(&[2, 4, 1], sigmoid!(), &Uniform::new(-1.0, 1.0).unwrap(), &mut StdRng::seed_from_u64(3)).unwrap()
+}
+
+fn trainer(epochs: usize) -> Trainer<'static, MSE> {
+    Trainer::new(MSE, 0.5, epochs).batch_size(2).ema(0.9).optimizer(Lookahead::new(RProp::default(), 3, 0.5).unwrap())
+}
+
+fn path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("neural-checkpoint-{name}-{}.bin", std::process::id()))
+}
+
+#[test]
+fn a_resumed_run_continues_the_uninterrupted_one() {
+    let dataset = common::xor();
+
+    let mut uninterrupted = network();
+    let full = trainer(10).fit(&mut uninterrupted, &dataset, &[]).unwrap();
+
+    // Two batches per epoch and a lookahead of three steps, so the checkpoint is between synchronizations.
+    let path = path("resume");
+    let mut first = trainer(5);
+    let mut interrupted = network();
+    let report = first.fit(&mut interrupted, &dataset, &[]).unwrap();
+    first.save_checkpoint(&path, &interrupted, report.ema_weights.as_ref()).unwrap();
+
+    let mut second = trainer(5);
+    let mut resumed = second.resume_from(&path).unwrap();
+    let rest = second.fit(&mut resumed, &dataset, &[]).unwrap();
+
+    assert_eq!(rest.history.train_losses(), &full.history.train_losses()[5..]);
+    assert_eq!(resumed.get_params(), uninterrupted.get_params());
+    assert_eq!(rest.ema_weights.unwrap().average(), full.ema_weights.unwrap().average());
+
+    // Without the optimizer state the run takes another course.
+    let mut cold = trainer(1).resume_from(&path).unwrap();
+    let restarted = trainer(5).fit(&mut cold, &dataset, &[]).unwrap();
+    assert_ne!(restarted.history.train_losses(), rest.history.train_losses());
+
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn rejects_the_state_of_another_optimizer() {
+    let dataset = common::xor();
+    let path = path("kind");
+    let mut trainer = trainer(2);
+    let mut network = network();
+    trainer.fit(&mut network, &dataset, &[]).unwrap();
+    trainer.save_checkpoint(&path, &network, None).unwrap();
+
+    let result = Trainer::new(MSE, 0.5, 2).optimizer(Lookahead::new(Sgd, 3, 0.5).unwrap()).resume_from(&path);
+    assert!(matches!(
+        result,
+        Err(CheckpointError::NetworkError(NetworkError::OptimizerKindMismatch { expected, found })) if expected == "lookahead(stateless)" && found == "lookahead(rprop)"
+    ));
+
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn rejects_state_that_doesnt_fit_the_network() {
+    let dataset = common::xor();
+    let mut rprop = RProp::default();
+    let mut trained = network();
+    trained.learn_with_optimizer(&dataset, &MSE, 0.5, &mut rprop).unwrap();
+
+    let state = rprop.save_state();
+    assert_eq!(state.buffers[0].1.len(), 17);
+
+    let other = Network::random_with_rng(&[2, 3, 1], sigmoid!(), &Uniform::new(-1.0, 1.0).unwrap(), &mut StdRng::seed_from_u64(3)).unwrap();
+    let mut fresh = RProp::default();
+    assert!(matches!(
+        fresh.load_state(&state, &other),
+        Err(NetworkError::OptimizerStateMismatch { expected: 13, found: 17, .. })
+    ));
+    assert!(fresh.step_sizes().is_empty());
+
+    assert!(matches!(fresh.load_state(&OptimizerState::new("rprop"), &trained), Err(NetworkError::MissingOptimizerState(name)) if name == "steps"));
+    fresh.load_state(&state, &trained).unwrap();
+    assert_eq!(fresh.step_sizes(), rprop.step_sizes());
+}
+
+#[test]
+fn rejects_a_truncated_checkpoint() {
+    let path = path("truncated");
+    let network = network();
+    trainer(1).save_checkpoint(&path, &network, None).unwrap();
+
+    let bytes = std::fs::read(&path).unwrap();
+    std::fs::write(&path, &bytes[..bytes.len() - 1]).unwrap();
+    assert!(matches!(trainer(1).resume_from(&path), Err(CheckpointError::Truncated)));
+
+    std::fs::write(&path, b"NRLN").unwrap();
+    assert!(matches!(trainer(1).resume_from(&path), Err(CheckpointError::BadMagic)));
+
+    std::fs::remove_file(&path).unwrap();
+}
@@ -0,0 +1,65 @@
+use rand::{distr::Uniform, rngs::StdRng, SeedableRng};
+
+use neural::{
+    activations::*,
+    dataset::{balance_weights, balanced::BalancedSampler, balanced_weights, Sample},
+    losses::MSE,
+    network::Network,
+    training::Trainer,
+};
+
+/// 90 samples of class 0 on `-1..0.6` and 10 of class 1 on `0.2..1`, overlapping on `0.2..0.6`.
+fn imbalanced() -> Vec<Sample> {
+    let majority = (0..90).map(|i| Sample::from_slices(&[-1.0 + 1.6 * i as f32 / 89.0], &[0.0]));
+    let minority = (0..10).map(|i| Sample::from_slices(&[0.2 + 0.8 * i as f32 / 9.0], &[1.0]));
+    majority.chain(minority).collect()
+}
+
+/// The fraction of the samples of class `class` classified as it.
+fn recall(network: &Network, samples: &[Sample], class: f32) -> f32 {
+    let members: Vec<_> = samples.iter().filter(|sample| sample.expected_outputs()[0] == class).collect();
+    let hits = members
+        .iter()
+        .filter(|sample| (network.predict(sample.inputs()).unwrap()[0] > 0.5) == (class == 1.0))
+        .count();
+    hits as f32 / members.len() as f32
+}
+
+fn train(samples: &[Sample]) -> Network {
+    let mut network = Network::random_with_rng(&[1, 1], sigmoid!(), &Uniform::new(-0.1, 0.1).unwrap(), &mut StdRng::seed_from_u64(28)).unwrap();
+    Trainer::new(MSE, 2.0, 500).fit(&mut network, samples, &[]).unwrap();
+    network
+}
+
+#[test]
+fn weights_are_the_inverse_class_frequencies() {
+    let weights = balanced_weights(&imbalanced());
+
+    assert!(weights[..90].iter().all(|&weight| (weight - 100.0 / 180.0).abs() < 1e-6));
+    assert!(weights[90..].iter().all(|&weight| (weight - 5.0).abs() < 1e-6));
+    // Both classes weigh half of the dataset.
+    assert!((weights.iter().sum::<f32>() - 100.0).abs() < 1e-3);
+}
+
+#[test]
+fn negative_zero_is_the_class_of_zero() {
+    let samples = [Sample::from_slices(&[0.0], &[0.0]), Sample::from_slices(&[1.0], &[-0.0]), Sample::from_slices(&[2.0], &[1.0])];
+
+    assert_eq!(balanced_weights(&samples), [0.75, 0.75, 1.5]);
+    assert_eq!(BalancedSampler::new(&samples).class_samples(), [vec![0, 1], vec![2]]);
+}
+
+#[test]
+fn weighting_rescues_the_minority_class() {
+    let unweighted = imbalanced();
+    let mut weighted = imbalanced();
+    balance_weights(&mut weighted);
+
+    let minority_unweighted = recall(&train(&unweighted), &unweighted, 1.0);
+    let network = train(&weighted);
+    let (minority_weighted, majority_weighted) = (recall(&network, &weighted, 1.0), recall(&network, &weighted, 0.0));
+
+    assert!(minority_unweighted <= 0.5, "unweighted minority recall {minority_unweighted}");
+    assert!(minority_weighted >= 0.8, "weighted minority recall {minority_weighted}");
+    assert!(majority_weighted >= 0.7, "weighted majority recall {majority_weighted}");
+}
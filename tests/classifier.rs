@@ -0,0 +1,32 @@
+use nalgebra::{DMatrix, DVector};
+
+use neural::{
+    activations::*,
+    classifier::Classifier,
+    network::{layer::Layer, Network},
+};
+
+/// A linear layer whose outputs are its inputs, as logits of three classes.
+fn linear() -> Network {
+    let mut layer = Layer::zeros(3, 3, identity!()).unwrap();
+    layer.set_weights(DMatrix::identity(3, 3)).unwrap();
+    Network::from_layers(vec![layer]).unwrap()
+}
+
+#[test]
+fn only_logits_go_through_a_softmax() {
+    let input = DVector::from_vec(vec![1.0, 3.0, 2.0]);
+
+    let scores = Classifier::new(linear(), ["a", "b", "c"]).unwrap();
+    assert!(!scores.has_logits());
+    assert_eq!(scores.predict_proba(input.as_view()).unwrap(), input);
+
+    let logits = Classifier::new(linear(), ["a", "b", "c"]).unwrap().with_logits();
+    assert!(logits.has_logits());
+    let probabilities = logits.predict_proba(input.as_view()).unwrap();
+    assert!((probabilities.sum() - 1.0).abs() < 1e-6);
+
+    let (label, probability) = logits.predict_class(input.as_view()).unwrap();
+    assert_eq!(label, "b");
+    assert_eq!(probability, probabilities[1]);
+}
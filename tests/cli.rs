@@ -0,0 +1,78 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    process::{Command, Output},
+};
+
+/// A directory of its own for every test, emptied first.
+fn directory(test: &str) -> PathBuf {
+    let directory = std::env::temp_dir().join(format!("neural-cli-{}-{test}", std::process::id()));
+    let _ = fs::remove_dir_all(&directory);
+    fs::create_dir_all(&directory).unwrap();
+    directory
+}
+
+fn run(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_neural")).args(args).output().unwrap()
+}
+
+fn path(directory: &Path, name: &str) -> String {
+    directory.join(name).to_str().unwrap().to_string()
+}
+
+#[test]
+fn trains_a_model_and_predicts_with_it() {
+    let directory = directory("end_to_end");
+    let (data, model, predictions) = (path(&directory, "or.csv"), path(&directory, "model.bin"), path(&directory, "predictions.csv"));
+    fs::write(&data, "a,b,or\n0,0,0\n0,1,1\n1,0,1\n1,1,1\n").unwrap();
+
+    let trained = run(&[
+        "train", "--data", &data, "--header", "--inputs", "0..2", "--outputs", "2", "--layers", "2,4,1",
+        "--epochs", "2000", "--rate", "2", "--out", &model,
+    ]);
+    assert!(trained.status.success(), "{}", String::from_utf8_lossy(&trained.stderr));
+
+    let stdout = String::from_utf8(trained.stdout).unwrap();
+    assert_eq!(stdout.lines().filter(|line| line.starts_with("epoch ")).count(), 2000);
+
+    let predicted = run(&["predict", "--model", &model, "--data", &data, "--header", "--out", &predictions]);
+    assert!(predicted.status.success(), "{}", String::from_utf8_lossy(&predicted.stderr));
+
+    let rows: Vec<f32> = fs::read_to_string(&predictions).unwrap().lines().map(|line| line.parse().unwrap()).collect();
+    assert_eq!(rows.len(), 4);
+    assert!(rows[0] < 0.5 && rows[1..].iter().all(|&row| row > 0.5), "{rows:?}");
+    assert!(rows.iter().all(|&row| (0.0..=1.0).contains(&row)));
+
+    fs::remove_dir_all(directory).unwrap();
+}
+
+#[test]
+fn bad_arguments_print_the_usage() {
+    let directory = directory("bad_arguments");
+    let data = path(&directory, "data.csv");
+    fs::write(&data, "0,0,0\n").unwrap();
+
+    for args in [
+        &["train", "--data", &data, "--inputs", "0..2", "--outputs", "2", "--layers", "3,1", "--out", "model.bin"][..],
+        &["train", "--data", &data, "--inputs", "0..2", "--outputs", "2", "--layers", "2,1", "--out", "model.bin", "--epoch", "5"],
+        &["fit"],
+    ] {
+        let output = run(args);
+        assert_eq!(output.status.code(), Some(2), "{args:?}");
+        assert!(String::from_utf8(output.stderr).unwrap().contains("usage:"));
+    }
+
+    fs::remove_dir_all(directory).unwrap();
+}
+
+#[test]
+fn unreadable_files_name_the_path() {
+    let directory = directory("unreadable");
+    let missing = path(&directory, "missing.bin");
+
+    let output = run(&["predict", "--model", &missing, "--data", &missing, "--out", &path(&directory, "out.csv")]);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(String::from_utf8(output.stderr).unwrap().contains(&missing));
+
+    fs::remove_dir_all(directory).unwrap();
+}
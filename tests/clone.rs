@@ -0,0 +1,70 @@
+mod common;
+
+use nalgebra::DVector;
+use rand::{distr::Uniform, rngs::StdRng, SeedableRng};
+
+use neural::{activations::*, losses::MSE, network::Network};
+
+use common::xor;
+
+fn network(sizes: &[usize]) -> Network {
+    Network::random_with_rng(sizes, sigmoid!(), &Uniform::new(-1.0, 1.0).unwrap(), &mut StdRng::seed_from_u64(1)).unwrap()
+}
+
+fn outputs(network: &mut Network) -> Vec<DVector<f32>> {
+    xor().iter().map(|sample| network.forward(sample.inputs().clone_owned()).unwrap()).collect()
+}
+
+#[test]
+fn a_clone_is_untouched_by_training_the_original() {
+    let mut original = network(&[2, 4, 1]);
+    for _ in 0..50 {
+        original.learn(&xor(), &MSE, 0.5).unwrap();
+    }
+
+    let mut snapshot = original.clone();
+    let before = outputs(&mut snapshot);
+    assert_eq!(outputs(&mut original), before);
+
+    for _ in 0..50 {
+        original.learn(&xor(), &MSE, 0.5).unwrap();
+    }
+
+    assert_eq!(outputs(&mut snapshot), before);
+    assert_ne!(outputs(&mut original), before);
+}
+
+#[test]
+fn accumulated_gradients_are_cloned_too() {
+    let mut original = network(&[2, 4, 1]);
+    original.backpropagate(&xor(), &MSE).unwrap();
+
+    let mut clone = original.clone();
+    original.apply_gradients(-0.5);
+    clone.apply_gradients(-0.5);
+
+    assert_eq!(clone.get_params(), original.get_params());
+    assert_ne!(clone.get_params(), network(&[2, 4, 1]).get_params());
+}
+
+#[test]
+fn debug_shows_the_shape_but_not_the_parameters() {
+    let network = network(&[2, 3, 1]);
+    let layer = network.dense_layer(0).unwrap();
+
+    assert_eq!(
+        format!("{layer:?}"),
+        "Layer { input_size: 2, output_size: 3, activation_fn: \"sigmoid\", batch_norm: false, trainable: true, use_bias: true, weight_constraint: None }"
+    );
+
+    let debug = format!("{network:?}");
+    assert!(debug.contains("input_size: 3, output_size: 1"), "{debug}");
+    assert!(!debug.contains(&format!("{:?}", layer.weights()[(0, 0)])), "{debug}");
+
+    // The size of the network doesn't matter.
+    let large = self::network(&[500, 400, 1]);
+    assert_eq!(
+        format!("{:?}", large.dense_layer(0).unwrap()),
+        "Layer { input_size: 500, output_size: 400, activation_fn: \"sigmoid\", batch_norm: false, trainable: true, use_bias: true, weight_constraint: None }"
+    );
+}
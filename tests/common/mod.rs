@@ -0,0 +1,92 @@
+#![allow(dead_code)]
+
+use nalgebra::{DMatrix, DVector, DVectorView};
+use neural::{
+    activations::ActivationFn,
+    dataset::Sample,
+    losses::LossFn,
+    network::{layer::Layer, Network},
+    scalar::Scalar,
+};
+
+pub fn xor() -> Vec<Sample> {
+    [([0.0, 0.0], 0.0), ([0.0, 1.0], 1.0), ([1.0, 0.0], 1.0), ([1.0, 1.0], 0.0)]
+        .into_iter()
+        .map(|(inputs, output)| Sample::from_slices(&inputs, &[output]))
+        .collect()
+}
+
+pub fn sample(inputs: &[f64], expected_outputs: &[f64]) -> Sample<f64> {
+    Sample::new(DVector::from_column_slice(inputs), DVector::from_column_slice(expected_outputs))
+}
+
+/// The gradient of the weighted mean loss over `dataset` in the order of `get_params`, read off one `learn`
+/// step with rate 1 on a copy of the network.
+pub fn analytic_gradient(network: &Network<f64>, dataset: &[Sample<f64>], loss: &impl LossFn<f64>) -> Vec<f64> {
+    let mut stepped = network.clone();
+    stepped.learn(dataset, loss, 1.0).unwrap();
+
+    network.get_params().iter().zip(stepped.get_params()).map(|(before, after)| before - after).collect()
+}
+
+/// Central differences of the weighted mean loss over `dataset` in the order of `get_params`.
+pub fn numeric_gradient(network: &Network<f64>, dataset: &[Sample<f64>], loss: &impl LossFn<f64>) -> Vec<f64> {
+    let epsilon = 1e-6;
+    let params = network.get_params();
+    let mut network = network.clone();
+
+    (0..params.len())
+        .map(|i| {
+            let mut shifted = params.clone();
+            shifted[i] = params[i] + epsilon;
+            network.set_params(&shifted).unwrap();
+            let plus = network.evaluate(dataset, loss).unwrap();
+
+            shifted[i] = params[i] - epsilon;
+            network.set_params(&shifted).unwrap();
+            let minus = network.evaluate(dataset, loss).unwrap();
+
+            (plus - minus) / (2.0 * epsilon)
+        })
+        .collect()
+}
+
+pub fn assert_close(actual: &[f64], expected: &[f64], tolerance: f64) {
+    assert_eq!(actual.len(), expected.len());
+
+    for (i, (actual, expected)) in actual.iter().zip(expected).enumerate() {
+        assert!((actual - expected).abs() <= tolerance, "entry {i}: {actual} vs {expected}");
+    }
+}
+
+/// `Layer::backpropagation_step` as the nested loops over the weights it was before it used matrix
+/// operations: accumulates into the given gradients for the layer's last `forward`, which gave `outputs`,
+/// and returns the gradient with respect to its inputs.
+pub fn scalar_backpropagation_step<T: Scalar>(
+    layer: &Layer<T>,
+    activation_fn: &dyn ActivationFn<T>,
+    outputs: DVectorView<T>,
+    output_partial_gradient: DVectorView<T>,
+    weight_gradient: &mut DMatrix<T>,
+    bias_gradient: &mut DVector<T>,
+) -> DVector<T> {
+    let (inputs, weights, biases) = (layer.get_previous_input(), layer.weights(), layer.biases());
+    let mut input_gradient = DVector::zeros(layer.input_size());
+
+    for output_index in 0..layer.output_size() {
+        let mut weighted_sum = biases[output_index];
+        for input_index in 0..layer.input_size() {
+            weighted_sum += weights[(output_index, input_index)] * inputs[input_index];
+        }
+
+        let delta = activation_fn.derivative(weighted_sum, outputs[output_index]) * output_partial_gradient[output_index];
+        bias_gradient[output_index] += delta;
+
+        for input_index in 0..layer.input_size() {
+            weight_gradient[(output_index, input_index)] += delta * inputs[input_index];
+            input_gradient[input_index] += weights[(output_index, input_index)] * delta;
+        }
+    }
+
+    input_gradient
+}
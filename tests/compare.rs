@@ -0,0 +1,61 @@
+use nalgebra::{DMatrix, DVector};
+use rand::{distr::Uniform, rngs::StdRng, SeedableRng};
+
+use neural::{
+    activations::*,
+    network::{layer::Layer, LayerDiff, Network, NetworkError},
+};
+
+fn network(sizes: &[usize]) -> Network {
+    Network::random_with_rng(sizes, sigmoid!(), &Uniform::new(-1.0, 1.0).unwrap(), &mut StdRng::seed_from_u64(1)).unwrap()
+}
+
+#[test]
+fn identical_networks_have_no_difference() {
+    let network = network(&[2, 3, 1]);
+    let clone = network.clone();
+
+    assert!(network.approx_eq(&clone, 0.0));
+    let diff = network.diff(&clone).unwrap();
+    assert_eq!(diff.layers.len(), 2);
+    assert!(diff.layers.iter().all(|layer| *layer == LayerDiff { max_weight_diff: 0.0, mean_weight_diff: 0.0, max_bias_diff: 0.0, mean_bias_diff: 0.0 }));
+    assert_eq!(diff.max_abs_diff(), 0.0);
+}
+
+#[test]
+fn perturbed_networks_differ_by_exactly_the_perturbation() {
+    let mut first = Layer::zeros(2, 2, identity!()).unwrap();
+    first.set_weights(DMatrix::from_row_slice(2, 2, &[1.0, 2.0, 3.0, 4.0])).unwrap();
+    let original = Network::from_layers(vec![first, Layer::zeros(2, 1, identity!()).unwrap()]).unwrap();
+
+    let mut perturbed = original.clone();
+    let layer = perturbed.dense_layer_mut(0).unwrap();
+    layer.set_weights(DMatrix::from_row_slice(2, 2, &[1.0, 2.5, 3.0, 3.75])).unwrap();
+    layer.set_biases(DVector::from_vec(vec![0.0, -0.125])).unwrap();
+
+    let diff = original.diff(&perturbed).unwrap();
+    assert_eq!(diff.layers[0], LayerDiff { max_weight_diff: 0.5, mean_weight_diff: 0.1875, max_bias_diff: 0.125, mean_bias_diff: 0.0625 });
+    assert_eq!(diff.layers[1].max_weight_diff, 0.0);
+    assert_eq!(diff.max_abs_diff(), 0.5);
+    assert_eq!(perturbed.diff(&original).unwrap(), diff);
+
+    // The tolerance bounds every single parameter.
+    assert!(original.approx_eq(&perturbed, 0.5));
+    assert!(!original.approx_eq(&perturbed, 0.4999));
+}
+
+#[test]
+fn different_architectures_are_an_error_naming_the_first_differing_layer() {
+    let network = network(&[2, 3, 1]);
+
+    assert!(matches!(network.diff(&self::network(&[2, 4, 1])), Err(NetworkError::ArchitectureMismatch { layer: 0 })));
+    assert!(matches!(network.diff(&self::network(&[2, 3, 1, 1])), Err(NetworkError::ArchitectureMismatch { layer: 2 })));
+
+    let tanh = Network::random_with_rng(&[2, 3, 1], tanh!(), &Uniform::new(-1.0, 1.0).unwrap(), &mut StdRng::seed_from_u64(1)).unwrap();
+    assert!(matches!(network.diff(&tanh), Err(NetworkError::ArchitectureMismatch { layer: 0 })));
+    assert!(matches!(network.diff(&network.clone().with_batch_norm()), Err(NetworkError::ArchitectureMismatch { layer: 0 })));
+
+    // Same parameters under another activation function still aren't the same network.
+    assert_eq!(tanh.get_params(), network.get_params());
+    assert!(!network.approx_eq(&tanh, f32::INFINITY));
+}
@@ -0,0 +1,89 @@
+use nalgebra::DMatrix;
+
+use neural::{
+    activations::*,
+    dataset::Sample,
+    metrics::confusion_matrix,
+    network::{layer::Layer, Network, NetworkError},
+};
+
+/// A network whose outputs are its inputs, so the samples spell out what it predicts.
+fn identity() -> Network {
+    let mut layer = Layer::zeros(3, 3, identity!()).unwrap();
+    layer.set_weights(DMatrix::identity(3, 3)).unwrap();
+    Network::from_layers(vec![layer]).unwrap()
+}
+
+/// Two samples of every class, none of them predicted as class 2.
+fn dataset() -> Vec<Sample> {
+    [
+        ([0.9, 0.1, 0.0], 0),
+        ([0.8, 0.1, 0.0], 0),
+        ([0.2, 0.7, 0.1], 1),
+        ([0.6, 0.3, 0.1], 1),
+        ([0.1, 0.5, 0.4], 2),
+        ([0.7, 0.2, 0.1], 2),
+    ]
+    .map(|(outputs, class)| {
+        let mut expected = [0.0; 3];
+        expected[class] = 1.0;
+        Sample::from_slices(&outputs, &expected)
+    })
+    .to_vec()
+}
+
+#[test]
+fn counts_true_classes_by_predicted_class() {
+    let confusion = confusion_matrix(&mut identity(), &dataset(), 3).unwrap();
+
+    assert_eq!(confusion.num_classes(), 3);
+    assert_eq!(*confusion.counts(), DMatrix::from_row_slice(3, 3, &[2, 0, 0, 1, 1, 0, 1, 1, 0]));
+}
+
+#[test]
+fn precision_and_recall_come_from_the_columns_and_rows() {
+    let confusion = confusion_matrix(&mut identity(), &dataset(), 3).unwrap();
+
+    assert_eq!([0, 1, 2].map(|class| confusion.precision(class)), [Some(0.5), Some(0.5), None]);
+    assert_eq!([0, 1, 2].map(|class| confusion.recall(class)), [Some(1.0), Some(0.5), Some(0.0)]);
+}
+
+#[test]
+fn display_prints_an_aligned_table() {
+    let confusion = confusion_matrix(&mut identity(), &dataset(), 3).unwrap();
+
+    assert_eq!(
+        confusion.to_string(),
+        [
+            "true \\ predicted 0 1 2",
+            "               0 2 0 0",
+            "               1 1 1 0",
+            "               2 1 1 0\n",
+        ]
+        .join("\n")
+    );
+
+    // Columns widen to the largest count.
+    let dataset: Vec<_> = dataset().into_iter().cycle().take(60).collect();
+    let confusion = confusion_matrix(&mut identity(), &dataset, 3).unwrap();
+    assert_eq!(confusion.to_string().lines().nth(1).unwrap(), "               0 20  0  0");
+}
+
+#[test]
+fn expected_outputs_without_a_class_are_reported() {
+    let mut dataset = dataset();
+    dataset.insert(3, Sample::from_slices(&[0.2, 0.7, 0.1], &[0.0, 0.0, 0.0]));
+
+    assert!(matches!(
+        confusion_matrix(&mut identity(), &dataset, 3),
+        Err(NetworkError::InvalidClassEncoding { index: 3 })
+    ));
+}
+
+#[test]
+fn the_class_count_has_to_match_the_outputs() {
+    assert!(matches!(
+        confusion_matrix(&mut identity(), &dataset(), 4),
+        Err(NetworkError::ClassCountMismatch { num_classes: 4, network_outputs: 3 })
+    ));
+}
@@ -0,0 +1,135 @@
+mod common;
+
+use nalgebra::{DMatrix, DVector};
+use rand::{distr::Uniform, rngs::StdRng, SeedableRng};
+
+use neural::{
+    activations::*,
+    losses::MSE,
+    network::{layer::{Layer, LayerError}, Network, NetworkError, RProp, WeightConstraint},
+    training::Trainer,
+};
+
+use common::{assert_close, sample, xor};
+
+fn network() -> Network {
+    Network::random_with_rng(&[2, 6, 1], sigmoid!(), &Uniform::new(-3.0, 3.0).unwrap(), &mut StdRng::seed_from_u64(2)).unwrap()
+}
+
+fn max_row_norm(layer: &Layer) -> f32 {
+    layer.weights().row_iter().map(|row| row.norm()).fold(0.0, f32::max)
+}
+
+#[test]
+fn clipped_weights_stay_in_range_while_learning() {
+    let mut network = network();
+    network.set_weight_constraint(Some(WeightConstraint::ClipRange(-0.5, 0.25))).unwrap();
+
+    for _ in 0..200 {
+        network.learn(&xor(), &MSE, 5.0).unwrap();
+
+        for layer in network.dense_layers() {
+            assert!(layer.weights().iter().all(|weight| (-0.5..=0.25).contains(weight)), "{}", layer.weights());
+        }
+    }
+
+    // The limits are reached rather than merely respected.
+    let weights: Vec<f32> = network.dense_layers().flat_map(|layer| layer.weights().iter().copied().collect::<Vec<_>>()).collect();
+    assert!(weights.contains(&-0.5) && weights.contains(&0.25));
+}
+
+#[test]
+fn max_norm_holds_for_every_output_while_training() {
+    let mut network = network();
+    network.set_weight_constraint(Some(WeightConstraint::MaxNorm(1.5))).unwrap();
+
+    Trainer::new(MSE, 2.0, 100)
+        .batch_size(1)
+        .fit_with_rng(&mut network, &xor(), &[], &mut StdRng::seed_from_u64(0))
+        .unwrap();
+
+    for layer in network.dense_layers() {
+        assert!(max_row_norm(layer) <= 1.5 + 1e-5, "{}", layer.weights());
+    }
+}
+
+#[test]
+fn rprop_enforces_the_constraint() {
+    let mut network = network();
+    network.set_weight_constraint(Some(WeightConstraint::MaxNorm(1.0))).unwrap();
+    let mut rprop = RProp::default();
+
+    for _ in 0..50 {
+        rprop.learn(&mut network, &xor(), &MSE).unwrap();
+
+        for layer in network.dense_layers() {
+            assert!(max_row_norm(layer) <= 1.0 + 1e-5, "{}", layer.weights());
+        }
+    }
+}
+
+#[test]
+fn max_norm_rescales_only_rows_above_the_limit() {
+    let mut layer = Layer::<f64>::zeros(2, 3, identity!()).unwrap();
+    layer.set_weights(DMatrix::from_row_slice(3, 2, &[3.0, 4.0, 0.6, 0.8, -1.0, 0.0])).unwrap();
+    layer.set_biases(DVector::from_vec(vec![10.0, -10.0, 0.0])).unwrap();
+    layer.set_weight_constraint(Some(WeightConstraint::MaxNorm(2.0))).unwrap();
+
+    // The constraint isn't enforced until the next update.
+    let mut network = Network::from_layers(vec![layer]).unwrap();
+    assert_eq!(network.dense_layer(0).unwrap().weights()[(0, 0)], 3.0);
+
+    // A step of 0 leaves the parameters as they are besides the constraint.
+    network.learn(&[sample(&[0.0, 0.0], &[0.0, 0.0, 0.0])], &MSE, 0.0).unwrap();
+
+    let layer = network.dense_layer(0).unwrap();
+    // The first row keeps its direction at a norm of 2, the others are within it already.
+    assert_close(layer.weights().transpose().as_slice(), &[1.2, 1.6, 0.6, 0.8, -1.0, 0.0], 1e-12);
+    assert_close(layer.biases().as_slice(), &[10.0, -10.0, 0.0], 0.0);
+}
+
+#[test]
+fn biases_are_never_clipped() {
+    let mut layer = Layer::<f64>::zeros(1, 1, identity!()).unwrap();
+    layer.set_biases(DVector::from_vec(vec![5.0])).unwrap();
+    layer.set_weight_constraint(Some(WeightConstraint::ClipRange(-1.0, 1.0))).unwrap();
+    let mut network = Network::from_layers(vec![layer]).unwrap();
+
+    // The bias starts outside the range and is pushed further out.
+    network.learn(&[sample(&[1.0], &[8.0])], &MSE, 0.1).unwrap();
+
+    let layer = network.dense_layer(0).unwrap();
+    assert!(layer.biases()[0] > 5.0);
+    assert!(layer.weights()[(0, 0)] <= 1.0);
+    assert_eq!(layer.weight_constraint(), Some(WeightConstraint::ClipRange(-1.0, 1.0)));
+}
+
+#[test]
+fn removing_the_constraint_lets_weights_grow() {
+    let mut network = network();
+    network.set_weight_constraint(Some(WeightConstraint::ClipRange(-0.1, 0.1))).unwrap();
+    network.learn(&xor(), &MSE, 1.0).unwrap();
+
+    network.set_weight_constraint(None).unwrap();
+    for _ in 0..200 {
+        network.learn(&xor(), &MSE, 5.0).unwrap();
+    }
+
+    assert!(network.dense_layers().all(|layer| layer.weight_constraint().is_none()));
+    assert!(network.dense_layers().any(|layer| layer.weights().amax() > 0.1));
+}
+
+#[test]
+fn invalid_constraints_are_rejected() {
+    for constraint in [WeightConstraint::ClipRange(1.0, -1.0), WeightConstraint::MaxNorm(0.0), WeightConstraint::MaxNorm(-1.0)] {
+        assert!(!constraint.is_valid());
+        assert!(matches!(
+            network().set_weight_constraint(Some(constraint)),
+            Err(NetworkError::LayerError(LayerError::InvalidWeightConstraint(rejected))) if rejected == constraint
+        ));
+    }
+
+    for constraint in [WeightConstraint::ClipRange(0.0, 0.0), WeightConstraint::ClipRange(f32::NEG_INFINITY, 1.0), WeightConstraint::MaxNorm(f32::INFINITY)] {
+        assert!(constraint.is_valid());
+    }
+}
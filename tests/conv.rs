@@ -0,0 +1,161 @@
+mod common;
+
+use nalgebra::{DMatrix, DVector};
+use rand::{distr::Uniform, rngs::StdRng, SeedableRng};
+
+use neural::{
+    activations::*,
+    losses::MSE,
+    network::{layer::LayerError, Conv2D, Convolution, ImageShape, Network},
+};
+
+use common::{assert_close, sample};
+
+fn conv(input_shape: ImageShape, convolution: Convolution, seed: u64) -> Conv2D<f64> {
+    Conv2D::random_with_rng(input_shape, convolution, tanh!(), &Uniform::new(-0.5, 0.5).unwrap(), &mut StdRng::seed_from_u64(seed)).unwrap()
+}
+
+fn image(shape: ImageShape, seed: usize) -> DVector<f64> {
+    DVector::from_fn(shape.size(), |i, _| ((seed * 31 + i) as f64 * 0.73).sin())
+}
+
+#[test]
+fn output_shapes() {
+    let shape = ImageShape::new(4, 4, 2);
+
+    assert_eq!(Convolution::new(3, (3, 3)).output_shape(shape).unwrap(), ImageShape::new(2, 2, 3));
+    assert_eq!(Convolution::new(3, (3, 3)).padding(1).output_shape(shape).unwrap(), ImageShape::new(4, 4, 3));
+    assert_eq!(Convolution::new(1, (2, 2)).stride(2).output_shape(shape).unwrap(), ImageShape::new(2, 2, 1));
+    assert_eq!(Convolution::new(1, (3, 3)).padding(1).stride(2).output_shape(shape).unwrap(), ImageShape::new(2, 2, 1));
+    assert_eq!(Convolution::new(2, (1, 3)).output_shape(ImageShape::new(5, 3, 1)).unwrap(), ImageShape::new(5, 1, 2));
+
+    let layer = Conv2D::<f64>::zeros(shape, Convolution::new(3, (3, 3)).padding(1), relu!()).unwrap();
+    assert_eq!((layer.input_size(), layer.output_size()), (32, 48));
+    assert_eq!(layer.kernels().shape(), (3, 18));
+}
+
+#[test]
+fn invalid_convolutions_are_rejected() {
+    let shape = ImageShape::new(3, 3, 1);
+
+    for convolution in [Convolution::new(1, (4, 1)), Convolution::new(1, (2, 2)).stride(0), Convolution::new(1, (0, 2))] {
+        assert!(matches!(convolution.output_shape(shape), Err(LayerError::InvalidConvolution { height: 3, width: 3, .. })));
+    }
+
+    assert!(Convolution::new(1, (4, 1)).padding(1).output_shape(shape).is_ok());
+    assert!(matches!(Convolution::new(0, (1, 1)).output_shape(shape), Err(LayerError::ZeroOutputSize)));
+    assert!(matches!(Convolution::new(1, (1, 1)).output_shape(ImageShape::new(3, 0, 1)), Err(LayerError::ZeroInputSize)));
+
+    let mut layer = Conv2D::<f64>::zeros(shape, Convolution::new(1, (2, 2)), identity!()).unwrap();
+    assert!(matches!(layer.forward(DVector::zeros(8)), Err(LayerError::InputSizeMismatch { layer_input_size: 9, given_input_size: 8 })));
+}
+
+#[test]
+fn convolves_in_the_flattened_layout() {
+    let shape = ImageShape::new(3, 3, 1);
+    let mut layer = Conv2D::zeros(shape, Convolution::new(1, (2, 2)), identity!()).unwrap();
+    layer.set_kernels(DMatrix::from_row_slice(1, 4, &[1.0, 2.0, 3.0, 4.0])).unwrap();
+    layer.set_biases(DVector::from_element(1, 0.5)).unwrap();
+
+    let inputs = DVector::from_fn(9, |i, _| i as f64 + 1.0);
+    assert_eq!(layer.predict(inputs.as_view()).unwrap().as_slice(), &[37.5, 47.5, 67.5, 77.5]);
+
+    // With padding, a 3x3 kernel of ones over a 2x2 image covers all of it at every position.
+    let mut padded = Conv2D::zeros(ImageShape::new(2, 2, 1), Convolution::new(1, (3, 3)).padding(1), identity!()).unwrap();
+    padded.set_kernels(DMatrix::from_element(1, 9, 1.0)).unwrap();
+    assert_eq!(padded.predict(DVector::from_column_slice(&[1.0, 2.0, 3.0, 4.0]).as_view()).unwrap().as_slice(), &[10.0; 4]);
+
+    // The second input channel follows the first and the second output channel follows the first.
+    let shape = ImageShape::new(2, 2, 2);
+    let mut channels = Conv2D::zeros(shape, Convolution::new(2, (1, 1)), identity!()).unwrap();
+    channels.set_kernels(DMatrix::from_row_slice(2, 2, &[1.0, 0.0, 0.0, -1.0])).unwrap();
+    let inputs = DVector::from_fn(8, |i, _| i as f64);
+    assert_eq!(shape.index(1, 0, 1), 5);
+    assert_eq!(channels.predict(inputs.as_view()).unwrap().as_slice(), &[0.0, 1.0, 2.0, 3.0, -4.0, -5.0, -6.0, -7.0]);
+}
+
+#[test]
+fn backpropagation_matches_numeric_gradients() {
+    let shape = ImageShape::new(4, 4, 2);
+    let epsilon = 1e-6;
+
+    for (seed, convolution) in [Convolution::new(3, (3, 3)), Convolution::new(2, (3, 3)).padding(1).stride(2), Convolution::new(2, (2, 1)).stride(2)]
+        .into_iter()
+        .enumerate()
+    {
+        let mut layer = conv(shape, convolution, seed as u64);
+        let inputs = image(shape, seed);
+        // The gradient of `sum(weights * outputs)` with respect to the outputs is `weights`.
+        let weights = DVector::from_fn(layer.output_size(), |i, _| (i as f64 * 1.3).cos());
+        let objective = |layer: &Conv2D<f64>, inputs: &DVector<f64>| layer.predict(inputs.as_view()).unwrap().dot(&weights);
+
+        let outputs = layer.forward(inputs.clone()).unwrap();
+        let input_gradient = layer.backpropagation_step(outputs.as_view(), weights.as_view());
+
+        let numeric_input_gradient: Vec<f64> = (0..inputs.len())
+            .map(|i| {
+                let (mut plus, mut minus) = (inputs.clone(), inputs.clone());
+                plus[i] += epsilon;
+                minus[i] -= epsilon;
+                (objective(&layer, &plus) - objective(&layer, &minus)) / (2.0 * epsilon)
+            })
+            .collect();
+        assert_close(input_gradient.as_slice(), &numeric_input_gradient, 1e-8);
+
+        let numeric_kernel_gradient = DMatrix::from_fn(layer.kernels().nrows(), layer.kernels().ncols(), |row, column| {
+            let mut shifted = layer.clone();
+            let mut kernels = layer.kernels().into_owned();
+            kernels[(row, column)] += epsilon;
+            shifted.set_kernels(kernels.clone()).unwrap();
+            let plus = objective(&shifted, &inputs);
+            kernels[(row, column)] -= 2.0 * epsilon;
+            shifted.set_kernels(kernels).unwrap();
+            (plus - objective(&shifted, &inputs)) / (2.0 * epsilon)
+        });
+        assert_close(layer.kernel_gradient().into_owned().as_slice(), numeric_kernel_gradient.as_slice(), 1e-8);
+
+        let numeric_bias_gradient: Vec<f64> = (0..layer.biases().len())
+            .map(|i| {
+                let mut shifted = layer.clone();
+                let mut biases = layer.biases().into_owned();
+                biases[i] += epsilon;
+                shifted.set_biases(biases.clone()).unwrap();
+                let plus = objective(&shifted, &inputs);
+                biases[i] -= 2.0 * epsilon;
+                shifted.set_biases(biases).unwrap();
+                (plus - objective(&shifted, &inputs)) / (2.0 * epsilon)
+            })
+            .collect();
+        assert_close(layer.bias_gradient().as_slice(), &numeric_bias_gradient, 1e-8);
+
+        // Applying the gradient steps the parameters and starts the next one from zero.
+        let kernels = layer.kernels().into_owned();
+        let kernel_gradient = layer.kernel_gradient().into_owned();
+        layer.apply_gradient(-0.1);
+        assert_close(layer.kernels().into_owned().as_slice(), (kernels - kernel_gradient * 0.1).as_slice(), 1e-12);
+        assert!(layer.kernel_gradient().iter().chain(layer.bias_gradient().iter()).all(|&x| x == 0.0));
+    }
+}
+
+#[test]
+fn learns_a_horizontal_edge_detector() {
+    let shape = ImageShape::new(4, 4, 1);
+    let layer = Conv2D::zeros(shape, Convolution::new(1, (1, 2)), identity!()).unwrap();
+    let mut network = Network::from_network_layers(vec![Box::new(layer)]).unwrap();
+
+    // The target is the difference between every pixel and the one to its left.
+    let dataset: Vec<_> = (0..8)
+        .map(|seed| {
+            let inputs = image(shape, seed);
+            let edges: Vec<f64> = (0..4).flat_map(|row| (0..3).map(move |column| (row, column))).map(|(row, column)| inputs[row * 4 + column + 1] - inputs[row * 4 + column]).collect();
+            sample(inputs.as_slice(), &edges)
+        })
+        .collect();
+
+    for _ in 0..500 {
+        network.learn(&dataset, &MSE, 0.5).unwrap();
+    }
+
+    assert!(network.evaluate(&dataset, &MSE).unwrap() < 1e-8);
+    assert_close(&network.get_params(), &[-1.0, 1.0, 0.0], 1e-4);
+}
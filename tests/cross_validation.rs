@@ -0,0 +1,110 @@
+use std::{cell::Cell, ops::ControlFlow};
+
+use rand::{distr::Uniform, rngs::StdRng, SeedableRng};
+
+use neural::{
+    activations::*,
+    dataset::Sample,
+    losses::MSE,
+    network::{Lookahead, Network, NetworkError, Sgd},
+    training::{
+        callback::{EpochContext, TrainingCallback},
+        cross_validation::cross_validate,
+        Trainer,
+        TrainingError,
+    },
+};
+
+/// `y = 2x - 1` on 23 points, which doesn't split evenly into folds.
+fn dataset() -> Vec<Sample> {
+    (0..23).map(|i| i as f32 / 22.0).map(|x| Sample::from_slices(&[x], &[2.0 * x - 1.0])).collect()
+}
+
+fn factory() -> Result<Network, NetworkError> {
+    Network::random_with_rng(&[1, 1], identity!(), &Uniform::new(-1.0, 1.0).unwrap(), &mut StdRng::seed_from_u64(2))
+}
+
+fn trainer() -> Trainer<'static, MSE> {
+    Trainer::new(MSE, 0.5, 300)
+}
+
+#[test]
+fn every_sample_is_validated_in_exactly_one_fold() {
+    let result = cross_validate(&dataset(), 5, factory, trainer, &mut StdRng::seed_from_u64(1)).unwrap();
+
+    assert_eq!(result.folds.len(), 5);
+    let sizes: Vec<_> = result.folds.iter().map(Vec::len).collect();
+    assert!(sizes.iter().all(|&size| size == 4 || size == 5), "{sizes:?}");
+
+    let mut validated: Vec<_> = result.folds.concat();
+    validated.sort();
+    assert_eq!(validated, (0..23).collect::<Vec<_>>());
+}
+
+#[test]
+fn a_learnable_dataset_has_a_low_validation_loss() {
+    let result = cross_validate(&dataset(), 4, factory, trainer, &mut StdRng::seed_from_u64(1)).unwrap();
+
+    assert_eq!(result.fold_losses.len(), 4);
+    assert!(result.mean_loss < 1e-4, "{}", result.mean_loss);
+    let mean = result.fold_losses.iter().sum::<f32>() / 4.0;
+    assert!((result.mean_loss - mean).abs() < 1e-9);
+    assert!(result.std_dev >= 0.0 && result.std_dev < 1e-4);
+}
+
+#[test]
+fn a_seed_gives_the_same_folds() {
+    let run = |seed| cross_validate(&dataset(), 3, factory, trainer, &mut StdRng::seed_from_u64(seed)).unwrap();
+
+    assert_eq!(run(4).folds, run(4).folds);
+    assert_eq!(run(4).fold_losses, run(4).fold_losses);
+    assert_ne!(run(4).folds, run(5).folds);
+}
+
+/// Stops the training after the given number of epochs.
+struct StopAfter(usize);
+
+impl TrainingCallback for StopAfter {
+    fn on_epoch_end(&mut self, _: &EpochContext) -> ControlFlow<()> {
+        self.0 = self.0.saturating_sub(1);
+        if self.0 == 0 { ControlFlow::Break(()) } else { ControlFlow::Continue(()) }
+    }
+}
+
+/// Counts the epochs of every trainer it's given to.
+struct CountEpochs<'a>(&'a Cell<usize>);
+
+impl TrainingCallback for CountEpochs<'_> {
+    fn on_epoch_end(&mut self, _: &EpochContext) -> ControlFlow<()> {
+        self.0.set(self.0.get() + 1);
+        ControlFlow::Continue(())
+    }
+}
+
+#[test]
+fn every_fold_trains_with_a_fresh_trainer() {
+    let trainers = Cell::new(0);
+    let epochs = Cell::new(0);
+    let counted = || {
+        trainers.set(trainers.get() + 1);
+        Trainer::new(MSE, 0.1, 50)
+            .optimizer(Lookahead::new(Sgd, 4, 0.5).unwrap())
+            .callback(StopAfter(10))
+            .callback(CountEpochs(&epochs))
+    };
+
+    cross_validate(&dataset(), 3, factory, counted, &mut StdRng::seed_from_u64(1)).unwrap();
+    assert_eq!(trainers.get(), 3);
+    // A stop callback shared between the folds would end every fold after the first one right away.
+    assert_eq!(epochs.get(), 30);
+}
+
+#[test]
+fn rejects_fold_counts_the_dataset_cant_fill() {
+    for folds in [0, 1, 24] {
+        assert!(matches!(
+            cross_validate(&dataset(), folds, factory, trainer, &mut StdRng::seed_from_u64(1)),
+            Err(TrainingError::InvalidFoldCount { samples: 23, .. })
+        ));
+    }
+}
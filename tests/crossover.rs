@@ -0,0 +1,81 @@
+use rand::{distr::Uniform, rngs::StdRng, SeedableRng};
+
+use neural::{
+    activations::*,
+    network::{Network, NetworkError},
+};
+
+fn network(seed: u64) -> Network {
+    Network::random_with_rng(&[20, 30, 10], sigmoid!(), &Uniform::new(-1.0, 1.0).unwrap(), &mut StdRng::seed_from_u64(seed)).unwrap()
+}
+
+/// The parameters of every dense layer, split by layer.
+fn layer_params(network: &Network) -> Vec<Vec<f32>> {
+    network.dense_layers().map(|layer| layer.weights().iter().chain(layer.biases().iter()).copied().collect()).collect()
+}
+
+#[test]
+fn every_child_parameter_comes_from_exactly_one_parent() {
+    let (a, b) = (network(1), network(2));
+    let child = Network::crossover(&a, &b, &mut StdRng::seed_from_u64(3)).unwrap();
+    let (a_params, b_params) = (a.get_params(), b.get_params());
+    assert!(a_params.iter().zip(&b_params).all(|(a, b)| a != b));
+
+    let mut from_a = 0;
+    for (i, &param) in child.get_params().iter().enumerate() {
+        assert!((param == a_params[i]) != (param == b_params[i]), "parameter {i}");
+        from_a += usize::from(param == a_params[i]);
+    }
+
+    // Every parameter is a fair coin flip, so each parent gives about half of the 940.
+    assert!((400..540).contains(&from_a), "{from_a}");
+    assert_eq!(child.to_string(), a.to_string());
+}
+
+#[test]
+fn layer_crossover_keeps_whole_layers() {
+    let (a, b) = (network(1), network(2));
+    let (a_layers, b_layers) = (layer_params(&a), layer_params(&b));
+    let mut choices = Vec::new();
+
+    for seed in 0..8 {
+        let child = Network::crossover_layers(&a, &b, &mut StdRng::seed_from_u64(seed)).unwrap();
+        let layers = layer_params(&child);
+
+        let choice: Vec<bool> = layers
+            .iter()
+            .enumerate()
+            .map(|(i, layer)| {
+                assert!(*layer == a_layers[i] || *layer == b_layers[i], "seed {seed}, layer {i}");
+                *layer == a_layers[i]
+            })
+            .collect();
+        choices.push(choice);
+    }
+
+    // Both parents give each layer in some of the children.
+    for layer in 0..2 {
+        assert!(choices.iter().any(|choice| choice[layer]) && choices.iter().any(|choice| !choice[layer]), "{choices:?}");
+    }
+}
+
+#[test]
+fn a_seed_gives_the_same_child() {
+    let (a, b) = (network(1), network(2));
+    let child = |seed| Network::crossover(&a, &b, &mut StdRng::seed_from_u64(seed)).unwrap().get_params();
+
+    assert_eq!(child(4), child(4));
+    assert_ne!(child(4), child(5));
+}
+
+#[test]
+fn parents_need_the_same_architecture() {
+    let a = network(1);
+    let narrower = Network::random_with_rng(&[20, 25, 10], sigmoid!(), &Uniform::new(-1.0, 1.0).unwrap(), &mut StdRng::seed_from_u64(2)).unwrap();
+    let tanh = Network::random_with_rng(&[20, 30, 10], tanh!(), &Uniform::new(-1.0, 1.0).unwrap(), &mut StdRng::seed_from_u64(2)).unwrap();
+
+    for other in [&narrower, &tanh] {
+        assert!(matches!(Network::crossover(&a, other, &mut StdRng::seed_from_u64(0)), Err(NetworkError::ArchitectureMismatch { layer: 0 })));
+        assert!(matches!(Network::crossover_layers(&a, other, &mut StdRng::seed_from_u64(0)), Err(NetworkError::ArchitectureMismatch { layer: 0 })));
+    }
+}
@@ -0,0 +1,83 @@
+use std::{io::Cursor, path::PathBuf};
+
+use neural::dataset::{from_csv, CsvOptions, DatasetError, Sample};
+
+fn file(name: &str, contents: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("neural-csv-{name}-{}.csv", std::process::id()));
+    std::fs::write(&path, contents).unwrap();
+    path
+}
+
+fn values(samples: &[Sample]) -> Vec<(Vec<f32>, Vec<f32>)> {
+    samples
+        .iter()
+        .map(|sample| (sample.inputs().iter().copied().collect(), sample.expected_outputs().iter().copied().collect()))
+        .collect()
+}
+
+#[test]
+fn round_trips_through_a_file() {
+    let expected = [(vec![0.5, -1.25], vec![1.0]), (vec![3.0, 1e-3], vec![0.0]), (vec![-7.5, 2.0], vec![0.25])];
+    let contents: String = expected
+        .iter()
+        .map(|(inputs, outputs)| format!("{},{},{}\n", outputs[0], inputs[0], inputs[1]))
+        .collect();
+    let path = file("round-trip", &contents);
+
+    // The output is the first column, the inputs are taken in the order the columns are given.
+    let samples = from_csv(&path, &[1, 2], &[0]).unwrap();
+    assert_eq!(values(&samples), expected);
+
+    let swapped = from_csv(&path, &[2, 1], &[0]).unwrap();
+    assert_eq!(swapped[0].inputs().as_slice(), [-1.25, 0.5]);
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn skips_the_header_and_blank_lines() {
+    let csv = "x;y;label\n1;2;0\n\n  \n3;4;1\n";
+
+    let samples = CsvOptions::new().header(true).delimiter(';').read_from(Cursor::new(csv), &[0, 1], &[2]).unwrap();
+
+    assert_eq!(values(&samples), [(vec![1.0, 2.0], vec![0.0]), (vec![3.0, 4.0], vec![1.0])]);
+    // Without skipping it, the header is a row of invalid numbers.
+    assert!(matches!(
+        CsvOptions::new().delimiter(';').read_from(Cursor::new(csv), &[0, 1], &[2]),
+        Err(DatasetError::InvalidNumber { line: 1, column: 0, .. })
+    ));
+}
+
+#[test]
+fn reports_the_line_of_a_missing_value() {
+    let csv = "x,y,label\n1,2,0\n3,,1\n";
+
+    let error = CsvOptions::new().header(true).read_from(Cursor::new(csv), &[0, 1], &[2]).unwrap_err();
+
+    assert!(matches!(error, DatasetError::MissingValue { line: 3, column: 1 }), "{error}");
+    // A missing value in a column that isn't read doesn't matter.
+    assert_eq!(CsvOptions::new().header(true).read_from(Cursor::new(csv), &[0], &[2]).unwrap().len(), 2);
+}
+
+#[test]
+fn reports_the_line_of_a_bad_row() {
+    let ragged = "1,2,0\n3,4\n";
+    let not_a_number = "1,2,0\n3,four,1\n";
+
+    assert!(matches!(
+        CsvOptions::new().read_from(Cursor::new(ragged), &[0, 1], &[2]),
+        Err(DatasetError::RaggedRow { line: 2, expected: 3, found: 2 })
+    ));
+    assert!(matches!(
+        CsvOptions::new().read_from(Cursor::new(not_a_number), &[0, 1], &[2]),
+        Err(DatasetError::InvalidNumber { line: 2, column: 1, value }) if value == "four"
+    ));
+    assert!(matches!(
+        CsvOptions::new().read_from(Cursor::new(ragged), &[0, 3], &[2]),
+        Err(DatasetError::ColumnOutOfRange { line: 1, column: 3, columns: 3 })
+    ));
+}
+
+#[test]
+fn a_missing_file_is_an_io_error() {
+    assert!(matches!(from_csv("/nonexistent/neural.csv", &[0], &[1]), Err(DatasetError::Io(_))));
+}
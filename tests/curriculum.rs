@@ -0,0 +1,114 @@
+use rand::{distr::Uniform, rngs::StdRng, SeedableRng};
+
+use neural::{
+    activations::*,
+    dataset::Sample,
+    losses::MSE,
+    network::Network,
+    training::{
+        curriculum::{loss_score, Curriculum},
+        Trainer,
+    },
+};
+
+fn network() -> Network {
+    Network::random_with_rng(&[1, 3, 1], sigmoid!(), &Uniform::new(-1.0, 1.0).unwrap(), &mut StdRng::seed_from_u64(5)).unwrap()
+}
+
+/// Samples whose expected output is their difficulty.
+fn dataset() -> Vec<Sample> {
+    [0.7, 0.1, 0.9, 0.3, 0.5, 0.2, 0.8].iter().map(|&score| Sample::from_slices(&[score * 2.0 - 1.0], &[score])).collect()
+}
+
+fn by_expected_output(sample: &Sample, _: &mut Network) -> f32 {
+    sample.expected_outputs()[0]
+}
+
+fn is_permutation(order: &[usize], len: usize) -> bool {
+    let mut sorted = order.to_vec();
+    sorted.sort();
+    sorted == (0..len).collect::<Vec<_>>()
+}
+
+#[test]
+fn ordered_epochs_train_on_batches_in_score_order() {
+    let dataset = dataset();
+
+    let mut trained = network();
+    Trainer::new(MSE, 0.5, 2)
+        .batch_size(3)
+        .curriculum(Curriculum::ordered(by_expected_output))
+        .fit_with_rng(&mut trained, &dataset, &[], &mut StdRng::seed_from_u64(1))
+        .unwrap();
+
+    let mut expected = network();
+    let order = [1, 5, 3, 4, 0, 6, 2];
+    for _ in 0..2 {
+        for batch in order.chunks(3) {
+            let batch: Vec<Sample> = batch.iter().map(|&index| dataset[index].clone()).collect();
+            expected.learn(&batch, &MSE, 0.5).unwrap();
+        }
+    }
+
+    assert_eq!(trained.get_params(), expected.get_params());
+}
+
+#[test]
+fn shuffling_only_breaks_ties() {
+    let curriculum = Curriculum::ordered(by_expected_output);
+    let scores = [1.0, 0.0, 1.0, 0.0, f32::NAN, 1.0];
+    let mut rng = StdRng::seed_from_u64(2);
+
+    for _ in 0..20 {
+        let order = curriculum.order(&scores, true, &mut rng);
+        assert!(is_permutation(&order, scores.len()));
+
+        let mut easy = order[..2].to_vec();
+        easy.sort();
+        let mut hard = order[2..5].to_vec();
+        hard.sort();
+        assert_eq!((easy, hard, order[5]), (vec![1, 3], vec![0, 2, 5], 4));
+    }
+}
+
+#[test]
+fn sampled_orders_follow_the_configured_distribution() {
+    let (scores, temperature) = ([0.0, 1.0, 2.0], 0.5);
+    let curriculum = Curriculum::sampled(by_expected_output, temperature).unwrap();
+    let mut rng = StdRng::seed_from_u64(3);
+
+    let draws = 20_000;
+    let mut first = [0usize; 3];
+    for _ in 0..draws {
+        let order = curriculum.order(&scores, false, &mut rng);
+        assert!(is_permutation(&order, scores.len()));
+        first[order[0]] += 1;
+    }
+
+    // The first sample is drawn with probability proportional to exp(-score / temperature).
+    let weights: Vec<f32> = scores.iter().map(|&score: &f32| (-score / temperature).exp()).collect();
+    let total: f32 = weights.iter().sum();
+    for (count, weight) in first.iter().zip(&weights) {
+        let (frequency, probability) = (*count as f32 / draws as f32, weight / total);
+        assert!((frequency - probability).abs() < 0.01, "{first:?}");
+    }
+
+    assert!(Curriculum::sampled(by_expected_output, 0.0).is_err());
+}
+
+#[test]
+fn scoring_leaves_no_gradient_behind() {
+    let mut network = network();
+    let dataset = dataset();
+
+    let backpropagating = Curriculum::ordered(|sample: &Sample, network: &mut Network| {
+        network.backpropagate(std::slice::from_ref(sample), &MSE).unwrap()
+    });
+    backpropagating.scores(&mut network, &dataset);
+    assert_eq!(network.gradient_norm(), 0.0);
+
+    let before = network.get_params();
+    let scores = Curriculum::ordered(loss_score(MSE)).scores(&mut network, &dataset);
+    assert_eq!(network.get_params(), before);
+    assert_eq!(scores[1], network.evaluate(&dataset[1..2], &MSE).unwrap());
+}
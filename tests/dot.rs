@@ -0,0 +1,98 @@
+use nalgebra::{DMatrix, DVector};
+use rand::{distr::Uniform, rngs::StdRng, SeedableRng};
+
+use neural::{
+    activations::*,
+    network::{layer::Layer, DotOptions, Network, NetworkLayer, Softmax},
+};
+
+/// A `[2, 3, 1]` network with fixed weights.
+fn small() -> Network {
+    let mut hidden = Layer::zeros(2, 3, relu!()).unwrap();
+    hidden.set_weights(DMatrix::from_row_slice(3, 2, &[1.0, -0.5, 0.0, 2.0, -2.0, 0.25])).unwrap();
+    let mut output = Layer::zeros(3, 1, sigmoid!()).unwrap();
+    output.set_weights(DMatrix::from_row_slice(1, 3, &[0.5, -1.0, 0.0])).unwrap();
+    output.set_biases(DVector::from_element(1, 0.1)).unwrap();
+
+    Network::from_layers(vec![hidden, output]).unwrap()
+}
+
+fn count(dot: &str, pattern: &str) -> usize {
+    dot.lines().filter(|line| line.contains(pattern)).count()
+}
+
+#[test]
+fn snapshot_of_a_small_network() {
+    let expected = r#"digraph network {
+    rankdir=LR;
+    splines=line;
+    node [shape=circle, label="", width=0.3];
+    subgraph cluster_0 {
+        label="inputs\n2";
+        n0_0;
+        n0_1;
+    }
+    subgraph cluster_1 {
+        label="layer 0\n3 relu";
+        n1_0;
+        n1_1;
+        n1_2;
+    }
+    subgraph cluster_2 {
+        label="layer 1\n1 sigmoid";
+        n2_0;
+    }
+    n0_0 -> n1_0 [penwidth=1.62, color=blue];
+    n0_0 -> n1_1 [penwidth=0.25, color=blue];
+    n0_0 -> n1_2 [penwidth=3.00, color=red];
+    n0_1 -> n1_0 [penwidth=0.94, color=red];
+    n0_1 -> n1_1 [penwidth=3.00, color=blue];
+    n0_1 -> n1_2 [penwidth=0.59, color=blue];
+    n1_0 -> n2_0 [penwidth=1.62, color=blue];
+    n1_1 -> n2_0 [penwidth=3.00, color=red];
+    n1_2 -> n2_0 [penwidth=0.25, color=blue];
+}
+"#;
+
+    assert_eq!(small().to_dot(DotOptions::new()), expected);
+}
+
+#[test]
+fn without_weights_edges_are_plain() {
+    let dot = small().to_dot(DotOptions::new().weights(false));
+
+    assert_eq!(count(&dot, " -> "), 9);
+    assert_eq!(count(&dot, "penwidth"), 0);
+    assert!(dot.contains("    n0_1 -> n1_2;\n"));
+}
+
+#[test]
+fn big_layers_are_summarized() {
+    let network = Network::random_with_rng(&[20, 8, 30, 3], tanh!(), &Uniform::new(-1.0, 1.0).unwrap(), &mut StdRng::seed_from_u64(1)).unwrap();
+    let dot = network.to_dot(DotOptions::new());
+
+    // The inputs and the 30 unit layer are one node each, the others a node per unit.
+    assert_eq!(count(&dot, "subgraph cluster_"), 4);
+    assert_eq!(count(&dot, "units\"]"), 2);
+    assert!(dot.contains("n0 [shape=box, label=\"20 units\"];") && dot.contains("n2 [shape=box, label=\"30 units\"];"));
+    assert_eq!(dot.lines().filter(|line| line.starts_with("        n") && !line.contains(" -> ")).count(), 1 + 8 + 1 + 3);
+
+    // Every edge touches a summarized node, so none of them shows a weight.
+    assert_eq!(count(&dot, " -> "), 8 + 8 + 3);
+    assert_eq!(count(&dot, "[style=bold]"), 19);
+    assert_eq!(count(&dot, "penwidth"), 0);
+
+    let dot = network.to_dot(DotOptions::new().max_nodes(30));
+    assert_eq!(count(&dot, " -> "), 20 * 8 + 8 * 30 + 30 * 3);
+    assert_eq!(count(&dot, "penwidth"), 20 * 8 + 8 * 30 + 30 * 3);
+}
+
+#[test]
+fn other_layers_are_labeled_with_their_kind() {
+    let layers: Vec<Box<dyn NetworkLayer>> = vec![Box::new(small().dense_layer(0).unwrap().clone()), Box::new(Softmax::new(3, 1.0).unwrap())];
+    let dot = Network::from_network_layers(layers).unwrap().to_dot(DotOptions::new());
+
+    assert!(dot.contains("label=\"layer 1\\n3 softmax\";"), "{dot}");
+    assert_eq!(count(&dot, " -> "), 6 + 9);
+    assert_eq!(count(&dot, "penwidth"), 6);
+}
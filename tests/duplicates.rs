@@ -0,0 +1,72 @@
+use nalgebra::DVector;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use neural::dataset::{dedup, find_conflicts, Conflict, DedupStrategy, Sample};
+
+fn sample(inputs: &[f32], output: f32) -> Sample {
+    Sample::from_slices(inputs, &[output])
+}
+
+#[test]
+fn exact_duplicates_conflict_only_when_their_outputs_differ() {
+    let samples = [sample(&[1.0, 2.0], 0.0), sample(&[3.0, 4.0], 1.0), sample(&[1.0, 2.0], 1.0), sample(&[3.0, 4.0], 1.0)];
+
+    assert_eq!(find_conflicts(&samples, 0.0, 0.0), [Conflict { samples: vec![0, 2], output_spread: 1.0 }]);
+    assert!(find_conflicts(&samples, 0.0, 1.0).is_empty());
+}
+
+#[test]
+fn near_duplicates_are_grouped_up_to_the_tolerance() {
+    // 0.25 apart, on both sides of a tolerance of 0.25 and chained through the middle sample.
+    let samples = [sample(&[0.0], 0.0), sample(&[0.25], 1.0), sample(&[0.5], 0.0), sample(&[0.76], 1.0)];
+
+    assert_eq!(find_conflicts(&samples, 0.25, 0.5), [Conflict { samples: vec![0, 1, 2], output_spread: 1.0 }]);
+    assert_eq!(find_conflicts(&samples, 0.2, 0.5), []);
+    assert_eq!(find_conflicts(&samples, 0.3, 0.5)[0].samples, [0, 1, 2, 3]);
+}
+
+#[test]
+fn dedup_keeps_the_first_the_last_or_the_average() {
+    let samples = [
+        sample(&[0.0, 0.0], 0.0).with_tag("first"),
+        sample(&[5.0, 5.0], 0.5),
+        sample(&[0.1, 0.0], 1.0).with_weight(3.0).with_tag("second"),
+    ];
+    let kept = |strategy| dedup(&samples, 0.1, strategy);
+
+    let first = kept(DedupStrategy::First);
+    assert_eq!(first.len(), 2);
+    assert_eq!((first[0].tag(), first[1].expected_outputs()[0]), (Some("first"), 0.5));
+
+    let last = kept(DedupStrategy::Last);
+    assert_eq!((last[0].tag(), last[0].weight()), (Some("second"), 3.0));
+
+    let average = kept(DedupStrategy::Average);
+    assert!((average[0].inputs() - DVector::from_vec(vec![0.05, 0.0])).abs().max() < 1e-6);
+    assert_eq!((average[0].expected_outputs()[0], average[0].weight(), average[0].tag()), (0.5, 2.0, Some("first")));
+    assert_eq!(average[1].inputs(), samples[1].inputs());
+}
+
+#[test]
+fn agrees_with_comparing_every_pair() {
+    let mut rng = StdRng::seed_from_u64(4);
+    let samples: Vec<Sample> = (0..300)
+        .map(|_| sample(&[rng.random_range(0..20) as f32 * 0.5, rng.random_range(0..20) as f32 * 0.5], rng.random_range(0..2) as f32))
+        .collect();
+
+    // Exact duplicates on a grid, compared pairwise: a group is a set of identical inputs with both outputs.
+    let mut expected: Vec<Vec<usize>> = Vec::new();
+    for i in 0..samples.len() {
+        if expected.iter().any(|group| group.contains(&i)) {
+            continue;
+        }
+
+        let group: Vec<usize> = (i..samples.len()).filter(|&j| samples[j].inputs() == samples[i].inputs()).collect();
+        if group.iter().any(|&j| samples[j].expected_outputs() != samples[i].expected_outputs()) {
+            expected.push(group);
+        }
+    }
+
+    let found: Vec<Vec<usize>> = find_conflicts(&samples, 0.0, 0.0).into_iter().map(|conflict| conflict.samples).collect();
+    assert_eq!(found, expected);
+}
@@ -0,0 +1,145 @@
+mod common;
+
+use nalgebra::{DMatrix, DVector};
+use rand::{distr::Uniform, rngs::StdRng, SeedableRng};
+
+use neural::{
+    activations::*,
+    losses::MSE,
+    network::{layer::Layer, EmaWeights, Network, NetworkError},
+    training::Trainer,
+};
+
+use common::xor;
+
+fn network() -> Network {
+    Network::random_with_rng(&[2, 4, 1], sigmoid!(), &Uniform::new(-1.0, 1.0).unwrap(), &mut StdRng::seed_from_u64(12)).unwrap()
+}
+
+/// A single weight and bias, `[w, b]` in `get_params`.
+fn tiny(weight: f64, bias: f64) -> Network<f64> {
+    let mut layer = Layer::zeros(1, 1, identity!()).unwrap();
+    layer.set_weights(DMatrix::from_element(1, 1, weight)).unwrap();
+    layer.set_biases(DVector::from_element(1, bias)).unwrap();
+    Network::from_layers(vec![layer]).unwrap()
+}
+
+#[test]
+fn the_average_follows_the_recursion() {
+    let mut network = tiny(1.0, 0.0);
+    let mut ema = EmaWeights::new(&network, 0.75).unwrap();
+    assert_eq!(ema.average(), [1.0, 0.0]);
+
+    // 0.75 * average + 0.25 * params, by hand.
+    let steps = [([5.0, 4.0], [2.0, 1.0]), ([-6.0, 1.0], [0.0, 1.0]), ([0.0, 1.0], [0.0, 1.0]), ([8.0, -3.0], [2.0, 0.0])];
+    for (params, average) in steps {
+        network.set_params(&params).unwrap();
+        ema.update(&network).unwrap();
+        assert_eq!(ema.average(), average);
+    }
+
+    assert_eq!(ema.to_network(&network).unwrap().get_params(), [2.0, 0.0]);
+    assert_eq!(network.get_params(), [8.0, -3.0]);
+}
+
+#[test]
+fn the_average_of_learning_steps_lags_behind_them() {
+    let mut network = network();
+    let mut ema = EmaWeights::new(&network, 0.9).unwrap();
+    let mut expected = network.get_params();
+
+    for _ in 0..20 {
+        network.learn(&xor(), &MSE, 1.0).unwrap();
+        ema.update(&network).unwrap();
+
+        for (average, param) in expected.iter_mut().zip(network.get_params()) {
+            *average = 0.9 * *average + 0.1 * param;
+        }
+    }
+
+    for (average, expected) in ema.average().iter().zip(&expected) {
+        assert!((average - expected).abs() < 1e-6);
+    }
+    assert_ne!(ema.average(), network.get_params());
+}
+
+#[test]
+fn apply_to_and_restore_round_trip_exactly() {
+    let mut network = network();
+    let mut ema = EmaWeights::new(&network, 0.5).unwrap();
+    for _ in 0..5 {
+        network.learn(&xor(), &MSE, 2.0).unwrap();
+        ema.update(&network).unwrap();
+    }
+
+    let trained = network.get_params();
+    let averaged = ema.to_network(&network).unwrap();
+
+    ema.apply_to(&mut network).unwrap();
+    assert!(ema.is_applied());
+    assert_eq!(network.get_params(), ema.average());
+    assert_eq!(network.predict_slice(&[1.0, 0.0]).unwrap(), averaged.predict_slice(&[1.0, 0.0]).unwrap());
+
+    // Applying again keeps the parameters saved by the first call.
+    ema.apply_to(&mut network).unwrap();
+    ema.restore(&mut network).unwrap();
+    assert!(!ema.is_applied());
+    assert_eq!(network.get_params(), trained);
+
+    // Nothing is applied, so nothing changes.
+    ema.restore(&mut network).unwrap();
+    assert_eq!(network.get_params(), trained);
+}
+
+#[test]
+fn the_trainer_keeps_the_average_of_every_batch() {
+    let mut trained = network();
+    let report = Trainer::new(MSE, 1.0, 3)
+        .batch_size(1)
+        .ema(0.8)
+        .fit_with_rng(&mut trained, &xor(), &[], &mut StdRng::seed_from_u64(0))
+        .unwrap();
+
+    let mut network = network();
+    let mut ema = EmaWeights::new(&network, 0.8).unwrap();
+    for _ in 0..3 {
+        for sample in xor() {
+            network.learn(&[sample], &MSE, 1.0).unwrap();
+            ema.update(&network).unwrap();
+        }
+    }
+
+    let kept = report.ema_weights.unwrap();
+    assert_eq!(kept.decay(), 0.8);
+    assert_eq!(kept.average(), ema.average());
+    // The network keeps its trained parameters.
+    assert_eq!(trained.get_params(), network.get_params());
+
+    let report = Trainer::new(MSE, 1.0, 1).fit_with_rng(&mut network, &xor(), &[], &mut StdRng::seed_from_u64(0)).unwrap();
+    assert!(report.ema_weights.is_none());
+}
+
+#[test]
+fn invalid_decays_and_sizes_are_rejected() {
+    let network = network();
+
+    for decay in [0.0, 1.0, -0.5, 1.5, f32::NAN] {
+        assert!(matches!(EmaWeights::new(&network, decay), Err(NetworkError::InvalidEmaDecay(_))), "{decay}");
+        assert!(matches!(
+            Trainer::new(MSE, 1.0, 1).ema(decay).fit_with_rng(&mut network.clone(), &xor(), &[], &mut StdRng::seed_from_u64(0)),
+            Err(NetworkError::InvalidEmaDecay(_))
+        ), "{decay}");
+    }
+
+    assert!(matches!(
+        EmaWeights::with_average(&network, 0.9, vec![0.0; 3]),
+        Err(NetworkError::ParameterCountMismatch { expected: 17, given: 3 })
+    ));
+    assert_eq!(EmaWeights::with_average(&network, 0.9, vec![0.5; 17]).unwrap().average(), [0.5; 17]);
+
+    let mut ema = EmaWeights::new(&network, 0.9).unwrap();
+    let other = Network::random_with_rng(&[2, 3, 1], sigmoid!(), &Uniform::new(-1.0, 1.0).unwrap(), &mut StdRng::seed_from_u64(0)).unwrap();
+    assert!(matches!(ema.update(&other), Err(NetworkError::ParameterCountMismatch { expected: 17, given: 13 })));
+    assert!(ema.apply_to(&mut other.clone()).is_err());
+    assert!(!ema.is_applied());
+}
@@ -0,0 +1,95 @@
+mod common;
+
+use nalgebra::{DMatrix, DVector};
+use rand::{distr::Uniform, rngs::StdRng, SeedableRng};
+
+use neural::{
+    activations::*,
+    losses::MSE,
+    network::{layer::{Layer, LayerError}, Embedding, Network},
+};
+
+use common::{assert_close, sample};
+
+/// Three embeddings of two values, row `i` being `[i, 10 * i]`.
+fn table() -> Embedding<f64> {
+    let mut embedding = Embedding::zeros(3, 2, 2).unwrap();
+    embedding.set_embeddings(DMatrix::from_fn(3, 2, |row, column| row as f64 * [1.0, 10.0][column])).unwrap();
+    embedding
+}
+
+#[test]
+fn concatenates_the_embeddings_of_every_feature() {
+    let embedding = table();
+    assert_eq!((embedding.input_size(), embedding.output_size()), (2, 4));
+
+    let outputs = embedding.predict(DVector::from_column_slice(&[2.0, 1.0]).as_view()).unwrap();
+    assert_eq!(outputs.as_slice(), &[2.0, 20.0, 1.0, 10.0]);
+
+    let outputs = embedding.predict(DVector::from_column_slice(&[0.0, 0.0]).as_view()).unwrap();
+    assert_eq!(outputs.as_slice(), &[0.0; 4]);
+}
+
+#[test]
+fn rejects_what_isnt_an_index() {
+    let embedding = table();
+
+    for (inputs, position, value) in [([0.0, 3.0], 1, 3.0), ([-1.0, 0.0], 0, -1.0), ([1.5, 0.0], 0, 1.5)] {
+        let result = embedding.predict(DVector::from_column_slice(&inputs).as_view());
+        assert!(
+            matches!(result, Err(LayerError::InvalidIndex { position: p, value: v, num_embeddings: 3 }) if p == position && v == value),
+            "{inputs:?}"
+        );
+    }
+
+    assert!(matches!(embedding.predict(DVector::from_column_slice(&[f64::NAN, 0.0]).as_view()), Err(LayerError::InvalidIndex { position: 0, .. })));
+    assert!(matches!(embedding.predict(DVector::zeros(3).as_view()), Err(LayerError::InputSizeMismatch { layer_input_size: 2, given_input_size: 3 })));
+
+    assert!(matches!(Embedding::<f64>::zeros(0, 2, 1), Err(LayerError::ZeroInputSize)));
+    assert!(matches!(Embedding::<f64>::zeros(3, 2, 0), Err(LayerError::ZeroInputSize)));
+    assert!(matches!(Embedding::<f64>::zeros(3, 0, 1), Err(LayerError::ZeroOutputSize)));
+    assert!(matches!(table().set_embeddings(DMatrix::zeros(2, 3)), Err(LayerError::ParameterShapeMismatch { .. })));
+}
+
+#[test]
+fn gradients_go_to_the_rows_looked_up() {
+    let mut embedding = table();
+    let outputs = embedding.forward(DVector::from_column_slice(&[1.0, 1.0])).unwrap();
+    let input_gradient = embedding.backpropagation_step(outputs.as_view(), DVector::from_column_slice(&[1.0, 2.0, 3.0, 4.0]).as_view());
+
+    // Indices aren't differentiable, and a row looked up twice gets the gradient of both features.
+    assert_eq!(input_gradient.as_slice(), &[0.0, 0.0]);
+    assert_eq!(embedding.gradient().row(1).iter().copied().collect::<Vec<_>>(), [4.0, 6.0]);
+    assert!(embedding.gradient().row(0).iter().chain(embedding.gradient().row(2).iter()).all(|&x| x == 0.0));
+
+    embedding.apply_gradient(-0.5);
+    assert_eq!(embedding.embeddings().row(1).iter().copied().collect::<Vec<_>>(), [-1.0, 7.0]);
+    assert_eq!(embedding.embeddings().row(2), table().embeddings().row(2));
+    assert!(embedding.gradient().iter().all(|&x| x == 0.0));
+
+    // The next step starts from a zero gradient.
+    let outputs = embedding.forward(DVector::from_column_slice(&[2.0, 0.0])).unwrap();
+    embedding.backpropagation_step(outputs.as_view(), DVector::from_element(4, 1.0).as_view());
+    embedding.apply_gradient(-1.0);
+    assert_eq!(embedding.embeddings().row(1).iter().copied().collect::<Vec<_>>(), [-1.0, 7.0]);
+    assert_eq!(embedding.embeddings().row(2).iter().copied().collect::<Vec<_>>(), [1.0, 19.0]);
+}
+
+#[test]
+fn learns_a_value_per_category() {
+    let mut rng = StdRng::seed_from_u64(1);
+    let uniform = Uniform::new(-0.5, 0.5).unwrap();
+    let embedding = Embedding::random_with_rng(4, 2, 1, &uniform, &mut rng).unwrap();
+    let output = Layer::random_with_rng(2, 1, sigmoid!(), &uniform, &mut rng).unwrap();
+    let mut network = Network::from_network_layers(vec![Box::new(embedding), Box::new(output)]).unwrap();
+
+    let targets = [0.9, 0.1, 0.7, 0.3];
+    let dataset: Vec<_> = targets.iter().enumerate().map(|(category, &target)| sample(&[category as f64], &[target])).collect();
+
+    for _ in 0..3000 {
+        network.learn(&dataset, &MSE, 2.0).unwrap();
+    }
+
+    let predictions: Vec<f64> = dataset.iter().map(|sample| network.predict(sample.inputs()).unwrap()[0]).collect();
+    assert_close(&predictions, &targets, 0.02);
+}
@@ -0,0 +1,124 @@
+use nalgebra::{DMatrix, DVector};
+use rand::{distr::Uniform, rngs::StdRng, Rng, SeedableRng};
+
+use neural::{
+    activations::*,
+    dataset::Sample,
+    losses::MSE,
+    network::{layer::Layer, Ensemble, Network, NetworkError},
+    training::Trainer,
+};
+
+/// A network of one linear layer computing `weights * x`.
+fn linear(weights: &[f32], outputs: usize) -> Network {
+    let mut layer = Layer::zeros(weights.len() / outputs, outputs, identity!()).unwrap();
+    layer.set_weights(DMatrix::from_row_slice(outputs, weights.len() / outputs, weights)).unwrap();
+    Network::from_layers(vec![layer]).unwrap()
+}
+
+fn input(values: &[f32]) -> DVector<f32> {
+    DVector::from_row_slice(values)
+}
+
+#[test]
+fn predictions_are_the_weighted_mean_of_the_members() {
+    let members = vec![linear(&[1.0, 0.0], 1), linear(&[3.0, 1.0], 1)];
+    let x = input(&[2.0, 4.0]);
+
+    let ensemble = Ensemble::new(members.clone()).unwrap();
+    let predictions: Vec<f32> = ensemble.member_predictions(x.as_view()).unwrap().iter().map(|p| p[0]).collect();
+    assert_eq!(predictions, [2.0, 10.0]);
+    assert_eq!(ensemble.predict(x.as_view()).unwrap()[0], 6.0);
+    assert_eq!(ensemble.weights(), &[0.5, 0.5]);
+
+    // Weights are normalized, so [1, 3] counts the second member three times as much as the first.
+    let weighted = Ensemble::weighted(members, vec![1.0, 3.0]).unwrap();
+    assert_eq!(weighted.weights(), &[0.25, 0.75]);
+    assert_eq!(weighted.predict(x.as_view()).unwrap()[0], 8.0);
+}
+
+#[test]
+fn members_vote_for_their_argmax() {
+    // Each member's largest output is the input picked by its row order.
+    let first = linear(&[1.0, 0.0, 0.0, 1.0], 2);
+    let second = linear(&[0.0, 1.0, 1.0, 0.0], 2);
+    let x = input(&[1.0, 0.0]);
+
+    let majority = Ensemble::new(vec![first.clone(), second.clone(), second.clone()]).unwrap();
+    assert_eq!(majority.vote(x.as_view()).unwrap(), 1);
+
+    // The vote counts members, not the size of their outputs, unlike the mean.
+    let confident = linear(&[100.0, 0.0, 0.0, 0.0], 2);
+    let outvoted = Ensemble::new(vec![confident, second.clone(), second.clone()]).unwrap();
+    assert_eq!(outvoted.vote(x.as_view()).unwrap(), 1);
+    assert_eq!(outvoted.predict(x.as_view()).unwrap().imax(), 0);
+
+    // A tie goes to the lowest class, and weights decide otherwise.
+    assert_eq!(Ensemble::new(vec![second.clone(), first.clone()]).unwrap().vote(x.as_view()).unwrap(), 0);
+    assert_eq!(Ensemble::weighted(vec![first, second], vec![1.0, 2.0]).unwrap().vote(x.as_view()).unwrap(), 1);
+}
+
+#[test]
+fn members_have_to_share_input_and_output_sizes() {
+    let mut rng = StdRng::seed_from_u64(1);
+    let uniform = Uniform::new(-1.0, 1.0).unwrap();
+    let network = |sizes: &[usize], rng: &mut StdRng| Network::random_with_rng(sizes, sigmoid!(), &uniform, rng).unwrap();
+
+    // The hidden layers may differ.
+    let ensemble = Ensemble::new(vec![network(&[2, 3, 1], &mut rng), network(&[2, 8, 4, 1], &mut rng)]).unwrap();
+    assert_eq!((ensemble.input_size(), ensemble.output_size()), (2, 1));
+
+    assert!(matches!(
+        Ensemble::new(vec![network(&[2, 3, 1], &mut rng), network(&[2, 3, 1], &mut rng), network(&[3, 3, 1], &mut rng)]),
+        Err(NetworkError::EnsembleSizeMismatch { member: 2, inputs: 3, outputs: 1, expected_inputs: 2, expected_outputs: 1 })
+    ));
+    assert!(matches!(
+        Ensemble::new(vec![network(&[2, 1], &mut rng), network(&[2, 2], &mut rng)]),
+        Err(NetworkError::EnsembleSizeMismatch { member: 1, outputs: 2, .. })
+    ));
+    assert!(matches!(Ensemble::<f32>::new(Vec::new()), Err(NetworkError::NoNetworks)));
+}
+
+#[test]
+fn weights_are_validated() {
+    let members = || vec![linear(&[1.0], 1), linear(&[2.0], 1)];
+
+    assert!(matches!(Ensemble::weighted(members(), vec![1.0]), Err(NetworkError::MergeWeightCountMismatch { networks: 2, weights: 1 })));
+
+    for weights in [vec![-1.0, 2.0], vec![0.0, 0.0], vec![f32::NAN, 1.0], vec![f32::INFINITY, 1.0]] {
+        assert!(matches!(Ensemble::weighted(members(), weights.clone()), Err(NetworkError::InvalidMergeWeights)), "{weights:?}");
+    }
+
+    assert!(Ensemble::weighted(members(), vec![0.0, 1.0]).is_ok());
+}
+
+#[test]
+fn an_ensemble_beats_its_average_member_on_noisy_data() {
+    let mut rng = StdRng::seed_from_u64(2);
+    let mut noisy = |count: usize, noise: f32| -> Vec<Sample> {
+        (0..count)
+            .map(|_| {
+                let x: f32 = rng.random_range(-1.0..1.0);
+                let target = 0.5 + 0.3 * (3.0 * x).sin() + noise * rng.random_range(-1.0..1.0);
+                Sample::from_slices(&[x], &[target])
+            })
+            .collect()
+    };
+    let (train, validation) = (noisy(30, 0.2), noisy(200, 0.0));
+
+    let members: Vec<Network> = (0..5)
+        .map(|seed| {
+            let mut rng = StdRng::seed_from_u64(seed);
+            let mut network = Network::random_with_rng(&[1, 12, 1], sigmoid!(), &Uniform::new(-2.0, 2.0).unwrap(), &mut rng).unwrap();
+            Trainer::new(MSE, 2.0, 400).batch_size(5).fit_with_rng(&mut network, &train, &[], &mut rng).unwrap();
+            network
+        })
+        .collect();
+
+    let member_losses: Vec<f32> = members.iter().map(|network| network.evaluate(&validation, &MSE).unwrap()).collect();
+    let mean_member_loss = member_losses.iter().sum::<f32>() / 5.0;
+    let ensemble_loss = Ensemble::new(members).unwrap().evaluate(&validation, &MSE).unwrap();
+
+    assert!(ensemble_loss < mean_member_loss, "{ensemble_loss} vs {member_losses:?}");
+    assert!(matches!(Ensemble::new(vec![linear(&[1.0], 1)]).unwrap().evaluate(&[], &MSE), Err(NetworkError::EmptyDataset)));
+}
@@ -0,0 +1,83 @@
+mod common;
+
+use rand::{distr::Uniform, rngs::StdRng, SeedableRng};
+
+use neural::{
+    activations::*,
+    dataset::Sample,
+    losses::MSE,
+    network::{Network, NetworkError},
+};
+
+use common::{sample, xor};
+
+fn network() -> Network<f64> {
+    Network::random_with_rng(&[2, 6, 1], tanh!(), &Uniform::new(-1.0, 1.0).unwrap(), &mut StdRng::seed_from_u64(5)).unwrap()
+}
+
+fn dataset() -> Vec<Sample<f64>> {
+    (0..12).map(|i| sample(&[(i as f64 * 0.4).sin(), (i as f64 * 0.7).cos()], &[(i % 3) as f64 - 1.0])).collect()
+}
+
+#[test]
+fn matches_the_loss_learn_reports() {
+    let mut network = network();
+    let dataset = dataset();
+
+    for _ in 0..50 {
+        let evaluated = network.evaluate(&dataset, &MSE).unwrap();
+        let learned = network.learn(&dataset, &MSE, 0.1).unwrap();
+        assert!((evaluated - learned).abs() < 1e-12, "{evaluated} vs {learned}");
+    }
+}
+
+#[test]
+fn matches_the_loss_learn_reports_in_f32() {
+    let mut network = Network::random_with_rng(&[2, 4, 1], sigmoid!(), &Uniform::new(-1.0, 1.0).unwrap(), &mut StdRng::seed_from_u64(2)).unwrap();
+
+    for _ in 0..20 {
+        let evaluated = network.evaluate(&xor(), &MSE).unwrap();
+        let learned = network.learn(&xor(), &MSE, 0.5).unwrap();
+        assert!((evaluated - learned).abs() < 1e-6, "{evaluated} vs {learned}");
+    }
+}
+
+#[test]
+fn evaluating_between_backpropagation_and_the_update_changes_nothing() {
+    let (mut plain, mut interleaved) = (network(), network());
+    let dataset = dataset();
+    let (dataset, held_out) = dataset.split_at(8);
+
+    for _ in 0..20 {
+        plain.backpropagate(dataset, &MSE).unwrap();
+        plain.apply_gradients(-0.1 / 8.0);
+
+        interleaved.backpropagate(dataset, &MSE).unwrap();
+        interleaved.evaluate(held_out, &MSE).unwrap();
+        interleaved.evaluate(dataset, &MSE).unwrap();
+        interleaved.apply_gradients(-0.1 / 8.0);
+    }
+
+    assert_eq!(interleaved.get_params(), plain.get_params());
+}
+
+#[test]
+fn works_on_a_shared_network() {
+    let network = &network();
+    let dataset = dataset();
+
+    assert_eq!(network.evaluate(&dataset, &MSE).unwrap(), network.clone().evaluate(&dataset, &MSE).unwrap());
+}
+
+#[test]
+fn an_empty_dataset_is_an_error() {
+    assert!(matches!(network().evaluate(&[], &MSE), Err(NetworkError::EmptyDataset)));
+}
+
+#[test]
+fn samples_of_the_wrong_size_are_rejected() {
+    let mut dataset = dataset();
+    dataset.push(sample(&[1.0, 2.0, 3.0], &[0.0]));
+
+    assert!(network().evaluate(&dataset, &MSE).is_err());
+}
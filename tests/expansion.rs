@@ -0,0 +1,103 @@
+use nalgebra::DVector;
+use rand::{distr::Uniform, rngs::StdRng, Rng, SeedableRng};
+
+use neural::{
+    activations::*,
+    dataset::{DatasetError, FeatureExpansion, Sample},
+    losses::MSE,
+    network::Network,
+    training::Trainer,
+};
+
+fn expand(expansion: &FeatureExpansion, inputs: &[f32]) -> Vec<f32> {
+    expansion.transform(DVector::from_row_slice(inputs).as_view()).unwrap().iter().copied().collect()
+}
+
+#[test]
+fn expands_to_every_monomial_in_order() {
+    let quadratic = FeatureExpansion::new(2, 2).unwrap();
+    assert_eq!(expand(&quadratic, &[2.0, 3.0]), [2.0, 3.0, 4.0, 6.0, 9.0]);
+
+    let cubic = FeatureExpansion::new(2, 3).unwrap();
+    assert_eq!(expand(&cubic, &[2.0, 3.0]), [2.0, 3.0, 4.0, 6.0, 9.0, 8.0, 12.0, 18.0, 27.0]);
+
+    let three = FeatureExpansion::new(3, 2).unwrap();
+    assert_eq!(expand(&three, &[1.0, 2.0, 3.0]), [1.0, 2.0, 3.0, 1.0, 2.0, 3.0, 4.0, 6.0, 9.0]);
+}
+
+#[test]
+fn interactions_leave_out_powers() {
+    let interactions = FeatureExpansion::interactions_only(3, 3).unwrap();
+    assert!(interactions.is_interactions_only());
+    assert_eq!(expand(&interactions, &[2.0, 3.0, 5.0]), [2.0, 3.0, 5.0, 6.0, 10.0, 15.0, 30.0]);
+
+    // There are no products of more distinct features than there are.
+    assert_eq!(FeatureExpansion::interactions_only(2, 5).unwrap().output_size(), 3);
+}
+
+#[test]
+fn degree_one_is_the_identity() {
+    for expansion in [FeatureExpansion::new(4, 1).unwrap(), FeatureExpansion::interactions_only(4, 1).unwrap()] {
+        assert_eq!(expansion.output_size(), 4);
+        assert_eq!(expand(&expansion, &[1.5, -2.0, 0.0, 7.0]), [1.5, -2.0, 0.0, 7.0]);
+    }
+}
+
+#[test]
+fn output_sizes_count_the_monomials() {
+    // The monomials of n features up to degree d without the constant are C(n + d, d) - 1 of them.
+    let binomial = |n: usize, k: usize| (0..k).fold(1, |product, i| product * (n - i) / (i + 1));
+
+    for (input_size, degree) in [(1, 4), (2, 2), (3, 3), (5, 2), (4, 4)] {
+        let expansion = FeatureExpansion::new(input_size, degree).unwrap();
+        assert_eq!(expansion.output_size(), binomial(input_size + degree, degree) - 1, "{input_size}, {degree}");
+        assert_eq!((expansion.input_size(), expansion.degree()), (input_size, degree));
+    }
+}
+
+#[test]
+fn rejects_empty_expansions_and_other_input_sizes() {
+    assert!(matches!(FeatureExpansion::new(0, 2), Err(DatasetError::InvalidExpansion { input_size: 0, degree: 2 })));
+    assert!(matches!(FeatureExpansion::interactions_only(2, 0), Err(DatasetError::InvalidExpansion { input_size: 2, degree: 0 })));
+
+    let expansion = FeatureExpansion::new(2, 2).unwrap();
+    assert!(matches!(
+        expansion.transform(DVector::zeros(3).as_view()),
+        Err(DatasetError::TransformSizeMismatch { expected: 2, given: 3 })
+    ));
+    assert!(expansion.expand_samples(&[Sample::from_slices(&[1.0], &[0.0])]).is_err());
+}
+
+#[test]
+fn expanded_samples_keep_everything_but_their_inputs() {
+    let expansion = FeatureExpansion::new(2, 2).unwrap();
+    let samples = [Sample::from_slices(&[2.0, 3.0], &[1.0, 0.0]).with_weight(2.5).with_tag("a")];
+
+    let expanded = expansion.expand_samples(&samples).unwrap();
+    assert_eq!(expanded[0].inputs().as_slice(), &[2.0, 3.0, 4.0, 6.0, 9.0]);
+    assert_eq!(expanded[0].expected_outputs(), samples[0].expected_outputs());
+    assert_eq!((expanded[0].weight(), expanded[0].tag()), (2.5, Some("a")));
+}
+
+#[test]
+fn a_circle_becomes_learnable() {
+    let mut rng = StdRng::seed_from_u64(1);
+    let samples: Vec<Sample> = (0..200)
+        .map(|_| {
+            let (x, y) = (rng.random_range(-1.0..1.0), rng.random_range(-1.0..1.0));
+            let inside = x * x + y * y < 0.4;
+            Sample::from_slices(&[x, y], &[inside as u8 as f32])
+        })
+        .collect();
+
+    let expansion = FeatureExpansion::new(2, 2).unwrap();
+    let expanded = expansion.expand_samples(&samples).unwrap();
+    let mut network = Network::random_with_rng(&[expansion.output_size(), 4, 1], sigmoid!(), &Uniform::new(-1.0, 1.0).unwrap(), &mut rng).unwrap();
+    Trainer::new(MSE, 2.0, 500).batch_size(10).fit_with_rng(&mut network, &expanded, &[], &mut rng).unwrap();
+
+    let correct = expanded
+        .iter()
+        .filter(|sample| (network.predict(sample.inputs()).unwrap()[0] > 0.5) == (sample.expected_outputs()[0] > 0.5))
+        .count();
+    assert!(correct >= 194, "{correct} of 200");
+}
@@ -0,0 +1,122 @@
+#![cfg(feature = "ffi")]
+
+use std::{
+    ffi::{CStr, CString},
+    path::{Path, PathBuf},
+    ptr,
+};
+
+use nalgebra::DVector;
+use rand::{distr::Uniform, rngs::StdRng, SeedableRng};
+
+use neural::{
+    activations::*,
+    ffi::{neural_last_error_message, neural_network_free, neural_network_load, neural_network_sizes, neural_predict, NeuralStatus},
+    network::Network,
+};
+
+fn saved(name: &str) -> (Network, PathBuf) {
+    let network = Network::random_with_rng(&[3, 4, 2], sigmoid!(), &Uniform::new(-1.0, 1.0).unwrap(), &mut StdRng::seed_from_u64(33)).unwrap();
+    let path = std::env::temp_dir().join(format!("neural-ffi-{name}-{}.bin", std::process::id()));
+    network.save(&path).unwrap();
+    (network, path)
+}
+
+fn load(path: &Path) -> *mut Network {
+    let path = CString::new(path.to_str().unwrap()).unwrap();
+    let mut handle = ptr::null_mut();
+    assert_eq!(unsafe { neural_network_load(path.as_ptr(), &mut handle) }, NeuralStatus::Ok);
+    handle
+}
+
+fn last_error() -> String {
+    unsafe { CStr::from_ptr(neural_last_error_message()) }.to_str().unwrap().to_string()
+}
+
+#[test]
+fn predicts_what_the_network_predicts() {
+    let (network, path) = saved("predict");
+    let handle = load(&path);
+    let (mut input_size, mut output_size) = (0, 0);
+
+    assert_eq!(unsafe { neural_network_sizes(handle, &mut input_size, &mut output_size) }, NeuralStatus::Ok);
+    assert_eq!((input_size, output_size), (3, 2));
+
+    let inputs = [0.5f32, -1.0, 0.25];
+    let mut outputs = [0.0f32; 2];
+    let status = unsafe { neural_predict(handle, inputs.as_ptr(), inputs.len(), outputs.as_mut_ptr(), outputs.len()) };
+
+    assert_eq!(status, NeuralStatus::Ok);
+    assert_eq!(last_error(), "");
+    assert_eq!(outputs.as_slice(), network.predict(DVector::from_row_slice(&inputs).as_view()).unwrap().as_slice());
+    assert_eq!(unsafe { neural_network_free(handle) }, NeuralStatus::Ok);
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn wrong_buffer_sizes_write_nothing() {
+    let (_, path) = saved("sizes");
+    let handle = load(&path);
+    let inputs = [0.5f32; 4];
+    let mut outputs = [7.0f32; 3];
+
+    let status = unsafe { neural_predict(handle, inputs.as_ptr(), 4, outputs.as_mut_ptr(), 2) };
+    assert_eq!(status, NeuralStatus::InputSizeMismatch);
+    assert_eq!(last_error(), "4 inputs were given, but the network takes 3");
+
+    let status = unsafe { neural_predict(handle, inputs.as_ptr(), 3, outputs.as_mut_ptr(), 3) };
+    assert_eq!(status, NeuralStatus::OutputSizeMismatch);
+    assert_eq!(last_error(), "the output buffer holds 3 values, but the network gives 2");
+
+    assert_eq!(outputs, [7.0; 3]);
+    unsafe { neural_network_free(handle) };
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn null_pointers_are_reported() {
+    let (_, path) = saved("null");
+    let handle = load(&path);
+    let inputs = [0.0f32; 3];
+    let mut outputs = [0.0f32; 2];
+    let mut size = 0;
+
+    assert_eq!(unsafe { neural_predict(ptr::null(), inputs.as_ptr(), 3, outputs.as_mut_ptr(), 2) }, NeuralStatus::NullPointer);
+    assert_eq!(last_error(), "the network handle is null");
+    assert_eq!(unsafe { neural_predict(handle, ptr::null(), 3, outputs.as_mut_ptr(), 2) }, NeuralStatus::NullPointer);
+    assert_eq!(unsafe { neural_predict(handle, inputs.as_ptr(), 3, ptr::null_mut(), 2) }, NeuralStatus::NullPointer);
+    assert_eq!(unsafe { neural_network_sizes(handle, &mut size, ptr::null_mut()) }, NeuralStatus::NullPointer);
+    assert_eq!(unsafe { neural_network_load(ptr::null(), &mut ptr::null_mut()) }, NeuralStatus::NullPointer);
+    assert_eq!(unsafe { neural_network_load(c"model.bin".as_ptr(), ptr::null_mut()) }, NeuralStatus::NullPointer);
+    assert_eq!(unsafe { neural_network_free(ptr::null_mut()) }, NeuralStatus::Ok);
+
+    unsafe { neural_network_free(handle) };
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn bad_paths_leave_a_null_handle() {
+    let mut handle = ptr::dangling_mut();
+
+    let status = unsafe { neural_network_load(c"/nonexistent/neural.bin".as_ptr(), &mut handle) };
+    assert_eq!(status, NeuralStatus::LoadFailed);
+    assert!(handle.is_null());
+    assert!(last_error().starts_with("couldn't load the network"));
+
+    handle = ptr::dangling_mut();
+    let status = unsafe { neural_network_load(c"\xff\xfe.bin".as_ptr(), &mut handle) };
+    assert_eq!(status, NeuralStatus::InvalidPath);
+    assert!(handle.is_null());
+}
+
+#[test]
+fn a_file_that_isnt_a_network_fails_to_load() {
+    let path = std::env::temp_dir().join(format!("neural-ffi-garbage-{}.bin", std::process::id()));
+    std::fs::write(&path, b"not a network").unwrap();
+    let c_path = CString::new(path.to_str().unwrap()).unwrap();
+    let mut handle = ptr::null_mut();
+
+    assert_eq!(unsafe { neural_network_load(c_path.as_ptr(), &mut handle) }, NeuralStatus::LoadFailed);
+    assert!(handle.is_null());
+    std::fs::remove_file(path).unwrap();
+}
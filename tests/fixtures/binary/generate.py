@@ -0,0 +1,70 @@
+"""Writes a model file of every version of the binary format of `Network::save`, see src/network/binary.rs.
+
+Every version is written the way `save` of that version wrote it, with a network that uses the field the
+version added: v1 an XOR network of f32s, v2 the same network as f16s, v3 a bias-free layer, v4 a layer
+sharing the parameters of the one before and v5 a maxout layer of two pieces.
+"""
+
+import struct
+
+XOR = [
+    # The hidden layer has an OR and a NAND unit, the output layer is an AND of them.
+    (2, 2, "sigmoid", [20.0, 20.0, -20.0, -20.0], [-10.0, 30.0]),
+    (2, 1, "sigmoid", [20.0, 20.0], [-30.0]),
+]
+
+
+def u32(value):
+    return struct.pack("<I", value)
+
+
+def values(precision, numbers):
+    return b"".join(struct.pack("<e" if precision == 1 else "<f", number) for number in numbers)
+
+
+def model(version, layers, precision=0):
+    """`layers` are (inputs, outputs, activation, weights row by row, biases or None, shared_with or None, further pieces)."""
+    data = b"NRLN" + u32(version)
+    if version >= 2:
+        data += bytes([precision])
+
+    data += u32(len(layers))
+    for inputs, outputs, activation, weights, biases, shared_with, pieces in layers:
+        data += u32(inputs) + u32(outputs) + u32(len(activation)) + activation.encode()
+        # No layer is batch normalized.
+        data += bytes([0])
+        if version >= 3:
+            data += bytes([biases is not None])
+        if version >= 4:
+            data += bytes([shared_with is not None])
+            if shared_with is not None:
+                data += u32(shared_with)
+        if version >= 5:
+            data += u32(1 + len(pieces))
+
+        if shared_with is None:
+            data += values(precision, weights)
+            if biases is not None:
+                data += values(precision, biases)
+            for piece_weights, piece_biases in pieces:
+                data += values(precision, piece_weights) + values(precision, piece_biases)
+
+    return data
+
+
+def write(path, data):
+    with open(path, "wb") as file:
+        file.write(data)
+
+
+xor = [(*layer, None, []) for layer in XOR]
+write("v1.nrln", model(1, xor))
+write("v2.nrln", model(2, xor, precision=1))
+write("v3.nrln", model(3, xor + [(1, 1, "identity", [-1.0], None, None, [])]))
+write("v4.nrln", model(4, [
+    (1, 1, "identity", [2.0], [0.5], None, []),
+    (1, 1, "identity", [2.0], [0.5], 0, []),
+]))
+write("v5.nrln", model(5, [
+    (2, 1, "maxout", [1.0, 0.0], [0.0], None, [([0.0, 1.0], [0.25])]),
+]))
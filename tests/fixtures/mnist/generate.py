@@ -0,0 +1,24 @@
+"""Writes a tiny MNIST in the IDX format `dataset::mnist` reads, raw and gzip compressed.
+
+Three 28x28 images with the labels of the first three MNIST test images, 7, 2 and 1: a black image, a white
+one and a gradient that brightens by 9 from every column to the next.
+"""
+
+import gzip
+import struct
+from pathlib import Path
+
+IMAGES = [
+    bytes(28 * 28),
+    bytes([255] * 28 * 28),
+    bytes(column * 9 for _ in range(28) for column in range(28)),
+]
+LABELS = [7, 2, 1]
+
+directory = Path(__file__).parent
+images = struct.pack(">IIII", 0x0803, len(IMAGES), 28, 28) + b"".join(IMAGES)
+labels = struct.pack(">II", 0x0801, len(LABELS)) + bytes(LABELS)
+
+for name, data in [("images.idx", images), ("labels.idx", labels)]:
+    (directory / name).write_bytes(data)
+    (directory / f"{name}.gz").write_bytes(gzip.compress(data, mtime=0))
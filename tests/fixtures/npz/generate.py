@@ -0,0 +1,45 @@
+"""Writes the .npz fixtures the way np.savez does, for checkouts without NumPy.
+
+np.savez stores every array uncompressed with `ZipFile.open(name, 'w', force_zip64=True)`, so every local
+header has a zip64 extra field, and writes it with `np.lib.format.write_array`: a version 1.0 header padded
+with spaces to a multiple of 64 bytes and ended by a newline, then the data, column by column for an array in
+Fortran order. With NumPy, `fortran_savez.npz` is
+
+    np.savez('fortran_savez.npz',
+             layer0_weights=np.asfortranarray(W0), layer0_biases=b0, layer1_weights=W1.astype('<f4'))
+"""
+
+import struct
+import zipfile
+
+W0 = [[0.5, -1.0], [0.25, 2.0], [-0.75, 1.5]]
+B0 = [0.1, -0.2, 0.3]
+W1 = [[1.0, -0.5, 0.25]]
+
+
+def npy(descr, fortran_order, shape, values):
+    shape = f"({shape[0]},)" if len(shape) == 1 else "(" + ", ".join(map(str, shape)) + ")"
+    header = f"{{'descr': '{descr}', 'fortran_order': {fortran_order}, 'shape': {shape}, }}"
+    padding = -(10 + len(header) + 1) % 64
+    header = (header + " " * padding + "\n").encode("latin1")
+    fmt = {"<f4": "<f", "<f8": "<d"}[descr]
+    return b"\x93NUMPY\x01\x00" + struct.pack("<H", len(header)) + header + b"".join(struct.pack(fmt, v) for v in values)
+
+
+def savez(path, arrays):
+    with zipfile.ZipFile(path, "w", compression=zipfile.ZIP_STORED, allowZip64=True) as archive:
+        for name, data in arrays:
+            with archive.open(name + ".npy", "w", force_zip64=True) as file:
+                file.write(data)
+
+
+savez("fortran_savez.npz", [
+    ("layer0_weights", npy("<f8", True, (3, 2), [W0[i][j] for j in range(2) for i in range(3)])),
+    ("layer0_biases", npy("<f8", False, (3,), B0)),
+    ("layer1_weights", npy("<f4", False, (1, 3), W1[0])),
+])
+
+# A shape whose size overflows, with a few bytes of data.
+savez("overflowing_shape.npz", [
+    ("layer0_weights", npy("<f4", False, (2**32, 2**32), [0.0, 0.0])),
+])
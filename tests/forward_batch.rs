@@ -0,0 +1,105 @@
+use nalgebra::{DMatrix, DVector};
+use rand::{distr::Uniform, rngs::StdRng, Rng, SeedableRng};
+
+use neural::{
+    activations::*,
+    network::{layer::{Layer, LayerError}, Network, NetworkError},
+};
+
+fn network() -> Network {
+    Network::random_with_rng(&[2, 16, 8, 3], tanh!(), &Uniform::new(-1.0, 1.0).unwrap(), &mut StdRng::seed_from_u64(3)).unwrap()
+}
+
+#[test]
+fn every_column_is_what_forward_gives() {
+    let mut network = network();
+    let mut rng = StdRng::seed_from_u64(4);
+    let inputs = DMatrix::from_fn(2, 50, |_, _| rng.random_range(-2.0..2.0));
+
+    let outputs = network.forward_batch(&inputs).unwrap();
+
+    assert_eq!(outputs.shape(), (3, 50));
+    for (input, output) in inputs.column_iter().zip(outputs.column_iter()) {
+        let expected = network.forward(input.clone_owned()).unwrap();
+        for (actual, expected) in output.iter().zip(expected.iter()) {
+            assert!((actual - expected).abs() < 1e-6, "{actual} vs {expected}");
+        }
+    }
+}
+
+#[test]
+fn a_single_column_batch_is_one_forward() {
+    let mut network = network();
+    let input = DVector::from_row_slice(&[0.25, -0.75]);
+
+    let batch = network.forward_batch(&DMatrix::from_columns(std::slice::from_ref(&input))).unwrap();
+    let single = network.forward(input).unwrap();
+
+    assert!((batch.column(0) - single).amax() < 1e-6);
+}
+
+#[test]
+fn leaves_the_training_state_alone() {
+    let network = network();
+    let before = network.get_params();
+
+    network.forward_batch(&DMatrix::from_element(2, 10, 0.5)).unwrap();
+
+    assert_eq!(network.get_params(), before);
+}
+
+#[test]
+fn rejects_a_row_count_other_than_the_input_size() {
+    let network = network();
+
+    assert!(matches!(
+        network.forward_batch(&DMatrix::zeros(3, 4)),
+        Err(NetworkError::LayerError(LayerError::InputSizeMismatch {
+            layer_input_size: 2,
+            given_input_size: 3
+        }))
+    ));
+}
+
+#[test]
+fn predict_batch_columns_are_what_predict_gives() {
+    let mut rng = StdRng::seed_from_u64(5);
+    let uniform = Uniform::new(-1.0, 1.0).unwrap();
+    let layers = vec![
+        Layer::random_with_rng(2, 6, relu!(), &uniform, &mut rng).unwrap().with_batch_norm(),
+        Layer::random_with_rng(6, 3, sigmoid!(), &uniform, &mut rng).unwrap(),
+    ];
+
+    for network in [network(), Network::from_layers(layers).unwrap()] {
+        let inputs = DMatrix::from_fn(2, 20, |_, _| rng.random_range(-2.0..2.0));
+        let outputs = network.predict_batch(&inputs).unwrap();
+
+        assert_eq!(outputs.shape(), (3, 20));
+        for (input, output) in inputs.column_iter().zip(outputs.column_iter()) {
+            assert!((output - network.predict(input).unwrap()).amax() < 1e-6);
+        }
+    }
+}
+
+#[test]
+fn predict_batch_of_no_columns_is_empty() {
+    let network = network();
+
+    assert_eq!(network.predict_batch(&DMatrix::zeros(2, 0)).unwrap().shape(), (3, 0));
+    assert!(matches!(
+        network.predict_batch(&DMatrix::zeros(5, 0)),
+        Err(NetworkError::BatchInputSizeMismatch { rows: 5, network_inputs: 2 })
+    ));
+}
+
+#[test]
+fn predict_batch_takes_samples_as_columns() {
+    let network = network();
+
+    // Five samples stored as rows are a batch of the wrong orientation.
+    assert!(matches!(
+        network.predict_batch(&DMatrix::zeros(5, 2)),
+        Err(NetworkError::BatchInputSizeMismatch { rows: 5, network_inputs: 2 })
+    ));
+    assert_eq!(network.predict_batch(&DMatrix::zeros(5, 2).transpose()).unwrap().shape(), (3, 5));
+}
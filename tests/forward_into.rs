@@ -0,0 +1,56 @@
+mod allocations;
+
+use nalgebra::DVector;
+use rand::{distr::Uniform, rngs::StdRng, SeedableRng};
+
+use neural::{
+    activations::*,
+    network::{Network, NetworkError},
+};
+
+use allocations::{allocations, Counting};
+
+#[global_allocator]
+static ALLOCATOR: Counting = Counting;
+
+fn network(layer_sizes: &[usize]) -> Network {
+    Network::random_with_rng(layer_sizes, relu!(), &Uniform::new(-1.0, 1.0).unwrap(), &mut StdRng::seed_from_u64(6)).unwrap()
+}
+
+#[test]
+fn gives_what_forward_gives_without_allocating() {
+    let mut network = network(&[3, 16, 8, 2]);
+    let mut scratch = network.scratch();
+    let mut output = DVector::zeros(2);
+
+    for inputs in [[0.0, 0.0, 0.0], [0.5, -1.0, 2.0], [-0.3, 0.7, 0.1]] {
+        let inputs = DVector::from_row_slice(&inputs);
+
+        let before = allocations();
+        network.forward_into(inputs.as_view(), &mut scratch, &mut output).unwrap();
+        assert_eq!(allocations(), before);
+
+        assert_eq!(output, network.forward(inputs).unwrap());
+    }
+}
+
+#[test]
+fn resizes_an_output_of_the_wrong_size() {
+    let network = network(&[2, 3, 4]);
+    let mut scratch = network.scratch();
+    let mut output = DVector::zeros(0);
+
+    network.forward_into(DVector::from_vec(vec![1.0, 2.0]).as_view(), &mut scratch, &mut output).unwrap();
+    assert_eq!(output, network.predict(DVector::from_vec(vec![1.0, 2.0]).as_view()).unwrap());
+}
+
+#[test]
+fn rejects_the_scratch_of_another_network() {
+    let network = network(&[2, 3, 1]);
+    let mut scratch = self::network(&[2, 5, 1]).scratch();
+
+    assert!(matches!(
+        network.forward_into(DVector::from_vec(vec![1.0, 2.0]).as_view(), &mut scratch, &mut DVector::zeros(1)),
+        Err(NetworkError::ScratchMismatch)
+    ));
+}
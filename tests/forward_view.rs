@@ -0,0 +1,70 @@
+mod allocations;
+
+use nalgebra::DVector;
+use rand::{distr::Uniform, rngs::StdRng, SeedableRng};
+
+use neural::{
+    activations::*,
+    dataset::Sample,
+    network::{layer::LayerError, Network, NetworkError},
+};
+
+use allocations::{allocations, Counting};
+
+#[global_allocator]
+static ALLOCATOR: Counting = Counting;
+
+fn network(layer_sizes: &[usize]) -> Network {
+    Network::random_with_rng(layer_sizes, relu!(), &Uniform::new(-0.1, 0.1).unwrap(), &mut StdRng::seed_from_u64(3)).unwrap()
+}
+
+#[test]
+fn gives_what_forward_gives_and_records_the_same_state() {
+    let mut viewed = network(&[3, 8, 4, 2]);
+    let mut owned = viewed.clone();
+
+    for inputs in [[0.0, 0.0, 0.0], [0.5, -1.0, 2.0], [-0.3, 0.7, 0.1]] {
+        let sample = Sample::from_slices(&inputs, &[0.0, 0.0]);
+
+        assert_eq!(viewed.forward_view(sample.inputs()).unwrap(), owned.forward(sample.inputs().clone_owned()).unwrap());
+        // The layers keep what they saw for backpropagation, so both networks must end up identical.
+        assert_eq!(format!("{viewed:?}"), format!("{owned:?}"));
+    }
+}
+
+#[test]
+fn does_not_copy_the_input() {
+    let mut network = network(&[784, 32, 10]);
+    let sample = Sample::new(DVector::from_element(784, 0.5), DVector::zeros(10));
+    network.forward_view(sample.inputs()).unwrap();
+
+    let before = allocations();
+    network.forward_view(sample.inputs()).unwrap();
+    let viewed = allocations() - before;
+
+    let before = allocations();
+    network.forward(sample.inputs().clone_owned()).unwrap();
+    let owned = allocations() - before;
+
+    assert_eq!(viewed + 1, owned);
+
+    // What remains are the outputs of the layers, whatever the size of the input.
+    let mut small = self::network(&[3, 32, 10]);
+    let sample = Sample::from_slices(&[1.0, 2.0, 3.0], &[0.0; 10]);
+    small.forward_view(sample.inputs()).unwrap();
+
+    let before = allocations();
+    small.forward_view(sample.inputs()).unwrap();
+    assert_eq!(allocations() - before, viewed);
+}
+
+#[test]
+fn rejects_an_input_of_the_wrong_size() {
+    let mut network = network(&[3, 4, 1]);
+    let sample = Sample::from_slices(&[1.0, 2.0], &[0.0]);
+
+    assert!(matches!(
+        network.forward_view(sample.inputs()),
+        Err(NetworkError::LayerError(LayerError::InputSizeMismatch { .. }))
+    ));
+}
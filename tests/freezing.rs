@@ -0,0 +1,70 @@
+mod common;
+
+use rand::{distr::Uniform, rngs::StdRng, SeedableRng};
+
+use neural::{
+    activations::*,
+    losses::MSE,
+    network::{MaxoutLayer, Network, NetworkError},
+};
+
+fn network() -> Network {
+    Network::random_with_rng(&[2, 4, 4, 1], sigmoid!(), &Uniform::new(-1.0, 1.0).unwrap(), &mut StdRng::seed_from_u64(6)).unwrap()
+}
+
+/// The weights and biases of every layer.
+fn parameters(network: &Network) -> Vec<(Vec<f32>, Vec<f32>)> {
+    network.dense_layers().map(|layer| (layer.weights().iter().copied().collect(), layer.biases().iter().copied().collect())).collect()
+}
+
+fn train(network: &mut Network) {
+    for _ in 0..20 {
+        network.learn(&common::xor(), &MSE, 0.5).unwrap();
+    }
+}
+
+#[test]
+fn a_frozen_middle_layer_stays_the_same_while_its_neighbors_learn() {
+    let mut network = network();
+    network.freeze_layers(1..2).unwrap();
+    let before = parameters(&network);
+
+    train(&mut network);
+    let after = parameters(&network);
+
+    assert_eq!(after[1], before[1]);
+    assert_ne!(after[0], before[0]);
+    assert_ne!(after[2], before[2]);
+    assert!(!network.dense_layer(1).unwrap().is_trainable());
+
+    network.unfreeze_layers(1..2).unwrap();
+    train(&mut network);
+    assert_ne!(parameters(&network)[1], after[1]);
+}
+
+#[test]
+fn invalid_ranges_are_rejected_without_freezing_anything() {
+    let mut network = network();
+
+    #[allow(clippy::reversed_empty_ranges)]
+    let reversed = 2..1;
+    assert!(matches!(network.freeze_layers(reversed), Err(NetworkError::InvalidLayerRange { start: 2, end: 1 })));
+    assert!(matches!(network.freeze_layers(1..4), Err(NetworkError::LayerIndexOutOfRange { index: 3, layers: 3 })));
+    assert!(network.dense_layers().all(|layer| layer.is_trainable()));
+
+    // An empty range is fine and changes nothing.
+    network.freeze_layers(2..2).unwrap();
+    assert!(network.dense_layers().all(|layer| layer.is_trainable()));
+}
+
+#[test]
+fn only_dense_layers_can_be_frozen() {
+    let mut rng = StdRng::seed_from_u64(1);
+    let uniform = Uniform::new(-1.0, 1.0).unwrap();
+    let maxout = MaxoutLayer::random_with_rng(2, 3, 2, &uniform, &mut rng).unwrap();
+    let output = neural::network::layer::Layer::random_with_rng(3, 1, sigmoid!(), &uniform, &mut rng).unwrap();
+    let mut network = Network::from_network_layers(vec![Box::new(maxout), Box::new(output)]).unwrap();
+
+    assert!(matches!(network.freeze_layers(0..2), Err(NetworkError::NotDense { layer: 0 })));
+    assert!(network.dense_layer(1).unwrap().is_trainable());
+}
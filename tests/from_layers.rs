@@ -0,0 +1,66 @@
+mod common;
+
+use nalgebra::{DMatrix, DVector};
+use rand::{distr::Uniform, rngs::StdRng, SeedableRng};
+
+use neural::{
+    activations::*,
+    losses::MSE,
+    network::{layer::Layer, Network, NetworkError},
+};
+
+use common::xor;
+
+#[test]
+fn zero_layers_assemble_into_network_zeros() {
+    let layers = vec![Layer::zeros(2, 3, sigmoid!()).unwrap(), Layer::zeros(3, 4, sigmoid!()).unwrap(), Layer::zeros(4, 1, sigmoid!()).unwrap()];
+    let mut assembled = Network::from_layers(layers).unwrap();
+    let mut zeros = Network::zeros(&[2, 3, 4, 1], sigmoid!()).unwrap();
+
+    assert_eq!(assembled.layer_sizes(), zeros.layer_sizes());
+    assert_eq!(assembled.to_string(), zeros.to_string());
+    assert_eq!(assembled.get_params(), zeros.get_params());
+
+    // They stay the same through training.
+    for _ in 0..10 {
+        assembled.learn(&xor(), &MSE, 0.5).unwrap();
+        zeros.learn(&xor(), &MSE, 0.5).unwrap();
+    }
+    assert_eq!(assembled.get_params(), zeros.get_params());
+}
+
+#[test]
+fn every_layer_keeps_its_own_activation_and_parameters() {
+    let uniform = Uniform::new(-1.0, 1.0).unwrap();
+    let mut rng = StdRng::seed_from_u64(1);
+    let hidden = Layer::random_with_rng(2, 4, relu!(), &uniform, &mut rng).unwrap();
+    let mut output = Layer::zeros(4, 1, identity!()).unwrap();
+    output.set_weights(DMatrix::from_element(1, 4, 1.0)).unwrap();
+    output.set_biases(DVector::from_element(1, -0.5)).unwrap();
+
+    let relu_outputs = hidden.predict(DVector::from_vec(vec![0.3, -0.8]).as_view()).unwrap();
+    let network = Network::from_layers(vec![hidden, output]).unwrap();
+
+    assert_eq!(network.to_string(), "Network: 2 -> 4 (relu) -> 1 (identity), 17 parameters");
+    let output: f32 = network.predict(DVector::from_vec(vec![0.3, -0.8]).as_view()).unwrap()[0];
+    assert!((output - (relu_outputs.sum() - 0.5)).abs() < 1e-6);
+}
+
+#[test]
+fn layers_that_dont_chain_are_rejected_by_index() {
+    let layers = vec![Layer::<f32>::zeros(2, 3, sigmoid!()).unwrap(), Layer::zeros(3, 4, sigmoid!()).unwrap(), Layer::zeros(5, 1, sigmoid!()).unwrap()];
+    let error = Network::from_layers(layers).unwrap_err();
+
+    assert!(matches!(error, NetworkError::LayerChainMismatch { layer: 1, next_layer: 2, output_size: 4, input_size: 5 }));
+    assert_eq!(error.to_string(), "layer 1 gives 4 outputs, but layer 2 takes 5 inputs");
+}
+
+#[test]
+fn no_layers_is_no_network() {
+    assert!(matches!(Network::<f32>::from_layers(Vec::new()), Err(NetworkError::NoLayers)));
+    assert!(matches!(Network::<f32>::from_network_layers(Vec::new()), Err(NetworkError::NoLayers)));
+
+    // A single layer is enough.
+    let network = Network::from_layers(vec![Layer::<f32>::zeros(3, 2, tanh!()).unwrap()]).unwrap();
+    assert_eq!(network.layer_sizes(), [3, 2]);
+}
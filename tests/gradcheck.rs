@@ -0,0 +1,107 @@
+mod common;
+
+use nalgebra::{DVector, DVectorView};
+use rand::{distr::Uniform, rngs::StdRng, SeedableRng};
+
+use neural::{
+    activations::*,
+    gradcheck::{check_gradients, Parameter},
+    losses::{LossFn, LossFnError, MSE},
+    network::{layer::Layer, Network},
+};
+
+use common::sample;
+
+fn network() -> Network<f64> {
+    Network::random_with_rng(&[3, 4, 2], sigmoid!(), &Uniform::new(-1.0, 1.0).unwrap(), &mut StdRng::seed_from_u64(17)).unwrap()
+}
+
+/// Sigmoid whose derivative forgot the `1 - activation` factor.
+#[derive(Clone)]
+struct WrongSigmoid;
+impl ActivationFn<f64> for WrongSigmoid {
+    fn apply(&self, x: f64) -> f64 {
+        Sigmoid.apply(x)
+    }
+
+    fn derivative(&self, _x: f64, activation: f64) -> f64 {
+        activation
+    }
+}
+
+/// MSE with a gradient twice as steep as its loss.
+struct DoubledMSE;
+impl LossFn<f64> for DoubledMSE {
+    fn apply(&self, output: DVectorView<f64>, expected_output: DVectorView<f64>) -> Result<f64, LossFnError> {
+        MSE.apply(output, expected_output)
+    }
+
+    fn partial_gradient(&self, output: DVectorView<f64>, expected_output: DVectorView<f64>) -> Result<DVector<f64>, LossFnError> {
+        Ok(MSE.partial_gradient(output, expected_output)? * 2.0)
+    }
+}
+
+#[test]
+fn sigmoid_and_mse_pass() {
+    let mut network = network();
+    let before = network.get_params();
+
+    let check = check_gradients(&mut network, &sample(&[0.5, -0.3, 0.9], &[1.0, 0.0]), &MSE, 1e-6).unwrap();
+
+    assert!(check.passed(1e-7), "{check:?}");
+    assert_eq!(network.get_params(), before);
+    let worst = check.worst.unwrap();
+    assert!((worst.analytic - worst.numeric).abs() < 1e-7);
+}
+
+#[test]
+fn a_wrong_activation_derivative_fails_loudly() {
+    let hidden = Layer::random_with_rng(3, 4, Box::new(WrongSigmoid), &Uniform::new(-1.0, 1.0).unwrap(), &mut StdRng::seed_from_u64(18)).unwrap();
+    let output = Layer::random_with_rng(4, 2, sigmoid!(), &Uniform::new(-1.0, 1.0).unwrap(), &mut StdRng::seed_from_u64(19)).unwrap();
+    let mut network = Network::from_layers(vec![hidden, output]).unwrap();
+
+    let check = check_gradients(&mut network, &sample(&[0.5, -0.3, 0.9], &[1.0, 0.0]), &MSE, 1e-6).unwrap();
+
+    assert!(!check.passed(1e-4));
+    // Only the hidden layer's gradients go through the wrong derivative.
+    let worst = check.worst.unwrap();
+    assert_eq!(worst.layer, 0);
+    assert!(worst.relative_error > 1e-2, "{worst:?}");
+}
+
+#[test]
+fn a_wrong_loss_gradient_fails_loudly() {
+    let mut network = network();
+
+    let check = check_gradients(&mut network, &sample(&[0.5, -0.3, 0.9], &[1.0, 0.0]), &DoubledMSE, 1e-6).unwrap();
+
+    assert!(!check.passed(1e-4));
+    let worst = check.worst.unwrap();
+    assert!((worst.analytic / worst.numeric - 2.0).abs() < 1e-4, "{worst:?}");
+}
+
+#[test]
+fn gradients_accumulated_before_dont_count() {
+    let mut network = network();
+    network.accumulate_gradients(&[sample(&[1.0, 1.0, 1.0], &[0.0, 1.0])], &MSE).unwrap();
+
+    let check = check_gradients(&mut network, &sample(&[0.5, -0.3, 0.9], &[1.0, 0.0]), &MSE, 1e-6).unwrap();
+
+    assert!(check.passed(1e-7), "{check:?}");
+    assert!(network.dense_layers().all(|layer| layer.weight_gradient().iter().all(|&gradient| gradient == 0.0)));
+}
+
+#[test]
+fn reports_the_worst_parameter() {
+    let mut network = network();
+
+    let check = check_gradients(&mut network, &sample(&[0.5, -0.3, 0.9], &[1.0, 0.0]), &DoubledMSE, 1e-6).unwrap();
+
+    let worst = check.worst.unwrap();
+    assert_eq!(worst.relative_error, check.max_relative_error);
+    let layer = network.dense_layer(worst.layer).unwrap();
+    match worst.parameter {
+        Parameter::Weight { input, output } => assert!(layer.get_weight(input, output).is_some()),
+        Parameter::Bias { output } => assert!(layer.get_bias(output).is_some()),
+    }
+}
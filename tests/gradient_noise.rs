@@ -0,0 +1,122 @@
+mod common;
+
+use nalgebra::DVector;
+use rand::{distr::Uniform, rngs::StdRng, RngCore, SeedableRng};
+
+use neural::{
+    activations::*,
+    dataset::Sample,
+    losses::MSE,
+    network::{layer::Layer, Network},
+    training::{gradient_noise::GradientNoise, Trainer, TrainingError},
+};
+
+use common::xor;
+
+fn xor_network() -> Network {
+    Network::random_with_rng(&[2, 4, 1], sigmoid!(), &Uniform::new(-1.0, 1.0).unwrap(), &mut StdRng::seed_from_u64(40)).unwrap()
+}
+
+/// Mean and variance of the values.
+fn moments(values: &[f32]) -> (f64, f64) {
+    let count = values.len() as f64;
+    let mean = values.iter().map(|&x| x as f64).sum::<f64>() / count;
+    let variance = values.iter().map(|&x| (x as f64 - mean).powi(2)).sum::<f64>() / count;
+    (mean, variance)
+}
+
+/// The weight changes of a layer that only ever sees zero inputs, so its weight gradients are exactly 0 and
+/// each weight moves by the rate times the noise of every step.
+fn noise_walk(noise: GradientNoise, steps: usize, seed: u64) -> Vec<f32> {
+    let mut network = Network::from_layers(vec![Layer::zeros(500, 4, identity!()).unwrap()]).unwrap();
+    let dataset = vec![Sample::new(DVector::zeros(500), DVector::zeros(4))];
+
+    Trainer::new(MSE, 1.0, steps)
+        .gradient_noise(noise)
+        .fit_with_rng(&mut network, &dataset, &[], &mut StdRng::seed_from_u64(seed))
+        .unwrap();
+
+    network.dense_layer(0).unwrap().weights().iter().copied().collect()
+}
+
+#[test]
+fn the_variance_anneals_with_the_step() {
+    let noise = GradientNoise::new(0.5, 0.55).unwrap();
+
+    assert_eq!(noise.variance(0), 0.5);
+    assert!((noise.variance(3) - 0.5 / 4f32.powf(0.55)).abs() < 1e-7);
+    assert!((noise.std_dev(99) - (0.5 / 100f32.powf(0.55)).sqrt()).abs() < 1e-7);
+    assert!((1..1000).all(|t| noise.variance(t) < noise.variance(t - 1)));
+
+    // Without annealing the variance stays at eta.
+    let constant = GradientNoise::new(0.5, 0.0).unwrap();
+    assert!((0..100).all(|t| constant.variance(t) == 0.5));
+}
+
+#[test]
+fn added_noise_has_the_requested_statistics() {
+    let mut network = Network::from_layers(vec![Layer::zeros(1000, 4, identity!()).unwrap()]).unwrap();
+    network.add_gradient_noise(0.5, &mut StdRng::seed_from_u64(41));
+    network.apply_gradients(1.0);
+
+    let (mean, variance) = moments(&network.get_params());
+    assert_eq!(network.get_params().len(), 4004);
+    assert!(mean.abs() < 0.025, "{mean}");
+    assert!((variance.sqrt() - 0.5).abs() < 0.015, "{}", variance.sqrt());
+}
+
+#[test]
+fn the_trainer_adds_the_annealed_variance_of_every_step() {
+    // The walk's variance is the sum of every step's variance.
+    for (noise, steps) in [(GradientNoise::new(0.5, 1.0).unwrap(), 10), (GradientNoise::new(0.02, 0.0).unwrap(), 25)] {
+        let expected: f64 = (0..steps).map(|t| noise.variance(t) as f64).sum();
+        let (mean, variance) = moments(&noise_walk(noise, steps, 42));
+
+        assert!(mean.abs() < 4.0 * (expected / 2000.0).sqrt(), "{noise:?}: mean {mean}");
+        assert!((variance / expected - 1.0).abs() < 0.12, "{noise:?}: variance {variance}, expected {expected}");
+    }
+}
+
+#[test]
+fn noise_is_reproducible_under_a_seed() {
+    let noise = GradientNoise::new(0.1, 0.55).unwrap();
+
+    assert_eq!(noise_walk(noise, 5, 43), noise_walk(noise, 5, 43));
+    assert_ne!(noise_walk(noise, 5, 43), noise_walk(noise, 5, 44));
+}
+
+#[test]
+fn zero_eta_is_no_noise_at_all() {
+    let train = |noise: Option<GradientNoise>| {
+        let mut network = xor_network();
+        let trainer = Trainer::new(MSE, 2.0, 50).batch_size(2).shuffle(true);
+        let mut trainer = match noise {
+            Some(noise) => trainer.gradient_noise(noise),
+            None => trainer,
+        };
+
+        let mut rng = StdRng::seed_from_u64(45);
+        trainer.fit_with_rng(&mut network, &xor(), &[], &mut rng).unwrap();
+        (network.get_params(), rng.next_u64())
+    };
+
+    // Bit-identical parameters, and the RNG was drawn from just as often.
+    assert_eq!(train(Some(GradientNoise::new(0.0, 0.55).unwrap())), train(None));
+
+    let mut network = xor_network();
+    let mut rng = StdRng::seed_from_u64(46);
+    network.add_gradient_noise(0.0, &mut rng);
+    network.apply_gradients(1.0);
+    assert_eq!(network.get_params(), xor_network().get_params());
+    assert_eq!(rng.next_u64(), StdRng::seed_from_u64(46).next_u64());
+}
+
+#[test]
+fn invalid_hyperparameters_are_rejected() {
+    for (eta, gamma) in [(-0.1, 0.5), (0.1, -0.5), (f32::NAN, 0.5), (0.1, f32::INFINITY)] {
+        assert!(matches!(GradientNoise::new(eta, gamma), Err(TrainingError::InvalidGradientNoise { .. })), "{eta} {gamma}");
+    }
+
+    let noise = GradientNoise::new(0.0, 0.0).unwrap();
+    assert_eq!((noise.eta(), noise.gamma()), (0.0, 0.0));
+}
@@ -0,0 +1,94 @@
+use nalgebra::{DMatrix, DVector};
+use rand::{distr::Uniform, rngs::StdRng, SeedableRng};
+
+use neural::{
+    activations::*,
+    dataset::Sample,
+    losses::MSE,
+    network::{layer::Layer, GradientHealth, GradientThresholds, LayerGradientNorm, Network},
+    training::Trainer,
+};
+
+/// `y = x1 + 2 x2 + 0.5`, which gives 7.5 for the sample (1, 3) with target 0.
+fn linear() -> (Network, Sample) {
+    let mut layer = Layer::zeros(2, 1, identity!()).unwrap();
+    layer.set_weights(DMatrix::from_row_slice(1, 2, &[1.0, 2.0])).unwrap();
+    layer.set_biases(DVector::from_vec(vec![0.5])).unwrap();
+
+    (Network::from_layers(vec![layer]).unwrap(), Sample::from_slices(&[1.0, 3.0], &[0.0]))
+}
+
+fn deep(activation_fn: Box<dyn ActivationFn>, weights: Uniform<f32>) -> Network {
+    Network::random_with_rng(&[2, 8, 8, 8, 8, 8, 8, 1], activation_fn, &weights, &mut StdRng::seed_from_u64(3)).unwrap()
+}
+
+fn inputs() -> Vec<Sample> {
+    [[0.5, -1.0], [1.0, 1.0], [-0.3, 0.8]].iter().map(|inputs| Sample::from_slices(inputs, &[0.5])).collect()
+}
+
+fn assert_close(actual: f32, expected: f32) {
+    assert!((actual - expected).abs() <= 1e-5 * expected, "{actual} vs {expected}");
+}
+
+#[test]
+fn norms_match_hand_computation_for_one_sample() {
+    let (mut network, sample) = linear();
+    assert_eq!(network.gradient_norms(), [LayerGradientNorm { weights: 0.0, biases: 0.0 }]);
+
+    // dL/dy = 2 * 7.5 = 15, so the weight gradient is 15 * (1, 3) and the bias gradient 15.
+    network.backpropagate(&[sample], &MSE).unwrap();
+    let norms = network.gradient_norms();
+
+    assert_close(norms[0].weights, 2250.0f32.sqrt());
+    assert_close(norms[0].biases, 15.0);
+    assert_close(norms[0].total(), 2475.0f32.sqrt());
+    assert_close(network.gradient_norm(), 2475.0f32.sqrt());
+
+    // Applying the step zeroes the gradients.
+    network.apply_gradients(-0.01);
+    assert_eq!(network.gradient_norm(), 0.0);
+}
+
+#[test]
+fn the_trainer_records_the_norm_of_every_epoch() {
+    let (mut network, sample) = linear();
+    let report = Trainer::new(MSE, 0.01, 1).fit(&mut network, &vec![sample], &[]).unwrap();
+
+    assert_close(report.history.gradient_norms()[0], 2475.0f32.sqrt());
+}
+
+#[test]
+fn a_saturated_deep_sigmoid_network_vanishes() {
+    // Saturated enough that the gradient shrinks layer by layer, not so much that the output's is 0 too.
+    let mut network = deep(sigmoid!(), Uniform::new(-8.0, 8.0).unwrap());
+    network.backpropagate(&inputs(), &MSE).unwrap();
+
+    let norms = network.gradient_norms();
+    assert!(norms[0].total() < 1e-7 && norms[6].total() > 100.0 * norms[0].total(), "{norms:?}");
+    assert!(matches!(
+        network.gradient_health(&GradientThresholds::default()),
+        GradientHealth::Vanishing { layer: 0, norm } if norm == norms[0].total()
+    ));
+}
+
+#[test]
+fn a_deep_linear_network_with_large_weights_explodes() {
+    let mut network = deep(identity!(), Uniform::new(1.0, 2.0).unwrap());
+    network.backpropagate(&inputs(), &MSE).unwrap();
+
+    let norm = network.gradient_norm();
+    assert!(norm > 1e3, "{norm}");
+    assert_eq!(network.gradient_health(&GradientThresholds::default()), GradientHealth::Exploding { norm });
+
+    // Exploding takes precedence, and the thresholds are the caller's.
+    let lenient = GradientThresholds { vanishing: 0.0, exploding: f32::INFINITY };
+    assert_eq!(network.gradient_health(&lenient), GradientHealth::Healthy);
+}
+
+#[test]
+fn a_well_initialized_network_is_healthy() {
+    let mut network = deep(tanh!(), Uniform::new(-0.7, 0.7).unwrap());
+    network.backpropagate(&inputs(), &MSE).unwrap();
+
+    assert_eq!(network.gradient_health(&GradientThresholds::default()), GradientHealth::Healthy);
+}
@@ -0,0 +1,65 @@
+use nalgebra::{DMatrix, DVector};
+
+use neural::{
+    activations::Sigmoid,
+    dataset::Sample,
+    losses::MSE,
+    network::{layer::Layer, Network},
+};
+
+/// `sigmoid(0.5 * x - 0.25)`.
+fn network() -> Network {
+    let mut layer = Layer::zeros(1, 1, Box::new(Sigmoid)).unwrap();
+    layer.set_weights(DMatrix::from_element(1, 1, 0.5)).unwrap();
+    layer.set_biases(DVector::from_element(1, -0.25)).unwrap();
+
+    Network::from_layers(vec![layer]).unwrap()
+}
+
+/// `d/dz (sigmoid(z) - y)^2` at `z = 0.5 * x - 0.25`, which is the bias gradient, and `x` times it the weight gradient.
+fn weighted_sum_gradient(x: f32, y: f32) -> f32 {
+    let a = 1.0 / (1.0 + (0.25 - 0.5 * x).exp());
+    2.0 * (a - y) * a * (1.0 - a)
+}
+
+fn assert_close(actual: f32, expected: f32) {
+    assert!((actual - expected).abs() < 1e-6, "{actual} != {expected}");
+}
+
+#[test]
+fn exposes_the_gradients_backpropagate_accumulated() {
+    let mut network = network();
+    network.backpropagate(&[Sample::from_slices(&[2.0], &[1.0]), Sample::from_slices(&[-1.0], &[0.0])], &MSE).unwrap();
+
+    let (first, second) = (weighted_sum_gradient(2.0, 1.0), weighted_sum_gradient(-1.0, 0.0));
+    let gradients: Vec<_> = network.gradients().collect();
+    assert_eq!(gradients.len(), 1);
+
+    let (index, weight_gradient, bias_gradient) = &gradients[0];
+    assert_eq!(*index, 0);
+    assert_close(weight_gradient[(0, 0)], 2.0 * first - second);
+    assert_close(bias_gradient[0], first + second);
+
+    let layer = network.layer(0).unwrap().as_dense().unwrap();
+    assert_eq!(layer.weight_gradient(), *weight_gradient);
+    assert_eq!(layer.bias_gradient(), *bias_gradient);
+}
+
+#[test]
+fn applying_or_zeroing_clears_them() {
+    let dataset = [Sample::from_slices(&[2.0], &[1.0])];
+
+    let mut network = network();
+    network.backpropagate(&dataset, &MSE).unwrap();
+    network.apply_gradients(-0.1);
+    assert!(network.gradients().all(|(_, weights, biases)| weights[(0, 0)] == 0.0 && biases[0] == 0.0));
+
+    let mut layer = network.layer(0).unwrap().as_dense().unwrap().clone();
+    layer.forward(DVector::from_element(1, 2.0)).unwrap();
+    let outputs = layer.predict(DVector::from_element(1, 2.0).as_view()).unwrap();
+    layer.backpropagation_step(outputs.as_view(), DVector::from_element(1, 1.0).as_view());
+    assert_ne!(layer.bias_gradient()[0], 0.0);
+
+    layer.zero_gradient();
+    assert_eq!((layer.weight_gradient()[(0, 0)], layer.bias_gradient()[0]), (0.0, 0.0));
+}
@@ -0,0 +1,139 @@
+mod common;
+
+use nalgebra::{DMatrix, DVector};
+use rand::{distr::Uniform, rngs::StdRng, SeedableRng};
+
+use neural::{
+    activations::*,
+    dataset::SequenceSample,
+    losses::MSE,
+    network::{
+        gru::{Gate, GruLayer},
+        layer::Layer,
+        recurrent::{RecurrentNetwork, SequenceLayer},
+    },
+};
+
+use common::assert_close;
+
+fn sequence(length: usize, size: usize, seed: usize) -> Vec<DVector<f64>> {
+    (0..length).map(|t| DVector::from_fn(size, |i, _| ((seed * 17 + t * size + i) as f64 * 0.61).sin())).collect()
+}
+
+type GateMut = fn(&mut GruLayer<f64>) -> &mut Gate<f64>;
+
+/// `sum(weights[t] * states[t])` over the states `predict` returns, whose gradient with respect to the states is `weights`.
+fn objective(layer: &GruLayer<f64>, inputs: &[DVector<f64>], weights: &[DVector<f64>]) -> f64 {
+    layer.predict(inputs).unwrap().iter().zip(weights).map(|(state, weights)| state.dot(weights)).sum()
+}
+
+/// Central differences of `f` with respect to every entry of `values`.
+fn numeric(values: &DMatrix<f64>, mut f: impl FnMut(DMatrix<f64>) -> f64) -> DMatrix<f64> {
+    let epsilon = 1e-6;
+
+    DMatrix::from_fn(values.nrows(), values.ncols(), |row, column| {
+        let (mut plus, mut minus) = (values.clone(), values.clone());
+        plus[(row, column)] += epsilon;
+        minus[(row, column)] -= epsilon;
+        (f(plus) - f(minus)) / (2.0 * epsilon)
+    })
+}
+
+#[test]
+fn follows_the_gru_equations() {
+    let mut layer = GruLayer::zeros(1, 1).unwrap();
+    layer.update_gate_mut().set_biases(DVector::from_element(1, 0.5)).unwrap();
+    layer.reset_gate_mut().set_recurrent_weights(DMatrix::from_element(1, 1, 1.0)).unwrap();
+    layer.candidate_gate_mut().set_input_weights(DMatrix::from_element(1, 1, 2.0)).unwrap();
+    layer.candidate_gate_mut().set_recurrent_weights(DMatrix::from_element(1, 1, -1.0)).unwrap();
+
+    let sigmoid = |x: f64| 1.0 / (1.0 + (-x).exp());
+    let inputs: Vec<_> = [0.3, -0.7].into_iter().map(|x| DVector::from_element(1, x)).collect();
+    let mut expected = Vec::new();
+    let mut state = 0.0;
+
+    for input in &inputs {
+        let (z, r) = (sigmoid(0.5), sigmoid(state));
+        let n = (2.0 * input[0] - r * state).tanh();
+        state = (1.0 - z) * n + z * state;
+        expected.push(state);
+    }
+
+    let states = layer.forward(&inputs).unwrap();
+    assert_close(&states.iter().map(|state| state[0]).collect::<Vec<_>>(), &expected, 1e-15);
+    assert_eq!(layer.predict(&inputs).unwrap(), states);
+}
+
+#[test]
+fn backpropagation_through_time_matches_numeric_gradients() {
+    let mut layer = GruLayer::random_with_rng(2, 3, &Uniform::new(-1.0, 1.0).unwrap(), &mut StdRng::seed_from_u64(1)).unwrap();
+    let inputs = sequence(5, 2, 0);
+    let weights = sequence(5, 3, 1);
+
+    layer.forward(&inputs).unwrap();
+    let input_gradients = layer.backpropagate_through_time(&weights, None);
+
+    for (t, gradient) in input_gradients.iter().enumerate() {
+        let expected = numeric(&DMatrix::from_column_slice(2, 1, inputs[t].as_slice()), |shifted| {
+            let mut inputs = inputs.clone();
+            inputs[t] = DVector::from_column_slice(shifted.as_slice());
+            objective(&layer, &inputs, &weights)
+        });
+        assert_close(gradient.as_slice(), expected.as_slice(), 1e-8);
+    }
+
+    for gate in [GruLayer::update_gate_mut, GruLayer::reset_gate_mut, GruLayer::candidate_gate_mut] as [GateMut; 3] {
+        let mut layer = layer.clone();
+        let current = gate(&mut layer).clone();
+
+        let expected = numeric(&current.input_weights().into_owned(), |shifted| {
+            let mut layer = layer.clone();
+            gate(&mut layer).set_input_weights(shifted).unwrap();
+            objective(&layer, &inputs, &weights)
+        });
+        assert_close(current.input_weight_gradient().into_owned().as_slice(), expected.as_slice(), 1e-8);
+
+        let expected = numeric(&current.recurrent_weights().into_owned(), |shifted| {
+            let mut layer = layer.clone();
+            gate(&mut layer).set_recurrent_weights(shifted).unwrap();
+            objective(&layer, &inputs, &weights)
+        });
+        assert_close(current.recurrent_weight_gradient().into_owned().as_slice(), expected.as_slice(), 1e-8);
+
+        let expected = numeric(&DMatrix::from_column_slice(3, 1, current.biases().as_slice()), |shifted| {
+            let mut layer = layer.clone();
+            gate(&mut layer).set_biases(DVector::from_column_slice(shifted.as_slice())).unwrap();
+            objective(&layer, &inputs, &weights)
+        });
+        assert_close(current.bias_gradient().as_slice(), expected.as_slice(), 1e-8);
+    }
+
+    // Applying the gradient zeroes it for the next sequence.
+    layer.apply_gradient(-0.1);
+    assert!(layer.update_gate().input_weight_gradient().iter().all(|&x| x == 0.0));
+}
+
+#[test]
+fn trains_in_a_recurrent_network() {
+    let mut rng = StdRng::seed_from_u64(2);
+    let uniform = Uniform::new(-0.5, 0.5).unwrap();
+    let gru = GruLayer::random_with_rng(1, 4, &uniform, &mut rng).unwrap();
+    let mut network = RecurrentNetwork::new(gru, Layer::random_with_rng(4, 1, identity!(), &uniform, &mut rng).unwrap()).unwrap();
+
+    // The output is the first input of the sequence, which the state has to hold on to.
+    let dataset: Vec<_> = [-0.5, -0.25, 0.25, 0.5]
+        .into_iter()
+        .map(|first| {
+            let inputs: Vec<_> = (0..5).map(|t| DVector::from_element(1, if t == 0 { first } else { 0.0 })).collect();
+            SequenceSample::new(inputs, vec![DVector::from_element(1, first); 5])
+        })
+        .collect();
+
+    let initial_loss = network.learn(&dataset, &MSE, 0.0).unwrap();
+    let mut loss = initial_loss;
+    for _ in 0..1000 {
+        loss = network.learn(&dataset, &MSE, 0.5).unwrap();
+    }
+
+    assert!(loss < initial_loss / 20.0, "{initial_loss} -> {loss}");
+}
@@ -0,0 +1,88 @@
+mod common;
+
+use std::time::Duration;
+
+use rand::{distr::Uniform, rngs::StdRng, SeedableRng};
+
+use neural::{
+    activations::*,
+    losses::MSE,
+    network::Network,
+    training::{
+        history::{TrainingHistory, HISTORY_COLUMNS},
+        Trainer,
+    },
+};
+
+use common::xor;
+
+fn history() -> TrainingHistory {
+    let mut history = TrainingHistory::new();
+    history.push(0.5, Some(0.75), 0.1, 2.0, Duration::from_micros(1500));
+    history.push_metric("accuracy", 0.25);
+    history.push(0.125, None, 0.05, 1.0, Duration::from_millis(3));
+
+    history
+}
+
+#[test]
+fn csv_rows_read_back_to_the_history() {
+    let mut csv = Vec::new();
+    history().to_csv(&mut csv).unwrap();
+    let csv = String::from_utf8(csv).unwrap();
+    let mut lines = csv.lines();
+
+    let header: Vec<_> = lines.next().unwrap().split(',').collect();
+    assert_eq!(header[..HISTORY_COLUMNS.len()], HISTORY_COLUMNS);
+    assert_eq!(header[HISTORY_COLUMNS.len()..], ["accuracy"]);
+
+    let rows: Vec<Vec<&str>> = lines.map(|line| line.split(',').collect()).collect();
+    assert_eq!(rows, [
+        ["0", "0.5", "0.75", "0.1", "1.5", "2", "0.25"],
+        ["1", "0.125", "", "0.05", "3", "1", ""],
+    ]);
+
+    let parse = |field: &str| field.parse::<f32>().unwrap();
+    let train_losses: Vec<_> = rows.iter().map(|row| parse(row[1])).collect();
+    assert_eq!(train_losses, history().train_losses());
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn json_has_nulls_for_missing_values() {
+    let json: serde_json::Value = serde_json::from_str(&history().to_json()).unwrap();
+
+    assert_eq!(json, serde_json::json!([
+        { "epoch": 0, "train_loss": 0.5, "validation_loss": 0.75, "learning_rate": 0.1, "duration_ms": 1.5, "gradient_norm": 2, "accuracy": 0.25 },
+        { "epoch": 1, "train_loss": 0.125, "validation_loss": null, "learning_rate": 0.05, "duration_ms": 3, "gradient_norm": 1, "accuracy": null },
+    ]));
+}
+
+#[test]
+fn training_records_every_epoch() {
+    let mut network = Network::random_with_rng(&[2, 3, 1], sigmoid!(), &Uniform::new(-1.0, 1.0).unwrap(), &mut StdRng::seed_from_u64(12)).unwrap();
+
+    let history = Trainer::new(MSE, 0.5, 15).fit(&mut network, &xor(), &xor()).unwrap().history;
+
+    assert_eq!(history.len(), 15);
+    assert!(history.train_losses().iter().all(|loss| loss.is_finite()));
+    assert!(history.validation_losses().iter().all(|loss| loss.is_some_and(f32::is_finite)));
+    assert!(history.gradient_norms().iter().all(|norm| norm.is_finite()));
+    assert_eq!(history.learning_rates(), [0.5; 15]);
+    assert_eq!(history.durations().len(), 15);
+    // Full-batch gradient descent at this rate only ever lowers the loss on its own training set.
+    assert!(history.train_losses().windows(2).all(|pair| pair[1] <= pair[0]));
+    assert_eq!(history.best_epoch(), Some(14));
+}
+
+#[test]
+fn the_best_epoch_prefers_validation_losses() {
+    assert_eq!(history().best_epoch(), Some(0));
+
+    let mut history = TrainingHistory::new();
+    history.push(0.5, None, 0.1, 1.0, Duration::ZERO);
+    history.push(f32::NAN, None, 0.1, 1.0, Duration::ZERO);
+    history.push(0.25, None, 0.1, 1.0, Duration::ZERO);
+    assert_eq!(history.best_epoch(), Some(2));
+    assert_eq!(TrainingHistory::new().best_epoch(), None);
+}
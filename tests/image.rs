@@ -0,0 +1,57 @@
+use nalgebra::DVector;
+
+use neural::dataset::{
+    image::{from_gray_buffer, samples_from_images, to_gray_buffer},
+    DatasetError,
+};
+
+#[test]
+fn buffers_round_trip_row_major() {
+    // Two rows of three pixels.
+    let pixels: Vec<u8> = vec![0, 51, 102, 153, 204, 255];
+    let values = from_gray_buffer(&pixels, 3, 2);
+
+    assert_eq!(values, DVector::from_vec(vec![0.0, 0.2, 0.4, 0.6, 0.8, 1.0]));
+    assert_eq!(to_gray_buffer(values.as_view(), 3, 2), pixels);
+}
+
+#[test]
+fn out_of_range_values_are_clamped() {
+    let values = DVector::from_vec(vec![-0.5, 1.5, f32::NAN, f32::INFINITY, 0.5]);
+
+    assert_eq!(to_gray_buffer(values.as_view(), 5, 1), [0, 255, 0, 255, 128]);
+}
+
+#[test]
+#[should_panic(expected = "a 2x2 image has 4 pixels")]
+fn a_buffer_of_the_wrong_size_panics() {
+    from_gray_buffer(&[0; 3], 2, 2);
+}
+
+#[test]
+fn samples_have_scaled_pixels_and_one_hot_labels() {
+    let images: [&[u8]; 2] = [&[0, 255], &[255, 0]];
+    let samples = samples_from_images(&images, &[2, 0], 3, 2, 1).unwrap();
+
+    assert_eq!(samples[0].inputs(), DVector::from_vec(vec![0.0, 1.0]));
+    assert_eq!(samples[0].expected_outputs(), DVector::from_vec(vec![0.0, 0.0, 1.0]));
+    assert_eq!(samples[1].expected_outputs(), DVector::from_vec(vec![1.0, 0.0, 0.0]));
+}
+
+#[test]
+fn bulk_errors_name_the_offending_image() {
+    let images: [&[u8]; 3] = [&[0; 4], &[0; 4], &[0; 5]];
+
+    assert!(matches!(
+        samples_from_images(&images, &[0, 1, 0], 2, 2, 2),
+        Err(DatasetError::ImageSizeMismatch { index: 2, width: 2, height: 2, pixels: 5 })
+    ));
+    assert!(matches!(
+        samples_from_images(&images[..2], &[0], 2, 2, 2),
+        Err(DatasetError::LabelCountMismatch { inputs: 2, labels: 1 })
+    ));
+    assert!(matches!(
+        samples_from_images(&images[..2], &[0, 2], 2, 2, 2),
+        Err(DatasetError::LabelOutOfRange { label: 2, num_classes: 2 })
+    ));
+}
@@ -0,0 +1,36 @@
+use nalgebra::{DMatrix, DVector};
+
+use neural::network::{Network, NetworkLoadError};
+
+#[test]
+fn imports_a_layer_without_bias_as_bias_free() {
+    let json = r#"{
+        "layer_sizes": [2, 2, 1],
+        "activations": ["relu", "identity"],
+        "layers": [
+            { "kernel": [[1.0, -1.0], [0.5, 2.0]] },
+            { "kernel": [[1.0], [0.5]], "bias": [0.25] }
+        ]
+    }"#;
+
+    let network = Network::import_dense_json(json).unwrap();
+
+    let first = network.layer(0).unwrap().as_dense().unwrap();
+    assert!(!first.has_bias());
+    assert_eq!(first.weights(), DMatrix::from_row_slice(2, 2, &[1.0, 0.5, -1.0, 2.0]));
+    assert_eq!(network.layer(1).unwrap().as_dense().unwrap().biases(), DVector::from_vec(vec![0.25]));
+
+    // relu([2 + 1, -2 + 4]) = [3, 2], then 3 + 1 + 0.25.
+    let outputs = network.predict(DVector::from_vec(vec![2.0, 2.0]).as_view()).unwrap();
+    assert_eq!(outputs, DVector::from_vec(vec![4.25]));
+}
+
+#[test]
+fn rejects_sizes_the_kernel_doesnt_have_before_allocating_them() {
+    // Either layer would need terabytes for its kernel.
+    for (layer_sizes, kernel) in [("[1099511627776, 1]", "[[0.5]]"), ("[1, 1099511627776]", "[[0.5]]")] {
+        let json = format!(r#"{{ "layer_sizes": {layer_sizes}, "activations": ["identity"], "layers": [{{ "kernel": {kernel} }}] }}"#);
+
+        assert!(matches!(Network::import_dense_json(&json), Err(NetworkLoadError::WeightShapeMismatch { layer: 0, .. })));
+    }
+}
@@ -0,0 +1,136 @@
+use rand::{distr::Uniform, rngs::StdRng, SeedableRng};
+
+use neural::{
+    activations::*,
+    network::{layer::LayerError, DynDistribution, Initializer, Network, NetworkBuilder, NetworkError, Normal},
+};
+
+/// The mean and standard deviation of a layer's weights and biases together.
+fn statistics(network: &Network, layer: usize) -> (f32, f32) {
+    let layer = network.layer(layer).unwrap().as_dense().unwrap();
+    let values: Vec<f32> = layer.weights().iter().chain(layer.biases().iter()).copied().collect();
+
+    let mean = values.iter().sum::<f32>() / values.len() as f32;
+    let variance = values.iter().map(|value| (value - mean).powi(2)).sum::<f32>() / values.len() as f32;
+    (mean, variance.sqrt())
+}
+
+fn assert_statistics(network: &Network, layer: usize, mean: f32, std_dev: f32) {
+    let (actual_mean, actual_std_dev) = statistics(network, layer);
+    assert!((actual_mean - mean).abs() < 0.05 * std_dev.max(1.0), "layer {layer}: mean {actual_mean}, expected {mean}");
+    assert!((actual_std_dev / std_dev - 1.0).abs() < 0.05, "layer {layer}: std dev {actual_std_dev}, expected {std_dev}");
+}
+
+#[test]
+fn every_layer_is_drawn_from_its_own_distribution() {
+    let tight = Uniform::new(-0.1, 0.1).unwrap();
+    let wide = Normal::new(3.0, 0.5);
+    let distributions: [&dyn DynDistribution<f32>; 2] = [&tight, &wide];
+
+    let network = Network::random_per_layer_with_rng(&[30, 100, 50], sigmoid!(), &distributions, &mut StdRng::seed_from_u64(4)).unwrap();
+
+    // A uniform distribution over a width of 0.2 has a standard deviation of 0.2 / sqrt(12).
+    assert_statistics(&network, 0, 0.0, 0.2 / 12f32.sqrt());
+    assert_statistics(&network, 1, 3.0, 0.5);
+}
+
+#[test]
+fn the_builder_takes_an_initializer_per_layer() {
+    let network = NetworkBuilder::new()
+        .input(30)
+        .layer_with_init(100, tanh!(), Initializer::Normal(-1.0, 0.25))
+        .layer_with_init(50, sigmoid!(), Initializer::Uniform(0.0, 2.0))
+        .build_with_rng(&mut StdRng::seed_from_u64(5))
+        .unwrap();
+
+    assert_statistics(&network, 0, -1.0, 0.25);
+    assert_statistics(&network, 1, 1.0, 2.0 / 12f32.sqrt());
+}
+
+#[test]
+fn needs_one_distribution_per_layer() {
+    let uniform = Uniform::new(-1.0, 1.0).unwrap();
+
+    assert!(matches!(
+        Network::random_per_layer(&[2, 3, 4, 1], sigmoid!(), &[&uniform, &uniform]),
+        Err(NetworkError::DistributionCountMismatch { distributions: 2, layers: 3 })
+    ));
+}
+
+type Variance = fn(f32, f32) -> f32;
+
+/// The mean and variance of a layer's weights alone.
+fn weight_statistics(network: &Network, layer: usize) -> (f32, f32) {
+    let weights = network.dense_layer(layer).unwrap().weights();
+    let mean = weights.mean();
+    (mean, weights.iter().map(|weight| (weight - mean).powi(2)).sum::<f32>() / weights.len() as f32)
+}
+
+#[test]
+fn fan_based_schemes_have_the_variance_of_their_formula_in_every_layer() {
+    let sizes = [200, 300, 100];
+    // The expected variance given the fan-in and fan-out.
+    let schemes: [(Initializer, Variance); 5] = [
+        (Initializer::XavierUniform, |fan_in, fan_out| 2.0 / (fan_in + fan_out)),
+        (Initializer::XavierNormal, |fan_in, fan_out| 2.0 / (fan_in + fan_out)),
+        (Initializer::HeUniform, |fan_in, _| 2.0 / fan_in),
+        (Initializer::HeNormal, |fan_in, _| 2.0 / fan_in),
+        (Initializer::LecunNormal, |fan_in, _| 1.0 / fan_in),
+    ];
+
+    for (initializer, variance) in schemes {
+        let network = Network::with_init(&sizes, tanh!(), initializer, &mut StdRng::seed_from_u64(7)).unwrap();
+
+        for (layer, pair) in sizes.windows(2).enumerate() {
+            let expected = variance(pair[0] as f32, pair[1] as f32);
+            let (mean, actual) = weight_statistics(&network, layer);
+
+            assert!(mean.abs() < 0.02 * expected.sqrt(), "{initializer:?} layer {layer}: mean {mean}");
+            assert!((actual / expected - 1.0).abs() < 0.03, "{initializer:?} layer {layer}: variance {actual}, expected {expected}");
+            assert!(network.dense_layer(layer).unwrap().biases().iter().all(|&bias| bias == 0.0));
+        }
+    }
+
+    // The uniform schemes stay within `±sqrt(3 * variance)`.
+    let network = Network::with_init(&sizes, tanh!(), Initializer::HeUniform, &mut StdRng::seed_from_u64(7)).unwrap();
+    assert!(network.dense_layer(0).unwrap().weights().iter().all(|weight| weight.abs() <= (6.0f32 / 200.0).sqrt()));
+}
+
+#[test]
+fn xavier_keeps_a_deep_tanh_network_out_of_saturation() {
+    let sizes = [2, 256, 256, 256, 256, 256, 1];
+    let xavier = Network::with_init(&sizes, tanh!(), Initializer::XavierUniform, &mut StdRng::seed_from_u64(1)).unwrap();
+    let legacy = Network::random_with_rng(&sizes, tanh!(), &Uniform::new(-0.5, 0.5).unwrap(), &mut StdRng::seed_from_u64(1)).unwrap();
+
+    // The fraction of hidden activations within 0.01 of ±1, and their root mean square, per hidden layer.
+    let activations = |network: &Network| {
+        let (_, cache) = network.forward_cached(nalgebra::DVector::from_vec(vec![0.3, -0.6]).as_view()).unwrap();
+        cache.activations()[1..6]
+            .iter()
+            .map(|activations| {
+                let saturated = activations.iter().filter(|x| x.abs() > 0.99).count() as f32 / activations.len() as f32;
+                (saturated, activations.norm() / (activations.len() as f32).sqrt())
+            })
+            .collect::<Vec<_>>()
+    };
+
+    // Xavier's activations keep their scale from layer to layer, the legacy ones grow until half the units saturate.
+    let xavier_activations = activations(&xavier);
+    assert!(xavier_activations.iter().all(|&(saturated, _)| saturated == 0.0), "{xavier_activations:?}");
+    let scales = xavier_activations.iter().map(|&(_, scale)| scale);
+    assert!(scales.clone().fold(0.0, f32::max) < 1.5 * scales.fold(f32::INFINITY, f32::min), "{xavier_activations:?}");
+
+    let legacy_activations = activations(&legacy);
+    assert!(legacy_activations[0].0 < 0.01 && legacy_activations[4].0 > 0.4, "{legacy_activations:?}");
+    assert!(legacy_activations[4].1 > 2.0 * legacy_activations[0].1, "{legacy_activations:?}");
+}
+
+#[test]
+fn invalid_distributions_are_rejected() {
+    for initializer in [Initializer::Uniform(1.0, 0.0), Initializer::Normal(0.0, -1.0), Initializer::Normal(f32::NAN, 1.0)] {
+        assert!(matches!(
+            Network::with_init(&[2, 3], tanh!(), initializer, &mut StdRng::seed_from_u64(0)),
+            Err(NetworkError::LayerError(LayerError::InvalidInitializer(_)))
+        ));
+    }
+}
@@ -0,0 +1,76 @@
+use nalgebra::DVector;
+use rand::{distr::Uniform, rngs::StdRng, SeedableRng};
+
+use neural::{
+    activations::*,
+    dataset::Sample,
+    losses::MSE,
+    network::{Network, NetworkError},
+};
+
+fn network(sizes: &[usize]) -> Network {
+    Network::random_with_rng(sizes, sigmoid!(), &Uniform::new(-1.0, 1.0).unwrap(), &mut StdRng::seed_from_u64(1)).unwrap()
+}
+
+/// Checks every shape query against `sizes`, and that they agree with each other.
+fn assert_shape(network: &Network, sizes: &[usize]) {
+    assert_eq!(network.layer_sizes(), sizes);
+    assert_eq!(network.input_size(), sizes[0]);
+    assert_eq!(network.output_size(), *sizes.last().unwrap());
+    assert_eq!(network.num_layers(), sizes.len() - 1);
+
+    let parameters: usize = sizes.windows(2).map(|pair| (pair[0] + 1) * pair[1]).sum();
+    assert_eq!(network.num_parameters(), parameters);
+    assert_eq!(network.get_params().len(), parameters);
+
+    assert_eq!(network.predict(DVector::zeros(sizes[0]).as_view()).unwrap().len(), *sizes.last().unwrap());
+}
+
+#[test]
+fn reports_the_shape_it_was_built_with() {
+    for sizes in [&[2, 50, 1][..], &[1, 1], &[4, 3, 3, 2], &[10, 20, 30, 20, 10]] {
+        assert_shape(&network(sizes), sizes);
+    }
+}
+
+#[test]
+fn stays_consistent_through_every_architecture_change() {
+    let uniform = Uniform::new(-1.0, 1.0).unwrap();
+    let mut rng = StdRng::seed_from_u64(2);
+    let mut network = network(&[3, 4, 2]);
+
+    network.insert_layer(1, 6, identity!()).unwrap();
+    assert_shape(&network, &[3, 4, 6, 2]);
+
+    network.widen_layer_with_rng(0, 7, &uniform, &mut rng).unwrap();
+    assert_shape(&network, &[3, 7, 6, 2]);
+
+    network.replace_output_layer_with_rng(5, sigmoid!(), &uniform, &mut rng).unwrap();
+    assert_shape(&network, &[3, 7, 6, 5]);
+
+    network.remove_layer(1).unwrap();
+    assert_shape(&network, &[3, 7, 5]);
+
+    network.truncate_to(1).unwrap();
+    assert_shape(&network, &[3, 7]);
+
+    // A failed change leaves the shape as it was.
+    assert!(network.widen_layer_with_rng(0, 9, &uniform, &mut rng).is_err());
+    assert_shape(&network, &[3, 7]);
+}
+
+#[test]
+fn mismatched_samples_are_reported_with_the_network_shape() {
+    let mut network = network(&[3, 4, 2]);
+    let dataset = [
+        Sample::new(DVector::zeros(3), DVector::zeros(2)),
+        Sample::new(DVector::zeros(2), DVector::zeros(2)),
+    ];
+
+    let error = network.learn(&dataset, &MSE, 0.1).unwrap_err();
+    assert!(matches!(error, NetworkError::SampleSizeMismatch { index: 1, inputs: 2, outputs: 2, network_inputs: 3, network_outputs: 2 }));
+    assert_eq!(
+        error.to_string(),
+        "sample 1 has 2 inputs and 2 expected outputs, but the network takes 3 inputs and gives 2 outputs"
+    );
+}
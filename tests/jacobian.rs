@@ -0,0 +1,71 @@
+mod common;
+
+use nalgebra::{DMatrix, DVector};
+use rand::{distr::Uniform, rngs::StdRng, SeedableRng};
+
+use neural::{
+    activations::*,
+    losses::{LossFn, MSE},
+    network::Network,
+};
+
+use common::{assert_close, sample};
+
+#[test]
+fn a_linear_network_has_the_product_of_its_weights() {
+    let network = Network::random_with_rng(&[3, 5, 2], identity!(), &Uniform::new(-1.0, 1.0).unwrap(), &mut StdRng::seed_from_u64(1)).unwrap();
+    let weights = |layer| network.layer(layer).unwrap().as_dense().unwrap().weights().clone_owned();
+
+    let jacobian = network.jacobian(DVector::from_vec(vec![0.3, -2.0, 7.0]).as_view()).unwrap();
+    assert_eq!(jacobian, weights(1) * weights(0));
+}
+
+fn nonlinear() -> Network<f64> {
+    Network::random_with_rng(&[3, 6, 4, 2], tanh!(), &Uniform::new(-1.0, 1.0).unwrap(), &mut StdRng::seed_from_u64(2)).unwrap()
+}
+
+#[test]
+fn a_nonlinear_network_matches_central_differences() {
+    let network = nonlinear();
+    let input = DVector::from_vec(vec![0.4, -0.7, 0.1]);
+    let jacobian = network.jacobian(input.as_view()).unwrap();
+
+    let epsilon = 1e-6;
+    let numeric = DMatrix::from_fn(2, 3, |output, column| {
+        let mut shifted = input.clone();
+        shifted[column] += epsilon;
+        let plus = network.predict(shifted.as_view()).unwrap()[output];
+        shifted[column] -= 2.0 * epsilon;
+        let minus = network.predict(shifted.as_view()).unwrap()[output];
+        (plus - minus) / (2.0 * epsilon)
+    });
+
+    assert_close(jacobian.as_slice(), numeric.as_slice(), 1e-8);
+}
+
+#[test]
+fn the_input_gradient_is_the_transposed_jacobian_times_the_loss_gradient() {
+    let network = nonlinear();
+    let (input, expected) = (DVector::from_vec(vec![0.4, -0.7, 0.1]), DVector::from_vec(vec![0.5, -0.5]));
+
+    let outputs = network.predict(input.as_view()).unwrap();
+    let loss_gradient = MSE.partial_gradient(outputs.as_view(), expected.as_view()).unwrap();
+    let via_jacobian = network.jacobian(input.as_view()).unwrap().transpose() * loss_gradient;
+
+    let input_gradient = network.input_gradient(input.as_view(), &MSE, expected.as_view()).unwrap();
+    assert_close(input_gradient.as_slice(), via_jacobian.as_slice(), 1e-12);
+}
+
+#[test]
+fn the_accumulated_gradients_are_left_alone() {
+    let mut network = nonlinear();
+    network.backpropagate(&[sample(&[0.2, 0.2, -0.5], &[1.0, 0.0])], &MSE).unwrap();
+    let before: Vec<_> = network.gradients().map(|(_, weights, biases)| (weights.clone_owned(), biases.clone_owned())).collect();
+
+    let input = DVector::from_vec(vec![0.4, -0.7, 0.1]);
+    network.jacobian(input.as_view()).unwrap();
+    network.input_gradient(input.as_view(), &MSE, DVector::from_vec(vec![0.5, -0.5]).as_view()).unwrap();
+
+    let after: Vec<_> = network.gradients().map(|(_, weights, biases)| (weights.clone_owned(), biases.clone_owned())).collect();
+    assert_eq!(after, before);
+}
@@ -0,0 +1,72 @@
+use neural::{
+    dataset::{self, Sample},
+    json::JsonError,
+    network::{Network, NetworkLoadError},
+};
+
+/// Samples with values that don't survive a lossy round trip, a weight and tags.
+fn samples() -> Vec<Sample> {
+    vec![
+        Sample::from_slices(&[0.1, -0.0, 1e-30], &[f32::MAX]).with_tag("first"),
+        Sample::from_slices(&[1.0 / 3.0, f32::MIN_POSITIVE, -7.5], &[f32::EPSILON]).with_weight(2.5),
+        Sample::from_slices(&[], &[0.0, 1.0]).with_tag("no inputs"),
+    ]
+}
+
+fn assert_same(loaded: &[Sample], saved: &[Sample]) {
+    assert_eq!(loaded.len(), saved.len());
+
+    for (loaded, saved) in loaded.iter().zip(saved) {
+        let bits = |values: &[f32]| values.iter().map(|value| value.to_bits()).collect::<Vec<_>>();
+        assert_eq!(bits(loaded.inputs().as_slice()), bits(saved.inputs().as_slice()));
+        assert_eq!(bits(loaded.expected_outputs().as_slice()), bits(saved.expected_outputs().as_slice()));
+        assert_eq!(loaded.weight(), saved.weight());
+        assert_eq!(loaded.tag(), saved.tag());
+    }
+}
+
+#[test]
+fn samples_round_trip_through_a_json_file() {
+    let path = std::env::temp_dir().join(format!("neural-samples-{}.json", std::process::id()));
+    let saved = samples();
+
+    dataset::save_json(&path, &saved).unwrap();
+    let loaded = dataset::load_json(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    assert_same(&loaded, &saved);
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn samples_round_trip_through_serde() {
+    let saved = samples();
+    let json = serde_json::to_string(&saved).unwrap();
+
+    assert!(json.starts_with(r#"[{"inputs":[0.1,-0.0,1e-30],"expected_outputs":[3.4028235e+38],"weight":1.0,"tag":"first"}"#), "{json}");
+    assert_same(&serde_json::from_str::<Vec<Sample>>(&json).unwrap(), &saved);
+}
+
+#[test]
+fn rejects_sizes_the_weights_dont_have_before_allocating_them() {
+    // A layer claiming 2^40 inputs would need terabytes for its weights.
+    let json = r#"{
+        "format": "neural",
+        "version": 1,
+        "layers": [
+            { "input_size": 1099511627776, "output_size": 1, "activation": "identity", "weights": [[0.5]], "biases": [0.0] }
+        ]
+    }"#;
+
+    assert!(matches!(
+        Network::from_json(json),
+        Err(NetworkLoadError::WeightShapeMismatch { layer: 0, input_size: 1099511627776, output_size: 1 })
+    ));
+}
+
+#[test]
+fn rejects_deeply_nested_input_without_overflowing_the_stack() {
+    let json = format!("{{\"format\":\"neural\",\"version\":1,\"layers\":{}{}}}", "[".repeat(100_000), "]".repeat(100_000));
+
+    assert!(matches!(Network::from_json(&json), Err(NetworkLoadError::Json(JsonError::Syntax { .. }))));
+}
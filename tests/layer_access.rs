@@ -0,0 +1,85 @@
+mod common;
+
+use nalgebra::{DMatrix, DVector};
+use rand::{distr::Uniform, rngs::StdRng, SeedableRng};
+
+use neural::{
+    activations::*,
+    losses::MSE,
+    network::{layer::{Layer, LayerError}, Network},
+};
+
+use common::xor;
+
+fn network() -> Network {
+    Network::random_with_rng(&[2, 3, 1], sigmoid!(), &Uniform::new(-1.0, 1.0).unwrap(), &mut StdRng::seed_from_u64(1)).unwrap()
+}
+
+#[test]
+fn layers_are_reachable_by_index_and_in_order() {
+    let network = network();
+
+    assert_eq!(network.layers().len(), 2);
+    assert_eq!(network.dense_layers().count(), 2);
+    assert_eq!(network.layer(0).unwrap().output_size(), 3);
+    assert_eq!(network.dense_layer(1).unwrap().input_size(), 3);
+    assert!(network.layer(2).is_none());
+    assert!(network.dense_layer(2).is_none());
+
+    let weights: Vec<_> = network.dense_layers().map(|layer| layer.weights().shape()).collect();
+    assert_eq!(weights, [(3, 2), (1, 3)]);
+}
+
+#[test]
+fn views_reflect_training_updates() {
+    let mut network = network();
+    let before: Vec<(DMatrix<f32>, DVector<f32>)> = network.dense_layers().map(|layer| (layer.weights().into_owned(), layer.biases().into_owned())).collect();
+
+    network.backpropagate(&xor(), &MSE).unwrap();
+    let gradients: Vec<_> = network.dense_layers().map(|layer| (layer.weight_gradient().into_owned(), layer.bias_gradient().into_owned())).collect();
+    network.apply_gradients(-0.5);
+
+    for ((layer, (weights, biases)), (weight_gradient, bias_gradient)) in network.dense_layers().zip(&before).zip(&gradients) {
+        assert!((layer.weights() - (weights - weight_gradient * 0.5)).amax() < 1e-6);
+        assert!((layer.biases() - (biases - bias_gradient * 0.5)).amax() < 1e-6);
+        assert_ne!(layer.weights(), *weights);
+    }
+}
+
+#[test]
+fn bulk_setters_replace_whole_parameters() {
+    let mut network = network();
+
+    let layer = network.dense_layer_mut(0).unwrap();
+    layer.set_weights(DMatrix::from_row_slice(3, 2, &[1.0, 0.0, 0.0, 1.0, 1.0, 1.0])).unwrap();
+    for layer in network.dense_layers_mut() {
+        layer.set_biases(DVector::from_element(layer.output_size(), 0.5)).unwrap();
+    }
+
+    let layer = network.dense_layer(0).unwrap();
+    assert_eq!(layer.weights(), DMatrix::from_row_slice(3, 2, &[1.0, 0.0, 0.0, 1.0, 1.0, 1.0]));
+    assert_eq!(network.get_params()[6..9], [0.5, 0.5, 0.5]);
+    assert_eq!(network.dense_layer(1).unwrap().biases()[0], 0.5);
+
+    let layer = network.layer_mut(1).unwrap().as_dense_mut().unwrap();
+    layer.set_weights(DMatrix::zeros(1, 3)).unwrap();
+    assert_eq!(network.predict(DVector::from_vec(vec![3.0, -2.0]).as_view()).unwrap()[0], 1.0 / (1.0 + (-0.5f32).exp()));
+}
+
+#[test]
+fn mismatched_setters_fail_without_changing_anything() {
+    let mut network = network();
+    let params = network.get_params();
+    let layer = network.dense_layer_mut(0).unwrap();
+
+    assert!(matches!(
+        layer.set_weights(DMatrix::zeros(2, 3)),
+        Err(LayerError::ParameterShapeMismatch { layer_input_size: 2, layer_output_size: 3, given_input_size: 3, given_output_size: 2 })
+    ));
+    assert!(matches!(layer.set_biases(DVector::zeros(4)), Err(LayerError::BiasSizeMismatch { layer_output_size: 3, given_size: 4 })));
+    assert_eq!(network.get_params(), params);
+
+    let mut unbiased = Layer::<f32>::zeros(2, 3, identity!()).unwrap().without_bias();
+    assert!(matches!(unbiased.set_biases(DVector::from_element(3, 1.0)), Err(LayerError::NoBiases)));
+    assert_eq!(unbiased.biases(), DVector::zeros(3));
+}
@@ -0,0 +1,108 @@
+mod allocations;
+mod common;
+
+use nalgebra::DVector;
+use rand::{distr::Uniform, rngs::StdRng, SeedableRng};
+
+use neural::{
+    activations::*,
+    dataset::Sample,
+    losses::{BCEWithLogits, MSE},
+    network::{layer::Layer, MaxoutLayer, Network, NetworkError},
+};
+
+use allocations::{allocations, Counting};
+
+#[global_allocator]
+static ALLOCATOR: Counting = Counting;
+
+fn network(layer_sizes: &[usize]) -> Network {
+    Network::random_with_rng(layer_sizes, tanh!(), &Uniform::new(-1.0, 1.0).unwrap(), &mut StdRng::seed_from_u64(4)).unwrap()
+}
+
+/// Steps one copy with `learn_sample` and the other with `learn` for every sample, asserting they stay equal.
+fn assert_steps_like_learn(mut network: Network, samples: &[Sample]) {
+    let mut reference = network.clone();
+    let mut scratch = network.scratch();
+
+    for sample in samples {
+        let loss = network.learn_sample_with_scratch(sample.inputs(), sample.expected_outputs(), &MSE, 0.3, &mut scratch).unwrap();
+        let reference_loss = reference.learn(std::slice::from_ref(sample), &MSE, 0.3).unwrap();
+
+        assert_eq!(loss, reference_loss);
+        assert_eq!(network.get_params(), reference.get_params());
+    }
+}
+
+#[test]
+fn steps_exactly_like_learn_on_one_sample() {
+    let mut network = network(&[3, 5, 4, 2]);
+    network.set_layer_lr_scale(0, 0.5).unwrap();
+    let samples: Vec<_> = (0..5)
+        .map(|i| Sample::from_slices(&[i as f32 * 0.2, -0.4, 0.9 - i as f32 * 0.3], &[0.5, -0.25]))
+        .collect();
+
+    assert_steps_like_learn(network, &samples);
+}
+
+#[test]
+fn steps_like_learn_with_frozen_and_bias_free_layers() {
+    let mut rng = StdRng::seed_from_u64(9);
+    let uniform = Uniform::new(-1.0, 1.0).unwrap();
+    let mut frozen = Layer::random_with_rng(2, 3, sigmoid!(), &uniform, &mut rng).unwrap();
+    frozen.set_trainable(false);
+    let bias_free = Layer::random_with_rng(3, 1, sigmoid!(), &uniform, &mut rng).unwrap().without_bias();
+
+    assert_steps_like_learn(Network::from_layers(vec![frozen, bias_free]).unwrap(), &common::xor());
+}
+
+#[test]
+fn takes_the_batched_path_for_other_layers() {
+    let mut rng = StdRng::seed_from_u64(9);
+    let uniform = Uniform::new(-1.0, 1.0).unwrap();
+    let maxout = MaxoutLayer::random_with_rng(2, 3, 2, &uniform, &mut rng).unwrap();
+    let output = Layer::random_with_rng(3, 1, sigmoid!(), &uniform, &mut rng).unwrap();
+
+    assert_steps_like_learn(Network::from_network_layers(vec![Box::new(maxout), Box::new(output)]).unwrap(), &common::xor());
+}
+
+#[test]
+fn only_the_loss_gradient_allocates() {
+    let samples = common::xor();
+
+    for layer_sizes in [&[2, 1][..], &[2, 8, 8, 8, 1]] {
+        let mut network = network(layer_sizes);
+        let mut scratch = network.scratch();
+        let (inputs, expected_outputs) = (samples[1].inputs(), samples[1].expected_outputs());
+        network.learn_sample_with_scratch(inputs, expected_outputs, &MSE, 0.1, &mut scratch).unwrap();
+
+        let before = allocations();
+        network.learn_sample_with_scratch(inputs, expected_outputs, &MSE, 0.1, &mut scratch).unwrap();
+        assert_eq!(allocations() - before, 1, "{layer_sizes:?}");
+    }
+}
+
+#[test]
+fn rejects_mismatched_samples_and_buffers() {
+    let mut network = network(&[2, 3, 1]);
+    let mut scratch = network.scratch();
+    let before = network.get_params();
+
+    let inputs = DVector::from_vec(vec![0.5, 0.5, 0.5]);
+    let expected_outputs = DVector::from_vec(vec![1.0]);
+    assert!(matches!(
+        network.learn_sample(inputs.as_view(), expected_outputs.as_view(), &MSE, 0.1),
+        Err(NetworkError::SampleSizeMismatch { inputs: 3, .. })
+    ));
+
+    let mut other = self::network(&[2, 4, 1]).scratch();
+    let inputs = DVector::from_vec(vec![0.5, 0.5]);
+    assert!(matches!(
+        network.learn_sample_with_scratch(inputs.as_view(), expected_outputs.as_view(), &MSE, 0.1, &mut other),
+        Err(NetworkError::ScratchMismatch)
+    ));
+
+    // The loss needs an identity output layer, so it's rejected before anything changes.
+    assert!(network.learn_sample_with_scratch(inputs.as_view(), expected_outputs.as_view(), &BCEWithLogits, 0.1, &mut scratch).is_err());
+    assert_eq!(network.get_params(), before);
+}
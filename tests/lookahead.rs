@@ -0,0 +1,104 @@
+mod common;
+
+use rand::{distr::Uniform, rngs::StdRng, SeedableRng};
+
+use neural::{
+    activations::*,
+    dataset::Sample,
+    losses::MSE,
+    network::{Lookahead, Network, NetworkError, Optimizer, RProp, Sgd},
+    training::Trainer,
+};
+
+fn network() -> Network {
+    Network::random_with_rng(&[2, 4, 1], sigmoid!(), &Uniform::new(-1.0, 1.0).unwrap(), &mut StdRng::seed_from_u64(3)).unwrap()
+}
+
+/// Moves every parameter by a fixed amount, whatever the gradient.
+struct Shift(f32);
+
+impl Optimizer for Shift {
+    fn step(&mut self, network: &mut Network, _rate: f32, _total_weight: f32) -> Result<(), NetworkError> {
+        let params: Vec<f32> = network.get_params().iter().map(|param| param + self.0).collect();
+        network.zero_gradients();
+        network.set_params(&params)
+    }
+}
+
+#[test]
+fn synchronizes_every_k_steps() {
+    let mut network = Network::random_with_rng(&[1, 1], identity!(), &Uniform::new(0.0, 1.0).unwrap(), &mut StdRng::seed_from_u64(1)).unwrap();
+    network.set_params(&[1.0, 0.0]).unwrap();
+    let mut lookahead = Lookahead::new(Shift(0.5), 3, 0.25).unwrap();
+    let dataset = [Sample::from_slices(&[0.0], &[0.0])];
+
+    // The fast weight goes 1.0, 1.5, 2.0, 2.5, and the third step moves the slow one to 1.0 + 0.25 * 1.5 = 1.375.
+    // Three more steps take the fast weight to 2.875 and the slow one to 1.375 + 0.25 * 1.5 = 1.75.
+    for expected in [1.5, 2.0, 1.375, 1.875, 2.375, 1.75] {
+        network.learn_with_optimizer(&dataset, &MSE, 0.1, &mut lookahead).unwrap();
+        assert_eq!(network.get_params()[0], expected);
+    }
+
+    assert_eq!(lookahead.slow_params()[0], 1.75);
+    assert_eq!(lookahead.steps_since_sync(), 0);
+}
+
+#[test]
+fn degenerates_to_sgd_with_k_1_and_alpha_1() {
+    let dataset = common::xor();
+    let (mut plain, mut wrapped) = (network(), network());
+    let mut lookahead = Lookahead::new(Sgd, 1, 1.0).unwrap();
+
+    for _ in 0..20 {
+        let plain_loss = plain.learn(&dataset, &MSE, 0.5).unwrap();
+        let wrapped_loss = wrapped.learn_with_optimizer(&dataset, &MSE, 0.5, &mut lookahead).unwrap();
+        assert_eq!(plain_loss, wrapped_loss);
+    }
+
+    assert_eq!(plain.get_params(), wrapped.get_params());
+}
+
+#[test]
+fn trains_as_the_trainers_optimizer() {
+    let dataset = common::xor();
+    let mut trained = network();
+    Trainer::new(MSE, 0.5, 4).batch_size(2).optimizer(Lookahead::new(Sgd, 3, 0.5).unwrap()).fit(&mut trained, &dataset, &[]).unwrap();
+
+    let mut expected = network();
+    let mut lookahead = Lookahead::new(Sgd, 3, 0.5).unwrap();
+    for _ in 0..4 {
+        for batch in dataset.chunks(2) {
+            expected.learn_with_optimizer(batch, &MSE, 0.5, &mut lookahead).unwrap();
+        }
+    }
+
+    assert_eq!(trained.get_params(), expected.get_params());
+
+    // Other optimizers compose the same way.
+    let mut rprop = network();
+    let lookahead = Lookahead::new(RProp::default(), 2, 0.5).unwrap();
+    let report = Trainer::new(MSE, 0.0, 50).optimizer(lookahead).fit(&mut rprop, &dataset, &[]).unwrap();
+    assert!(report.history.train_losses()[49] < report.history.train_losses()[0]);
+}
+
+#[test]
+fn rejects_invalid_settings_and_another_network() {
+    assert!(matches!(Lookahead::new(Sgd, 0, 0.5), Err(NetworkError::InvalidLookahead { k: 0, .. })));
+    assert!(matches!(Lookahead::new(Sgd, 5, 0.0), Err(NetworkError::InvalidLookahead { .. })));
+    assert!(matches!(Lookahead::new(Sgd, 5, 1.5), Err(NetworkError::InvalidLookahead { .. })));
+
+    let dataset = common::xor();
+    let mut lookahead = Lookahead::new(Sgd, 5, 0.5).unwrap();
+    network().learn_with_optimizer(&dataset, &MSE, 0.5, &mut lookahead).unwrap();
+
+    let mut other = Network::random_with_rng(&[2, 3, 1], sigmoid!(), &Uniform::new(-1.0, 1.0).unwrap(), &mut StdRng::seed_from_u64(3)).unwrap();
+    let before = other.get_params();
+    assert!(matches!(
+        other.learn_with_optimizer(&dataset, &MSE, 0.5, &mut lookahead),
+        Err(NetworkError::ParameterCountMismatch { expected: 17, given: 13 })
+    ));
+    assert_eq!(other.get_params(), before);
+
+    lookahead.reset();
+    other.learn_with_optimizer(&dataset, &MSE, 0.5, &mut lookahead).unwrap();
+}
@@ -0,0 +1,252 @@
+use nalgebra::{DVector, DVectorView};
+use rand::{distr::Uniform, rngs::StdRng, SeedableRng};
+
+use neural::{
+    activations::*,
+    dataset::Sample,
+    network::{layer::Layer, Network, NetworkLayer, Softmax},
+};
+use neural::losses::{
+    verify_gradient,
+    BCEWithLogits,
+    CategoricalCrossEntropy,
+    CosineLoss,
+    FocalLoss,
+    GradientMismatch,
+    LogCosh,
+    LossFn,
+    LossFnError,
+    MultiHeadLoss,
+    PoissonLoss,
+    QuantileLoss,
+    MSE,
+};
+
+fn assert_gradient(name: &str, loss: &dyn LossFn, output: &[f32], expected: &[f32]) {
+    let (output, expected) = (DVector::from_column_slice(output), DVector::from_column_slice(expected));
+
+    if let Err(mismatch) = verify_gradient(loss, output.as_view(), expected.as_view(), 1e-2) {
+        panic!("{name}: {mismatch:?}");
+    }
+}
+
+#[test]
+fn built_in_gradients_match_central_differences() {
+    // Outputs stay away from the kinks of the quantile loss and the clamps of the probabilities and rates.
+    assert_gradient("mse", &MSE, &[0.3, -1.2, 2.0], &[0.0, 0.5, 1.0]);
+    assert_gradient("bce_with_logits", &BCEWithLogits, &[0.4, -1.5], &[1.0, 0.0]);
+    assert_gradient("log_cosh", &LogCosh, &[0.3, -2.0, 5.0], &[0.0, 1.0, 0.5]);
+    assert_gradient("quantile", &QuantileLoss::new(0.9).unwrap(), &[0.2, -1.0], &[1.0, -2.0]);
+    assert_gradient("quantiles", &QuantileLoss::per_output(vec![0.1, 0.5, 0.9]).unwrap(), &[0.2, -1.0, 3.0], &[1.0, -2.0, 2.5]);
+    assert_gradient("categorical_cross_entropy", &CategoricalCrossEntropy::new(), &[0.2, 0.5, 0.3], &[0.0, 1.0, 0.0]);
+    assert_gradient("smoothed", &CategoricalCrossEntropy::with_smoothing(0.1).unwrap(), &[0.2, 0.5, 0.3], &[0.0, 1.0, 0.0]);
+    assert_gradient("focal", &FocalLoss::new(2.0, Some(0.25)).unwrap(), &[0.3, 0.8], &[1.0, 0.0]);
+    assert_gradient("cosine", &CosineLoss, &[0.5, -1.2, 2.0], &[1.0, 0.5, -0.3]);
+    assert_gradient("poisson", &PoissonLoss, &[0.5, 2.0, 3.5], &[0.0, 1.0, 4.0]);
+
+    let multi_head = MultiHeadLoss::new(3).head("value", 0..2, MSE, 0.5).unwrap().head("count", 2..3, PoissonLoss, 2.0).unwrap();
+    assert_gradient("multi_head", &multi_head, &[0.3, -1.2, 1.5], &[0.0, 0.5, 2.0]);
+}
+
+/// MSE with half the gradient.
+struct HalvedMSE;
+impl LossFn for HalvedMSE {
+    fn apply(
+        &self,
+        output: DVectorView<f32>,
+        expected_output: DVectorView<f32>,
+    ) -> Result<f32, LossFnError> {
+        MSE.apply(output, expected_output)
+    }
+
+    fn partial_gradient(
+        &self,
+        output: DVectorView<f32>,
+        expected_output: DVectorView<f32>,
+    ) -> Result<DVector<f32>, LossFnError> {
+        Ok(MSE.partial_gradient(output, expected_output)? / 2.0)
+    }
+}
+
+#[test]
+fn a_wrong_gradient_is_caught() {
+    let (output, expected) = (DVector::from_vec(vec![0.9, 3.0]), DVector::from_vec(vec![1.0, 0.0]));
+
+    assert!(matches!(
+        verify_gradient(&HalvedMSE, output.as_view(), expected.as_view(), 1e-2),
+        Err(GradientMismatch::Component { index: 1, .. })
+    ));
+}
+
+#[test]
+fn label_smoothing_mixes_the_targets_towards_uniform() {
+    let (output, one_hot) = (DVector::from_vec(vec![0.2f64, 0.5, 0.3]), DVector::from_vec(vec![0.0, 1.0, 0.0]));
+    let smoothed = CategoricalCrossEntropy::with_smoothing(0.3).unwrap();
+
+    // The targets become [0.1, 0.8, 0.1], up to the f32 the smoothing is stored in.
+    let expected = -(0.1 * 0.2f64.ln() + 0.8 * 0.5f64.ln() + 0.1 * 0.3f64.ln());
+    assert!((smoothed.apply(output.as_view(), one_hot.as_view()).unwrap() - expected).abs() < 1e-6);
+    let gradient = smoothed.partial_gradient(output.as_view(), one_hot.as_view()).unwrap();
+    assert!((gradient - DVector::from_vec(vec![-0.1 / 0.2, -0.8 / 0.5, -0.1 / 0.3])).amax() < 1e-6);
+
+    let unsmoothed = CategoricalCrossEntropy::with_smoothing(0.0).unwrap();
+    assert_eq!(
+        unsmoothed.apply(output.as_view(), one_hot.as_view()).unwrap(),
+        CategoricalCrossEntropy::new().apply(output.as_view(), one_hot.as_view()).unwrap()
+    );
+
+    // The smoothed targets are the prediction with the least loss, any other distribution costs more.
+    let targets = DVector::from_vec(vec![0.1f64, 0.8, 0.1]);
+    let minimum = smoothed.apply(targets.as_view(), one_hot.as_view()).unwrap();
+    for (i, j) in [(0, 1), (1, 0), (1, 2), (0, 2)] {
+        let mut shifted = targets.clone();
+        shifted[i] += 0.05;
+        shifted[j] -= 0.05;
+        assert!(smoothed.apply(shifted.as_view(), one_hot.as_view()).unwrap() > minimum, "{shifted:?}");
+    }
+    assert!(smoothed.apply(DVector::from_vec(vec![1e-7, 1.0, 1e-7]).as_view(), one_hot.as_view()).unwrap() > minimum);
+}
+
+#[test]
+fn label_smoothing_has_to_be_below_one() {
+    for epsilon in [-0.1, 1.0, 1.5, f32::NAN] {
+        assert!(matches!(CategoricalCrossEntropy::with_smoothing(epsilon), Err(LossFnError::InvalidSmoothing(_))), "{epsilon}");
+    }
+
+    assert_eq!(CategoricalCrossEntropy::with_smoothing(0.99).unwrap().smoothing(), 0.99);
+    assert_eq!(CategoricalCrossEntropy::new().smoothing(), 0.0);
+}
+
+#[test]
+fn a_softmax_network_learns_the_smoothed_targets() {
+    let layers: Vec<Box<dyn NetworkLayer<f64>>> = vec![Box::new(Layer::zeros(2, 3, identity!()).unwrap()), Box::new(Softmax::new(3, 1.0).unwrap())];
+    let mut network = Network::from_network_layers(layers).unwrap();
+    let dataset = [Sample::new(DVector::from_vec(vec![1.0, -1.0]), DVector::from_vec(vec![0.0, 1.0, 0.0]))];
+    let loss = CategoricalCrossEntropy::with_smoothing(0.3).unwrap();
+
+    for _ in 0..2000 {
+        network.learn(&dataset, &loss, 0.5).unwrap();
+    }
+
+    let prediction = network.predict(dataset[0].inputs()).unwrap();
+    assert!((prediction - DVector::from_vec(vec![0.1, 0.8, 0.1])).amax() < 1e-3);
+}
+
+#[test]
+fn focal_loss_rejects_invalid_parameters() {
+    for gamma in [-0.5, f32::NAN, f32::INFINITY] {
+        assert!(matches!(FocalLoss::new(gamma, None), Err(LossFnError::InvalidFocusing(_))), "{gamma}");
+    }
+
+    for alpha in [-0.1, 1.5, f32::NAN] {
+        assert!(matches!(FocalLoss::new(2.0, Some(alpha)), Err(LossFnError::InvalidAlpha(_))), "{alpha}");
+    }
+
+    let loss = FocalLoss::new(0.0, Some(1.0)).unwrap();
+    assert_eq!((loss.gamma(), loss.alpha()), (0.0, Some(1.0)));
+}
+
+#[test]
+fn log_cosh_stays_finite_for_errors_where_cosh_overflows() {
+    let (output, expected) = (DVector::from_vec(vec![1e4, -1e4, 0.5]), DVector::from_vec(vec![0.0, 0.0, 0.0]));
+    assert!(1e4f32.cosh().is_infinite());
+
+    // Large errors cost |d| - ln 2 and pull with a gradient of ±1 / n.
+    let loss = LogCosh.apply(output.as_view(), expected.as_view()).unwrap();
+    let small = 0.5f32.cosh().ln();
+    assert!((loss - (2.0 * (1e4 - std::f32::consts::LN_2) + small) / 3.0).abs() < 1e-2, "{loss}");
+
+    let gradient = LogCosh.partial_gradient(output.as_view(), expected.as_view()).unwrap();
+    assert_eq!(gradient.as_slice()[..2], [1.0 / 3.0, -1.0 / 3.0]);
+
+    // In f32 a loss this large drowns the small error's differences, so only the large ones are checked.
+    assert_gradient("log_cosh", &LogCosh, &[1e4, -1e4], &[0.0, 0.0]);
+}
+
+fn cosine(output: &[f64], expected: &[f64]) -> (f64, DVector<f64>) {
+    let (output, expected) = (DVector::from_column_slice(output), DVector::from_column_slice(expected));
+    (CosineLoss.apply(output.as_view(), expected.as_view()).unwrap(), CosineLoss.partial_gradient(output.as_view(), expected.as_view()).unwrap())
+}
+
+#[test]
+fn cosine_loss_is_one_minus_the_cosine_whatever_the_scale() {
+    assert!(cosine(&[2.0, 0.0], &[5.0, 0.0]).0.abs() < 1e-12);
+    assert!((cosine(&[0.0, 3.0], &[1.0, 0.0]).0 - 1.0).abs() < 1e-12);
+    assert!((cosine(&[1.0, -1.0], &[-4.0, 4.0]).0 - 2.0).abs() < 1e-12);
+    assert!((cosine(&[1.0, 1.0], &[1.0, 0.0]).0 - (1.0 - 0.5f64.sqrt())).abs() < 1e-12);
+
+    let (loss, gradient) = cosine(&[0.5, -1.2, 2.0], &[1.0, 0.5, -0.3]);
+    let (scaled_loss, scaled_gradient) = cosine(&[0.5, -1.2, 2.0], &[10.0, 5.0, -3.0]);
+    assert!((loss - scaled_loss).abs() < 1e-12);
+    assert!((&gradient - scaled_gradient).amax() < 1e-12);
+
+    // The gradient is orthogonal to the output, since stretching the output doesn't change the loss.
+    assert!(gradient.dot(&DVector::from_vec(vec![0.5, -1.2, 2.0])).abs() < 1e-12);
+}
+
+#[test]
+fn cosine_gradients_match_central_differences_in_f64() {
+    let cases: [(&[f64], &[f64]); 4] = [
+        (&[0.5, -1.2, 2.0], &[1.0, 0.5, -0.3]),
+        (&[1e-3, 2e-3], &[-1.0, 1.0]),
+        (&[100.0, -50.0, 3.0, 0.1], &[0.2, 0.2, 0.2, 0.2]),
+        (&[0.9, 0.1], &[1.0, 0.0]),
+    ];
+
+    for (output, expected) in cases {
+        let (_, gradient) = cosine(output, expected);
+        // Relative to the output's scale, since the loss only changes across its direction.
+        let step = 1e-6 * DVector::from_column_slice(output).norm();
+
+        for i in 0..output.len() {
+            let (mut plus, mut minus) = (output.to_vec(), output.to_vec());
+            plus[i] += step;
+            minus[i] -= step;
+            let numeric = (cosine(&plus, expected).0 - cosine(&minus, expected).0) / (2.0 * step);
+
+            assert!((gradient[i] - numeric).abs() < 1e-6 * gradient.amax().max(1e-3), "{output:?}[{i}]: {} vs {numeric}", gradient[i]);
+        }
+    }
+}
+
+#[test]
+fn cosine_loss_of_a_vector_without_direction_is_one_with_no_gradient() {
+    for (output, expected) in [(&[0.0, 0.0][..], &[1.0, 2.0][..]), (&[1.0, 2.0], &[0.0, 0.0]), (&[1e-9, -1e-9], &[1.0, 0.0]), (&[0.0, 0.0], &[0.0, 0.0])] {
+        let (loss, gradient) = cosine(output, expected);
+
+        assert_eq!(loss, 1.0, "{output:?} {expected:?}");
+        assert_eq!(gradient.as_slice(), [0.0, 0.0], "{output:?} {expected:?}");
+    }
+
+    let (output, expected) = (DVector::from_vec(vec![0.0f32; 3]), DVector::from_vec(vec![1.0f32, 0.0, 0.0]));
+    assert!(CosineLoss.partial_gradient(output.as_view(), expected.as_view()).unwrap().iter().all(|x| x.is_finite()));
+}
+
+#[test]
+fn cosine_loss_checks_sizes() {
+    let (output, expected) = (DVector::from_vec(vec![1.0f32, 2.0]), DVector::from_vec(vec![1.0f32, 2.0, 3.0]));
+
+    for result in [CosineLoss.apply(output.as_view(), expected.as_view()).map(|_| ()), CosineLoss.partial_gradient(output.as_view(), expected.as_view()).map(|_| ())] {
+        assert!(matches!(result, Err(LossFnError::OutputSizeMismatch { given_output_size: 2, expected_output_size: 3 })));
+    }
+}
+
+#[test]
+fn a_network_learns_target_directions_under_cosine_loss() {
+    let mut network = Network::<f64>::random_with_rng(&[2, 3], identity!(), &Uniform::new(-1.0, 1.0).unwrap(), &mut StdRng::seed_from_u64(3)).unwrap();
+    // The targets' lengths differ wildly, only their directions can be learned.
+    let dataset = [
+        Sample::new(DVector::from_vec(vec![1.0, 0.0]), DVector::from_vec(vec![30.0, 10.0, 0.0])),
+        Sample::new(DVector::from_vec(vec![0.0, 1.0]), DVector::from_vec(vec![0.0, -0.1, 0.2])),
+        Sample::new(DVector::from_vec(vec![1.0, 1.0]), DVector::from_vec(vec![-2.0, 0.0, 2.0])),
+    ];
+
+    for _ in 0..3000 {
+        network.learn(&dataset, &CosineLoss, 0.5).unwrap();
+    }
+
+    for sample in dataset.iter() {
+        let loss = CosineLoss.apply(network.predict(sample.inputs()).unwrap().as_view(), sample.expected_outputs()).unwrap();
+        assert!(loss < 1e-3, "{loss}");
+    }
+}
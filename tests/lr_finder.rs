@@ -0,0 +1,70 @@
+mod common;
+
+use rand::{distr::Uniform, rngs::StdRng, SeedableRng};
+
+use neural::{
+    activations::*,
+    dataset::Sample,
+    losses::MSE,
+    network::{layer::Layer, Network},
+    training::{lr_finder::LearningRateFinder, TrainingError},
+};
+
+use common::xor;
+
+fn network() -> Network {
+    Network::random_with_rng(&[2, 4, 1], sigmoid!(), &Uniform::new(-1.0, 1.0).unwrap(), &mut StdRng::seed_from_u64(5)).unwrap()
+}
+
+#[test]
+fn suggests_a_sane_rate_for_xor_and_leaves_the_network_alone() {
+    let mut network = network();
+    let before = network.get_params();
+
+    let curve = LearningRateFinder::new(1e-3, 1e3, 60).run(&mut network, &xor(), &MSE).unwrap();
+
+    assert_eq!(network.get_params(), before);
+    assert_eq!(curve.rates.len(), curve.losses.len());
+    assert!(curve.rates.windows(2).all(|pair| pair[0] < pair[1]));
+    assert_eq!(curve.rates[0], 1e-3);
+
+    let suggested_rate = curve.suggested_rate.unwrap();
+    assert!((0.1..=100.0).contains(&suggested_rate), "suggested {suggested_rate}");
+    assert!(curve.rates.contains(&suggested_rate));
+}
+
+#[test]
+fn stops_once_the_loss_diverges() {
+    let train = vec![Sample::from_slices(&[1.0], &[1.0])];
+    let mut network = Network::from_layers(vec![Layer::zeros(1, 1, identity!()).unwrap()]).unwrap();
+
+    // Past a rate of 1 every step overshoots by more than it corrects.
+    let curve = LearningRateFinder::new(1e-2, 1e6, 50).run(&mut network, &train, &MSE).unwrap();
+
+    assert!(curve.rates.len() < 50);
+    let last = *curve.losses.last().unwrap();
+    let min = curve.losses.iter().copied().fold(f32::INFINITY, f32::min);
+    assert!(!last.is_finite() || last > 4.0 * min);
+}
+
+#[test]
+fn a_zero_minimum_isnt_divergence() {
+    // Batches of one alternate between a sample the network fits exactly and one it barely misses.
+    let train = vec![Sample::from_slices(&[0.0], &[0.0]), Sample::from_slices(&[0.0], &[0.01])];
+    let mut network = Network::from_layers(vec![Layer::zeros(1, 1, identity!()).unwrap()]).unwrap();
+
+    let curve = LearningRateFinder::new(1e-6, 1e-4, 10).batch_size(1).run(&mut network, &train, &MSE).unwrap();
+
+    assert_eq!(curve.losses[0], 0.0);
+    assert_eq!(curve.rates.len(), 10);
+}
+
+#[test]
+fn rejects_bad_sweeps() {
+    let mut network = network();
+
+    assert!(matches!(LearningRateFinder::new(1.0, 0.1, 10).run(&mut network, &xor(), &MSE), Err(TrainingError::InvalidRateRange { .. })));
+    assert!(matches!(LearningRateFinder::new(0.0, 1.0, 10).run(&mut network, &xor(), &MSE), Err(TrainingError::InvalidRateRange { .. })));
+    assert!(matches!(LearningRateFinder::new(0.1, 1.0, 1).run(&mut network, &xor(), &MSE), Err(TrainingError::TooFewSteps(1))));
+    assert!(LearningRateFinder::new(0.1, 1.0, 10).run(&mut network, &[], &MSE).is_err());
+}
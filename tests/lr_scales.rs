@@ -0,0 +1,98 @@
+mod common;
+
+use rand::{distr::Uniform, rngs::StdRng, SeedableRng};
+
+use neural::{
+    activations::*,
+    dataset::Sample,
+    losses::MSE,
+    network::{Network, NetworkError},
+    training::Trainer,
+};
+
+use common::{assert_close, sample, xor};
+
+fn network() -> Network<f64> {
+    Network::random_with_rng(&[2, 4, 1], sigmoid!(), &Uniform::new(-1.0, 1.0).unwrap(), &mut StdRng::seed_from_u64(5)).unwrap()
+}
+
+fn xor64() -> Vec<Sample<f64>> {
+    [([0.0, 0.0], 0.0), ([0.0, 1.0], 1.0), ([1.0, 0.0], 1.0), ([1.0, 1.0], 0.0)].iter().map(|(inputs, output)| sample(inputs, &[*output])).collect()
+}
+
+#[test]
+fn scales_of_one_reproduce_learn() {
+    let (mut scaled, mut plain) = (network(), network());
+    scaled.set_layer_lr_scale(0, 1.0).unwrap();
+    scaled.set_layer_lr_scale(1, 1.0).unwrap();
+    assert_eq!(scaled.layer_lr_scales(), &[1.0, 1.0]);
+
+    for _ in 0..50 {
+        assert_eq!(scaled.learn(&xor64(), &MSE, 0.5).unwrap(), plain.learn(&xor64(), &MSE, 0.5).unwrap());
+    }
+
+    assert_eq!(scaled.get_params(), plain.get_params());
+}
+
+#[test]
+fn a_scale_of_zero_freezes_the_layer_while_the_others_train() {
+    let mut network = network();
+    let initial = network.clone();
+    network.set_layer_lr_scale(0, 0.0).unwrap();
+
+    let first_loss = network.learn(&xor64(), &MSE, 0.5).unwrap();
+    let mut loss = first_loss;
+    for _ in 0..500 {
+        loss = network.learn(&xor64(), &MSE, 0.5).unwrap();
+    }
+
+    let (layer, initial_layer) = (network.dense_layer(0).unwrap(), initial.dense_layer(0).unwrap());
+    assert_eq!(layer.weights(), initial_layer.weights());
+    assert_eq!(layer.biases(), initial_layer.biases());
+    assert_ne!(network.dense_layer(1).unwrap().weights(), initial.dense_layer(1).unwrap().weights());
+    assert!(loss < first_loss);
+}
+
+#[test]
+fn a_scale_multiplies_the_layers_step() {
+    let (mut halved, mut plain) = (network(), network());
+    halved.set_layer_lr_scale(0, 0.5).unwrap();
+    let initial = network();
+
+    halved.learn(&xor64(), &MSE, 0.5).unwrap();
+    plain.learn(&xor64(), &MSE, 0.5).unwrap();
+
+    let step = |network: &Network<f64>, layer: usize| {
+        let (after, before) = (network.dense_layer(layer).unwrap(), initial.dense_layer(layer).unwrap());
+        (after.weights() - before.weights()).iter().chain((after.biases() - before.biases()).iter()).copied().collect::<Vec<_>>()
+    };
+
+    let half_of_plain: Vec<f64> = step(&plain, 0).iter().map(|x| x * 0.5).collect();
+    assert_close(&step(&halved, 0), &half_of_plain, 1e-15);
+    assert_eq!(step(&halved, 1), step(&plain, 1));
+}
+
+#[test]
+fn the_trainer_honours_the_scales() {
+    let mut network = Network::random_with_rng(&[2, 3, 1], sigmoid!(), &Uniform::new(-1.0, 1.0).unwrap(), &mut StdRng::seed_from_u64(6)).unwrap();
+    let initial = network.clone();
+    network.set_layer_lr_scale(1, 0.0).unwrap();
+
+    Trainer::new(MSE, 0.5, 20).batch_size(2).fit(&mut network, &xor(), &[]).unwrap();
+
+    assert_eq!(network.dense_layer(1).unwrap().weights(), initial.dense_layer(1).unwrap().weights());
+    assert_ne!(network.dense_layer(0).unwrap().weights(), initial.dense_layer(0).unwrap().weights());
+}
+
+#[test]
+fn invalid_scales_are_rejected() {
+    let mut network = network();
+
+    assert!(matches!(network.set_layer_lr_scale(2, 1.0), Err(NetworkError::LayerIndexOutOfRange { index: 2, layers: 2 })));
+
+    for scale in [-0.5, f64::NAN, f64::INFINITY] {
+        assert!(matches!(network.set_layer_lr_scale(1, scale), Err(NetworkError::InvalidLrScale { layer: 1, .. })), "{scale}");
+    }
+
+    assert_eq!(network.layer_lr_scales(), &[1.0, 1.0]);
+}
@@ -0,0 +1,107 @@
+mod common;
+
+use nalgebra::DVector;
+use rand::{
+    distr::{uniform::SampleUniform, Uniform},
+    rngs::StdRng,
+    SeedableRng,
+};
+
+use neural::{
+    activations::*,
+    losses::MSE,
+    network::{layer::Layer, MaxoutLayer, Network, NetworkLoadError},
+    scalar::Scalar,
+};
+
+use common::{analytic_gradient, assert_close, numeric_gradient, sample};
+
+/// A maxout layer of three pieces under a dense sigmoid output.
+fn network<T: Scalar + SampleUniform>(seed: u64) -> Network<T> {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let uniform = Uniform::new(T::constant(-1.0), T::constant(1.0)).unwrap();
+    let maxout = MaxoutLayer::random_with_rng(2, 4, 3, &uniform, &mut rng).unwrap();
+    let output = Layer::random_with_rng(4, 1, sigmoid!(), &uniform, &mut rng).unwrap();
+
+    Network::from_network_layers(vec![Box::new(maxout), Box::new(output)]).unwrap()
+}
+
+fn assert_same(loaded: &Network, saved: &Network) {
+    assert_eq!(loaded.get_params(), saved.get_params());
+    assert_eq!(loaded.layer(0).unwrap().downcast_ref::<MaxoutLayer>().unwrap().pieces(), 3);
+
+    for inputs in [[0.0, 0.0], [0.0, 1.0], [1.0, 0.0], [1.0, 1.0]] {
+        let inputs = DVector::from_row_slice(&inputs);
+        assert_eq!(loaded.predict(inputs.as_view()).unwrap(), saved.predict(inputs.as_view()).unwrap());
+    }
+}
+
+#[test]
+fn gradients_of_every_piece_match_central_differences() {
+    let network = network::<f64>(5);
+    let dataset = [sample(&[0.3, -0.8], &[0.9]), sample(&[-0.6, 0.4], &[0.1]), sample(&[0.9, 0.2], &[0.5])];
+
+    assert_close(&analytic_gradient(&network, &dataset, &MSE), &numeric_gradient(&network, &dataset, &MSE), 1e-6);
+}
+
+#[test]
+fn learns_xor() {
+    let dataset = common::xor();
+    let mut network = network::<f32>(2);
+
+    for _ in 0..3000 {
+        network.learn(&dataset, &MSE, 0.5).unwrap();
+    }
+
+    assert!(network.evaluate(&dataset, &MSE).unwrap() < 0.01);
+}
+
+#[test]
+fn round_trips_through_the_binary_format() {
+    let saved = network(7);
+    let mut bytes = Vec::new();
+    saved.write_to(&mut bytes).unwrap();
+
+    assert_same(&Network::read_from(bytes.as_slice()).unwrap(), &saved);
+}
+
+#[test]
+fn round_trips_through_json() {
+    let saved = network(7);
+
+    assert_same(&Network::from_json(&saved.to_json().unwrap()).unwrap(), &saved);
+}
+
+#[test]
+fn rejects_pieces_on_a_dense_layer() {
+    let json = network::<f32>(7).to_json().unwrap();
+    let json = json.replacen("\"activation\":\"maxout\"", "\"activation\":\"relu\"", 1);
+
+    assert!(matches!(Network::from_json(&json), Err(NetworkLoadError::UnexpectedPieces { layer: 0 })));
+}
+
+#[cfg(feature = "onnx")]
+#[test]
+fn isnt_exported_to_onnx() {
+    let network = network::<f32>(7);
+
+    assert!(network.to_onnx().is_err());
+}
+
+#[cfg(feature = "npz")]
+#[test]
+fn round_trips_through_npz() {
+    let saved = network(7);
+    let loaded = Network::from_npz(&saved.to_npz().unwrap(), &[2, 4, 1], vec![identity!(), sigmoid!()]).unwrap();
+
+    assert_same(&loaded, &saved);
+}
+
+#[cfg(feature = "safetensors")]
+#[test]
+fn round_trips_through_safetensors() {
+    let saved = network(7);
+    let loaded = Network::from_safetensors(&saved.to_safetensors().unwrap(), &[2, 4, 1], sigmoid!()).unwrap();
+
+    assert_same(&loaded, &saved);
+}
@@ -0,0 +1,100 @@
+use rand::{distr::Uniform, rngs::StdRng, SeedableRng};
+
+use neural::{
+    activations::*,
+    dataset::Sample,
+    losses::MSE,
+    network::{Network, NetworkError},
+};
+
+fn network(seed: u64) -> Network {
+    Network::random_with_rng(&[2, 3, 1], sigmoid!(), &Uniform::new(-1.0, 1.0).unwrap(), &mut StdRng::seed_from_u64(seed)).unwrap()
+}
+
+fn assert_params_close(actual: &[f32], expected: &[f32]) {
+    assert_eq!(actual.len(), expected.len());
+    for (actual, expected) in actual.iter().zip(expected) {
+        assert!((actual - expected).abs() < 1e-6, "{actual} vs {expected}");
+    }
+}
+
+#[test]
+fn averaging_a_network_with_itself_changes_nothing() {
+    let network = network(1);
+
+    assert_eq!(Network::average(&[&network, &network]).unwrap().get_params(), network.get_params());
+    assert_eq!(Network::average(&[&network]).unwrap().get_params(), network.get_params());
+    assert_eq!(Network::average(&[&network, &network]).unwrap().to_string(), network.to_string());
+}
+
+#[test]
+fn every_parameter_is_the_elementwise_mean() {
+    let (a, b, c) = (network(1), network(2), network(3));
+    let mean = |weights: [f32; 3]| -> Vec<f32> {
+        let total: f32 = weights.iter().sum();
+        (0..a.parameter_count())
+            .map(|i| (weights[0] * a.get_params()[i] + weights[1] * b.get_params()[i] + weights[2] * c.get_params()[i]) / total)
+            .collect()
+    };
+
+    assert_params_close(&Network::average(&[&a, &b, &c]).unwrap().get_params(), &mean([1.0, 1.0, 1.0]));
+    // Weights are normalized by their sum, and a weight of 0 leaves a network out.
+    assert_params_close(&Network::weighted_average(&[&a, &b, &c], &[1.0, 3.0, 0.0]).unwrap().get_params(), &mean([1.0, 3.0, 0.0]));
+    assert_params_close(&Network::weighted_average(&[&a, &b, &c], &[10.0, 30.0, 0.0]).unwrap().get_params(), &mean([1.0, 3.0, 0.0]));
+}
+
+/// `y = x1 + x2`, where shard 0 only varies `x1` and shard 1 only `x2`.
+fn shard(index: usize) -> Vec<Sample> {
+    (0..10)
+        .map(|i| i as f32 / 9.0 - 0.5)
+        .map(|x| {
+            let inputs = if index == 0 { [x, 0.0] } else { [0.0, x] };
+            Sample::from_slices(&inputs, &[x])
+        })
+        .collect()
+}
+
+#[test]
+fn averaged_shard_models_beat_either_shard_on_the_union() {
+    let union: Vec<Sample> = shard(0).into_iter().chain(shard(1)).collect();
+
+    // Both copies start from the same network, as in federated training, so their parameters line up.
+    let start = Network::zeros(&[2, 1], identity!()).unwrap();
+    let trained: Vec<Network> = (0..2)
+        .map(|index| {
+            let mut network = start.clone();
+            for _ in 0..500 {
+                network.learn(&shard(index), &MSE, 0.5).unwrap();
+            }
+            network
+        })
+        .collect();
+
+    let averaged = Network::average(&[&trained[0], &trained[1]]).unwrap();
+    let union_loss = |network: &Network| network.evaluate(&union, &MSE).unwrap();
+
+    // Every shard model learned its own input's weight only, the average has half of both.
+    for (index, network) in trained.iter().enumerate() {
+        assert!(network.evaluate(&shard(index), &MSE).unwrap() < 1e-6);
+        assert!(union_loss(&averaged) < 0.6 * union_loss(network), "{} vs {}", union_loss(&averaged), union_loss(network));
+    }
+}
+
+#[test]
+fn invalid_merges_are_rejected() {
+    let (a, b) = (network(1), network(2));
+
+    assert!(matches!(Network::<f32>::average(&[]), Err(NetworkError::NoNetworks)));
+    assert!(matches!(Network::weighted_average(&[&a, &b], &[1.0]), Err(NetworkError::MergeWeightCountMismatch { networks: 2, weights: 1 })));
+    for weights in [[1.0, -1.0], [0.0, 0.0], [f32::NAN, 1.0], [f32::INFINITY, 1.0]] {
+        assert!(matches!(Network::weighted_average(&[&a, &b], &weights), Err(NetworkError::InvalidMergeWeights)));
+    }
+
+    let wider = Network::random_with_rng(&[2, 3, 2], sigmoid!(), &Uniform::new(-1.0, 1.0).unwrap(), &mut StdRng::seed_from_u64(4)).unwrap();
+    let error = Network::average(&[&a, &b, &wider]).unwrap_err();
+    assert!(matches!(error, NetworkError::ArchitectureMismatch { layer: 1 }));
+    assert_eq!(error.to_string(), "the networks' architectures differ at layer 1");
+
+    let relu = Network::random_with_rng(&[2, 3, 1], relu!(), &Uniform::new(-1.0, 1.0).unwrap(), &mut StdRng::seed_from_u64(4)).unwrap();
+    assert!(matches!(Network::average(&[&a, &relu]), Err(NetworkError::ArchitectureMismatch { layer: 0 })));
+}
@@ -0,0 +1,62 @@
+use nalgebra::{DMatrix, DVector};
+
+use neural::{
+    activations::Identity,
+    dataset::Sample,
+    metrics::{regression_report, OutputErrors},
+    network::{layer::Layer, Network},
+};
+
+/// Outputs the input and a constant 1.
+fn network() -> Network {
+    let mut layer = Layer::zeros(1, 2, Box::new(Identity)).unwrap();
+    layer.set_weights(DMatrix::from_row_slice(2, 1, &[1.0, 0.0])).unwrap();
+    layer.set_biases(DVector::from_vec(vec![0.0, 1.0])).unwrap();
+
+    Network::from_layers(vec![layer]).unwrap()
+}
+
+fn dataset(constant: f32) -> Vec<Sample> {
+    [(0.0, 0.0), (1.0, 1.0), (2.0, 4.0)]
+        .into_iter()
+        .map(|(x, y)| Sample::from_slices(&[x], &[y, constant]))
+        .collect()
+}
+
+fn assert_close(actual: f32, expected: f32) {
+    assert!((actual - expected).abs() < 1e-6, "{actual} != {expected}");
+}
+
+#[test]
+fn matches_hand_computed_errors() {
+    let report = regression_report(&mut network(), &dataset(2.0)).unwrap();
+
+    // The first output misses the last sample by 2, around a mean of 5/3 with squared deviations summing to 26/3.
+    let first = report.outputs[0];
+    assert_close(first.rmse, (4.0f32 / 3.0).sqrt());
+    assert_close(first.mae, 2.0 / 3.0);
+    assert_close(first.r2, 1.0 - 4.0 / (26.0 / 3.0));
+
+    // The second output misses every sample by 1.
+    assert_eq!(report.outputs[1], OutputErrors { rmse: 1.0, mae: 1.0, r2: 0.0 });
+
+    assert_close(report.rmse, (7.0f32 / 6.0).sqrt());
+    assert_close(report.mae, 5.0 / 6.0);
+    assert_close(report.r2, (1.0 - 4.0 / (26.0 / 3.0)) / 2.0);
+}
+
+#[test]
+fn a_constant_target_predicted_exactly_has_an_r2_of_1() {
+    let report = regression_report(&mut network(), &dataset(1.0)).unwrap();
+
+    assert_eq!(report.outputs[1], OutputErrors { rmse: 0.0, mae: 0.0, r2: 1.0 });
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn reports_round_trip_through_serde() {
+    let report = regression_report(&mut network(), &dataset(2.0)).unwrap();
+    let json = serde_json::to_string(&report).unwrap();
+
+    assert_eq!(serde_json::from_str::<neural::metrics::RegressionReport>(&json).unwrap(), report);
+}
@@ -0,0 +1,104 @@
+use std::path::PathBuf;
+
+use neural::dataset::mnist::{self, MnistError};
+
+const FIXTURES: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/mnist");
+
+/// An IDX image file with the given header numbers after the magic and the given pixels.
+fn images(name: &str, header: &[u32], pixels: &[u8]) -> PathBuf {
+    let mut bytes = 0x0803u32.to_be_bytes().to_vec();
+    header.iter().for_each(|number| bytes.extend(number.to_be_bytes()));
+    bytes.extend_from_slice(pixels);
+
+    let path = std::env::temp_dir().join(format!("neural-mnist-{name}-{}.idx", std::process::id()));
+    std::fs::write(&path, bytes).unwrap();
+    path
+}
+
+#[test]
+fn loads_images_scaled_to_one() {
+    let path = images("valid", &[2, 1, 2], &[0, 255, 51, 102]);
+    let loaded = mnist::load_images(&path).unwrap();
+
+    assert_eq!(loaded.len(), 2);
+    assert_eq!(loaded[0].as_slice(), [0.0, 1.0]);
+    assert_eq!(loaded[1].as_slice(), [0.2, 0.4]);
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn rejects_a_header_bigger_than_the_file() {
+    let path = images("truncated", &[3, 1, 2], &[0, 255, 51, 102]);
+    assert!(matches!(mnist::load_images(&path), Err(MnistError::Truncated { expected: 22, found: 20 })));
+    std::fs::remove_file(path).unwrap();
+
+    // u32::MAX^3 pixels overflow a 64 bit size.
+    let path = images("overflowing", &[u32::MAX, u32::MAX, u32::MAX], &[0; 4]);
+    assert!(matches!(mnist::load_images(&path), Err(MnistError::TooManyPixels { .. })));
+    std::fs::remove_file(path).unwrap();
+
+    let path = images("empty", &[u32::MAX, 0, 28], &[]);
+    assert!(matches!(mnist::load_images(&path), Err(MnistError::EmptyImages { rows: 0, columns: 28 })));
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn loads_the_fixture_as_one_hot_samples() {
+    let samples = mnist::load(format!("{FIXTURES}/images.idx"), format!("{FIXTURES}/labels.idx")).unwrap();
+
+    assert_eq!(samples.len(), 3);
+    for (sample, label) in samples.iter().zip([7, 2, 1]) {
+        assert_eq!(sample.inputs().len(), 784);
+        assert_eq!(sample.expected_outputs().len(), 10);
+        assert_eq!(sample.expected_outputs().argmax().0, label);
+        assert_eq!(sample.expected_outputs().sum(), 1.0);
+    }
+
+    assert!(samples[0].inputs().iter().all(|&pixel| pixel == 0.0));
+    assert!(samples[1].inputs().iter().all(|&pixel| pixel == 1.0));
+    // Row-major, so every row of the gradient repeats the first.
+    let gradient = samples[2].inputs();
+    assert_eq!(gradient[27], 243.0 / 255.0);
+    assert_eq!(gradient[28], 0.0);
+    assert_eq!(gradient[28 * 27 + 5], 45.0 / 255.0);
+}
+
+#[test]
+fn loads_the_fixture_labels() {
+    assert_eq!(mnist::load_labels(format!("{FIXTURES}/labels.idx")).unwrap(), [7, 2, 1]);
+}
+
+#[test]
+fn rejects_images_and_labels_that_dont_match() {
+    let path = images("three", &[2, 28, 28], &[0; 2 * 784]);
+
+    assert!(matches!(
+        mnist::load(&path, format!("{FIXTURES}/labels.idx")),
+        Err(MnistError::CountMismatch { images: 2, labels: 3 })
+    ));
+    // The labels aren't images.
+    assert!(matches!(
+        mnist::load_images(format!("{FIXTURES}/labels.idx")),
+        Err(MnistError::BadMagic { expected: 0x0803, found: 0x0801 })
+    ));
+    std::fs::remove_file(path).unwrap();
+}
+
+#[cfg(feature = "gzip")]
+#[test]
+fn loads_the_gzip_compressed_fixture_like_the_raw_one() {
+    let raw = mnist::load(format!("{FIXTURES}/images.idx"), format!("{FIXTURES}/labels.idx")).unwrap();
+    let compressed = mnist::load(format!("{FIXTURES}/images.idx.gz"), format!("{FIXTURES}/labels.idx.gz")).unwrap();
+
+    assert_eq!(compressed.len(), raw.len());
+    for (compressed, raw) in compressed.iter().zip(&raw) {
+        assert_eq!(compressed.inputs(), raw.inputs());
+        assert_eq!(compressed.expected_outputs(), raw.expected_outputs());
+    }
+}
+
+#[cfg(not(feature = "gzip"))]
+#[test]
+fn compressed_files_need_the_gzip_feature() {
+    assert!(matches!(mnist::load_labels(format!("{FIXTURES}/labels.idx.gz")), Err(MnistError::GzipUnsupported)));
+}
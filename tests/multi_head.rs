@@ -0,0 +1,87 @@
+use nalgebra::DVector;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use neural::{
+    activations::*,
+    dataset::Sample,
+    losses::{BCEWithLogits, CategoricalCrossEntropy, LossFn, LossFnError, MultiHeadLoss, PoissonLoss, MSE},
+    network::{NetworkBuilder, NetworkError},
+};
+
+#[test]
+fn the_gradient_is_the_weighted_head_gradients_in_their_slices() {
+    let heads = MultiHeadLoss::new(6).head("value", 0..2, MSE, 0.5).unwrap().head("count", 3..6, PoissonLoss, 2.0).unwrap();
+    let output = DVector::from_vec(vec![0.3, -1.2, 7.0, 1.5, 0.5, 2.0]);
+    let expected = DVector::from_vec(vec![0.0, 0.5, -3.0, 2.0, 0.0, 1.0]);
+
+    let value = MSE.partial_gradient(output.rows(0, 2), expected.rows(0, 2)).unwrap() * 0.5;
+    let count = PoissonLoss.partial_gradient(output.rows(3, 3), expected.rows(3, 3)).unwrap() * 2.0;
+    let concatenated: Vec<f32> = value.iter().chain(&[0.0]).chain(count.iter()).copied().collect();
+
+    assert_eq!(heads.partial_gradient(output.as_view(), expected.as_view()).unwrap().as_slice(), concatenated);
+
+    let losses = heads.head_losses(output.as_view(), expected.as_view()).unwrap();
+    let combined = heads.apply(output.as_view(), expected.as_view()).unwrap();
+    assert_eq!(combined, 0.5 * losses[0].1 + 2.0 * losses[1].1);
+}
+
+#[test]
+fn heads_are_validated_when_added() {
+    let heads = || MultiHeadLoss::<f32>::new(4).head("a", 0..2, MSE, 1.0).unwrap();
+
+    assert!(matches!(heads().head("a", 2..3, MSE, 1.0), Err(LossFnError::DuplicateHead(_))));
+    assert!(matches!(heads().head("b", 2..2, MSE, 1.0), Err(LossFnError::EmptyHead(_))));
+    assert!(matches!(heads().head("b", 3..5, MSE, 1.0), Err(LossFnError::HeadOutOfRange { start: 3, end: 5, outputs: 4, .. })));
+    assert!(matches!(heads().head("b", 1..3, MSE, 1.0), Err(LossFnError::OverlappingHeads { .. })));
+    assert!(matches!(heads().head("b", 2..4, MSE, -1.0), Err(LossFnError::InvalidHeadWeight { .. })));
+
+    let logits = MultiHeadLoss::<f32>::new(4).head("logits", 0..2, BCEWithLogits, 1.0).unwrap();
+    assert!(matches!(logits.head("classes", 2..4, CategoricalCrossEntropy::new(), 1.0), Err(LossFnError::HeadActivationConflict { .. })));
+}
+
+#[test]
+fn learns_a_class_and_a_value_together() {
+    let mut rng = StdRng::seed_from_u64(7);
+
+    // The class is which third of [-1, 1) the input is in, the value its square.
+    let sample = |x: f32| {
+        let class = ((x + 1.0) * 1.5).floor().min(2.0) as usize;
+        let mut expected = [0.0; 4];
+        expected[class] = 1.0;
+        expected[3] = x * x;
+        Sample::from_slices(&[x], &expected)
+    };
+    let dataset: Vec<Sample> = (0..200).map(|_| sample(rng.random_range(-1.0..1.0))).collect();
+
+    let heads = MultiHeadLoss::new(4).head("class", 0..3, BCEWithLogits, 1.0).unwrap().head("value", 3..4, MSE, 2.0).unwrap();
+    let mut network = NetworkBuilder::new().input(1).layer(16, tanh!()).layer(4, identity!()).build_with_rng(&mut rng).unwrap();
+
+    for _ in 0..300 {
+        for batch in dataset.chunks(20) {
+            network.learn_multi_head(batch, &heads, 0.1).unwrap();
+        }
+    }
+
+    let test: Vec<Sample> = (0..50).map(|i| sample(-0.99 + i as f32 * 0.04)).collect();
+    let mut correct = 0;
+    let mut squared_error = 0.0;
+    for sample in &test {
+        let outputs = network.predict(sample.inputs()).unwrap();
+        correct += (outputs.rows(0, 3).argmax().0 == sample.expected_outputs().rows(0, 3).argmax().0) as usize;
+        squared_error += (outputs[3] - sample.expected_outputs()[3]).powi(2);
+    }
+
+    assert!(correct >= 45, "{correct} of 50 classified correctly");
+    assert!(squared_error / 50.0 < 0.01, "{}", squared_error / 50.0);
+}
+
+#[test]
+fn heads_for_another_output_size_are_rejected() {
+    let mut network = NetworkBuilder::new().input(1).layer(3, identity!()).build().unwrap();
+    let heads = MultiHeadLoss::new(4).head("value", 0..4, MSE, 1.0).unwrap();
+
+    assert!(matches!(
+        network.learn_multi_head(&[Sample::from_slices(&[0.0], &[0.0; 3])], &heads, 0.1),
+        Err(NetworkError::LossFnError(LossFnError::OutputSizeMismatch { given_output_size: 3, expected_output_size: 4 }))
+    ));
+}
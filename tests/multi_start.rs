@@ -0,0 +1,50 @@
+mod common;
+
+use std::ops::ControlFlow;
+
+use rand::{distr::Uniform, rngs::StdRng, SeedableRng};
+
+use neural::{
+    activations::*,
+    losses::MSE,
+    network::{Network, NetworkError},
+    training::{
+        callback::{EpochContext, TrainingCallback},
+        multi_start::multi_start,
+        Trainer,
+    },
+};
+
+/// Stops the training after the given number of epochs, counted over every `fit` it's part of.
+struct StopAfter(usize);
+
+impl TrainingCallback for StopAfter {
+    fn on_epoch_end(&mut self, _: &EpochContext) -> ControlFlow<()> {
+        self.0 = self.0.saturating_sub(1);
+        if self.0 == 0 { ControlFlow::Break(()) } else { ControlFlow::Continue(()) }
+    }
+}
+
+fn factory(rng: &mut StdRng) -> Result<Network, NetworkError> {
+    Network::random_with_rng(&[2, 3, 1], sigmoid!(), &Uniform::new(-1.0, 1.0).unwrap(), rng)
+}
+
+fn trainer() -> Trainer<'static, MSE> {
+    Trainer::new(MSE, 0.5, 50).shuffle(true).callback(StopAfter(10))
+}
+
+#[test]
+fn every_restart_trains_with_a_fresh_trainer() {
+    let dataset = common::xor();
+    let result = multi_start(3, factory, &dataset, &dataset, trainer, &mut StdRng::seed_from_u64(1)).unwrap();
+
+    for restart in &result.restarts {
+        let mut rng = StdRng::seed_from_u64(restart.seed);
+        let mut network = factory(&mut rng).unwrap();
+        let report = trainer().fit_with_rng(&mut network, &dataset, &dataset, &mut rng).unwrap();
+
+        assert_eq!(report.history.len(), 10);
+        assert_eq!(restart.train_loss, *report.history.train_losses().last().unwrap());
+        assert_eq!(restart.validation_loss, network.evaluate(&dataset, &MSE).unwrap());
+    }
+}
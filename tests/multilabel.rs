@@ -0,0 +1,103 @@
+use nalgebra::DMatrix;
+
+use neural::{
+    activations::*,
+    dataset::Sample,
+    metrics::{multilabel_report, ClassScores},
+    network::{layer::Layer, Network, NetworkError},
+};
+
+/// Outputs its 3 inputs, so a sample's inputs are its label scores.
+fn network() -> Network {
+    let mut layer = Layer::zeros(3, 3, identity!()).unwrap();
+    layer.set_weights(DMatrix::identity(3, 3)).unwrap();
+    Network::from_layers(vec![layer]).unwrap()
+}
+
+/// Label 0 is frequent and missed once, label 1 is rare and predicted once too often, label 2 never occurs.
+fn dataset() -> Vec<Sample> {
+    [
+        ([0.9, 0.1, 0.1], [1.0, 0.0, 0.0]),
+        ([0.9, 0.8, 0.0], [1.0, 1.0, 0.0]),
+        ([0.7, 0.6, 0.2], [1.0, 0.0, 0.0]),
+        ([0.2, 0.1, 0.1], [1.0, 0.0, 0.0]),
+        // Exactly at the threshold counts as predicted.
+        ([0.5, 0.0, 0.0], [1.0, 0.0, 0.0]),
+        ([0.1, 0.2, 0.3], [0.0, 0.0, 0.0]),
+    ]
+    .into_iter()
+    .map(Sample::from)
+    .collect()
+}
+
+fn assert_close(actual: f32, expected: f32) {
+    assert!((actual - expected).abs() < 1e-6, "{actual} != {expected}");
+}
+
+fn assert_scores(scores: &ClassScores, (precision, recall, f1): (f32, f32, f32), (predicted, support): (usize, usize)) {
+    assert_close(scores.precision, precision);
+    assert_close(scores.recall, recall);
+    assert_close(scores.f1, f1);
+    assert_eq!((scores.predicted, scores.support), (predicted, support));
+}
+
+#[test]
+fn every_label_is_scored_on_its_own() {
+    let report = multilabel_report(&mut network(), &dataset(), 0.5).unwrap();
+
+    assert_eq!(report.labels.len(), 3);
+    // 4 of 5 found, all predictions right.
+    assert_scores(&report.labels[0], (1.0, 0.8, 16.0 / 18.0), (4, 5));
+    // Found, but predicted twice.
+    assert_scores(&report.labels[1], (0.5, 1.0, 2.0 / 3.0), (2, 1));
+    // Never present and never predicted: zeros rather than NaN.
+    assert_scores(&report.labels[2], (0.0, 0.0, 0.0), (0, 0));
+}
+
+#[test]
+fn hamming_loss_and_subset_accuracy_count_pairs_and_samples() {
+    let report = multilabel_report(&mut network(), &dataset(), 0.5).unwrap();
+
+    // 2 wrong pairs out of 18, and 4 of 6 samples entirely right.
+    assert_close(report.hamming_loss, 2.0 / 18.0);
+    assert_close(report.subset_accuracy, 4.0 / 6.0);
+}
+
+#[test]
+fn micro_averages_pool_the_counts_and_macro_averages_the_labels() {
+    let report = multilabel_report(&mut network(), &dataset(), 0.5).unwrap();
+
+    // 5 true positives out of 6 predictions and 6 present labels.
+    assert_close(report.micro_precision, 5.0 / 6.0);
+    assert_close(report.micro_recall, 5.0 / 6.0);
+    assert_close(report.micro_f1, 5.0 / 6.0);
+
+    // The label that never occurs pulls the macro averages down, the frequent one dominates the micro ones.
+    assert_close(report.macro_precision, (1.0 + 0.5 + 0.0) / 3.0);
+    assert_close(report.macro_recall, (0.8 + 1.0 + 0.0) / 3.0);
+    assert_close(report.macro_f1, (16.0 / 18.0 + 2.0 / 3.0 + 0.0) / 3.0);
+}
+
+#[test]
+fn the_threshold_applies_to_every_output() {
+    // Everything is predicted: every present label is found, the absent one is only ever wrong.
+    let report = multilabel_report(&mut network(), &dataset(), 0.0).unwrap();
+
+    assert_scores(&report.labels[0], (5.0 / 6.0, 1.0, 10.0 / 11.0), (6, 5));
+    assert_scores(&report.labels[2], (0.0, 0.0, 0.0), (6, 0));
+    assert_close(report.hamming_loss, 12.0 / 18.0);
+    assert_close(report.subset_accuracy, 0.0);
+    assert!([report.micro_f1, report.macro_f1].iter().all(|score| score.is_finite()));
+}
+
+#[test]
+fn invalid_datasets_are_rejected() {
+    let mut samples = dataset();
+    samples[4] = ([0.5, 0.5, 0.5], [1.0, 0.5, 0.0]).into();
+    assert!(matches!(multilabel_report(&mut network(), &samples, 0.5), Err(NetworkError::InvalidClassEncoding { index: 4 })));
+
+    assert!(matches!(multilabel_report(&mut network(), &[], 0.5), Err(NetworkError::EmptyDataset)));
+
+    let wrong_size = [Sample::from(([0.5, 0.5], [1.0, 0.0, 0.0]))];
+    assert!(matches!(multilabel_report(&mut network(), &wrong_size, 0.5), Err(NetworkError::SampleSizeMismatch { index: 0, .. })));
+}
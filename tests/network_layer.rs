@@ -0,0 +1,119 @@
+mod common;
+
+use nalgebra::{DMatrix, DMatrixView, DVector, DVectorView};
+use rand::{distr::Uniform, rngs::StdRng, SeedableRng};
+
+use neural::{
+    activations::*,
+    losses::MSE,
+    network::{layer::{Layer, LayerError}, Conv2D, Convolution, Embedding, ImageShape, Network, NetworkLayer},
+};
+
+use common::{analytic_gradient, assert_close, numeric_gradient, sample};
+
+fn uniform() -> Uniform<f64> {
+    Uniform::new(-0.5, 0.5).unwrap()
+}
+
+/// Multiplies its inputs by a fixed factor, a layer without parameters.
+#[derive(Clone, Debug)]
+struct Scale(usize, f64);
+
+impl NetworkLayer<f64> for Scale {
+    fn input_size(&self) -> usize { self.0 }
+
+    fn output_size(&self) -> usize { self.0 }
+
+    fn forward(&mut self, inputs: DVectorView<f64>) -> Result<DVector<f64>, LayerError> {
+        self.predict(inputs)
+    }
+
+    fn predict(&self, inputs: DVectorView<f64>) -> Result<DVector<f64>, LayerError> {
+        Ok(inputs * self.1)
+    }
+
+    fn forward_training_batch(&mut self, inputs: DMatrixView<f64>) -> Result<DMatrix<f64>, LayerError> {
+        Ok(inputs * self.1)
+    }
+
+    fn backpropagation_step_batch(&mut self, _inputs: DMatrixView<f64>, output_partial_gradient: &DMatrix<f64>) -> Result<DMatrix<f64>, LayerError> {
+        Ok(output_partial_gradient * self.1)
+    }
+
+    fn apply_gradient(&mut self, _scale: f64) {}
+
+    fn zero_gradient(&mut self) {}
+
+    fn boxed_clone(&self) -> Box<dyn NetworkLayer<f64>> {
+        Box::new(self.clone())
+    }
+}
+
+fn dense(input_size: usize, output_size: usize, rng: &mut StdRng) -> Box<dyn NetworkLayer<f64>> {
+    Box::new(Layer::random_with_rng(input_size, output_size, sigmoid!(), &uniform(), rng).unwrap())
+}
+
+#[test]
+fn custom_layer_trains_with_dense_layers() {
+    let mut rng = StdRng::seed_from_u64(1);
+    let network = Network::from_network_layers(vec![dense(2, 3, &mut rng), Box::new(Scale(3, 2.0)), dense(3, 1, &mut rng)]).unwrap();
+    let dataset = [sample(&[0.2, -0.4], &[0.7]), sample(&[-0.1, 0.3], &[0.2])];
+
+    assert_close(&analytic_gradient(&network, &dataset, &MSE), &numeric_gradient(&network, &dataset, &MSE), 1e-8);
+}
+
+#[test]
+fn conv2d_trains_in_a_network() {
+    let mut rng = StdRng::seed_from_u64(2);
+    let shape = ImageShape::new(4, 4, 1);
+    let conv = Conv2D::random_with_rng(shape, Convolution::new(2, (3, 3)).padding(1).stride(2), tanh!(), &uniform(), &mut rng).unwrap();
+    let conv_outputs = NetworkLayer::output_size(&conv);
+    let network = Network::from_network_layers(vec![Box::new(conv), dense(conv_outputs, 1, &mut rng)]).unwrap();
+
+    let dataset: Vec<_> = (0..3)
+        .map(|i| {
+            let inputs: Vec<f64> = (0..16).map(|j| ((i * 16 + j) as f64 * 0.37).sin()).collect();
+            sample(&inputs, &[i as f64 * 0.3])
+        })
+        .collect();
+
+    assert_close(&analytic_gradient(&network, &dataset, &MSE), &numeric_gradient(&network, &dataset, &MSE), 1e-8);
+}
+
+#[test]
+fn embedding_trains_in_a_network() {
+    let mut rng = StdRng::seed_from_u64(3);
+    let embedding = Embedding::random_with_rng(5, 3, 2, &uniform(), &mut rng).unwrap();
+    let network = Network::from_network_layers(vec![Box::new(embedding), dense(6, 1, &mut rng)]).unwrap();
+    let dataset = [sample(&[0.0, 4.0], &[0.9]), sample(&[4.0, 2.0], &[0.1]), sample(&[1.0, 1.0], &[0.5])];
+
+    assert_close(&analytic_gradient(&network, &dataset, &MSE), &numeric_gradient(&network, &dataset, &MSE), 1e-8);
+
+    // Rows that no sample looks up don't change.
+    let mut trained = network.clone();
+    trained.learn(&dataset, &MSE, 0.5).unwrap();
+    let embeddings = |network: &Network<f64>| network.layer(0).unwrap().downcast_ref::<Embedding<f64>>().unwrap().embeddings().into_owned();
+    assert_eq!(embeddings(&trained).row(3), embeddings(&network).row(3));
+    assert_ne!(embeddings(&trained).row(4), embeddings(&network).row(4));
+}
+
+#[test]
+fn backpropagation_without_a_training_pass_fails() {
+    let mut rng = StdRng::seed_from_u64(4);
+    let gradient = DMatrix::zeros(3, 2);
+    let mut layers: Vec<Box<dyn NetworkLayer<f64>>> = vec![
+        dense(2, 3, &mut rng),
+        Box::new(Embedding::zeros(4, 1, 3).unwrap()),
+        Box::new(Conv2D::zeros(ImageShape::new(1, 3, 1), Convolution::new(1, (1, 1)), identity!()).unwrap()),
+    ];
+
+    for layer in layers.iter_mut() {
+        let (inputs, gradient) = (DMatrix::zeros(layer.input_size(), 2), DMatrix::zeros(layer.output_size(), 2));
+        assert!(matches!(layer.backpropagation_step_batch(inputs.as_view(), &gradient), Err(LayerError::NoTrainingBatch(2))));
+    }
+
+    // A training pass over a batch of another size doesn't count either.
+    let layer = &mut layers[0];
+    layer.forward_training_batch(DMatrix::zeros(2, 1).as_view()).unwrap();
+    assert!(matches!(layer.backpropagation_step_batch(DMatrix::zeros(2, 2).as_view(), &gradient), Err(LayerError::NoTrainingBatch(2))));
+}
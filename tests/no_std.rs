@@ -0,0 +1,85 @@
+//! Inference without std: `cargo test --no-default-features --test no_std` builds the crate without std,
+//! and the test itself only uses `core` and `alloc`.
+
+#![no_std]
+
+extern crate alloc;
+
+use alloc::{boxed::Box, vec};
+
+use nalgebra::{DMatrix, DVector, SVector};
+
+use neural::{
+    activations::{ReLU, Sigmoid},
+    losses::{LossFn, MSE},
+    network::{layer::{Layer, LayerError}, Network, StaticForward, StaticLayer},
+};
+
+/// XOR from an OR and a NAND unit in the hidden layer, and an AND of them as the output.
+fn xor() -> Network {
+    let mut hidden = Layer::zeros(2, 2, Box::new(Sigmoid)).unwrap();
+    hidden.set_weights(DMatrix::from_row_slice(2, 2, &[20.0, 20.0, -20.0, -20.0])).unwrap();
+    hidden.set_biases(DVector::from_vec(vec![-10.0, 30.0])).unwrap();
+
+    let mut output = Layer::zeros(2, 1, Box::new(Sigmoid)).unwrap();
+    output.set_weights(DMatrix::from_row_slice(1, 2, &[20.0, 20.0])).unwrap();
+    output.set_biases(DVector::from_vec(vec![-30.0])).unwrap();
+
+    Network::from_layers(vec![hidden, output]).unwrap()
+}
+
+#[test]
+fn a_network_from_raw_weights_runs_forward() {
+    let mut network = xor();
+
+    for (inputs, expected) in [([0.0, 0.0], 0.0), ([0.0, 1.0], 1.0), ([1.0, 0.0], 1.0), ([1.0, 1.0], 0.0)] {
+        let inputs = DVector::from_row_slice(&inputs);
+        let predicted = network.predict(inputs.as_view()).unwrap();
+        let forward = network.forward(inputs).unwrap();
+
+        assert_eq!(predicted, forward);
+        assert!((forward[0] - expected).abs() < 1e-3);
+
+        let loss = MSE.apply(forward.as_view(), DVector::from_element(1, expected).as_view()).unwrap();
+        assert!(loss < 1e-6);
+    }
+}
+
+#[test]
+fn a_layer_runs_forward() {
+    let mut layer = Layer::zeros(2, 1, Box::new(Sigmoid)).unwrap();
+    layer.set_weights(DMatrix::from_row_slice(1, 2, &[1.0, 2.0])).unwrap();
+    layer.set_biases(DVector::from_vec(vec![1.0])).unwrap();
+
+    // The weighted sum is 3 - 4 + 1 = 0, right in the middle of the sigmoid.
+    let outputs = layer.forward(DVector::from_vec(vec![3.0, -2.0])).unwrap();
+    assert_eq!(outputs, DVector::from_vec(vec![0.5]));
+}
+
+#[test]
+fn static_layers_reproduce_the_dynamic_ones() {
+    let network = xor();
+    let hidden = network.layer(0).unwrap().as_dense().unwrap();
+    let output = network.layer(1).unwrap().as_dense().unwrap();
+    let stacked: (StaticLayer<2, 2, Sigmoid>, StaticLayer<2, 1, Sigmoid>) = (hidden.try_into().unwrap(), output.try_into().unwrap());
+
+    for inputs in [[0.0, 0.0], [0.0, 1.0], [1.0, 0.0], [1.0, 1.0]] {
+        let dynamic = network.predict(DVector::from_row_slice(&inputs).as_view()).unwrap();
+        assert_eq!(stacked.forward(&SVector::from(inputs)).as_slice(), dynamic.as_slice());
+    }
+}
+
+#[test]
+fn a_static_layer_checks_the_sizes_and_the_activation_function() {
+    let network = xor();
+    let hidden = network.layer(0).unwrap().as_dense().unwrap();
+
+    assert!(matches!(
+        StaticLayer::<3, 2, Sigmoid>::try_from(hidden),
+        Err(LayerError::ParameterShapeMismatch { layer_input_size: 2, layer_output_size: 2, given_input_size: 3, given_output_size: 2 })
+    ));
+    assert!(matches!(
+        StaticLayer::<2, 2, ReLU>::try_from(hidden),
+        Err(LayerError::ActivationMismatch { expected: "relu", found: "sigmoid" })
+    ));
+}
@@ -0,0 +1,119 @@
+mod common;
+
+use rand::{distr::Uniform, rngs::StdRng, SeedableRng};
+
+use neural::{
+    activations::*,
+    dataset::Sample,
+    losses::MSE,
+    network::{Network, NetworkError, NonFiniteKind},
+};
+
+use common::xor;
+
+/// A linear network, which diverges instead of saturating when the rate is too high.
+fn network() -> Network {
+    Network::random_with_rng(&[2, 4, 1], identity!(), &Uniform::new(-1.0, 1.0).unwrap(), &mut StdRng::seed_from_u64(1)).unwrap()
+}
+
+#[test]
+fn learn_silently_diverges_to_nan() {
+    let mut network = network();
+
+    for _ in 0..10 {
+        network.learn(&xor(), &MSE, 10.0).unwrap();
+    }
+
+    assert!(!network.is_finite());
+    assert!(network.learn(&xor(), &MSE, 10.0).unwrap().is_nan());
+}
+
+#[test]
+fn learn_checked_stops_at_the_first_non_finite_loss() {
+    let mut network = network();
+    let mut steps = 0;
+
+    let (error, before) = loop {
+        let before = network.get_params();
+        match network.learn_checked(&xor(), &MSE, 10.0) {
+            Ok(loss) => assert!(loss.is_finite()),
+            Err(error) => break (error, before),
+        }
+        steps += 1;
+        assert!(steps < 10, "training didn't diverge");
+    };
+
+    assert!(matches!(error, NetworkError::NonFiniteValue { layer: None, kind: NonFiniteKind::Loss }));
+    assert_eq!(error.to_string(), "a non-finite loss appeared");
+
+    // The refused step changed nothing, so every retry fails the same way.
+    assert!(network.is_finite());
+    assert_eq!(network.get_params(), before);
+    assert!(network.dense_layers().all(|layer| layer.weight_gradient().iter().all(|&gradient| gradient == 0.0)));
+    assert!(matches!(network.learn_checked(&xor(), &MSE, 10.0), Err(NetworkError::NonFiniteValue { .. })));
+    assert_eq!(network.get_params(), before);
+}
+
+#[test]
+fn an_overflowing_update_names_its_layer() {
+    let mut network = network();
+    let before = network.get_params();
+
+    let error = network.learn_checked(&xor(), &MSE, f32::MAX).unwrap_err();
+    assert!(matches!(error, NetworkError::NonFiniteValue { layer: Some(0), kind: NonFiniteKind::Weight }));
+    assert_eq!(error.to_string(), "a non-finite weight appeared in layer 0");
+    assert_eq!(network.get_params(), before);
+
+    // Frozen layers aren't updated, so the check moves on to the next one.
+    network.freeze_layers(0..1).unwrap();
+    assert!(matches!(
+        network.learn_checked(&xor(), &MSE, f32::MAX),
+        Err(NetworkError::NonFiniteValue { layer: Some(1), .. })
+    ));
+    assert_eq!(network.get_params(), before);
+}
+
+#[test]
+fn learn_checked_steps_like_learn_while_everything_is_finite() {
+    let (mut plain, mut checked) = (network(), network());
+
+    for _ in 0..50 {
+        let loss = plain.learn(&xor(), &MSE, 0.05).unwrap();
+        assert_eq!(checked.learn_checked(&xor(), &MSE, 0.05).unwrap(), loss);
+    }
+
+    assert_eq!(checked.get_params(), plain.get_params());
+    assert_eq!(checked.learn_checked(&[], &MSE, 0.05).unwrap(), 0.0);
+}
+
+#[test]
+fn an_infinite_target_is_caught_before_the_step() {
+    let mut network = network();
+    let before = network.get_params();
+    let dataset = [Sample::from_slices(&[1.0, 0.0], &[f32::INFINITY])];
+
+    assert!(matches!(
+        network.learn_checked(&dataset, &MSE, 0.1),
+        Err(NetworkError::NonFiniteValue { layer: None, kind: NonFiniteKind::Loss })
+    ));
+    assert_eq!(network.get_params(), before);
+}
+
+#[test]
+fn is_finite_looks_at_every_parameter() {
+    let mut network = network();
+    assert!(network.is_finite());
+
+    for value in [f32::NAN, f32::INFINITY, f32::NEG_INFINITY] {
+        let mut params = network.get_params();
+        let last = params.len() - 1;
+        params[last] = value;
+
+        let mut broken = network.clone();
+        broken.set_params(&params).unwrap();
+        assert!(!broken.is_finite());
+    }
+
+    network.learn(&xor(), &MSE, 0.1).unwrap();
+    assert!(network.is_finite());
+}
@@ -0,0 +1,116 @@
+use nalgebra::DVector;
+
+use neural::dataset::{DatasetError, Normalization, Normalizer, Sample};
+
+fn samples(rows: &[[f32; 3]]) -> Vec<Sample> {
+    rows.iter().map(|row| Sample::from_slices(row, &[row[0] + row[1]])).collect()
+}
+
+fn column(samples: &[Sample], column: usize) -> Vec<f32> {
+    samples.iter().map(|sample| sample.inputs()[column]).collect()
+}
+
+fn assert_close(actual: &[f32], expected: &[f32], tolerance: f32) {
+    assert_eq!(actual.len(), expected.len());
+    for (actual, expected) in actual.iter().zip(expected) {
+        assert!((actual - expected).abs() <= tolerance, "{actual:?} vs {expected:?}");
+    }
+}
+
+/// The middle column is constant.
+const ROWS: [[f32; 3]; 4] = [[0.0, 5.0, 400.0], [200.0, 5.0, -200.0], [800.0, 5.0, 100.0], [600.0, 5.0, 500.0]];
+
+#[test]
+fn min_max_maps_every_feature_to_zero_to_one_and_back() {
+    let samples = samples(&ROWS);
+    let normalizer = Normalizer::fit(&samples, Normalization::MinMax).unwrap();
+
+    let normalized = normalizer.normalized_inputs(&samples).unwrap();
+
+    assert_close(&column(&normalized, 0), &[0.0, 0.25, 1.0, 0.75], 1e-6);
+    assert_close(&column(&normalized, 2), &[6.0 / 7.0, 0.0, 3.0 / 7.0, 1.0], 1e-6);
+    for (sample, normalized) in samples.iter().zip(&normalized) {
+        let restored = normalizer.inverse(normalized.inputs()).unwrap();
+        assert_close(restored.as_slice(), sample.inputs().as_slice(), 1e-4);
+    }
+}
+
+#[test]
+fn z_score_centers_and_scales_every_feature_and_back() {
+    let mut samples = samples(&ROWS);
+    let original = samples.clone();
+    let normalizer = Normalizer::fit(&samples, Normalization::ZScore).unwrap();
+
+    normalizer.normalize_inputs(&mut samples).unwrap();
+
+    for index in [0, 2] {
+        let values = column(&samples, index);
+        let mean = values.iter().sum::<f32>() / 4.0;
+        let variance = values.iter().map(|value| (value - mean).powi(2)).sum::<f32>() / 4.0;
+        assert!(mean.abs() < 1e-6 && (variance - 1.0).abs() < 1e-5, "column {index}: {mean} {variance}");
+    }
+    for (sample, original) in samples.iter().zip(&original) {
+        let mut restored = sample.inputs().into_owned();
+        normalizer.inverse_mut(&mut restored).unwrap();
+        assert_close(restored.as_slice(), original.inputs().as_slice(), 1e-4);
+    }
+}
+
+#[test]
+fn a_constant_column_is_only_shifted() {
+    for normalization in [Normalization::MinMax, Normalization::ZScore] {
+        let samples = samples(&ROWS);
+        let normalizer = Normalizer::fit(&samples, normalization).unwrap();
+
+        assert_eq!(normalizer.scale()[1], 1.0);
+        assert_eq!(column(&normalizer.normalized_inputs(&samples).unwrap(), 1), [0.0; 4]);
+        // At inference a value off the constant is shifted by the same amount.
+        let transformed = normalizer.transform(DVector::from_row_slice(&[0.0, 7.0, 0.0]).as_view()).unwrap();
+        assert_eq!(transformed[1], 2.0);
+    }
+}
+
+#[test]
+fn rounding_noise_in_a_large_constant_column_isnt_a_spread() {
+    let samples = samples(&[[1e5, 0.0, 123_456.7], [2e5, 0.0, 123_456.7], [7e5, 0.0, 123_456.7]]);
+    let normalizer = Normalizer::fit(&samples, Normalization::ZScore).unwrap();
+
+    assert_eq!(normalizer.scale()[2], 1.0);
+    assert!(column(&normalizer.normalized_inputs(&samples).unwrap(), 2).iter().all(|value| value.abs() < 0.1));
+}
+
+#[test]
+fn tiny_features_are_still_scaled() {
+    let samples = samples(&[[1e-9, 0.0, 0.0], [2e-9, 0.0, 0.0], [3e-9, 0.0, 0.0]]);
+    let normalizer = Normalizer::fit(&samples, Normalization::MinMax).unwrap();
+
+    assert_close(&column(&normalizer.normalized_inputs(&samples).unwrap(), 0), &[0.0, 0.5, 1.0], 1e-5);
+}
+
+#[test]
+fn a_wrong_size_changes_nothing() {
+    let mut samples = samples(&ROWS);
+    let normalizer = Normalizer::fit(&samples, Normalization::MinMax).unwrap();
+    samples.push(Sample::from_slices(&[1.0, 2.0], &[3.0]));
+    let original = samples.clone();
+
+    assert!(matches!(
+        normalizer.normalize_inputs(&mut samples),
+        Err(DatasetError::NormalizerSizeMismatch { normalizer_size: 3, given_size: 2 })
+    ));
+    assert!(samples.iter().zip(&original).all(|(sample, original)| sample.inputs() == original.inputs()));
+    assert!(normalizer.transform(DVector::zeros(4).as_view()).is_err());
+    assert!(normalizer.inverse(DVector::zeros(2).as_view()).is_err());
+}
+
+#[test]
+fn fits_the_expected_outputs_too() {
+    let mut samples = samples(&ROWS);
+    let normalizer = Normalizer::fit_outputs(&samples, Normalization::MinMax).unwrap();
+
+    normalizer.normalize_outputs(&mut samples).unwrap();
+
+    let outputs: Vec<f32> = samples.iter().map(|sample| sample.expected_outputs()[0]).collect();
+    assert_close(&outputs, &[0.0, 0.25, 1.0, 0.75], 1e-6);
+    assert!(matches!(Normalizer::fit(&[], Normalization::ZScore), Err(DatasetError::EmptyDataset)));
+}
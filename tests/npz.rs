@@ -0,0 +1,28 @@
+#![cfg(feature = "npz")]
+
+use nalgebra::{DMatrix, DVector};
+
+use neural::{activations::*, network::{npz::NpzError, Network}};
+
+const FIXTURES: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/npz");
+
+#[test]
+fn loads_a_fortran_order_zip64_archive_like_numpy_writes() {
+    let network = Network::import_npz(format!("{FIXTURES}/fortran_savez.npz"), &[2, 3, 1], vec![relu!(), identity!()]).unwrap();
+
+    let first = network.layer(0).unwrap().as_dense().unwrap();
+    assert_eq!(first.weights(), DMatrix::from_row_slice(3, 2, &[0.5, -1.0, 0.25, 2.0, -0.75, 1.5]));
+    assert_eq!(first.biases(), DVector::from_vec(vec![0.1, -0.2, 0.3]));
+    assert!(!network.layer(1).unwrap().as_dense().unwrap().has_bias());
+
+    // relu([0.5 - 2, 0.25 + 4, -0.75 + 3] + biases) = [0, 4.05, 2.55], then [1, -0.5, 0.25].
+    let outputs = network.predict(DVector::from_vec(vec![1.0, 2.0]).as_view()).unwrap();
+    assert!((outputs[0] - (-4.05 * 0.5 + 2.55 * 0.25)).abs() < 1e-6);
+}
+
+#[test]
+fn rejects_a_shape_whose_size_overflows() {
+    let result = Network::import_npz(format!("{FIXTURES}/overflowing_shape.npz"), &[2, 1], vec![identity!()]);
+
+    assert!(matches!(result, Err(NpzError::InvalidArray(name)) if name == "layer0_weights"));
+}
@@ -0,0 +1,116 @@
+use nalgebra::DVector;
+use rand::{distr::Uniform, rngs::StdRng, SeedableRng};
+
+use neural::{
+    activations::*,
+    dataset::Sample,
+    losses::MSE,
+    network::Network,
+    training::{
+        schedule::{Annealing, OneCycle},
+        Trainer, TrainingError,
+    },
+};
+
+fn rates(schedule: &OneCycle) -> Vec<f32> {
+    (0..schedule.total_steps()).map(|step| schedule.rate_at(step).unwrap()).collect()
+}
+
+/// Equal up to f32 rounding of rates around 1.
+fn close(a: f32, b: f32) -> bool {
+    (a - b).abs() <= 1e-6 * a.abs().max(b.abs()).max(1.0)
+}
+
+#[test]
+fn the_rate_rises_to_the_peak_then_falls_to_the_final_rate() {
+    // 11 steps put step 3 exactly at the end of the 30% warmup.
+    let schedule = OneCycle::new(1.0, 11).unwrap();
+    let rates = rates(&schedule);
+
+    assert_eq!(schedule.initial_rate(), 0.04);
+    assert!(close(schedule.final_rate(), 4e-6));
+    assert!(close(rates[0], schedule.initial_rate()));
+    assert_eq!(rates[3], 1.0);
+    assert!(close(rates[10], schedule.final_rate()));
+
+    assert!(rates[..4].windows(2).all(|pair| pair[0] < pair[1]), "{rates:?}");
+    assert!(rates[3..].windows(2).all(|pair| pair[0] > pair[1]), "{rates:?}");
+    assert!(rates.iter().all(|&rate| rate <= 1.0));
+}
+
+#[test]
+fn cosine_annealing_is_flat_at_the_ends_and_linear_isnt() {
+    let (max_rate, steps) = (2.0, 101);
+    let cosine = rates(&OneCycle::new(max_rate, steps).unwrap().warmup(0.5).unwrap().initial_div(2.0).unwrap());
+    let linear = rates(&OneCycle::new(max_rate, steps).unwrap().warmup(0.5).unwrap().initial_div(2.0).unwrap().annealing(Annealing::Linear));
+
+    // Halfway through the warmup both are halfway between the initial and the maximum rate.
+    assert!(close(cosine[25], 1.5) && close(linear[25], 1.5), "{} {}", cosine[25], linear[25]);
+
+    // The linear warmup rises by the same amount every step.
+    assert!(linear[..51].windows(2).all(|pair| close(pair[1] - pair[0], 0.02)));
+
+    // The cosine rises slowly at the ends and fastest in the middle, by pi / 2 times the linear slope.
+    let rise = |step: usize| cosine[step + 1] - cosine[step];
+    assert!(rise(0) < 0.05 * rise(25) && rise(49) < 0.05 * rise(25));
+    assert!((rise(25) / 0.02 - std::f32::consts::FRAC_PI_2).abs() < 2e-3);
+
+    // The descent mirrors that, ending at the final rate.
+    assert!(cosine[99] - cosine[100] < 0.05 * (cosine[75] - cosine[76]));
+    assert!(close(cosine[100], 1.0 / 1e4));
+    assert!(close(linear[75], (2.0 + 1e-4) / 2.0));
+}
+
+#[test]
+fn next_rate_walks_the_budget_then_fails() {
+    let mut schedule = OneCycle::new(0.5, 4).unwrap();
+    let expected = rates(&schedule);
+
+    let walked: Vec<f32> = (0..4).map(|_| schedule.next_rate().unwrap()).collect();
+    assert_eq!(walked, expected);
+    assert_eq!(schedule.steps_taken(), 4);
+
+    assert!(matches!(schedule.next_rate(), Err(TrainingError::ScheduleExhausted { step: 4, total_steps: 4 })));
+    assert!(matches!(schedule.rate_at(10), Err(TrainingError::ScheduleExhausted { step: 10, total_steps: 4 })));
+
+    // A single step is all warmup start.
+    assert_eq!(OneCycle::new(0.5, 1).unwrap().rate_at(0).unwrap(), 0.02);
+}
+
+#[test]
+fn the_trainer_takes_every_batch_rate_from_the_schedule() {
+    let schedule = OneCycle::new(0.5, 20).unwrap();
+    let mut network = Network::random_with_rng(&[1, 2, 1], sigmoid!(), &Uniform::new(-1.0, 1.0).unwrap(), &mut StdRng::seed_from_u64(50)).unwrap();
+    let dataset = vec![Sample::new(DVector::from_element(1, 0.5), DVector::from_element(1, 0.2)); 2];
+
+    // 2 batches per epoch, so 10 epochs use the budget and the 11th doesn't fit.
+    let report = Trainer::new(MSE, 100.0, 15)
+        .batch_size(1)
+        .one_cycle(schedule)
+        .fit_with_rng(&mut network, &dataset, &[], &mut StdRng::seed_from_u64(0))
+        .unwrap();
+
+    assert!(report.stopped_early);
+    assert_eq!(report.batches, 20);
+    // Every epoch records the rate of its last batch.
+    let expected: Vec<f32> = (0..10).map(|epoch| schedule.rate_at(2 * epoch + 1).unwrap()).collect();
+    assert_eq!(report.history.learning_rates(), expected);
+}
+
+#[test]
+fn invalid_schedules_are_rejected() {
+    for (schedule, part) in [
+        (OneCycle::new(0.0, 10), "maximum rate"),
+        (OneCycle::new(f32::INFINITY, 10), "maximum rate"),
+        (OneCycle::new(0.1, 0), "step budget"),
+        (OneCycle::new(0.1, 10).unwrap().warmup(1.0), "warmup fraction"),
+        (OneCycle::new(0.1, 10).unwrap().warmup(-0.1), "warmup fraction"),
+        (OneCycle::new(0.1, 10).unwrap().initial_div(0.5), "initial divisor"),
+        (OneCycle::new(0.1, 10).unwrap().final_div(f32::NAN), "final divisor"),
+    ] {
+        assert!(matches!(schedule, Err(TrainingError::InvalidSchedule(rejected)) if rejected == part), "{part}");
+    }
+
+    // Without warmup the cycle starts at the peak.
+    assert_eq!(OneCycle::new(0.1, 10).unwrap().warmup(0.0).unwrap().rate_at(0).unwrap(), 0.1);
+}
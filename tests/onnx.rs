@@ -0,0 +1,239 @@
+#![cfg(feature = "onnx")]
+
+mod common;
+
+use nalgebra::DMatrix;
+use rand::{distr::Uniform, rngs::StdRng, SeedableRng};
+
+use neural::{
+    activations::*,
+    losses::MSE,
+    network::{layer::Layer, onnx::OnnxExportError, Network},
+    training::Trainer,
+};
+
+use common::xor;
+
+/// A decoded protobuf field, enough of the wire format for what `to_onnx` writes.
+#[derive(Clone, Debug)]
+enum Field {
+    Varint(u64),
+    Bytes(Vec<u8>),
+}
+
+fn varint(bytes: &[u8], position: &mut usize) -> u64 {
+    let mut value = 0;
+    for shift in (0..).step_by(7) {
+        let byte = bytes[*position];
+        *position += 1;
+        value |= u64::from(byte & 0x7f) << shift;
+        if byte < 0x80 {
+            return value;
+        }
+    }
+    unreachable!()
+}
+
+/// Every field of a message in order, as (field number, value).
+fn parse(bytes: &[u8]) -> Vec<(u64, Field)> {
+    let mut fields = Vec::new();
+    let mut position = 0;
+
+    while position < bytes.len() {
+        let key = varint(bytes, &mut position);
+        let field = match key & 7 {
+            0 => Field::Varint(varint(bytes, &mut position)),
+            2 => {
+                let len = varint(bytes, &mut position) as usize;
+                position += len;
+                Field::Bytes(bytes[position - len..position].to_vec())
+            }
+            wire_type => panic!("unexpected wire type {wire_type}"),
+        };
+        fields.push((key >> 3, field));
+    }
+
+    fields
+}
+
+fn messages(fields: &[(u64, Field)], number: u64) -> Vec<Vec<(u64, Field)>> {
+    fields
+        .iter()
+        .filter_map(|(field, value)| match value {
+            Field::Bytes(bytes) if *field == number => Some(parse(bytes)),
+            _ => None,
+        })
+        .collect()
+}
+
+fn strings(fields: &[(u64, Field)], number: u64) -> Vec<String> {
+    fields
+        .iter()
+        .filter_map(|(field, value)| match value {
+            Field::Bytes(bytes) if *field == number => Some(String::from_utf8(bytes.clone()).unwrap()),
+            _ => None,
+        })
+        .collect()
+}
+
+fn varints(fields: &[(u64, Field)], number: u64) -> Vec<u64> {
+    fields
+        .iter()
+        .filter_map(|(field, value)| match value {
+            Field::Varint(value) if *field == number => Some(*value),
+            _ => None,
+        })
+        .collect()
+}
+
+/// An initializer's name, dimensions and values.
+fn tensor(fields: &[(u64, Field)]) -> (String, Vec<usize>, Vec<f32>) {
+    assert_eq!(varints(fields, 2), [1], "initializers are f32");
+    let raw = match &fields.iter().find(|(field, _)| *field == 9).unwrap().1 {
+        Field::Bytes(bytes) => bytes.chunks(4).map(|chunk| f32::from_le_bytes(chunk.try_into().unwrap())).collect(),
+        Field::Varint(_) => panic!("raw data is bytes"),
+    };
+
+    (strings(fields, 8).remove(0), varints(fields, 1).into_iter().map(|dim| dim as usize).collect(), raw)
+}
+
+fn trained() -> Network {
+    let hidden = Layer::random_with_rng(2, 3, tanh!(), &Uniform::new(-1.0, 1.0).unwrap(), &mut StdRng::seed_from_u64(30)).unwrap();
+    let output = Layer::random_with_rng(3, 1, sigmoid!(), &Uniform::new(-1.0, 1.0).unwrap(), &mut StdRng::seed_from_u64(31)).unwrap();
+    let mut network = Network::from_layers(vec![hidden, output]).unwrap();
+    Trainer::new(MSE, 1.0, 100).fit(&mut network, &xor(), &[]).unwrap();
+    network
+}
+
+#[test]
+fn the_graph_has_a_matmul_add_and_activation_per_layer() {
+    let model = parse(&trained().to_onnx().unwrap());
+    assert_eq!(varints(&model, 1), [8]);
+    let opset = messages(&model, 8).remove(0);
+    assert_eq!((strings(&opset, 1), varints(&opset, 2)), (vec![String::new()], vec![13]));
+
+    let graph = messages(&model, 7).remove(0);
+    let nodes = messages(&graph, 1);
+    let operators: Vec<String> = nodes.iter().map(|node| strings(node, 4).remove(0)).collect();
+    assert_eq!(operators, ["MatMul", "Add", "Tanh", "MatMul", "Add", "Sigmoid", "Identity"]);
+    // Every node reads the output of the one before it.
+    for pair in nodes.windows(2) {
+        assert_eq!(strings(&pair[1], 1)[0], strings(&pair[0], 2)[0]);
+    }
+    assert_eq!(strings(&nodes[0], 1), ["input", "layer0.weight"]);
+    assert_eq!(strings(&nodes[6], 2), ["output"]);
+
+    let dims = |value_info: &[(u64, Field)]| {
+        let shape = messages(&messages(&messages(value_info, 2)[0], 1)[0], 2).remove(0);
+        messages(&shape, 1).iter().map(|dim| varints(dim, 1)).collect::<Vec<_>>()
+    };
+    assert_eq!(dims(&messages(&graph, 11)[0]), [vec![], vec![2]]);
+    assert_eq!(dims(&messages(&graph, 12)[0]), [vec![], vec![1]]);
+}
+
+#[test]
+fn the_initializers_are_the_transposed_weights_and_the_biases() {
+    let network = trained();
+    let graph = messages(&parse(&network.to_onnx().unwrap()), 7).remove(0);
+    let initializers: Vec<_> = messages(&graph, 5).iter().map(|fields| tensor(fields)).collect();
+    assert_eq!(initializers.len(), 4);
+
+    for (index, layer) in network.dense_layers().enumerate() {
+        let (name, dims, values) = &initializers[2 * index];
+        assert_eq!((name.clone(), dims.clone()), (format!("layer{index}.weight"), vec![layer.input_size(), layer.output_size()]));
+        for input in 0..layer.input_size() {
+            for output in 0..layer.output_size() {
+                assert_eq!(values[input * layer.output_size() + output], *layer.get_weight(input, output).unwrap());
+            }
+        }
+
+        let (name, dims, values) = &initializers[2 * index + 1];
+        assert_eq!((name.clone(), dims.clone()), (format!("layer{index}.bias"), vec![layer.output_size()]));
+        assert_eq!(values.as_slice(), layer.biases().as_slice());
+    }
+}
+
+/// Runs the exported graph on one input, `[1, inputs] × [inputs, outputs] + biases` and the activation per layer.
+fn run(onnx: &[u8], input: &[f32]) -> Vec<f32> {
+    let graph = messages(&parse(onnx), 7).remove(0);
+    let initializers: Vec<_> = messages(&graph, 5).iter().map(|fields| tensor(fields)).collect();
+    let mut values = DMatrix::from_row_slice(1, input.len(), input);
+
+    for node in messages(&graph, 1) {
+        let inputs = strings(&node, 1);
+        let find = |name: &str| initializers.iter().find(|(initializer, ..)| initializer == name).unwrap();
+        values = match strings(&node, 4)[0].as_str() {
+            "MatMul" => {
+                let (_, dims, data) = find(&inputs[1]);
+                values * DMatrix::from_row_slice(dims[0], dims[1], data)
+            }
+            "Add" => {
+                let (_, _, biases) = find(&inputs[1]);
+                values + DMatrix::from_row_slice(1, biases.len(), biases)
+            }
+            "Tanh" => values.map(f32::tanh),
+            "Sigmoid" => values.map(|x| 1.0 / (1.0 + (-x).exp())),
+            "Relu" => values.map(|x| x.max(0.0)),
+            "Identity" => values,
+            operator => panic!("unexpected operator {operator}"),
+        };
+    }
+
+    values.iter().copied().collect()
+}
+
+#[test]
+fn the_graph_computes_what_the_network_predicts() {
+    let network = trained();
+    let mut normalized = Network::random_with_rng(&[2, 4, 1], relu!(), &Uniform::new(-1.0, 1.0).unwrap(), &mut StdRng::seed_from_u64(32))
+        .unwrap()
+        .with_batch_norm();
+    // A few training steps move the running statistics the export folds in.
+    Trainer::new(MSE, 0.1, 5).fit(&mut normalized, &xor(), &[]).unwrap();
+
+    for network in [network, normalized] {
+        let onnx = network.to_onnx().unwrap();
+        for sample in xor() {
+            let expected = network.predict(sample.inputs()).unwrap();
+            let actual = run(&onnx, sample.inputs().as_slice());
+            assert!((actual[0] - expected[0]).abs() < 1e-5, "{actual:?} vs {expected}");
+        }
+    }
+}
+
+#[test]
+fn export_writes_the_model_to_the_file() {
+    let network = trained();
+    let path = std::env::temp_dir().join(format!("neural-onnx-{}.onnx", std::process::id()));
+
+    network.export_onnx(&path).unwrap();
+
+    assert_eq!(std::fs::read(&path).unwrap(), network.to_onnx().unwrap());
+    std::fs::remove_file(path).unwrap();
+}
+
+#[derive(Clone)]
+struct Swish;
+impl ActivationFn for Swish {
+    fn apply(&self, x: f32) -> f32 {
+        x * Sigmoid.apply(x)
+    }
+
+    fn derivative(&self, x: f32, activation: f32) -> f32 {
+        let sigmoid: f32 = Sigmoid.apply(x);
+        sigmoid + activation * (1.0 - sigmoid)
+    }
+
+    fn name(&self) -> &'static str {
+        "swish"
+    }
+}
+
+#[test]
+fn activations_without_an_operator_are_rejected() {
+    let hidden = Layer::zeros(2, 3, identity!()).unwrap();
+    let output = Layer::zeros(3, 1, Box::new(Swish)).unwrap();
+    let network = Network::from_layers(vec![hidden, output]).unwrap();
+
+    assert!(matches!(network.to_onnx(), Err(OnnxExportError::UnsupportedActivation { layer: 1, name }) if name == "swish"));
+}
@@ -0,0 +1,84 @@
+#![cfg(feature = "rayon")]
+
+mod common;
+
+use rand::{distr::Uniform, rngs::StdRng, Rng, SeedableRng};
+
+use neural::{activations::*, dataset::Sample, losses::MSE, network::Network};
+
+use common::{assert_close, sample};
+
+fn network(seed: u64) -> Network<f64> {
+    Network::random_with_rng(&[4, 8, 8, 3], tanh!(), &Uniform::new(-0.5, 0.5).unwrap(), &mut StdRng::seed_from_u64(seed)).unwrap()
+}
+
+/// Enough samples that every thread of the pool gets a chunk of several, with varied weights.
+fn dataset(len: usize) -> Vec<Sample<f64>> {
+    let mut rng = StdRng::seed_from_u64(2);
+    (0..len)
+        .map(|i| {
+            let inputs: Vec<f64> = (0..4).map(|_| rng.random_range(-1.0..1.0)).collect();
+            let outputs = [inputs[0] * inputs[1], inputs[2].sin(), inputs[3] - inputs[0]];
+            sample(&inputs, &outputs).with_weight((i % 3) as f32 * 0.5 + 0.5)
+        })
+        .collect()
+}
+
+#[test]
+fn the_parallel_gradient_matches_the_sequential_one() {
+    for len in [1, 7, 2000] {
+        let dataset = dataset(len);
+        let mut sequential = network(1);
+        let mut parallel = network(1);
+
+        let sequential_loss = sequential.learn(&dataset, &MSE, 0.1).unwrap();
+        let parallel_loss = parallel.learn_parallel(&dataset, &MSE, 0.1).unwrap();
+
+        assert!((sequential_loss - parallel_loss).abs() < 1e-12, "{len} samples: {sequential_loss} vs {parallel_loss}");
+        assert_close(&parallel.get_params(), &sequential.get_params(), 1e-12);
+    }
+}
+
+#[test]
+fn the_parallel_loss_is_the_sequential_one() {
+    let dataset = dataset(500);
+    let mut sequential = network(3);
+    let mut parallel = network(3);
+
+    let sequential_loss = sequential.backpropagate(&dataset, &MSE).unwrap();
+    let parallel_loss = parallel.backpropagate_parallel(&dataset, &MSE).unwrap();
+
+    assert!((sequential_loss - parallel_loss).abs() < 1e-9 * sequential_loss.abs());
+}
+
+#[test]
+fn batch_norm_falls_back_to_the_sequential_path() {
+    let dataset = dataset(64);
+    let mut sequential = network(4).with_batch_norm();
+    let mut parallel = network(4).with_batch_norm();
+
+    sequential.learn(&dataset, &MSE, 0.1).unwrap();
+    parallel.learn_parallel(&dataset, &MSE, 0.1).unwrap();
+
+    assert_eq!(parallel.get_params(), sequential.get_params());
+}
+
+#[test]
+fn a_bad_sample_fails_without_touching_the_network() {
+    let mut dataset = dataset(100);
+    dataset[57] = sample(&[1.0, 2.0], &[0.0, 0.0, 0.0]);
+    let mut network = network(5);
+    let before = network.get_params();
+
+    assert!(network.learn_parallel(&dataset, &MSE, 0.1).is_err());
+    assert_eq!(network.get_params(), before);
+}
+
+#[test]
+fn an_empty_dataset_is_a_zero_loss() {
+    let mut network = network(6);
+    let before = network.get_params();
+
+    assert_eq!(network.learn_parallel(&[], &MSE, 0.1).unwrap(), 0.0);
+    assert_eq!(network.get_params(), before);
+}
@@ -0,0 +1,96 @@
+use nalgebra::{DMatrix, DVector};
+use rand::{distr::Uniform, rngs::StdRng, SeedableRng};
+
+use neural::{
+    activations::*,
+    network::{layer::Layer, Network, NetworkError},
+};
+
+fn network(sizes: &[usize]) -> Network {
+    Network::random_with_rng(sizes, sigmoid!(), &Uniform::new(-1.0, 1.0).unwrap(), &mut StdRng::seed_from_u64(1)).unwrap()
+}
+
+#[test]
+fn the_count_is_inputs_plus_one_times_outputs_per_layer() {
+    for sizes in [&[2, 50, 1][..], &[3, 3], &[784, 128, 64, 10], &[1, 1, 1, 1, 1]] {
+        let network = network(sizes);
+        let expected: usize = sizes.windows(2).map(|pair| (pair[0] + 1) * pair[1]).sum();
+
+        assert_eq!(network.parameter_count(), expected, "{sizes:?}");
+        assert_eq!(network.num_parameters(), expected);
+        assert_eq!(network.get_params().len(), expected);
+    }
+}
+
+#[test]
+fn params_run_layer_by_layer_with_column_major_weights_then_biases() {
+    let mut first = Layer::zeros(2, 3, identity!()).unwrap();
+    first.set_weights(DMatrix::from_row_slice(3, 2, &[1.0, 2.0, 3.0, 4.0, 5.0, 6.0])).unwrap();
+    first.set_biases(DVector::from_vec(vec![7.0, 8.0, 9.0])).unwrap();
+    let mut second = Layer::zeros(3, 1, identity!()).unwrap();
+    second.set_weights(DMatrix::from_row_slice(1, 3, &[10.0, 11.0, 12.0])).unwrap();
+    second.set_biases(DVector::from_vec(vec![13.0])).unwrap();
+
+    let network = Network::from_layers(vec![first, second]).unwrap();
+    assert_eq!(network.get_params(), [1.0, 3.0, 5.0, 2.0, 4.0, 6.0, 7.0, 8.0, 9.0, 10.0, 11.0, 12.0, 13.0]);
+}
+
+#[test]
+fn get_then_set_is_the_identity() {
+    let mut network = network(&[2, 5, 3]);
+    let params = network.get_params();
+    let outputs = network.predict(DVector::from_vec(vec![0.3, -0.7]).as_view()).unwrap();
+
+    network.set_params(&params).unwrap();
+    assert_eq!(network.get_params(), params);
+    assert_eq!(network.predict(DVector::from_vec(vec![0.3, -0.7]).as_view()).unwrap(), outputs);
+
+    // Params moved to another network of the same shape make it compute the same function.
+    let mut other = Network::random_with_rng(&[2, 5, 3], sigmoid!(), &Uniform::new(-1.0, 1.0).unwrap(), &mut StdRng::seed_from_u64(2)).unwrap();
+    assert_ne!(other.get_params(), params);
+    other.set_params(&params).unwrap();
+    assert_eq!(other.predict(DVector::from_vec(vec![0.3, -0.7]).as_view()).unwrap(), outputs);
+}
+
+#[test]
+fn perturbing_one_parameter_changes_only_what_depends_on_it() {
+    let mut network = network(&[2, 3, 2]);
+    let input = DVector::from_vec(vec![0.5, -0.25]);
+    let outputs = network.predict(input.as_view()).unwrap();
+
+    // The bias of the second output is the last parameter, and only feeds that output.
+    let mut params = network.get_params();
+    *params.last_mut().unwrap() += 1.0;
+    network.set_params(&params).unwrap();
+    let shifted = network.predict(input.as_view()).unwrap();
+    assert_eq!(shifted[0], outputs[0]);
+    assert!(shifted[1] > outputs[1]);
+
+    // The weight from the first input to the first hidden neuron reaches both outputs.
+    let mut params = network.get_params();
+    params[0] += 1.0;
+    network.set_params(&params).unwrap();
+    let moved = network.predict(input.as_view()).unwrap();
+    assert!(moved[0] != shifted[0] && moved[1] != shifted[1]);
+
+    // With a zero input it has nothing to scale, so the outputs stay put.
+    let zero = DVector::zeros(2);
+    let before = network.predict(zero.as_view()).unwrap();
+    params[0] += 1.0;
+    network.set_params(&params).unwrap();
+    assert_eq!(network.predict(zero.as_view()).unwrap(), before);
+}
+
+#[test]
+fn wrong_lengths_are_rejected_with_both_counts() {
+    let mut network = network(&[2, 3, 1]);
+    let params = network.get_params();
+
+    for length in [0, 12, 14] {
+        let error = network.set_params(&vec![0.0; length]).unwrap_err();
+        assert!(matches!(error, NetworkError::ParameterCountMismatch { expected: 13, given } if given == length));
+        assert_eq!(error.to_string(), format!("the network has 13 parameters, but {length} were given"));
+    }
+
+    assert_eq!(network.get_params(), params);
+}
@@ -0,0 +1,75 @@
+use nalgebra::DVector;
+use rand::{distr::Uniform, rngs::StdRng, SeedableRng};
+
+use neural::{
+    activations::*,
+    dataset::{FeatureExpansion, Normalization, Sample},
+    losses::MSE,
+    network::Network,
+    pipeline::{Pipeline, PipelineError, Transform},
+};
+
+fn samples() -> Vec<Sample> {
+    (0..20)
+        .map(|i| {
+            let (x, y) = (i as f32 * 0.5, (i as f32 * 0.7).sin() * 10.0);
+            Sample::from_slices(&[x, y], &[x * y + 3.0])
+        })
+        .collect()
+}
+
+fn pipeline() -> Pipeline {
+    let network = Network::random_with_rng(&[5, 4, 1], tanh!(), &Uniform::new(-0.5, 0.5).unwrap(), &mut StdRng::seed_from_u64(1)).unwrap();
+    let transforms = vec![Transform::normalize(Normalization::ZScore, 2), FeatureExpansion::new(2, 2).unwrap().into()];
+
+    Pipeline::new(transforms, network).unwrap().with_output_normalization(Normalization::MinMax)
+}
+
+#[test]
+fn round_trips_through_json() {
+    let samples = samples();
+    let mut pipeline = pipeline();
+    pipeline.fit(&samples).unwrap();
+    pipeline.learn(&samples, &MSE, 0.1).unwrap();
+
+    let loaded = Pipeline::from_json(&pipeline.to_json().unwrap()).unwrap();
+
+    assert_eq!(loaded.transforms(), pipeline.transforms());
+    assert_eq!(loaded.output_normalizer(), pipeline.output_normalizer());
+    for sample in &samples {
+        assert_eq!(loaded.predict(sample.inputs()).unwrap(), pipeline.predict(sample.inputs()).unwrap());
+    }
+
+    let path = std::env::temp_dir().join(format!("neural-pipeline-{}.json", std::process::id()));
+    pipeline.save(&path).unwrap();
+    let loaded = Pipeline::load(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    let input = DVector::from_vec(vec![1.5, -2.0]);
+    assert_eq!(loaded.predict(input.as_view()).unwrap(), pipeline.predict(input.as_view()).unwrap());
+}
+
+/// The saved pipeline with the expansion's degree replaced.
+fn with_degree(degree: &str) -> String {
+    let json = pipeline().to_json().unwrap();
+    assert!(json.contains("\"degree\":2"));
+    json.replace("\"degree\":2", &format!("\"degree\":{degree}"))
+}
+
+#[test]
+fn checks_the_expansion_size_before_building_it() {
+    assert!(matches!(
+        Pipeline::from_json(&with_degree("1")),
+        Err(PipelineError::TransformSizeMismatch { transform: 1, output_size: 2, input_size: 5, .. })
+    ));
+    assert!(matches!(
+        Pipeline::from_json(&with_degree("3")),
+        Err(PipelineError::ExpansionTooLarge { transform: 1, input_size: 5, .. })
+    ));
+
+    // Far too many terms to build, this has to fail without trying.
+    assert!(matches!(
+        Pipeline::from_json(&with_degree("1000000000000")),
+        Err(PipelineError::ExpansionTooLarge { transform: 1, input_size: 5, .. })
+    ));
+}
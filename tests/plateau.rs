@@ -0,0 +1,118 @@
+mod common;
+
+use rand::{distr::Uniform, rngs::StdRng, SeedableRng};
+
+use neural::{
+    activations::*,
+    losses::MSE,
+    network::Network,
+    training::{schedule::ReduceLrOnPlateau, Trainer, TrainingError},
+};
+
+use common::xor;
+
+/// The rate after every epoch of the losses, starting from 1.
+fn rates(mut plateau: ReduceLrOnPlateau, losses: &[f32]) -> Vec<f32> {
+    let mut rate = 1.0;
+    losses
+        .iter()
+        .map(|&loss| {
+            rate = plateau.step(loss, rate);
+            rate
+        })
+        .collect()
+}
+
+#[test]
+fn the_rate_drops_after_patience_epochs_without_improvement() {
+    let plateau = ReduceLrOnPlateau::new(0.5, 2).unwrap();
+    let losses = [1.0, 0.9, 0.95, 0.95, 0.95, 0.95, 0.8, 0.85, 0.85];
+
+    assert_eq!(rates(plateau, &losses), [1.0, 1.0, 1.0, 0.5, 0.5, 0.25, 0.25, 0.25, 0.125]);
+}
+
+#[test]
+fn improvements_smaller_than_min_delta_dont_count() {
+    let mut plateau = ReduceLrOnPlateau::new(0.5, 2).unwrap().min_delta(0.01);
+    let losses = [1.0, 0.999, 0.998, 0.997, 0.996, 0.98];
+
+    assert_eq!(rates(plateau, &losses), [1.0, 1.0, 0.5, 0.5, 0.25, 0.25]);
+
+    plateau.step(1.0, 1.0);
+    plateau.step(0.999, 1.0);
+    assert_eq!(plateau.best(), Some(1.0));
+    plateau.step(0.98, 1.0);
+    assert_eq!(plateau.best(), Some(0.98));
+}
+
+#[test]
+fn the_cooldown_skips_epochs_after_a_reduction() {
+    let constant = [1.0; 9];
+
+    assert_eq!(rates(ReduceLrOnPlateau::new(0.5, 1).unwrap(), &constant[..4]), [1.0, 0.5, 0.25, 0.125]);
+    // Reductions at epochs 1, 4 and 7, the 2 epochs after each don't count.
+    assert_eq!(
+        rates(ReduceLrOnPlateau::new(0.5, 1).unwrap().cooldown(2), &constant),
+        [1.0, 0.5, 0.5, 0.5, 0.25, 0.25, 0.25, 0.125, 0.125]
+    );
+
+    // An improvement during the cooldown still counts as the new best.
+    let mut plateau = ReduceLrOnPlateau::new(0.5, 1).unwrap().cooldown(3);
+    assert_eq!(rates(plateau, &[1.0, 1.0, 0.5, 0.6]), [1.0, 0.5, 0.5, 0.5]);
+    plateau.step(1.0, 1.0);
+    plateau.step(1.0, 1.0);
+    plateau.step(0.5, 1.0);
+    assert_eq!(plateau.best(), Some(0.5));
+}
+
+#[test]
+fn the_rate_never_goes_below_the_floor() {
+    let plateau = ReduceLrOnPlateau::new(0.5, 1).unwrap().min_rate(0.3);
+
+    assert_eq!(rates(plateau, &[1.0; 6]), [1.0, 0.5, 0.3, 0.3, 0.3, 0.3]);
+}
+
+#[test]
+fn nan_losses_never_improve() {
+    let mut plateau = ReduceLrOnPlateau::new(0.5, 2).unwrap();
+    assert_eq!(rates(plateau, &[1.0, f32::NAN, f32::NAN, 0.5]), [1.0, 1.0, 0.5, 0.5]);
+    assert_eq!(rates(plateau, &[f32::NAN, f32::NAN]), [1.0, 0.5]);
+
+    plateau.step(f32::NAN, 1.0);
+    assert_eq!(plateau.best(), None);
+    plateau.step(2.0, 1.0);
+    plateau.step(f32::NAN, 1.0);
+    assert_eq!(plateau.best(), Some(2.0));
+
+    plateau.reset();
+    assert_eq!(plateau.best(), None);
+}
+
+#[test]
+fn the_trainer_records_every_reduction() {
+    let mut network = Network::random_with_rng(&[2, 3, 1], sigmoid!(), &Uniform::new(-1.0, 1.0).unwrap(), &mut StdRng::seed_from_u64(60)).unwrap();
+    // Frozen layers keep the loss on a plateau from the start.
+    network.freeze_layers(0..2).unwrap();
+
+    let report = Trainer::new(MSE, 0.8, 8)
+        .reduce_lr_on_plateau(ReduceLrOnPlateau::new(0.5, 2).unwrap().min_rate(0.1))
+        .fit_with_rng(&mut network, &xor(), &xor(), &mut StdRng::seed_from_u64(0))
+        .unwrap();
+
+    assert_eq!(report.history.learning_rates(), [0.8, 0.8, 0.8, 0.4, 0.4, 0.2, 0.2, 0.1]);
+    assert_eq!(report.history.rate_reductions(), [3, 5, 7]);
+
+    // Every fit starts over from the configured rate.
+    let report = Trainer::new(MSE, 0.8, 2)
+        .reduce_lr_on_plateau(ReduceLrOnPlateau::new(0.5, 1).unwrap())
+        .fit_with_rng(&mut network, &xor(), &[], &mut StdRng::seed_from_u64(0))
+        .unwrap();
+    assert_eq!(report.history.learning_rates(), [0.8, 0.8]);
+}
+
+#[test]
+fn the_factor_has_to_be_between_zero_and_one() {
+    for factor in [0.0, 1.0, -0.5, 1.5, f32::NAN] {
+        assert!(matches!(ReduceLrOnPlateau::new(factor, 2), Err(TrainingError::InvalidSchedule("reduction factor"))), "{factor}");
+    }
+}
@@ -0,0 +1,89 @@
+use nalgebra::DVector;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use neural::{
+    activations::ActivationFn,
+    dataset::Sample,
+    losses::{verify_gradient, LossFn, PoissonLoss},
+    network::{layer::Layer, Network},
+    training::Trainer,
+};
+
+fn loss(output: &[f32], expected: &[f32]) -> (f32, Vec<f32>) {
+    let (output, expected) = (DVector::from_column_slice(output), DVector::from_column_slice(expected));
+
+    (
+        PoissonLoss.apply(output.as_view(), expected.as_view()).unwrap(),
+        PoissonLoss.partial_gradient(output.as_view(), expected.as_view()).unwrap().as_slice().to_vec(),
+    )
+}
+
+#[test]
+fn a_count_of_zero_only_costs_the_rate() {
+    assert_eq!(loss(&[2.5, 0.5], &[0.0, 0.0]), (1.5, vec![0.5, 0.5]));
+}
+
+#[test]
+fn outputs_at_or_below_zero_stay_finite() {
+    for output in [0.0, 1e-12, -3.0] {
+        let (value, gradient) = loss(&[output], &[2.0]);
+        assert!(value.is_finite() && gradient[0].is_finite(), "{output}: {value} {gradient:?}");
+        // Clamped at 1e-7, the rate is pushed up hard.
+        assert!(gradient[0] < -1e6, "{output}: {gradient:?}");
+    }
+}
+
+#[test]
+fn the_gradient_matches_central_differences() {
+    for (output, expected) in [([0.5, 2.0, 7.5], [0.0, 1.0, 4.0]), ([0.05, 1.0, 20.0], [1.0, 0.0, 25.0])] {
+        let (output, expected) = (DVector::from_row_slice(&output), DVector::from_row_slice(&expected));
+        verify_gradient(&PoissonLoss, output.as_view(), expected.as_view(), 1e-2).unwrap();
+    }
+}
+
+/// `exp(x)`, a positive activation for predicted rates.
+#[derive(Clone)]
+struct Exp;
+impl ActivationFn for Exp {
+    fn apply(&self, x: f32) -> f32 {
+        x.exp()
+    }
+
+    fn derivative(&self, _x: f32, activation: f32) -> f32 {
+        activation
+    }
+
+    fn name(&self) -> &'static str {
+        "exp"
+    }
+}
+
+/// A Poisson draw by counting uniform factors until their product drops below `exp(-rate)`.
+fn poisson(rate: f32, rng: &mut impl Rng) -> f32 {
+    let (limit, mut product, mut count) = ((-rate).exp(), rng.random::<f32>(), 0.0);
+    while product > limit {
+        product *= rng.random::<f32>();
+        count += 1.0;
+    }
+
+    count
+}
+
+#[test]
+fn fits_the_rate_of_counts() {
+    let mut rng = StdRng::seed_from_u64(3);
+    let dataset: Vec<Sample> = (0..2000)
+        .map(|_| {
+            let x: f32 = rng.random_range(0.0..1.0);
+            Sample::from_slices(&[x], &[poisson((1.0 + x).exp(), &mut rng)])
+        })
+        .collect();
+
+    let mut network = Network::from_layers(vec![Layer::zeros(1, 1, Box::new(Exp)).unwrap()]).unwrap();
+    Trainer::new(PoissonLoss, 0.05, 200).batch_size(50).shuffle(true).fit_with_rng(&mut network, &dataset, &[], &mut rng).unwrap();
+
+    // The rate is exp(x + 1), so the layer's weight and bias should both be close to 1.
+    let layer = network.layer(0).unwrap().as_dense().unwrap();
+    let (weight, bias) = (layer.weights()[(0, 0)], layer.biases()[0]);
+    assert!((weight - 1.0).abs() < 0.15 && (bias - 1.0).abs() < 0.15, "{weight} {bias}");
+}
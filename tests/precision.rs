@@ -0,0 +1,69 @@
+mod common;
+
+use rand::{distr::Uniform, rngs::StdRng, SeedableRng};
+
+use neural::{activations::*, dataset::Sample, losses::MSE, network::Network};
+
+use common::{sample, xor};
+
+/// The same seeded XOR network in both precisions.
+fn networks() -> (Network, Network<f64>) {
+    let single = Network::random_with_rng(&[2, 4, 1], sigmoid!(), &Uniform::new(-1.0, 1.0).unwrap(), &mut StdRng::seed_from_u64(1)).unwrap();
+    let mut double = Network::<f64>::zeros(&[2, 4, 1], sigmoid!()).unwrap();
+    double.set_params(&single.get_params().into_iter().map(f64::from).collect::<Vec<_>>()).unwrap();
+    (single, double)
+}
+
+fn xor_f64() -> Vec<Sample<f64>> {
+    let widen = |values: &[f32]| values.iter().copied().map(f64::from).collect::<Vec<_>>();
+    xor().iter().map(|sample| self::sample(&widen(sample.inputs().as_slice()), &widen(sample.expected_outputs().as_slice()))).collect()
+}
+
+#[test]
+fn both_precisions_follow_the_same_trajectory() {
+    let (mut single, mut double) = networks();
+
+    for _ in 0..1000 {
+        single.learn(&xor(), &MSE, 2.0).unwrap();
+        double.learn(&xor_f64(), &MSE, 2.0).unwrap();
+    }
+
+    let single_loss = f64::from(single.evaluate(&xor(), &MSE).unwrap());
+    let double_loss = double.evaluate(&xor_f64(), &MSE).unwrap();
+    assert!(double_loss < 0.01, "{double_loss}");
+    assert!((single_loss / double_loss - 1.0).abs() < 1e-3, "{single_loss} vs {double_loss}");
+}
+
+#[test]
+fn f64_keeps_making_progress_where_f32_updates_round_away() {
+    let (mut single, mut double) = networks();
+    for _ in 0..2000 {
+        single.learn(&xor(), &MSE, 2.0).unwrap();
+        double.learn(&xor_f64(), &MSE, 2.0).unwrap();
+    }
+
+    // Fine-tuning with a small rate takes steps below the resolution of most f32 weights.
+    let single_start = f64::from(single.evaluate(&xor(), &MSE).unwrap());
+    let double_start = double.evaluate(&xor_f64(), &MSE).unwrap();
+    for _ in 0..2000 {
+        single.learn(&xor(), &MSE, 0.001).unwrap();
+        double.learn(&xor_f64(), &MSE, 0.001).unwrap();
+    }
+
+    let single_progress = single_start - f64::from(single.evaluate(&xor(), &MSE).unwrap());
+    let double_progress = double_start - double.evaluate(&xor_f64(), &MSE).unwrap();
+    assert!(double_progress > 0.0 && double_progress > 5.0 * single_progress, "{single_progress:e} vs {double_progress:e}");
+    assert!(double.evaluate(&xor_f64(), &MSE).unwrap() < f64::from(single.evaluate(&xor(), &MSE).unwrap()));
+}
+
+#[test]
+fn f32_is_the_default() {
+    // No type annotations are needed for the default precision.
+    let mut network = Network::zeros(&[2, 1], sigmoid!()).unwrap();
+    let loss: f32 = network.learn(&xor(), &MSE, 0.5).unwrap();
+    assert!(loss > 0.0);
+
+    let sample = Sample::from_slices(&[0.0, 1.0], &[1.0]);
+    let _: &Sample<f32> = &sample;
+    let _: Network<f32> = network;
+}
@@ -0,0 +1,83 @@
+mod common;
+
+use std::{sync::Arc, thread};
+
+use nalgebra::DVector;
+use rand::{distr::Uniform, rngs::StdRng, SeedableRng};
+
+use neural::{
+    activations::*,
+    losses::MSE,
+    network::{layer::LayerError, Network, NetworkError},
+};
+
+use common::xor;
+
+fn network(activation_fn: Box<dyn ActivationFn>) -> Network {
+    Network::random_with_rng(&[2, 8, 4, 1], activation_fn, &Uniform::new(-1.0, 1.0).unwrap(), &mut StdRng::seed_from_u64(3)).unwrap()
+}
+
+fn inputs() -> Vec<DVector<f32>> {
+    (0..20).map(|i| DVector::from_vec(vec![(i as f32 * 0.37).sin(), (i as f32 * 0.91).cos()])).collect()
+}
+
+#[test]
+fn predict_gives_exactly_what_forward_gives() {
+    let activation_fns: [Box<dyn ActivationFn>; 4] = [sigmoid!(), tanh!(), relu!(), identity!()];
+    for activation_fn in activation_fns {
+        let mut network = network(activation_fn);
+
+        for input in inputs() {
+            let predicted = network.predict(input.as_view()).unwrap();
+            assert_eq!(predicted, network.forward(input).unwrap());
+        }
+    }
+}
+
+#[test]
+fn predicting_between_steps_doesnt_change_training() {
+    let (mut plain, mut interleaved) = (network(sigmoid!()), network(sigmoid!()));
+
+    for _ in 0..20 {
+        plain.learn(&xor(), &MSE, 0.5).unwrap();
+
+        interleaved.learn(&xor(), &MSE, 0.5).unwrap();
+        for input in inputs() {
+            interleaved.predict(input.as_view()).unwrap();
+        }
+    }
+
+    assert_eq!(interleaved.get_params(), plain.get_params());
+}
+
+#[test]
+fn one_shared_network_predicts_from_several_threads() {
+    let network = Arc::new(network(tanh!()));
+    let expected: Vec<_> = inputs().iter().map(|input| network.predict(input.as_view()).unwrap()).collect();
+
+    let results: Vec<Vec<DVector<f32>>> = thread::scope(|scope| {
+        let handles: Vec<_> = (0..4)
+            .map(|_| {
+                let network = Arc::clone(&network);
+                scope.spawn(move || (0..50).flat_map(|_| inputs()).map(|input| network.predict(input.as_view()).unwrap()).collect())
+            })
+            .collect();
+        handles.into_iter().map(|handle| handle.join().unwrap()).collect()
+    });
+
+    for outputs in results {
+        for (outputs, expected) in outputs.chunks(20).flat_map(|chunk| chunk.iter().zip(&expected)) {
+            assert_eq!(outputs, expected);
+        }
+    }
+}
+
+#[test]
+fn inputs_of_the_wrong_size_are_rejected() {
+    let network = network(sigmoid!());
+
+    assert!(matches!(
+        network.predict(DVector::zeros(3).as_view()),
+        Err(NetworkError::LayerError(LayerError::InputSizeMismatch { layer_input_size: 2, given_input_size: 3 }))
+    ));
+}
@@ -0,0 +1,63 @@
+use nalgebra::DVector;
+use rand::{distr::Uniform, rngs::StdRng, Rng, SeedableRng};
+
+use neural::{
+    activations::*,
+    network::{Network, NetworkError},
+};
+
+fn network() -> Network {
+    Network::random_with_rng(&[16, 32, 8, 4], tanh!(), &Uniform::new(-0.5, 0.5).unwrap(), &mut StdRng::seed_from_u64(8)).unwrap()
+}
+
+fn inputs(len: usize) -> Vec<DVector<f32>> {
+    let mut rng = StdRng::seed_from_u64(9);
+    (0..len).map(|_| DVector::from_fn(16, |_, _| rng.random_range(-1.0..1.0))).collect()
+}
+
+fn forward_all(inputs: &[DVector<f32>]) -> Vec<DVector<f32>> {
+    let mut network = network();
+    inputs.iter().map(|input| network.forward(input.clone()).unwrap()).collect()
+}
+
+#[test]
+fn gives_what_forward_gives_for_every_input() {
+    let inputs = inputs(300);
+
+    assert_eq!(network().predict_all(&inputs).unwrap(), forward_all(&inputs));
+    assert!(network().predict_all(&[]).unwrap().is_empty());
+}
+
+#[test]
+fn reports_the_index_of_the_first_malformed_input() {
+    let mut inputs = inputs(300);
+    inputs[123] = DVector::zeros(15);
+    inputs[200] = DVector::zeros(17);
+
+    assert!(matches!(
+        network().predict_all(&inputs),
+        Err(NetworkError::InputSizeMismatch { index: 123, inputs: 15, network_inputs: 16 })
+    ));
+}
+
+#[cfg(feature = "rayon")]
+#[test]
+fn the_parallel_outputs_are_the_sequential_ones_in_order() {
+    for len in [0, 1, 7, 300] {
+        let inputs = inputs(len);
+        assert_eq!(network().predict_all_parallel(&inputs).unwrap(), forward_all(&inputs), "{len} inputs");
+    }
+}
+
+#[cfg(feature = "rayon")]
+#[test]
+fn the_parallel_version_reports_the_first_malformed_input() {
+    let mut inputs = inputs(300);
+    inputs[250] = DVector::zeros(3);
+    inputs[40] = DVector::zeros(20);
+
+    assert!(matches!(
+        network().predict_all_parallel(&inputs),
+        Err(NetworkError::InputSizeMismatch { index: 40, inputs: 20, network_inputs: 16 })
+    ));
+}
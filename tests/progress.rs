@@ -0,0 +1,117 @@
+mod common;
+
+use std::time::Duration;
+
+use rand::{distr::Uniform, rngs::StdRng, SeedableRng};
+
+use neural::{
+    activations::*,
+    losses::MSE,
+    network::Network,
+    training::{
+        callback::{BatchContext, EpochContext, TrainingCallback},
+        progress::ProgressReporter,
+        Trainer,
+    },
+};
+
+use common::xor;
+
+fn network() -> Network {
+    Network::random_with_rng(&[2, 3, 1], sigmoid!(), &Uniform::new(-1.0, 1.0).unwrap(), &mut StdRng::seed_from_u64(80)).unwrap()
+}
+
+fn batch(epoch: usize, batch: usize, batches: Option<usize>, epochs: Option<usize>) -> BatchContext {
+    BatchContext { epoch, batch, batches, epochs, loss: 0.125 }
+}
+
+fn epoch<'a>(network: &'a Network, epoch: usize, validation_loss: Option<f32>) -> EpochContext<'a> {
+    EpochContext { epoch, train_loss: 0.25, validation_loss, gradient_norm: 1.0, metrics: &[], network }
+}
+
+#[test]
+fn batch_lines_show_the_counts_a_bar_and_the_loss() {
+    let mut reporter = ProgressReporter::new(Vec::new());
+
+    let line = reporter.batch_line(&batch(0, 4, Some(10), Some(4)));
+    assert!(line.starts_with("epoch 1/4 [==                  ] batch 5/10 loss 0.125000 eta "), "{line}");
+
+    // Halfway through epoch 3 of 4 is 25 of 40 batches.
+    let line = reporter.batch_line(&batch(2, 4, Some(10), Some(4)));
+    assert!(line.starts_with("epoch 3/4 [============        ] batch 5/10 loss 0.125000 eta "), "{line}");
+
+    let line = reporter.batch_line(&batch(3, 9, Some(10), Some(4)));
+    assert!(line.starts_with("epoch 4/4 [====================] batch 10/10 loss 0.125000 eta 0.0s"), "{line}");
+}
+
+#[test]
+fn unknown_totals_spin_instead() {
+    let mut reporter = ProgressReporter::new(Vec::new());
+
+    let lines: Vec<String> = (0..5).map(|i| reporter.batch_line(&batch(1, i, None, None))).collect();
+    let spinner: Vec<char> = lines.iter().map(|line| line.chars().next().unwrap()).collect();
+    assert_eq!(spinner, ['/', '-', '\\', '|', '/']);
+    assert!(lines[2].starts_with("\\ epoch 2 batch 3 loss 0.125000 elapsed "), "{}", lines[2]);
+
+    // A known total is still shown.
+    let line = reporter.batch_line(&batch(0, 6, None, Some(3)));
+    assert!(line.starts_with("- epoch 1/3 batch 7 loss 0.125000 elapsed "), "{line}");
+    let line = reporter.batch_line(&batch(0, 6, Some(8), None));
+    assert!(line.starts_with("\\ epoch 1 batch 7/8 loss"), "{line}");
+}
+
+#[test]
+fn epoch_lines_show_the_losses() {
+    let network = network();
+    let mut reporter = ProgressReporter::new(Vec::new());
+    assert_eq!(reporter.epoch_line(&epoch(&network, 0, None)), "epoch 1: train loss 0.250000");
+
+    // The total comes from the batches seen.
+    reporter.batch_line(&batch(6, 0, Some(2), Some(9)));
+    assert_eq!(reporter.epoch_line(&epoch(&network, 6, Some(0.5))), "epoch 7/9: train loss 0.250000, validation loss 0.500000");
+}
+
+#[test]
+fn status_lines_are_throttled_but_epoch_lines_are_not() {
+    let network = network();
+    let mut throttled = ProgressReporter::new(Vec::new()).interval(Duration::from_secs(3600));
+    let mut every = ProgressReporter::new(Vec::new()).interval(Duration::ZERO);
+
+    for reporter in [&mut throttled, &mut every] {
+        for epoch_index in 0..2 {
+            for i in 0..50 {
+                reporter.on_batch_end(&batch(epoch_index, i, Some(50), Some(2)));
+            }
+            assert!(reporter.on_epoch_end(&epoch(&network, epoch_index, None)).is_continue());
+        }
+    }
+
+    let count = |output: Vec<u8>, pattern: &str| String::from_utf8(output).unwrap().matches(pattern).count();
+    let (throttled, every) = (throttled.into_inner(), every.into_inner());
+
+    // The first batch after every epoch line draws again.
+    assert_eq!(count(throttled.clone(), " batch "), 2);
+    assert_eq!(count(every.clone(), " batch "), 100);
+    assert_eq!(count(throttled, "train loss"), 2);
+    assert_eq!(count(every, "\r\x1b[2Kepoch 2/2: train loss 0.250000\n"), 1);
+}
+
+#[test]
+fn the_trainer_drives_the_reporter() {
+    let mut output = Vec::new();
+    let mut network = network();
+
+    Trainer::new(MSE, 0.5, 3)
+        .batch_size(2)
+        .callback(ProgressReporter::new(&mut output).interval(Duration::ZERO))
+        .fit_with_rng(&mut network, &xor(), &xor(), &mut StdRng::seed_from_u64(0))
+        .unwrap();
+
+    let output = String::from_utf8(output).unwrap();
+    for epoch in 1..=3 {
+        assert!(output.contains(&format!("epoch {epoch}/3 [")), "{output}");
+        assert!(output.contains(&format!("epoch {epoch}/3: train loss ")), "{output}");
+    }
+    assert_eq!(output.matches("batch 2/2 loss").count(), 3);
+    assert_eq!(output.matches("validation loss").count(), 3);
+}
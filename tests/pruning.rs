@@ -0,0 +1,89 @@
+mod common;
+
+use nalgebra::{DMatrix, DVector};
+use rand::{distr::Uniform, rngs::StdRng, SeedableRng};
+
+use neural::{
+    activations::*,
+    losses::MSE,
+    network::{layer::Layer, LayerPruneReport, Network},
+};
+
+use common::xor;
+
+/// Weights `[0.05, -0.3; 0.0, 2.0]` and biases `[0.01, -0.5]`, then weights `[-0.08, 0.4]` and bias `0.02`.
+fn network() -> Network {
+    let mut first = Layer::zeros(2, 2, identity!()).unwrap();
+    first.set_weights(DMatrix::from_row_slice(2, 2, &[0.05, -0.3, 0.0, 2.0])).unwrap();
+    first.set_biases(DVector::from_vec(vec![0.01, -0.5])).unwrap();
+    let mut second = Layer::zeros(2, 1, identity!()).unwrap();
+    second.set_weights(DMatrix::from_row_slice(1, 2, &[-0.08, 0.4])).unwrap();
+    second.set_biases(DVector::from_vec(vec![0.02])).unwrap();
+    Network::from_layers(vec![first, second]).unwrap()
+}
+
+#[test]
+fn weights_below_the_threshold_become_exactly_zero() {
+    let mut network = network();
+    let report = network.prune(0.1);
+
+    assert_eq!(report.layers, [LayerPruneReport { pruned: 1, remaining: 2 }, LayerPruneReport { pruned: 1, remaining: 1 }]);
+    assert_eq!((report.pruned, report.remaining), (2, 3));
+    // The weight that was zero already counts as neither.
+    assert_eq!(network.get_params(), [0.0, 0.0, -0.3, 2.0, 0.01, -0.5, 0.0, 0.4, 0.02]);
+
+    // Pruning again finds nothing new.
+    let again = network.prune(0.1);
+    assert_eq!((again.pruned, again.remaining), (0, 3));
+}
+
+#[test]
+fn biases_are_only_pruned_when_asked() {
+    let mut network = network();
+    let report = network.prune_with_biases(0.1);
+
+    assert_eq!(report.layers, [LayerPruneReport { pruned: 2, remaining: 3 }, LayerPruneReport { pruned: 2, remaining: 1 }]);
+    assert_eq!(network.get_params(), [0.0, 0.0, -0.3, 2.0, 0.0, -0.5, 0.0, 0.4, 0.0]);
+}
+
+#[test]
+fn sparsity_is_the_fraction_of_zero_parameters() {
+    let mut network = network();
+    assert_eq!(network.sparsity(), 1.0 / 9.0);
+
+    network.prune(0.1);
+    assert_eq!(network.sparsity(), 3.0 / 9.0);
+    network.prune_with_biases(0.1);
+    assert_eq!(network.sparsity(), 5.0 / 9.0);
+}
+
+#[test]
+fn a_zero_threshold_changes_nothing() {
+    let mut network = network();
+    let params = network.get_params();
+    let report = network.prune(0.0);
+
+    assert_eq!((report.pruned, report.remaining), (0, 5));
+    assert_eq!(network.get_params(), params);
+    assert_eq!(network.prune_with_biases(0.0).pruned, 0);
+    assert_eq!(network.get_params(), params);
+}
+
+#[test]
+fn a_trained_network_loses_little_from_pruning_its_smallest_weights() {
+    let uniform = Uniform::new(-0.3, 0.3).unwrap();
+    let mut network = Network::random_with_rng(&[2, 16, 16, 1], sigmoid!(), &uniform, &mut StdRng::seed_from_u64(1)).unwrap();
+    for _ in 0..5000 {
+        network.learn(&xor(), &MSE, 2.0).unwrap();
+    }
+
+    let before = network.evaluate(&xor(), &MSE).unwrap();
+    let report = network.prune(0.1);
+    let after = network.evaluate(&xor(), &MSE).unwrap();
+
+    // Over a tenth of the 304 weights go, and the loss barely moves.
+    assert_eq!(report.pruned + report.remaining, 304);
+    assert!(report.pruned > 30, "{report:?}");
+    assert!(after - before < 1e-5, "{before} -> {after}");
+    assert!(network.sparsity() >= report.pruned as f32 / network.parameter_count() as f32);
+}
@@ -0,0 +1,76 @@
+use nalgebra::DVector;
+use rand::{distr::Uniform, rngs::StdRng, Rng, SeedableRng};
+
+use neural::{
+    activations::*,
+    dataset::Sample,
+    losses::{LossFn, LossFnError, QuantileLoss},
+    network::{Network, NetworkBuilder},
+    training::Trainer,
+};
+
+fn gradient(loss: &QuantileLoss, output: &[f32], expected: &[f32]) -> Vec<f32> {
+    let (output, expected) = (DVector::from_column_slice(output), DVector::from_column_slice(expected));
+    loss.partial_gradient(output.as_view(), expected.as_view()).unwrap().as_slice().to_vec()
+}
+
+#[test]
+fn the_gradient_pulls_towards_the_quantile() {
+    let loss = QuantileLoss::new(0.9).unwrap();
+
+    // Below the target the output is pushed up with weight q, above it down with weight 1 - q.
+    assert_eq!(gradient(&loss, &[0.0], &[1.0]), [-0.9]);
+    assert!((gradient(&loss, &[2.0], &[1.0])[0] - 0.1).abs() < 1e-6);
+    assert_eq!(gradient(&loss, &[1.0], &[1.0]), [0.0]);
+
+    let per_output = QuantileLoss::per_output(vec![0.1, 0.9]).unwrap();
+    let both = gradient(&per_output, &[0.0, 0.0], &[1.0, 1.0]);
+    assert!((both[0] + 0.05).abs() < 1e-6 && (both[1] + 0.45).abs() < 1e-6);
+}
+
+#[test]
+fn quantiles_are_validated() {
+    for quantile in [0.0, 1.0, -0.5, f32::NAN] {
+        assert!(matches!(QuantileLoss::new(quantile), Err(LossFnError::InvalidQuantile(_))));
+    }
+    assert!(QuantileLoss::per_output(vec![0.5, 1.5]).is_err());
+
+    let loss = QuantileLoss::per_output(vec![0.1, 0.5, 0.9]).unwrap();
+    let (output, expected) = (DVector::from_vec(vec![0.0, 0.0]), DVector::from_vec(vec![1.0, 1.0]));
+    assert!(matches!(
+        loss.apply(output.as_view(), expected.as_view()),
+        Err(LossFnError::QuantileCountMismatch { quantiles: 3, outputs: 2 })
+    ));
+}
+
+#[test]
+fn learns_an_interval_that_widens_with_the_noise() {
+    let mut rng = StdRng::seed_from_u64(11);
+    let noise = Uniform::new(-1.0f32, 1.0).unwrap();
+
+    // The target is x plus noise whose spread grows with x.
+    let dataset: Vec<Sample> = (0..400)
+        .map(|_| {
+            let x: f32 = rng.random_range(0.0..1.0);
+            let y = x + (0.1 + x) * rng.sample(noise);
+            Sample::from_slices(&[x], &[y, y])
+        })
+        .collect();
+
+    let mut network: Network = NetworkBuilder::new().input(1).layer(16, tanh!()).layer(2, identity!()).build_with_rng(&mut rng).unwrap();
+    Trainer::new(QuantileLoss::per_output(vec![0.1, 0.9]).unwrap(), 0.05, 150)
+        .batch_size(32)
+        .shuffle(true)
+        .fit_with_rng(&mut network, &dataset, &[], &mut rng)
+        .unwrap();
+
+    let widths: Vec<f32> = (0..=10)
+        .map(|i| {
+            let outputs = network.predict(DVector::from_vec(vec![i as f32 / 10.0]).as_view()).unwrap();
+            outputs[1] - outputs[0]
+        })
+        .collect();
+
+    assert!(widths.iter().filter(|&&width| width > 0.0).count() >= 10, "{widths:?}");
+    assert!(widths[10] > widths[0], "{widths:?}");
+}
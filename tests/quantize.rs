@@ -0,0 +1,125 @@
+mod common;
+
+use nalgebra::{DMatrix, DVector};
+use rand::{distr::{Distribution, Uniform}, rngs::StdRng, SeedableRng};
+
+use neural::{
+    activations::*,
+    dataset::Sample,
+    losses::MSE,
+    network::{layer::Layer, Conv2D, Convolution, ImageShape, Network, NetworkError, NetworkLayer},
+};
+
+use common::xor;
+
+fn inputs(size: usize, count: usize, rng: &mut StdRng) -> Vec<DVector<f32>> {
+    let uniform = Uniform::new(-1.0, 1.0).unwrap();
+    (0..count).map(|_| DVector::from_fn(size, |_, _| uniform.sample(rng))).collect()
+}
+
+#[test]
+fn every_weight_is_within_half_a_step_of_its_row_scale() {
+    let mut rng = StdRng::seed_from_u64(1);
+    let mut layer = Layer::random_with_rng(6, 4, identity!(), &Uniform::new(-2.0, 2.0).unwrap(), &mut rng).unwrap();
+    let mut weights = layer.weights().into_owned();
+    weights.row_mut(1).fill(0.0);
+    weights.row_mut(2).scale_mut(0.001);
+    layer.set_weights(weights.clone()).unwrap();
+
+    let network = Network::from_layers(vec![layer]).unwrap();
+    let quantized = network.quantize().unwrap();
+    let layer = &quantized.layers()[0];
+
+    for row in 0..4 {
+        let scale = layer.scales()[row];
+        assert_eq!(scale, weights.row(row).amax() / 127.0);
+
+        for column in 0..6 {
+            let error = (layer.dequantized_weight(column, row).unwrap() - weights[(row, column)]).abs();
+            assert!(error <= scale / 2.0 + f32::EPSILON * weights[(row, column)].abs(), "({row}, {column}): {error} vs scale {scale}");
+        }
+    }
+
+    // The largest weight of every row is exact, and a row of zeros stays zeros.
+    assert!(layer.weights().row(0).iter().any(|&q| q.abs() == 127));
+    assert_eq!(layer.scales()[1], 0.0);
+    assert!(layer.weights().row(1).iter().all(|&q| q == 0));
+    assert!(layer.weights().row(2).iter().any(|&q| q.abs() == 127));
+    assert_eq!(layer.biases(), network.dense_layer(0).unwrap().biases());
+    assert_eq!(layer.dequantized_weight(6, 0), None);
+}
+
+#[test]
+fn outputs_of_a_linear_layer_stay_within_the_rounding_bound() {
+    let mut rng = StdRng::seed_from_u64(2);
+    let network = Network::from_layers(vec![Layer::random_with_rng(16, 8, identity!(), &Uniform::new(-1.0, 1.0).unwrap(), &mut rng).unwrap()]).unwrap();
+    let quantized = network.quantize().unwrap();
+
+    for inputs in inputs(16, 50, &mut rng) {
+        let (expected, outputs) = (network.predict(inputs.as_view()).unwrap(), quantized.predict(inputs.as_view()).unwrap());
+        let input_norm: f32 = inputs.iter().map(|x| x.abs()).sum();
+
+        // Every weight is off by at most half its row's scale, so every output by that times the inputs' 1-norm.
+        for (row, (x, y)) in outputs.iter().zip(expected.iter()).enumerate() {
+            let bound = quantized.layers()[0].scales()[row] / 2.0 * input_norm + 1e-5;
+            assert!((x - y).abs() <= bound, "output {row}: {x} vs {y}, bound {bound}");
+        }
+    }
+}
+
+#[test]
+fn random_networks_predict_nearly_the_same() {
+    for seed in 0..5 {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let network = Network::random_with_rng(&[20, 32, 16, 5], sigmoid!(), &Uniform::new(-1.0, 1.0).unwrap(), &mut rng).unwrap();
+        let dataset: Vec<_> = inputs(20, 200, &mut rng).into_iter().map(|inputs| Sample::new(inputs, DVector::zeros(5))).collect();
+
+        let report = network.quantize().unwrap().compare(&network, &dataset).unwrap();
+        assert!(report.max_abs_error < 0.01, "seed {seed}: {report:?}");
+        assert!(report.mean_abs_error < 0.002, "seed {seed}: {report:?}");
+        assert!(report.mean_abs_error <= report.max_abs_error);
+        assert!(report.argmax_agreement >= 0.95, "seed {seed}: {report:?}");
+    }
+}
+
+#[test]
+fn weights_take_about_a_quarter_of_the_memory() {
+    let network = Network::random_with_rng(&[64, 128, 10], relu!(), &Uniform::new(-1.0, 1.0).unwrap(), &mut StdRng::seed_from_u64(3)).unwrap();
+    let quantized = network.quantize().unwrap();
+
+    // One byte per weight and an f32 scale per row.
+    let weights = 64 * 128 + 128 * 10;
+    assert_eq!(quantized.weight_bytes(), weights + 4 * (128 + 10));
+    let ratio = (4 * weights) as f32 / quantized.weight_bytes() as f32;
+    assert!(ratio > 3.75, "{ratio}");
+    assert_eq!((quantized.input_size(), quantized.output_size()), (64, 10));
+}
+
+#[test]
+fn batch_normalization_is_folded_in() {
+    let mut rng = StdRng::seed_from_u64(4);
+    let uniform = Uniform::new(-1.0, 1.0).unwrap();
+    let layers = vec![
+        Layer::random_with_rng(2, 6, relu!(), &uniform, &mut rng).unwrap().with_batch_norm(),
+        Layer::random_with_rng(6, 1, sigmoid!(), &uniform, &mut rng).unwrap(),
+    ];
+    let mut network = Network::from_layers(layers).unwrap();
+    for _ in 0..20 {
+        network.learn(&xor(), &MSE, 0.5).unwrap();
+    }
+
+    let report = network.quantize().unwrap().compare(&network, &xor()).unwrap();
+    assert!(report.max_abs_error < 0.01, "{report:?}");
+}
+
+#[test]
+fn quantizing_needs_dense_layers_and_comparing_a_dataset() {
+    let conv = Conv2D::zeros(ImageShape::new(2, 2, 1), Convolution::new(1, (1, 1)), identity!()).unwrap();
+    let layers: Vec<Box<dyn NetworkLayer>> = vec![Box::new(Layer::zeros(4, 4, identity!()).unwrap()), Box::new(conv)];
+    assert!(matches!(Network::from_network_layers(layers).unwrap().quantize(), Err(NetworkError::NotDense { layer: 1 })));
+
+    let network = Network::from_layers(vec![Layer::zeros(2, 1, identity!()).unwrap()]).unwrap();
+    let quantized = network.quantize().unwrap();
+    assert!(matches!(quantized.compare(&network, &[]), Err(NetworkError::EmptyDataset)));
+    assert!(quantized.predict(DMatrix::zeros(3, 1).column(0)).is_err());
+}
@@ -0,0 +1,188 @@
+mod common;
+
+use nalgebra::{DMatrix, DVector};
+use rand::{distr::Uniform, rngs::StdRng, SeedableRng};
+
+use neural::{
+    activations::*,
+    dataset::SequenceSample,
+    losses::{LossFn, MSE},
+    network::{
+        layer::{Layer, LayerError},
+        recurrent::{RecurrentLayer, RecurrentNetwork, SequenceLayer},
+        NetworkError,
+    },
+};
+
+use common::assert_close;
+
+fn uniform() -> Uniform<f64> {
+    Uniform::new(-0.5, 0.5).unwrap()
+}
+
+fn sequence(length: usize, size: usize, seed: usize) -> Vec<DVector<f64>> {
+    (0..length).map(|t| DVector::from_fn(size, |i, _| ((seed * 17 + t * size + i) as f64 * 0.61).sin())).collect()
+}
+
+/// `sum(weights[t] * states[t])` over the states `predict` returns, whose gradient with respect to the states is `weights`.
+fn objective(layer: &RecurrentLayer<f64>, inputs: &[DVector<f64>], weights: &[DVector<f64>]) -> f64 {
+    layer.predict(inputs).unwrap().iter().zip(weights).map(|(state, weights)| state.dot(weights)).sum()
+}
+
+/// Central differences of `f` with respect to every entry of `values`.
+fn numeric(values: &DMatrix<f64>, mut f: impl FnMut(DMatrix<f64>) -> f64) -> DMatrix<f64> {
+    let epsilon = 1e-6;
+
+    DMatrix::from_fn(values.nrows(), values.ncols(), |row, column| {
+        let (mut plus, mut minus) = (values.clone(), values.clone());
+        plus[(row, column)] += epsilon;
+        minus[(row, column)] -= epsilon;
+        (f(plus) - f(minus)) / (2.0 * epsilon)
+    })
+}
+
+#[test]
+fn follows_the_elman_recurrence() {
+    let mut layer = RecurrentLayer::zeros(1, 1, identity!()).unwrap();
+    layer.set_input_weights(DMatrix::from_element(1, 1, 1.0)).unwrap();
+    layer.set_recurrent_weights(DMatrix::from_element(1, 1, 0.5)).unwrap();
+    layer.set_biases(DVector::from_element(1, 0.25)).unwrap();
+
+    let inputs: Vec<_> = [1.0, 0.0, 2.0].into_iter().map(|x| DVector::from_element(1, x)).collect();
+    let states = layer.forward(&inputs).unwrap();
+    assert_eq!(states.iter().map(|state| state[0]).collect::<Vec<_>>(), [1.25, 0.875, 2.6875]);
+    assert_eq!(layer.predict(&inputs).unwrap(), states);
+
+    // Every sequence starts from a zero state, unless `forward_from` is given another one.
+    assert_eq!(layer.forward(&inputs[..1]).unwrap()[0][0], 1.25);
+    assert_eq!(layer.forward_from(&inputs[..1], &DVector::from_element(1, 2.0)).unwrap()[0][0], 2.25);
+    assert!(matches!(
+        layer.forward_from(&inputs, &DVector::zeros(2)),
+        Err(LayerError::StateSizeMismatch { hidden_size: 1, given_size: 2 })
+    ));
+    assert!(matches!(layer.predict(&[DVector::zeros(2)]), Err(LayerError::InputSizeMismatch { layer_input_size: 1, given_input_size: 2 })));
+}
+
+#[test]
+fn backpropagation_through_time_matches_numeric_gradients() {
+    let mut layer = RecurrentLayer::random_with_rng(2, 3, tanh!(), &uniform(), &mut StdRng::seed_from_u64(1)).unwrap();
+    let inputs = sequence(5, 2, 0);
+    let weights = sequence(5, 3, 1);
+
+    layer.forward(&inputs).unwrap();
+    let input_gradients = layer.backpropagate_through_time(&weights, None);
+
+    for (t, gradient) in input_gradients.iter().enumerate() {
+        let input = DMatrix::from_column_slice(2, 1, inputs[t].as_slice());
+        let expected = numeric(&input, |shifted| {
+            let mut inputs = inputs.clone();
+            inputs[t] = DVector::from_column_slice(shifted.as_slice());
+            objective(&layer, &inputs, &weights)
+        });
+        assert_close(gradient.as_slice(), expected.as_slice(), 1e-8);
+    }
+
+    let expected = numeric(&layer.input_weights().into_owned(), |shifted| {
+        let mut layer = layer.clone();
+        layer.set_input_weights(shifted).unwrap();
+        objective(&layer, &inputs, &weights)
+    });
+    assert_close(layer.input_weight_gradient().into_owned().as_slice(), expected.as_slice(), 1e-8);
+
+    let expected = numeric(&layer.recurrent_weights().into_owned(), |shifted| {
+        let mut layer = layer.clone();
+        layer.set_recurrent_weights(shifted).unwrap();
+        objective(&layer, &inputs, &weights)
+    });
+    assert_close(layer.recurrent_weight_gradient().into_owned().as_slice(), expected.as_slice(), 1e-8);
+
+    let expected = numeric(&DMatrix::from_column_slice(3, 1, layer.biases().as_slice()), |shifted| {
+        let mut layer = layer.clone();
+        layer.set_biases(DVector::from_column_slice(shifted.as_slice())).unwrap();
+        objective(&layer, &inputs, &weights)
+    });
+    assert_close(layer.bias_gradient().as_slice(), expected.as_slice(), 1e-8);
+}
+
+#[test]
+fn learn_steps_along_the_mean_loss_gradient() {
+    let mut rng = StdRng::seed_from_u64(2);
+    let recurrent = RecurrentLayer::random_with_rng(2, 3, tanh!(), &uniform(), &mut rng).unwrap();
+    let output = Layer::random_with_rng(3, 1, sigmoid!(), &uniform(), &mut rng).unwrap();
+    let network = RecurrentNetwork::new(recurrent, output).unwrap();
+    let dataset = [
+        SequenceSample::new(sequence(4, 2, 0), sequence(4, 1, 1)),
+        SequenceSample::new(sequence(2, 2, 2), sequence(2, 1, 3)),
+    ];
+
+    // The mean loss per step, which `learn` descends.
+    let mean_loss = |network: &RecurrentNetwork<f64>| {
+        let total: f64 = dataset
+            .iter()
+            .flat_map(|sample| network.predict(sample.inputs()).unwrap().into_iter().zip(sample.expected_outputs()))
+            .map(|(outputs, expected)| MSE.apply(outputs.as_view(), expected.as_view()).unwrap())
+            .sum();
+        total / 6.0
+    };
+
+    let mut stepped = network.clone();
+    assert!((stepped.learn(&dataset, &MSE, 1.0).unwrap() - mean_loss(&network)).abs() < 1e-12);
+
+    let analytic = network.recurrent().recurrent_weights() - stepped.recurrent().recurrent_weights();
+    let expected = numeric(&network.recurrent().recurrent_weights().into_owned(), |shifted| {
+        let mut recurrent = network.recurrent().clone();
+        recurrent.set_recurrent_weights(shifted).unwrap();
+        mean_loss(&RecurrentNetwork::new(recurrent, network.output().clone()).unwrap())
+    });
+    assert_close(analytic.as_slice(), expected.as_slice(), 1e-8);
+}
+
+#[test]
+fn learns_to_echo_the_previous_input() {
+    let mut rng = StdRng::seed_from_u64(3);
+    let recurrent = RecurrentLayer::random_with_rng(1, 4, tanh!(), &uniform(), &mut rng).unwrap();
+    let output = Layer::random_with_rng(4, 1, identity!(), &uniform(), &mut rng).unwrap();
+    let mut network = RecurrentNetwork::new(recurrent, output).unwrap();
+
+    let dataset: Vec<_> = (0..16)
+        .map(|seed| {
+            let inputs: Vec<_> = (0..6).map(|t| DVector::from_element(1, if (seed >> (t % 4)) & 1 == 1 { 0.5 } else { -0.5 })).collect();
+            let outputs = (0..6).map(|t| if t == 0 { DVector::zeros(1) } else { inputs[t - 1].clone() }).collect();
+            SequenceSample::new(inputs, outputs)
+        })
+        .collect();
+
+    let initial_loss = network.learn(&dataset, &MSE, 0.0).unwrap();
+    let mut loss = initial_loss;
+    for _ in 0..2000 {
+        loss = network.learn(&dataset, &MSE, 0.5).unwrap();
+    }
+
+    assert!(loss < initial_loss / 20.0, "{initial_loss} -> {loss}");
+}
+
+#[test]
+fn sequence_samples_are_checked() {
+    let recurrent = RecurrentLayer::<f64>::zeros(2, 3, tanh!()).unwrap();
+    let mut network = RecurrentNetwork::new(recurrent, Layer::zeros(3, 1, identity!()).unwrap()).unwrap();
+
+    let sample = SequenceSample::new(sequence(3, 2, 0), sequence(3, 1, 0));
+    assert_eq!(sample.len(), 3);
+    assert!(!sample.is_empty());
+    assert!(SequenceSample::<f64>::new(Vec::new(), Vec::new()).is_empty());
+    assert!(network.check_dataset(std::slice::from_ref(&sample)).is_ok());
+
+    let short_outputs = SequenceSample::new(sequence(3, 2, 0), sequence(2, 1, 0));
+    assert!(matches!(
+        network.learn(&[sample.clone(), short_outputs], &MSE, 0.1),
+        Err(NetworkError::SequenceLengthMismatch { index: 1, inputs: 3, outputs: 2 })
+    ));
+
+    let wide_inputs = SequenceSample::new(sequence(3, 3, 0), sequence(3, 1, 0));
+    assert!(matches!(network.check_dataset(&[wide_inputs]), Err(NetworkError::SampleSizeMismatch { index: 0, inputs: 3, network_inputs: 2, .. })));
+
+    assert!(matches!(
+        RecurrentNetwork::new(RecurrentLayer::<f64>::zeros(2, 3, tanh!()).unwrap(), Layer::zeros(4, 1, identity!()).unwrap()),
+        Err(NetworkError::LayerChainMismatch { output_size: 3, input_size: 4, .. })
+    ));
+}
@@ -0,0 +1,121 @@
+use std::collections::BTreeSet;
+
+use rand::{rngs::StdRng, SeedableRng};
+
+use neural::dataset::{DatasetError, ReplayBuffer, ReplayMode, Sample};
+
+/// A sample identified by its one input.
+fn sample(id: usize) -> Sample {
+    Sample::from_slices(&[id as f32], &[0.0])
+}
+
+fn ids<'a>(samples: impl IntoIterator<Item = &'a Sample>) -> Vec<usize> {
+    samples.into_iter().map(|sample| sample.inputs()[0] as usize).collect()
+}
+
+#[test]
+fn never_holds_more_than_its_capacity() {
+    let mut rng = StdRng::seed_from_u64(0);
+
+    for mode in [ReplayMode::Ring, ReplayMode::Reservoir] {
+        let mut buffer = ReplayBuffer::new(5, mode).unwrap();
+
+        for id in 0..50 {
+            buffer.push_with_rng(sample(id), &mut rng);
+            assert_eq!(buffer.len(), (id + 1).min(5), "{mode:?}");
+            assert_eq!(buffer.seen(), id + 1, "{mode:?}");
+        }
+
+        // Every sample held is a distinct one that was pushed.
+        let held: BTreeSet<usize> = ids(buffer.samples()).into_iter().collect();
+        assert_eq!(held.len(), 5, "{mode:?}");
+        assert!(held.iter().all(|&id| id < 50), "{mode:?}");
+    }
+}
+
+#[test]
+fn ring_mode_keeps_the_most_recent_from_the_oldest() {
+    let mut rng = StdRng::seed_from_u64(0);
+    let mut buffer = ReplayBuffer::new(4, ReplayMode::Ring).unwrap();
+
+    for id in 0..3 {
+        buffer.push_with_rng(sample(id), &mut rng);
+    }
+    assert_eq!(ids(buffer.iter()), [0, 1, 2]);
+
+    for id in 3..10 {
+        buffer.push_with_rng(sample(id), &mut rng);
+    }
+    assert_eq!(ids(buffer.iter()), [6, 7, 8, 9]);
+}
+
+#[test]
+fn reservoir_mode_keeps_every_sample_equally_often() {
+    const CAPACITY: usize = 10;
+    const STREAM: usize = 100;
+    const TRIALS: usize = 4000;
+
+    let mut rng = StdRng::seed_from_u64(7);
+    let mut kept = [0usize; STREAM];
+
+    for _ in 0..TRIALS {
+        let mut buffer = ReplayBuffer::new(CAPACITY, ReplayMode::Reservoir).unwrap();
+        for id in 0..STREAM {
+            buffer.push_with_rng(sample(id), &mut rng);
+        }
+
+        for id in ids(buffer.samples()) {
+            kept[id] += 1;
+        }
+    }
+
+    // Each sample should be kept with probability 10 / 100, i.e. 400 times with a standard deviation of 19.
+    let expected = TRIALS * CAPACITY / STREAM;
+    for (id, &count) in kept.iter().enumerate() {
+        assert!(count.abs_diff(expected) < 80, "sample {id} was kept {count} times, expected about {expected}");
+    }
+
+    // Neither the first nor the last samples are favoured.
+    let early: usize = kept[..STREAM / 2].iter().sum();
+    let late: usize = kept[STREAM / 2..].iter().sum();
+    assert!(early.abs_diff(late) < TRIALS * CAPACITY / 20, "{early} early vs {late} late");
+}
+
+#[test]
+fn sample_batch_draws_distinct_held_samples() {
+    let mut rng = StdRng::seed_from_u64(3);
+    let mut buffer = ReplayBuffer::new(8, ReplayMode::Ring).unwrap();
+    for id in 0..20 {
+        buffer.push_with_rng(sample(id), &mut rng);
+    }
+
+    let batch = ids(buffer.sample_batch(5, &mut rng));
+    let distinct: BTreeSet<usize> = batch.iter().copied().collect();
+    assert_eq!(distinct.len(), 5);
+    assert!(distinct.iter().all(|id| (12..20).contains(id)));
+
+    // Asking for more than the buffer holds gives all of it.
+    let all: BTreeSet<usize> = ids(&buffer.sample_batch_cloned(100, &mut rng)).into_iter().collect();
+    assert_eq!(all, (12..20).collect());
+}
+
+#[test]
+fn clear_empties_the_buffer_and_its_count() {
+    let mut rng = StdRng::seed_from_u64(0);
+    let mut buffer = ReplayBuffer::new(3, ReplayMode::Ring).unwrap();
+    for id in 0..5 {
+        buffer.push_with_rng(sample(id), &mut rng);
+    }
+
+    buffer.clear();
+    assert!(buffer.is_empty());
+    assert_eq!(buffer.seen(), 0);
+
+    buffer.push_with_rng(sample(9), &mut rng);
+    assert_eq!(ids(buffer.iter()), [9]);
+}
+
+#[test]
+fn a_zero_capacity_is_rejected() {
+    assert!(matches!(ReplayBuffer::new(0, ReplayMode::Reservoir), Err(DatasetError::ZeroCapacity)));
+}
@@ -0,0 +1,62 @@
+mod common;
+
+use rand::{distr::Uniform, rngs::StdRng, SeedableRng};
+
+use neural::{
+    activations::*,
+    losses::MSE,
+    network::{layer::Layer, Network},
+    training::Trainer,
+};
+
+use common::xor;
+
+fn network(seed: u64) -> Network {
+    Network::random_with_rng(&[2, 8, 4, 1], tanh!(), &Uniform::new(-1.0, 1.0).unwrap(), &mut StdRng::seed_from_u64(seed)).unwrap()
+}
+
+/// Trains with shuffled mini-batches, so the result depends on the order the RNG draws.
+fn train(network: &mut Network, seed: u64) {
+    Trainer::new(MSE, 0.3, 20)
+        .batch_size(2)
+        .shuffle(true)
+        .fit_with_rng(network, &xor(), &[], &mut StdRng::seed_from_u64(seed))
+        .unwrap();
+}
+
+#[test]
+fn the_same_seed_gives_the_same_network() {
+    assert_eq!(network(42).get_params(), network(42).get_params());
+    assert_ne!(network(42).get_params(), network(43).get_params());
+}
+
+#[test]
+fn the_same_seed_gives_the_same_layer() {
+    let layer = |seed| Layer::random_with_rng(3, 5, sigmoid!(), &Uniform::new(-1.0, 1.0).unwrap(), &mut StdRng::seed_from_u64(seed)).unwrap();
+
+    assert_eq!(layer(42).weights(), layer(42).weights());
+    assert_eq!(layer(42).biases(), layer(42).biases());
+    assert_ne!(layer(42).weights(), layer(7).weights());
+}
+
+#[test]
+fn seeded_training_is_bit_identical() {
+    let mut first = network(42);
+    let mut second = network(42);
+
+    train(&mut first, 1);
+    train(&mut second, 1);
+
+    assert_eq!(first.get_params(), second.get_params());
+}
+
+#[test]
+fn the_rng_drives_the_shuffling() {
+    let mut first = network(42);
+    let mut second = network(42);
+
+    train(&mut first, 1);
+    train(&mut second, 2);
+
+    assert_ne!(first.get_params(), second.get_params());
+}
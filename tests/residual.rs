@@ -0,0 +1,79 @@
+mod common;
+
+use nalgebra::{DMatrix, DVector};
+use rand::{distr::Uniform, rngs::StdRng, SeedableRng};
+
+use neural::{
+    activations::*,
+    losses::MSE,
+    network::{layer::Layer, Network, NetworkError, NetworkLayer, ResidualBlock},
+};
+
+use common::{analytic_gradient, assert_close, numeric_gradient, sample};
+
+fn dense(input_size: usize, output_size: usize, rng: &mut StdRng) -> Layer<f64> {
+    Layer::random_with_rng(input_size, output_size, tanh!(), &Uniform::new(-0.5, 0.5).unwrap(), rng).unwrap()
+}
+
+#[test]
+fn adds_the_input_to_the_stack_output() {
+    let mut rng = StdRng::seed_from_u64(1);
+    let (first, second) = (dense(3, 4, &mut rng), dense(4, 3, &mut rng));
+    let mut block = ResidualBlock::from_layers(vec![first.clone(), second.clone()]).unwrap();
+
+    let inputs = DVector::from_column_slice(&[0.3, -0.8, 0.5]);
+    let stack = second.predict(first.predict(inputs.as_view()).unwrap().as_view()).unwrap();
+    let expected = &stack + &inputs;
+
+    assert_eq!(block.predict(inputs.as_view()).unwrap(), expected);
+    assert_eq!(NetworkLayer::forward(&mut block, inputs.as_view()).unwrap(), expected);
+
+    let batch = DMatrix::from_columns(&[inputs.clone(), -&inputs]);
+    let outputs = block.forward_batch(&batch).unwrap();
+    assert_eq!(outputs.column(0), expected.column(0));
+    assert_eq!(outputs.column(1), block.predict((-&inputs).as_view()).unwrap().column(0));
+
+    // A stack that outputs zeros leaves only the skip path.
+    let zeros = ResidualBlock::from_layers(vec![Layer::zeros(3, 3, identity!()).unwrap()]).unwrap();
+    assert_eq!(zeros.predict(inputs.as_view()).unwrap(), inputs);
+}
+
+#[test]
+fn the_skip_path_passes_the_gradient_through() {
+    // However deep a stack of zero layers is, the gradient with respect to the input is the output gradient.
+    let layers = (0..10).map(|_| Layer::zeros(2, 2, sigmoid!()).unwrap().without_bias()).collect();
+    let mut block = ResidualBlock::<f64>::from_layers(layers).unwrap();
+    let inputs = DMatrix::from_column_slice(2, 2, &[0.5, -0.5, 1.0, 2.0]);
+    let gradient = DMatrix::from_column_slice(2, 2, &[1.0, -2.0, 3.0, 0.5]);
+
+    block.forward_training_batch(inputs.as_view()).unwrap();
+    let input_gradient = block.backpropagation_step_batch(inputs.as_view(), &gradient).unwrap();
+    assert_close(input_gradient.as_slice(), gradient.as_slice(), 1e-12);
+}
+
+#[test]
+fn gradients_match_numeric_ones() {
+    let mut rng = StdRng::seed_from_u64(2);
+    let block = ResidualBlock::from_layers(vec![dense(3, 5, &mut rng), dense(5, 3, &mut rng)]).unwrap();
+    let layers: Vec<Box<dyn NetworkLayer<f64>>> = vec![Box::new(dense(2, 3, &mut rng)), Box::new(block), Box::new(dense(3, 1, &mut rng))];
+    let network = Network::from_network_layers(layers).unwrap();
+    let dataset = [sample(&[0.2, -0.4], &[0.7]), sample(&[-0.1, 0.3], &[0.2]), sample(&[0.9, 0.5], &[-0.3])];
+
+    assert_eq!(network.get_params().len(), 9 + 20 + 18 + 4);
+    assert_close(&analytic_gradient(&network, &dataset, &MSE), &numeric_gradient(&network, &dataset, &MSE), 1e-8);
+}
+
+#[test]
+fn stacks_have_to_chain_back_to_their_input_size() {
+    let mut rng = StdRng::seed_from_u64(3);
+
+    assert!(matches!(ResidualBlock::<f64>::new(Vec::new()), Err(NetworkError::NoLayers)));
+    assert!(matches!(
+        ResidualBlock::from_layers(vec![dense(3, 4, &mut rng), dense(5, 3, &mut rng)]),
+        Err(NetworkError::LayerChainMismatch { layer: 0, next_layer: 1, output_size: 4, input_size: 5 })
+    ));
+    assert!(matches!(
+        ResidualBlock::from_layers(vec![dense(3, 4, &mut rng)]),
+        Err(NetworkError::ResidualSizeMismatch { input_size: 3, output_size: 4 })
+    ));
+}
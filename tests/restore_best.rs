@@ -0,0 +1,59 @@
+use rand::{distr::Uniform, rngs::StdRng, SeedableRng};
+
+use neural::{activations::*, dataset::Sample, losses::MSE, network::Network, training::Trainer};
+
+fn network() -> Network {
+    Network::random_with_rng(&[1, 1], sigmoid!(), &Uniform::new(-0.1, 0.1).unwrap(), &mut StdRng::seed_from_u64(7)).unwrap()
+}
+
+/// Training pulls the output from about 0.5 towards 1, so it passes the validation target of 0.75
+/// partway through and the validation loss gets worse from then on.
+fn sets() -> (Vec<Sample>, Vec<Sample>) {
+    (vec![Sample::from_slices(&[1.0], &[1.0])], vec![Sample::from_slices(&[1.0], &[0.75])])
+}
+
+#[test]
+fn restores_the_epoch_with_the_lowest_validation_loss() {
+    let (train, validation) = sets();
+    let mut network = network();
+
+    let report = Trainer::new(MSE, 0.5, 40).restore_best(true).fit(&mut network, &train, &validation).unwrap();
+
+    let losses: Vec<f32> = report.history.validation_losses().iter().map(|loss| loss.unwrap()).collect();
+    let best_epoch = (0..losses.len()).min_by(|&a, &b| losses[a].total_cmp(&losses[b])).unwrap();
+    assert!(0 < best_epoch && best_epoch < 39, "the best epoch {best_epoch} has to be in the middle: {losses:?}");
+    assert!(losses[39] > losses[best_epoch]);
+    assert_eq!(report.best_epoch, Some(best_epoch));
+
+    // Full-batch training without shuffling is deterministic, so stopping at the best epoch gives its weights.
+    let mut stopped = self::network();
+    Trainer::new(MSE, 0.5, best_epoch + 1).fit(&mut stopped, &train, &validation).unwrap();
+    assert_eq!(network.get_params(), stopped.get_params());
+    assert_eq!(network.evaluate(&validation, &MSE).unwrap(), losses[best_epoch]);
+}
+
+#[test]
+fn keeps_the_last_weights_without_restore_best() {
+    let (train, validation) = sets();
+    let mut network = network();
+    let mut trained = self::network();
+
+    let report = Trainer::new(MSE, 0.5, 40).fit(&mut network, &train, &validation).unwrap();
+    Trainer::new(MSE, 0.5, 40).restore_best(true).fit(&mut trained, &train, &validation).unwrap();
+
+    assert_eq!((report.best_epoch, report.best_weights.is_none()), (None, true));
+    assert_ne!(network.get_params(), trained.get_params());
+}
+
+#[test]
+fn skips_epochs_whose_loss_isnt_finite() {
+    let train = vec![Sample::from_slices(&[1.0e3], &[1.0e3])];
+    let mut network = Network::random_with_rng(&[1, 1], identity!(), &Uniform::new(0.5, 1.0).unwrap(), &mut StdRng::seed_from_u64(1)).unwrap();
+
+    // The first step already overflows the weights, and every loss after it is infinite or NaN.
+    let report = Trainer::new(MSE, 1.0e30, 3).restore_best(true).fit(&mut network, &train, &train).unwrap();
+
+    assert!(report.history.validation_losses().iter().all(|loss| !loss.unwrap().is_finite()));
+    assert_eq!(report.best_epoch, None);
+    assert!(report.best_weights.is_none());
+}
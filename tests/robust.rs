@@ -0,0 +1,48 @@
+mod common;
+
+use rand::{distr::Uniform, rngs::StdRng, SeedableRng};
+
+use neural::{
+    activations::*,
+    dataset::Sample,
+    losses::MSE,
+    network::{Network, NetworkError},
+};
+
+fn network() -> Network {
+    Network::random_with_rng(&[2, 4, 1], sigmoid!(), &Uniform::new(-1.0, 1.0).unwrap(), &mut StdRng::seed_from_u64(5)).unwrap()
+}
+
+#[test]
+fn skips_a_sample_with_an_infinite_input() {
+    let mut dataset = common::xor();
+    dataset.insert(2, Sample::from_slices(&[f32::INFINITY, 0.0], &[1.0]));
+    let mut network = network();
+
+    for _ in 0..100 {
+        let step = network.learn_robust(&dataset, &MSE, 0.5).unwrap();
+        assert_eq!(step.skipped, vec![2]);
+        assert!(step.mean_loss.is_finite());
+    }
+
+    assert!(network.is_finite());
+
+    // The step is the one `learn` takes on the clean samples.
+    let clean = common::xor();
+    let (mut robust, mut plain) = (network.clone(), network);
+    robust.learn_robust(&dataset, &MSE, 0.5).unwrap();
+    plain.learn(&clean, &MSE, 0.5).unwrap();
+
+    for (a, b) in robust.get_params().iter().zip(plain.get_params()) {
+        assert!((a - b).abs() < 1e-6);
+    }
+}
+
+#[test]
+fn rejects_batch_normalization() {
+    let mut network = network().with_batch_norm();
+    let before = network.get_params();
+
+    assert!(matches!(network.learn_robust(&common::xor(), &MSE, 0.5), Err(NetworkError::BatchNormUnsupported { layer: 0 })));
+    assert_eq!(network.get_params(), before);
+}
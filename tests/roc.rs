@@ -0,0 +1,88 @@
+use nalgebra::DMatrix;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use neural::{
+    activations::*,
+    dataset::Sample,
+    metrics::{roc_curve, RocPoint},
+    network::{layer::Layer, Network, NetworkError},
+};
+
+/// A network whose output is its input, so the samples spell out the scores.
+fn identity() -> Network {
+    let mut layer = Layer::zeros(1, 1, identity!()).unwrap();
+    layer.set_weights(DMatrix::identity(1, 1)).unwrap();
+    Network::from_layers(vec![layer]).unwrap()
+}
+
+fn scored(samples: &[(f32, f32)]) -> Vec<Sample> {
+    samples.iter().map(|&(score, expected)| Sample::from_slices(&[score], &[expected])).collect()
+}
+
+fn point(false_positive_rate: f32, true_positive_rate: f32, threshold: f32) -> RocPoint {
+    RocPoint { false_positive_rate, true_positive_rate, threshold }
+}
+
+#[test]
+fn a_separable_dataset_has_an_auc_of_1() {
+    let dataset = scored(&[(0.2, 0.0), (0.8, 1.0), (0.1, 0.0), (0.6, 1.0), (0.3, 0.0)]);
+    let roc = roc_curve(&mut identity(), &dataset).unwrap();
+
+    assert_eq!(
+        roc.points,
+        [
+            point(0.0, 0.0, f32::INFINITY),
+            point(0.0, 0.5, 0.8),
+            point(0.0, 1.0, 0.6),
+            point(1.0 / 3.0, 1.0, 0.3),
+            point(2.0 / 3.0, 1.0, 0.2),
+            point(1.0, 1.0, 0.1),
+        ]
+    );
+    assert_eq!(roc.auc(), 1.0);
+
+    // Flipping the labels ranks every negative first.
+    let flipped: Vec<_> = dataset
+        .iter()
+        .map(|sample| Sample::from_slices(sample.inputs_slice(), &[1.0 - sample.expected_outputs()[0]]))
+        .collect();
+    assert_eq!(roc_curve(&mut identity(), &flipped).unwrap().auc(), 0.0);
+}
+
+#[test]
+fn random_scores_have_an_auc_near_one_half() {
+    let mut rng = StdRng::seed_from_u64(12);
+    let samples: Vec<_> = (0..2000).map(|_| (rng.random_range(0.0..1.0), rng.random_range(0..2) as f32)).collect();
+    let auc = roc_curve(&mut identity(), &scored(&samples)).unwrap().auc();
+
+    assert!((auc - 0.5).abs() < 0.05, "{auc}");
+}
+
+#[test]
+fn equal_scores_share_one_point_and_count_half() {
+    // The positive and the negative at 0.5 are one diagonal step, so their pair counts half as in the
+    // Mann-Whitney statistic: (1 + 1 + 0.5 + 1) / 4 pairs.
+    let dataset = scored(&[(0.5, 0.0), (0.9, 1.0), (0.2, 0.0), (0.5, 1.0)]);
+    let roc = roc_curve(&mut identity(), &dataset).unwrap();
+
+    assert_eq!(roc.points, [point(0.0, 0.0, f32::INFINITY), point(0.0, 0.5, 0.9), point(0.5, 1.0, 0.5), point(1.0, 1.0, 0.2)]);
+    assert_eq!(roc.auc(), 0.875);
+
+    let tied = roc_curve(&mut identity(), &scored(&[(0.4, 0.0), (0.4, 1.0), (0.4, 1.0)])).unwrap();
+    assert_eq!(tied.points, [point(0.0, 0.0, f32::INFINITY), point(1.0, 1.0, 0.4)]);
+    assert_eq!(tied.auc(), 0.5);
+}
+
+#[test]
+fn a_single_class_has_no_curve() {
+    for class in [0.0, 1.0] {
+        let dataset = scored(&[(0.1, class), (0.7, class)]);
+        assert!(matches!(roc_curve(&mut identity(), &dataset), Err(NetworkError::SingleClass)));
+    }
+
+    assert!(matches!(roc_curve(&mut identity(), &[]), Err(NetworkError::EmptyDataset)));
+    assert!(matches!(
+        roc_curve(&mut identity(), &scored(&[(0.1, 0.0), (0.7, 0.5)])),
+        Err(NetworkError::InvalidClassEncoding { index: 1 })
+    ));
+}
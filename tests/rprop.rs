@@ -0,0 +1,206 @@
+mod common;
+
+use nalgebra::DMatrix;
+use rand::{distr::Uniform, rngs::StdRng, SeedableRng};
+
+use neural::{
+    activations::*,
+    dataset::Sample,
+    losses::MSE,
+    network::{layer::Layer, MaxoutLayer, Network, NetworkError, Optimizer, RProp},
+    training::{cross_validation::cross_validate, Trainer},
+};
+
+use common::{assert_close, sample, xor};
+
+/// `y = w * x + b` starting from `w = b = 0`, so both parameters always share the sign of their gradient.
+fn line() -> Network<f64> {
+    Network::from_layers(vec![Layer::zeros(1, 1, identity!()).unwrap()]).unwrap()
+}
+
+fn weight_and_bias(network: &Network<f64>) -> [f64; 2] {
+    let layer = network.dense_layer(0).unwrap();
+    [layer.weights()[(0, 0)], layer.biases()[0]]
+}
+
+/// Pulls the output at 1 towards `target`.
+fn step_towards(rprop: &mut RProp<f64>, network: &mut Network<f64>, target: f64) {
+    rprop.learn(network, &[sample(&[1.0], &[target])], &MSE).unwrap();
+}
+
+#[test]
+fn steps_grow_while_the_sign_holds_and_shrink_when_it_flips() {
+    let (mut network, mut rprop) = (line(), RProp::default());
+    assert!(rprop.step_sizes().is_empty());
+
+    // The output 2p approaches 1 with steps of 0.1, 0.12, 0.144 and 0.1728 on each parameter, the magnitude of
+    // the gradient doesn't matter.
+    let mut expected = 0.0;
+    for step in [0.1, 0.12, 0.144, 0.1728] {
+        step_towards(&mut rprop, &mut network, 1.0);
+        expected += step;
+        assert_close(&weight_and_bias(&network), &[expected, expected], 1e-12);
+        assert_close(rprop.step_sizes(), &[step, step], 1e-12);
+    }
+
+    // The output of 1.0736 overshot: the step halves and the parameters wait.
+    step_towards(&mut rprop, &mut network, 1.0);
+    assert_close(&weight_and_bias(&network), &[expected, expected], 1e-12);
+    assert_close(rprop.step_sizes(), &[0.0864, 0.0864], 1e-12);
+
+    // After a wait the step neither grows nor shrinks, and the parameters move back.
+    step_towards(&mut rprop, &mut network, 1.0);
+    expected -= 0.0864;
+    assert_close(&weight_and_bias(&network), &[expected, expected], 1e-12);
+    assert_close(rprop.step_sizes(), &[0.0864, 0.0864], 1e-12);
+}
+
+#[test]
+fn steps_stay_within_their_bounds() {
+    let (mut network, mut rprop) = (line(), RProp::new(0.1, 2.0, 0.5, 0.05, 0.3).unwrap());
+    for expected in [0.1, 0.2, 0.3, 0.3] {
+        step_towards(&mut rprop, &mut network, 100.0);
+        assert_close(rprop.step_sizes(), &[expected, expected], 1e-12);
+    }
+
+    // Alternating targets flip the sign every other step, since every flip is followed by a wait.
+    let (mut network, mut rprop) = (line(), RProp::new(0.1, 2.0, 0.5, 0.05, 0.3).unwrap());
+    for (target, expected) in [(100.0, 0.1), (-100.0, 0.05), (-100.0, 0.05), (100.0, 0.05), (100.0, 0.05), (-100.0, 0.05)] {
+        step_towards(&mut rprop, &mut network, target);
+        assert_close(rprop.step_sizes(), &[expected, expected], 1e-12);
+    }
+}
+
+#[test]
+fn frozen_layers_are_skipped_and_learning_rate_scales_multiply_the_steps() {
+    let layers = (0..3).map(|_| Layer::<f64>::zeros(1, 1, identity!()).unwrap()).map(|mut layer| {
+        layer.set_weights(DMatrix::from_element(1, 1, 1.0)).unwrap();
+        layer
+    });
+    let mut network = Network::from_layers(layers.collect()).unwrap();
+    network.set_layer_lr_scale(1, 0.5).unwrap();
+    network.freeze_layers(2..3).unwrap();
+
+    let mut rprop = RProp::default();
+    rprop.learn(&mut network, &[sample(&[1.0], &[10.0])], &MSE).unwrap();
+
+    let parameters: Vec<_> = network.dense_layers().map(|layer| [layer.weights()[(0, 0)], layer.biases()[0]]).collect();
+    assert_close(&parameters.concat(), &[1.1, 0.1, 1.05, 0.05, 1.0, 0.0], 1e-12);
+    // Every layer has its steps, in the order of `get_params`.
+    assert_eq!(rprop.step_sizes().len(), network.parameter_count());
+}
+
+#[test]
+fn learns_xor_without_a_learning_rate() {
+    let mut network = Network::random_with_rng(&[2, 4, 1], sigmoid!(), &Uniform::new(-1.0, 1.0).unwrap(), &mut StdRng::seed_from_u64(1)).unwrap();
+    let mut rprop = RProp::default();
+
+    let initial_loss = rprop.learn(&mut network, &xor(), &MSE).unwrap();
+    let mut loss = initial_loss;
+    for _ in 0..300 {
+        loss = rprop.learn(&mut network, &xor(), &MSE).unwrap();
+    }
+
+    assert!(loss < initial_loss / 100.0, "{initial_loss} -> {loss}");
+    for sample in xor() {
+        let output = network.predict(sample.inputs()).unwrap()[0];
+        assert!((output - sample.expected_outputs()[0]).abs() < 0.1, "{output}");
+    }
+}
+
+#[test]
+fn as_a_trainer_optimizer_it_ignores_the_rate() {
+    let network = || Network::random_with_rng(&[2, 4, 1], sigmoid!(), &Uniform::new(-1.0, 1.0).unwrap(), &mut StdRng::seed_from_u64(2)).unwrap();
+    let train = |rate| {
+        let mut network = network();
+        Trainer::new(MSE, rate, 20).optimizer(RProp::default()).fit_with_rng(&mut network, &xor(), &[], &mut StdRng::seed_from_u64(3)).unwrap();
+        network.get_params()
+    };
+
+    let mut manual = network();
+    let mut rprop = RProp::default();
+    for _ in 0..20 {
+        rprop.learn(&mut manual, &xor(), &MSE).unwrap();
+    }
+
+    assert_eq!(train(0.5), manual.get_params());
+    assert_eq!(train(0.01), manual.get_params());
+}
+
+#[test]
+fn the_state_is_tied_to_one_network_until_reset() {
+    let mut rprop = RProp::default();
+    let mut small = Network::random_with_rng(&[2, 2, 1], sigmoid!(), &Uniform::new(-1.0, 1.0).unwrap(), &mut StdRng::seed_from_u64(4)).unwrap();
+    let mut large = Network::random_with_rng(&[2, 4, 1], sigmoid!(), &Uniform::new(-1.0, 1.0).unwrap(), &mut StdRng::seed_from_u64(4)).unwrap();
+    rprop.learn(&mut small, &xor(), &MSE).unwrap();
+
+    // The other network is rejected before anything changes.
+    let before = large.get_params();
+    assert!(matches!(rprop.learn(&mut large, &xor(), &MSE), Err(NetworkError::ParameterCountMismatch { expected: 9, given: 17 })));
+    assert_eq!(large.get_params(), before);
+
+    Optimizer::<f32>::reset(&mut rprop);
+    assert!(rprop.step_sizes().is_empty());
+    rprop.learn(&mut large, &xor(), &MSE).unwrap();
+    assert_eq!(rprop.step_sizes().len(), 17);
+}
+
+#[test]
+fn every_cross_validation_fold_starts_from_fresh_step_sizes() {
+    let dataset: Vec<Sample> = (0..12).map(|i| i as f32 / 11.0).map(|x| Sample::from_slices(&[x], &[2.0 * x - 1.0])).collect();
+    let factory = || Network::random_with_rng(&[1, 1], identity!(), &Uniform::new(-1.0, 1.0).unwrap(), &mut StdRng::seed_from_u64(5));
+    let trainer = || Trainer::new(MSE, 0.1, 15).optimizer(RProp::default());
+
+    let result = cross_validate(&dataset, 3, factory, trainer, &mut StdRng::seed_from_u64(6)).unwrap();
+
+    // The folds in order are the shuffled dataset, each fold trains on the others in that order.
+    let fold_data = |fold: usize| {
+        let training: Vec<_> = (0..3).filter(|&other| other != fold).flat_map(|other| result.folds[other].iter().map(|&i| dataset[i].clone())).collect();
+        let validation: Vec<_> = result.folds[fold].iter().map(|&i| dataset[i].clone()).collect();
+        (training, validation)
+    };
+
+    let mut shared = trainer();
+    for fold in 0..3 {
+        let (training, validation) = fold_data(fold);
+
+        let mut network = factory().unwrap();
+        trainer().fit_with_rng(&mut network, &training, &[], &mut StdRng::seed_from_u64(0)).unwrap();
+        assert_eq!(network.evaluate(&validation, &MSE).unwrap(), result.fold_losses[fold]);
+
+        // A trainer reused across folds would start every fold after the first with the step sizes it ended the last one with.
+        let mut reused = factory().unwrap();
+        shared.fit_with_rng(&mut reused, &training, &[], &mut StdRng::seed_from_u64(0)).unwrap();
+        if fold > 0 {
+            assert_ne!(reused.get_params(), network.get_params());
+        }
+    }
+}
+
+#[test]
+fn rejects_invalid_settings_and_other_layers() {
+    for (initial, increase, decrease, min, max) in [
+        (0.1, 1.0, 0.5, 1e-6, 50.0),
+        (0.1, 1.2, 1.0, 1e-6, 50.0),
+        (0.1, 1.2, 0.0, 1e-6, 50.0),
+        (0.1, 1.2, 0.5, 0.0, 50.0),
+        (0.1, 1.2, 0.5, 0.2, 50.0),
+        (0.1, 1.2, 0.5, 1e-6, 0.05),
+        (f64::NAN, 1.2, 0.5, 1e-6, 50.0),
+        (0.1, f64::INFINITY, 0.5, 1e-6, 50.0),
+    ] {
+        assert!(matches!(RProp::new(initial, increase, decrease, min, max), Err(NetworkError::InvalidRProp { .. })));
+    }
+
+    let mut rng = StdRng::seed_from_u64(7);
+    let uniform = Uniform::new(-1.0, 1.0).unwrap();
+    let maxout = MaxoutLayer::random_with_rng(2, 3, 2, &uniform, &mut rng).unwrap();
+    let output = Layer::random_with_rng(3, 1, sigmoid!(), &uniform, &mut rng).unwrap();
+    let mut network = Network::from_network_layers(vec![Box::new(maxout), Box::new(output)]).unwrap();
+    assert!(matches!(RProp::default().learn(&mut network, &xor(), &MSE), Err(NetworkError::NotDense { layer: 0 })));
+
+    // An empty dataset is no step.
+    let mut rprop = RProp::default();
+    assert_eq!(rprop.learn(&mut line(), &[], &MSE).unwrap(), 0.0);
+    assert!(rprop.step_sizes().is_empty());
+}
@@ -0,0 +1,55 @@
+#![cfg(feature = "safetensors")]
+
+use neural::{
+    activations::*,
+    network::{layer::Layer, safetensors::SafetensorsError, Network},
+};
+
+fn bias_free() -> Network {
+    let hidden = Layer::zeros(2, 3, sigmoid!()).unwrap().without_bias();
+    let output = Layer::zeros(3, 1, sigmoid!()).unwrap();
+    Network::from_layers(vec![hidden, output]).unwrap()
+}
+
+/// The file with its header rewritten by `edit`, keeping the header's length so the data offsets still hold.
+fn edit_header(bytes: &[u8], edit: impl FnOnce(&str) -> String) -> Vec<u8> {
+    let len = u64::from_le_bytes(bytes[..8].try_into().unwrap()) as usize;
+    let header = edit(std::str::from_utf8(&bytes[8..8 + len]).unwrap());
+    assert!(header.len() <= len);
+
+    let mut edited = bytes[..8].to_vec();
+    edited.extend(format!("{header:len$}").into_bytes());
+    edited.extend_from_slice(&bytes[8 + len..]);
+    edited
+}
+
+#[test]
+fn a_bias_free_layer_round_trips() {
+    let bytes = bias_free().to_safetensors().unwrap();
+    let loaded = Network::from_safetensors(&bytes, &[2, 3, 1], sigmoid!()).unwrap();
+
+    assert!(!loaded.layer(0).unwrap().as_dense().unwrap().has_bias());
+    assert!(loaded.layer(1).unwrap().as_dense().unwrap().has_bias());
+}
+
+#[test]
+fn a_missing_bias_without_the_mark_is_an_error() {
+    let bytes = bias_free().to_safetensors().unwrap();
+    let unmarked = edit_header(&bytes, |header| header.replace(r#""layer0.bias":"none""#, r#""layer0.note":"none""#));
+
+    let result = Network::from_safetensors(&unmarked, &[2, 3, 1], sigmoid!());
+    assert!(matches!(result, Err(SafetensorsError::MissingTensor(name)) if name == "layer0.bias"));
+}
+
+#[test]
+fn rejects_a_shape_whose_size_overflows() {
+    // 2^32 * 2^32 * 4 bytes wraps around to the empty data the offsets give.
+    let mut header = r#"{"layer0.weight":{"dtype":"F32","shape":[4294967296,4294967296],"data_offsets":[0,0]}}"#.to_string();
+    header.push_str(&" ".repeat(header.len().next_multiple_of(8) - header.len()));
+
+    let mut bytes = (header.len() as u64).to_le_bytes().to_vec();
+    bytes.extend(header.into_bytes());
+
+    let result = Network::from_safetensors(&bytes, &[2, 1], sigmoid!());
+    assert!(matches!(result, Err(SafetensorsError::InvalidDataOffsets(name)) if name == "layer0.weight"));
+}
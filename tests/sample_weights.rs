@@ -0,0 +1,91 @@
+mod common;
+
+use nalgebra::DVector;
+use rand::{distr::Uniform, rngs::StdRng, SeedableRng};
+
+use neural::{
+    activations::*,
+    dataset::{DatasetError, Sample},
+    losses::MSE,
+    network::{Network, NetworkError},
+};
+
+use common::{assert_close, sample};
+
+fn network() -> Network<f64> {
+    Network::random_with_rng(&[2, 4, 1], sigmoid!(), &Uniform::new(-1.0, 1.0).unwrap(), &mut StdRng::seed_from_u64(11)).unwrap()
+}
+
+fn dataset() -> Vec<Sample<f64>> {
+    (0..8).map(|i| sample(&[(i as f64 * 0.9).sin(), (i as f64 * 0.4).cos()], &[(i % 2) as f64])).collect()
+}
+
+/// Learns 20 steps and returns the losses and the final parameters.
+fn train(dataset: &[Sample<f64>]) -> (Vec<f64>, Vec<f64>) {
+    let mut network = network();
+    let losses = (0..20).map(|_| network.learn(dataset, &MSE, 0.5).unwrap()).collect();
+    (losses, network.get_params())
+}
+
+#[test]
+fn a_weight_of_zero_is_like_leaving_the_sample_out() {
+    let mut weighted = dataset();
+    weighted[3].set_weight(0.0);
+    weighted[6].set_weight(0.0);
+
+    let omitted: Vec<_> = dataset().into_iter().enumerate().filter(|(i, _)| ![3, 6].contains(i)).map(|(_, sample)| sample).collect();
+
+    let (weighted_losses, weighted_params) = train(&weighted);
+    let (omitted_losses, omitted_params) = train(&omitted);
+    assert_close(&weighted_losses, &omitted_losses, 1e-12);
+    assert_close(&weighted_params, &omitted_params, 1e-12);
+}
+
+#[test]
+fn a_weight_of_two_is_like_duplicating_the_sample() {
+    let mut weighted = dataset();
+    weighted[2].set_weight(2.0);
+
+    let mut duplicated = dataset();
+    duplicated.push(duplicated[2].clone());
+
+    let (weighted_losses, weighted_params) = train(&weighted);
+    let (duplicated_losses, duplicated_params) = train(&duplicated);
+    assert_close(&weighted_losses, &duplicated_losses, 1e-12);
+    assert_close(&weighted_params, &duplicated_params, 1e-12);
+}
+
+#[test]
+fn scaling_every_weight_changes_nothing() {
+    let scaled: Vec<_> = dataset().into_iter().map(|sample| sample.with_weight(3.0)).collect();
+
+    let (scaled_losses, scaled_params) = train(&scaled);
+    let (losses, params) = train(&dataset());
+    assert_close(&scaled_losses, &losses, 1e-12);
+    assert_close(&scaled_params, &params, 1e-12);
+}
+
+#[test]
+fn invalid_weights_are_rejected() {
+    for weight in [-1.0, f32::NAN, f32::INFINITY] {
+        assert!(matches!(
+            Sample::<f64>::weighted(DVector::zeros(2), DVector::zeros(1), weight),
+            Err(DatasetError::InvalidWeight(_))
+        ));
+
+        let mut dataset = dataset();
+        dataset[5].set_weight(weight);
+        let before = network();
+        let mut network = before.clone();
+
+        assert!(matches!(
+            network.learn(&dataset, &MSE, 0.5),
+            Err(NetworkError::InvalidSampleWeight { index: 5, .. })
+        ));
+        assert_eq!(network.get_params(), before.get_params());
+    }
+
+    assert_eq!(Sample::<f64>::weighted(DVector::zeros(2), DVector::zeros(1), 0.0).unwrap().weight(), 0.0);
+    assert_eq!(sample(&[0.0], &[0.0]).weight(), 1.0);
+}
+
@@ -0,0 +1,98 @@
+use nalgebra::DMatrix;
+
+use neural::{
+    activations::*,
+    dataset::Sample,
+    metrics::{precision_recall_f1, ClassScores, ClassificationMode},
+    network::{layer::Layer, Network, NetworkError},
+};
+
+/// A network whose outputs are its inputs, so the samples spell out what it predicts.
+fn identity(size: usize) -> Network {
+    let mut layer = Layer::zeros(size, size, identity!()).unwrap();
+    layer.set_weights(DMatrix::identity(size, size)).unwrap();
+    Network::from_layers(vec![layer]).unwrap()
+}
+
+fn binary(samples: &[(f32, f32)]) -> Vec<Sample> {
+    samples.iter().map(|&(output, expected)| Sample::from_slices(&[output], &[expected])).collect()
+}
+
+fn assert_scores(actual: &ClassScores, [precision, recall, f1]: [f32; 3], predicted: usize, support: usize) {
+    let close = |a: f32, b: f32| (a - b).abs() < 1e-6;
+
+    assert!(close(actual.precision, precision) && close(actual.recall, recall) && close(actual.f1, f1), "{actual:?}");
+    assert_eq!((actual.predicted, actual.support), (predicted, support));
+}
+
+fn assert_close(actual: f32, expected: f32) {
+    assert!((actual - expected).abs() < 1e-6, "{actual} vs {expected}");
+}
+
+#[test]
+fn binary_scores_match_hand_computation() {
+    // One true negative, one false positive at the threshold and two true positives.
+    let dataset = binary(&[(0.2, 0.0), (0.7, 1.0), (0.5, 0.0), (0.9, 1.0)]);
+    let scores = precision_recall_f1(&mut identity(1), &dataset, ClassificationMode::default()).unwrap();
+
+    assert_scores(&scores.classes[0], [1.0, 0.5, 2.0 / 3.0], 1, 2);
+    assert_scores(&scores.classes[1], [2.0 / 3.0, 1.0, 0.8], 3, 2);
+    assert_close(scores.macro_precision, (1.0 + 2.0 / 3.0) / 2.0);
+    assert_close(scores.macro_recall, 0.75);
+    assert_close(scores.macro_f1, (2.0 / 3.0 + 0.8) / 2.0);
+}
+
+#[test]
+fn multi_class_macro_averages_include_a_never_predicted_class() {
+    let dataset: Vec<_> = [
+        ([0.9, 0.1, 0.0], [1.0, 0.0, 0.0]),
+        ([0.8, 0.1, 0.0], [1.0, 0.0, 0.0]),
+        ([0.2, 0.7, 0.1], [0.0, 1.0, 0.0]),
+        ([0.6, 0.3, 0.1], [0.0, 1.0, 0.0]),
+        ([0.1, 0.5, 0.4], [0.0, 0.0, 1.0]),
+        ([0.7, 0.2, 0.1], [0.0, 0.0, 1.0]),
+    ]
+    .iter()
+    .map(|(outputs, expected)| Sample::from_slices(outputs, expected))
+    .collect();
+    let scores = precision_recall_f1(&mut identity(3), &dataset, ClassificationMode::MultiClass).unwrap();
+
+    assert_scores(&scores.classes[0], [0.5, 1.0, 2.0 / 3.0], 4, 2);
+    assert_scores(&scores.classes[1], [0.5, 0.5, 0.5], 2, 2);
+    // Class 2 is never predicted: a precision of 0 instead of NaN, flagged by `predicted`.
+    assert_scores(&scores.classes[2], [0.0, 0.0, 0.0], 0, 2);
+    assert_close(scores.macro_precision, 1.0 / 3.0);
+    assert_close(scores.macro_recall, 0.5);
+    assert_close(scores.macro_f1, (2.0 / 3.0 + 0.5) / 3.0);
+}
+
+#[test]
+fn a_dataset_of_one_class_scores_the_absent_class_zero() {
+    let dataset = binary(&[(0.9, 1.0), (0.6, 1.0), (0.8, 1.0)]);
+    let scores = precision_recall_f1(&mut identity(1), &dataset, ClassificationMode::default()).unwrap();
+
+    // Class 0 has neither samples nor predictions, so all its scores are 0 and so are both counts.
+    assert_scores(&scores.classes[0], [0.0, 0.0, 0.0], 0, 0);
+    assert_scores(&scores.classes[1], [1.0, 1.0, 1.0], 3, 3);
+    assert_eq!([scores.macro_precision, scores.macro_recall, scores.macro_f1], [0.5; 3]);
+
+    // Predicting the absent class doesn't give it a recall, only costs the present one.
+    let dataset = binary(&[(0.9, 1.0), (0.1, 1.0), (0.8, 1.0), (0.3, 1.0)]);
+    let scores = precision_recall_f1(&mut identity(1), &dataset, ClassificationMode::default()).unwrap();
+
+    assert_scores(&scores.classes[0], [0.0, 0.0, 0.0], 2, 0);
+    assert_scores(&scores.classes[1], [1.0, 0.5, 2.0 / 3.0], 2, 4);
+    assert!([scores.macro_precision, scores.macro_recall, scores.macro_f1].iter().all(|score| score.is_finite()));
+}
+
+#[test]
+fn fails_like_accuracy() {
+    assert!(matches!(
+        precision_recall_f1(&mut identity(1), &[], ClassificationMode::default()),
+        Err(NetworkError::EmptyDataset)
+    ));
+    assert!(matches!(
+        precision_recall_f1(&mut identity(1), &binary(&[(0.5, 1.0), (0.5, 2.0)]), ClassificationMode::default()),
+        Err(NetworkError::InvalidClassEncoding { index: 1 })
+    ));
+}
@@ -0,0 +1,123 @@
+use nalgebra::DVector;
+use rand::{distr::Uniform, rngs::StdRng, SeedableRng};
+
+use neural::{
+    activations::*,
+    network::{layer::Layer, Network, NetworkLoadError},
+};
+
+/// Layers with different activation functions, one without biases and one with batch normalization.
+fn network() -> Network {
+    let mut rng = StdRng::seed_from_u64(8);
+    let uniform = Uniform::new(-1.0, 1.0).unwrap();
+
+    Network::from_layers(vec![
+        Layer::random_with_rng(3, 5, relu!(), &uniform, &mut rng).unwrap(),
+        Layer::random_with_rng(5, 4, tanh!(), &uniform, &mut rng).unwrap().without_bias(),
+        Layer::random_with_rng(4, 4, sigmoid!(), &uniform, &mut rng).unwrap().with_batch_norm(),
+        Layer::random_with_rng(4, 2, identity!(), &uniform, &mut rng).unwrap(),
+    ])
+    .unwrap()
+}
+
+/// `forward` gives bit for bit the same outputs for both networks.
+fn assert_same_outputs(loaded: &mut Network, saved: &mut Network) {
+    assert_eq!(loaded.get_params(), saved.get_params());
+
+    for inputs in [[0.0, 0.0, 0.0], [0.3, -1.7, 2.2], [1e-7, 5.0, -0.25]] {
+        let inputs = DVector::from_row_slice(&inputs);
+        let bits = |outputs: DVector<f32>| outputs.iter().map(|output| output.to_bits()).collect::<Vec<_>>();
+        assert_eq!(bits(loaded.forward(inputs.clone()).unwrap()), bits(saved.forward(inputs).unwrap()));
+    }
+}
+
+#[test]
+fn records_round_trip_bit_for_bit() {
+    let mut saved = network();
+    let mut loaded = Network::from_records(saved.to_records().unwrap()).unwrap();
+
+    assert_same_outputs(&mut loaded, &mut saved);
+}
+
+#[test]
+fn json_round_trips_bit_for_bit() {
+    let mut saved = network();
+    let mut loaded = Network::from_json(&saved.to_json().unwrap()).unwrap();
+
+    assert_same_outputs(&mut loaded, &mut saved);
+}
+
+#[test]
+fn records_that_dont_chain_or_name_an_unknown_activation_are_rejected() {
+    let mut records = network().to_records().unwrap();
+    records.remove(1);
+    assert!(matches!(
+        Network::from_records(records),
+        Err(NetworkLoadError::LayerSizeMismatch { layer: 1, input_size: 4, previous_output_size: 5 })
+    ));
+
+    let mut records = network().to_records().unwrap();
+    records[2].activation = "swish".to_string();
+    assert!(matches!(Network::from_records(records), Err(NetworkLoadError::UnknownActivation { layer: 2, .. })));
+}
+
+/// An activation function that keeps the default name.
+#[derive(Clone)]
+struct Square;
+
+impl ActivationFn for Square {
+    fn apply(&self, x: f32) -> f32 {
+        x * x
+    }
+
+    fn derivative(&self, x: f32, _activation: f32) -> f32 {
+        2.0 * x
+    }
+}
+
+#[test]
+fn custom_activation_functions_are_named_after_their_type() {
+    assert_eq!(Square.name(), "serialization::Square");
+
+    let network = Network::from_layers(vec![Layer::zeros(1, 1, Box::new(Square)).unwrap()]).unwrap();
+    let json = network.to_json().unwrap();
+    assert!(matches!(Network::from_json(&json), Err(NetworkLoadError::UnknownActivation { layer: 0, .. })));
+}
+
+#[cfg(feature = "serde")]
+mod serde {
+    use super::*;
+
+    #[test]
+    fn networks_round_trip_through_serde_bit_for_bit() {
+        let mut saved = network();
+        let json = serde_json::to_string(&saved).unwrap();
+        let mut loaded: Network = serde_json::from_str(&json).unwrap();
+
+        assert_same_outputs(&mut loaded, &mut saved);
+    }
+
+    #[test]
+    fn deserializing_checks_the_layers() {
+        let mut records = network().to_records().unwrap();
+        records.remove(1);
+        let json = serde_json::to_string(&records).unwrap();
+        let error = serde_json::from_str::<Network>(&json).err().unwrap();
+        assert!(error.to_string().contains("layer 1"), "{error}");
+
+        let json = serde_json::to_string(&network()).unwrap().replacen("\"relu\"", "\"swish\"", 1);
+        let error = serde_json::from_str::<Network>(&json).err().unwrap();
+        assert!(error.to_string().contains("swish"), "{error}");
+
+        let ragged = r#"[{"weights":[[1.0],[1.0,2.0]],"biases":null,"activation":"relu","batch_norm":null,"shared_with":null,"maxout_pieces":[]}]"#;
+        assert!(serde_json::from_str::<Network>(ragged).is_err());
+    }
+
+    #[test]
+    fn only_dense_and_maxout_layers_are_serialized() {
+        let embedding = neural::network::Embedding::random(4, 2, 1, &Uniform::new(-1.0, 1.0).unwrap()).unwrap();
+        let network = Network::from_network_layers(vec![Box::new(embedding), Box::new(Layer::zeros(2, 1, identity!()).unwrap())]).unwrap();
+
+        assert!(serde_json::to_string(&network).is_err());
+    }
+}
@@ -0,0 +1,46 @@
+mod common;
+
+use rand::{distr::Uniform, rngs::StdRng, SeedableRng};
+
+use neural::{
+    activations::*,
+    losses::MSE,
+    network::{Migration, Network},
+    training::Trainer,
+};
+
+#[test]
+fn trainer_takes_the_gradient_norm_of_the_merged_gradient() {
+    let mut network = Network::random_with_rng(&[2, 3, 3, 3, 1], tanh!(), &Uniform::new(-1.0, 1.0).unwrap(), &mut StdRng::seed_from_u64(1)).unwrap();
+    network.share_parameters(2, 1).unwrap();
+    let before = network.get_params();
+
+    let report = Trainer::new(MSE, 0.5, 1).fit(&mut network, &common::xor(), &[]).unwrap();
+
+    // The step is the merged gradient times the rate, so its norm is the one the history should have.
+    let step_norm = before.iter().zip(network.get_params()).map(|(a, b)| (a - b).powi(2)).sum::<f32>().sqrt() / 0.5;
+    let gradient_norm = report.history.gradient_norms()[0];
+    assert!((gradient_norm - step_norm).abs() <= 1e-4 * step_norm, "{gradient_norm} vs {step_norm}");
+}
+
+#[test]
+fn version_3_files_load_without_sharing() {
+    let mut file = b"NRLN".to_vec();
+    file.extend(3u32.to_le_bytes());
+    file.push(0);
+    file.extend(1u32.to_le_bytes());
+    file.extend(1u32.to_le_bytes());
+    file.extend(1u32.to_le_bytes());
+    file.extend(8u32.to_le_bytes());
+    file.extend(b"identity");
+    file.extend([0, 1]);
+    file.extend(2.0f32.to_le_bytes());
+    file.extend(0.5f32.to_le_bytes());
+
+    let (network, report) = Network::read_from_with_report(file.as_slice()).unwrap();
+
+    assert_eq!(report.version, 3);
+    assert_eq!(report.migrations, vec![Migration::AssumedUnshared, Migration::AssumedSinglePiece]);
+    assert_eq!(network.shared_parameters(0), None);
+    assert_eq!(network.get_params(), vec![2.0, 0.5]);
+}
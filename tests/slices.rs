@@ -0,0 +1,71 @@
+//! Inference and samples from plain slices, without importing nalgebra.
+
+use rand::{distr::Uniform, rngs::StdRng, SeedableRng};
+
+use neural::{
+    activations::*,
+    dataset::Sample,
+    network::{layer::LayerError, Network, NetworkError},
+};
+
+fn network() -> Network {
+    Network::random_with_rng(&[3, 5, 2], sigmoid!(), &Uniform::new(-1.0, 1.0).unwrap(), &mut StdRng::seed_from_u64(4)).unwrap()
+}
+
+const INPUTS: [[f32; 3]; 3] = [[0.0, 0.0, 0.0], [0.5, -1.0, 2.0], [-3.0, 0.25, 1.0]];
+
+#[test]
+fn slices_give_the_same_outputs_as_vectors() {
+    let mut network = network();
+
+    for input in INPUTS {
+        let sample = Sample::from_slices(&input, &[0.0, 0.0]);
+        let expected = network.predict(sample.inputs()).unwrap();
+
+        assert_eq!(network.predict_slice(&input).unwrap(), expected.as_slice());
+        assert_eq!(network.forward_slice(&input).unwrap(), expected.as_slice());
+        assert_eq!(network.forward_view(sample.inputs()).unwrap().as_slice(), expected.as_slice());
+    }
+}
+
+#[test]
+fn slices_work_for_f64_networks() {
+    let network = Network::<f64>::random_with_rng(&[2, 3, 1], tanh!(), &Uniform::new(-1.0, 1.0).unwrap(), &mut StdRng::seed_from_u64(1)).unwrap();
+    let sample = Sample::from_slices(&[0.3, -0.7], &[0.0]);
+
+    assert_eq!(network.predict_slice(&[0.3, -0.7]).unwrap(), network.predict(sample.inputs()).unwrap().as_slice());
+}
+
+#[test]
+fn a_slice_of_the_wrong_length_is_rejected() {
+    let mut network = network();
+
+    for input in [&[1.0, 2.0][..], &[1.0, 2.0, 3.0, 4.0], &[]] {
+        let mismatch = |result: Result<Vec<f32>, NetworkError>| {
+            matches!(
+                result,
+                Err(NetworkError::LayerError(LayerError::InputSizeMismatch { layer_input_size: 3, given_input_size }))
+                    if given_input_size == input.len()
+            )
+        };
+
+        assert!(mismatch(network.predict_slice(input)), "{input:?}");
+        assert!(mismatch(network.forward_slice(input)), "{input:?}");
+    }
+}
+
+#[test]
+fn samples_convert_from_slices_arrays_and_vectors() {
+    let expected = Sample::from_slices(&[1.0, 2.0], &[3.0]);
+
+    let samples: [Sample; 3] = [
+        (&[1.0, 2.0][..], &[3.0][..]).into(),
+        ([1.0, 2.0], [3.0]).into(),
+        (vec![1.0, 2.0], vec![3.0]).into(),
+    ];
+
+    for sample in samples {
+        assert_eq!(sample.inputs(), expected.inputs());
+        assert_eq!(sample.expected_outputs(), expected.expected_outputs());
+    }
+}
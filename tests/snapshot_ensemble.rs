@@ -0,0 +1,68 @@
+mod common;
+
+use std::ops::ControlFlow;
+
+use rand::{distr::Uniform, rngs::StdRng, SeedableRng};
+
+use neural::{
+    activations::*,
+    losses::MSE,
+    network::Network,
+    training::{
+        callback::{BatchContext, BestLossTracker, EpochContext, TrainingCallback},
+        snapshot_ensemble::SnapshotEnsemble,
+        Trainer,
+    },
+};
+
+#[derive(Default)]
+struct Epochs {
+    epochs: Vec<usize>,
+    batch_epochs: Vec<(usize, Option<usize>)>,
+}
+
+impl TrainingCallback for Epochs {
+    fn on_epoch_end(&mut self, ctx: &EpochContext) -> ControlFlow<()> {
+        self.epochs.push(ctx.epoch);
+        ControlFlow::Continue(())
+    }
+
+    fn on_batch_end(&mut self, ctx: &BatchContext) {
+        self.batch_epochs.push((ctx.epoch, ctx.epochs));
+    }
+}
+
+fn network(rng: &mut StdRng) -> Network {
+    Network::random_with_rng(&[2, 4, 1], sigmoid!(), &Uniform::new(-1.0, 1.0).unwrap(), rng).unwrap()
+}
+
+#[test]
+fn callbacks_count_epochs_across_cycles() {
+    let mut rng = StdRng::seed_from_u64(1);
+    let mut network = network(&mut rng);
+    let mut epochs = Epochs::default();
+
+    let trainer = Trainer::new(MSE, 0.5, 1).batch_size(2).callback(&mut epochs);
+    let report = SnapshotEnsemble::new(trainer, 1.0, 3, 4).unwrap().fit_with_rng(&mut network, &common::xor(), &[], &mut rng).unwrap();
+
+    assert_eq!(report.snapshot_epochs, vec![2, 5, 8, 11]);
+    assert_eq!(epochs.epochs, (0..12).collect::<Vec<_>>());
+    assert_eq!(epochs.batch_epochs.len(), 24);
+    assert!(epochs.batch_epochs.iter().enumerate().all(|(i, &batch)| batch == (i / 2, Some(12))));
+}
+
+#[test]
+fn patience_spans_cycles() {
+    let mut rng = StdRng::seed_from_u64(2);
+    let mut network = network(&mut rng);
+    let mut tracker = BestLossTracker::with_patience(4);
+
+    // The rate is too small to change the loss, so it never improves on the first epoch's.
+    let trainer = Trainer::new(MSE, 0.5, 1).callback(&mut tracker);
+    let report = SnapshotEnsemble::new(trainer, 1e-30, 3, 3).unwrap().fit_with_rng(&mut network, &common::xor(), &[], &mut rng).unwrap();
+
+    assert!(report.stopped_early);
+    assert_eq!(report.history.len(), 5);
+    assert_eq!(report.snapshot_epochs, vec![2]);
+    assert_eq!(tracker.best_epoch(), Some(0));
+}
@@ -0,0 +1,84 @@
+mod common;
+
+use nalgebra::DVector;
+use rand::{distr::Uniform, rngs::StdRng, SeedableRng};
+
+use neural::{
+    activations::*,
+    dataset::Sample,
+    losses::MSE,
+    network::{layer::LayerError, Network, NetworkError, SparseSample},
+};
+
+use common::assert_close;
+
+fn network() -> Network<f64> {
+    Network::random_with_rng(&[6, 4, 2], tanh!(), &Uniform::new(-0.5, 0.5).unwrap(), &mut StdRng::seed_from_u64(7)).unwrap()
+}
+
+fn sparse_dataset() -> Vec<SparseSample<f64>> {
+    vec![
+        (vec![(0, 1.0), (3, -0.5)], DVector::from_vec(vec![0.3, -0.2])),
+        (vec![(5, 2.0)], DVector::from_vec(vec![-0.7, 0.1])),
+        (vec![(1, 0.25), (2, 0.75), (4, -1.0)], DVector::from_vec(vec![0.0, 0.9])),
+        (vec![], DVector::from_vec(vec![0.5, 0.5])),
+    ]
+}
+
+fn densify((inputs, expected_outputs): &SparseSample<f64>) -> Sample<f64> {
+    let mut dense = DVector::zeros(6);
+
+    for &(index, value) in inputs {
+        dense[index] = value;
+    }
+
+    Sample::new(dense, expected_outputs.clone())
+}
+
+#[test]
+fn matches_the_dense_passes() {
+    let sparse = sparse_dataset();
+    let dense: Vec<_> = sparse.iter().map(densify).collect();
+    let (mut sparse_network, mut dense_network) = (network(), network());
+
+    for (sparse_sample, dense_sample) in sparse.iter().zip(&dense) {
+        let sparse_outputs = sparse_network.predict_sparse(&sparse_sample.0).unwrap();
+        let dense_outputs = dense_network.predict(dense_sample.inputs()).unwrap();
+        assert_close(sparse_outputs.as_slice(), dense_outputs.as_slice(), 1e-12);
+    }
+
+    for _ in 0..5 {
+        let sparse_loss = sparse_network.learn_sparse(&sparse, &MSE, 0.3).unwrap();
+        let dense_loss = dense_network.learn(&dense, &MSE, 0.3).unwrap();
+        assert!((sparse_loss - dense_loss).abs() < 1e-12);
+    }
+
+    assert_close(&sparse_network.get_params(), &dense_network.get_params(), 1e-12);
+}
+
+#[test]
+fn rejects_an_index_past_the_inputs() {
+    let mut network = network();
+    let before = network.get_params();
+    let mut dataset = sparse_dataset();
+    dataset[2].0.push((6, 1.0));
+
+    assert!(matches!(
+        network.predict_sparse(&dataset[2].0),
+        Err(NetworkError::LayerError(LayerError::SparseIndexOutOfRange { .. }))
+    ));
+    assert!(network.learn_sparse(&dataset, &MSE, 0.3).is_err());
+    assert_eq!(network.get_params(), before);
+}
+
+#[test]
+fn rejects_batch_normalization() {
+    let mut network = network().with_batch_norm();
+    let before = network.get_params();
+
+    assert!(matches!(
+        network.learn_sparse(&sparse_dataset(), &MSE, 0.3),
+        Err(NetworkError::BatchNormUnsupported { layer: 0 })
+    ));
+    assert_eq!(network.get_params(), before);
+}
@@ -0,0 +1,128 @@
+mod common;
+
+use std::{
+    ops::ControlFlow,
+    thread,
+    time::{Duration, Instant},
+};
+
+use rand::{distr::Uniform, rngs::StdRng, SeedableRng};
+
+use neural::{
+    activations::*,
+    losses::MSE,
+    network::Network,
+    training::{
+        callback::{BatchContext, EpochContext, TrainingCallback},
+        stop::StopToken,
+        Trainer,
+    },
+};
+
+use common::xor;
+
+fn network() -> Network {
+    Network::random_with_rng(&[2, 4, 1], sigmoid!(), &Uniform::new(-1.0, 1.0).unwrap(), &mut StdRng::seed_from_u64(70)).unwrap()
+}
+
+/// The network after `batches` single-sample steps through the XOR samples in order, like the trainer takes them.
+fn stepped(batches: usize) -> Network {
+    let mut network = network();
+    for sample in xor().iter().cycle().take(batches) {
+        network.learn(std::slice::from_ref(sample), &MSE, 0.5).unwrap();
+    }
+
+    network
+}
+
+/// Stops the token after the given batch of the given epoch.
+struct StopAt {
+    token: StopToken,
+    epoch: usize,
+    batch: usize,
+}
+
+impl TrainingCallback for StopAt {
+    fn on_epoch_end(&mut self, _: &EpochContext) -> ControlFlow<()> {
+        ControlFlow::Continue(())
+    }
+
+    fn on_batch_end(&mut self, ctx: &BatchContext) {
+        if (ctx.epoch, ctx.batch) == (self.epoch, self.batch) {
+            self.token.stop();
+        }
+    }
+}
+
+#[test]
+fn stopping_from_another_thread_ends_training_promptly() {
+    let token = StopToken::new();
+    let stopper = {
+        let token = token.clone();
+        thread::spawn(move || {
+            thread::sleep(Duration::from_millis(50));
+            token.stop();
+            Instant::now()
+        })
+    };
+
+    let mut network = network();
+    let report = Trainer::new(MSE, 0.5, usize::MAX)
+        .batch_size(1)
+        .stop_token(token.clone())
+        .fit_with_rng(&mut network, &xor(), &[], &mut StdRng::seed_from_u64(0))
+        .unwrap();
+    let returned = Instant::now();
+    let stopped = stopper.join().unwrap();
+
+    assert!(report.cancelled && !report.stopped_early);
+    assert!(returned.duration_since(stopped) < Duration::from_millis(500));
+    assert!(token.is_stopped());
+
+    // Only whole epochs are in the history, and the batches of the cut epoch were all applied in full.
+    let epochs = report.history.len();
+    assert!(epochs > 0);
+    assert!((4 * epochs..4 * epochs + 4).contains(&report.batches), "{} batches in {epochs} epochs", report.batches);
+    assert!(report.history.train_losses().iter().all(|loss| loss.is_finite()));
+    assert_eq!(network.get_params(), stepped(report.batches).get_params());
+}
+
+#[test]
+fn training_stops_at_the_next_batch_boundary() {
+    let token = StopToken::new();
+    let mut network = network();
+
+    let report = Trainer::new(MSE, 0.5, 10)
+        .batch_size(1)
+        .stop_token(token.clone())
+        .callback(StopAt { token, epoch: 3, batch: 2 })
+        .fit_with_rng(&mut network, &xor(), &[], &mut StdRng::seed_from_u64(0))
+        .unwrap();
+
+    // Epochs 0 to 2 and the first 3 batches of epoch 3.
+    assert!(report.cancelled);
+    assert_eq!((report.history.len(), report.batches), (3, 15));
+    assert_eq!(network.get_params(), stepped(15).get_params());
+}
+
+#[test]
+fn a_stopped_token_trains_nothing_until_reset() {
+    let token = StopToken::new();
+    let other_holder = token.clone();
+    other_holder.stop();
+    assert!(token.is_stopped());
+
+    let mut network = network();
+    let trainer = || Trainer::new(MSE, 0.5, 3).batch_size(1).stop_token(token.clone());
+
+    let report = trainer().fit_with_rng(&mut network, &xor(), &[], &mut StdRng::seed_from_u64(0)).unwrap();
+    assert!(report.cancelled);
+    assert_eq!((report.history.len(), report.batches), (0, 0));
+    assert_eq!(network.get_params(), self::network().get_params());
+
+    other_holder.reset();
+    assert!(!token.is_stopped());
+    let report = trainer().fit_with_rng(&mut network, &xor(), &[], &mut StdRng::seed_from_u64(0)).unwrap();
+    assert!(!report.cancelled);
+    assert_eq!((report.history.len(), report.batches), (3, 12));
+}
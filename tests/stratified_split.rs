@@ -0,0 +1,110 @@
+use std::collections::BTreeMap;
+
+use rand::{rngs::StdRng, SeedableRng};
+
+use neural::dataset::{argmax, stratified_split, stratified_split_by, DatasetError, Sample};
+
+/// `counts[class]` one-hot samples of every class, each tagged with its class and index.
+fn dataset(counts: &[usize]) -> Vec<Sample> {
+    counts
+        .iter()
+        .enumerate()
+        .flat_map(|(class, &count)| {
+            (0..count).map(move |i| {
+                let mut outputs = vec![0.0; counts.len()];
+                outputs[class] = 1.0;
+                Sample::from_slices(&[class as f32, i as f32], &outputs).with_tag(format!("{class}-{i}"))
+            })
+        })
+        .collect()
+}
+
+fn class_counts(samples: &[Sample]) -> BTreeMap<usize, usize> {
+    let mut counts = BTreeMap::new();
+    for sample in samples {
+        *counts.entry(argmax(sample.expected_outputs())).or_insert(0) += 1;
+    }
+    counts
+}
+
+fn tags(samples: &[Sample]) -> Vec<&str> {
+    samples.iter().map(|sample| sample.tag().unwrap()).collect()
+}
+
+#[test]
+fn every_class_is_split_in_proportion() {
+    let samples = dataset(&[20, 10, 5, 3]);
+    let split = stratified_split(&samples, 0.8, &mut StdRng::seed_from_u64(1)).unwrap();
+
+    // 0.8 of every class, rounded: 16 of 20, 8 of 10, 4 of 5 and 2 of 3.
+    assert_eq!(class_counts(&split.train), BTreeMap::from([(0, 16), (1, 8), (2, 4), (3, 2)]));
+    assert_eq!(class_counts(&split.test), BTreeMap::from([(0, 4), (1, 2), (2, 1), (3, 1)]));
+    assert!(split.singleton_classes.is_empty());
+
+    // Every sample ends up in exactly one part.
+    let mut all = tags(&split.train);
+    all.extend(tags(&split.test));
+    all.sort_unstable();
+    let mut expected = tags(&samples);
+    expected.sort_unstable();
+    assert_eq!(all, expected);
+}
+
+#[test]
+fn single_samples_of_a_class_go_to_training() {
+    let samples = dataset(&[6, 1, 4, 1]);
+
+    for fraction in [0.0, 0.5, 1.0] {
+        let split = stratified_split(&samples, fraction, &mut StdRng::seed_from_u64(2)).unwrap();
+
+        assert_eq!(split.singleton_classes, [1, 3]);
+        assert!(tags(&split.train).contains(&"1-0") && tags(&split.train).contains(&"3-0"));
+        assert_eq!(class_counts(&split.test).get(&1), None);
+        assert_eq!(split.train.len() + split.test.len(), 12);
+    }
+
+    let split = stratified_split(&samples, 0.0, &mut StdRng::seed_from_u64(2)).unwrap();
+    assert_eq!(class_counts(&split.train), BTreeMap::from([(1, 1), (3, 1)]));
+}
+
+#[test]
+fn a_seed_gives_the_same_split() {
+    let samples = dataset(&[30, 12]);
+    let split = |seed| stratified_split(&samples, 0.75, &mut StdRng::seed_from_u64(seed)).unwrap();
+    let (first, again, other) = (split(3), split(3), split(4));
+
+    assert_eq!(tags(&first.train), tags(&again.train));
+    assert_eq!(tags(&first.test), tags(&again.test));
+    assert_ne!(tags(&first.test), tags(&other.test));
+
+    // The test set is a shuffled draw from every class, not the last samples of each.
+    let mut zeros: Vec<_> = tags(&first.test).into_iter().filter(|tag| tag.starts_with("0-")).collect();
+    zeros.sort_unstable();
+    assert_eq!(zeros.len(), 7);
+    assert_ne!(zeros, (23..30).map(|i| format!("0-{i}")).collect::<Vec<_>>());
+}
+
+#[test]
+fn a_labeler_classifies_other_targets() {
+    // A single regression output, split by its sign.
+    let samples: Vec<_> = (0..10).map(|i| Sample::from_slices(&[i as f32], &[if i < 8 { -1.0 } else { 1.0 }])).collect();
+    let split = stratified_split_by(&samples, 0.5, &mut StdRng::seed_from_u64(5), |sample| (sample.expected_outputs()[0] > 0.0) as usize).unwrap();
+
+    let positives = |samples: &[Sample]| samples.iter().filter(|sample| sample.expected_outputs()[0] > 0.0).count();
+    assert_eq!((split.train.len(), positives(&split.train)), (5, 1));
+    assert_eq!((split.test.len(), positives(&split.test)), (5, 1));
+}
+
+#[test]
+fn invalid_fractions_and_empty_outputs_are_rejected() {
+    let samples = dataset(&[3, 3]);
+    let mut rng = StdRng::seed_from_u64(6);
+
+    for fraction in [-0.1, 1.5, f32::NAN] {
+        assert!(matches!(stratified_split(&samples, fraction, &mut rng), Err(DatasetError::InvalidFraction(_))), "{fraction}");
+    }
+
+    let mut samples = samples;
+    samples.push(Sample::from_slices(&[0.0, 0.0], &[]));
+    assert!(matches!(stratified_split(&samples, 0.5, &mut rng), Err(DatasetError::EmptyExpectedOutputs { index: 6 })));
+}
@@ -0,0 +1,74 @@
+use rand::{distr::Uniform, rngs::StdRng, Rng, SeedableRng};
+
+use neural::{activations::*, dataset::Sample, losses::MSE, network::Network};
+
+fn network() -> Network {
+    Network::random_with_rng(&[2, 4, 1], tanh!(), &Uniform::new(-1.0, 1.0).unwrap(), &mut StdRng::seed_from_u64(26)).unwrap()
+}
+
+fn dataset(len: usize) -> Vec<Sample> {
+    let mut rng = StdRng::seed_from_u64(27);
+    (0..len)
+        .map(|_| {
+            let inputs = [rng.random_range(-1.0..1.0), rng.random_range(-1.0..1.0)];
+            Sample::from_slices(&inputs, &[inputs[0] - inputs[1]])
+        })
+        .collect()
+}
+
+#[test]
+fn steps_like_training_on_chunks_of_the_slice() {
+    let dataset = dataset(10);
+    let mut streamed = network();
+    let mut sliced = network();
+
+    // The last batch only has one sample.
+    let consumed = streamed.learn_from_iter(dataset.iter().cloned(), &MSE, 0.1, 3).unwrap();
+    for batch in dataset.chunks(3) {
+        sliced.learn(batch, &MSE, 0.1).unwrap();
+    }
+
+    assert_eq!(consumed, 10);
+    assert_eq!(streamed.get_params(), sliced.get_params());
+}
+
+#[test]
+fn trains_on_samples_generated_on_the_fly() {
+    let generate = |i: usize| {
+        let x = i as f32 / 500.0 - 1.0;
+        Sample::from_slices(&[x, -x], &[x * x])
+    };
+    let mut streamed = network();
+    let mut sliced = network();
+
+    let consumed = streamed.learn_from_iter((0..1000).map(generate), &MSE, 0.1, 64).unwrap();
+    let dataset: Vec<_> = (0..1000).map(generate).collect();
+    for batch in dataset.chunks(64) {
+        sliced.learn(batch, &MSE, 0.1).unwrap();
+    }
+
+    assert_eq!(consumed, 1000);
+    assert_eq!(streamed.get_params(), sliced.get_params());
+}
+
+#[test]
+fn an_empty_iterator_is_a_no_op() {
+    let mut network = network();
+    let before = network.get_params();
+
+    assert_eq!(network.learn_from_iter(std::iter::empty(), &MSE, 0.1, 4).unwrap(), 0);
+    assert_eq!(network.get_params(), before);
+}
+
+#[test]
+fn a_bad_sample_stops_the_stream() {
+    let mut dataset = dataset(9);
+    dataset[5] = Sample::from_slices(&[1.0], &[0.0]);
+    let mut network = network();
+    let mut first_batch = self::network();
+    first_batch.learn(&dataset[..3], &MSE, 0.1).unwrap();
+
+    assert!(network.learn_from_iter(dataset, &MSE, 0.1, 3).is_err());
+    // The batch before the bad one was trained on, the bad one wasn't.
+    assert_eq!(network.get_params(), first_batch.get_params());
+}
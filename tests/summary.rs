@@ -0,0 +1,123 @@
+use nalgebra::{DMatrix, DVector};
+use rand::{distr::Uniform, rngs::StdRng, SeedableRng};
+
+use neural::{
+    activations::*,
+    network::{layer::Layer, Network, NetworkLayer, Softmax},
+};
+
+fn network(sizes: &[usize]) -> Network {
+    Network::random_with_rng(sizes, sigmoid!(), &Uniform::new(-1.0, 1.0).unwrap(), &mut StdRng::seed_from_u64(0)).unwrap()
+}
+
+#[test]
+fn networks_display_their_architecture_on_one_line() {
+    assert_eq!(network(&[2, 50, 1]).to_string(), "Network: 2 -> 50 (sigmoid) -> 1 (sigmoid), 201 parameters");
+
+    let single = Network::<f64>::random_with_rng(&[1, 1], tanh!(), &Uniform::new(-1.0, 1.0).unwrap(), &mut StdRng::seed_from_u64(0)).unwrap();
+    assert_eq!(format!("{single}"), "Network: 1 -> 1 (tanh), 2 parameters");
+}
+
+#[test]
+fn layers_display_like_networks() {
+    let network = network(&[2, 50, 1]);
+
+    assert_eq!(network.dense_layer(0).unwrap().to_string(), "Layer: 2 -> 50 (sigmoid), 150 parameters");
+    assert_eq!(network.dense_layer(1).unwrap().to_string(), "Layer: 50 -> 1 (sigmoid), 51 parameters");
+}
+
+#[test]
+fn batch_norm_and_other_layers_are_named() {
+    // Batch normalization parameters aren't counted, as in `get_params`.
+    assert_eq!(network(&[3, 4, 2]).with_batch_norm().to_string(), "Network: 3 -> 4 (sigmoid + batch norm) -> 2 (sigmoid), 26 parameters");
+
+    let layers: Vec<Box<dyn NetworkLayer>> = vec![Box::new(Layer::zeros(2, 3, identity!()).unwrap()), Box::new(Softmax::new(3, 1.0).unwrap())];
+    let softmax = Network::from_network_layers(layers).unwrap();
+    assert_eq!(softmax.to_string(), "Network: 2 -> 3 (identity) -> 3 (softmax), 9 parameters");
+}
+
+#[test]
+fn shared_parameters_are_counted_once() {
+    let mut network = network(&[4, 4, 4, 1]);
+    network.share_parameters(1, 0).unwrap();
+
+    assert_eq!(network.to_string(), "Network: 4 -> 4 (sigmoid) -> 4 (sigmoid) -> 1 (sigmoid), 25 parameters");
+    assert_eq!(
+        network.summary(),
+        "layer  inputs  outputs  activation  parameters\n\
+         0      4       4        sigmoid     20\n\
+         1      4       4        sigmoid     shared with 0\n\
+         2      4       1        sigmoid     5\n\
+         total parameters: 25\n"
+    );
+}
+
+#[test]
+fn summary_columns_fit_the_widest_cell() {
+    assert_eq!(
+        network(&[2, 50, 1]).with_batch_norm().summary(),
+        "layer  inputs  outputs  activation            parameters\n\
+         0      2       50       sigmoid + batch norm  150\n\
+         1      50      1        sigmoid               51\n\
+         total parameters: 201\n"
+    );
+}
+
+#[test]
+fn summary_lists_every_layer_and_the_total() {
+    assert_eq!(
+        network(&[2, 50, 1]).summary(),
+        "layer  inputs  outputs  activation  parameters\n\
+         0      2       50       sigmoid     150\n\
+         1      50      1        sigmoid     51\n\
+         total parameters: 201\n"
+    );
+    assert_eq!(network(&[2, 50, 1]).parameter_count(), 201);
+}
+
+fn dump(network: &Network, precision: usize) -> String {
+    let mut dump = String::new();
+    network.dump_weights(&mut dump, precision).unwrap();
+    dump
+}
+
+#[test]
+fn weights_are_dumped_right_aligned_with_the_given_precision() {
+    let mut layer = Layer::zeros(3, 2, identity!()).unwrap();
+    layer.set_weights(DMatrix::from_row_slice(2, 3, &[1.0, -0.75, 12.25, 0.126, 3.0, -10.0])).unwrap();
+    layer.set_biases(DVector::from_vec(vec![0.5, -2.0])).unwrap();
+    let layers: Vec<Box<dyn NetworkLayer>> = vec![Box::new(layer), Box::new(Softmax::new(2, 1.0).unwrap())];
+    let network = Network::from_network_layers(layers).unwrap();
+
+    assert_eq!(
+        dump(&network, 2),
+        "layer 0 weights (2 x 3):\n\
+         \x20   1.00  -0.75  12.25\n\
+         \x20   0.13   3.00 -10.00\n\
+         layer 0 biases (2): 0.50 -2.00\n\
+         layer 1: softmax layer with 0 parameters\n"
+    );
+    assert_eq!(
+        dump(&network, 0),
+        "layer 0 weights (2 x 3):\n\
+         \x20   1  -1  12\n\
+         \x20   0   3 -10\n\
+         layer 0 biases (2): 0 -2\n\
+         layer 1: softmax layer with 0 parameters\n"
+    );
+}
+
+#[test]
+fn large_layers_are_cut_off_with_their_full_size() {
+    let mut layer = Layer::zeros(10, 12, identity!()).unwrap();
+    layer.set_weights(DMatrix::from_element(12, 10, 1.0)).unwrap();
+    let dump = dump(&Network::from_layers(vec![layer]).unwrap(), 1);
+    let lines: Vec<_> = dump.lines().collect();
+
+    assert_eq!(lines[0], "layer 0 weights (12 x 10):");
+    // 8 rows of 8 columns, each marked as cut off, then the count of the rows left out.
+    assert_eq!(lines.len(), 1 + 8 + 1 + 1);
+    assert!(lines[1..9].iter().all(|&line| line == format!("  {}  …", ["1.0"; 8].join(" "))));
+    assert_eq!(lines[9], "  … 4 more rows");
+    assert_eq!(lines[10], format!("layer 0 biases (12): {} …", ["0.0"; 8].join(" ")));
+}
@@ -0,0 +1,52 @@
+use rand::{distr::Uniform, rngs::StdRng, SeedableRng};
+
+use neural::{activations::*, dataset::Sample, losses::MSE, metrics::worst_samples, network::Network, training::Trainer};
+
+fn network() -> Network {
+    Network::random_with_rng(&[1, 1], sigmoid!(), &Uniform::new(-0.1, 0.1).unwrap(), &mut StdRng::seed_from_u64(29)).unwrap()
+}
+
+/// Points labeled by whether they're positive, tagged with their record id. Record 2 is mislabeled.
+fn records() -> Vec<Sample> {
+    (0..20)
+        .map(|id| {
+            let x = (id as f32 - 9.5) / 4.0;
+            let label = if id == 2 { 1.0 } else { f32::from(x > 0.0) };
+            Sample::from_slices(&[x], &[label]).with_tag(format!("record {id}"))
+        })
+        .collect()
+}
+
+#[test]
+fn samples_are_untagged_unless_tagged() {
+    let sample = Sample::from_slices(&[1.0], &[0.0]);
+
+    assert_eq!(sample.tag(), None);
+    assert_eq!(sample.clone().with_tag("id").tag(), Some("id"));
+}
+
+#[test]
+fn training_ignores_tags() {
+    let tagged = records();
+    let untagged: Vec<_> = tagged.iter().map(|sample| Sample::new(sample.inputs().into_owned(), sample.expected_outputs().into_owned())).collect();
+    let (mut with_tags, mut without_tags) = (network(), network());
+
+    Trainer::new(MSE, 1.0, 50).fit(&mut with_tags, &tagged, &tagged).unwrap();
+    Trainer::new(MSE, 1.0, 50).fit(&mut without_tags, &untagged, &untagged).unwrap();
+
+    assert_eq!(with_tags.get_params(), without_tags.get_params());
+}
+
+#[test]
+fn the_worst_samples_trace_back_to_the_mislabeled_record() {
+    let records = records();
+    let mut network = network();
+    Trainer::new(MSE, 1.0, 500).fit(&mut network, &records, &[]).unwrap();
+
+    let worst = worst_samples(&mut network, &records, &MSE, 2).unwrap();
+
+    assert_eq!(worst[0].tag.as_deref(), Some("record 2"));
+    assert_eq!(worst[0].index, 2);
+    // Every other record is fitted far better than the mislabeled one.
+    assert!(worst[1].loss < worst[0].loss / 4.0, "{} vs {}", worst[1].loss, worst[0].loss);
+}
@@ -0,0 +1,79 @@
+use nalgebra::DMatrix;
+
+use neural::{
+    activations::*,
+    dataset::Sample,
+    metrics::{best_threshold, roc_curve, ThresholdCriterion},
+    network::{layer::Layer, Network, NetworkError},
+};
+
+/// Outputs its input, so a sample's input is its score.
+fn network() -> Network {
+    let mut layer = Layer::zeros(1, 1, identity!()).unwrap();
+    layer.set_weights(DMatrix::from_element(1, 1, 1.0)).unwrap();
+    Network::from_layers(vec![layer]).unwrap()
+}
+
+fn dataset(scores: &[(f32, f32)]) -> Vec<Sample> {
+    scores.iter().map(|&(score, label)| Sample::from_slices(&[score], &[label])).collect()
+}
+
+/// Mostly separated at 0.3, with a positive scored 0.05 and a negative scored 0.6.
+fn separated_at_0_3() -> Vec<Sample> {
+    dataset(&[
+        (0.05, 1.0), (0.1, 0.0), (0.15, 0.0), (0.2, 0.0), (0.25, 0.0),
+        (0.3, 1.0), (0.35, 1.0), (0.4, 1.0), (0.6, 0.0), (0.8, 1.0),
+    ])
+}
+
+#[test]
+fn recovers_the_best_threshold_for_every_criterion() {
+    // At 0.3, 4 of the 5 positives and 1 of the 5 negatives count as positive.
+    for (criterion, value) in [(ThresholdCriterion::F1, 0.8), (ThresholdCriterion::YoudenJ, 0.6), (ThresholdCriterion::Accuracy, 0.8)] {
+        let best = best_threshold(&mut network(), &separated_at_0_3(), criterion).unwrap();
+
+        assert_eq!(best.threshold, 0.3, "{criterion:?}");
+        assert!((best.value - value).abs() < 1e-6, "{criterion:?}: {}", best.value);
+    }
+}
+
+#[test]
+fn the_curve_holds_every_candidate_from_the_highest() {
+    let best = best_threshold(&mut network(), &separated_at_0_3(), ThresholdCriterion::Accuracy).unwrap();
+    let thresholds: Vec<f32> = best.curve.iter().map(|point| point.threshold).collect();
+
+    assert_eq!(thresholds, [f32::INFINITY, 0.8, 0.6, 0.4, 0.35, 0.3, 0.25, 0.2, 0.15, 0.1, 0.05]);
+    // Nothing counts as positive at an infinite threshold and everything at the lowest output.
+    assert_eq!((best.curve[0].value, best.curve[10].value), (0.5, 0.5));
+    assert!(best.curve.iter().all(|point| point.value <= best.value));
+
+    let roc = roc_curve(&mut network(), &separated_at_0_3()).unwrap();
+    assert_eq!(roc.points.iter().map(|point| point.threshold).collect::<Vec<_>>(), thresholds);
+}
+
+#[test]
+fn tied_scores_are_one_candidate() {
+    let samples = dataset(&[(0.2, 0.0), (0.5, 0.0), (0.5, 1.0), (0.5, 1.0), (0.9, 1.0)]);
+    let best = best_threshold(&mut network(), &samples, ThresholdCriterion::YoudenJ).unwrap();
+
+    assert_eq!(best.curve.iter().map(|point| point.threshold).collect::<Vec<_>>(), [f32::INFINITY, 0.9, 0.5, 0.2]);
+    assert_eq!(best.threshold, 0.5);
+    assert!((best.value - 0.5).abs() < 1e-6);
+}
+
+#[test]
+fn equally_good_thresholds_give_the_highest() {
+    // 0.7 and 0.4 both get 3 of 4 right.
+    let samples = dataset(&[(0.1, 0.0), (0.4, 1.0), (0.7, 0.0), (0.9, 1.0)]);
+    let best = best_threshold(&mut network(), &samples, ThresholdCriterion::Accuracy).unwrap();
+
+    assert_eq!((best.threshold, best.value), (0.9, 0.75));
+}
+
+#[test]
+fn a_single_class_has_no_threshold() {
+    for label in [0.0, 1.0] {
+        let samples = dataset(&[(0.2, label), (0.7, label)]);
+        assert!(matches!(best_threshold(&mut network(), &samples, ThresholdCriterion::F1), Err(NetworkError::SingleClass)));
+    }
+}
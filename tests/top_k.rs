@@ -0,0 +1,93 @@
+use nalgebra::DMatrix;
+use rand::{distr::Uniform, rngs::StdRng, Rng, SeedableRng};
+
+use neural::{
+    activations::*,
+    dataset::Sample,
+    metrics::{accuracy, top_k_accuracy, ClassificationMode},
+    network::{layer::Layer, Network, NetworkError},
+};
+
+/// A network whose outputs are its inputs, so the samples spell out what it predicts.
+fn identity() -> Network {
+    let mut layer = Layer::zeros(4, 4, identity!()).unwrap();
+    layer.set_weights(DMatrix::identity(4, 4)).unwrap();
+    Network::from_layers(vec![layer]).unwrap()
+}
+
+fn one_hot(class: usize) -> [f32; 4] {
+    let mut expected = [0.0; 4];
+    expected[class] = 1.0;
+    expected
+}
+
+/// The rank of the true class among the outputs is 1, 2, 2, 4 and 4.
+fn dataset() -> Vec<Sample> {
+    [
+        ([0.9, 0.1, 0.2, 0.3], 0),
+        ([0.1, 0.4, 0.3, 0.2], 2),
+        // Equal outputs rank the higher index first.
+        ([0.5, 0.5, 0.0, 0.0], 0),
+        ([0.0, 0.0, 0.0, 0.0], 0),
+        ([-0.3, 0.1, 0.2, 0.3], 0),
+    ]
+    .map(|(outputs, class)| Sample::from_slices(&outputs, &one_hot(class)))
+    .to_vec()
+}
+
+#[test]
+fn counts_the_true_class_among_the_k_largest_outputs() {
+    let mut network = identity();
+    let accuracies = [1, 2, 3, 4].map(|k| top_k_accuracy(&mut network, &dataset(), k).unwrap());
+
+    assert_eq!(accuracies, [0.2, 0.6, 0.6, 1.0]);
+}
+
+#[test]
+fn ties_rank_the_higher_index_first() {
+    let mut network = identity();
+    let tied = |class| [Sample::from_slices(&[0.7, 0.7, 0.7, 0.1], &one_hot(class))];
+
+    assert_eq!([1, 2, 3].map(|k| top_k_accuracy(&mut network, &tied(2), k).unwrap()), [1.0, 1.0, 1.0]);
+    assert_eq!([1, 2, 3].map(|k| top_k_accuracy(&mut network, &tied(1), k).unwrap()), [0.0, 1.0, 1.0]);
+    assert_eq!([1, 2, 3].map(|k| top_k_accuracy(&mut network, &tied(0), k).unwrap()), [0.0, 0.0, 1.0]);
+}
+
+#[test]
+fn k_of_1_is_multi_class_accuracy() {
+    let mut network =
+        Network::random_with_rng(&[3, 8, 4], tanh!(), &Uniform::new(-1.0, 1.0).unwrap(), &mut StdRng::seed_from_u64(4)).unwrap();
+    let mut rng = StdRng::seed_from_u64(5);
+    let random: Vec<_> = (0..200)
+        .map(|_| {
+            let inputs: Vec<f32> = (0..3).map(|_| rng.random_range(-1.0..1.0)).collect();
+            Sample::from_slices(&inputs, &one_hot(rng.random_range(0..4)))
+        })
+        .collect();
+
+    let top_1 = top_k_accuracy(&mut network, &random, 1).unwrap();
+    assert!(top_1 > 0.0 && top_1 < 1.0);
+    assert_eq!(top_1, accuracy(&mut network, &random, ClassificationMode::MultiClass).unwrap());
+
+    let mut network = identity();
+    assert_eq!(top_k_accuracy(&mut network, &dataset(), 1).unwrap(), accuracy(&mut network, &dataset(), ClassificationMode::MultiClass).unwrap());
+}
+
+#[test]
+fn k_has_to_be_between_1_and_the_output_size() {
+    for k in [0, 5] {
+        assert!(matches!(
+            top_k_accuracy(&mut identity(), &dataset(), k),
+            Err(NetworkError::InvalidTopK { k: invalid, network_outputs: 4 }) if invalid == k
+        ));
+    }
+}
+
+#[test]
+fn fails_like_accuracy() {
+    assert!(matches!(top_k_accuracy(&mut identity(), &[], 2), Err(NetworkError::EmptyDataset)));
+
+    let mut dataset = dataset();
+    dataset.push(Sample::from_slices(&[0.1, 0.2, 0.3, 0.4], &[0.0; 4]));
+    assert!(matches!(top_k_accuracy(&mut identity(), &dataset, 2), Err(NetworkError::InvalidClassEncoding { index: 5 })));
+}
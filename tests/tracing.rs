@@ -0,0 +1,163 @@
+#![cfg(feature = "tracing")]
+
+mod common;
+
+use std::{
+    fmt,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+};
+
+use rand::{distr::Uniform, rngs::StdRng, SeedableRng};
+use tracing::{
+    field::{Field, Visit},
+    span, Event, Level, Metadata, Subscriber,
+};
+
+use neural::{activations::*, losses::MSE, network::Network, training::Trainer};
+
+/// A span or event with its fields formatted with `Debug`, events under their message.
+#[derive(Clone, Debug)]
+struct Record {
+    name: String,
+    level: Level,
+    fields: Vec<(String, String)>,
+}
+
+impl Record {
+    fn field(&self, name: &str) -> Option<&str> {
+        self.fields.iter().find(|(field, _)| field == name).map(|(_, value)| value.as_str())
+    }
+}
+
+#[derive(Default)]
+struct Fields(Vec<(String, String)>);
+
+impl Visit for Fields {
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        self.0.push((field.name().to_string(), format!("{value:?}")));
+    }
+}
+
+/// Records every span and event.
+#[derive(Clone, Default)]
+struct Capture {
+    spans: Arc<Mutex<Vec<Record>>>,
+    events: Arc<Mutex<Vec<Record>>>,
+    next_id: Arc<AtomicU64>,
+}
+
+impl Capture {
+    fn spans(&self, name: &str) -> Vec<Record> {
+        self.spans.lock().unwrap().iter().filter(|span| span.name == name).cloned().collect()
+    }
+
+    fn events(&self, message: &str) -> Vec<Record> {
+        self.events.lock().unwrap().iter().filter(|event| event.name == message).cloned().collect()
+    }
+}
+
+impl Subscriber for Capture {
+    fn enabled(&self, _metadata: &Metadata<'_>) -> bool {
+        true
+    }
+
+    fn new_span(&self, span: &span::Attributes<'_>) -> span::Id {
+        let mut fields = Fields::default();
+        span.record(&mut fields);
+        self.spans.lock().unwrap().push(Record {
+            name: span.metadata().name().to_string(),
+            level: *span.metadata().level(),
+            fields: fields.0,
+        });
+
+        span::Id::from_u64(self.next_id.fetch_add(1, Ordering::Relaxed) + 1)
+    }
+
+    fn record(&self, _span: &span::Id, _values: &span::Record<'_>) {}
+
+    fn record_follows_from(&self, _span: &span::Id, _follows: &span::Id) {}
+
+    fn event(&self, event: &Event<'_>) {
+        let mut fields = Fields::default();
+        event.record(&mut fields);
+        let message = fields.0.iter().position(|(name, _)| name == "message").map(|i| fields.0.remove(i).1);
+
+        self.events.lock().unwrap().push(Record {
+            name: message.unwrap_or_default(),
+            level: *event.metadata().level(),
+            fields: fields.0,
+        });
+    }
+
+    fn enter(&self, _span: &span::Id) {}
+
+    fn exit(&self, _span: &span::Id) {}
+}
+
+fn network() -> Network {
+    Network::random_with_rng(&[2, 4, 1], sigmoid!(), &Uniform::new(-1.0, 1.0).unwrap(), &mut StdRng::seed_from_u64(3)).unwrap()
+}
+
+#[test]
+fn a_fit_emits_spans_and_an_event_per_epoch() {
+    let capture = Capture::default();
+    let dataset = common::xor();
+    let mut network = network();
+
+    let report = tracing::subscriber::with_default(capture.clone(), || {
+        Trainer::new(MSE, 0.5, 3).batch_size(2).fit(&mut network, &dataset, &dataset).unwrap()
+    });
+
+    let fit = capture.spans("fit");
+    assert_eq!(fit.len(), 1);
+    assert_eq!(fit[0].level, Level::INFO);
+    assert_eq!(fit[0].field("epochs"), Some("3"));
+    assert_eq!(fit[0].field("samples"), Some("4"));
+
+    let epochs: Vec<_> = capture.spans("epoch").iter().map(|span| span.field("epoch").unwrap().to_string()).collect();
+    assert_eq!(epochs, ["0", "1", "2"]);
+
+    // Two batches per epoch, every one a step of its own.
+    assert_eq!(capture.spans("learn").len(), 6);
+    let steps = capture.events("step");
+    assert_eq!(steps.len(), 6);
+    assert!(steps.iter().all(|step| step.level == Level::TRACE && step.field("mean_loss").is_some() && step.field("gradient_norm").is_some()));
+
+    let events = capture.events("epoch");
+    assert_eq!(events.len(), 3);
+    for (i, event) in events.iter().enumerate() {
+        assert_eq!(event.level, Level::INFO);
+        assert_eq!(event.field("epoch"), Some(i.to_string().as_str()));
+
+        let number = |name| event.field(name).unwrap().parse::<f64>().unwrap();
+        assert_eq!(number("train_loss"), report.history.train_losses()[i] as f64);
+        assert_eq!(number("validation_loss"), report.history.validation_losses()[i].unwrap() as f64);
+        assert!(number("gradient_norm") > 0.0);
+        assert_eq!(number("rate"), 0.5);
+        assert!(number("elapsed_ms") >= 0.0);
+    }
+}
+
+#[test]
+fn unusual_steps_are_debug_events() {
+    let capture = Capture::default();
+    let mut network = network();
+
+    tracing::subscriber::with_default(capture.clone(), || {
+        assert_eq!(network.learn(&[], &MSE, 0.5).unwrap(), 0.0);
+        assert!(network.learn_checked(&common::xor(), &MSE, f32::INFINITY).is_err());
+    });
+
+    let empty = capture.events("no step, the dataset is empty or weighs nothing");
+    assert_eq!(empty.len(), 1);
+    assert_eq!(empty[0].level, Level::DEBUG);
+    assert_eq!(empty[0].field("samples"), Some("0"));
+
+    let refused = capture.events("refused a step with a non-finite value");
+    assert_eq!(refused.len(), 1);
+    assert_eq!(refused[0].level, Level::DEBUG);
+    assert!(refused[0].field("kind").is_some());
+}
@@ -0,0 +1,67 @@
+mod common;
+
+use rand::{distr::Uniform, rngs::StdRng, SeedableRng};
+
+use neural::{
+    activations::*,
+    dataset::{BatchSamples, DatasetError, Sample},
+    losses::MSE,
+    network::Network,
+    training::{metric::{MeanLoss, MetricSet}, Trainer},
+};
+
+fn network() -> Network {
+    Network::random_with_rng(&[2, 4, 1], sigmoid!(), &Uniform::new(-1.0, 1.0).unwrap(), &mut StdRng::seed_from_u64(3)).unwrap()
+}
+
+fn assert_close(actual: &[f32], expected: &[f32]) {
+    assert_eq!(actual.len(), expected.len());
+
+    for (actual, expected) in actual.iter().zip(expected) {
+        assert!((actual - expected).abs() < 1e-5, "{actual} != {expected}");
+    }
+}
+
+#[test]
+fn batch_samples_train_like_the_samples() {
+    let samples: Vec<Sample> = common::xor().into_iter().cycle().take(10).collect();
+    let batch_samples = BatchSamples::from_samples(&samples).unwrap();
+
+    for shuffle in [false, true] {
+        let trainer = || Trainer::new(MSE, 0.5, 3).batch_size(3).shuffle(shuffle);
+        let (mut from_samples, mut from_batch) = (network(), network());
+
+        let samples_report = trainer().fit_with_rng(&mut from_samples, &samples, &[], &mut StdRng::seed_from_u64(9)).unwrap();
+        let batch_report = trainer().fit_with_rng(&mut from_batch, &batch_samples, &[], &mut StdRng::seed_from_u64(9)).unwrap();
+
+        assert_close(&from_batch.get_params(), &from_samples.get_params());
+        assert_close(batch_report.history.train_losses(), samples_report.history.train_losses());
+    }
+}
+
+#[test]
+fn batch_samples_provide_samples_for_train_metrics() {
+    let samples = common::xor();
+    let batch_samples = BatchSamples::from_samples(&samples).unwrap();
+    let mut network = network();
+
+    let report = Trainer::new(MSE, 0.5, 2)
+        .metric(MeanLoss::new(MSE), MetricSet::Train)
+        .fit_with_rng(&mut network, &batch_samples, &[], &mut StdRng::seed_from_u64(1))
+        .unwrap();
+
+    let mean_loss = report.history.metric("mean_loss").unwrap();
+    assert_eq!(mean_loss.len(), 2);
+    assert!((mean_loss[1].unwrap() - network.evaluate(&samples, &MSE).unwrap()).abs() < 1e-6);
+}
+
+#[test]
+fn batches_past_the_last_sample_fail() {
+    let batch_samples = BatchSamples::from_samples(&common::xor()).unwrap();
+
+    assert_eq!(batch_samples.batch(1..4).unwrap().len(), 3);
+    assert!(matches!(batch_samples.batch(2..5), Err(DatasetError::BatchOutOfRange { start: 2, end: 5, len: 4 })));
+    #[allow(clippy::reversed_empty_ranges)]
+    let reversed = batch_samples.batch(3..2);
+    assert!(matches!(reversed, Err(DatasetError::BatchOutOfRange { start: 3, end: 2, len: 4 })));
+}
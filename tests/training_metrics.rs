@@ -0,0 +1,77 @@
+mod common;
+
+use rand::{distr::Uniform, rngs::StdRng, SeedableRng};
+
+use neural::{
+    activations::*,
+    dataset::Sample,
+    losses::MSE,
+    metrics::ClassificationMode,
+    network::{Network, NetworkError},
+    training::{
+        callback::BestMetricTracker,
+        metric::{Accuracy, MeanLoss, Metric, MetricSet},
+        Trainer,
+    },
+};
+
+fn network() -> Network {
+    Network::random_with_rng(&[2, 4, 1], sigmoid!(), &Uniform::new(-1.0, 1.0).unwrap(), &mut StdRng::seed_from_u64(3)).unwrap()
+}
+
+#[test]
+fn every_epoch_records_every_metric() {
+    let dataset = common::xor();
+    let mut network = network();
+
+    let report = Trainer::new(MSE, 0.5, 5)
+        .metric(Accuracy::new(ClassificationMode::default()), MetricSet::Both)
+        .metric(MeanLoss::named(MSE, "mse"), MetricSet::Train)
+        .fit(&mut network, &dataset, &dataset)
+        .unwrap();
+    let history = report.history;
+
+    assert_eq!(history.metric_names().collect::<Vec<_>>(), ["accuracy", "validation_accuracy", "mse"]);
+    for name in ["accuracy", "validation_accuracy", "mse"] {
+        let values = history.metric(name).unwrap();
+        assert_eq!(values.len(), 5, "{name}");
+        assert!(values.iter().all(|value| value.is_some_and(|value| (0.0..=1.0).contains(&value))), "{name}: {values:?}");
+    }
+
+    // Training set values are taken after the epoch's last step.
+    assert_eq!(history.metric("mse").unwrap()[4], Some(network.evaluate(&dataset, &MSE).unwrap()));
+    assert_eq!(history.metric("accuracy").unwrap(), history.metric("validation_accuracy").unwrap());
+}
+
+/// Always the same value, so it never improves after the first epoch.
+struct Constant;
+impl Metric for Constant {
+    fn name(&self) -> &str {
+        "constant"
+    }
+
+    fn compute(&self, _network: &mut Network, _dataset: &[Sample]) -> Result<f32, NetworkError> {
+        Ok(0.5)
+    }
+
+    fn higher_is_better(&self) -> bool {
+        true
+    }
+}
+
+#[test]
+fn early_stopping_can_watch_a_named_metric() {
+    let dataset = common::xor();
+    let mut tracker = BestMetricTracker::maximizing("validation_constant").with_patience(2);
+
+    let report = Trainer::new(MSE, 0.5, 20)
+        .metric(Constant, MetricSet::Validation)
+        .callback(&mut tracker)
+        .fit(&mut network(), &dataset, &dataset)
+        .unwrap();
+
+    // Best in epoch 0, and two epochs later the patience runs out.
+    assert!(report.stopped_early);
+    assert_eq!(report.history.len(), 3);
+    assert_eq!((tracker.best_epoch(), tracker.best_value()), (Some(0), Some(0.5)));
+}
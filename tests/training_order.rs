@@ -0,0 +1,31 @@
+mod common;
+
+use rand::{distr::Uniform, rngs::StdRng, seq::SliceRandom, SeedableRng};
+
+use neural::{activations::*, dataset::Sample, losses::MSE, network::Network, training::Trainer};
+
+fn network() -> Network {
+    Network::random_with_rng(&[2, 4, 1], sigmoid!(), &Uniform::new(-1.0, 1.0).unwrap(), &mut StdRng::seed_from_u64(3)).unwrap()
+}
+
+#[test]
+fn shuffled_epochs_train_on_batches_in_the_shuffled_order() {
+    let dataset: Vec<Sample> = common::xor().into_iter().cycle().take(10).collect();
+
+    let mut trained = network();
+    Trainer::new(MSE, 0.5, 3).batch_size(3).shuffle(true).fit_with_rng(&mut trained, &dataset, &[], &mut StdRng::seed_from_u64(9)).unwrap();
+
+    // Every epoch reshuffles the previous one's order, then steps through it in batches.
+    let mut expected = network();
+    let mut rng = StdRng::seed_from_u64(9);
+    let mut order: Vec<usize> = (0..dataset.len()).collect();
+    for _ in 0..3 {
+        order.shuffle(&mut rng);
+        for batch in order.chunks(3) {
+            let batch: Vec<Sample> = batch.iter().map(|&index| dataset[index].clone()).collect();
+            expected.learn(&batch, &MSE, 0.5).unwrap();
+        }
+    }
+
+    assert_eq!(trained.get_params(), expected.get_params());
+}
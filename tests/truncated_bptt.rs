@@ -0,0 +1,215 @@
+mod common;
+
+use core::ops::Range;
+
+use nalgebra::{DMatrix, DVector};
+use rand::{distr::Uniform, rngs::StdRng, SeedableRng};
+
+use neural::{
+    activations::*,
+    dataset::SequenceSample,
+    losses::{LossFn, MSE},
+    network::{
+        layer::Layer,
+        recurrent::{RecurrentLayer, RecurrentNetwork, SequenceLayer},
+    },
+};
+
+use common::assert_close;
+
+fn sequence(length: usize, size: usize, seed: usize) -> Vec<DVector<f64>> {
+    (0..length).map(|t| DVector::from_fn(size, |i, _| ((seed * 17 + t * size + i) as f64 * 0.61).sin())).collect()
+}
+
+fn network(seed: u64) -> RecurrentNetwork<f64> {
+    let uniform = Uniform::new(-0.5, 0.5).unwrap();
+    let mut rng = StdRng::seed_from_u64(seed);
+    let recurrent = RecurrentLayer::random_with_rng(2, 3, tanh!(), &uniform, &mut rng).unwrap();
+    let output = Layer::random_with_rng(3, 1, sigmoid!(), &uniform, &mut rng).unwrap();
+    RecurrentNetwork::new(recurrent, output).unwrap()
+}
+
+/// Sequences of 7, 2 and 5 steps, so chunks of 3 leave a partial last chunk and one sequence fits in a single chunk.
+fn dataset() -> Vec<SequenceSample<f64>> {
+    [7, 2, 5].into_iter().enumerate().map(|(seed, length)| SequenceSample::new(sequence(length, 2, 2 * seed), sequence(length, 1, 2 * seed + 1))).collect()
+}
+
+fn params(network: &RecurrentNetwork<f64>) -> Vec<f64> {
+    let (recurrent, output) = (network.recurrent(), network.output());
+    recurrent
+        .input_weights()
+        .iter()
+        .chain(recurrent.recurrent_weights().iter())
+        .chain(recurrent.biases().iter())
+        .chain(output.weights().iter())
+        .chain(output.biases().iter())
+        .copied()
+        .collect()
+}
+
+fn with_params(network: &RecurrentNetwork<f64>, params: &[f64]) -> RecurrentNetwork<f64> {
+    let (mut recurrent, mut output) = (network.recurrent().clone(), network.output().clone());
+    let (input_weights, rest) = params.split_at(6);
+    let (recurrent_weights, rest) = rest.split_at(9);
+    let (biases, rest) = rest.split_at(3);
+    let (output_weights, output_biases) = rest.split_at(3);
+
+    recurrent.set_input_weights(DMatrix::from_column_slice(3, 2, input_weights)).unwrap();
+    recurrent.set_recurrent_weights(DMatrix::from_column_slice(3, 3, recurrent_weights)).unwrap();
+    recurrent.set_biases(DVector::from_column_slice(biases)).unwrap();
+    output.set_weights(DMatrix::from_column_slice(1, 3, output_weights)).unwrap();
+    output.set_biases(DVector::from_column_slice(output_biases)).unwrap();
+    RecurrentNetwork::new(recurrent, output).unwrap()
+}
+
+/// The loss summed over the steps `range` of a sequence started from the constant `state`, and the state after them.
+fn chunk_loss(network: &RecurrentNetwork<f64>, sample: &SequenceSample<f64>, range: Range<usize>, state: &DVector<f64>) -> (f64, DVector<f64>) {
+    let states = network.recurrent().clone().forward_from(&sample.inputs()[range.clone()], state).unwrap();
+    let loss = states
+        .iter()
+        .zip(&sample.expected_outputs()[range])
+        .map(|(state, expected)| MSE.apply(network.output().predict(state.as_view()).unwrap().as_view(), expected.as_view()).unwrap())
+        .sum();
+    (loss, states.last().cloned().unwrap_or_else(|| state.clone()))
+}
+
+/// Central differences of the summed loss of the given chunks, each started from its constant state.
+fn chunk_gradient(network: &RecurrentNetwork<f64>, chunks: &[(&SequenceSample<f64>, Range<usize>, DVector<f64>)]) -> Vec<f64> {
+    let loss = |params: &[f64]| {
+        let network = with_params(network, params);
+        chunks.iter().map(|(sample, range, state)| chunk_loss(&network, sample, range.clone(), state).0).sum::<f64>()
+    };
+
+    let params = params(network);
+    (0..params.len())
+        .map(|i| {
+            let (mut plus, mut minus) = (params.clone(), params.clone());
+            plus[i] += 1e-6;
+            minus[i] -= 1e-6;
+            (loss(&plus) - loss(&minus)) / 2e-6
+        })
+        .collect()
+}
+
+/// Truncated backpropagation through time the slow way: one numeric gradient step per chunk, carrying every
+/// sequence's state forward as a constant.
+fn reference_chunked(network: &RecurrentNetwork<f64>, dataset: &[SequenceSample<f64>], rate: f64, chunk_length: usize) -> RecurrentNetwork<f64> {
+    let mut network = network.clone();
+    let mut states = vec![DVector::zeros(3); dataset.len()];
+    let longest = dataset.iter().map(SequenceSample::len).max().unwrap();
+
+    for start in (0..longest).step_by(chunk_length) {
+        let chunks: Vec<_> = dataset
+            .iter()
+            .zip(&states)
+            .filter(|(sample, _)| start < sample.len())
+            .map(|(sample, state)| (sample, start..sample.len().min(start + chunk_length), state.clone()))
+            .collect();
+        let steps: usize = chunks.iter().map(|(_, range, _)| range.len()).sum();
+
+        for (state, sample) in states.iter_mut().zip(dataset).filter(|(_, sample)| start < sample.len()) {
+            *state = chunk_loss(&network, sample, start..sample.len().min(start + chunk_length), state).1;
+        }
+
+        let gradient = chunk_gradient(&network, &chunks);
+        let stepped: Vec<_> = params(&network).iter().zip(&gradient).map(|(param, gradient)| param - rate / steps as f64 * gradient).collect();
+        network = with_params(&network, &stepped);
+    }
+
+    network
+}
+
+#[test]
+fn a_chunk_covering_every_sequence_is_full_backpropagation_through_time() {
+    let dataset = dataset();
+    let (mut full, mut chunked, mut longer) = (network(1), network(1), network(1));
+
+    let loss = full.learn(&dataset, &MSE, 0.5).unwrap();
+    assert_eq!(chunked.learn_chunked(&dataset, &MSE, 0.5, 7).unwrap(), loss);
+    assert_eq!(longer.learn_chunked(&dataset, &MSE, 0.5, 100).unwrap(), loss);
+
+    assert_eq!(params(&chunked), params(&full));
+    assert_eq!(params(&longer), params(&full));
+}
+
+#[test]
+fn chunks_carry_the_state_but_not_the_gradient() {
+    let dataset = dataset();
+
+    for chunk_length in [1, 2, 3] {
+        let mut chunked = network(2);
+        chunked.learn_chunked(&dataset, &MSE, 0.5, chunk_length).unwrap();
+
+        let expected = reference_chunked(&network(2), &dataset, 0.5, chunk_length);
+        assert_close(&params(&chunked), &params(&expected), 1e-8);
+    }
+}
+
+#[test]
+fn the_loss_is_the_mean_over_every_step_with_the_state_carried() {
+    let dataset = dataset();
+    let mut chunked = network(3);
+
+    // Without updates, chunking changes nothing about the forward pass.
+    let full_loss = network(3).learn(&dataset, &MSE, 0.0).unwrap();
+    assert!((chunked.learn_chunked(&dataset, &MSE, 0.0, 3).unwrap() - full_loss).abs() < 1e-12);
+    assert_eq!(params(&chunked), params(&network(3)));
+
+    // A zero chunk length is treated as 1.
+    let (mut zero, mut one) = (network(3), network(3));
+    zero.learn_chunked(&dataset, &MSE, 0.5, 0).unwrap();
+    one.learn_chunked(&dataset, &MSE, 0.5, 1).unwrap();
+    assert_eq!(params(&zero), params(&one));
+}
+
+#[test]
+fn truncation_limits_how_far_back_each_output_reaches() {
+    let dataset = dataset();
+
+    // Truncated to a single step, every output only sees the step that produced it, from the state before it.
+    let chunks: Vec<_> = dataset
+        .iter()
+        .flat_map(|sample| {
+            let states = network(4).recurrent().predict(sample.inputs()).unwrap();
+            (0..sample.len()).map(move |t| (sample, t..t + 1, if t == 0 { DVector::zeros(3) } else { states[t - 1].clone() }))
+        })
+        .collect();
+    let gradient = chunk_gradient(&network(4), &chunks);
+    let expected: Vec<_> = params(&network(4)).iter().zip(&gradient).map(|(param, gradient)| param - 0.5 / 14.0 * gradient).collect();
+
+    let mut truncated = network(4).truncation(1);
+    truncated.learn(&dataset, &MSE, 0.5).unwrap();
+    assert_close(&params(&truncated), &expected, 1e-8);
+
+    // Reaching back at least a whole sequence is no truncation at all.
+    let (mut full, mut long) = (network(4), network(4).truncation(7));
+    full.learn(&dataset, &MSE, 0.5).unwrap();
+    long.learn(&dataset, &MSE, 0.5).unwrap();
+    assert_eq!(params(&long), params(&full));
+}
+
+#[test]
+fn learns_to_echo_the_previous_input_in_short_chunks() {
+    let uniform = Uniform::new(-0.5, 0.5).unwrap();
+    let mut rng = StdRng::seed_from_u64(5);
+    let recurrent = RecurrentLayer::random_with_rng(1, 4, tanh!(), &uniform, &mut rng).unwrap();
+    let output = Layer::random_with_rng(4, 1, identity!(), &uniform, &mut rng).unwrap();
+    let mut network = RecurrentNetwork::new(recurrent, output).unwrap();
+
+    // Sequences of 24 steps, far longer than the chunks of 3 gradients flow through.
+    let dataset: Vec<_> = (0..16)
+        .map(|seed| {
+            let inputs: Vec<_> = (0..24).map(|t| DVector::from_element(1, if (seed >> (t % 4)) & 1 == 1 { 0.5 } else { -0.5 })).collect();
+            let outputs = (0..24).map(|t| if t == 0 { DVector::zeros(1) } else { inputs[t - 1].clone() }).collect();
+            SequenceSample::new(inputs, outputs)
+        })
+        .collect();
+
+    let initial_loss = network.learn_chunked(&dataset, &MSE, 0.0, 3).unwrap();
+    let mut loss = initial_loss;
+    for _ in 0..300 {
+        loss = network.learn_chunked(&dataset, &MSE, 0.2, 3).unwrap();
+    }
+
+    assert!(loss < initial_loss / 20.0, "{initial_loss} -> {loss}");
+}
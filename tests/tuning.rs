@@ -0,0 +1,135 @@
+mod common;
+
+use rand::{distr::Uniform, rngs::StdRng, SeedableRng};
+
+use neural::{
+    activations,
+    dataset::Sample,
+    losses::MSE,
+    network::{Lookahead, Network, NetworkError, Sgd},
+    training::{
+        tuning::{grid_search, random_search, Candidate, Grid, SearchReport, SearchSpace, Validation},
+        Trainer,
+    },
+};
+
+/// XOR five times over, so a split leaves samples on both sides.
+fn dataset() -> Vec<Sample> {
+    common::xor().into_iter().cycle().take(20).collect()
+}
+
+fn factory(candidate: &Candidate) -> Result<Network, NetworkError> {
+    let mut layer_sizes = vec![2];
+    layer_sizes.extend(&candidate.hidden_layers);
+    layer_sizes.push(1);
+
+    let activation_fn = activations::from_name(&candidate.activation).unwrap();
+    Network::random_with_rng(&layer_sizes, activation_fn, &Uniform::new(-1.0, 1.0).unwrap(), &mut StdRng::seed_from_u64(7))
+}
+
+fn trainer() -> Trainer<'static, MSE> {
+    Trainer::new(MSE, 0.1, 10)
+}
+
+fn losses(report: &SearchReport) -> Vec<f32> {
+    report.trials.iter().map(|trial| trial.validation_loss).collect()
+}
+
+#[test]
+fn a_grid_search_evaluates_every_combination_in_order_of_the_loss() {
+    let grid = Grid::new().rates([0.5, 2.0]).hidden_layers([vec![3], vec![6]]).epochs([200]);
+    let report = grid_search(&grid, &dataset(), factory, trainer, &mut StdRng::seed_from_u64(1)).unwrap();
+
+    assert_eq!(report.trials.len(), 4);
+    assert_eq!(report.skipped, 0);
+    for candidate in grid.candidates() {
+        assert!(report.trials.iter().any(|trial| trial.candidate == candidate), "{candidate:?}");
+    }
+    assert!(losses(&report).windows(2).all(|pair| pair[0] <= pair[1]));
+    assert_eq!(report.best(), report.trials.first());
+}
+
+#[test]
+fn a_seeded_search_is_reproducible() {
+    let grid = Grid::new().rates([0.5, 1.0]).validation(Validation::KFold(4));
+    let search = |seed| grid_search(&grid, &dataset(), factory, trainer, &mut StdRng::seed_from_u64(seed)).unwrap();
+    assert_eq!(search(3).trials, search(3).trials);
+
+    let space = SearchSpace::new().rates(0.01, 1.0).hidden_layers([2..=5]).activations(["sigmoid", "tanh"]).epochs([20]);
+    let search = |seed| random_search(&space, 4, &dataset(), factory, trainer, &mut StdRng::seed_from_u64(seed)).unwrap();
+    assert_eq!(search(3).trials, search(3).trials);
+    assert_ne!(search(3).trials, search(4).trials);
+}
+
+#[test]
+fn every_candidate_gets_a_fresh_optimizer() {
+    // Lookahead keeps slow weights the size of the network it stepped, which a second candidate of another
+    // size couldn't use.
+    let trainer = || Trainer::new(MSE, 0.1, 10).optimizer(Lookahead::new(Sgd, 3, 0.5).unwrap());
+    let grid = Grid::new().hidden_layers([vec![2], vec![5], vec![3, 3]]);
+
+    let report = grid_search(&grid, &dataset(), factory, trainer, &mut StdRng::seed_from_u64(1)).unwrap();
+    assert_eq!(report.trials.len(), 3);
+}
+
+#[test]
+fn the_budget_skips_the_remaining_candidates() {
+    let grid = Grid::new().rates([0.1, 0.2, 0.3]).hidden_layers([vec![2], vec![3]]).max_candidates(4);
+    let report = grid_search(&grid, &dataset(), factory, trainer, &mut StdRng::seed_from_u64(1)).unwrap();
+
+    assert_eq!((report.trials.len(), report.skipped), (4, 2));
+    // The first four in grid order were the ones evaluated.
+    assert!(report.trials.iter().all(|trial| trial.candidate.rate < 0.25));
+}
+
+#[test]
+fn diverging_candidates_are_stopped_and_ranked_last() {
+    let grid = Grid::new().rates([0.1, 1e3]).activations(["identity"]).hidden_layers([vec![4]]).epochs([500]);
+    let report = grid_search(&grid, &dataset(), factory, trainer, &mut StdRng::seed_from_u64(1)).unwrap();
+
+    assert_eq!(report.trials[0].candidate.rate, 0.1);
+    assert!(!report.trials[0].diverged);
+    assert!(report.trials[1].diverged);
+    assert!(!report.trials[1].validation_loss.is_finite());
+}
+
+#[test]
+fn sampled_candidates_respect_their_distributions() {
+    let space = SearchSpace::new()
+        .rates(1e-4, 1.0)
+        .hidden_layers([3..=6, 1..=1])
+        .batch_sizes([None, Some(2)])
+        .activations(["sigmoid", "tanh", "relu"])
+        .epochs([5, 10]);
+    let mut rng = StdRng::seed_from_u64(11);
+    let candidates: Vec<_> = (0..4000).map(|_| space.sample(&mut rng).unwrap()).collect();
+
+    assert!(candidates.iter().all(|candidate| (1e-4..=1.0).contains(&candidate.rate)));
+    // Log-uniform, so a quarter of the rates fall in each decade.
+    let below = |rate| candidates.iter().filter(|candidate| candidate.rate < rate).count() as f32 / 4000.0;
+    for (decade, rate) in [1e-3, 1e-2, 1e-1].into_iter().enumerate() {
+        assert!((below(rate) - 0.25 * (decade + 1) as f32).abs() < 0.03, "{rate}: {}", below(rate));
+    }
+
+    for size in 3..=6 {
+        let share = candidates.iter().filter(|candidate| candidate.hidden_layers[0] == size).count() as f32 / 4000.0;
+        assert!((share - 0.25).abs() < 0.03, "{size}: {share}");
+    }
+    assert!(candidates.iter().all(|candidate| candidate.hidden_layers[1] == 1));
+    for activation in ["sigmoid", "tanh", "relu"] {
+        let share = candidates.iter().filter(|candidate| candidate.activation == activation).count() as f32 / 4000.0;
+        assert!((share - 1.0 / 3.0).abs() < 0.03, "{activation}: {share}");
+    }
+    assert!(candidates.iter().all(|candidate| [None, Some(2)].contains(&candidate.batch_size)));
+    assert!(candidates.iter().all(|candidate| [5, 10].contains(&candidate.epochs)));
+}
+
+#[test]
+fn the_best_random_trial_is_never_worse_than_another() {
+    let space = SearchSpace::new().rates(0.05, 5.0).hidden_layers([2..=6]).epochs([50]);
+    let report = random_search(&space, 6, &dataset(), factory, trainer, &mut StdRng::seed_from_u64(5)).unwrap();
+
+    assert_eq!(report.trials.len(), 6);
+    let best = report.best().unwrap().validation_loss;
+    assert!(losses(&report).iter().all(|&loss| best <= loss || loss.is_nan()));
+}
@@ -0,0 +1,95 @@
+use nalgebra::{DMatrix, DVector};
+use rand::{distr::Uniform, rngs::StdRng, SeedableRng};
+
+use neural::{
+    activations::*,
+    network::{layer::Layer, Network, NetworkError},
+    visualize::{grid_evaluate, grid_point, to_rgba, Colormap, Gradient},
+};
+
+/// `weights · (x, y)`, to read the coordinates of every cell off the field.
+fn linear(weights: [f32; 2]) -> Network {
+    let mut layer = Layer::zeros(2, 1, identity!()).unwrap();
+    layer.set_weights(DMatrix::from_row_slice(1, 2, &weights)).unwrap();
+    Network::from_layers(vec![layer]).unwrap()
+}
+
+#[test]
+fn the_field_has_a_row_per_y_step_and_a_column_per_x_step() {
+    for (columns, rows) in [(160, 120), (5, 3), (1, 4), (1, 1)] {
+        let field = grid_evaluate(&linear([1.0, 0.0]), -1.0..1.0, 0.0..2.0, (columns, rows)).unwrap();
+        assert_eq!(field.shape(), (rows, columns));
+    }
+
+    assert_eq!(grid_evaluate(&linear([1.0, 0.0]), -1.0..1.0, 0.0..2.0, (0, 3)).unwrap().shape(), (3, 0));
+}
+
+#[test]
+fn corners_map_to_the_ends_of_the_ranges() {
+    let (x_range, y_range, resolution) = (-2.0..4.0, 1.0..3.0, (7, 5));
+    let xs = grid_evaluate(&linear([1.0, 0.0]), x_range.clone(), y_range.clone(), resolution).unwrap();
+    let ys = grid_evaluate(&linear([0.0, 1.0]), x_range.clone(), y_range.clone(), resolution).unwrap();
+
+    // The top left cell is (x_range.start, y_range.end), like the first pixel of an image.
+    let corners = [(0, 0, -2.0, 3.0), (0, 6, 4.0, 3.0), (4, 0, -2.0, 1.0), (4, 6, 4.0, 1.0)];
+    for (row, column, x, y) in corners {
+        assert_eq!((xs[(row, column)], ys[(row, column)]), (x, y), "cell ({row}, {column})");
+        assert_eq!(grid_point(&x_range, &y_range, resolution, row, column), (x, y));
+    }
+
+    // Steps of 1 in x and 0.5 in y between the corners.
+    assert_eq!(xs.row(2).iter().copied().collect::<Vec<_>>(), [-2.0, -1.0, 0.0, 1.0, 2.0, 3.0, 4.0]);
+    assert_eq!(ys.column(3).iter().copied().collect::<Vec<_>>(), [3.0, 2.5, 2.0, 1.5, 1.0]);
+}
+
+#[test]
+fn every_cell_is_the_networks_prediction_at_its_point() {
+    let network =
+        Network::random_with_rng(&[2, 16, 16, 1], tanh!(), &Uniform::new(-1.0, 1.0).unwrap(), &mut StdRng::seed_from_u64(1)).unwrap();
+    let (x_range, y_range, resolution) = (0.0..1.0, 0.0..1.0, (16, 12));
+    let field = grid_evaluate(&network, x_range.clone(), y_range.clone(), resolution).unwrap();
+
+    for row in 0..12 {
+        for column in 0..16 {
+            let (x, y) = grid_point(&x_range, &y_range, resolution, row, column);
+            let predicted = network.predict(DVector::from_vec(vec![x, y]).as_view()).unwrap()[0];
+            assert!((field[(row, column)] - predicted).abs() < 1e-6, "cell ({row}, {column})");
+        }
+    }
+}
+
+#[test]
+fn only_planar_networks_are_accepted() {
+    for layer_sizes in [[3, 4, 1], [2, 4, 2]] {
+        let network = Network::random_with_rng(&layer_sizes, tanh!(), &Uniform::new(-1.0, 1.0).unwrap(), &mut StdRng::seed_from_u64(2)).unwrap();
+        let error = grid_evaluate(&network, 0.0..1.0, 0.0..1.0, (4, 4)).unwrap_err();
+
+        assert!(matches!(error, NetworkError::NotPlanar { .. }));
+        let (inputs, outputs) = (layer_sizes[0], layer_sizes[2]);
+        assert_eq!(
+            error.to_string(),
+            format!("a network with 2 inputs and 1 output is needed, but this one has {inputs} inputs and {outputs} outputs")
+        );
+    }
+}
+
+#[test]
+fn rgba_bytes_follow_the_rows_from_the_top() {
+    let field = DMatrix::from_row_slice(2, 2, &[0.0, 1.0, 0.5, 2.0]);
+    let gradient = Gradient { low: [0, 0, 0, 255], high: [200, 100, 0, 255] };
+
+    assert_eq!(to_rgba(&field, &gradient), [0, 0, 0, 255, 200, 100, 0, 255, 100, 50, 0, 255, 200, 100, 0, 255]);
+
+    // Any closure is a colormap.
+    let threshold = |value: f32| if value >= 0.5 { [255; 4] } else { [0; 4] };
+    assert_eq!(to_rgba(&field, &threshold), [[0; 4], [255; 4], [255; 4], [255; 4]].concat());
+}
+
+#[test]
+fn the_gradient_clamps_and_maps_nan_to_low() {
+    let gradient = Gradient::default();
+
+    assert_eq!(gradient.color(-3.0), gradient.low);
+    assert_eq!(gradient.color(f32::NAN), gradient.low);
+    assert_eq!(gradient.color(7.0), gradient.high);
+}
@@ -0,0 +1,95 @@
+use nalgebra::DVector;
+use rand::{distr::Uniform, rngs::StdRng, SeedableRng};
+
+use neural::{
+    activations::*,
+    dataset::{windowed, windowed_multivariate, DatasetError, Sample, WindowOptions},
+    losses::MSE,
+    network::Network,
+    training::Trainer,
+};
+
+fn parts(sample: &Sample) -> (Vec<f32>, Vec<f32>) {
+    (sample.inputs().iter().copied().collect(), sample.expected_outputs().iter().copied().collect())
+}
+
+#[test]
+fn windows_slide_over_the_series() {
+    let series: Vec<f32> = (0..10).map(|i| i as f32).collect();
+    let samples = windowed(&series, 3, 2).unwrap();
+
+    // Every start from 0 to 10 - 3 - 2.
+    assert_eq!(samples.len(), 6);
+    assert_eq!(parts(&samples[0]), (vec![0.0, 1.0, 2.0], vec![3.0, 4.0]));
+    assert_eq!(parts(&samples[5]), (vec![5.0, 6.0, 7.0], vec![8.0, 9.0]));
+
+    // A series of exactly one window gives one sample.
+    assert_eq!(windowed(&series[..5], 3, 2).unwrap().len(), 1);
+}
+
+#[test]
+fn a_stride_skips_starts() {
+    let series: Vec<f32> = (0..10).map(|i| i as f32).collect();
+
+    let samples = WindowOptions::new().stride(2).windowed(&series, 3, 2).unwrap();
+    assert_eq!(samples.iter().map(|sample| sample.inputs()[0]).collect::<Vec<_>>(), [0.0, 2.0, 4.0]);
+
+    // The last start that fits is 5, which a stride of 4 skips.
+    let samples = WindowOptions::new().stride(4).windowed(&series, 3, 2).unwrap();
+    assert_eq!(samples.iter().map(|sample| sample.inputs()[0]).collect::<Vec<_>>(), [0.0, 4.0]);
+
+    assert_eq!(WindowOptions::new().stride(0).windowed(&series, 3, 2).unwrap().len(), 6);
+}
+
+#[test]
+fn multivariate_steps_follow_each_other() {
+    let series: Vec<_> = (0..5).map(|i| DVector::from_row_slice(&[i as f32, -(i as f32)])).collect();
+    let samples = windowed_multivariate(&series, 2, 1).unwrap();
+
+    assert_eq!(samples.len(), 3);
+    assert_eq!(parts(&samples[2]), (vec![2.0, -2.0, 3.0, -3.0], vec![4.0, -4.0]));
+
+    let mut ragged = series;
+    ragged[3] = DVector::zeros(3);
+    assert!(matches!(windowed_multivariate(&ragged, 2, 1), Err(DatasetError::SeriesDimensionMismatch { index: 3, expected: 2, found: 3 })));
+}
+
+#[test]
+fn normalizing_uses_the_input_window() {
+    let series = [1.0, 3.0, 5.0, 7.0, 2.0, 2.0, 2.0];
+    let samples = WindowOptions::new().normalize(true).windowed(&series, 2, 1).unwrap();
+
+    // The window [1, 3] has mean 2 and standard deviation 1, so 5 becomes 3.
+    assert_eq!(parts(&samples[0]), (vec![-1.0, 1.0], vec![3.0]));
+    // A constant window is only shifted.
+    assert_eq!(parts(&samples[4]), (vec![0.0, 0.0], vec![0.0]));
+
+    let series: Vec<_> = (0..4).map(|i| DVector::from_row_slice(&[i as f32, 10.0 * i as f32])).collect();
+    let samples = WindowOptions::new().normalize(true).windowed_multivariate(&series, 2, 1).unwrap();
+    assert_eq!(parts(&samples[0]), (vec![-1.0, -1.0, 1.0, 1.0], vec![3.0, 3.0]));
+}
+
+#[test]
+fn short_series_report_the_length_needed() {
+    let series = [0.0; 4];
+
+    assert!(matches!(windowed(&series, 3, 2), Err(DatasetError::SeriesTooShort { length: 4, required: 5 })));
+    assert!(matches!(windowed(&[], 1, 1), Err(DatasetError::SeriesTooShort { length: 0, required: 2 })));
+    assert!(matches!(windowed(&series, 0, 2), Err(DatasetError::ZeroWindow)));
+    assert!(matches!(windowed(&series, 2, 0), Err(DatasetError::ZeroWindow)));
+}
+
+#[test]
+fn a_network_learns_to_forecast_a_sine_wave() {
+    let series: Vec<f32> = (0..200).map(|i| 0.5 + 0.4 * (i as f32 * 0.3).sin()).collect();
+    let samples = windowed(&series, 6, 1).unwrap();
+    let (train, test) = samples.split_at(150);
+
+    let mut rng = StdRng::seed_from_u64(1);
+    let mut network = Network::random_with_rng(&[6, 8, 1], sigmoid!(), &Uniform::new(-0.5, 0.5).unwrap(), &mut rng).unwrap();
+    let initial_loss = network.evaluate(test, &MSE).unwrap();
+    Trainer::new(MSE, 1.0, 300).batch_size(10).fit_with_rng(&mut network, train, &[], &mut rng).unwrap();
+
+    let loss = network.evaluate(test, &MSE).unwrap();
+    assert!(loss < 1e-3 && loss < initial_loss / 10.0, "{initial_loss} -> {loss}");
+}
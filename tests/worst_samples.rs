@@ -0,0 +1,87 @@
+mod common;
+
+use nalgebra::{DMatrix, DVector};
+use rand::{distr::Uniform, rngs::StdRng, SeedableRng};
+
+use neural::{
+    activations::*,
+    dataset::Sample,
+    losses::{LossFn, MSE},
+    metrics::worst_samples,
+    network::{layer::Layer, Network},
+};
+
+/// A network that passes its single input through, so a sample's loss is set by how far its input is from its
+/// expected output.
+fn passthrough() -> Network {
+    let mut layer = Layer::zeros(1, 1, identity!()).unwrap();
+    layer.set_weights(DMatrix::from_element(1, 1, 1.0)).unwrap();
+    Network::from_layers(vec![layer]).unwrap()
+}
+
+/// Samples off by 0.1, 2, 0.5, 3 and 1, tagged with their index.
+fn dataset() -> Vec<Sample> {
+    [0.1, 2.0, 0.5, 3.0, 1.0]
+        .into_iter()
+        .enumerate()
+        .map(|(i, error)| Sample::from_slices(&[error], &[0.0]).with_tag(format!("sample {i}")))
+        .collect()
+}
+
+#[test]
+fn the_worst_samples_come_first_with_their_losses() {
+    let dataset = dataset();
+    let worst = worst_samples(&mut passthrough(), &dataset, &MSE, 3).unwrap();
+
+    assert_eq!(worst.iter().map(|sample| sample.index).collect::<Vec<_>>(), [3, 1, 4]);
+    for sample in &worst {
+        let expected = &dataset[sample.index];
+        assert_eq!(sample.loss, MSE.apply(expected.inputs(), expected.expected_outputs()).unwrap());
+        assert_eq!(sample.outputs, expected.inputs());
+        assert_eq!(sample.expected_outputs, expected.expected_outputs());
+        assert_eq!(sample.tag.as_deref(), Some(format!("sample {}", sample.index).as_str()));
+    }
+    assert!(worst.windows(2).all(|pair| pair[0].loss > pair[1].loss));
+}
+
+#[test]
+fn k_is_clamped_to_the_dataset_size() {
+    let dataset = dataset();
+
+    assert_eq!(worst_samples(&mut passthrough(), &dataset, &MSE, 100).unwrap().len(), dataset.len());
+    assert!(worst_samples(&mut passthrough(), &dataset, &MSE, 0).unwrap().is_empty());
+    assert!(worst_samples(&mut passthrough(), &[], &MSE, 3).unwrap().is_empty());
+}
+
+#[test]
+fn nan_losses_count_as_the_worst() {
+    let mut dataset = dataset();
+    dataset.push(Sample::from_slices(&[f32::NAN], &[0.0]));
+
+    let worst = worst_samples(&mut passthrough(), &dataset, &MSE, 2).unwrap();
+    assert_eq!(worst[0].index, 5);
+    assert!(worst[0].loss.is_nan());
+    assert_eq!(worst[1].index, 3);
+}
+
+#[test]
+fn training_state_is_left_alone() {
+    let mut network = Network::random_with_rng(&[2, 4, 1], sigmoid!(), &Uniform::new(-1.0, 1.0).unwrap(), &mut StdRng::seed_from_u64(5)).unwrap();
+    let dataset = common::xor();
+    network.backpropagate(&dataset[..2], &MSE).unwrap();
+    let gradients = |network: &Network| -> Vec<_> {
+        network.gradients().map(|(_, weights, biases)| (weights.clone_owned(), biases.clone_owned())).collect()
+    };
+    let mut reference = network.clone();
+
+    worst_samples(&mut network, &dataset, &MSE, 2).unwrap();
+    assert_eq!(network.get_params(), reference.get_params());
+    assert_eq!(gradients(&network), gradients(&reference));
+
+    // Training goes on exactly as if nothing had been looked at.
+    network.learn(&dataset, &MSE, 0.5).unwrap();
+    reference.learn(&dataset, &MSE, 0.5).unwrap();
+    assert_eq!(network.get_params(), reference.get_params());
+    let inputs = DVector::from_vec(vec![1.0, 0.0]);
+    assert_eq!(network.predict(inputs.as_view()).unwrap(), reference.predict(inputs.as_view()).unwrap());
+}